@@ -58,6 +58,45 @@ pub struct AuthToken {
     pub token_type: Option<String>,
 }
 
+/// Keychain service for the default profile.
+const KEYCHAIN_SERVICE: &str = "minna_ai";
+
+/// Active profile from `MINNA_PROFILE`, or None for the default profile
+/// (unset, empty or `default`). Invalid names are ignored here; entry
+/// points reject them with [`validate_profile`] before anything is opened.
+pub fn profile_from_env() -> Option<String> {
+    let profile = std::env::var("MINNA_PROFILE").ok()?;
+    let profile = profile.trim();
+    if profile.is_empty() || profile == "default" || validate_profile(profile).is_err() {
+        return None;
+    }
+    Some(profile.to_string())
+}
+
+/// Profile names become directory and keychain service names, so only
+/// letters, digits, `-` and `_` are allowed.
+pub fn validate_profile(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!(
+            "invalid profile name '{}': use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Keychain service holding credentials for the active profile, so work and
+/// personal accounts never share tokens.
+pub fn keychain_service() -> String {
+    match profile_from_env() {
+        Some(profile) => format!("{}.{}", KEYCHAIN_SERVICE, profile),
+        None => KEYCHAIN_SERVICE.to_string(),
+    }
+}
+
 /// TokenStore now reads from macOS Keychain instead of JSON file
 /// This matches the Swift CredentialManager implementation
 #[derive(Debug, Clone)]
@@ -68,8 +107,6 @@ pub struct TokenStore {
 }
 
 impl TokenStore {
-    const KEYCHAIN_SERVICE: &'static str = "minna_ai";
-
     /// Load TokenStore (now just initializes keychain access)
     pub fn load(path: &Path) -> Result<Self> {
         // Create directory for compatibility with existing code
@@ -79,7 +116,7 @@ impl TokenStore {
 
         Ok(TokenStore {
             path: path.to_path_buf(),
-            service: Self::KEYCHAIN_SERVICE.to_string(),
+            service: keychain_service(),
         })
     }
 
@@ -87,6 +124,41 @@ impl TokenStore {
         &self.path
     }
 
+    /// Key for the encrypted database (see `MINNA_ENCRYPT_DB`), as 64 hex
+    /// characters. Generated and stored in the keychain on first use.
+    pub fn database_key(&self) -> Result<String> {
+        const ACCOUNT: &str = "database_key";
+        if let Ok(key) = self.get_keychain_token(ACCOUNT) {
+            if !key.is_empty() {
+                return Ok(key);
+            }
+        }
+
+        use std::io::Read;
+        let mut bytes = [0u8; 32];
+        std::fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+        let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        self.set_keychain_token(ACCOUNT, &key)?;
+        info!("Generated database encryption key in keychain");
+        Ok(key)
+    }
+
+    /// API key for a remote embedding endpoint (`MINNA_EMBED_BACKEND=remote`),
+    /// stored under the `embedding_api_key` account.
+    pub fn embedding_api_key(&self) -> Option<String> {
+        self.get_keychain_token("embedding_api_key")
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+
+    /// API key for the LLM that summarizes clusters, stored under the
+    /// `summary_api_key` account.
+    pub fn summary_api_key(&self) -> Option<String> {
+        self.get_keychain_token("summary_api_key")
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+
     /// Get token for a provider from macOS Keychain
     pub fn get(&self, provider: Provider) -> Option<AuthToken> {
         let account = provider.user_token_account();
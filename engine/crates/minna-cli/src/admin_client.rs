@@ -1,9 +1,11 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
 
+use crate::paths::get_admin_socket_path;
+
 #[derive(Debug, Serialize)]
 pub struct AdminRequest {
     pub id: Option<String>,
@@ -108,6 +110,62 @@ impl AdminClient {
         Ok(CredentialsStatus { providers })
     }
 
+    /// Import an export bundle into the daemon's database. `path` must be
+    /// absolute, since the daemon resolves it.
+    pub async fn import_bundle(&self, path: &Path) -> Result<serde_json::Value> {
+        let response = self
+            .send(AdminRequest {
+                id: Some("import".to_string()),
+                method: "import".to_string(),
+                params: Some(serde_json::json!({ "path": path })),
+            })
+            .await?;
+
+        if !response.ok {
+            return Err(anyhow!(
+                response.error.unwrap_or_else(|| "Import failed".to_string())
+            ));
+        }
+        response.result.ok_or_else(|| anyhow!("No result"))
+    }
+
+    /// Run database maintenance in the daemon (see `MaintenanceOptions`).
+    pub async fn maintenance(&self, options: serde_json::Value) -> Result<serde_json::Value> {
+        let response = self
+            .send(AdminRequest {
+                id: Some("maintenance".to_string()),
+                method: "maintenance".to_string(),
+                params: Some(options),
+            })
+            .await?;
+
+        if !response.ok {
+            return Err(anyhow!(
+                response.error.unwrap_or_else(|| "Maintenance failed".to_string())
+            ));
+        }
+        response.result.ok_or_else(|| anyhow!("No result"))
+    }
+
+    /// Re-embed documents with the daemon's current model; `all` includes
+    /// those already embedded with it.
+    pub async fn reindex_embeddings(&self, all: bool) -> Result<serde_json::Value> {
+        let response = self
+            .send(AdminRequest {
+                id: Some("reindex_embeddings".to_string()),
+                method: "reindex_embeddings".to_string(),
+                params: Some(serde_json::json!({ "all": all })),
+            })
+            .await?;
+
+        if !response.ok {
+            return Err(anyhow!(
+                response.error.unwrap_or_else(|| "Re-embedding failed".to_string())
+            ));
+        }
+        response.result.ok_or_else(|| anyhow!("No result"))
+    }
+
     pub async fn sync_provider<F>(
         &self,
         provider: &str,
@@ -161,7 +219,8 @@ impl AdminClient {
                     minna_core::progress::InternalEvent::Progress(p) => {
                         progress_callback(p);
                     }
-                    minna_core::progress::InternalEvent::Result(_) => {
+                    minna_core::progress::InternalEvent::Result(_)
+                    | minna_core::progress::InternalEvent::RingChange(_) => {
                         // Results are usually redundant with the final return value
                     }
                 }
@@ -198,17 +257,3 @@ pub struct ProviderStatus {
 pub struct SyncResult {
     pub items_synced: usize,
 }
-
-fn get_admin_socket_path() -> PathBuf {
-    if let Some(dir) = std::env::var_os("MINNA_DATA_DIR") {
-        return PathBuf::from(dir).join("admin.sock");
-    }
-    if let Some(home) = std::env::var_os("HOME") {
-        return PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Minna")
-            .join("admin.sock");
-    }
-    PathBuf::from(".minna").join("admin.sock")
-}
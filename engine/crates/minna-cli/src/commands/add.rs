@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use minna_auth_bridge::{AuthToken, Provider, TokenStore};
+use minna_auth_bridge::{keychain_service, AuthToken, Provider, TokenStore};
 use std::path::PathBuf;
 
 use crate::admin_client::AdminClient;
@@ -226,10 +226,10 @@ fn store_token(source: Source, token: &str) -> Result<()> {
             use std::process::Command;
             let account = format!("{}_token", source.as_str());
             let _ = Command::new("security")
-                .args(["delete-generic-password", "-s", "minna_ai", "-a", &account])
+                .args(["delete-generic-password", "-s", &keychain_service(), "-a", &account])
                 .output();
             Command::new("security")
-                .args(["add-generic-password", "-s", "minna_ai", "-a", &account, "-w", token])
+                .args(["add-generic-password", "-s", &keychain_service(), "-a", &account, "-w", token])
                 .output()?;
             return Ok(());
         }
@@ -453,17 +453,17 @@ async fn connect_google() -> Result<()> {
     // Also store client credentials for refresh
     use std::process::Command;
     let _ = Command::new("security")
-        .args(["delete-generic-password", "-s", "minna_ai", "-a", "google_client_id"])
+        .args(["delete-generic-password", "-s", &keychain_service(), "-a", "google_client_id"])
         .output();
     Command::new("security")
-        .args(["add-generic-password", "-s", "minna_ai", "-a", "google_client_id", "-w", &client_id])
+        .args(["add-generic-password", "-s", &keychain_service(), "-a", "google_client_id", "-w", &client_id])
         .output()?;
 
     let _ = Command::new("security")
-        .args(["delete-generic-password", "-s", "minna_ai", "-a", "google_client_secret"])
+        .args(["delete-generic-password", "-s", &keychain_service(), "-a", "google_client_secret"])
         .output();
     Command::new("security")
-        .args(["add-generic-password", "-s", "minna_ai", "-a", "google_client_secret", "-w", &client_secret])
+        .args(["add-generic-password", "-s", &keychain_service(), "-a", "google_client_secret", "-w", &client_secret])
         .output()?;
 
     // Trigger sync
@@ -28,6 +28,12 @@ pub async fn ensure_running() -> Result<bool> {
     wait_for_ready(&client).await
 }
 
+/// Start the daemon without waiting for it to be ready or printing to
+/// stdout, for callers whose stdout is a protocol stream.
+pub async fn start_background() -> Result<()> {
+    start_internal(false).await
+}
+
 async fn wait_for_ready(client: &AdminClient) -> Result<bool> {
     let spinner = ui::spinner("Waiting for daemon to be ready...");
 
@@ -235,13 +241,18 @@ fn get_socket_path() -> PathBuf {
 }
 
 fn get_log_file() -> PathBuf {
+    let name = match minna_auth_bridge::profile_from_env() {
+        Some(profile) => format!("daemon-{}.log", profile),
+        None => "daemon.log".to_string(),
+    };
     dirs::cache_dir()
         .unwrap_or_else(|| {
             dirs::home_dir()
                 .unwrap_or_else(|| PathBuf::from("."))
                 .join(".cache")
         })
-        .join("minna/logs/daemon.log")
+        .join("minna/logs")
+        .join(name)
 }
 
 fn find_daemon_binary() -> Result<PathBuf> {
@@ -0,0 +1,43 @@
+//! `minna export` command - Dump local memory to a portable bundle.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use minna_core::bundle::{export_bundle, ExportOptions};
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+pub async fn run(dest: PathBuf, embeddings: bool, edges: bool, json: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    // The daemon may be running; SQLite WAL lets us read alongside it
+    let ingest = db::open_ingest(&db_path).await?;
+    let vector = db::open_vector(&db_path).await?;
+    let graph = ingest.graph_store();
+
+    let spinner = (!json).then(|| ui::spinner("Exporting..."));
+    let manifest = export_bundle(&ingest, &vector, &graph, &dest, ExportOptions { embeddings, edges }).await;
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+    let manifest = manifest?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&manifest)?);
+        return Ok(());
+    }
+    ui::success(&format!("Exported {} documents to {}", manifest.documents, dest.display()));
+    if let Some(count) = manifest.embeddings {
+        println!("  Embeddings: {}", count);
+    }
+    if let Some(count) = manifest.edges {
+        println!("  Graph edges: {}", count);
+    }
+    Ok(())
+}
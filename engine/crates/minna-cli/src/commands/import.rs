@@ -0,0 +1,46 @@
+//! `minna import` command - Restore an export bundle through the daemon.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+
+use crate::admin_client::AdminClient;
+use crate::commands::daemon;
+use crate::ui;
+
+pub async fn run(path: PathBuf, json: bool) -> Result<()> {
+    // The daemon resolves the path and embeds what the bundle lacks
+    let path = path
+        .canonicalize()
+        .map_err(|err| anyhow!("Cannot read {}: {}", path.display(), err))?;
+    if !daemon::ensure_running().await? {
+        ui::info("Daemon is starting. Run the import again once it is ready.");
+        return Ok(());
+    }
+
+    let spinner = (!json).then(|| ui::spinner("Importing..."));
+    let report = AdminClient::new().import_bundle(&path).await;
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+    let report = report?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let count = |key: &str| report[key].as_u64().unwrap_or(0);
+    ui::success(&format!(
+        "Imported {} documents from {}",
+        count("documents_imported"),
+        path.display()
+    ));
+    if count("documents_skipped") > 0 {
+        println!("  Already up to date: {}", count("documents_skipped"));
+    }
+    println!("  Embeddings: {} (re-embedding {})", count("embeddings"), count("reembed_queued"));
+    println!("  Clusters: {}", count("clusters"));
+    println!("  Sync cursors: {}", count("cursors"));
+    println!("  Graph: {} nodes, {} edges", count("nodes"), count("edges"));
+    Ok(())
+}
@@ -1,15 +1,17 @@
 //! `minna link` command - Review and link user identities across sources.
 
+use std::collections::HashSet;
 use std::io::{self, Write};
-use std::path::PathBuf;
 
 use anyhow::Result;
-use minna_graph::{GraphStore, IdentityService, MatchType};
-use sqlx::sqlite::SqlitePoolOptions;
+use minna_graph::{IdentityService, MatchType};
+
+use crate::db;
+use crate::paths::get_db_path;
 
 /// Run the link command - review and confirm identity matches.
 pub async fn run() -> Result<()> {
-    let db_path = get_db_path()?;
+    let db_path = get_db_path();
 
     if !db_path.exists() {
         println!("No Minna database found. Run 'minna sync' first to populate data.");
@@ -17,18 +19,13 @@ pub async fn run() -> Result<()> {
     }
 
     // Connect to database
-    let pool = SqlitePoolOptions::new()
-        .max_connections(1)
-        .connect(&format!("sqlite:{}", db_path.display()))
-        .await?;
-
-    let graph = GraphStore::new(pool);
+    let graph = db::open_ingest(&db_path).await?.graph_store();
 
     // First, run auto-linking for exact email matches
     println!("Checking for exact email matches...");
     let auto_linked = IdentityService::auto_link_by_email(&graph).await?;
     if auto_linked > 0 {
-        println!("  Auto-linked {} users with matching emails.", auto_linked);
+        println!("  Auto-linked {} accounts with matching emails.", auto_linked);
     } else {
         println!("  No new exact email matches found.");
     }
@@ -72,51 +69,54 @@ pub async fn run() -> Result<()> {
     }
 
     // Interactive confirmation
-    print!("Link these accounts? [y/N/number to skip specific]: ");
+    print!("Link these accounts? [y/N/numbers to skip, e.g. 2,3]: ");
     io::stdout().flush()?;
 
     let mut input = String::new();
     io::stdin().read_line(&mut input)?;
     let input = input.trim().to_lowercase();
 
-    if input == "y" || input == "yes" {
-        // Link all matches
-        for m in &matches {
-            if m.users.len() >= 2 {
-                let u1 = &m.users[0];
-                let u2 = &m.users[1];
-                IdentityService::link_users(
-                    &graph,
-                    &u1.provider,
-                    &u1.provider_user_id,
-                    &u2.provider,
-                    &u2.provider_user_id,
-                ).await?;
-            }
-        }
-        println!("\nLinked {} identity matches.", matches.len());
+    let skipped: HashSet<usize> = if input == "y" || input == "yes" {
+        HashSet::new()
     } else if input.is_empty() || input == "n" || input == "no" {
         println!("\nNo changes made.");
+        return Ok(());
     } else {
-        println!("\nSkipped. Run 'minna link' again to review.");
-    }
-
-    Ok(())
-}
-
-/// Get the path to the Minna database.
-fn get_db_path() -> Result<PathBuf> {
-    if let Some(dir) = std::env::var_os("MINNA_DATA_DIR") {
-        return Ok(PathBuf::from(dir).join("minna.db"));
-    }
+        match input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<usize>())
+            .collect::<Result<HashSet<_>, _>>()
+        {
+            Ok(numbers) => numbers,
+            Err(_) => {
+                println!("\nSkipped. Run 'minna link' again to review.");
+                return Ok(());
+            }
+        }
+    };
 
-    if let Some(home) = std::env::var_os("HOME") {
-        return Ok(PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Minna")
-            .join("minna.db"));
+    // Record each confirmed match as a SameAs link
+    let mut linked = 0;
+    for (i, m) in matches.iter().enumerate() {
+        if skipped.contains(&(i + 1)) {
+            continue;
+        }
+        let Some((first, rest)) = m.users.split_first() else {
+            continue;
+        };
+        for other in rest {
+            IdentityService::link_users(
+                &graph,
+                &first.provider,
+                &first.provider_user_id,
+                &other.provider,
+                &other.provider_user_id,
+            ).await?;
+        }
+        linked += 1;
     }
+    println!("\nLinked {} identity matches.", linked);
 
-    Ok(PathBuf::from(".minna").join("minna.db"))
+    Ok(())
 }
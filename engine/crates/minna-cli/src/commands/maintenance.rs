@@ -0,0 +1,75 @@
+//! `minna maintenance` command - Integrity check, search index rebuild and
+//! vacuum through the daemon.
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+
+use crate::admin_client::AdminClient;
+use crate::commands::daemon;
+use crate::ui;
+
+pub async fn run(check_only: bool, json: bool) -> Result<()> {
+    if !daemon::ensure_running().await? {
+        ui::info("Daemon is starting. Run maintenance again once it is ready.");
+        return Ok(());
+    }
+
+    let options = if check_only {
+        json!({ "integrity_check": true, "rebuild_fts": false, "vacuum": false })
+    } else {
+        json!({})
+    };
+    let spinner = (!json).then(|| ui::spinner("Running maintenance..."));
+    let report = AdminClient::new().maintenance(options).await;
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+    let report = report?;
+    let healthy = report["integrity_ok"] != json!(false)
+        && (report["fts_ok"] != json!(false) || report["fts_rebuilt"] == json!(true));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        ui::header("Database maintenance");
+        match report["integrity_ok"].as_bool() {
+            Some(true) => println!("  Integrity check: ok"),
+            Some(false) => {
+                ui::error("Integrity check found problems:");
+                for problem in report["integrity_errors"].as_array().into_iter().flatten() {
+                    println!("    {}", problem.as_str().unwrap_or_default());
+                }
+            }
+            None => {}
+        }
+        match report["fts_ok"].as_bool() {
+            Some(true) => println!("  Search index: ok"),
+            Some(false) if report["fts_rebuilt"] == json!(true) => {
+                println!("  Search index: damaged, rebuilt")
+            }
+            Some(false) => ui::error("Search index is damaged. Run `minna maintenance` to rebuild it."),
+            None => {}
+        }
+        let count = |key: &str| report[key].as_u64().unwrap_or(0);
+        if count("orphaned_embeddings") + count("orphaned_edge_sources") > 0 {
+            println!(
+                "  Removed {} orphaned embeddings and {} orphaned edge sources",
+                count("orphaned_embeddings"),
+                count("orphaned_edge_sources")
+            );
+        }
+        if report["vacuumed"] == json!(true) {
+            let mb = |key: &str| report[key].as_i64().unwrap_or(0) as f64 / 1_048_576.0;
+            println!("  Size: {:.1} MB -> {:.1} MB", mb("size_before"), mb("size_after"));
+        }
+        if healthy {
+            ui::success("Database is healthy");
+        }
+    }
+
+    if healthy {
+        Ok(())
+    } else {
+        Err(anyhow!("database integrity check failed"))
+    }
+}
@@ -0,0 +1,112 @@
+//! `minna mcp-serve --stdio` command - Serve MCP over stdin/stdout.
+//!
+//! Many MCP clients can only launch a server and talk to it over stdio. This
+//! relays each line between stdin/stdout and the daemon's MCP socket,
+//! starting the daemon if needed. Stdout carries protocol messages only.
+
+use anyhow::{anyhow, Result};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::commands::daemon;
+use crate::paths;
+
+/// How long to wait for a freshly started daemon to open its MCP socket.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn run(stdio: bool) -> Result<()> {
+    let socket_path = paths::get_socket_path();
+    if !stdio {
+        return Err(anyhow!(
+            "Only --stdio is supported; clients that speak unix sockets can connect to {}",
+            socket_path.display()
+        ));
+    }
+
+    // A leftover socket file doesn't mean the daemon is up
+    if UnixStream::connect(&socket_path).await.is_err() {
+        daemon::start_background().await?;
+    }
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    let stream = loop {
+        match UnixStream::connect(&socket_path).await {
+            Ok(stream) => break stream,
+            Err(err) if Instant::now() >= deadline => {
+                return Err(anyhow!("Cannot connect to daemon at {}: {}", socket_path.display(), err));
+            }
+            Err(_) => sleep(Duration::from_millis(250)).await,
+        }
+    };
+    relay(tokio::io::stdin(), &mut tokio::io::stdout(), stream).await
+}
+
+/// Send each line of `input` to the daemon and copy its replies to `output`
+/// until the daemon closes the socket.
+async fn relay<I, O>(input: I, output: &mut O, stream: UnixStream) -> Result<()>
+where
+    I: AsyncRead + Unpin + Send + 'static,
+    O: AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = stream.into_split();
+
+    // Requests go to the daemon as they arrive; once stdin closes the daemon
+    // finishes what is in flight and closes the socket
+    let requests = tokio::spawn(async move {
+        let mut input = BufReader::new(input).lines();
+        while let Some(line) = input.next_line().await? {
+            writer.write_all(line.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+        writer.shutdown().await?;
+        Ok::<_, std::io::Error>(())
+    });
+
+    let mut responses = BufReader::new(reader).lines();
+    while let Some(line) = responses.next_line().await? {
+        output.write_all(line.as_bytes()).await?;
+        output.write_all(b"\n").await?;
+        output.flush().await?;
+    }
+
+    requests.abort();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn test_relay_round_trips_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("mcp.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        // A daemon that answers each request line, then closes once the
+        // client has shut down its side
+        let daemon = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut seen = 0;
+            while let Some(line) = lines.next_line().await.unwrap() {
+                seen += 1;
+                writer.write_all(format!("{{\"reply\":{}}}\n", line).as_bytes()).await.unwrap();
+            }
+            seen
+        });
+
+        let input: &'static [u8] = b"{\"id\":1}\n{\"id\":2}\n";
+        let mut output = Vec::new();
+        let stream = UnixStream::connect(&socket_path).await.unwrap();
+        relay(input, &mut output, stream).await.unwrap();
+
+        assert_eq!(daemon.await.unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"reply\":{\"id\":1}}\n{\"reply\":{\"id\":2}}\n"
+        );
+    }
+}
@@ -1,8 +1,18 @@
 pub mod add;
 pub mod checkpoint;
 pub mod daemon;
+pub mod export;
+pub mod import;
 pub mod link;
+pub mod maintenance;
 pub mod mcp;
+pub mod mcp_serve;
+pub mod pin;
+pub mod purge;
+pub mod reindex;
 pub mod remove;
+pub mod search;
+pub mod stats;
 pub mod status;
 pub mod sync;
+pub mod verify;
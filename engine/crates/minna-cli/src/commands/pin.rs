@@ -0,0 +1,40 @@
+//! `minna pin` command - Keep documents from being evicted.
+
+use anyhow::{anyhow, Result};
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+/// Pin (or with `remove`, unpin) documents given by short ID or URI.
+pub async fn run(documents: Vec<String>, remove: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    let engine = db::open_ingest(&db_path).await?;
+    let mut missing = 0;
+    for reference in &documents {
+        let Some(doc) = engine.resolve_document(reference).await? else {
+            ui::error(&format!("No document {}", reference));
+            missing += 1;
+            continue;
+        };
+        let id = doc.id.ok_or_else(|| anyhow!("document {} has no ID", reference))?;
+        let name = doc.title.as_deref().unwrap_or(&doc.uri);
+        if remove {
+            engine.set_pinned(id, false).await?;
+            ui::success(&format!("Unpinned {}", name));
+        } else {
+            engine.set_pinned(id, true).await?;
+            ui::success(&format!("Pinned {}", name));
+        }
+    }
+
+    if missing > 0 {
+        return Err(anyhow!("{} of {} documents not found", missing, documents.len()));
+    }
+    Ok(())
+}
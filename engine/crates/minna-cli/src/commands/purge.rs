@@ -0,0 +1,43 @@
+//! `minna purge` command - Delete documents from local memory.
+
+use anyhow::{anyhow, Result};
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+/// Delete documents given by short ID or URI. They are soft-deleted; the
+/// daemon drops their embeddings and graph edges on its next cleanup.
+/// A later sync brings them back if they still exist upstream.
+pub async fn run(documents: Vec<String>, yes: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    let engine = db::open_ingest(&db_path).await?;
+    let mut found = Vec::new();
+    for reference in &documents {
+        match engine.resolve_document(reference).await? {
+            Some(doc) => found.push(doc),
+            None => ui::error(&format!("No document {}", reference)),
+        }
+    }
+    if found.len() < documents.len() {
+        return Err(anyhow!("{} of {} documents not found", documents.len() - found.len(), documents.len()));
+    }
+
+    for doc in &found {
+        println!("  {}", doc.title.as_deref().unwrap_or(&doc.uri));
+    }
+    if !yes && !ui::prompt_confirm(&format!("Delete {} documents from local memory?", found.len()))? {
+        ui::info("Cancelled.");
+        return Ok(());
+    }
+
+    let ids: Vec<i64> = found.iter().filter_map(|doc| doc.id).collect();
+    let deleted = engine.delete_documents(&ids).await?;
+    ui::success(&format!("Deleted {} documents", deleted));
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! `minna reindex-embeddings` command - Re-embed documents with the daemon's
+//! current embedding model.
+
+use anyhow::Result;
+
+use crate::admin_client::AdminClient;
+use crate::commands::daemon;
+use crate::ui;
+
+pub async fn run(all: bool, json: bool) -> Result<()> {
+    if !daemon::ensure_running().await? {
+        ui::info("Daemon is starting. Run reindex-embeddings again once it is ready.");
+        return Ok(());
+    }
+
+    let spinner = (!json).then(|| ui::spinner("Re-embedding documents..."));
+    let summary = AdminClient::new().reindex_embeddings(all).await;
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+    let summary = summary?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+    let model = summary["model"].as_str().unwrap_or("unknown");
+    match summary["reembedded"].as_u64().unwrap_or(0) {
+        0 => ui::success(&format!("All documents are embedded with {}", model)),
+        count => ui::success(&format!("Re-embedded {} documents with {}", count, model)),
+    }
+    Ok(())
+}
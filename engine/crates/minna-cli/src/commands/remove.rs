@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Result};
+use minna_auth_bridge::keychain_service;
 use std::process::Command;
 
 use crate::sources::Source;
@@ -34,19 +35,19 @@ pub async fn run(source_name: &str) -> Result<()> {
     let spinner = ui::spinner(&format!("Removing {}...", source.display_name()));
 
     let _ = Command::new("security")
-        .args(["delete-generic-password", "-s", "minna_ai", "-a", account])
+        .args(["delete-generic-password", "-s", &keychain_service(), "-a", account])
         .output();
 
     // For Google, also remove client credentials and refresh token
     if source == Source::Google {
         let _ = Command::new("security")
-            .args(["delete-generic-password", "-s", "minna_ai", "-a", "google_client_id"])
+            .args(["delete-generic-password", "-s", &keychain_service(), "-a", "google_client_id"])
             .output();
         let _ = Command::new("security")
-            .args(["delete-generic-password", "-s", "minna_ai", "-a", "google_client_secret"])
+            .args(["delete-generic-password", "-s", &keychain_service(), "-a", "google_client_secret"])
             .output();
         let _ = Command::new("security")
-            .args(["delete-generic-password", "-s", "minna_ai", "-a", "googleWorkspace_refresh_token"])
+            .args(["delete-generic-password", "-s", &keychain_service(), "-a", "googleWorkspace_refresh_token"])
             .output();
     }
 
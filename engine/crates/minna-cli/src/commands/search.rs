@@ -0,0 +1,72 @@
+//! `minna search` command - Keyword search over local memory.
+
+use anyhow::Result;
+use console::style;
+use minna_ingest::fts::SearchQuery;
+use serde::Serialize;
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+#[derive(Serialize)]
+struct SearchHit {
+    short_id: Option<String>,
+    uri: String,
+    source: String,
+    title: Option<String>,
+    snippet: String,
+}
+
+pub async fn run(query: Vec<String>, limit: usize, json: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    // Terms are quoted by the parser, so punctuation is searched literally
+    let query = SearchQuery::parse(&query.join(" "));
+    if query.match_expr().is_none() {
+        ui::error("Nothing to search for");
+        return Ok(());
+    }
+
+    let engine = db::open_ingest(&db_path).await?;
+    let hits: Vec<SearchHit> = engine
+        .search_query(&query, limit)
+        .await?
+        .into_iter()
+        .map(|hit| SearchHit {
+            short_id: hit.document.short_id(),
+            uri: hit.document.uri,
+            source: hit.document.source,
+            title: hit.document.title,
+            snippet: hit.snippet,
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        ui::info("No matches.");
+        return Ok(());
+    }
+
+    println!();
+    for hit in &hits {
+        println!(
+            "  {}  {:<12} {}",
+            style(hit.short_id.as_deref().unwrap_or("-")).cyan().bold(),
+            hit.source,
+            hit.title.as_deref().unwrap_or(&hit.uri)
+        );
+        println!("        {}", style(hit.snippet.replace('\n', " ")).dim());
+    }
+    println!();
+    ui::info(&style("Pass a short ID to read_resource to open a result.").dim().to_string());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! `minna stats` command - Document counts and recent documents by short ID.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use console::style;
+use serde::Serialize;
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+/// Pinned documents listed at most.
+const MAX_PINNED: usize = 50;
+
+#[derive(Serialize)]
+struct Stats {
+    total: i64,
+    sources: Vec<SourceCount>,
+    recent: Vec<DocumentRow>,
+    pinned: Vec<DocumentRow>,
+}
+
+#[derive(Serialize)]
+struct SourceCount {
+    source: String,
+    documents: i64,
+}
+
+#[derive(Serialize)]
+struct DocumentRow {
+    short_id: Option<String>,
+    uri: String,
+    source: String,
+    title: Option<String>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<minna_ingest::Document> for DocumentRow {
+    fn from(doc: minna_ingest::Document) -> Self {
+        Self {
+            short_id: doc.short_id(),
+            uri: doc.uri,
+            source: doc.source,
+            title: doc.title,
+            updated_at: doc.updated_at,
+        }
+    }
+}
+
+pub async fn run(recent: usize, json: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    let engine = db::open_ingest(&db_path).await?;
+    let mut sources: Vec<SourceCount> = engine
+        .document_counts_by_source()
+        .await?
+        .into_iter()
+        .map(|(source, documents)| SourceCount { source, documents })
+        .collect();
+    sources.sort_by(|a, b| b.documents.cmp(&a.documents).then_with(|| a.source.cmp(&b.source)));
+    let stats = Stats {
+        total: sources.iter().map(|source| source.documents).sum(),
+        sources,
+        recent: engine
+            .recent_documents(recent)
+            .await?
+            .into_iter()
+            .map(DocumentRow::from)
+            .collect(),
+        pinned: engine
+            .list_pinned(MAX_PINNED)
+            .await?
+            .into_iter()
+            .map(DocumentRow::from)
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    ui::header(&format!("{} documents", stats.total));
+    for source in &stats.sources {
+        println!("  {:<16} {}", source.source, source.documents);
+    }
+    print_documents("Recently updated", &stats.recent);
+    print_documents("Pinned", &stats.pinned);
+    println!();
+    ui::info(&style("Pass a short ID to read_resource, minna pin or minna purge.").dim().to_string());
+    Ok(())
+}
+
+fn print_documents(heading: &str, docs: &[DocumentRow]) {
+    if docs.is_empty() {
+        return;
+    }
+    println!();
+    ui::header(heading);
+    for doc in docs {
+        println!(
+            "  {}  {:<12} {}",
+            style(doc.short_id.as_deref().unwrap_or("-")).cyan().bold(),
+            doc.source,
+            doc.title.as_deref().unwrap_or(&doc.uri)
+        );
+    }
+}
@@ -1,16 +1,14 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use console::style;
-use minna_graph::{GraphStore, IdentityService};
-use minna_ingest::IngestionEngine;
-use minna_vector::VectorStore;
+use minna_graph::{GraphStoreStats, IdentityService};
 use serde::Serialize;
-use sqlx::sqlite::SqlitePoolOptions;
 
 use crate::admin_client::AdminClient;
+use crate::db;
+use crate::paths::get_db_path;
 use crate::ui;
 
 #[derive(Serialize)]
@@ -19,6 +17,7 @@ struct Status {
     sources: Vec<SourceStatus>,
     storage: StorageStatus,
     identity: IdentityStatus,
+    graph: Option<GraphStoreStats>,
 }
 
 #[derive(Serialize)]
@@ -72,6 +71,7 @@ pub async fn run(json: bool) -> Result<()> {
                     db_bytes: 0,
                 },
                 identity: IdentityStatus { pending_links: 0 },
+                graph: None,
             };
             println!("{}", serde_json::to_string_pretty(&status)?);
             return Ok(());
@@ -106,6 +106,7 @@ pub async fn run(json: bool) -> Result<()> {
                         db_bytes: 0,
                     },
                     identity: IdentityStatus { pending_links: 0 },
+                    graph: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&status)?);
                 return Ok(());
@@ -124,6 +125,9 @@ pub async fn run(json: bool) -> Result<()> {
     // Get pending identity links
     let pending_links = get_pending_identity_links().await.unwrap_or(0);
 
+    // Get graph counts, to tell whether syncs are populating it
+    let graph = get_graph_stats().await.ok().flatten();
+
     // Get per-source document counts and sync times
     let (doc_counts, sync_times) = get_source_stats().await.unwrap_or_default();
 
@@ -163,6 +167,7 @@ pub async fn run(json: bool) -> Result<()> {
         sources,
         storage: db_stats,
         identity: IdentityStatus { pending_links },
+        graph,
     };
 
     if json {
@@ -240,6 +245,32 @@ pub async fn run(json: bool) -> Result<()> {
         status.storage.db_bytes as f64 / 1_000_000.0
     );
 
+    if let Some(graph) = &status.graph {
+        println!();
+        println!("  {}", style("GRAPH").bold());
+        println!("  {}", "─".repeat(45));
+        println!("  {:<12} {}", "nodes", graph.nodes);
+        println!("  {:<12} {}", "edges", graph.edges);
+        for provider in &graph.providers {
+            let extracted = provider
+                .last_extracted_at
+                .map(|dt| format!("updated {}", format_relative_time(dt)))
+                .unwrap_or_default();
+            println!(
+                "  {:<12} {:>6} edges    {}",
+                provider.provider,
+                provider.edges,
+                style(extracted).dim()
+            );
+        }
+        if graph.edges == 0 {
+            println!(
+                "  {}",
+                style("No relationships yet; they appear as sources sync").dim()
+            );
+        }
+    }
+
     // Show identity linking suggestions if any
     if status.identity.pending_links > 0 {
         println!();
@@ -284,8 +315,8 @@ async fn get_storage_counts() -> Result<(u64, u64)> {
         return Ok((0, 0));
     }
 
-    let engine = IngestionEngine::new(&db_path).await?;
-    let vector_store = VectorStore::new(&db_path).await?;
+    let engine = db::open_ingest(&db_path).await?;
+    let vector_store = db::open_vector(&db_path).await?;
 
     let documents = engine.document_count().await.unwrap_or(0) as u64;
     let vectors = vector_store.count().await.unwrap_or(0) as u64;
@@ -299,7 +330,7 @@ async fn get_source_stats() -> Result<(HashMap<String, u64>, HashMap<String, Dat
         return Ok((HashMap::new(), HashMap::new()));
     }
 
-    let engine = IngestionEngine::new(&db_path).await?;
+    let engine = db::open_ingest(&db_path).await?;
 
     let doc_counts: HashMap<String, u64> = engine
         .document_counts_by_source()
@@ -335,18 +366,15 @@ fn format_relative_time(dt: DateTime<Utc>) -> String {
     }
 }
 
-fn get_db_path() -> PathBuf {
-    if let Some(dir) = std::env::var_os("MINNA_DATA_DIR") {
-        return PathBuf::from(dir).join("minna.db");
-    }
-    if let Some(home) = std::env::var_os("HOME") {
-        return PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Minna")
-            .join("minna.db");
+/// Get node and edge counts from the graph, if the database exists.
+async fn get_graph_stats() -> Result<Option<GraphStoreStats>> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        return Ok(None);
     }
-    PathBuf::from(".minna").join("minna.db")
+
+    let graph = db::open_ingest(&db_path).await?.graph_store();
+    Ok(Some(graph.stats().await?))
 }
 
 /// Get the count of pending identity link suggestions.
@@ -356,11 +384,6 @@ async fn get_pending_identity_links() -> Result<usize> {
         return Ok(0);
     }
 
-    let pool = SqlitePoolOptions::new()
-        .max_connections(1)
-        .connect(&format!("sqlite:{}", db_path.display()))
-        .await?;
-
-    let graph = GraphStore::new(pool);
+    let graph = db::open_ingest(&db_path).await?.graph_store();
     IdentityService::pending_suggestions_count(&graph).await
 }
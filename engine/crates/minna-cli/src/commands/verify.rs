@@ -0,0 +1,60 @@
+//! `minna verify` command - Check the integrity hash chain.
+
+use anyhow::{anyhow, Result};
+
+use crate::db;
+use crate::paths::get_db_path;
+use crate::ui;
+
+pub async fn run(json: bool) -> Result<()> {
+    let db_path = get_db_path();
+    if !db_path.exists() {
+        ui::info("No Minna database found. Run 'minna sync' first to populate data.");
+        return Ok(());
+    }
+
+    let engine = db::open_ingest(&db_path).await?;
+    let report = engine.verify_integrity().await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.entries == 0 {
+        ui::info("Integrity chain is empty. Set MINNA_INTEGRITY_CHAIN=1 on the daemon to enable it.");
+        return Ok(());
+    } else {
+        ui::header("Integrity chain");
+        println!("  Entries checked: {}", report.entries);
+        if let Some(seq) = report.broken_at {
+            ui::error(&format!("Chain broken at entry #{} (hash or link mismatch)", seq));
+        }
+        if !report.modified.is_empty() {
+            ui::error(&format!(
+                "{} documents modified outside Minna: {:?}",
+                report.modified.len(),
+                report.modified
+            ));
+        }
+        if !report.missing.is_empty() {
+            ui::error(&format!(
+                "{} documents deleted outside Minna: {:?}",
+                report.missing.len(),
+                report.missing
+            ));
+        }
+        if report.untracked > 0 {
+            ui::info(&format!(
+                "{} documents predate the chain and can't be verified",
+                report.untracked
+            ));
+        }
+        if report.is_ok() {
+            ui::success("Local memory verified");
+        }
+    }
+
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(anyhow!("integrity verification failed"))
+    }
+}
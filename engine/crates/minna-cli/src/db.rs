@@ -0,0 +1,18 @@
+//! Opening the daemon's database from the CLI.
+//!
+//! Goes through the keychain when the database is encrypted
+//! (`MINNA_ENCRYPT_DB=1`), so commands work the same either way.
+
+use std::path::Path;
+
+use anyhow::Result;
+use minna_ingest::IngestionEngine;
+use minna_vector::VectorStore;
+
+pub async fn open_ingest(db_path: &Path) -> Result<IngestionEngine> {
+    IngestionEngine::open(db_path, minna_core::database_key()?.as_deref()).await
+}
+
+pub async fn open_vector(db_path: &Path) -> Result<VectorStore> {
+    VectorStore::open(db_path, minna_core::database_key()?.as_deref()).await
+}
@@ -1,8 +1,11 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
 mod admin_client;
 mod commands;
+mod db;
 mod paths;
 mod sources;
 mod tui;
@@ -13,6 +16,11 @@ mod ui;
 #[command(about = "Your AI's memory. Local-first. Zero config.")]
 #[command(version)]
 struct Cli {
+    /// Profile to use, e.g. `work` or `personal` (each has its own data,
+    /// credentials and daemon). Defaults to $MINNA_PROFILE.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -54,6 +62,15 @@ enum Commands {
         ui_test: bool,
     },
 
+    /// Serve MCP over stdin/stdout, for clients that launch their servers
+    /// (starts the daemon if needed)
+    #[command(name = "mcp-serve")]
+    McpServe {
+        /// Speak MCP on stdin/stdout
+        #[arg(long)]
+        stdio: bool,
+    },
+
     /// Manage the background daemon
     Daemon {
         #[command(subcommand)]
@@ -81,6 +98,116 @@ enum Commands {
     /// Review and link user identities across sources
     Link,
 
+    /// Search local memory by keyword
+    Search {
+        /// Search terms. Supports "exact phrase", source:slack and -term
+        #[arg(value_name = "QUERY", required = true)]
+        query: Vec<String>,
+
+        /// Maximum number of results
+        #[arg(long, short = 'n', default_value = "10")]
+        limit: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show document counts, recent and pinned documents with short IDs
+    Stats {
+        /// Number of recently updated documents to list
+        #[arg(long, short = 'n', default_value = "10")]
+        recent: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Pin documents so they are never evicted
+    Pin {
+        /// Short IDs (e.g. sl-4f2a) or URIs
+        #[arg(value_name = "DOCUMENT", required = true)]
+        documents: Vec<String>,
+
+        /// Unpin instead
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Delete documents from local memory
+    Purge {
+        /// Short IDs (e.g. sl-4f2a) or URIs
+        #[arg(value_name = "DOCUMENT", required = true)]
+        documents: Vec<String>,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Export documents (and optionally embeddings and graph edges) to a bundle
+    Export {
+        /// Destination directory, or a `.tar` file
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+
+        /// Include embeddings
+        #[arg(long)]
+        embeddings: bool,
+
+        /// Include graph edges
+        #[arg(long)]
+        edges: bool,
+
+        /// Output the manifest as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Import a bundle written by `minna export` (requires the daemon)
+    Import {
+        /// Bundle directory or `.tar` file
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+
+        /// Output the import report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check the database for corruption, rebuild the search index and
+    /// reclaim space (requires the daemon)
+    Maintenance {
+        /// Only run the integrity checks
+        #[arg(long)]
+        check: bool,
+
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Re-embed documents after the embedding model changed (requires the
+    /// daemon)
+    #[command(name = "reindex-embeddings")]
+    ReindexEmbeddings {
+        /// Re-embed every document, not only those from another model
+        #[arg(long)]
+        all: bool,
+
+        /// Output the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Verify local memory against the integrity hash chain
+    Verify {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Save checkpoint and prepare for context reset (used by hooks)
     #[command(name = "checkpoint-and-clear")]
     CheckpointAndClear {
@@ -122,6 +249,14 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
+    // Paths, keychain and the daemon we spawn all follow MINNA_PROFILE
+    if let Some(profile) = &cli.profile {
+        std::env::set_var("MINNA_PROFILE", profile);
+    }
+    if let Some(profile) = std::env::var("MINNA_PROFILE").ok().filter(|p| !p.trim().is_empty()) {
+        minna_auth_bridge::validate_profile(profile.trim())?;
+    }
+
     match cli.command {
         None => tui::welcome::run().await,
         Some(Commands::Add { sources, ui_test }) => {
@@ -145,6 +280,7 @@ async fn main() -> Result<()> {
                 commands::mcp::run(tool).await
             }
         }
+        Some(Commands::McpServe { stdio }) => commands::mcp_serve::run(stdio).await,
         Some(Commands::Daemon { command }) => match command {
             DaemonCommand::Status => commands::daemon::status().await,
             DaemonCommand::Start => commands::daemon::start().await,
@@ -154,6 +290,19 @@ async fn main() -> Result<()> {
         Some(Commands::Remove { source }) => commands::remove::run(&source).await,
         Some(Commands::Sync { sources, all }) => commands::sync::run(sources, all).await,
         Some(Commands::Link) => commands::link::run().await,
+        Some(Commands::Search { query, limit, json }) => {
+            commands::search::run(query, limit, json).await
+        }
+        Some(Commands::Stats { recent, json }) => commands::stats::run(recent, json).await,
+        Some(Commands::Pin { documents, remove }) => commands::pin::run(documents, remove).await,
+        Some(Commands::Purge { documents, yes }) => commands::purge::run(documents, yes).await,
+        Some(Commands::Export { path, embeddings, edges, json }) => {
+            commands::export::run(path, embeddings, edges, json).await
+        }
+        Some(Commands::Import { path, json }) => commands::import::run(path, json).await,
+        Some(Commands::Maintenance { check, json }) => commands::maintenance::run(check, json).await,
+        Some(Commands::ReindexEmbeddings { all, json }) => commands::reindex::run(all, json).await,
+        Some(Commands::Verify { json }) => commands::verify::run(json).await,
         Some(Commands::CheckpointAndClear { trigger }) => {
             commands::checkpoint::run(trigger).await
         }
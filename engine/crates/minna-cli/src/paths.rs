@@ -1,34 +1,25 @@
 //! Shared path utilities for minna-cli
 //!
-//! These paths must match minna-core's MinnaPaths::from_env() to ensure
-//! the CLI and daemon use the same locations.
+//! These come from minna-core's `MinnaPaths::from_env()` so the CLI and daemon
+//! use the same locations, including the active profile's directory.
 
 use std::path::PathBuf;
 
-/// Get the base Minna data directory.
-/// Matches minna-core's MinnaPaths::from_env() logic.
+use minna_core::MinnaPaths;
+
+/// Get the base Minna data directory for the active profile.
 pub fn get_data_dir() -> PathBuf {
-    if let Some(dir) = std::env::var_os("MINNA_DATA_DIR") {
-        return PathBuf::from(dir);
-    }
-    if let Some(home) = std::env::var_os("HOME") {
-        return PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Minna");
-    }
-    PathBuf::from(".minna")
+    MinnaPaths::from_env().base_dir
 }
 
 /// Get the MCP socket path (used by AI clients)
 pub fn get_socket_path() -> PathBuf {
-    get_data_dir().join("mcp.sock")
+    MinnaPaths::from_env().socket_path
 }
 
 /// Get the admin socket path (used by CLI to control daemon)
-#[allow(dead_code)]
 pub fn get_admin_socket_path() -> PathBuf {
-    get_data_dir().join("admin.sock")
+    MinnaPaths::from_env().admin_socket_path
 }
 
 /// Get the daemon PID file path
@@ -37,12 +28,11 @@ pub fn get_pid_file() -> PathBuf {
 }
 
 /// Get the database path
-#[allow(dead_code)]
 pub fn get_db_path() -> PathBuf {
-    get_data_dir().join("minna.db")
+    MinnaPaths::from_env().db_path
 }
 
 /// Get the auth file path
 pub fn get_auth_path() -> PathBuf {
-    get_data_dir().join("auth.json")
+    MinnaPaths::from_env().auth_path
 }
@@ -0,0 +1,638 @@
+//! Portable corpus bundles for backup and moving to another machine.
+//!
+//! A bundle is a directory (or, if the path ends in `.tar`, a tar archive of
+//! one) containing:
+//!
+//! - `manifest.json`: format version, export time and counts
+//! - `documents.jsonl`: live documents with their tags ([`ExportedDocument`])
+//! - `clusters.jsonl`: stored clusters
+//! - `cursors.jsonl`: sync cursors, `{provider, cursor}` per line
+//! - `embeddings.jsonl`: optional, `{doc_id, embedding, updated_at}` per line
+//! - `nodes.jsonl` / `edges.jsonl`: optional, the graph; each edge carries
+//!   the documents it was extracted from
+//!
+//! Embeddings, clusters and edge sources refer to documents by their `id` in
+//! `documents.jsonl`. Importing maps those onto the local database by URI, so
+//! a bundle can be imported into a database that already holds some of the
+//! same documents: the newer copy of each document wins.
+//!
+//! [`ExportedDocument`]: minna_ingest::ExportedDocument
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use minna_graph::{EdgeSource, GraphEdge, GraphNode, GraphStore};
+use minna_ingest::{ClusterRecord, ExportedDocument};
+use minna_vector::StoredEmbedding;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use crate::{Core, Document, IngestionEngine, VectorStore};
+
+/// Bumped when the bundle layout changes incompatibly.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+const GRAPH_PAGE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// Include `embeddings.jsonl` (large; can be recomputed on import).
+    #[serde(default)]
+    pub embeddings: bool,
+    /// Include the graph (`nodes.jsonl` and `edges.jsonl`).
+    #[serde(default)]
+    pub edges: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub documents: usize,
+    #[serde(default)]
+    pub clusters: usize,
+    #[serde(default)]
+    pub cursors: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edges: Option<usize>,
+}
+
+/// A line of `edges.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedEdge {
+    #[serde(flatten)]
+    pub edge: GraphEdge,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<EdgeSource>,
+}
+
+/// A line of `cursors.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportedCursor {
+    provider: String,
+    cursor: String,
+}
+
+/// What an import changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Documents written (new, or newer than the local copy).
+    pub documents_imported: usize,
+    /// Documents whose local copy was as new or newer.
+    pub documents_skipped: usize,
+    pub embeddings: usize,
+    /// Imported documents without an embedding in the bundle, queued for
+    /// re-embedding.
+    pub reembed_queued: usize,
+    pub clusters: usize,
+    pub cursors: usize,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// Export the corpus to `dest`, which must not exist yet.
+pub async fn export_bundle(
+    ingest: &IngestionEngine,
+    vector: &VectorStore,
+    graph: &GraphStore,
+    dest: &Path,
+    options: ExportOptions,
+) -> Result<ExportManifest> {
+    if dest.exists() {
+        return Err(anyhow!("{} already exists", dest.display()));
+    }
+    let as_tar = is_tar(dest);
+    let dir = if as_tar {
+        staging_dir(dest, "partial")
+    } else {
+        dest.to_path_buf()
+    };
+    std::fs::create_dir_all(&dir)?;
+
+    let result = write_bundle(ingest, vector, graph, &dir, options).await;
+    let result = match result {
+        Ok(manifest) if as_tar => pack_tar(&dir, dest).map(|_| manifest),
+        other => other,
+    };
+    if as_tar || result.is_err() {
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    result
+}
+
+async fn write_bundle(
+    ingest: &IngestionEngine,
+    vector: &VectorStore,
+    graph: &GraphStore,
+    dir: &Path,
+    options: ExportOptions,
+) -> Result<ExportManifest> {
+    let mut out = BufWriter::new(File::create(dir.join("documents.jsonl"))?);
+    let documents = ingest.export_all(&mut out).await?;
+    out.flush()?;
+
+    // SQLite treats a negative LIMIT as no limit
+    let clusters = ingest.list_clusters(usize::MAX).await?;
+    write_jsonl(&dir.join("clusters.jsonl"), &clusters)?;
+
+    let cursors: Vec<ExportedCursor> = ingest
+        .list_sync_cursors()
+        .await?
+        .into_iter()
+        .map(|(provider, cursor)| ExportedCursor { provider, cursor })
+        .collect();
+    write_jsonl(&dir.join("cursors.jsonl"), &cursors)?;
+
+    let embeddings = if options.embeddings {
+        let stored = vector.list_embeddings().await?;
+        write_jsonl(&dir.join("embeddings.jsonl"), &stored)?;
+        Some(stored.len())
+    } else {
+        None
+    };
+
+    let (nodes, edges) = if options.edges {
+        let (nodes, edges) = write_graph(graph, dir).await?;
+        (Some(nodes), Some(edges))
+    } else {
+        (None, None)
+    };
+
+    let manifest = ExportManifest {
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        documents,
+        clusters: clusters.len(),
+        cursors: cursors.len(),
+        embeddings,
+        nodes,
+        edges,
+    };
+    std::fs::write(dir.join("manifest.json"), serde_json::to_string_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+async fn write_graph(graph: &GraphStore, dir: &Path) -> Result<(usize, usize)> {
+    let mut out = BufWriter::new(File::create(dir.join("nodes.jsonl"))?);
+    let mut nodes = 0;
+    let mut after = String::new();
+    loop {
+        let page = graph.nodes_after(&after, GRAPH_PAGE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = last.id.clone();
+        for node in &page {
+            serde_json::to_writer(&mut out, node)?;
+            out.write_all(b"\n")?;
+        }
+        nodes += page.len();
+    }
+    out.flush()?;
+
+    let mut out = BufWriter::new(File::create(dir.join("edges.jsonl"))?);
+    let mut edges = 0;
+    let mut after = 0;
+    loop {
+        let page = graph.edges_after(after, GRAPH_PAGE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = last.id;
+        let ids: Vec<i64> = page.iter().map(|edge| edge.id).collect();
+        let mut sources: HashMap<i64, Vec<EdgeSource>> = HashMap::new();
+        for source in graph.sources_for_edges(&ids).await? {
+            sources.entry(source.edge_id).or_default().push(source);
+        }
+        for edge in page {
+            let line = ExportedEdge {
+                sources: sources.remove(&edge.id).unwrap_or_default(),
+                edge,
+            };
+            serde_json::to_writer(&mut out, &line)?;
+            out.write_all(b"\n")?;
+            edges += 1;
+        }
+    }
+    out.flush()?;
+    Ok((nodes, edges))
+}
+
+/// Restore a bundle written by [`export_bundle`] into this database.
+///
+/// Documents are matched by URI and only overwritten by a newer copy.
+/// Clusters are added unless one with the same label exists, and cursors
+/// only fill in providers that have never synced here. Imported documents
+/// without an embedding are queued for re-embedding.
+///
+/// The import is a single transaction, so one that fails leaves nothing
+/// behind. `vector` and `graph` must share `ingest`'s database.
+pub async fn import_bundle(
+    ingest: &IngestionEngine,
+    vector: &VectorStore,
+    graph: &GraphStore,
+    src: &Path,
+) -> Result<ImportReport> {
+    if !is_tar(src) {
+        return read_bundle(ingest, vector, graph, src).await;
+    }
+    let dir = std::env::temp_dir().join(format!(
+        "minna-import-{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_millis()
+    ));
+    let result = match unpack_tar(src, &dir) {
+        Ok(()) => read_bundle(ingest, vector, graph, &dir).await,
+        Err(err) => Err(err),
+    };
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+async fn read_bundle(
+    ingest: &IngestionEngine,
+    vector: &VectorStore,
+    graph: &GraphStore,
+    dir: &Path,
+) -> Result<ImportReport> {
+    let manifest: ExportManifest = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("manifest.json"))
+            .with_context(|| format!("{} is not a Minna bundle", dir.display()))?,
+    )?;
+    if manifest.format_version > EXPORT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "bundle format {} is newer than this version of Minna supports ({})",
+            manifest.format_version,
+            EXPORT_FORMAT_VERSION
+        ));
+    }
+
+    // The vector pool's connections have sqlite-vec loaded, and the stores
+    // share one database, so everything goes through a single transaction
+    let mut conn = vector.pool().acquire().await?;
+    sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+    let result = read_bundle_on(&mut conn, ingest, vector, graph, dir).await;
+    match result {
+        Ok(report) => {
+            sqlx::query("COMMIT").execute(&mut *conn).await?;
+            if report.embeddings > 0 {
+                vector.reset_ann();
+            }
+            Ok(report)
+        }
+        Err(err) => {
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+            Err(err)
+        }
+    }
+}
+
+/// The writes of [`read_bundle`], on a connection inside its transaction.
+/// Lookups see only what was committed before the import.
+async fn read_bundle_on(
+    conn: &mut SqliteConnection,
+    ingest: &IngestionEngine,
+    vector: &VectorStore,
+    graph: &GraphStore,
+    dir: &Path,
+) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    // Bundle document ID -> local document ID, for everything in the bundle
+    let mut ids: HashMap<i64, i64> = HashMap::new();
+    // Local IDs of documents written by this import
+    let mut written: HashSet<i64> = HashSet::new();
+
+    for doc in read_jsonl::<ExportedDocument>(&dir.join("documents.jsonl"))? {
+        let doc = doc?;
+        let existing = ingest.get_document_by_uri(&doc.uri).await?;
+        let local_id = match existing {
+            Some(existing) if existing.updated_at >= doc.updated_at => {
+                report.documents_skipped += 1;
+                existing.id.ok_or_else(|| anyhow!("stored document without id"))?
+            }
+            _ => {
+                let id = ingest
+                    .upsert_document_on(
+                        conn,
+                        &Document {
+                            id: None,
+                            uri: doc.uri,
+                            source: doc.source,
+                            title: doc.title,
+                            body: doc.body,
+                            updated_at: doc.updated_at,
+                        },
+                    )
+                    .await?;
+                written.insert(id);
+                report.documents_imported += 1;
+                id
+            }
+        };
+        for tag in &doc.tags {
+            ingest.add_tag_on(conn, local_id, tag).await?;
+        }
+        ids.insert(doc.id, local_id);
+    }
+
+    let mut embedded: HashSet<i64> = HashSet::new();
+    for embedding in read_optional_jsonl::<StoredEmbedding>(&dir.join("embeddings.jsonl"))? {
+        let embedding = embedding?;
+        let Some(local_id) = ids.get(&embedding.doc_id).filter(|id| written.contains(id)) else {
+            continue;
+        };
+        vector.upsert_embedding_on(conn, *local_id, &embedding.embedding).await?;
+        embedded.insert(*local_id);
+        report.embeddings += 1;
+    }
+    for id in written.difference(&embedded) {
+        vector.queue_reembed_on(conn, *id).await?;
+        report.reembed_queued += 1;
+    }
+
+    let mut labels: HashSet<String> = HashSet::new();
+    for cluster in read_optional_jsonl::<ClusterRecord>(&dir.join("clusters.jsonl"))? {
+        let cluster = cluster?;
+        if labels.contains(&cluster.label) || !ingest.get_cluster_doc_ids(&cluster.label).await?.is_empty() {
+            continue;
+        }
+        let doc_ids: Vec<i64> = cluster.doc_ids.iter().filter_map(|id| ids.get(id).copied()).collect();
+        if doc_ids.is_empty() {
+            continue;
+        }
+        labels.insert(cluster.label.clone());
+        ingest
+            .store_clusters_on(
+                conn,
+                &[ClusterRecord {
+                    id: None,
+                    doc_ids,
+                    ..cluster
+                }],
+            )
+            .await?;
+        report.clusters += 1;
+    }
+
+    for cursor in read_optional_jsonl::<ExportedCursor>(&dir.join("cursors.jsonl"))? {
+        let cursor = cursor?;
+        if ingest.get_sync_cursor(&cursor.provider).await?.is_none() {
+            ingest.set_sync_cursor_on(conn, &cursor.provider, &cursor.cursor).await?;
+            report.cursors += 1;
+        }
+    }
+
+    for node in read_optional_jsonl::<GraphNode>(&dir.join("nodes.jsonl"))? {
+        graph.import_node_on(conn, &node?).await?;
+        report.nodes += 1;
+    }
+    for line in read_optional_jsonl::<ExportedEdge>(&dir.join("edges.jsonl"))? {
+        let line = line?;
+        let edge_id = graph.import_edge_on(conn, &line.edge).await?;
+        for source in line.sources {
+            if let Some(doc_id) = ids.get(&source.doc_id) {
+                graph
+                    .add_edge_source_on(
+                        conn,
+                        &EdgeSource {
+                            edge_id,
+                            doc_id: *doc_id,
+                            observed_at: source.observed_at,
+                        },
+                    )
+                    .await?;
+            }
+        }
+        report.edges += 1;
+    }
+
+    Ok(report)
+}
+
+fn is_tar(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tar")
+}
+
+/// Sibling directory a tar bundle is assembled in before it's archived.
+fn staging_dir(dest: &Path, suffix: &str) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    dest.with_file_name(name)
+}
+
+/// Archive the bundle directory's files at the root of a tar file.
+fn pack_tar(dir: &Path, dest: &Path) -> Result<()> {
+    let mut builder = tar::Builder::new(BufWriter::new(File::create(dest)?));
+    builder.append_dir_all(".", dir)?;
+    builder.into_inner()?.flush()?;
+    Ok(())
+}
+
+fn unpack_tar(src: &Path, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    tar::Archive::new(BufReader::new(File::open(src)?)).unpack(dir)?;
+    Ok(())
+}
+
+fn write_jsonl<T: Serialize>(path: &Path, items: &[T]) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for item in items {
+        serde_json::to_writer(&mut out, item)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Parse a JSON-lines file lazily, skipping blank lines.
+fn read_jsonl<T: DeserializeOwned>(path: &Path) -> Result<impl Iterator<Item = Result<T>>> {
+    let file = File::open(path).with_context(|| format!("missing {}", path.display()))?;
+    Ok(BufReader::new(file).lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line).map_err(Into::into)),
+        Err(err) => Some(Err(err.into())),
+    }))
+}
+
+/// Like [`read_jsonl`], but a missing file reads as empty.
+fn read_optional_jsonl<T: DeserializeOwned + Send + 'static>(
+    path: &Path,
+) -> Result<Box<dyn Iterator<Item = Result<T>> + Send>> {
+    if !path.exists() {
+        return Ok(Box::new(std::iter::empty()));
+    }
+    Ok(Box::new(read_jsonl(path)?))
+}
+
+impl Core {
+    pub async fn export_bundle(&self, dest: &Path, options: ExportOptions) -> Result<ExportManifest> {
+        export_bundle(&self.ingest, &self.vector, &self.graph, dest, options).await
+    }
+
+    /// Import a bundle, then embed what it didn't carry embeddings for
+    /// (left queued while the embedding model is still loading).
+    pub async fn import_bundle(&self, src: &Path) -> Result<ImportReport> {
+        let report = import_bundle(&self.ingest, &self.vector, &self.graph, src).await?;
+        if report.reembed_queued > 0 {
+            self.reembed_pending(64).await?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staging_dir() {
+        assert_eq!(
+            staging_dir(Path::new("/backups/minna.tar"), "partial"),
+            PathBuf::from("/backups/minna.tar.partial")
+        );
+    }
+
+    #[test]
+    fn test_options_default_to_documents_only() {
+        let options: ExportOptions = serde_json::from_str("{}").unwrap();
+        assert!(!options.embeddings && !options.edges);
+
+        let manifest = ExportManifest {
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            documents: 3,
+            clusters: 0,
+            cursors: 1,
+            embeddings: None,
+            nodes: Some(3),
+            edges: Some(2),
+        };
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert!(json.get("embeddings").is_none());
+        assert_eq!(json["edges"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_dedups_by_uri() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = dir.path().join("source.db");
+        let ingest = IngestionEngine::new(&source_db).await.unwrap();
+        let vector = VectorStore::new(&source_db).await.unwrap();
+        let graph = ingest.graph_store();
+
+        let doc = |uri: &str, body: &str, updated_at: DateTime<Utc>| Document {
+            id: None,
+            uri: uri.to_string(),
+            source: "slack".to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at,
+        };
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(1);
+        let a = ingest.upsert_document(&doc("slack://a", "exported a", now)).await.unwrap();
+        let b = ingest.upsert_document(&doc("slack://b", "exported b", old)).await.unwrap();
+        ingest.add_tag(a, "general").await.unwrap();
+        vector.upsert_embedding(a, &[0.5, 0.5]).await.unwrap();
+        ingest.set_sync_cursor("slack", "cursor-1").await.unwrap();
+        ingest
+            .store_clusters(&[ClusterRecord {
+                id: None,
+                label: "launch".to_string(),
+                doc_ids: vec![a, b],
+                created_at: now,
+                summary: Some("Launch planning".to_string()),
+            }])
+            .await
+            .unwrap();
+
+        let bundle = dir.path().join("bundle.tar");
+        let options = ExportOptions { embeddings: true, edges: true };
+        let manifest = export_bundle(&ingest, &vector, &graph, &bundle, options).await.unwrap();
+        assert_eq!(manifest.documents, 2);
+        assert_eq!(manifest.cursors, 1);
+
+        // The target has a newer b and a cursor of its own
+        let target_db = dir.path().join("target.db");
+        let target = IngestionEngine::new(&target_db).await.unwrap();
+        let target_vector = VectorStore::new(&target_db).await.unwrap();
+        let local_b = target.upsert_document(&doc("slack://b", "local b", now)).await.unwrap();
+        target.set_sync_cursor("slack", "cursor-2").await.unwrap();
+
+        let report = import_bundle(&target, &target_vector, &target.graph_store(), &bundle)
+            .await
+            .unwrap();
+        assert_eq!(report.documents_imported, 1);
+        assert_eq!(report.documents_skipped, 1);
+        assert_eq!(report.embeddings, 1);
+        assert_eq!(report.reembed_queued, 0);
+        assert_eq!(report.clusters, 1);
+        assert_eq!(report.cursors, 0);
+
+        let imported = target.get_document_by_uri("slack://a").await.unwrap().unwrap();
+        let imported_id = imported.id.unwrap();
+        assert_eq!(target.tags_for(imported_id).await.unwrap(), vec!["general".to_string()]);
+        let kept = target.get_document_by_uri("slack://b").await.unwrap().unwrap();
+        assert_eq!(kept.body, "local b");
+        let mut cluster = target.get_cluster_doc_ids("launch").await.unwrap();
+        cluster.sort();
+        let mut expected = vec![imported_id, local_b];
+        expected.sort();
+        assert_eq!(cluster, expected);
+        let clusters = target.list_clusters(10).await.unwrap();
+        assert_eq!(clusters[0].summary.as_deref(), Some("Launch planning"));
+        assert_eq!(target.get_sync_cursor("slack").await.unwrap().as_deref(), Some("cursor-2"));
+
+        // Importing again changes nothing
+        let again = import_bundle(&target, &target_vector, &target.graph_store(), &bundle)
+            .await
+            .unwrap();
+        assert_eq!(again.documents_imported, 0);
+        assert_eq!(again.clusters, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = dir.path().join("source.db");
+        let ingest = IngestionEngine::new(&source_db).await.unwrap();
+        let vector = VectorStore::new(&source_db).await.unwrap();
+        let doc = Document {
+            id: None,
+            uri: "slack://a".to_string(),
+            source: "slack".to_string(),
+            title: None,
+            body: "exported a".to_string(),
+            updated_at: Utc::now(),
+        };
+        let a = ingest.upsert_document(&doc).await.unwrap();
+        vector.upsert_embedding(a, &[0.5, 0.5]).await.unwrap();
+        ingest.set_sync_cursor("slack", "cursor-1").await.unwrap();
+
+        let bundle = dir.path().join("bundle");
+        let options = ExportOptions { embeddings: true, edges: true };
+        export_bundle(&ingest, &vector, &ingest.graph_store(), &bundle, options).await.unwrap();
+        // Edges are read last, after everything else was written
+        std::fs::write(bundle.join("edges.jsonl"), "not json\n").unwrap();
+
+        let target_db = dir.path().join("target.db");
+        let target = IngestionEngine::new(&target_db).await.unwrap();
+        let target_vector = VectorStore::new(&target_db).await.unwrap();
+        assert!(import_bundle(&target, &target_vector, &target.graph_store(), &bundle)
+            .await
+            .is_err());
+        assert_eq!(target.document_count().await.unwrap(), 0);
+        assert_eq!(target.get_sync_cursor("slack").await.unwrap(), None);
+        assert!(target_vector.get_embedding(a).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Cluster labels and summaries.
+//!
+//! A cluster is named after the terms that best set it apart: terms found in
+//! many of its documents (TF) and in few other clusters (IDF). Labels join
+//! the top terms with hyphens, e.g. `kafka-consumer-lag`, so they can be
+//! written as `pack=kafka-consumer-lag` in a query.
+//!
+//! If `MINNA_CLUSTER_SUMMARY_MODEL` is set, each cluster is also summarized
+//! by that model through an OpenAI-compatible chat endpoint
+//! (`MINNA_CLUSTER_SUMMARY_URL`), a local Ollama server by default.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Terms in a label.
+const LABEL_TERMS: usize = 3;
+
+/// Only the start of each document is read; titles and openings carry the topic.
+const LABEL_TEXT_CHARS: usize = 2000;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "because", "been", "before",
+    "being", "but", "can", "com", "could", "did", "does", "doing", "done", "for", "from", "get",
+    "got", "had", "has", "have", "here", "how", "http", "https", "into", "its", "just", "let",
+    "like", "more", "most", "need", "not", "now", "off", "once", "one", "only", "other", "our",
+    "out", "over", "same", "see", "should", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "too", "under", "until", "use",
+    "very", "was", "way", "were", "what", "when", "where", "which", "while", "who", "why",
+    "will", "with", "would", "www", "yes", "you", "your",
+];
+
+/// Documents of a cluster shown to the summarizer.
+const SUMMARY_DOCUMENTS: usize = 8;
+
+/// Characters of each document shown to the summarizer.
+const SUMMARY_EXCERPT_CHARS: usize = 600;
+
+const DEFAULT_SUMMARY_URL: &str = "http://localhost:11434/v1";
+
+const SUMMARY_PROMPT: &str = "These excerpts come from one cluster of related workplace \
+documents. Reply with a title of at most five words naming what they have in common, then \
+a newline and one sentence describing it. No other text.";
+
+/// Writes a [`ClusterRecord`](minna_ingest::ClusterRecord) summary.
+#[async_trait]
+pub trait ClusterSummarizer: Send + Sync {
+    /// Summarize a cluster from excerpts of its documents.
+    async fn summarize(&self, label: &str, excerpts: &[String]) -> Result<String>;
+}
+
+/// Summarizes with a model behind an OpenAI-compatible `/chat/completions`
+/// endpoint (Ollama, LM Studio, llama.cpp, or a hosted API).
+pub struct ChatSummarizer {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+impl ChatSummarizer {
+    /// Configure from the environment; `None` unless
+    /// `MINNA_CLUSTER_SUMMARY_MODEL` is set. `api_key` takes precedence over
+    /// `MINNA_CLUSTER_SUMMARY_API_KEY`.
+    pub fn from_env(api_key: Option<String>) -> Result<Option<Self>> {
+        let Some(model) = std::env::var("MINNA_CLUSTER_SUMMARY_MODEL")
+            .ok()
+            .filter(|model| !model.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let base_url = std::env::var("MINNA_CLUSTER_SUMMARY_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_SUMMARY_URL.to_string());
+        let api_key = api_key.or_else(|| {
+            std::env::var("MINNA_CLUSTER_SUMMARY_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty())
+        });
+        // Local models can take a while, but a run shouldn't hang on one
+        let client = Client::builder().timeout(Duration::from_secs(120)).build()?;
+        Ok(Some(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+        }))
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send one system and one user message; returns the model's reply.
+    pub async fn complete(&self, system: &str, user: &str) -> Result<String> {
+        let body = json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": user },
+            ],
+            "temperature": 0.2,
+        });
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("summary request failed ({}): {}", status, body));
+        }
+        let parsed: ChatResponse = response.json().await?;
+        parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content.trim().to_string())
+            .filter(|summary| !summary.is_empty())
+            .ok_or_else(|| anyhow!("summary model returned no text"))
+    }
+}
+
+#[async_trait]
+impl ClusterSummarizer for ChatSummarizer {
+    async fn summarize(&self, label: &str, excerpts: &[String]) -> Result<String> {
+        let documents = excerpts
+            .iter()
+            .map(|excerpt| format!("---\n{}", excerpt))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.complete(SUMMARY_PROMPT, &format!("Keywords: {}\n\n{}", label, documents)).await
+    }
+}
+
+/// Excerpts of a cluster's documents for [`ClusterSummarizer::summarize`].
+pub fn summary_excerpts(texts: &[String]) -> Vec<String> {
+    texts
+        .iter()
+        .take(SUMMARY_DOCUMENTS)
+        .map(|text| text.chars().take(SUMMARY_EXCERPT_CHARS).collect())
+        .collect()
+}
+
+/// Label each cluster, given the texts of its documents. Labels are unique;
+/// a cluster without usable terms is `cluster-<n>`.
+pub fn keyword_labels(clusters: &[Vec<String>]) -> Vec<String> {
+    let stopwords: HashSet<&str> = STOPWORDS.iter().copied().collect();
+    // Per cluster: in how many of its documents each term occurs
+    let term_docs: Vec<HashMap<String, usize>> = clusters
+        .iter()
+        .map(|texts| {
+            let mut counts = HashMap::new();
+            for text in texts {
+                for term in terms(text, &stopwords) {
+                    *counts.entry(term).or_insert(0) += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+    let mut cluster_freq: HashMap<&str, usize> = HashMap::new();
+    for counts in &term_docs {
+        for term in counts.keys() {
+            *cluster_freq.entry(term.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    let total = clusters.len() as f32;
+    let mut used = HashSet::new();
+    let mut labels = Vec::with_capacity(clusters.len());
+    for (idx, counts) in term_docs.iter().enumerate() {
+        let size = clusters[idx].len().max(1) as f32;
+        let mut scored: Vec<(&str, f32)> = counts
+            .iter()
+            .map(|(term, docs)| {
+                let tf = *docs as f32 / size;
+                let idf = ((1.0 + total) / (1.0 + cluster_freq[term.as_str()] as f32)).ln() + 1.0;
+                (term.as_str(), tf * idf)
+            })
+            .collect();
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(b.0))
+        });
+        let base = if scored.is_empty() {
+            format!("cluster-{}", idx + 1)
+        } else {
+            scored
+                .iter()
+                .take(LABEL_TERMS)
+                .map(|(term, _)| *term)
+                .collect::<Vec<_>>()
+                .join("-")
+        };
+        let mut label = base.clone();
+        let mut n = 2;
+        while !used.insert(label.clone()) {
+            label = format!("{}-{}", base, n);
+            n += 1;
+        }
+        labels.push(label);
+    }
+    labels
+}
+
+/// Text of a document that labelling reads.
+pub fn label_text(title: Option<&str>, body: &str) -> String {
+    let body: String = body.chars().take(LABEL_TEXT_CHARS).collect();
+    match title {
+        Some(title) => format!("{}\n{}", title, body),
+        None => body,
+    }
+}
+
+/// Distinct candidate terms of a text.
+fn terms(text: &str, stopwords: &HashSet<&str>) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|word| word.to_lowercase())
+        .filter(|word| word.chars().count() >= 3)
+        .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        .filter(|word| !stopwords.contains(word.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_labels_use_distinctive_terms() {
+        let labels = keyword_labels(&[
+            texts(&[
+                "Kafka consumer lag on the billing cluster",
+                "Consumer lag alert: kafka partitions behind",
+                "Billing kafka consumer restarted",
+            ]),
+            texts(&[
+                "Offsite planning: agenda for the billing review",
+                "Offsite travel and agenda",
+                "Agenda draft for the offsite",
+            ]),
+        ]);
+        assert_eq!(labels[0], "consumer-kafka-lag");
+        assert_eq!(labels[1], "agenda-offsite-draft");
+    }
+
+    #[test]
+    fn test_labels_are_unique() {
+        let labels = keyword_labels(&[
+            texts(&["release notes"]),
+            texts(&["release notes"]),
+            texts(&["the and of"]),
+        ]);
+        assert_eq!(labels, vec!["notes-release", "notes-release-2", "cluster-3"]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Weekly workspace digest.
+//!
+//! Once a week Minna indexes a report document under source `minna`
+//! summarizing what was synced, the most active clusters, who entered or left
+//! Ring 1, and the health of the index. The previous Ring 1 membership is kept
+//! in `sync_state` so each digest can report movements since the last one.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use minna_graph::Ring;
+use serde::{Deserialize, Serialize};
+
+use crate::{Core, Document};
+
+/// Source name for documents Minna generates about itself.
+pub const DIGEST_SOURCE: &str = "minna";
+
+/// `sync_state` key holding the last digest's snapshot.
+const DIGEST_STATE_KEY: &str = "minna_digest";
+
+/// Clusters listed in the digest.
+const TOP_CLUSTERS: usize = 5;
+
+/// State carried from one digest to the next.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    generated_at: Option<DateTime<Utc>>,
+    ring_one: Vec<String>,
+}
+
+/// Figures that make up a digest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestStats {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Documents added or updated during the period, per source.
+    pub synced_by_source: Vec<(String, i64)>,
+    /// Clusters ranked by documents updated during the period.
+    pub active_clusters: Vec<(String, usize)>,
+    pub entered_ring_one: Vec<String>,
+    pub left_ring_one: Vec<String>,
+    pub total_documents: i64,
+    pub total_vectors: i64,
+    pub reembed_pending: i64,
+    pub tombstones: i64,
+    /// Integrity chain result, if the chain is enabled.
+    pub integrity_ok: Option<bool>,
+}
+
+impl DigestStats {
+    /// Render the digest as markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Minna weekly digest\n\n{} to {}\n\n## Synced\n\n",
+            self.period_start.format("%Y-%m-%d"),
+            self.period_end.format("%Y-%m-%d")
+        );
+        if self.synced_by_source.is_empty() {
+            out.push_str("Nothing new this week.\n");
+        } else {
+            let total: i64 = self.synced_by_source.iter().map(|(_, count)| count).sum();
+            out.push_str(&format!("{} documents added or updated:\n\n", total));
+            for (source, count) in &self.synced_by_source {
+                out.push_str(&format!("- {}: {}\n", source, count));
+            }
+        }
+
+        out.push_str("\n## Most active clusters\n\n");
+        if self.active_clusters.is_empty() {
+            out.push_str("No cluster activity.\n");
+        } else {
+            for (label, count) in &self.active_clusters {
+                out.push_str(&format!("- {} ({} updated)\n", label, count));
+            }
+        }
+
+        out.push_str("\n## Ring 1 changes\n\n");
+        if self.entered_ring_one.is_empty() && self.left_ring_one.is_empty() {
+            out.push_str("No changes.\n");
+        } else {
+            if !self.entered_ring_one.is_empty() {
+                out.push_str(&format!("Entered: {}\n", self.entered_ring_one.join(", ")));
+            }
+            if !self.left_ring_one.is_empty() {
+                out.push_str(&format!("Left: {}\n", self.left_ring_one.join(", ")));
+            }
+        }
+
+        out.push_str("\n## Index health\n\n");
+        out.push_str(&format!("- Documents: {}\n", self.total_documents));
+        out.push_str(&format!("- Embeddings: {}\n", self.total_vectors));
+        out.push_str(&format!("- Awaiting re-embedding: {}\n", self.reembed_pending));
+        out.push_str(&format!("- Awaiting deletion cleanup: {}\n", self.tombstones));
+        match self.integrity_ok {
+            Some(true) => out.push_str("- Integrity chain: verified\n"),
+            Some(false) => out.push_str("- Integrity chain: FAILED (run `minna verify`)\n"),
+            None => {}
+        }
+        out
+    }
+}
+
+/// Members added to and removed from a set, each sorted.
+pub fn ring_changes(previous: &[String], current: &[String]) -> (Vec<String>, Vec<String>) {
+    let previous: BTreeSet<&String> = previous.iter().collect();
+    let current: BTreeSet<&String> = current.iter().collect();
+    let entered = current.difference(&previous).map(|s| s.to_string()).collect();
+    let left = previous.difference(&current).map(|s| s.to_string()).collect();
+    (entered, left)
+}
+
+impl Core {
+    /// Generate and index the weekly digest if a week has passed since the
+    /// last one. Returns the document ID when a digest was written.
+    pub async fn weekly_digest_if_due(&self) -> Result<Option<i64>> {
+        let state = self.digest_state().await?;
+        let now = Utc::now();
+        if let Some(last) = state.generated_at {
+            if now - last < Duration::days(7) {
+                return Ok(None);
+            }
+        }
+        self.write_digest(state, now).await.map(Some)
+    }
+
+    /// Generate and index a digest covering the week up to `now`.
+    pub async fn generate_digest(&self) -> Result<i64> {
+        let state = self.digest_state().await?;
+        self.write_digest(state, Utc::now()).await
+    }
+
+    async fn digest_state(&self) -> Result<DigestState> {
+        Ok(self
+            .ingest
+            .get_sync_cursor(DIGEST_STATE_KEY)
+            .await?
+            .and_then(|cursor| serde_json::from_str(&cursor).ok())
+            .unwrap_or_default())
+    }
+
+    async fn write_digest(&self, state: DigestState, now: DateTime<Utc>) -> Result<i64> {
+        let period_start = now - Duration::days(7);
+
+        let mut active_clusters = Vec::new();
+        for cluster in self.ingest.list_clusters(20).await? {
+            let docs = self.ingest.fetch_documents_by_ids(&cluster.doc_ids).await?;
+            let updated = docs.iter().filter(|doc| doc.updated_at >= period_start).count();
+            if updated > 0 {
+                active_clusters.push((cluster.label, updated));
+            }
+        }
+        active_clusters.sort_by_key(|(_, updated)| std::cmp::Reverse(*updated));
+        active_clusters.truncate(TOP_CLUSTERS);
+
+        let mut ring_one = Vec::new();
+        for node_id in self.graph.nodes_in_ring(Ring::One).await? {
+            let name = self
+                .graph
+                .get_node(&node_id)
+                .await?
+                .and_then(|node| node.display_name)
+                .unwrap_or(node_id);
+            ring_one.push(name);
+        }
+        ring_one.sort();
+        // The first digest has no baseline to compare against
+        let (entered_ring_one, left_ring_one) = if state.generated_at.is_some() {
+            ring_changes(&state.ring_one, &ring_one)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let integrity_ok = if self.ingest.integrity_chain_enabled() {
+            Some(self.ingest.verify_integrity().await?.is_ok())
+        } else {
+            None
+        };
+
+        let stats = DigestStats {
+            period_start,
+            period_end: now,
+            synced_by_source: self
+                .ingest
+                .document_counts_updated_since(period_start)
+                .await?
+                .into_iter()
+                .filter(|(source, _)| source != DIGEST_SOURCE)
+                .collect(),
+            active_clusters,
+            entered_ring_one,
+            left_ring_one,
+            total_documents: self.ingest.document_count().await?,
+            total_vectors: self.vector.count().await?,
+            reembed_pending: self.vector.reembed_queue_len().await?,
+            tombstones: self.ingest.tombstone_count().await?,
+            integrity_ok,
+        };
+
+        let doc = Document {
+            id: None,
+            uri: format!("minna://digest/{}", now.format("%G-W%V")),
+            source: DIGEST_SOURCE.to_string(),
+            title: Some(format!("Minna weekly digest {}", now.format("%Y-%m-%d"))),
+            body: stats.to_markdown(),
+            updated_at: now,
+        };
+        let id = self.index_document(doc).await?;
+
+        let state = DigestState {
+            generated_at: Some(now),
+            ring_one,
+        };
+        self.ingest
+            .set_sync_cursor(DIGEST_STATE_KEY, &serde_json::to_string(&state)?)
+            .await?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_changes() {
+        let previous = vec!["alice".to_string(), "bob".to_string()];
+        let current = vec!["bob".to_string(), "carol".to_string()];
+        let (entered, left) = ring_changes(&previous, &current);
+        assert_eq!(entered, vec!["carol".to_string()]);
+        assert_eq!(left, vec!["alice".to_string()]);
+    }
+
+    #[test]
+    fn test_markdown_sections() {
+        let stats = DigestStats {
+            synced_by_source: vec![("slack".to_string(), 12), ("github".to_string(), 3)],
+            active_clusters: vec![("auth-refactor".to_string(), 4)],
+            entered_ring_one: vec!["carol".to_string()],
+            total_documents: 100,
+            integrity_ok: Some(true),
+            ..Default::default()
+        };
+        let md = stats.to_markdown();
+        assert!(md.contains("15 documents added or updated"));
+        assert!(md.contains("- slack: 12"));
+        assert!(md.contains("- auth-refactor (4 updated)"));
+        assert!(md.contains("Entered: carol"));
+        assert!(!md.contains("Left:"));
+        assert!(md.contains("Integrity chain: verified"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use regex::Regex;
 
 use anyhow::Result;
@@ -11,20 +11,58 @@ use base64::Engine;
 use std::time::Duration;
 use tracing::{info, warn};
 
-pub mod progress;
+pub mod bundle;
+pub mod clusters;
+pub mod digest;
 pub mod providers;
 pub mod scheduler;
+pub mod storage;
 pub mod tools;
+pub mod trends;
+pub mod workspace;
 
-pub use progress::{emit_progress, emit_result, emit_error, emit_warmup_progress, emit_ready};
-pub use providers::{ProviderRegistry, SyncProvider, SyncContext, ProvidersConfig};
+pub use minna_provider_sdk::progress;
+
+pub use progress::{emit_progress, emit_result, emit_error, emit_ring_change, emit_warmup_progress, emit_ready};
+pub use providers::{ProviderRegistry, SyncProvider, SyncContext, ProvidersConfig, RingsConfig, SourceLimit};
 pub use scheduler::{SyncScheduler, SyncDepth, SchedulerConfig, ScheduledSync, SyncPlanner};
-pub use tools::{Checkpoint, CheckpointStore, LoadQuery};
-// SyncSummary is defined below and re-exported from providers for convenience
+pub use storage::{Storage, StorageHit};
+pub use tools::{Checkpoint, CheckpointDiff, CheckpointMatch, CheckpointStore, LoadQuery, SectionDiff};
+pub use minna_provider_sdk::SyncSummary;
 
 pub use minna_auth_bridge::{AuthToken, TokenStore};
-pub use minna_ingest::{Document, IngestionEngine};
-pub use minna_vector::{embedder_from_env_or_hash, Cluster, Embedder, VectorStore};
+pub use minna_ingest::{Document, IngestionEngine, RedactionConfig, Redactor, TransformChain, TransformConfig};
+pub use minna_ingest::maintenance::{MaintenanceOptions, MaintenanceReport};
+pub use minna_vector::{
+    cosine_similarity, embed_document, embedder_for_model, embedder_from_env_or_hash,
+    embedder_with_standby, ollama_backend, remote_backend, reranker_from_env, Cluster, Embedder,
+    ModelUsage, OllamaEmbedder, RemoteEmbedder, Reranker, SearchFilter, SourceEmbedders,
+    StandbyEmbedder, VectorStore,
+};
+
+/// Text embedded to learn the dimension a model produces.
+const DIMENSION_PROBE: &str = "dimension probe";
+
+/// Whether `minna.db` is encrypted at rest (`MINNA_ENCRYPT_DB=1`, needs the
+/// `sqlcipher` feature).
+pub fn encryption_enabled() -> bool {
+    std::env::var("MINNA_ENCRYPT_DB")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// The database key from the keychain if encryption is enabled, for opening
+/// `minna.db` outside the daemon (e.g. from the CLI).
+pub fn database_key() -> Result<Option<String>> {
+    database_key_for(&TokenStore::load(&MinnaPaths::from_env().auth_path)?)
+}
+
+fn database_key_for(auth: &TokenStore) -> Result<Option<String>> {
+    if !encryption_enabled() {
+        return Ok(None);
+    }
+    auth.database_key().map(Some)
+}
 
 #[derive(Debug, Clone)]
 pub struct MinnaPaths {
@@ -37,19 +75,50 @@ pub struct MinnaPaths {
 }
 
 impl MinnaPaths {
+    /// Paths for the active profile (`MINNA_PROFILE`) under the data root.
     pub fn from_env() -> Self {
+        let profile = minna_auth_bridge::profile_from_env();
+        Self::for_profile(&Self::root_from_env(), profile.as_deref())
+    }
+
+    /// Data root: `MINNA_DATA_DIR`, else `~/Library/Application Support/Minna`.
+    pub fn root_from_env() -> PathBuf {
         if let Some(dir) = std::env::var_os("MINNA_DATA_DIR") {
-            let base_dir = PathBuf::from(dir);
-            return Self::from_base(base_dir);
+            return PathBuf::from(dir);
         }
         if let Some(home) = std::env::var_os("HOME") {
-            let base_dir = PathBuf::from(home)
+            return PathBuf::from(home)
                 .join("Library")
                 .join("Application Support")
                 .join("Minna");
-            return Self::from_base(base_dir);
         }
-        Self::from_base(PathBuf::from(".minna"))
+        PathBuf::from(".minna")
+    }
+
+    /// The default profile lives directly in `root`; others get their own
+    /// directory (database, sockets, settings) under `root/profiles`.
+    pub fn for_profile(root: &Path, profile: Option<&str>) -> Self {
+        match profile {
+            Some(profile) => Self::from_base(root.join("profiles").join(profile)),
+            None => Self::from_base(root.to_path_buf()),
+        }
+    }
+
+    /// Names of the profiles created under `root`, not counting the default.
+    pub fn list_profiles(root: &Path) -> Result<Vec<String>> {
+        let dir = root.join("profiles");
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut profiles = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                profiles.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        profiles.sort();
+        Ok(profiles)
     }
 
     pub fn from_base(base_dir: PathBuf) -> Self {
@@ -74,45 +143,338 @@ impl MinnaPaths {
     }
 }
 
+/// Result of [`Core::maintenance`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceSummary {
+    #[serde(flatten)]
+    pub database: MaintenanceReport,
+    pub orphaned_embeddings: u64,
+    pub orphaned_edge_sources: u64,
+}
+
+/// Result of [`Core::graph_stats`].
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphStats {
+    /// Counts by node type, relation and provider, and when each
+    /// provider's edges were last extracted.
+    #[serde(flatten)]
+    pub store: minna_graph::GraphStoreStats,
+    /// Node count per ring, as of the last ring calculation.
+    pub rings: Vec<(minna_graph::Ring, i64)>,
+    /// The most central people, by PageRank as of the last centrality pass.
+    pub central_people: Vec<minna_graph::GraphNode>,
+}
+
+/// Result of [`Core::reindex_embeddings`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReindexSummary {
+    pub model: String,
+    pub queued: u64,
+    pub reembedded: usize,
+}
+
+/// Result of [`Core::check_embedding_dims`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DimensionCheck {
+    pub model: String,
+    pub dims: usize,
+    /// Embedded documents whose vectors have another dimension than their
+    /// model produces.
+    pub mismatched: i64,
+    /// How many of those were queued for re-embedding.
+    pub queued: u64,
+    pub checked_at: DateTime<Utc>,
+}
+
 #[derive(Clone)]
 pub struct Core {
     pub ingest: IngestionEngine,
     pub vector: VectorStore,
     pub auth: TokenStore,
     pub embedder: Arc<dyn Embedder>,
+    /// Set when startup fell back to the hash embedder while the real model loads.
+    pub standby: Option<Arc<StandbyEmbedder>>,
+    /// Cross-encoder for the top of get_context results (`MINNA_RERANK=1`).
+    pub reranker: Option<Arc<dyn Reranker>>,
+    /// Writes cluster summaries (`MINNA_CLUSTER_SUMMARY_MODEL`).
+    pub summarizer: Option<Arc<dyn clusters::ClusterSummarizer>>,
+    /// The model behind `summarizer`, for other summaries (threads, PRs).
+    pub chat: Option<Arc<clusters::ChatSummarizer>>,
     pub graph: minna_graph::GraphStore,
+    /// What indexing, search and source cleanup go through; the stores
+    /// above unless [`Self::with_storage`] plugs in another backend.
+    storage: Storage,
+    /// The user's node and ring calculation settings (`[rings]`).
+    rings: RingsConfig,
+    /// Edge pruning before ring calculation (`[graph_pruning]`).
+    graph_pruning: minna_graph::PrunePolicy,
+    dimension_check: Arc<std::sync::RwLock<Option<DimensionCheck>>>,
 }
 
 impl Core {
     pub async fn init(paths: &MinnaPaths) -> Result<Self> {
         info!("Initializing Minna Core...");
         paths.ensure_dirs()?;
-        let ingest = IngestionEngine::new(&paths.db_path).await?;
-        let vector = VectorStore::new(&paths.db_path).await?;
+        let integrity_chain = std::env::var("MINNA_INTEGRITY_CHAIN")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
         let auth = TokenStore::load(&paths.auth_path)?;
-        let embedder = embedder_from_env_or_hash();
+        let key = database_key_for(&auth)?;
+        if key.is_some() {
+            info!("Opening encrypted database");
+        }
+        let ingest = IngestionEngine::open(&paths.db_path, key.as_deref())
+            .await?
+            .with_integrity_chain(integrity_chain);
+        if ingest.configure_fts(minna_ingest::fts::FtsTokenizer::from_env()).await? {
+            info!("Full-text index rebuilt with new tokenizer");
+        }
+        let vector = VectorStore::open(&paths.db_path, key.as_deref()).await?;
+        let standby_wait = std::env::var("MINNA_EMBED_STANDBY_WAIT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        let (embedder, standby) = if remote_backend() {
+            let remote = RemoteEmbedder::from_env(auth.embedding_api_key())?;
+            info!("Embedding with {} through the remote endpoint", remote.model_name());
+            (Arc::new(remote) as Arc<dyn Embedder>, None)
+        } else if ollama_backend() {
+            let ollama = OllamaEmbedder::from_env()?;
+            info!("Embedding with {} through Ollama", ollama.model_name());
+            (Arc::new(ollama) as Arc<dyn Embedder>, None)
+        } else {
+            embedder_with_standby(Duration::from_secs(standby_wait))
+        };
+        let reranker = match reranker_from_env() {
+            Ok(reranker) => reranker.map(|reranker| reranker as Arc<dyn Reranker>),
+            Err(err) => {
+                warn!("[RERANK] Reranking disabled: {}", err);
+                None
+            }
+        };
+        let chat = match clusters::ChatSummarizer::from_env(auth.summary_api_key()) {
+            Ok(Some(summarizer)) => {
+                info!("[CLUSTER] Summarizing clusters with {}", summarizer.model());
+                Some(Arc::new(summarizer))
+            }
+            Ok(None) => None,
+            Err(err) => {
+                warn!("[CLUSTER] Cluster summaries disabled: {}", err);
+                None
+            }
+        };
         // Initialize GraphStore using the same pool as ingest
         let graph = minna_graph::GraphStore::new(ingest.pool().clone());
         // Ensure graph schema is initialized
         minna_graph::GraphStore::init_schema(ingest.pool()).await?;
+        let storage = Storage::sqlite(&ingest, &vector, &graph);
         Ok(Self {
             ingest,
             vector,
             auth,
             embedder,
+            standby,
+            reranker,
+            summarizer: chat.clone().map(|chat| chat as Arc<dyn clusters::ClusterSummarizer>),
+            chat,
             graph,
+            storage,
+            rings: RingsConfig::default(),
+            graph_pruning: minna_graph::PrunePolicy::default(),
+            dimension_check: Arc::default(),
         })
     }
 
+    /// Redact documents matching `config` before storing or embedding them.
+    pub fn with_redaction(mut self, config: &RedactionConfig) -> Result<Self> {
+        if let Some(redactor) = Redactor::from_config(config)? {
+            info!("[REDACT] Redacting {}", redactor.rule_names().join(", "));
+            self.ingest = self.ingest.with_redactor(redactor);
+            self.storage.documents = Arc::new(self.ingest.clone());
+        }
+        Ok(self)
+    }
+
+    /// Clean documents with the transforms configured for their source
+    /// before storing or embedding them.
+    pub fn with_transforms(mut self, config: &TransformConfig) -> Result<Self> {
+        let chain = TransformChain::from_config(config)?;
+        if !chain.is_empty() {
+            info!("[TRANSFORM] Transforms configured for {} sources", config.len());
+            self.ingest = self.ingest.with_transforms(chain);
+            self.storage.documents = Arc::new(self.ingest.clone());
+        }
+        Ok(self)
+    }
+
+    /// Calculate rings with these settings, from the configured user node.
+    pub fn with_rings(mut self, config: &RingsConfig) -> Self {
+        self.rings = config.clone();
+        self
+    }
+
+    /// Index, search, re-embed and purge through `storage` instead of the
+    /// local SQLite stores; rings, clustering, bundles and maintenance keep
+    /// using the stores above. Apply after [`Self::with_redaction`] and
+    /// [`Self::with_transforms`], which reset the document store.
+    pub fn with_storage(mut self, storage: Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Prune the graph with this policy (see [`Core::prune_graph`]).
+    pub fn with_graph_pruning(mut self, policy: &minna_graph::PrunePolicy) -> Self {
+        self.graph_pruning = policy.clone();
+        self
+    }
+
+    /// Embed documents from the given sources with their own models. A model
+    /// that fails to load is skipped with a warning; its source keeps the
+    /// default model.
+    pub fn with_embedding_models(mut self, models: &HashMap<String, String>) -> Self {
+        if models.is_empty() {
+            return self;
+        }
+        let mut by_source: HashMap<String, Arc<dyn Embedder>> = HashMap::new();
+        for (source, model) in models {
+            match embedder_for_model(model, self.auth.embedding_api_key()) {
+                Ok(embedder) => {
+                    info!("[EMBED] Embedding {} with {}", source, embedder.model_name());
+                    by_source.insert(source.clone(), embedder);
+                }
+                Err(err) => warn!("[EMBED] Can't load {} for {}: {}", model, source, err),
+            }
+        }
+        if !by_source.is_empty() {
+            self.embedder = Arc::new(SourceEmbedders::new(self.embedder.clone(), by_source));
+        }
+        self
+    }
+
+    /// Model each configured source's documents are embedded with.
+    fn source_models(&self) -> HashMap<String, String> {
+        self.embedder
+            .source_embedders()
+            .into_iter()
+            .map(|(source, embedder)| (source, embedder.model_name()))
+            .collect()
+    }
+
+    /// Warn when documents are embedded with another model than the one now
+    /// configured for them. Skipped while the fallback embedder stands in.
+    pub async fn check_embedding_models(&self) -> Result<()> {
+        if self.embedder.is_fallback() {
+            return Ok(());
+        }
+        let stale = self
+            .vector
+            .count_stale(&self.embedder.model_name(), &self.source_models())
+            .await?;
+        if stale > 0 {
+            warn!(
+                "[EMBED] {} documents were embedded with another model than configured; run `minna reindex-embeddings`",
+                stale
+            );
+        }
+        Ok(())
+    }
+
+    /// Compare the dimension of every stored vector with what its model
+    /// produces now, queueing mismatched documents for re-embedding if
+    /// `reembed` is set. Skipped (None) while the fallback embedder stands in.
+    pub async fn check_embedding_dims(&self, reembed: bool) -> Result<Option<DimensionCheck>> {
+        if self.embedder.is_fallback() {
+            return Ok(None);
+        }
+        let dims = self.embedder.embed(DIMENSION_PROBE).await?.len();
+        let mut source_dims = HashMap::new();
+        for (source, embedder) in self.embedder.source_embedders() {
+            source_dims.insert(source, embedder.embed(DIMENSION_PROBE).await?.len());
+        }
+        let mismatched = self.vector.count_dimension_mismatches(dims, &source_dims).await?;
+        let mut queued = 0;
+        if mismatched > 0 {
+            if reembed {
+                queued = self.vector.queue_reembed_mismatched_dims(dims, &source_dims).await?;
+            }
+            warn!(
+                "[EMBED] {} documents have embeddings of another dimension than {} ({}); {} queued for re-embedding",
+                mismatched,
+                self.embedder.model_name(),
+                dims,
+                queued
+            );
+        }
+        let check = DimensionCheck {
+            model: self.embedder.model_name(),
+            dims,
+            mismatched,
+            queued,
+            checked_at: Utc::now(),
+        };
+        if let Ok(mut last) = self.dimension_check.write() {
+            *last = Some(check.clone());
+        }
+        Ok(Some(check))
+    }
+
+    /// The latest [`Self::check_embedding_dims`] result.
+    pub fn last_dimension_check(&self) -> Option<DimensionCheck> {
+        self.dimension_check.read().ok().and_then(|last| last.clone())
+    }
+
     pub fn auth_path(&self) -> Result<PathBuf> {
         Ok(self.auth.path().to_path_buf())
     }
 
     pub async fn index_document(&self, doc: Document) -> Result<i64> {
-        let id = self.ingest.upsert_document(&doc).await?;
-        let embedding = self.embedder.embed(&doc.body).await?;
-        self.vector.upsert_embedding(id, &embedding).await?;
-        Ok(id)
+        self.storage.index_document(self.embedder.as_ref(), &doc).await
+    }
+
+    /// Hybrid semantic and keyword search over the stored documents.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<StorageHit>> {
+        self.storage.search(self.embedder.as_ref(), query, limit).await
+    }
+
+    /// Re-embed documents that were indexed with the hash fallback.
+    ///
+    /// No-op until the standby embedder has been promoted to the real model.
+    /// Returns the number of documents re-embedded.
+    pub async fn reembed_pending(&self, batch_size: usize) -> Result<usize> {
+        if self.embedder.is_fallback() {
+            return Ok(0);
+        }
+        self.storage.reembed_pending(self.embedder.as_ref(), batch_size).await
+    }
+
+    /// Re-embed documents with the current models: those embedded with
+    /// another model than configured for their source, or every document if
+    /// `all` is set.
+    pub async fn reindex_embeddings(&self, all: bool) -> Result<ReindexSummary> {
+        if self.embedder.is_fallback() {
+            return Err(anyhow::anyhow!("embedding model is still loading, try again shortly"));
+        }
+        let model = self.embedder.model_name();
+        let queued = if all {
+            self.vector.queue_reembed_all().await?
+        } else {
+            let stale = self
+                .vector
+                .queue_reembed_stale(&model, &self.source_models())
+                .await?;
+            let mismatched = self
+                .check_embedding_dims(true)
+                .await?
+                .map_or(0, |check| check.queued);
+            stale + mismatched
+        };
+        info!("[EMBED] Re-embedding {} documents with {}", queued, model);
+        let reembedded = self.reembed_pending(64).await?;
+        Ok(ReindexSummary {
+            model,
+            queued,
+            reembedded,
+        })
     }
 
     pub async fn run_clustering(
@@ -120,7 +482,31 @@ impl Core {
         min_similarity: f32,
         min_points: usize,
     ) -> Result<Vec<Cluster>> {
-        let clusters = self.vector.cluster_documents(min_similarity, min_points).await?;
+        let mut clusters = self.vector.cluster_documents(min_similarity, min_points).await?;
+        let mut texts = Vec::with_capacity(clusters.len());
+        for cluster in &clusters {
+            let mut cluster_texts = Vec::with_capacity(cluster.doc_ids.len());
+            for ids in cluster.doc_ids.chunks(500) {
+                let docs = self.ingest.fetch_documents_by_ids(ids).await?;
+                cluster_texts.extend(
+                    docs.iter()
+                        .map(|doc| clusters::label_text(doc.title.as_deref(), &doc.body)),
+                );
+            }
+            texts.push(cluster_texts);
+        }
+        for (cluster, label) in clusters.iter_mut().zip(clusters::keyword_labels(&texts)) {
+            cluster.label = label;
+        }
+        if let Some(summarizer) = &self.summarizer {
+            for (cluster, texts) in clusters.iter_mut().zip(&texts) {
+                let excerpts = clusters::summary_excerpts(texts);
+                match summarizer.summarize(&cluster.label, &excerpts).await {
+                    Ok(summary) => cluster.summary = Some(summary),
+                    Err(err) => warn!("[CLUSTER] Failed to summarize {}: {}", cluster.label, err),
+                }
+            }
+        }
         let records = clusters
             .iter()
             .map(|cluster| minna_ingest::ClusterRecord {
@@ -128,21 +514,226 @@ impl Core {
                 label: cluster.label.clone(),
                 doc_ids: cluster.doc_ids.clone(),
                 created_at: Utc::now(),
+                summary: cluster.summary.clone(),
             })
             .collect::<Vec<_>>();
         self.ingest.store_clusters(&records).await?;
         Ok(clusters)
     }
 
+    /// Add documents embedded since `since` to `clusters` from an earlier
+    /// [`Self::run_clustering`] and drop purged members, overwriting the
+    /// stored rows of the clusters that changed. Returns how many documents
+    /// were assigned.
+    pub async fn update_clusters(
+        &self,
+        clusters: &mut [Cluster],
+        since: DateTime<Utc>,
+        min_similarity: f32,
+    ) -> Result<usize> {
+        let before: HashSet<i64> = clusters
+            .iter()
+            .flat_map(|cluster| cluster.doc_ids.iter().copied())
+            .collect();
+        let changed = self
+            .vector
+            .assign_to_clusters(clusters, since, min_similarity)
+            .await?;
+        let mut records = Vec::with_capacity(changed.len());
+        for idx in changed {
+            let cluster = &clusters[idx];
+            let stored = self.ingest.get_cluster(&cluster.label).await?;
+            records.push(minna_ingest::ClusterRecord {
+                id: stored.as_ref().and_then(|stored| stored.id),
+                label: cluster.label.clone(),
+                doc_ids: cluster.doc_ids.clone(),
+                created_at: stored.map(|stored| stored.created_at).unwrap_or_else(Utc::now),
+                summary: cluster.summary.clone(),
+            });
+        }
+        self.ingest.store_clusters(&records).await?;
+        Ok(clusters
+            .iter()
+            .flat_map(|cluster| &cluster.doc_ids)
+            .filter(|doc_id| !before.contains(doc_id))
+            .count())
+    }
+
     pub async fn reset_provider(&self, provider_id: &str) -> Result<()> {
-        info!("Resetting provider: {}", provider_id);
-        // 1. Delete sync cursor (prevents delta sync)
-        self.ingest.set_sync_cursor(provider_id, "").await?;
-        // 2. Delete documents from this provider
-        self.ingest.delete_documents_by_source(provider_id).await?;
-        // 3. Scrub orphaned embeddings
-        self.vector.scrub_orphaned_embeddings().await?;
-        Ok(())
+        self.storage.reset_provider(provider_id).await
+    }
+
+    /// Clean up after soft-deleted documents: drop their embeddings and edge
+    /// provenance, drop graph edges of sources with no documents left, then
+    /// purge the rows and tombstones. Returns the number of documents purged.
+    pub async fn purge_tombstones(&self, batch_size: usize) -> Result<usize> {
+        self.storage.purge_tombstones(batch_size).await
+    }
+
+    /// Evict the oldest-updated documents of each source over its limit.
+    /// Documents involving the user (sources of edges touching the Core
+    /// ring) are never evicted. Returns the number evicted.
+    pub async fn enforce_source_limits(&self, limits: &HashMap<String, SourceLimit>) -> Result<usize> {
+        self.storage.enforce_source_limits(limits).await
+    }
+
+    /// Drop orphaned embeddings and edge provenance, then run database
+    /// maintenance (integrity checks, FTS rebuild, vacuum) as selected.
+    pub async fn maintenance(&self, options: &MaintenanceOptions) -> Result<MaintenanceSummary> {
+        let orphaned_embeddings = self.vector.maintenance().await?;
+        let orphaned_edge_sources = self.graph.maintenance().await?;
+        let database = self.ingest.maintenance(options).await?;
+        Ok(MaintenanceSummary {
+            database,
+            orphaned_embeddings,
+            orphaned_edge_sources,
+        })
+    }
+
+    /// The nodes rings are measured from: `[rings] user` and `identities`
+    /// if set, otherwise the nodes the last calculation placed in the Core
+    /// ring, each with the accounts linked to it as the same person. Empty
+    /// until either exists.
+    pub async fn ring_user_nodes(&self) -> Result<Vec<String>> {
+        let mut users: Vec<String> = self.rings.user.iter().chain(&self.rings.identities).cloned().collect();
+        if users.is_empty() {
+            users = self.graph.nodes_in_ring(minna_graph::Ring::Core).await?;
+        }
+        let mut nodes = BTreeSet::new();
+        for user in users {
+            nodes.extend(self.graph.linked_identities(&user).await?);
+        }
+        Ok(nodes.into_iter().collect())
+    }
+
+    /// Archive or delete stale edges and compact edge sources per the
+    /// `[graph_pruning]` policy.
+    pub async fn prune_graph(&self) -> Result<minna_graph::PruneReport> {
+        self.graph.prune(&self.graph_pruning).await
+    }
+
+    /// Recompute every node's degree and PageRank. Ring calculation uses
+    /// PageRank to break ties, so this runs before it.
+    pub async fn recalculate_centrality(&self) -> Result<usize> {
+        minna_graph::RingEngine::with_config(self.rings.engine.clone())
+            .recalculate_centrality(&self.graph)
+            .await
+    }
+
+    /// Size of the graph, what each provider contributed, its ring
+    /// distribution and the `limit` most central people.
+    pub async fn graph_stats(&self, limit: usize) -> Result<GraphStats> {
+        Ok(GraphStats {
+            store: self.graph.stats().await?,
+            rings: self.graph.ring_distribution().await?,
+            central_people: self.graph.most_central(Some(minna_graph::NodeType::User), limit).await?,
+        })
+    }
+
+    /// The `limit` people the user interacted with most in the last `days`,
+    /// grouped by ring (see [`minna_graph::RingEngine::collaborators`]).
+    pub async fn collaborators(&self, limit: usize, days: i64) -> Result<Vec<minna_graph::CollaboratorGroup>> {
+        let me = self.ring_user_nodes().await?;
+        if me.is_empty() {
+            anyhow::bail!("No user node yet; set [rings] user in providers.toml");
+        }
+        minna_graph::RingEngine::with_config(self.rings.engine.clone())
+            .collaborators(&self.graph, &me, Utc::now() - chrono::Duration::days(days), limit)
+            .await
+    }
+
+    /// Recalculate Gravity Well rings from the user's nodes and broadcast each
+    /// ring change as a typed ring change event (node, old and new ring, and
+    /// why it moved), so the app and MCP clients can react without polling.
+    /// The net changes are also emitted as `rings` progress events with a
+    /// readable message, and together as one `ring_diff` result event.
+    pub async fn recalculate_rings(
+        &self,
+        user_node_ids: &[String],
+    ) -> Result<minna_graph::RecalculationResult> {
+        let result = minna_graph::RingEngine::with_config(self.rings.engine.clone())
+            .recalculate_rings_from(&self.graph, user_node_ids)
+            .await?;
+        for transition in &result.transitions {
+            let node = self.graph.get_node(&transition.node_id).await?;
+            let change = minna_graph::RingChange {
+                node_id: transition.node_id.clone(),
+                display_name: node.as_ref().and_then(|node| node.display_name.clone()),
+                avatar_url: node.and_then(|node| node.avatar_url),
+                from: transition.from,
+                to: transition.to,
+                changed_at: transition.transitioned_at,
+            };
+            emit_ring_change(progress::RingChangeEvent {
+                message: change.describe(),
+                node_id: change.node_id,
+                display_name: change.display_name,
+                avatar_url: change.avatar_url,
+                from: change.from,
+                to: change.to,
+                reason: transition.reason,
+            });
+        }
+        let diff = self.graph.name_ring_diff(result.diff()).await?;
+        for change in diff.changes() {
+            let status = if change.to.is_closer_than(change.from) { "promoted" } else { "demoted" };
+            emit_progress("rings", status, &change.describe(), None);
+        }
+        if !diff.is_empty() {
+            emit_result("ring_diff", "complete", serde_json::to_value(&diff)?);
+        }
+        Ok(result)
+    }
+
+    /// Who moved between rings in the last `days`, with display names, for
+    /// "Alice moved into your Core ring this week".
+    pub async fn ring_changes(&self, days: i64) -> Result<minna_graph::RingDiff> {
+        self.graph.ring_diff_since(Utc::now() - chrono::Duration::days(days)).await
+    }
+
+    /// How the rings would look from only the last `days` of activity,
+    /// compared with the saved rings: who the user works with now versus
+    /// across all history. Nothing is saved.
+    pub async fn recent_rings(&self, days: i64) -> Result<minna_graph::RingDiff> {
+        use minna_graph::Ring;
+
+        let me = self.ring_user_nodes().await?;
+        if me.is_empty() {
+            anyhow::bail!("No user node yet; set [rings] user in providers.toml");
+        }
+        let mut recent: HashMap<String, Ring> = minna_graph::RingEngine::with_config(self.rings.engine.clone())
+            .rings_in(&self.graph, &me, minna_graph::TimeRange::last_days(days))
+            .await?
+            .into_iter()
+            .map(|assignment| (assignment.node_id, assignment.ring))
+            .collect();
+        let now = Utc::now();
+        let change = |node_id: String, from: Ring, to: Ring| minna_graph::RingChange {
+            node_id,
+            display_name: None,
+            avatar_url: None,
+            from,
+            to,
+            changed_at: now,
+        };
+        let mut changes = Vec::new();
+        for ring in [Ring::Core, Ring::One, Ring::Two, Ring::Beyond] {
+            for node_id in self.graph.nodes_in_ring(ring).await? {
+                // Not reached from recent edges at all
+                let to = recent.remove(&node_id).unwrap_or(Ring::Beyond);
+                if to != ring {
+                    changes.push(change(node_id, ring, to));
+                }
+            }
+        }
+        changes.extend(
+            recent
+                .into_iter()
+                .filter(|(_, ring)| *ring != Ring::Beyond)
+                .map(|(node_id, ring)| change(node_id, Ring::Beyond, ring)),
+        );
+        let (promoted, demoted) = changes.into_iter().partition(|change| change.to.is_closer_than(change.from));
+        self.graph.name_ring_diff(minna_graph::RingDiff { promoted, demoted }).await
     }
 
     /// Sync a provider using the extensible provider registry.
@@ -176,12 +767,20 @@ impl Core {
             vector: &self.vector,
             embedder: &self.embedder,
             http_client: &http_client,
-            registry,
+            host: registry,
             graph: &graph,
             auth_path,
         };
 
-        provider.sync(&ctx, since_days, mode).await
+        let summary = provider.sync(&ctx, since_days, mode).await?;
+        self.enforce_source_limits(registry.source_limits()).await?;
+        // Link the accounts this sync shares an email address with
+        match minna_graph::IdentityService::auto_link_by_email(&graph).await {
+            Ok(0) => {}
+            Ok(linked) => info!("[IDENTITY] Linked {} accounts by email", linked),
+            Err(err) => warn!("[IDENTITY] Email matching failed: {}", err),
+        }
+        Ok(summary)
     }
 
     /// Discover resources for a provider using the extensible registry.
@@ -206,7 +805,7 @@ impl Core {
             vector: &self.vector,
             embedder: &self.embedder,
             http_client: &http_client,
-            registry,
+            host: registry,
             graph: &graph,
             auth_path,
         };
@@ -1949,14 +2548,6 @@ impl Core {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SyncSummary {
-    pub provider: String,
-    pub items_scanned: usize,
-    pub documents_processed: usize,
-    pub updated_at: String,
-}
-
 #[derive(Debug, Clone, Deserialize)]
 struct GithubRepo {
     name: String,
@@ -10,7 +10,10 @@ use tracing::info;
 
 use crate::Document;
 use crate::progress::emit_progress;
-use super::{SyncContext, SyncProvider, SyncSummary, call_with_backoff, calculate_since};
+use super::{
+    call_with_backoff, calculate_since, EdgeSet, NodeRef, NodeType, Relation, SyncContext,
+    SyncProvider, SyncSummary,
+};
 
 /// Atlassian provider for syncing Jira issues and Confluence pages.
 pub struct AtlassianProvider;
@@ -32,7 +35,7 @@ impl SyncProvider for AtlassianProvider {
         mode: Option<&str>,
     ) -> Result<SyncSummary> {
         // Parse Basic Auth credentials (email:token)
-        let (email, token) = ctx.registry.parse_basic_auth("atlassian")?;
+        let (email, token) = ctx.host.parse_basic_auth("atlassian")?;
 
         // Get cloud ID (required for API calls)
         let cloud_id = self.get_cloud_id(ctx, &email, &token).await?;
@@ -59,7 +62,7 @@ impl SyncProvider for AtlassianProvider {
     }
 
     async fn discover(&self, ctx: &SyncContext<'_>) -> Result<serde_json::Value> {
-        let (email, token) = ctx.registry.parse_basic_auth("atlassian")?;
+        let (email, token) = ctx.host.parse_basic_auth("atlassian")?;
 
         // Get accessible resources
         let resources = self.get_accessible_resources(ctx, &email, &token).await?;
@@ -175,17 +178,23 @@ impl AtlassianProvider {
                     .unwrap_or_default();
 
                 // Build document
+                let updated_at = parse_atlassian_timestamp(&issue.fields.updated)
+                    .unwrap_or_else(Utc::now);
                 let doc = Document {
                     id: None,
                     uri: browse_url.clone(),
                     source: "jira".to_string(),
                     title: Some(format!("{}: {}", issue.key, issue.fields.summary)),
                     body: self.format_jira_body(issue, &description, &browse_url),
-                    updated_at: parse_atlassian_timestamp(&issue.fields.updated)
-                        .unwrap_or_else(Utc::now),
+                    updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
+                if let Some(project) = &issue.fields.project {
+                    ctx.add_tags(doc_id, &[&project.key]).await?;
+                }
+                let edges = self.extract_jira_edges(issue, updated_at);
+                ctx.index_document_edges(doc_id, &edges.into_edges()).await?;
                 documents_processed += 1;
 
                 if documents_processed % 10 == 0 {
@@ -302,16 +311,22 @@ impl AtlassianProvider {
                     .unwrap_or_default();
 
                 // Build document
+                let updated_at = updated.unwrap_or_else(Utc::now);
                 let doc = Document {
                     id: None,
                     uri: page_url.clone(),
                     source: "confluence".to_string(),
                     title: Some(page.title.clone()),
                     body: self.format_confluence_body(page, &content, &page_url),
-                    updated_at: updated.unwrap_or_else(Utc::now),
+                    updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
+                if let Some(space) = &page.space {
+                    ctx.add_tags(doc_id, &[&space.key]).await?;
+                }
+                let edges = self.extract_confluence_edges(page, updated_at);
+                ctx.index_document_edges(doc_id, &edges.into_edges()).await?;
                 documents_processed += 1;
 
                 if documents_processed % 10 == 0 {
@@ -340,6 +355,51 @@ impl AtlassianProvider {
         })
     }
 
+    /// Edges for a Jira issue: its project, assignee and reporter. Users are
+    /// keyed by Atlassian account ID, shared with Confluence.
+    fn extract_jira_edges(&self, issue: &JiraIssue, observed_at: DateTime<Utc>) -> EdgeSet {
+        let mut edges = EdgeSet::new(observed_at);
+        let issue_node =
+            NodeRef::with_name(NodeType::Issue, "jira", &issue.key, &issue.fields.summary);
+        if let Some(project) = &issue.fields.project {
+            let project_node =
+                NodeRef::with_name(NodeType::Project, "jira", &project.key, &project.name);
+            edges.add(issue_node.clone(), project_node, Relation::BelongsTo);
+        }
+        edges.add_opt(
+            issue.fields.assignee.as_ref().and_then(JiraUser::node),
+            issue_node.clone(),
+            Relation::AssignedTo,
+        );
+        edges.add_opt(
+            issue.fields.reporter.as_ref().and_then(JiraUser::node),
+            issue_node,
+            Relation::AuthorOf,
+        );
+        edges
+    }
+
+    /// Edges for a Confluence page: its space and its last editor.
+    fn extract_confluence_edges(
+        &self,
+        page: &ConfluencePage,
+        observed_at: DateTime<Utc>,
+    ) -> EdgeSet {
+        let mut edges = EdgeSet::new(observed_at);
+        let page_node = NodeRef::with_name(NodeType::Document, "confluence", &page.id, &page.title);
+        if let Some(space) = &page.space {
+            let space_node =
+                NodeRef::with_name(NodeType::Project, "confluence", &space.key, &space.name);
+            edges.add(page_node.clone(), space_node, Relation::BelongsTo);
+        }
+        edges.add_opt(
+            page.version.as_ref().and_then(|v| v.by.as_ref()).and_then(JiraUser::node),
+            page_node,
+            Relation::AuthorOf,
+        );
+        edges
+    }
+
     /// Convert Atlassian Document Format (ADF) to plain text.
     fn adf_to_text(&self, adf: &serde_json::Value) -> String {
         let mut text = String::new();
@@ -563,10 +623,20 @@ struct JiraStatus {
     name: String,
 }
 
+/// An Atlassian account, as returned by both Jira and Confluence.
 #[derive(Debug, Deserialize)]
 struct JiraUser {
     #[serde(rename = "displayName")]
     display_name: String,
+    #[serde(default, rename = "accountId")]
+    account_id: Option<String>,
+}
+
+impl JiraUser {
+    fn node(&self) -> Option<NodeRef> {
+        let account_id = self.account_id.as_deref()?;
+        Some(NodeRef::with_name(NodeType::User, "atlassian", account_id, &self.display_name))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -638,6 +708,8 @@ struct ConfluenceStorage {
 #[derive(Debug, Deserialize)]
 struct ConfluenceVersion {
     when: String,
+    #[serde(default)]
+    by: Option<JiraUser>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,16 +1,18 @@
 //! GitHub provider implementation.
 //!
 //! Syncs pull requests and issues from GitHub, extracting relationship edges for Gravity Well.
+//! Each repo's CODEOWNERS file adds ownership edges for its paths.
 
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::Document;
 use crate::progress::emit_progress;
 use minna_auth_bridge::TokenStore;
+use minna_graph::extractors::codeowners::{CodeownersExtractor, CODEOWNERS_PATHS};
 
 use super::{
     call_with_backoff, calculate_since, ExtractedEdge, NodeRef, NodeType, Relation,
@@ -79,6 +81,19 @@ impl SyncProvider for GithubProvider {
         let mut repos_scanned = 0usize;
 
         for repo in repos.into_iter().take(repo_limit) {
+            let full_name = format!("{}/{}", repo.owner.login, repo.name);
+            let skip = ctx
+                .track_archived(
+                    "github",
+                    &full_name,
+                    Some(&full_name),
+                    &format!("https://github.com/{}/", full_name),
+                    repo.archived,
+                )
+                .await?;
+            if skip {
+                continue;
+            }
             repos_scanned += 1;
 
             // Fetch issues/PRs for this repo
@@ -117,13 +132,14 @@ impl SyncProvider for GithubProvider {
                     updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
+                ctx.add_tags(doc_id, &[&repo.name]).await?;
                 docs_indexed += 1;
 
                 // Extract and store edges
                 let edges = self.extract_edges_from_issue(&repo, &issue, updated_at);
                 if !edges.is_empty() {
-                    ctx.index_edges(&edges).await?;
+                    ctx.index_document_edges(doc_id, &edges).await?;
                     edges_extracted += edges.len();
                 }
 
@@ -136,6 +152,17 @@ impl SyncProvider for GithubProvider {
                     );
                 }
             }
+
+            match self.sync_codeowners(ctx, &token.access_token, &repo).await {
+                Ok(count) => edges_extracted += count,
+                Err(err) => warn!("GitHub CODEOWNERS unavailable for {}: {}", full_name, err),
+            }
+        }
+
+        // Team membership needs the read:org scope; without it, skip
+        match self.sync_teams(ctx, &token.access_token).await {
+            Ok(count) => edges_extracted += count,
+            Err(err) => warn!("GitHub teams unavailable: {}", err),
         }
 
         // Update sync cursor
@@ -220,6 +247,83 @@ impl GithubProvider {
         Ok(repos)
     }
 
+    /// Record membership of the user's teams as `MemberOf` edges to `Team`
+    /// nodes keyed by `org/slug`.
+    async fn sync_teams(&self, ctx: &SyncContext<'_>, access_token: &str) -> Result<usize> {
+        let teams: Vec<GithubTeam> = self
+            .fetch_pages(ctx, access_token, "https://api.github.com/user/teams")
+            .await?;
+        let now = Utc::now();
+        let mut edges = Vec::new();
+        for team in teams {
+            let url = format!(
+                "https://api.github.com/orgs/{}/teams/{}/members",
+                team.organization.login, team.slug
+            );
+            let members: Vec<GithubUser> = self.fetch_pages(ctx, access_token, &url).await?;
+            edges.extend(self.extract_team_edges(&team, &members, now));
+        }
+        ctx.index_edges(&edges).await
+    }
+
+    /// Extract `OwnerOf` edges from the repo's CODEOWNERS file, if any.
+    async fn sync_codeowners(
+        &self,
+        ctx: &SyncContext<'_>,
+        access_token: &str,
+        repo: &GithubRepo,
+    ) -> Result<usize> {
+        let full_name = format!("{}/{}", repo.owner.login, repo.name);
+        for path in CODEOWNERS_PATHS {
+            let url = format!("https://api.github.com/repos/{}/contents/{}", full_name, path);
+            let response = ctx
+                .http_client
+                .get(&url)
+                .header("Authorization", format!("token {}", access_token))
+                .header("Accept", "application/vnd.github.raw")
+                .send()
+                .await?;
+            if response.status().as_u16() == 404 {
+                continue;
+            }
+            let content = response.error_for_status()?.text().await?;
+            let edges = CodeownersExtractor::new("github").extract_from_content(
+                &full_name,
+                &content,
+                Utc::now(),
+            );
+            return ctx.index_edges(&edges).await;
+        }
+        Ok(0)
+    }
+
+    /// Fetch every page of a list endpoint.
+    async fn fetch_pages<T: serde::de::DeserializeOwned>(
+        &self,
+        ctx: &SyncContext<'_>,
+        access_token: &str,
+        url: &str,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut page = 1;
+        loop {
+            let page_url = format!("{}?per_page=100&page={}", url, page);
+            let response = call_with_backoff("github", || {
+                ctx.http_client
+                    .get(&page_url)
+                    .header("Authorization", format!("token {}", access_token))
+            })
+            .await?;
+            let batch: Vec<T> = response.json().await?;
+            let done = batch.len() < 100;
+            items.extend(batch);
+            if done {
+                return Ok(items);
+            }
+            page += 1;
+        }
+    }
+
     /// Fetch issues/PRs for a repository.
     async fn fetch_issues(
         &self,
@@ -281,21 +385,25 @@ impl GithubProvider {
         // Edge: Issue/PR → Repo (BelongsTo)
         edges.push(ExtractedEdge::new(
             issue_node.clone(),
-            repo_node,
+            repo_node.clone(),
             Relation::BelongsTo,
             observed_at,
         ));
 
+        // Edge: Owner → Repo (OwnerOf) - personal repos only, not orgs
+        if repo.owner.kind.as_deref() == Some("User") {
+            edges.push(ExtractedEdge::new(
+                repo.owner.node(),
+                repo_node,
+                Relation::OwnerOf,
+                observed_at,
+            ));
+        }
+
         // Edge: Author → Issue/PR (AuthorOf)
         if let Some(ref user) = issue.user {
-            let user_node = NodeRef::with_name(
-                NodeType::User,
-                "github",
-                &user.login,
-                &user.login,
-            );
             edges.push(ExtractedEdge::new(
-                user_node,
+                user.node(),
                 issue_node.clone(),
                 Relation::AuthorOf,
                 observed_at,
@@ -305,14 +413,8 @@ impl GithubProvider {
         // Edge: Assignees → Issue/PR (AssignedTo)
         if let Some(ref assignees) = issue.assignees {
             for assignee in assignees {
-                let user_node = NodeRef::with_name(
-                    NodeType::User,
-                    "github",
-                    &assignee.login,
-                    &assignee.login,
-                );
                 edges.push(ExtractedEdge::new(
-                    user_node,
+                    assignee.node(),
                     issue_node.clone(),
                     Relation::AssignedTo,
                     observed_at,
@@ -324,14 +426,8 @@ impl GithubProvider {
         if is_pr {
             if let Some(ref reviewers) = issue.requested_reviewers {
                 for reviewer in reviewers {
-                    let user_node = NodeRef::with_name(
-                        NodeType::User,
-                        "github",
-                        &reviewer.login,
-                        &reviewer.login,
-                    );
                     edges.push(ExtractedEdge::new(
-                        user_node,
+                        reviewer.node(),
                         issue_node.clone(),
                         Relation::ReviewerOf,
                         observed_at,
@@ -342,6 +438,32 @@ impl GithubProvider {
 
         edges
     }
+
+    /// Extract `MemberOf` edges from a team's members.
+    fn extract_team_edges(
+        &self,
+        team: &GithubTeam,
+        members: &[GithubUser],
+        observed_at: DateTime<Utc>,
+    ) -> Vec<ExtractedEdge> {
+        let team_node = NodeRef::with_name(
+            NodeType::Team,
+            "github",
+            format!("{}/{}", team.organization.login, team.slug),
+            &team.name,
+        );
+        members
+            .iter()
+            .map(|member| {
+                ExtractedEdge::new(
+                    member.node(),
+                    team_node.clone(),
+                    Relation::MemberOf,
+                    observed_at,
+                )
+            })
+            .collect()
+    }
 }
 
 // --- GitHub API Response Types ---
@@ -353,11 +475,25 @@ struct GithubRepo {
     #[allow(dead_code)]
     #[serde(default)]
     private: Option<bool>,
+    #[serde(default)]
+    archived: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct GithubOwner {
     login: String,
+    /// "User" or "Organization"
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    #[serde(default)]
+    avatar_url: Option<String>,
+}
+
+impl GithubOwner {
+    /// User node for a personal repo's owner.
+    fn node(&self) -> NodeRef {
+        user_node(&self.login, self.avatar_url.as_deref())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -377,6 +513,30 @@ struct GithubIssue {
 #[derive(Debug, Clone, Deserialize)]
 struct GithubUser {
     login: String,
+    #[serde(default)]
+    avatar_url: Option<String>,
+}
+
+impl GithubUser {
+    fn node(&self) -> NodeRef {
+        user_node(&self.login, self.avatar_url.as_deref())
+    }
+}
+
+/// User node named by login, with the account's avatar if known.
+fn user_node(login: &str, avatar_url: Option<&str>) -> NodeRef {
+    let node = NodeRef::with_name(NodeType::User, "github", login, login);
+    match avatar_url {
+        Some(url) => node.with_avatar(url),
+        None => node,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GithubTeam {
+    slug: String,
+    name: String,
+    organization: GithubOwner,
 }
 
 #[cfg(test)]
@@ -391,8 +551,11 @@ mod tests {
             name: "minna-core".to_string(),
             owner: GithubOwner {
                 login: "getminna".to_string(),
+                kind: Some("Organization".to_string()),
+                avatar_url: None,
             },
             private: Some(false),
+            archived: false,
         };
 
         let issue = GithubIssue {
@@ -404,19 +567,23 @@ mod tests {
             state: Some("open".to_string()),
             user: Some(GithubUser {
                 login: "alice".to_string(),
+                avatar_url: Some("https://avatars.githubusercontent.com/u/1".to_string()),
             }),
             assignees: Some(vec![GithubUser {
                 login: "bob".to_string(),
+                avatar_url: None,
             }]),
             requested_reviewers: Some(vec![GithubUser {
                 login: "charlie".to_string(),
+                avatar_url: None,
             }]),
             pull_request: Some(serde_json::json!({})),
         };
 
         let edges = provider.extract_edges_from_issue(&repo, &issue, Utc::now());
 
-        // Should have: BelongsTo, AuthorOf, AssignedTo, ReviewerOf
+        // Should have: BelongsTo, AuthorOf, AssignedTo, ReviewerOf (no
+        // OwnerOf: the repo belongs to an organization)
         assert_eq!(edges.len(), 4);
 
         // Check BelongsTo
@@ -427,6 +594,10 @@ mod tests {
         let author_of = edges.iter().find(|e| e.relation == Relation::AuthorOf);
         assert!(author_of.is_some());
         assert_eq!(author_of.unwrap().from.external_id, "alice");
+        assert_eq!(
+            author_of.unwrap().from.avatar_url.as_deref(),
+            Some("https://avatars.githubusercontent.com/u/1")
+        );
 
         // Check AssignedTo
         let assigned_to = edges.iter().find(|e| e.relation == Relation::AssignedTo);
@@ -437,5 +608,39 @@ mod tests {
         let reviewer_of = edges.iter().find(|e| e.relation == Relation::ReviewerOf);
         assert!(reviewer_of.is_some());
         assert_eq!(reviewer_of.unwrap().from.external_id, "charlie");
+
+        // A personal repo is owned by its user
+        let mut repo = repo;
+        repo.owner = GithubOwner {
+            login: "alice".to_string(),
+            kind: Some("User".to_string()),
+            avatar_url: None,
+        };
+        let edges = provider.extract_edges_from_issue(&repo, &issue, Utc::now());
+        let owner_of = edges.iter().find(|e| e.relation == Relation::OwnerOf).unwrap();
+        assert_eq!(owner_of.from.external_id, "alice");
+        assert_eq!(owner_of.to.external_id, "alice/minna-core");
+    }
+
+    #[test]
+    fn test_extract_team_edges() {
+        let team = GithubTeam {
+            slug: "core".to_string(),
+            name: "Core".to_string(),
+            organization: GithubOwner {
+                login: "getminna".to_string(),
+                kind: None,
+                avatar_url: None,
+            },
+        };
+        let members = vec![
+            GithubUser { login: "alice".to_string(), avatar_url: None },
+            GithubUser { login: "bob".to_string(), avatar_url: None },
+        ];
+        let edges = GithubProvider.extract_team_edges(&team, &members, Utc::now());
+        assert_eq!(edges.len(), 2);
+        assert!(edges.iter().all(|e| e.relation == Relation::MemberOf));
+        assert_eq!(edges[0].to.canonical_id(), "team:github:getminna/core");
+        assert_eq!(edges[1].from.external_id, "bob");
     }
 }
@@ -7,11 +7,13 @@ use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::Document;
+use minna_ingest::extractors;
 use crate::progress::emit_progress;
 use minna_auth_bridge::TokenStore;
+use minna_graph::{Meeting, MeetingAttendee, MeetingExtractor};
 
 use super::{
     call_google_api, ExtractedEdge, NodeRef, NodeType, Relation,
@@ -122,7 +124,7 @@ impl GoogleProvider {
 
             let mut query_params: Vec<(&str, String)> = vec![
                 ("q", query),
-                ("fields", "files(id,name,mimeType,modifiedTime,webViewLink,owners,sharingUser),nextPageToken".to_string()),
+                ("fields", "files(id,name,mimeType,size,modifiedTime,webViewLink,owners,sharingUser),nextPageToken".to_string()),
                 ("pageSize", "100".to_string()),
             ];
 
@@ -154,28 +156,34 @@ impl GoogleProvider {
                         .map(|dt| dt.with_timezone(&Utc))
                         .unwrap_or_else(Utc::now);
 
+                    let mut body = format!(
+                        "# {}\n\n- Type: {}\n- Modified: {}\n- URL: {}",
+                        file.name,
+                        file.mime_type.as_deref().unwrap_or("unknown"),
+                        updated_at.to_rfc3339(),
+                        file.web_view_link.as_deref().unwrap_or("N/A")
+                    );
+                    if let Some(text) = self.fetch_drive_text(ctx, &mut current_token, &file).await {
+                        body.push_str("\n\n");
+                        body.push_str(&text);
+                    }
+
                     let doc = Document {
                         id: None,
                         uri: file.web_view_link.clone().unwrap_or_else(|| format!("drive://{}", file.id)),
                         source: "google_drive".to_string(),
                         title: Some(file.name.clone()),
-                        body: format!(
-                            "# {}\n\n- Type: {}\n- Modified: {}\n- URL: {}",
-                            file.name,
-                            file.mime_type.as_deref().unwrap_or("unknown"),
-                            updated_at.to_rfc3339(),
-                            file.web_view_link.as_deref().unwrap_or("N/A")
-                        ),
+                        body,
                         updated_at,
                     };
 
-                    ctx.index_document(doc).await?;
+                    let doc_id = ctx.index_document(doc).await?;
                     docs_indexed += 1;
 
                     // Extract edges
                     let edges = self.extract_drive_edges(&file, &user_email, updated_at);
                     if !edges.is_empty() {
-                        ctx.index_edges(&edges).await?;
+                        ctx.index_document_edges(doc_id, &edges).await?;
                         edges_extracted += edges.len();
                     }
                 }
@@ -193,6 +201,66 @@ impl GoogleProvider {
         Ok((docs_indexed, edges_extracted, docs_indexed))
     }
 
+    /// Download a Drive file and extract its text, if the format is supported.
+    ///
+    /// Failures are logged and the file falls back to a metadata-only body.
+    async fn fetch_drive_text(
+        &self,
+        ctx: &SyncContext<'_>,
+        current_token: &mut String,
+        file: &DriveFile,
+    ) -> Option<String> {
+        let mime_type = file.mime_type.as_deref()?;
+        if !extractors::is_supported(mime_type) {
+            return None;
+        }
+        let size = file.size.as_deref().and_then(|s| s.parse::<usize>().ok());
+        if size.is_some_and(|s| s > extractors::MAX_EXTRACT_BYTES) {
+            info!("Drive: skipping extraction for {} ({} bytes)", file.name, size.unwrap_or(0));
+            return None;
+        }
+
+        let url = format!("https://www.googleapis.com/drive/v3/files/{}", file.id);
+        let api_result = call_google_api("google_drive", ctx.http_client, current_token, |token| {
+            ctx.http_client
+                .get(&url)
+                .query(&[("alt", "media")])
+                .bearer_auth(token)
+        })
+        .await;
+        let bytes = match api_result {
+            Ok(result) => {
+                *current_token = result.token;
+                match result.response.bytes().await {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        warn!("Drive: failed to download {}: {}", file.name, err);
+                        return None;
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Drive: failed to download {}: {}", file.name, err);
+                return None;
+            }
+        };
+
+        let mime_type = mime_type.to_string();
+        let extracted = tokio::task::spawn_blocking(move || extractors::extract_text(&mime_type, &bytes)).await;
+        match extracted {
+            Ok(Ok(Some(text))) if !text.is_empty() => Some(text),
+            Ok(Ok(_)) => None,
+            Ok(Err(err)) => {
+                warn!("Drive: text extraction failed for {}: {}", file.name, err);
+                None
+            }
+            Err(err) => {
+                warn!("Drive: extraction task failed for {}: {}", file.name, err);
+                None
+            }
+        }
+    }
+
     /// Sync Google Calendar events.
     async fn sync_calendar(
         &self,
@@ -290,13 +358,13 @@ impl GoogleProvider {
                         updated_at,
                     };
 
-                    ctx.index_document(doc).await?;
+                    let doc_id = ctx.index_document(doc).await?;
                     docs_indexed += 1;
 
                     // Extract edges
                     let edges = self.extract_calendar_edges(&event, updated_at);
                     if !edges.is_empty() {
-                        ctx.index_edges(&edges).await?;
+                        ctx.index_document_edges(doc_id, &edges).await?;
                         edges_extracted += edges.len();
                     }
                 }
@@ -433,13 +501,13 @@ impl GoogleProvider {
                     updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
                 docs_indexed += 1;
 
                 // Extract edges
                 let edges = self.extract_gmail_edges(&message.id, &from, &to, &headers, updated_at);
                 if !edges.is_empty() {
-                    ctx.index_edges(&edges).await?;
+                    ctx.index_document_edges(doc_id, &edges).await?;
                     edges_extracted += edges.len();
                 }
             }
@@ -488,20 +556,23 @@ impl GoogleProvider {
             &file.name,
         );
 
-        // Owner → Document (AuthorOf)
+        // Owner → Document (OwnerOf)
         if let Some(ref owners) = file.owners {
             for owner in owners {
                 if let Some(ref email) = owner.email_address {
-                    let user_node = NodeRef::with_name(
+                    let mut user_node = NodeRef::with_name(
                         NodeType::User,
                         "google",
                         email,
                         owner.display_name.as_deref().unwrap_or(email),
                     );
+                    if let Some(ref photo_link) = owner.photo_link {
+                        user_node = user_node.with_avatar(photo_link);
+                    }
                     edges.push(ExtractedEdge::new(
                         user_node,
                         doc_node.clone(),
-                        Relation::AuthorOf,
+                        Relation::OwnerOf,
                         observed_at,
                     ));
                 }
@@ -516,58 +587,7 @@ impl GoogleProvider {
         event: &CalendarEvent,
         observed_at: DateTime<Utc>,
     ) -> Vec<ExtractedEdge> {
-        let mut edges = Vec::new();
-
-        let event_node = NodeRef::with_name(
-            NodeType::Document, // Using Document for events
-            "google_calendar",
-            &event.id,
-            event.summary.as_deref().unwrap_or("Event"),
-        );
-
-        // Organizer → Event (AuthorOf)
-        if let Some(ref organizer) = event.organizer {
-            if let Some(ref email) = organizer.email {
-                let user_node = NodeRef::with_name(
-                    NodeType::User,
-                    "google",
-                    email,
-                    organizer.display_name.as_deref().unwrap_or(email),
-                );
-                edges.push(ExtractedEdge::new(
-                    user_node,
-                    event_node.clone(),
-                    Relation::AuthorOf,
-                    observed_at,
-                ));
-            }
-        }
-
-        // Attendees → Event (MentionedIn - they're mentioned/invited)
-        if let Some(ref attendees) = event.attendees {
-            for attendee in attendees {
-                if let Some(ref email) = attendee.email {
-                    // Skip the organizer if they're also an attendee
-                    if event.organizer.as_ref().and_then(|o| o.email.as_ref()) == Some(email) {
-                        continue;
-                    }
-                    let user_node = NodeRef::with_name(
-                        NodeType::User,
-                        "google",
-                        email,
-                        attendee.display_name.as_deref().unwrap_or(email),
-                    );
-                    edges.push(ExtractedEdge::new(
-                        user_node,
-                        event_node.clone(),
-                        Relation::MentionedIn,
-                        observed_at,
-                    ));
-                }
-            }
-        }
-
-        edges
+        MeetingExtractor::new("google", "google_calendar").extract(&event.to_meeting(), observed_at)
     }
 
     fn extract_gmail_edges(
@@ -586,28 +606,8 @@ impl GoogleProvider {
             message_id,
         );
 
-        // Extract email from "Name <email>" format
-        let extract_email = |s: &str| -> Option<String> {
-            if let Some(start) = s.find('<') {
-                if let Some(end) = s.find('>') {
-                    return Some(s[start + 1..end].to_string());
-                }
-            }
-            if s.contains('@') {
-                Some(s.trim().to_string())
-            } else {
-                None
-            }
-        };
-
         // From → Message (AuthorOf)
-        if let Some(from_email) = extract_email(from) {
-            let user_node = NodeRef::with_name(
-                NodeType::User,
-                "google",
-                &from_email,
-                &from_email,
-            );
+        if let Some(user_node) = address_node(from) {
             edges.push(ExtractedEdge::new(
                 user_node,
                 message_node.clone(),
@@ -618,13 +618,7 @@ impl GoogleProvider {
 
         // To recipients → Message (MentionedIn)
         for recipient in to.split(',') {
-            if let Some(email) = extract_email(recipient.trim()) {
-                let user_node = NodeRef::with_name(
-                    NodeType::User,
-                    "google",
-                    &email,
-                    &email,
-                );
+            if let Some(user_node) = address_node(recipient) {
                 edges.push(ExtractedEdge::new(
                     user_node,
                     message_node.clone(),
@@ -641,13 +635,7 @@ impl GoogleProvider {
             .unwrap_or_default();
 
         for recipient in cc.split(',') {
-            if let Some(email) = extract_email(recipient.trim()) {
-                let user_node = NodeRef::with_name(
-                    NodeType::User,
-                    "google",
-                    &email,
-                    &email,
-                );
+            if let Some(user_node) = address_node(recipient) {
                 edges.push(ExtractedEdge::new(
                     user_node,
                     message_node.clone(),
@@ -681,6 +669,8 @@ struct DriveFile {
     name: String,
     #[serde(rename = "mimeType")]
     mime_type: Option<String>,
+    /// Size in bytes (Drive returns this as a string; absent for native Google docs).
+    size: Option<String>,
     #[serde(rename = "modifiedTime")]
     modified_time: Option<String>,
     #[serde(rename = "webViewLink")]
@@ -694,6 +684,8 @@ struct DriveUser {
     email_address: Option<String>,
     #[serde(rename = "displayName")]
     display_name: Option<String>,
+    #[serde(rename = "photoLink")]
+    photo_link: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -706,6 +698,9 @@ struct CalendarEventsResponse {
 #[derive(Debug, Clone, Deserialize)]
 struct CalendarEvent {
     id: String,
+    /// Series ID, for instances of a recurring event
+    #[serde(rename = "recurringEventId")]
+    recurring_event_id: Option<String>,
     summary: Option<String>,
     description: Option<String>,
     #[serde(rename = "htmlLink")]
@@ -724,11 +719,44 @@ struct CalendarTime {
     date: Option<String>,
 }
 
+impl CalendarEvent {
+    /// The event as a meeting; instances of a recurring event share the
+    /// series' meeting.
+    fn to_meeting(&self) -> Meeting {
+        Meeting {
+            id: self.recurring_event_id.clone().unwrap_or_else(|| self.id.clone()),
+            title: self.summary.clone(),
+            organizer: self.organizer.as_ref().and_then(CalendarPerson::to_attendee),
+            attendees: self
+                .attendees
+                .iter()
+                .flatten()
+                .filter_map(CalendarPerson::to_attendee)
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct CalendarPerson {
     email: Option<String>,
     #[serde(rename = "displayName")]
     display_name: Option<String>,
+    #[serde(rename = "responseStatus")]
+    response_status: Option<String>,
+    #[serde(default)]
+    resource: bool,
+}
+
+impl CalendarPerson {
+    fn to_attendee(&self) -> Option<MeetingAttendee> {
+        Some(MeetingAttendee {
+            email: self.email.clone()?,
+            display_name: self.display_name.clone(),
+            declined: self.response_status.as_deref() == Some("declined"),
+            resource: self.resource,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -760,27 +788,74 @@ struct GmailHeader {
     value: Option<String>,
 }
 
+/// Split a "Name <email>" address into its display name, if any, and email.
+fn parse_address(address: &str) -> Option<(Option<&str>, &str)> {
+    let address = address.trim();
+    if let (Some(start), Some(end)) = (address.find('<'), address.find('>')) {
+        if start < end {
+            let name = address[..start].trim().trim_matches('"').trim();
+            return Some(((!name.is_empty()).then_some(name), &address[start + 1..end]));
+        }
+    }
+    address.contains('@').then_some((None, address))
+}
+
+/// User node for an email address, named after the address's display name.
+/// Bare addresses get no name, so one from Drive or Calendar is kept.
+fn address_node(address: &str) -> Option<NodeRef> {
+    let (name, email) = parse_address(address)?;
+    Some(match name {
+        Some(name) => NodeRef::with_name(NodeType::User, "google", email, name),
+        None => NodeRef::user("google", email),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_extract_email() {
-        let extract_email = |s: &str| -> Option<String> {
-            if let Some(start) = s.find('<') {
-                if let Some(end) = s.find('>') {
-                    return Some(s[start + 1..end].to_string());
-                }
-            }
-            if s.contains('@') {
-                Some(s.trim().to_string())
-            } else {
-                None
-            }
+    fn test_recurring_meeting_edges() {
+        let instance = |id: &str| -> CalendarEvent {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "recurringEventId": "weekly-sync",
+                "summary": "Weekly sync",
+                "organizer": {"email": "me@example.com"},
+                "attendees": [
+                    {"email": "me@example.com", "responseStatus": "accepted"},
+                    {"email": "Dana@example.com", "displayName": "Dana", "responseStatus": "accepted"},
+                    {"email": "eli@example.com", "responseStatus": "declined"},
+                    {"email": "room@resource.calendar.google.com", "resource": true}
+                ]
+            }))
+            .unwrap()
         };
 
+        let edges = GoogleProvider.extract_calendar_edges(&instance("weekly-sync_20260105"), Utc::now());
+        let again = GoogleProvider.extract_calendar_edges(&instance("weekly-sync_20260112"), Utc::now());
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].relation, Relation::AuthorOf);
+        assert_eq!(edges[1].relation, Relation::AttendeeOf);
+        assert_eq!(edges[1].from.canonical_id(), "user:google:Dana@example.com");
+        assert_eq!(edges[1].to.canonical_id(), "meeting:google_calendar:weekly-sync");
+        // Every instance lands on the series node
+        assert_eq!(again[1].to.canonical_id(), edges[1].to.canonical_id());
+        assert_eq!(edges[1].weight, 2.0);
+    }
+
+    #[test]
+    fn test_extract_email() {
+        let extract_email = |s: &str| parse_address(s).map(|(_, email)| email.to_string());
+
         assert_eq!(extract_email("Alice <alice@example.com>"), Some("alice@example.com".to_string()));
         assert_eq!(extract_email("bob@example.com"), Some("bob@example.com".to_string()));
         assert_eq!(extract_email("No Email"), None);
+
+        assert_eq!(parse_address("\"Alice Smith\" <alice@example.com>").unwrap().0, Some("Alice Smith"));
+        assert_eq!(parse_address(" bob@example.com ").unwrap(), (None, "bob@example.com"));
+        // Without a name, the node's name from other sources is kept
+        assert_eq!(address_node("bob@example.com").unwrap().display_name, None);
+        assert_eq!(address_node("Alice <alice@example.com>").unwrap().display_name.as_deref(), Some("Alice"));
     }
 }
@@ -6,7 +6,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::Document;
 use crate::progress::emit_progress;
@@ -165,15 +165,22 @@ impl SyncProvider for LinearProvider {
                     updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
                 docs_indexed += 1;
 
                 // Extract and store edges for Gravity Well
                 let edges = self.extract_edges_from_issue(&issue, updated_at);
                 if !edges.is_empty() {
-                    ctx.index_edges(&edges).await?;
+                    ctx.index_document_edges(doc_id, &edges).await?;
                     edges_extracted += edges.len();
                 }
+                for user in issue.assignee.iter().chain(issue.creator.iter()) {
+                    if let Some(email) = &user.email {
+                        ctx.graph
+                            .set_node_email(&NodeRef::user("linear", &user.id).canonical_id(), email)
+                            .await?;
+                    }
+                }
 
                 if docs_indexed.is_multiple_of(10) {
                     emit_progress(
@@ -192,6 +199,11 @@ impl SyncProvider for LinearProvider {
             }
         }
 
+        match self.sync_teams(ctx, &token.access_token).await {
+            Ok(count) => edges_extracted += count,
+            Err(err) => warn!("Linear teams unavailable: {}", err),
+        }
+
         // Update sync cursor
         ctx.set_sync_cursor("linear", &max_updated).await?;
 
@@ -210,6 +222,81 @@ impl SyncProvider for LinearProvider {
 }
 
 impl LinearProvider {
+    /// Record team membership as `MemberOf` edges to `Team` nodes.
+    async fn sync_teams(&self, ctx: &SyncContext<'_>, access_token: &str) -> Result<usize> {
+        let query = r#"
+            query Teams($after: String) {
+                teams(first: 50, after: $after) {
+                    nodes {
+                        id
+                        name
+                        members(first: 250) { nodes { id name email } }
+                    }
+                    pageInfo { hasNextPage endCursor }
+                }
+            }
+        "#;
+        let now = Utc::now();
+        let mut after: Option<String> = None;
+        let mut count = 0;
+        loop {
+            let payload = serde_json::json!({
+                "query": query,
+                "variables": { "after": after }
+            });
+            let response = call_with_backoff("linear", || {
+                ctx.http_client
+                    .post("https://api.linear.app/graphql")
+                    .header("Authorization", access_token.to_string())
+                    .json(&payload)
+            })
+            .await?;
+            let body: LinearTeamsResponse = response.json().await?;
+            if let Some(errors) = body.errors {
+                return Err(anyhow::anyhow!("Linear API error: {}", errors[0].message));
+            }
+            let teams = body
+                .data
+                .ok_or_else(|| anyhow::anyhow!("Linear response missing data"))?
+                .teams;
+
+            for team in &teams.nodes {
+                let edges = self.extract_team_edges(team, now);
+                count += ctx.index_edges(&edges).await?;
+                for member in &team.members.nodes {
+                    if let Some(email) = &member.email {
+                        ctx.graph
+                            .set_node_email(&NodeRef::user("linear", &member.id).canonical_id(), email)
+                            .await?;
+                    }
+                }
+            }
+
+            if teams.page_info.has_next_page {
+                after = teams.page_info.end_cursor;
+            } else {
+                return Ok(count);
+            }
+        }
+    }
+
+    /// Extract `MemberOf` edges from a team's members.
+    fn extract_team_edges(&self, team: &LinearTeamMembers, observed_at: DateTime<Utc>) -> Vec<ExtractedEdge> {
+        let team_node = NodeRef::with_name(NodeType::Team, "linear", &team.id, &team.name);
+        team.members
+            .nodes
+            .iter()
+            .map(|member| {
+                ExtractedEdge::new(
+                    NodeRef::with_name(NodeType::User, "linear", &member.id, &member.name),
+                    team_node.clone(),
+                    Relation::MemberOf,
+                    observed_at,
+                )
+            })
+            .collect()
+    }
+
     /// Extract relationship edges from a Linear issue.
     fn extract_edges_from_issue(
         &self,
@@ -274,10 +361,10 @@ impl LinearProvider {
             ));
         }
 
-        // Edge: Issue → Team (BelongsTo) - teams are like containers
+        // Edge: Issue → Team (BelongsTo)
         if let Some(ref team) = issue.team {
             let team_node = NodeRef::with_name(
-                NodeType::Project, // Teams are project-like containers
+                NodeType::Team,
                 "linear",
                 &team.id,
                 &team.name,
@@ -343,6 +430,36 @@ struct LinearIssue {
     team: Option<LinearTeam>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct LinearTeamsResponse {
+    data: Option<LinearTeamsData>,
+    errors: Option<Vec<LinearError>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LinearTeamsData {
+    teams: LinearTeamPage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LinearTeamPage {
+    nodes: Vec<LinearTeamMembers>,
+    #[serde(rename = "pageInfo")]
+    page_info: LinearPageInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LinearTeamMembers {
+    id: String,
+    name: String,
+    members: LinearUsers,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LinearUsers {
+    nodes: Vec<LinearUser>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct LinearState {
     name: String,
@@ -429,5 +546,24 @@ mod tests {
             .filter(|e| e.relation == Relation::BelongsTo)
             .collect();
         assert_eq!(project_edges.len(), 2); // project + team
+        assert!(project_edges.iter().any(|e| e.to.node_type == NodeType::Team));
+    }
+
+    #[test]
+    fn test_extract_team_edges() {
+        let team: LinearTeamMembers = serde_json::from_value(serde_json::json!({
+            "id": "team-1",
+            "name": "Engineering",
+            "members": { "nodes": [
+                { "id": "user-456", "name": "Alice", "email": "alice@example.com" },
+                { "id": "user-789", "name": "Bob", "email": null }
+            ] }
+        }))
+        .unwrap();
+        let edges = LinearProvider.extract_team_edges(&team, Utc::now());
+        assert_eq!(edges.len(), 2);
+        assert!(edges.iter().all(|e| e.relation == Relation::MemberOf));
+        assert_eq!(edges[0].to.canonical_id(), "team:linear:team-1");
+        assert_eq!(edges[1].from.external_id, "user-789");
     }
 }
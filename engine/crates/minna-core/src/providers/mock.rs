@@ -0,0 +1,130 @@
+//! Offline provider with a fixed set of documents.
+//!
+//! Used by the daemon's end-to-end tests and for poking at the engine without
+//! credentials. It is never enabled by default; turn it on in
+//! `providers.toml`:
+//!
+//! ```toml
+//! [providers.mock]
+//! enabled = true
+//! display_name = "Mock"
+//! auth = { type = "none" }
+//! ```
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+
+use minna_provider_sdk::progress::emit_progress;
+
+use super::{DocumentBuilder, EdgeSet, NodeRef, Relation, SyncContext, SyncProvider, SyncSummary};
+
+const PROVIDER: &str = "mock";
+
+/// (id, title, author, body); documents are tagged with their author
+const DOCUMENTS: &[(&str, &str, &str, &str)] = &[
+    (
+        "1",
+        "Quarterly planning notes",
+        "ada",
+        "Roadmap review for the quarterly planning cycle. The sync engine rewrite ships first.",
+    ),
+    (
+        "2",
+        "Incident report: search latency",
+        "grace",
+        "Keyword search latency spiked after the index rebuild. Rolled back and added a canary.",
+    ),
+    (
+        "3",
+        "Onboarding checklist",
+        "ada",
+        "Set up the development database, run the daemon locally and read the provider guide. \
+         Planning notes: mock://doc/1.",
+    ),
+];
+
+/// (document id, name, MIME type, contents)
+const ATTACHMENT: (&str, &str, &str, &str) = (
+    "1",
+    "agenda.txt",
+    "text/plain",
+    "1. Sync engine rewrite\n2. Search quality\n3. Hiring",
+);
+
+pub struct MockProvider;
+
+impl MockProvider {
+    pub fn uri(id: &str) -> String {
+        format!("mock://doc/{}", id)
+    }
+}
+
+#[async_trait]
+impl SyncProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Mock"
+    }
+
+    async fn sync(
+        &self,
+        ctx: &SyncContext<'_>,
+        _since_days: Option<i64>,
+        _mode: Option<&str>,
+    ) -> Result<SyncSummary> {
+        let started_at = Utc::now();
+        // Stable, distinct timestamps so ordering is deterministic
+        let updated_at = |i: usize| started_at - Duration::hours(i as i64 + 1);
+        let docs = DOCUMENTS
+            .iter()
+            .enumerate()
+            .map(|(i, (id, title, author, body))| {
+                DocumentBuilder::new(PROVIDER, Self::uri(id))
+                    .title(*title)
+                    .field("Author", *author)
+                    .body(*body)
+                    .updated_at(updated_at(i))
+                    .build()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let doc_ids = ctx.index_documents(&docs).await?;
+
+        let mut indexed = 0;
+        for (i, ((id, _, author, _), doc_id)) in DOCUMENTS.iter().zip(doc_ids).enumerate() {
+            ctx.add_tags(doc_id, &[author]).await?;
+            let (attach_to, name, mime_type, contents) = ATTACHMENT;
+            if *id == attach_to {
+                ctx.attach(doc_id, name, mime_type, contents.as_bytes()).await?;
+            }
+
+            let mut edges = EdgeSet::new(updated_at(i));
+            edges.add(
+                NodeRef::user(PROVIDER, *author),
+                NodeRef::document(PROVIDER, *id),
+                Relation::AuthorOf,
+            );
+            ctx.index_document_edges(doc_id, &edges.into_edges()).await?;
+            indexed += 1;
+        }
+        emit_progress(PROVIDER, "syncing", &format!("Indexed {} documents", indexed), Some(indexed));
+
+        ctx.commit_cursor(PROVIDER, started_at).await?;
+        Ok(SyncSummary {
+            provider: PROVIDER.to_string(),
+            items_scanned: DOCUMENTS.len(),
+            documents_processed: indexed,
+            updated_at: started_at.to_rfc3339(),
+        })
+    }
+
+    async fn discover(&self, _ctx: &SyncContext<'_>) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "provider": PROVIDER,
+            "documents": DOCUMENTS.len(),
+        }))
+    }
+}
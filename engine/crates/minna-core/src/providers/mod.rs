@@ -1,14 +1,18 @@
 //! Extensible provider system for data synchronization.
 //!
 //! This module provides:
-//! - `SyncProvider` trait that all providers implement
 //! - `ProviderRegistry` for managing and dispatching to providers
 //! - Configuration loading from TOML
+//! - Keychain and Google OAuth helpers for the built-in providers
+//!
+//! The `SyncProvider` trait, `SyncContext` and related helpers come from
+//! the `minna-provider-sdk` crate and are re-exported here.
 //!
 //! # Adding a New Provider
 //!
 //! 1. Add config to `~/.minna/providers.toml`
 //! 2. Create a new file in `providers/` implementing `SyncProvider`
+//!    (see `minna-provider-sdk/examples/cookbook.rs`)
 //! 3. Register in `ProviderRegistry::register_builtin_providers()`
 
 pub mod config;
@@ -17,14 +21,16 @@ mod atlassian;
 mod github;
 mod google;
 mod linear;
+mod mock;
 mod notion;
 mod slack;
 
 pub use atlassian::AtlassianProvider;
-pub use config::{AuthConfig, ProviderConfig, ProvidersConfig};
+pub use config::{AuthConfig, ProviderConfig, ProvidersConfig, RingsConfig, SourceLimit};
 pub use github::GithubProvider;
 pub use google::GoogleProvider;
 pub use linear::LinearProvider;
+pub use mock::MockProvider;
 pub use notion::NotionProvider;
 pub use slack::SlackProvider;
 
@@ -33,123 +39,16 @@ use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-// serde re-exported from config module
-
-use crate::{Document, IngestionEngine, Embedder, VectorStore};
-
-// Re-export graph types for providers to use
+use minna_auth_bridge::keychain_service;
+use minna_ingest::{RedactionConfig, TransformConfig};
+
+// Provider-facing building blocks live in the SDK crate
+pub use minna_provider_sdk::{
+    calculate_since, call_with_backoff, DocumentBuilder, EdgeSet, ProviderHost, SyncContext,
+    SyncProvider, SyncSummary,
+};
 pub use minna_graph::{ExtractedEdge, GraphStore, NodeRef, Relation, NodeType};
 
-// Re-export the main SyncSummary from lib.rs
-// This is defined in lib.rs line ~1930 and used by all sync methods
-pub use crate::SyncSummary;
-
-/// Context passed to providers during sync operations.
-///
-/// Contains all the shared resources providers need to index documents.
-pub struct SyncContext<'a> {
-    /// Document storage engine.
-    pub ingest: &'a IngestionEngine,
-    /// Vector embeddings storage.
-    pub vector: &'a VectorStore,
-    /// Embedding model.
-    pub embedder: &'a Arc<dyn Embedder>,
-    /// HTTP client for API requests.
-    pub http_client: &'a reqwest::Client,
-    /// Provider registry for token loading.
-    pub registry: &'a ProviderRegistry,
-    /// Graph store for relationship tracking (Gravity Well).
-    pub graph: &'a GraphStore,
-    /// Path to auth token storage (for OAuth providers using TokenStore).
-    pub auth_path: &'a Path,
-}
-
-impl<'a> SyncContext<'a> {
-    /// Index a document (store + embed + vectorize).
-    pub async fn index_document(&self, doc: Document) -> Result<i64> {
-        let id = self.ingest.upsert_document(&doc).await?;
-        let embedding = self.embedder.embed(&doc.body).await?;
-        self.vector.upsert_embedding(id, &embedding).await?;
-        Ok(id)
-    }
-
-    /// Get sync cursor for incremental syncing.
-    pub async fn get_sync_cursor(&self, provider: &str) -> Result<Option<String>> {
-        self.ingest.get_sync_cursor(provider).await
-    }
-
-    /// Set sync cursor after successful sync.
-    pub async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()> {
-        self.ingest.set_sync_cursor(provider, cursor).await
-    }
-
-    /// Store extracted edges in the graph (Gravity Well).
-    ///
-    /// Upserts nodes and edges. The GraphStore handles node creation internally.
-    pub async fn index_edges(&self, edges: &[ExtractedEdge]) -> Result<usize> {
-        let mut count = 0;
-        for edge in edges {
-            // upsert_edge handles node creation internally
-            self.graph.upsert_edge(edge).await?;
-            count += 1;
-        }
-        Ok(count)
-    }
-}
-
-/// Trait that all sync providers must implement.
-#[async_trait]
-pub trait SyncProvider: Send + Sync {
-    /// Provider identifier (e.g., "notion", "slack").
-    fn name(&self) -> &'static str;
-
-    /// Human-readable display name (e.g., "Notion", "Slack").
-    fn display_name(&self) -> &'static str;
-
-    /// Sync documents from this provider.
-    ///
-    /// # Arguments
-    /// * `ctx` - Shared context with storage and HTTP client
-    /// * `since_days` - Optional number of days to look back
-    /// * `mode` - Optional sync mode ("full", "sprint", etc.)
-    async fn sync(
-        &self,
-        ctx: &SyncContext<'_>,
-        since_days: Option<i64>,
-        mode: Option<&str>,
-    ) -> Result<SyncSummary>;
-
-    /// Optional: Quick discovery scan for UI metadata.
-    ///
-    /// Returns counts, available resources, etc. without full sync.
-    async fn discover(&self, _ctx: &SyncContext<'_>) -> Result<serde_json::Value> {
-        Ok(serde_json::json!({
-            "provider": self.name(),
-            "error": "discovery not implemented"
-        }))
-    }
-
-    /// Extract relationship edges from synced data.
-    ///
-    /// Called after sync to populate the Gravity Well graph.
-    /// Default implementation returns empty - providers opt-in by overriding.
-    ///
-    /// # Arguments
-    /// * `ctx` - Shared context with graph store
-    /// * `doc` - The document that was just synced
-    /// * `raw_data` - Optional raw API response for richer extraction
-    async fn extract_edges(
-        &self,
-        _ctx: &SyncContext<'_>,
-        _doc: &Document,
-        _raw_data: Option<&serde_json::Value>,
-    ) -> Result<Vec<ExtractedEdge>> {
-        Ok(Vec::new())
-    }
-}
-
 /// Registry managing all available providers.
 pub struct ProviderRegistry {
     config: ProvidersConfig,
@@ -196,6 +95,10 @@ impl ProviderRegistry {
         if config.is_enabled("google") {
             map.insert("google".to_string(), Arc::new(GoogleProvider));
         }
+        // Offline provider for tests; only registered when configured
+        if config.is_enabled("mock") {
+            map.insert("mock".to_string(), Arc::new(MockProvider));
+        }
 
         map
     }
@@ -210,6 +113,41 @@ impl ProviderRegistry {
         self.config.get(name)
     }
 
+    /// Whether a provider should keep syncing archived resources.
+    pub fn include_archived(&self, name: &str) -> bool {
+        self.get_config(name).is_some_and(|c| c.include_archived)
+    }
+
+    /// Storage caps by document source (see [`SourceLimit`]).
+    pub fn source_limits(&self) -> &HashMap<String, SourceLimit> {
+        &self.config.limits
+    }
+
+    /// The `[redaction]` section of the config.
+    pub fn redaction(&self) -> &RedactionConfig {
+        &self.config.redaction
+    }
+
+    /// The `[transforms]` section of the config.
+    pub fn transforms(&self) -> &TransformConfig {
+        &self.config.transforms
+    }
+
+    /// Embedding models by document source (see `[embedding_models]`).
+    pub fn embedding_models(&self) -> &HashMap<String, String> {
+        &self.config.embedding_models
+    }
+
+    /// The `[rings]` section of the config.
+    pub fn rings(&self) -> &RingsConfig {
+        &self.config.rings
+    }
+
+    /// The `[graph_pruning]` section of the config.
+    pub fn graph_pruning(&self) -> &minna_graph::PrunePolicy {
+        &self.config.graph_pruning
+    }
+
     /// Check if a provider is registered and enabled.
     pub fn is_available(&self, name: &str) -> bool {
         self.providers.contains_key(name)
@@ -253,15 +191,15 @@ impl ProviderRegistry {
             _ => Err(anyhow!("Provider {} does not use OAuth", name)),
         }
     }
+}
 
-    /// Parse Basic Auth credentials (email:token format).
-    pub fn parse_basic_auth(&self, name: &str) -> Result<(String, String)> {
-        let creds = self.load_token(name)?;
-        let parts: Vec<&str> = creds.splitn(2, ':').collect();
-        if parts.len() != 2 {
-            return Err(anyhow!("Invalid Basic Auth format for {}. Expected 'email:token'", name));
-        }
-        Ok((parts[0].to_string(), parts[1].to_string()))
+impl ProviderHost for ProviderRegistry {
+    fn load_token(&self, name: &str) -> Result<String> {
+        ProviderRegistry::load_token(self, name)
+    }
+
+    fn include_archived(&self, name: &str) -> bool {
+        ProviderRegistry::include_archived(self, name)
     }
 }
 
@@ -279,7 +217,7 @@ fn keychain_get(account: &str) -> Result<String> {
     use std::process::Command;
 
     let output = Command::new("security")
-        .args(["find-generic-password", "-s", "minna_ai", "-a", account, "-w"])
+        .args(["find-generic-password", "-s", &keychain_service(), "-a", account, "-w"])
         .output()
         .map_err(|e| anyhow!("Failed to run security command: {}", e))?;
 
@@ -305,12 +243,12 @@ pub fn keychain_set(account: &str, value: &str) -> Result<()> {
 
     // Delete existing entry (ignore errors if it doesn't exist)
     let _ = Command::new("security")
-        .args(["delete-generic-password", "-s", "minna_ai", "-a", account])
+        .args(["delete-generic-password", "-s", &keychain_service(), "-a", account])
         .output();
 
     // Add new entry
     let output = Command::new("security")
-        .args(["add-generic-password", "-s", "minna_ai", "-a", account, "-w", value])
+        .args(["add-generic-password", "-s", &keychain_service(), "-a", account, "-w", value])
         .output()
         .map_err(|e| anyhow!("Failed to run security command: {}", e))?;
 
@@ -362,94 +300,6 @@ pub async fn refresh_google_token(http_client: &reqwest::Client) -> Result<Strin
     Ok(new_token.to_string())
 }
 
-/// Calculate the "since" timestamp for sync operations.
-pub fn calculate_since(
-    since_days: Option<i64>,
-    mode: Option<&str>,
-    cursor: Option<&str>,
-) -> DateTime<Utc> {
-    let is_full = mode == Some("full");
-
-    if is_full {
-        // Full sync: default 90 days
-        let days = since_days.unwrap_or(90);
-        Utc::now() - chrono::Duration::days(days)
-    } else if let Some(days) = since_days {
-        // Explicit days override
-        Utc::now() - chrono::Duration::days(days)
-    } else if let Some(cursor_str) = cursor {
-        // Use cursor if available
-        DateTime::parse_from_rfc3339(cursor_str)
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|_| Utc::now() - chrono::Duration::days(30))
-    } else {
-        // Default: 30 days
-        Utc::now() - chrono::Duration::days(30)
-    }
-}
-
-/// HTTP request helper with exponential backoff for rate limiting.
-pub async fn call_with_backoff<F>(
-    provider: &str,
-    mut builder_fn: F,
-) -> Result<reqwest::Response>
-where
-    F: FnMut() -> reqwest::RequestBuilder,
-{
-    use std::time::Duration;
-    use tokio::time::sleep;
-
-    let mut retries = 0;
-    let mut delay = Duration::from_secs(1);
-    let max_retries = 8;
-
-    loop {
-        let response = builder_fn().send().await?;
-        let status = response.status();
-
-        if status.is_success() {
-            return Ok(response);
-        }
-
-        if status.as_u16() == 429 {
-            // Rate limited
-            if retries >= max_retries {
-                return Err(anyhow!("{}: Rate limited after {} retries", provider, retries));
-            }
-
-            // Check for Retry-After header
-            let wait = response
-                .headers()
-                .get("Retry-After")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|v| v.parse::<u64>().ok())
-                .map(Duration::from_secs)
-                .unwrap_or(delay);
-
-            tracing::warn!("{}: Rate limited, waiting {:?}", provider, wait);
-            sleep(wait).await;
-
-            retries += 1;
-            delay = std::cmp::min(delay * 2, Duration::from_secs(60));
-            continue;
-        }
-
-        if status.is_server_error() && retries < 3 {
-            tracing::warn!("{}: Server error {}, retrying...", provider, status);
-            sleep(delay).await;
-            retries += 1;
-            delay *= 2;
-            continue;
-        }
-
-        if status.as_u16() == 403 {
-            return Err(anyhow!("{}: Access forbidden (403). Check permissions.", provider));
-        }
-
-        return Err(anyhow!("{}: HTTP {} - {}", provider, status, response.text().await.unwrap_or_default()));
-    }
-}
-
 /// Result of a Google API call, including potentially refreshed token.
 pub struct GoogleApiResult {
     pub response: reqwest::Response,
@@ -10,7 +10,10 @@ use tracing::{info, warn};
 
 use crate::Document;
 use crate::progress::emit_progress;
-use super::{SyncContext, SyncProvider, SyncSummary, call_with_backoff, calculate_since};
+use super::{
+    call_with_backoff, calculate_since, EdgeSet, NodeRef, NodeType, Relation, SyncContext,
+    SyncProvider, SyncSummary,
+};
 
 /// Notion provider for syncing pages and database items.
 pub struct NotionProvider;
@@ -34,7 +37,7 @@ impl SyncProvider for NotionProvider {
         since_days: Option<i64>,
         mode: Option<&str>,
     ) -> Result<SyncSummary> {
-        let token = ctx.registry.load_token("notion")?;
+        let token = ctx.host.load_token("notion")?;
 
         // Get existing cursor for delta sync
         let cursor_str = ctx.get_sync_cursor("notion").await?;
@@ -91,6 +94,14 @@ impl SyncProvider for NotionProvider {
                     break;
                 }
 
+                let page_url = page.url.clone().unwrap_or_else(|| format!("notion://{}", page.id));
+                let skip = ctx
+                    .track_archived("notion", &page.id, None, &page_url, page.archived || page.in_trash)
+                    .await?;
+                if skip {
+                    continue;
+                }
+
                 // Fetch page content (blocks)
                 let content = match self.fetch_page_content(ctx, &token, &page.id).await {
                     Ok(c) => c,
@@ -104,17 +115,19 @@ impl SyncProvider for NotionProvider {
                 let title = self.extract_title(page);
 
                 // Build document
+                let updated_at = parse_notion_timestamp(last_edited).unwrap_or_else(Utc::now);
                 let doc = Document {
                     id: None,
-                    uri: page.url.clone().unwrap_or_else(|| format!("notion://{}", page.id)),
+                    uri: page_url,
                     source: "notion".to_string(),
                     title: title.clone(),
                     body: self.format_body(page, &title, &content),
-                    updated_at: parse_notion_timestamp(last_edited)
-                        .unwrap_or_else(Utc::now),
+                    updated_at,
                 };
 
-                ctx.index_document(doc).await?;
+                let doc_id = ctx.index_document(doc).await?;
+                let edges = self.extract_edges(page, title.as_deref(), updated_at);
+                ctx.index_document_edges(doc_id, &edges.into_edges()).await?;
                 documents_processed += 1;
 
                 // Emit progress periodically
@@ -151,7 +164,7 @@ impl SyncProvider for NotionProvider {
     }
 
     async fn discover(&self, ctx: &SyncContext<'_>) -> Result<serde_json::Value> {
-        let token = ctx.registry.load_token("notion")?;
+        let token = ctx.host.load_token("notion")?;
 
         // Quick search to count available pages
         let response = call_with_backoff("notion", || {
@@ -183,6 +196,37 @@ impl SyncProvider for NotionProvider {
 }
 
 impl NotionProvider {
+    /// Edges for a page: the database it's an item of or the page it's
+    /// nested under, and its creator.
+    fn extract_edges(
+        &self,
+        page: &NotionObject,
+        title: Option<&str>,
+        observed_at: DateTime<Utc>,
+    ) -> EdgeSet {
+        let mut edges = EdgeSet::new(observed_at);
+        let page_node = NodeRef::with_name(
+            NodeType::Document,
+            "notion",
+            &page.id,
+            title.unwrap_or("Untitled"),
+        );
+        let parent = page.parent.as_ref();
+        if let Some(database_id) = parent.and_then(|p| p["database_id"].as_str()) {
+            let database_node = NodeRef::project("notion", database_id);
+            edges.add(page_node.clone(), database_node, Relation::BelongsTo);
+        } else if let Some(parent_id) = parent.and_then(|p| p["page_id"].as_str()) {
+            let parent_node = NodeRef::document("notion", parent_id);
+            edges.add(page_node.clone(), parent_node, Relation::ChildOf);
+        }
+        edges.add_opt(
+            page.created_by.as_ref().map(|user| NodeRef::user("notion", &user.id)),
+            page_node,
+            Relation::AuthorOf,
+        );
+        edges
+    }
+
     /// Fetch all blocks (content) for a page.
     async fn fetch_page_content(
         &self,
@@ -405,6 +449,17 @@ struct NotionObject {
     properties: Option<serde_json::Value>,
     #[serde(default)]
     parent: Option<serde_json::Value>,
+    #[serde(default)]
+    created_by: Option<NotionUser>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    in_trash: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NotionUser {
+    id: String,
 }
 
 #[derive(Debug, Deserialize)]
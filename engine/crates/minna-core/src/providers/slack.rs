@@ -13,6 +13,7 @@ use tracing::{info, warn};
 
 use crate::Document;
 use crate::progress::emit_progress;
+use minna_ingest::{extractors, ocr};
 use minna_auth_bridge::TokenStore;
 
 use super::{
@@ -58,7 +59,7 @@ impl SyncProvider for SlackProvider {
         info!("Slack sync context: my_user_id={}", my_user_id);
 
         // Build user directory cache
-        let user_cache = self.build_user_cache(ctx, &token.access_token).await?;
+        let (user_cache, user_emails, user_avatars) = self.build_user_cache(ctx, &token.access_token).await?;
         info!("Slack user directory cached: {} users", user_cache.len());
 
         let is_full_sync = mode == Some("full");
@@ -67,8 +68,23 @@ impl SyncProvider for SlackProvider {
         let channel_limit = self.get_channel_limit(is_full_sync);
         let message_limit = self.get_message_limit(is_full_sync);
 
-        // Fetch channels
-        let channels = self.fetch_channels(ctx, &token.access_token, channel_limit).await?;
+        // Fetch channels, dropping archived ones unless configured otherwise
+        let fetched = self.fetch_channels(ctx, &token.access_token, channel_limit).await?;
+        let mut channels = Vec::with_capacity(fetched.len());
+        for channel in fetched {
+            let skip = ctx
+                .track_archived(
+                    "slack",
+                    &channel.id,
+                    channel.name.as_deref(),
+                    &format!("https://slack.com/archives/{}/", channel.id),
+                    channel.is_archived.unwrap_or(false),
+                )
+                .await?;
+            if !skip {
+                channels.push(channel);
+            }
+        }
         info!("Scanning messages in {} Slack channels", channels.len());
 
         // Separate DMs from regular channels
@@ -131,6 +147,19 @@ impl SyncProvider for SlackProvider {
             }
         }
 
+        // Profile emails let identity matching link these users' other accounts
+        for (user_id, email) in &user_emails {
+            ctx.graph
+                .set_node_email(&NodeRef::user("slack", user_id).canonical_id(), email)
+                .await?;
+        }
+        for (user_id, avatar_url) in &user_avatars {
+            ctx.graph
+                .set_node_avatar(&NodeRef::user("slack", user_id).canonical_id(), avatar_url)
+                .await?;
+        }
+        edges_extracted += self.sync_user_groups(ctx, &token.access_token, &user_cache).await?;
+
         // Update sync cursor
         let cursor = format!("{:.6}", max_ts);
         ctx.set_sync_cursor("slack", &cursor).await?;
@@ -150,13 +179,17 @@ impl SyncProvider for SlackProvider {
 }
 
 impl SlackProvider {
-    /// Build user ID -> name cache for @mention resolution.
+    /// Build user ID -> name cache for @mention resolution, along with the
+    /// profile emails the token may read (`users:read.email`) and profile
+    /// pictures.
     async fn build_user_cache(
         &self,
         ctx: &SyncContext<'_>,
         access_token: &str,
-    ) -> Result<HashMap<String, String>> {
+    ) -> Result<(UserMap, UserMap, UserMap)> {
         let mut cache = HashMap::new();
+        let mut emails = HashMap::new();
+        let mut avatars = HashMap::new();
         let mut cursor: Option<String> = None;
 
         loop {
@@ -180,6 +213,12 @@ impl SlackProvider {
 
             if let Some(members) = payload.members {
                 for member in members {
+                    if let Some(email) = member.profile.email.filter(|e| !e.is_empty()) {
+                        emails.insert(member.id.clone(), email);
+                    }
+                    if let Some(avatar_url) = member.profile.image_192.filter(|url| !url.is_empty()) {
+                        avatars.insert(member.id.clone(), avatar_url);
+                    }
                     let name = member
                         .profile
                         .real_name
@@ -199,7 +238,53 @@ impl SlackProvider {
             }
         }
 
-        Ok(cache)
+        Ok((cache, emails, avatars))
+    }
+
+    /// Record user group membership as `MemberOf` edges to `Team` nodes.
+    /// Tokens without `usergroups:read` skip it with a warning.
+    async fn sync_user_groups(
+        &self,
+        ctx: &SyncContext<'_>,
+        access_token: &str,
+        user_cache: &HashMap<String, String>,
+    ) -> Result<usize> {
+        let response = call_with_backoff("slack", || {
+            ctx.http_client
+                .get("https://slack.com/api/usergroups.list")
+                .header("Authorization", format!("Bearer {}", access_token))
+                .query(&[("include_users", "true")])
+        })
+        .await?;
+        let payload: SlackUserGroupsResponse = response.json().await?;
+        if !payload.ok {
+            warn!(
+                "Slack user groups unavailable: {}",
+                payload.error.as_deref().unwrap_or("unknown error")
+            );
+            return Ok(0);
+        }
+
+        let now = Utc::now();
+        let mut edges = Vec::new();
+        for group in payload.usergroups.unwrap_or_default() {
+            let team_node = NodeRef::with_name(NodeType::Team, "slack", &group.id, &group.name);
+            for user_id in &group.users {
+                let user_node = NodeRef::with_name(
+                    NodeType::User,
+                    "slack",
+                    user_id,
+                    user_cache.get(user_id).unwrap_or(user_id),
+                );
+                edges.push(ExtractedEdge::new(
+                    user_node,
+                    team_node.clone(),
+                    Relation::MemberOf,
+                    now,
+                ));
+            }
+        }
+        ctx.index_edges(&edges).await
     }
 
     /// Calculate oldest timestamp for sync window.
@@ -434,6 +519,12 @@ impl SlackProvider {
                                 }
                             }
 
+                            if let Some(files) = message.files.as_deref() {
+                                full_body.push_str(
+                                    &self.ocr_image_files(ctx, access_token, files).await,
+                                );
+                            }
+
                             let doc = Document {
                                 id: None,
                                 uri: permalink.clone(),
@@ -443,7 +534,8 @@ impl SlackProvider {
                                 updated_at,
                             };
 
-                            ctx.index_document(doc).await?;
+                            let doc_id = ctx.index_document(doc).await?;
+                            ctx.add_tags(doc_id, &[channel_name]).await?;
                             docs_indexed += 1;
 
                             // Extract and store edges
@@ -456,9 +548,12 @@ impl SlackProvider {
                                 user_cache,
                                 my_user_id,
                                 updated_at,
-                            );
+                            )
+                            .into_iter()
+                            .map(|edge| edge.with_weight(interaction_weight(channel)))
+                            .collect::<Vec<_>>();
                             if !edges.is_empty() {
-                                ctx.index_edges(&edges).await?;
+                                ctx.index_document_edges(doc_id, &edges).await?;
                                 edges_extracted += edges.len();
                             }
 
@@ -488,6 +583,60 @@ impl SlackProvider {
         Ok((docs_indexed, edges_extracted, max_ts))
     }
 
+    /// OCR image attachments so screenshots become searchable.
+    ///
+    /// Returns an empty string when OCR is disabled. Failures are logged and skipped.
+    async fn ocr_image_files(
+        &self,
+        ctx: &SyncContext<'_>,
+        access_token: &str,
+        files: &[SlackFile],
+    ) -> String {
+        if ocr::OcrConfig::global().is_none() {
+            return String::new();
+        }
+
+        let mut text = String::new();
+        for file in files {
+            let (Some(mime), Some(url)) = (file.mimetype.as_deref(), file.url_private.as_deref()) else {
+                continue;
+            };
+            if !ocr::is_image(mime) || file.size.unwrap_or(0) as usize > extractors::MAX_EXTRACT_BYTES {
+                continue;
+            }
+            let name = file.name.as_deref().unwrap_or("image");
+
+            let bytes = match ctx.http_client.get(url).bearer_auth(access_token).send().await {
+                Ok(resp) if resp.status().is_success() => match resp.bytes().await {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        warn!("Slack: failed to download {}: {}", name, err);
+                        continue;
+                    }
+                },
+                Ok(resp) => {
+                    warn!("Slack: failed to download {}: HTTP {}", name, resp.status());
+                    continue;
+                }
+                Err(err) => {
+                    warn!("Slack: failed to download {}: {}", name, err);
+                    continue;
+                }
+            };
+
+            let mime = mime.to_string();
+            match tokio::task::spawn_blocking(move || extractors::extract_text(&mime, &bytes)).await {
+                Ok(Ok(Some(ocr_text))) if !ocr_text.is_empty() => {
+                    text.push_str(&format!("\n\n[Image: {}]\n{}", name, ocr_text));
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => warn!("Slack: OCR failed for {}: {}", name, err),
+                Err(err) => warn!("Slack: OCR task failed for {}: {}", name, err),
+            }
+        }
+        text
+    }
+
     /// Fetch thread replies and return (formatted text, participant user IDs).
     async fn fetch_thread_replies(
         &self,
@@ -646,12 +795,50 @@ impl SlackProvider {
             }
         }
 
+        // Edge: Reacting users → Message (ReactedTo)
+        for user_id in reacting_users(message) {
+            let user_name = user_cache.get(user_id).cloned().unwrap_or_else(|| user_id.clone());
+            let user_node = NodeRef::with_name(NodeType::User, "slack", user_id, &user_name);
+            edges.push(ExtractedEdge::new(
+                user_node,
+                message_node.clone(),
+                Relation::ReactedTo,
+                observed_at,
+            ));
+        }
+
         edges
     }
 }
 
 // --- Helper Functions ---
 
+/// Interaction strength of messages in a conversation: a DM says most about
+/// closeness, a group DM less, a channel post least.
+fn interaction_weight(channel: &SlackChannel) -> f32 {
+    if channel.is_im == Some(true) {
+        2.0
+    } else if channel.is_mpim == Some(true) {
+        1.5
+    } else {
+        1.0
+    }
+}
+
+/// Users who reacted to a message, each once, excluding its author.
+fn reacting_users(message: &SlackMessage) -> Vec<&String> {
+    let mut users: Vec<&String> = message
+        .reactions
+        .iter()
+        .flatten()
+        .flat_map(|reaction| &reaction.users)
+        .filter(|user| Some(*user) != message.user.as_ref())
+        .collect();
+    users.sort();
+    users.dedup();
+    users
+}
+
 fn slack_ts_from_datetime(dt: DateTime<Utc>) -> String {
     format!("{}.000000", dt.timestamp())
 }
@@ -721,6 +908,7 @@ struct SlackChannel {
     name_normalized: Option<String>,
     is_im: Option<bool>,
     is_mpim: Option<bool>,
+    is_archived: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -739,8 +927,27 @@ struct SlackMessage {
     text: Option<String>,
     thread_ts: Option<String>,
     reply_count: Option<i32>,
+    files: Option<Vec<SlackFile>>,
+    reactions: Option<Vec<SlackReaction>>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct SlackReaction {
+    #[serde(default)]
+    users: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SlackFile {
+    name: Option<String>,
+    mimetype: Option<String>,
+    size: Option<u64>,
+    url_private: Option<String>,
+}
+
+/// Slack user ID -> name, email or avatar URL.
+type UserMap = HashMap<String, String>;
+
 #[derive(Debug, Clone, Deserialize)]
 struct SlackUsersResponse {
     ok: bool,
@@ -756,10 +963,27 @@ struct SlackUser {
     profile: SlackUserProfile,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct SlackUserGroupsResponse {
+    ok: bool,
+    usergroups: Option<Vec<SlackUserGroup>>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SlackUserGroup {
+    id: String,
+    name: String,
+    #[serde(default)]
+    users: Vec<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct SlackUserProfile {
     real_name: Option<String>,
     display_name: Option<String>,
+    email: Option<String>,
+    image_192: Option<String>,
 }
 
 #[cfg(test)]
@@ -798,4 +1022,41 @@ mod tests {
 
         assert_eq!(mentions, vec!["U12345", "U67890"]);
     }
+
+    #[test]
+    fn test_interaction_weights() {
+        let channel: SlackChannel =
+            serde_json::from_value(serde_json::json!({"id": "D1", "is_im": true})).unwrap();
+        assert_eq!(interaction_weight(&channel), 2.0);
+        let channel: SlackChannel =
+            serde_json::from_value(serde_json::json!({"id": "C1", "name": "general"})).unwrap();
+        assert_eq!(interaction_weight(&channel), 1.0);
+
+        let message: SlackMessage = serde_json::from_value(serde_json::json!({
+            "ts": "1704067200.000000",
+            "user": "U1",
+            "text": "shipped",
+            "reactions": [
+                {"name": "tada", "users": ["U2", "U1"], "count": 2},
+                {"name": "+1", "users": ["U3", "U2"], "count": 2}
+            ]
+        }))
+        .unwrap();
+        let edges = SlackProvider.extract_edges_from_message(
+            "C1",
+            "general",
+            &message,
+            &[],
+            "shipped",
+            &HashMap::new(),
+            "U1",
+            Utc::now(),
+        );
+        let reacted: Vec<_> = edges
+            .iter()
+            .filter(|e| e.relation == Relation::ReactedTo)
+            .map(|e| e.from.external_id.as_str())
+            .collect();
+        assert_eq!(reacted, vec!["U2", "U3"]);
+    }
 }
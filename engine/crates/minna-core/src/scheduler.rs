@@ -392,6 +392,23 @@ impl SyncScheduler {
         self.pending.insert(0, sync); // Insert at front
     }
 
+    /// Claim a provider for an immediate on-demand sync.
+    ///
+    /// Returns false if the provider is already syncing or the budget is spent.
+    /// The caller must finish with `complete_sync` or `fail_sync`.
+    pub fn begin_on_demand(&mut self, provider: &str) -> bool {
+        if self.is_syncing(provider) || !self.budget.has_budget(self.config.hourly_budget) {
+            return false;
+        }
+        self.in_progress.insert(provider.to_string());
+        true
+    }
+
+    /// Check if a provider has a sync pending or in progress.
+    pub fn is_syncing(&self, provider: &str) -> bool {
+        self.in_progress.contains(provider) || self.pending.iter().any(|p| p.provider == provider)
+    }
+
     /// Get pending sync count.
     pub fn pending_count(&self) -> usize {
         self.pending.len()
@@ -572,6 +589,19 @@ mod tests {
         assert!(scheduler.next_sync().is_some());
     }
 
+    #[test]
+    fn test_begin_on_demand() {
+        let mut scheduler = SyncScheduler::new();
+
+        assert!(scheduler.begin_on_demand("linear"));
+        assert!(scheduler.is_syncing("linear"));
+        assert!(!scheduler.begin_on_demand("linear"));
+
+        scheduler.complete_sync("linear", Ring::One, 1);
+        assert!(!scheduler.is_syncing("linear"));
+        assert!(scheduler.begin_on_demand("linear"));
+    }
+
     #[test]
     fn test_sync_planner() {
         let (days, mode) = SyncPlanner::plan_for_ring(Ring::One);
@@ -0,0 +1,648 @@
+//! The stores [`Core`](crate::Core) indexes, searches and maintains through.
+//!
+//! [`Storage`] holds the three storage traits rather than the SQLite stores,
+//! so a shared backend (e.g. Postgres/pgvector for a team deployment) could
+//! stand in for the local database. [`Storage::sqlite`] is the default and,
+//! for now, the only implementation. Only the paths that take a `Storage`
+//! go through it; the rest of Core (rings, clustering, bundles, maintenance)
+//! still uses the SQLite stores.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::info;
+
+use minna_graph::{GraphBackend, GraphStore, Ring};
+use minna_ingest::fts::SearchQuery;
+use minna_ingest::{Document, DocumentStore, IngestionEngine};
+use minna_vector::{embed_document, Embedder, VectorIndex, VectorStore};
+
+use crate::progress::emit_progress;
+use crate::SourceLimit;
+
+/// RRF rank constant for [`Storage::search`], as get_context's default.
+const RRF_K: f32 = 60.0;
+
+/// A document found by [`Storage::search`].
+#[derive(Debug, Clone)]
+pub struct StorageHit {
+    pub document: Document,
+    /// Reciprocal Rank Fusion of the semantic and keyword ranks.
+    pub score: f32,
+}
+
+#[derive(Clone)]
+pub struct Storage {
+    pub documents: Arc<dyn DocumentStore>,
+    pub vectors: Arc<dyn VectorIndex>,
+    pub graph: Arc<dyn GraphBackend>,
+}
+
+impl Storage {
+    pub fn new(
+        documents: Arc<dyn DocumentStore>,
+        vectors: Arc<dyn VectorIndex>,
+        graph: Arc<dyn GraphBackend>,
+    ) -> Self {
+        Self { documents, vectors, graph }
+    }
+
+    /// The local SQLite stores.
+    pub fn sqlite(ingest: &IngestionEngine, vector: &VectorStore, graph: &GraphStore) -> Self {
+        Self::new(
+            Arc::new(ingest.clone()),
+            Arc::new(vector.clone()),
+            Arc::new(graph.clone()),
+        )
+    }
+
+    /// Store, embed and link a document, returning its ID.
+    pub async fn index_document(&self, embedder: &dyn Embedder, doc: &Document) -> Result<i64> {
+        let doc = self.documents.prepare(doc);
+        let id = self.documents.upsert_document(&doc).await?;
+        embed_document(embedder, self.vectors.as_ref(), id, &doc.source, &doc.body).await?;
+        minna_provider_sdk::index_reference_edges(self.documents.as_ref(), self.graph.as_ref(), id).await?;
+        Ok(id)
+    }
+
+    /// Hybrid search: the semantic and keyword rankings merged with
+    /// Reciprocal Rank Fusion, best first.
+    pub async fn search(&self, embedder: &dyn Embedder, query: &str, limit: usize) -> Result<Vec<StorageHit>> {
+        let candidates = limit.saturating_mul(3).max(limit);
+        let embedding = embedder.embed(query).await?;
+        let semantic = self.vectors.search_with_embedding(&embedding, candidates).await?;
+        let parsed = SearchQuery::parse(query);
+        let keyword = if parsed.match_expr().is_some() {
+            self.documents.search_query(&parsed, candidates).await?
+        } else {
+            Vec::new()
+        };
+
+        let mut scores: HashMap<i64, f32> = HashMap::new();
+        for (rank, (id, _)) in semantic.iter().enumerate() {
+            *scores.entry(*id).or_default() += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+        for (rank, hit) in keyword.iter().enumerate() {
+            if let Some(id) = hit.document.id {
+                *scores.entry(id).or_default() += 1.0 / (RRF_K + rank as f32 + 1.0);
+            }
+        }
+        let mut ranked: Vec<(i64, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+
+        let ids: Vec<i64> = ranked.iter().map(|(id, _)| *id).collect();
+        let mut documents: HashMap<i64, Document> = self
+            .documents
+            .fetch_documents_by_ids(&ids)
+            .await?
+            .into_iter()
+            .filter_map(|doc| doc.id.map(|id| (id, doc)))
+            .collect();
+        // Deleted documents can linger in the vector index until purged
+        Ok(ranked
+            .into_iter()
+            .filter_map(|(id, score)| documents.remove(&id).map(|document| StorageHit { document, score }))
+            .collect())
+    }
+
+    /// Re-embed queued documents with `embedder`, returning how many.
+    pub async fn reembed_pending(&self, embedder: &dyn Embedder, batch_size: usize) -> Result<usize> {
+        let mut total = 0usize;
+        loop {
+            let ids = self.vectors.pending_reembed(batch_size).await?;
+            if ids.is_empty() {
+                break;
+            }
+            let docs = self.documents.fetch_documents_by_ids(&ids).await?;
+            for doc in &docs {
+                let Some(id) = doc.id else { continue };
+                embed_document(embedder, self.vectors.as_ref(), id, &doc.source, &doc.body).await?;
+            }
+            // Deleted documents have nothing to re-embed; drop them from the queue too.
+            for id in &ids {
+                self.vectors.dequeue_reembed(*id).await?;
+            }
+            total += docs.len();
+            emit_progress("engine", "reembedding", &format!("Re-embedded {} documents", total), Some(total));
+        }
+        Ok(total)
+    }
+
+    /// Forget a provider's sync cursor and soft-delete its documents.
+    pub async fn reset_provider(&self, provider_id: &str) -> Result<()> {
+        info!("Resetting provider: {}", provider_id);
+        // 1. Delete sync cursor (prevents delta sync)
+        self.documents.set_sync_cursor(provider_id, "").await?;
+        // 2. Soft-delete documents from this provider
+        let deleted = self.documents.delete_documents_by_source(provider_id).await?;
+        info!("Tombstoned {} documents from {}", deleted, provider_id);
+        // 3. Scrub orphaned embeddings
+        self.vectors.scrub_orphaned_embeddings().await?;
+        Ok(())
+    }
+
+    /// See [`Core::purge_tombstones`](crate::Core::purge_tombstones).
+    pub async fn purge_tombstones(&self, batch_size: usize) -> Result<usize> {
+        let mut total = 0usize;
+        loop {
+            let tombstones = self.documents.pending_tombstones(batch_size).await?;
+            if tombstones.is_empty() {
+                break;
+            }
+            let ids: Vec<i64> = tombstones.iter().map(|t| t.doc_id).collect();
+            // Only what was purged: a document revived by a sync meanwhile
+            // keeps its fresh embedding and edges
+            let purged = self.documents.purge_tombstones(&ids).await?;
+            self.vectors.delete_embeddings(&purged).await?;
+            self.graph.delete_edge_sources_for_documents(&purged).await?;
+
+            let live: HashMap<String, i64> = self
+                .documents
+                .document_counts_by_source()
+                .await?
+                .into_iter()
+                .collect();
+            let mut sources: Vec<&str> = tombstones.iter().map(|t| t.source.as_str()).collect();
+            sources.sort_unstable();
+            sources.dedup();
+            for source in sources {
+                if live.get(source).copied().unwrap_or(0) == 0 {
+                    let edges = self.graph.delete_edges_by_provider(source).await?;
+                    if edges > 0 {
+                        info!("Removed {} graph edges for deleted source {}", edges, source);
+                    }
+                }
+            }
+
+            total += purged.len();
+        }
+        Ok(total)
+    }
+
+    /// See [`Core::enforce_source_limits`](crate::Core::enforce_source_limits).
+    pub async fn enforce_source_limits(&self, limits: &HashMap<String, SourceLimit>) -> Result<usize> {
+        if limits.is_empty() {
+            return Ok(0);
+        }
+        let keep: HashSet<i64> = self
+            .graph
+            .document_ids_in_ring(Ring::Core)
+            .await?
+            .into_iter()
+            .collect();
+        let mut total = 0;
+        for (source, limit) in limits {
+            let evicted = self
+                .documents
+                .evict_oldest(source, limit.max_documents, limit.max_bytes, &keep)
+                .await?;
+            if !evicted.is_empty() {
+                info!("[LIMITS] Evicted {} documents from {} to stay within its limit", evicted.len(), source);
+                total += evicted.len();
+            }
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use minna_graph::{EdgeSource, ExtractedEdge, GraphEdge, GraphNode, NodeRef};
+    use minna_ingest::{Attachment, DocumentReference, KeywordHit, Tombstone};
+    use minna_vector::{cosine_similarity, HashEmbedder};
+
+    /// A second backend, to check `Storage` needs nothing SQLite-specific.
+    #[derive(Default)]
+    struct MemoryBackend {
+        documents: Mutex<Vec<(Document, bool)>>,
+        cursors: Mutex<HashMap<String, String>>,
+        embeddings: Mutex<HashMap<i64, Vec<f32>>>,
+        reembed: Mutex<Vec<i64>>,
+        /// Provider and source documents of each edge.
+        edges: Mutex<Vec<(String, Vec<i64>)>>,
+        core_documents: Mutex<Vec<i64>>,
+    }
+
+    impl MemoryBackend {
+        fn live(&self) -> Vec<Document> {
+            let documents = self.documents.lock().unwrap();
+            documents.iter().filter(|(_, deleted)| !deleted).map(|(doc, _)| doc.clone()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl DocumentStore for MemoryBackend {
+        async fn upsert_document(&self, doc: &Document) -> Result<i64> {
+            let mut documents = self.documents.lock().unwrap();
+            if let Some((existing, deleted)) = documents.iter_mut().find(|(d, _)| d.uri == doc.uri) {
+                let id = existing.id;
+                *existing = Document { id, ..doc.clone() };
+                *deleted = false;
+                return Ok(id.unwrap());
+            }
+            let id = documents.len() as i64 + 1;
+            documents.push((Document { id: Some(id), ..doc.clone() }, false));
+            Ok(id)
+        }
+
+        async fn upsert_documents_batch(&self, docs: &[Document]) -> Result<Vec<i64>> {
+            let mut ids = Vec::new();
+            for doc in docs {
+                ids.push(self.upsert_document(doc).await?);
+            }
+            Ok(ids)
+        }
+
+        fn prepare<'d>(&self, doc: &'d Document) -> Cow<'d, Document> {
+            Cow::Borrowed(doc)
+        }
+
+        async fn get_document_by_uri(&self, uri: &str) -> Result<Option<Document>> {
+            Ok(self.live().into_iter().find(|doc| doc.uri == uri))
+        }
+
+        async fn fetch_documents_by_ids(&self, ids: &[i64]) -> Result<Vec<Document>> {
+            Ok(self.live().into_iter().filter(|doc| ids.contains(&doc.id.unwrap())).collect())
+        }
+
+        async fn document_references(&self, _doc_id: i64) -> Result<Vec<DocumentReference>> {
+            Ok(Vec::new())
+        }
+
+        async fn search_query(&self, query: &SearchQuery, limit: usize) -> Result<Vec<KeywordHit>> {
+            Ok(self
+                .live()
+                .into_iter()
+                .filter(|doc| query.terms.iter().any(|term| doc.body.to_lowercase().contains(&term.to_lowercase())))
+                .take(limit)
+                .map(|doc| KeywordHit { snippet: doc.body.clone(), document: doc })
+                .collect())
+        }
+
+        async fn add_tag(&self, _doc_id: i64, _tag: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn store_attachment(
+            &self,
+            _doc_id: i64,
+            _name: &str,
+            _mime_type: &str,
+            _data: &[u8],
+        ) -> Result<Attachment> {
+            anyhow::bail!("attachments aren't supported")
+        }
+
+        async fn get_sync_cursor(&self, provider: &str) -> Result<Option<String>> {
+            Ok(self.cursors.lock().unwrap().get(provider).cloned())
+        }
+
+        async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()> {
+            self.cursors.lock().unwrap().insert(provider.to_string(), cursor.to_string());
+            Ok(())
+        }
+
+        async fn mark_archived(
+            &self,
+            _provider: &str,
+            _resource_id: &str,
+            _name: Option<&str>,
+            _uri_prefix: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn unmark_archived(&self, _provider: &str, _resource_id: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete_documents_by_source(&self, source: &str) -> Result<u64> {
+            let mut deleted = 0;
+            for (doc, is_deleted) in self.documents.lock().unwrap().iter_mut() {
+                if doc.source == source && !*is_deleted {
+                    *is_deleted = true;
+                    deleted += 1;
+                }
+            }
+            Ok(deleted)
+        }
+
+        async fn pending_tombstones(&self, limit: usize) -> Result<Vec<Tombstone>> {
+            let documents = self.documents.lock().unwrap();
+            Ok(documents
+                .iter()
+                .filter(|(_, deleted)| *deleted)
+                .take(limit)
+                .map(|(doc, _)| Tombstone {
+                    doc_id: doc.id.unwrap(),
+                    uri: doc.uri.clone(),
+                    source: doc.source.clone(),
+                    deleted_at: Utc::now(),
+                })
+                .collect())
+        }
+
+        async fn purge_tombstones(&self, doc_ids: &[i64]) -> Result<Vec<i64>> {
+            let mut documents = self.documents.lock().unwrap();
+            let purged = documents
+                .iter()
+                .filter(|(doc, deleted)| *deleted && doc_ids.contains(&doc.id.unwrap()))
+                .map(|(doc, _)| doc.id.unwrap())
+                .collect::<Vec<_>>();
+            documents.retain(|(doc, _)| !purged.contains(&doc.id.unwrap()));
+            Ok(purged)
+        }
+
+        async fn document_counts_by_source(&self) -> Result<Vec<(String, i64)>> {
+            let mut counts: HashMap<String, i64> = HashMap::new();
+            for doc in self.live() {
+                *counts.entry(doc.source).or_default() += 1;
+            }
+            Ok(counts.into_iter().collect())
+        }
+
+        async fn evict_oldest(
+            &self,
+            source: &str,
+            max_documents: Option<u64>,
+            _max_bytes: Option<u64>,
+            keep: &HashSet<i64>,
+        ) -> Result<Vec<i64>> {
+            let mut documents = self.documents.lock().unwrap();
+            let mut live: Vec<&mut (Document, bool)> = documents
+                .iter_mut()
+                .filter(|(doc, deleted)| doc.source == source && !deleted)
+                .collect();
+            live.sort_by_key(|(doc, _)| doc.updated_at);
+            let over = live.len().saturating_sub(max_documents.unwrap_or(u64::MAX) as usize);
+            let mut evicted = Vec::new();
+            for (doc, deleted) in live {
+                if evicted.len() == over {
+                    break;
+                }
+                if !keep.contains(&doc.id.unwrap()) {
+                    *deleted = true;
+                    evicted.push(doc.id.unwrap());
+                }
+            }
+            Ok(evicted)
+        }
+    }
+
+    #[async_trait]
+    impl VectorIndex for MemoryBackend {
+        async fn upsert_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()> {
+            self.embeddings.lock().unwrap().insert(doc_id, embedding.to_vec());
+            Ok(())
+        }
+
+        async fn queue_reembed(&self, doc_id: i64) -> Result<()> {
+            self.reembed.lock().unwrap().push(doc_id);
+            Ok(())
+        }
+
+        async fn delete_embeddings(&self, doc_ids: &[i64]) -> Result<()> {
+            self.embeddings.lock().unwrap().retain(|id, _| !doc_ids.contains(id));
+            Ok(())
+        }
+
+        async fn get_embedding(&self, doc_id: i64) -> Result<Option<Vec<f32>>> {
+            Ok(self.embeddings.lock().unwrap().get(&doc_id).cloned())
+        }
+
+        async fn record_model(&self, _doc_id: i64, _model: &str, _dims: usize) -> Result<()> {
+            Ok(())
+        }
+
+        async fn upsert_chunk_embeddings(&self, _doc_id: i64, _embeddings: &[Vec<f32>]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn search_with_embedding(
+            &self,
+            query_embedding: &[f32],
+            limit: usize,
+        ) -> Result<Vec<(i64, f32)>> {
+            let mut hits: Vec<(i64, f32)> = self
+                .embeddings
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(id, embedding)| (*id, cosine_similarity(query_embedding, embedding)))
+                .filter(|(_, similarity)| *similarity > 0.0)
+                .collect();
+            hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+            hits.truncate(limit);
+            Ok(hits)
+        }
+
+        async fn pending_reembed(&self, limit: usize) -> Result<Vec<i64>> {
+            Ok(self.reembed.lock().unwrap().iter().take(limit).copied().collect())
+        }
+
+        async fn dequeue_reembed(&self, doc_id: i64) -> Result<()> {
+            self.reembed.lock().unwrap().retain(|id| *id != doc_id);
+            Ok(())
+        }
+
+        async fn scrub_orphaned_embeddings(&self) -> Result<u64> {
+            let live: HashSet<i64> = self.live().iter().filter_map(|doc| doc.id).collect();
+            let mut embeddings = self.embeddings.lock().unwrap();
+            let before = embeddings.len();
+            embeddings.retain(|id, _| live.contains(id));
+            Ok((before - embeddings.len()) as u64)
+        }
+    }
+
+    #[async_trait]
+    impl GraphBackend for MemoryBackend {
+        async fn upsert_node(&self, node_ref: &NodeRef) -> Result<String> {
+            Ok(node_ref.canonical_id())
+        }
+
+        async fn upsert_edge(&self, edge: &ExtractedEdge) -> Result<i64> {
+            let mut edges = self.edges.lock().unwrap();
+            edges.push((edge.from.provider.clone(), Vec::new()));
+            Ok(edges.len() as i64)
+        }
+
+        async fn upsert_edge_from_document(&self, edge: &ExtractedEdge, doc_id: i64) -> Result<i64> {
+            let mut edges = self.edges.lock().unwrap();
+            edges.push((edge.from.provider.clone(), vec![doc_id]));
+            Ok(edges.len() as i64)
+        }
+
+        async fn get_node(&self, _id: &str) -> Result<Option<GraphNode>> {
+            Ok(None)
+        }
+
+        async fn edges_from(&self, _node_id: &str) -> Result<Vec<GraphEdge>> {
+            Ok(Vec::new())
+        }
+
+        async fn edges_to(&self, _node_id: &str) -> Result<Vec<GraphEdge>> {
+            Ok(Vec::new())
+        }
+
+        async fn edge_sources(&self, _edge_id: i64, _limit: usize) -> Result<Vec<EdgeSource>> {
+            Ok(Vec::new())
+        }
+
+        async fn set_node_email(&self, _node_id: &str, _email: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn set_node_avatar(&self, _node_id: &str, _avatar_url: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete_edge_sources_for_documents(&self, doc_ids: &[i64]) -> Result<u64> {
+            let mut removed = 0;
+            for (_, sources) in self.edges.lock().unwrap().iter_mut() {
+                let before = sources.len();
+                sources.retain(|id| !doc_ids.contains(id));
+                removed += (before - sources.len()) as u64;
+            }
+            Ok(removed)
+        }
+
+        async fn delete_edges_by_provider(&self, provider: &str) -> Result<u64> {
+            let mut edges = self.edges.lock().unwrap();
+            let before = edges.len();
+            edges.retain(|(edge_provider, _)| edge_provider != provider);
+            Ok((before - edges.len()) as u64)
+        }
+
+        async fn document_ids_in_ring(&self, ring: Ring) -> Result<Vec<i64>> {
+            Ok(match ring {
+                Ring::Core => self.core_documents.lock().unwrap().clone(),
+                _ => Vec::new(),
+            })
+        }
+    }
+
+    /// Embeds like the hash embedder but counts as the stand-in model.
+    struct FallbackEmbedder;
+
+    #[async_trait]
+    impl Embedder for FallbackEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            HashEmbedder { dims: 8 }.embed(text).await
+        }
+
+        fn is_fallback(&self) -> bool {
+            true
+        }
+    }
+
+    fn memory_storage() -> (Arc<MemoryBackend>, Storage) {
+        let backend = Arc::new(MemoryBackend::default());
+        let storage = Storage::new(backend.clone(), backend.clone(), backend.clone());
+        (backend, storage)
+    }
+
+    fn doc(uri: &str, source: &str, body: &str) -> Document {
+        Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_index_and_search() {
+        let (backend, storage) = memory_storage();
+        let embedder = HashEmbedder { dims: 64 };
+        let launch = storage
+            .index_document(&embedder, &doc("slack://1", "slack", "launch plan for friday"))
+            .await
+            .unwrap();
+        storage
+            .index_document(&embedder, &doc("slack://2", "slack", "lunch order"))
+            .await
+            .unwrap();
+        assert!(backend.embeddings.lock().unwrap().contains_key(&launch));
+
+        let hits = storage.search(&embedder, "launch plan", 5).await.unwrap();
+        assert_eq!(hits[0].document.id, Some(launch));
+        // Both rankings agree, so the top hit scores above either alone
+        assert!(hits[0].score > 1.0 / (RRF_K + 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_reembed_pending() {
+        let (backend, storage) = memory_storage();
+        let id = storage
+            .index_document(&FallbackEmbedder, &doc("linear://1", "linear", "fix the build"))
+            .await
+            .unwrap();
+        assert_eq!(backend.embeddings.lock().unwrap()[&id].len(), 8);
+        assert_eq!(*backend.reembed.lock().unwrap(), vec![id]);
+
+        let reembedded = storage.reembed_pending(&HashEmbedder { dims: 64 }, 10).await.unwrap();
+        assert_eq!(reembedded, 1);
+        assert_eq!(backend.embeddings.lock().unwrap()[&id].len(), 64);
+        assert!(backend.reembed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_purge() {
+        let (backend, storage) = memory_storage();
+        let embedder = HashEmbedder { dims: 16 };
+        let slack = storage
+            .index_document(&embedder, &doc("slack://1", "slack", "standup notes"))
+            .await
+            .unwrap();
+        let linear = storage
+            .index_document(&embedder, &doc("linear://1", "linear", "standup ticket"))
+            .await
+            .unwrap();
+        backend.edges.lock().unwrap().push(("slack".to_string(), vec![slack]));
+        backend.edges.lock().unwrap().push(("linear".to_string(), vec![linear]));
+        storage.documents.set_sync_cursor("slack", "cursor-1").await.unwrap();
+
+        storage.reset_provider("slack").await.unwrap();
+        assert_eq!(storage.documents.get_sync_cursor("slack").await.unwrap().as_deref(), Some(""));
+        assert!(!backend.embeddings.lock().unwrap().contains_key(&slack));
+
+        assert_eq!(storage.purge_tombstones(10).await.unwrap(), 1);
+        assert!(backend.pending_tombstones(10).await.unwrap().is_empty());
+        let edges = backend.edges.lock().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0], ("linear".to_string(), vec![linear]));
+    }
+
+    #[tokio::test]
+    async fn test_enforce_source_limits_keeps_core_documents() {
+        let (backend, storage) = memory_storage();
+        let embedder = HashEmbedder { dims: 16 };
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let mut slack = doc(&format!("slack://{}", i), "slack", "message");
+            slack.updated_at = Utc::now() - chrono::Duration::days(3 - i);
+            ids.push(storage.index_document(&embedder, &slack).await.unwrap());
+        }
+        // The oldest involves the user
+        backend.core_documents.lock().unwrap().push(ids[0]);
+
+        let limits = HashMap::from([(
+            "slack".to_string(),
+            SourceLimit { max_documents: Some(2), max_bytes: None },
+        )]);
+        assert_eq!(storage.enforce_source_limits(&limits).await.unwrap(), 1);
+        let live: Vec<i64> = backend.live().iter().filter_map(|doc| doc.id).collect();
+        assert_eq!(live, vec![ids[0], ids[2]]);
+    }
+}
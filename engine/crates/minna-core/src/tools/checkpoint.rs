@@ -217,6 +217,37 @@ impl LoadQuery {
     }
 }
 
+/// A checkpoint found by [`CheckpointStore::search`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointMatch {
+    pub checkpoint: Checkpoint,
+    /// Occurrences of the query terms; higher is better.
+    pub score: usize,
+    /// The first line mentioning a query term.
+    pub snippet: String,
+}
+
+/// Lines that changed in one section between two versions of a checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionDiff {
+    /// Section heading, e.g. "Next Steps".
+    pub section: String,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+/// What changed between two versions of the same checkpoint title.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub title: String,
+    pub from: u32,
+    pub to: u32,
+    pub from_created_at: DateTime<Utc>,
+    pub to_created_at: DateTime<Utc>,
+    /// Only the sections that changed.
+    pub sections: Vec<SectionDiff>,
+}
+
 /// Manages checkpoint storage and retrieval.
 pub struct CheckpointStore {
     /// Base directory for checkpoint storage (e.g., ~/.minna/vault/checkpoints/)
@@ -430,6 +461,175 @@ impl CheckpointStore {
 
         Ok(checkpoints)
     }
+
+    /// Delete one version of a checkpoint, or every version of its title.
+    ///
+    /// Returns how many checkpoint files were removed.
+    pub fn delete(&self, title: &str, version: Option<u32>) -> Result<usize> {
+        if !self.base_dir.exists() {
+            return Ok(0);
+        }
+
+        let prefix = format!("{}_v", slug::slugify(title));
+        let mut deleted = 0;
+        for entry in fs::read_dir(&self.base_dir)?.flatten() {
+            let filename = entry.file_name();
+            let Some(file_version) = filename
+                .to_string_lossy()
+                .strip_prefix(&prefix)
+                .and_then(|s| s.strip_suffix(".md"))
+                .and_then(|s| s.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            if version.is_some_and(|version| version != file_version) {
+                continue;
+            }
+
+            let path = entry.path();
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to delete checkpoint: {:?}", path))?;
+            debug!("Deleted checkpoint: {:?}", path);
+            deleted += 1;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Full-text search across all checkpoints. Every whitespace-separated
+    /// term of `query` must appear (case-insensitively) in the title or a
+    /// section; the best matches come first, newest first among equals.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<CheckpointMatch>> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matches = Vec::new();
+        for checkpoint in self.list(None)? {
+            let lines: Vec<&str> = std::iter::once(checkpoint.title.as_str())
+                .chain(checkpoint.summary.lines())
+                .chain(checkpoint.current_task.lines())
+                .chain(checkpoint.next_steps.lines())
+                .chain(checkpoint.files.iter().map(String::as_str))
+                .collect();
+            let text = lines.join("\n").to_lowercase();
+            if !terms.iter().all(|term| text.contains(term.as_str())) {
+                continue;
+            }
+
+            let score = terms.iter().map(|term| text.matches(term.as_str()).count()).sum();
+            let snippet = lines
+                .iter()
+                .skip(1)
+                .find(|line| {
+                    let line = line.to_lowercase();
+                    terms.iter().any(|term| line.contains(term.as_str()))
+                })
+                .unwrap_or(&lines[0])
+                .trim()
+                .to_string();
+            matches.push(CheckpointMatch { checkpoint, score, snippet });
+        }
+
+        // `list` is newest first and the sort is stable
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    /// Diff two versions of a checkpoint title, section by section.
+    ///
+    /// `to` defaults to the latest version and `from` to the version saved
+    /// before `to`.
+    pub fn diff(&self, title: &str, from: Option<u32>, to: Option<u32>) -> Result<CheckpointDiff> {
+        let mut versions: Vec<u32> = self.list(Some(title))?.iter().map(|c| c.version).collect();
+        versions.sort_unstable();
+        let to = match to {
+            Some(to) => to,
+            None => *versions.last().ok_or_else(|| anyhow!("no checkpoint titled {}", title))?,
+        };
+        let from = match from {
+            Some(from) => from,
+            None => versions
+                .iter()
+                .rev()
+                .find(|&&version| version < to)
+                .copied()
+                .ok_or_else(|| anyhow!("no version of {} before v{}", title, to))?,
+        };
+
+        let load = |version: u32| {
+            self.load(LoadQuery::exact(title, version))?
+                .ok_or_else(|| anyhow!("no checkpoint {} v{}", title, version))
+        };
+        let (old, new) = (load(from)?, load(to)?);
+
+        let sections = [
+            ("Summary", old.summary.as_str(), new.summary.as_str()),
+            ("Current Task", old.current_task.as_str(), new.current_task.as_str()),
+            ("Next Steps", old.next_steps.as_str(), new.next_steps.as_str()),
+        ]
+        .into_iter()
+        .map(|(section, old, new)| {
+            let old: Vec<&str> = old.lines().collect();
+            let new: Vec<&str> = new.lines().collect();
+            diff_lines(section, &old, &new)
+        })
+        .chain(std::iter::once(diff_lines(
+            "Active Files",
+            &old.files.iter().map(String::as_str).collect::<Vec<_>>(),
+            &new.files.iter().map(String::as_str).collect::<Vec<_>>(),
+        )))
+        .filter(|section| !section.removed.is_empty() || !section.added.is_empty())
+        .collect();
+
+        Ok(CheckpointDiff {
+            title: new.title,
+            from,
+            to,
+            from_created_at: old.created_at,
+            to_created_at: new.created_at,
+            sections,
+        })
+    }
+}
+
+/// Lines of `old` and `new` outside their longest common subsequence.
+fn diff_lines(section: &str, old: &[&str], new: &[&str]) -> SectionDiff {
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = SectionDiff {
+        section: section.to_string(),
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.removed.push(old[i].to_string());
+            i += 1;
+        } else {
+            diff.added.push(new[j].to_string());
+            j += 1;
+        }
+    }
+    diff.removed.extend(old[i..].iter().map(|line| line.to_string()));
+    diff.added.extend(new[j..].iter().map(|line| line.to_string()));
+    diff
 }
 
 #[cfg(test)]
@@ -546,4 +746,95 @@ mod tests {
         assert_eq!(loaded.version, 2);
         assert_eq!(loaded.summary, "Version 2");
     }
+
+    #[test]
+    fn test_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = CheckpointStore::new(temp_dir.path());
+
+        for title in ["Deletable", "Deletable", "Deletable", "Kept"] {
+            let checkpoint = Checkpoint::new(title, "Summary", "Task", "Steps", vec![], "manual");
+            store.save(checkpoint).unwrap();
+        }
+
+        assert_eq!(store.delete("Deletable", Some(2)).unwrap(), 1);
+        assert_eq!(store.delete("Deletable", Some(2)).unwrap(), 0);
+        let versions: Vec<u32> = store
+            .list(Some("Deletable"))
+            .unwrap()
+            .iter()
+            .map(|checkpoint| checkpoint.version)
+            .collect();
+        assert_eq!(versions.len(), 2);
+        assert!(!versions.contains(&2));
+
+        assert_eq!(store.delete("Deletable", None).unwrap(), 2);
+        assert!(store.list(Some("Deletable")).unwrap().is_empty());
+        assert_eq!(store.list(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = CheckpointStore::new(temp_dir.path());
+
+        let saves = [
+            ("Auth", "Moving login to OAuth", "- Test OAuth refresh\n- Remove OAuth shim"),
+            ("Billing", "Invoice rounding", "- Check OAuth scopes"),
+            ("Docs", "README pass", "- Publish"),
+        ];
+        for (title, summary, next_steps) in saves {
+            let checkpoint = Checkpoint::new(title, summary, "Task", next_steps, vec![], "manual");
+            store.save(checkpoint).unwrap();
+        }
+
+        let found = store.search("oauth", 10).unwrap();
+        let titles: Vec<&str> = found.iter().map(|m| m.checkpoint.title.as_str()).collect();
+        assert_eq!(titles, vec!["Auth", "Billing"]);
+        assert_eq!(found[0].score, 3);
+        assert_eq!(found[0].snippet, "Moving login to OAuth");
+        assert_eq!(found[1].snippet, "- Check OAuth scopes");
+
+        // Every term must match
+        let found = store.search("OAuth invoice", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].checkpoint.title, "Billing");
+        assert!(store.search("  ", 10).unwrap().is_empty());
+        assert_eq!(store.search("oauth", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_diff() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = CheckpointStore::new(temp_dir.path());
+
+        let versions = [
+            ("Started", "- Write parser\n- Add tests", vec!["src/parse.rs"]),
+            ("Parser done", "- Add tests\n- Wire CLI", vec!["src/parse.rs", "src/cli.rs"]),
+            ("Parser done", "- Wire CLI", vec!["src/cli.rs"]),
+        ];
+        for (summary, next_steps, files) in versions {
+            let files = files.into_iter().map(str::to_string).collect();
+            let checkpoint =
+                Checkpoint::new("Parser", summary, "Task", next_steps, files, "manual");
+            store.save(checkpoint).unwrap();
+        }
+
+        let diff = store.diff("Parser", Some(1), Some(2)).unwrap();
+        assert_eq!((diff.from, diff.to), (1, 2));
+        let sections: Vec<&str> = diff.sections.iter().map(|s| s.section.as_str()).collect();
+        assert_eq!(sections, vec!["Summary", "Next Steps", "Active Files"]);
+        assert_eq!(diff.sections[1].removed, vec!["- Write parser"]);
+        assert_eq!(diff.sections[1].added, vec!["- Wire CLI"]);
+        assert_eq!(diff.sections[2].added, vec!["src/cli.rs"]);
+
+        // Defaults to the latest version against the one before it, skipping gaps
+        store.delete("Parser", Some(2)).unwrap();
+        let diff = store.diff("Parser", None, None).unwrap();
+        assert_eq!((diff.from, diff.to), (1, 3));
+        assert_eq!(diff.sections[2].removed, vec!["src/parse.rs"]);
+
+        assert!(store.diff("Parser", None, Some(1)).is_err());
+        assert!(store.diff("Missing", None, None).is_err());
+    }
 }
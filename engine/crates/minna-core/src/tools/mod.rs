@@ -1,3 +1,3 @@
 pub mod checkpoint;
 
-pub use checkpoint::{Checkpoint, CheckpointStore, LoadQuery};
+pub use checkpoint::{Checkpoint, CheckpointDiff, CheckpointMatch, CheckpointStore, LoadQuery, SectionDiff};
@@ -0,0 +1,276 @@
+//! Emerging topics.
+//!
+//! Documents are bucketed into weeks (seven-day windows ending now) by when
+//! they were last updated. The current week's documents are grouped the way
+//! clusters are, linking any two that are similar enough, and each group is
+//! traced back through the earlier weeks by counting the documents similar to
+//! one of its members. A group much larger this week than in an average week
+//! before it is an emerging topic.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{clusters, cosine_similarity, Core};
+
+/// A topic must have this many times its average weekly documents.
+const MIN_GROWTH: f32 = 2.0;
+
+/// Documents listed per topic.
+const SAMPLE_DOCUMENTS: usize = 5;
+
+/// Settings for [`Core::trending_topics`].
+#[derive(Debug, Clone, Copy)]
+pub struct TrendOptions {
+    /// Weeks to look at, including the current one (default: 8)
+    pub weeks: usize,
+    /// Similarity that links two documents (default: 0.82, as for clusters)
+    pub min_similarity: f32,
+    /// Documents a topic needs this week (default: 3)
+    pub min_documents: usize,
+}
+
+impl Default for TrendOptions {
+    fn default() -> Self {
+        Self {
+            weeks: 8,
+            min_similarity: 0.82,
+            min_documents: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicTrend {
+    pub label: String,
+    /// Documents on the topic per week, oldest first; the last is this week.
+    pub weekly: Vec<usize>,
+    /// Average documents per week before this one.
+    pub baseline: f32,
+    /// This week's most recently updated documents on the topic.
+    pub doc_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendReport {
+    pub generated_at: DateTime<Utc>,
+    /// Start of each week, oldest first.
+    pub weeks: Vec<DateTime<Utc>>,
+    /// Emerging topics, fastest growing first.
+    pub topics: Vec<TopicTrend>,
+}
+
+/// A group of this week's documents and its weekly document counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmergingGroup {
+    pub doc_ids: Vec<i64>,
+    pub weekly: Vec<usize>,
+}
+
+impl EmergingGroup {
+    pub fn baseline(&self) -> f32 {
+        let earlier = &self.weekly[..self.weekly.len().saturating_sub(1)];
+        if earlier.is_empty() {
+            return 0.0;
+        }
+        earlier.iter().sum::<usize>() as f32 / earlier.len() as f32
+    }
+
+    fn growth(&self) -> f32 {
+        let current = self.weekly.last().copied().unwrap_or(0) as f32;
+        current / self.baseline().max(1.0)
+    }
+}
+
+/// Emerging groups among `(doc_id, week, embedding)` rows, where `week`
+/// counts from 0 (oldest) to `weeks - 1` (this week). Fastest growing first.
+pub fn emerging_groups(
+    documents: &[(i64, usize, Vec<f32>)],
+    weeks: usize,
+    min_similarity: f32,
+    min_documents: usize,
+) -> Vec<EmergingGroup> {
+    let Some(current_week) = weeks.checked_sub(1) else {
+        return Vec::new();
+    };
+    let current: Vec<&(i64, usize, Vec<f32>)> = documents
+        .iter()
+        .filter(|(_, week, _)| *week == current_week)
+        .collect();
+
+    // Link this week's documents and take the connected groups
+    let mut seen = HashSet::new();
+    let mut groups = Vec::new();
+    for start in 0..current.len() {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut members = vec![start];
+        let mut next = 0;
+        while next < members.len() {
+            let member = members[next];
+            next += 1;
+            for other in 0..current.len() {
+                if !seen.contains(&other)
+                    && cosine_similarity(&current[member].2, &current[other].2) >= min_similarity
+                {
+                    seen.insert(other);
+                    members.push(other);
+                }
+            }
+        }
+        if members.len() >= min_documents {
+            groups.push(members);
+        }
+    }
+
+    let mut emerging: Vec<EmergingGroup> = groups
+        .into_iter()
+        .map(|members| {
+            let mut weekly = vec![0; weeks];
+            weekly[current_week] = members.len();
+            for (_, week, embedding) in documents.iter().filter(|(_, week, _)| *week < current_week)
+            {
+                if members.iter().any(|member| {
+                    cosine_similarity(&current[*member].2, embedding) >= min_similarity
+                }) {
+                    weekly[*week] += 1;
+                }
+            }
+            EmergingGroup {
+                doc_ids: members.iter().map(|member| current[*member].0).collect(),
+                weekly,
+            }
+        })
+        .filter(|group| group.growth() >= MIN_GROWTH)
+        .collect();
+    emerging.sort_by(|a, b| {
+        b.growth()
+            .partial_cmp(&a.growth())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.doc_ids.len().cmp(&a.doc_ids.len()))
+    });
+    emerging
+}
+
+impl Core {
+    /// Topics that emerged this week: groups of documents updated in the
+    /// last seven days that had few similar documents in the weeks before.
+    pub async fn trending_topics(&self, options: TrendOptions) -> Result<TrendReport> {
+        let now = Utc::now();
+        let weeks = options.weeks.max(1);
+        let week_starts: Vec<DateTime<Utc>> = (0..weeks)
+            .map(|week| now - Duration::weeks((weeks - week) as i64))
+            .collect();
+        let times: HashMap<i64, DateTime<Utc>> = self
+            .ingest
+            .document_times_updated_since(week_starts[0])
+            .await?
+            .into_iter()
+            .collect();
+        let documents: Vec<(i64, usize, Vec<f32>)> = self
+            .vector
+            .list_embeddings()
+            .await?
+            .into_iter()
+            .filter_map(|stored| {
+                let updated_at = times.get(&stored.doc_id)?;
+                let age = ((now - *updated_at).num_seconds().max(0)
+                    / Duration::weeks(1).num_seconds()) as usize;
+                let week = weeks.checked_sub(age + 1)?;
+                Some((stored.doc_id, week, stored.embedding))
+            })
+            .collect();
+        let groups = emerging_groups(
+            &documents,
+            weeks,
+            options.min_similarity,
+            options.min_documents,
+        );
+
+        let mut texts = Vec::with_capacity(groups.len());
+        let mut samples = Vec::with_capacity(groups.len());
+        for group in &groups {
+            let mut docs = self.ingest.fetch_documents_by_ids(&group.doc_ids).await?;
+            docs.sort_by_key(|doc| std::cmp::Reverse(doc.updated_at));
+            texts.push(
+                docs.iter()
+                    .map(|doc| clusters::label_text(doc.title.as_deref(), &doc.body))
+                    .collect::<Vec<_>>(),
+            );
+            samples.push(
+                docs.iter()
+                    .filter_map(|doc| doc.id)
+                    .take(SAMPLE_DOCUMENTS)
+                    .collect(),
+            );
+        }
+        let topics = groups
+            .iter()
+            .zip(clusters::keyword_labels(&texts))
+            .zip(samples)
+            .map(|((group, label), doc_ids)| TopicTrend {
+                label,
+                weekly: group.weekly.clone(),
+                baseline: group.baseline(),
+                doc_ids,
+            })
+            .collect();
+        Ok(TrendReport {
+            generated_at: now,
+            weeks: week_starts,
+            topics,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(doc_id: i64, week: usize, direction: usize) -> (i64, usize, Vec<f32>) {
+        let mut embedding = vec![0.0; 4];
+        embedding[direction] = 1.0;
+        embedding[3] = 0.01 * (doc_id % 5) as f32;
+        (doc_id, week, embedding)
+    }
+
+    #[test]
+    fn test_new_topic_emerges() {
+        let documents = vec![
+            // Two a week, three this week
+            row(1, 0, 0),
+            row(2, 0, 0),
+            row(3, 1, 0),
+            row(4, 1, 0),
+            row(5, 2, 0),
+            row(6, 2, 0),
+            row(7, 3, 0),
+            row(8, 3, 0),
+            row(9, 3, 0),
+            // Rare until this week
+            row(10, 0, 1),
+            row(11, 3, 1),
+            row(12, 3, 1),
+            row(13, 3, 1),
+            row(14, 3, 1),
+            // Too small
+            row(15, 3, 2),
+        ];
+        let groups = emerging_groups(&documents, 4, 0.8, 3);
+        assert_eq!(groups.len(), 1, "groups: {:?}", groups);
+        assert_eq!(groups[0].doc_ids, vec![11, 12, 13, 14]);
+        assert_eq!(groups[0].weekly, vec![1, 0, 0, 4]);
+        assert!((groups[0].baseline() - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_steady_topic_is_not_emerging() {
+        let documents: Vec<_> = (0..4)
+            .flat_map(|week| (0..3).map(move |n| row((week * 3 + n) as i64, week, 0)))
+            .collect();
+        assert!(emerging_groups(&documents, 4, 0.8, 3).is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+//! Workspace context files for agents without MCP.
+//!
+//! Some coding agents only read files from the repository they work in. For
+//! them Minna writes a `MINNA.md` (or `MINNA.json`) into each configured repo
+//! root, summarizing the active projects, the people in Ring 1, and the indexed
+//! documents most relevant to that repo. Roots come from `MINNA_CONTEXT_ROOTS`
+//! (a `:`-separated list of directories); `MINNA_CONTEXT_FORMAT=json` switches
+//! the format. Files are only rewritten when their content changes.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use minna_graph::{NodeType, Ring};
+use serde::{Deserialize, Serialize};
+
+use crate::digest::DIGEST_SOURCE;
+use crate::Core;
+
+/// Window for "active" projects.
+const ACTIVE_DAYS: i64 = 14;
+const MAX_PROJECTS: usize = 8;
+const MAX_PEOPLE: usize = 10;
+const MAX_DOCUMENTS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContextFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl ContextFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "MINNA.md",
+            Self::Json => "MINNA.json",
+        }
+    }
+}
+
+/// Where and how to write workspace context files.
+#[derive(Debug, Clone, Default)]
+pub struct ContextFileConfig {
+    pub roots: Vec<PathBuf>,
+    pub format: ContextFormat,
+}
+
+impl ContextFileConfig {
+    pub fn from_env() -> Self {
+        let roots = std::env::var_os("MINNA_CONTEXT_ROOTS")
+            .map(|roots| {
+                std::env::split_paths(&roots)
+                    .filter(|root| !root.as_os_str().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let format = match std::env::var("MINNA_CONTEXT_FORMAT") {
+            Ok(format) if format.eq_ignore_ascii_case("json") => ContextFormat::Json,
+            _ => ContextFormat::Markdown,
+        };
+        Self { roots, format }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.roots.is_empty()
+    }
+}
+
+/// A document linked from a context file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextLink {
+    pub title: String,
+    pub uri: String,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a context file says about one repo.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceContext {
+    /// Repo directory name, also used as its tag.
+    pub workspace: String,
+    /// Tags ranked by recent activity, with the number of updated documents.
+    pub active_projects: Vec<(String, i64)>,
+    pub key_people: Vec<String>,
+    pub documents: Vec<ContextLink>,
+}
+
+impl WorkspaceContext {
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Minna context: {}\n\n\
+            <!-- Generated by Minna from your synced tools; edits are overwritten. -->\n\n\
+            ## Active projects\n\n",
+            self.workspace
+        );
+        if self.active_projects.is_empty() {
+            out.push_str("No recent activity.\n");
+        } else {
+            for (project, count) in &self.active_projects {
+                out.push_str(&format!("- {} ({} recently updated)\n", project, count));
+            }
+        }
+
+        out.push_str("\n## Key people\n\n");
+        if self.key_people.is_empty() {
+            out.push_str("No one in Ring 1 yet.\n");
+        } else {
+            for person in &self.key_people {
+                out.push_str(&format!("- {}\n", person));
+            }
+        }
+
+        out.push_str("\n## Relevant documents\n\n");
+        if self.documents.is_empty() {
+            out.push_str("Nothing indexed for this workspace yet.\n");
+        } else {
+            for doc in &self.documents {
+                out.push_str(&format!(
+                    "- [{}]({}) ({}, {})\n",
+                    doc.title,
+                    doc.uri,
+                    doc.source,
+                    doc.updated_at.format("%Y-%m-%d")
+                ));
+            }
+        }
+        out
+    }
+
+    pub fn render(&self, format: ContextFormat) -> Result<String> {
+        match format {
+            ContextFormat::Markdown => Ok(self.to_markdown()),
+            ContextFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+        }
+    }
+}
+
+/// Name a repo root goes by: its directory name.
+fn workspace_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.display().to_string())
+}
+
+impl Core {
+    /// Build the context for the repo at `root`. Documents tagged with the
+    /// repo's name come first, then keyword matches on it.
+    pub async fn workspace_context(&self, root: &Path) -> Result<WorkspaceContext> {
+        let workspace = workspace_name(root);
+        let active_projects = self
+            .ingest
+            .tag_counts_updated_since(Utc::now() - Duration::days(ACTIVE_DAYS), MAX_PROJECTS)
+            .await?;
+
+        let mut key_people = Vec::new();
+        for node_id in self.graph.nodes_in_ring(Ring::One).await? {
+            let Some(node) = self.graph.get_node(&node_id).await? else {
+                continue;
+            };
+            if node.node_type == NodeType::User {
+                key_people.push(node.display_name.unwrap_or(node.external_id));
+            }
+        }
+        key_people.sort();
+        key_people.dedup();
+        key_people.truncate(MAX_PEOPLE);
+
+        let mut docs = self.ingest.list_by_tag(&workspace, MAX_DOCUMENTS).await?;
+        if docs.len() < MAX_DOCUMENTS {
+            for hit in self.ingest.search_keyword(&workspace, MAX_DOCUMENTS).await? {
+                if !docs.iter().any(|doc| doc.id == hit.document.id) {
+                    docs.push(hit.document);
+                }
+            }
+        }
+        let documents = docs
+            .into_iter()
+            .filter(|doc| doc.source != DIGEST_SOURCE)
+            .take(MAX_DOCUMENTS)
+            .map(|doc| ContextLink {
+                title: doc.title.unwrap_or_else(|| doc.uri.clone()),
+                uri: doc.uri,
+                source: doc.source,
+                updated_at: doc.updated_at,
+            })
+            .collect();
+
+        Ok(WorkspaceContext {
+            workspace,
+            active_projects,
+            key_people,
+            documents,
+        })
+    }
+
+    /// Write a context file into each configured root whose content changed.
+    /// Returns the number of files written; missing roots are skipped.
+    pub async fn write_context_files(&self, config: &ContextFileConfig) -> Result<usize> {
+        let mut written = 0;
+        for root in &config.roots {
+            if !root.is_dir() {
+                continue;
+            }
+            let content = self.workspace_context(root).await?.render(config.format)?;
+            if write_if_changed(&root.join(config.format.file_name()), &content)? {
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Write `content` to `path` unless it already holds exactly that.
+fn write_if_changed(path: &Path, content: &str) -> Result<bool> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    std::fs::write(path, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_sections() {
+        let context = WorkspaceContext {
+            workspace: "minna-core".to_string(),
+            active_projects: vec![("minna-core".to_string(), 7)],
+            key_people: vec!["Ada".to_string()],
+            documents: vec![ContextLink {
+                title: "Sync engine rewrite".to_string(),
+                uri: "https://github.com/getminna/minna-core/pull/1".to_string(),
+                source: "github".to_string(),
+                updated_at: DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            }],
+        };
+        let md = context.to_markdown();
+        assert!(md.starts_with("# Minna context: minna-core"));
+        assert!(md.contains("- minna-core (7 recently updated)"));
+        assert!(md.contains("- Ada"));
+        assert!(md.contains(
+            "- [Sync engine rewrite](https://github.com/getminna/minna-core/pull/1) (github, 2026-01-05)"
+        ));
+
+        let json = context.render(ContextFormat::Json).unwrap();
+        let parsed: WorkspaceContext = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, context);
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("MINNA.md");
+        assert!(write_if_changed(&path, "a").unwrap());
+        assert!(!write_if_changed(&path, "a").unwrap());
+        assert!(write_if_changed(&path, "b").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "b");
+    }
+}
@@ -0,0 +1,128 @@
+//! Storage interface for the relationship graph.
+//!
+//! Providers write edges through [`GraphBackend`], so a backend other than
+//! the local SQLite database (e.g. Postgres for a team-shared deployment) can
+//! be plugged in. [`GraphStore`] is the SQLite implementation and the default.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::schema::{EdgeSource, ExtractedEdge, GraphEdge, GraphNode, NodeRef, Ring};
+use crate::storage::GraphStore;
+
+#[async_trait]
+pub trait GraphBackend: Send + Sync {
+    /// Upsert a node, returning its canonical ID.
+    async fn upsert_node(&self, node_ref: &NodeRef) -> Result<String>;
+
+    /// Upsert an edge and both of its nodes, returning the edge ID.
+    async fn upsert_edge(&self, edge: &ExtractedEdge) -> Result<i64>;
+
+    /// Upsert an edge and record `doc_id` as one of its sources.
+    async fn upsert_edge_from_document(&self, edge: &ExtractedEdge, doc_id: i64) -> Result<i64>;
+
+    /// Upsert many edges, returning their IDs in order. Backends should do
+    /// this in one transaction; the default upserts them one by one.
+    async fn upsert_edges(&self, edges: &[ExtractedEdge]) -> Result<Vec<i64>> {
+        let mut ids = Vec::with_capacity(edges.len());
+        for edge in edges {
+            ids.push(self.upsert_edge(edge).await?);
+        }
+        Ok(ids)
+    }
+
+    /// Upsert many edges extracted from one document, as
+    /// [`Self::upsert_edges`] does.
+    async fn upsert_edges_from_document(&self, edges: &[ExtractedEdge], doc_id: i64) -> Result<Vec<i64>> {
+        let mut ids = Vec::with_capacity(edges.len());
+        for edge in edges {
+            ids.push(self.upsert_edge_from_document(edge, doc_id).await?);
+        }
+        Ok(ids)
+    }
+
+    async fn get_node(&self, id: &str) -> Result<Option<GraphNode>>;
+
+    async fn edges_from(&self, node_id: &str) -> Result<Vec<GraphEdge>>;
+
+    async fn edges_to(&self, node_id: &str) -> Result<Vec<GraphEdge>>;
+
+    /// Documents an edge was extracted from, most recent first.
+    async fn edge_sources(&self, edge_id: i64, limit: usize) -> Result<Vec<EdgeSource>>;
+
+    /// Record a user's email address, for linking their accounts across
+    /// providers.
+    async fn set_node_email(&self, node_id: &str, email: &str) -> Result<()>;
+
+    /// Record a node's profile picture.
+    async fn set_node_avatar(&self, node_id: &str, avatar_url: &str) -> Result<()>;
+
+    /// Forget that edges were extracted from these documents, returning how
+    /// many provenance rows were removed.
+    async fn delete_edge_sources_for_documents(&self, doc_ids: &[i64]) -> Result<u64>;
+
+    /// Remove the edges a provider contributed, returning how many.
+    async fn delete_edges_by_provider(&self, provider: &str) -> Result<u64>;
+
+    /// Documents that edges touching a node in `ring` were extracted from.
+    async fn document_ids_in_ring(&self, ring: Ring) -> Result<Vec<i64>>;
+}
+
+#[async_trait]
+impl GraphBackend for GraphStore {
+    async fn upsert_node(&self, node_ref: &NodeRef) -> Result<String> {
+        GraphStore::upsert_node(self, node_ref).await
+    }
+
+    async fn upsert_edge(&self, edge: &ExtractedEdge) -> Result<i64> {
+        GraphStore::upsert_edge(self, edge).await
+    }
+
+    async fn upsert_edge_from_document(&self, edge: &ExtractedEdge, doc_id: i64) -> Result<i64> {
+        GraphStore::upsert_edge_from_document(self, edge, doc_id).await
+    }
+
+    async fn upsert_edges(&self, edges: &[ExtractedEdge]) -> Result<Vec<i64>> {
+        GraphStore::upsert_edges(self, edges).await
+    }
+
+    async fn upsert_edges_from_document(&self, edges: &[ExtractedEdge], doc_id: i64) -> Result<Vec<i64>> {
+        GraphStore::upsert_edges_from_document(self, edges, doc_id).await
+    }
+
+    async fn get_node(&self, id: &str) -> Result<Option<GraphNode>> {
+        GraphStore::get_node(self, id).await
+    }
+
+    async fn edges_from(&self, node_id: &str) -> Result<Vec<GraphEdge>> {
+        GraphStore::edges_from(self, node_id).await
+    }
+
+    async fn edges_to(&self, node_id: &str) -> Result<Vec<GraphEdge>> {
+        GraphStore::edges_to(self, node_id).await
+    }
+
+    async fn edge_sources(&self, edge_id: i64, limit: usize) -> Result<Vec<EdgeSource>> {
+        GraphStore::edge_sources(self, edge_id, limit).await
+    }
+
+    async fn set_node_email(&self, node_id: &str, email: &str) -> Result<()> {
+        GraphStore::set_node_email(self, node_id, email).await
+    }
+
+    async fn set_node_avatar(&self, node_id: &str, avatar_url: &str) -> Result<()> {
+        GraphStore::set_node_avatar(self, node_id, avatar_url).await
+    }
+
+    async fn delete_edge_sources_for_documents(&self, doc_ids: &[i64]) -> Result<u64> {
+        GraphStore::delete_edge_sources_for_documents(self, doc_ids).await
+    }
+
+    async fn delete_edges_by_provider(&self, provider: &str) -> Result<u64> {
+        GraphStore::delete_edges_by_provider(self, provider).await
+    }
+
+    async fn document_ids_in_ring(&self, ring: Ring) -> Result<Vec<i64>> {
+        GraphStore::document_ids_in_ring(self, ring).await
+    }
+}
@@ -0,0 +1,105 @@
+//! Centrality metrics for the relationship graph.
+//!
+//! Degree counts a node's distinct neighbours; weighted PageRank finds the
+//! people, channels and projects the rest of the graph revolves around. Both
+//! treat edges as undirected, like ring calculation. They are computed by
+//! [`RingEngine::recalculate_centrality`](crate::RingEngine::recalculate_centrality),
+//! stored on each [`GraphNode`](crate::GraphNode) and used to break ties
+//! between nodes at the same ring distance.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::schema::NodeCentrality;
+
+/// Probability of following a link rather than jumping to a random node.
+pub const DAMPING: f64 = 0.85;
+
+/// PageRank iterations before giving up on convergence.
+pub const MAX_ITERATIONS: usize = 50;
+
+/// Total change in scores below which PageRank has converged.
+const TOLERANCE: f64 = 1e-9;
+
+/// Degree and PageRank of every node in `links` (from, to, weight). Links
+/// between the same two nodes add up; self-loops and links without weight
+/// are ignored. Results are ordered by node ID.
+pub fn compute(links: &[(String, String, f64)]) -> Vec<NodeCentrality> {
+    let mut neighbors: BTreeMap<&str, HashMap<&str, f64>> = BTreeMap::new();
+    for (from, to, weight) in links {
+        if from == to || *weight <= 0.0 {
+            continue;
+        }
+        *neighbors.entry(from).or_default().entry(to).or_default() += weight;
+        *neighbors.entry(to).or_default().entry(from).or_default() += weight;
+    }
+    if neighbors.is_empty() {
+        return Vec::new();
+    }
+
+    let index: HashMap<&str, usize> = neighbors.keys().enumerate().map(|(i, id)| (*id, i)).collect();
+    let adjacency: Vec<Vec<(usize, f64)>> = neighbors
+        .values()
+        .map(|links| links.iter().map(|(id, weight)| (index[id], *weight)).collect())
+        .collect();
+    let strength: Vec<f64> = adjacency.iter().map(|links| links.iter().map(|(_, w)| w).sum()).collect();
+
+    let n = adjacency.len();
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let mut next = vec![(1.0 - DAMPING) / n as f64; n];
+        for (i, links) in adjacency.iter().enumerate() {
+            let share = DAMPING * rank[i] / strength[i];
+            for (j, weight) in links {
+                next[*j] += share * weight;
+            }
+        }
+        let change: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+
+    neighbors
+        .iter()
+        .zip(adjacency.iter().zip(rank))
+        .map(|((id, _), (links, pagerank))| NodeCentrality {
+            node_id: id.to_string(),
+            degree: links.len() as i64,
+            pagerank,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(from: &str, to: &str, weight: f64) -> (String, String, f64) {
+        (from.to_string(), to.to_string(), weight)
+    }
+
+    #[test]
+    fn test_star_centre_ranks_highest() {
+        let scores = compute(&[
+            link("hub", "a", 1.0),
+            link("hub", "b", 1.0),
+            link("c", "hub", 1.0),
+            link("a", "b", 0.5),
+            link("a", "a", 3.0),
+            link("a", "b", 0.5),
+        ]);
+        let ids: Vec<&str> = scores.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "hub"]);
+
+        let score = |id: &str| scores.iter().find(|s| s.node_id == id).unwrap().clone();
+        assert_eq!(score("hub").degree, 3);
+        assert_eq!(score("a").degree, 2);
+        assert!(scores.iter().all(|s| s.node_id == "hub" || s.pagerank < score("hub").pagerank));
+        assert!(score("c").pagerank < score("a").pagerank);
+        let total: f64 = scores.iter().map(|s| s.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+
+        assert!(compute(&[]).is_empty());
+    }
+}
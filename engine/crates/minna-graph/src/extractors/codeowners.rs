@@ -0,0 +1,195 @@
+//! CODEOWNERS extractor for Gravity Well.
+//!
+//! Parses a repository's CODEOWNERS file and extracts:
+//! - User/Team → File (OwnerOf) edges, one per owner of each path pattern
+//! - File → Project/Repo (BelongsTo) edges for each path pattern
+//!
+//! Path patterns become `File` nodes named after the pattern (`/docs/`,
+//! `*.rs`), so retrieval about code under a path can be routed to the
+//! people and teams who own it. `@user` owners map to GitHub users,
+//! `@org/team` owners to GitHub teams, and email owners to the same user
+//! nodes the local-git extractor creates for commit authors.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::schema::{ExtractedEdge, NodeRef, NodeType, Relation};
+
+/// Where GitHub looks for a CODEOWNERS file, in order of precedence.
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// One line of a CODEOWNERS file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeownersRule {
+    /// Path pattern, as written (`/src/`, `*.js`, `docs/**`)
+    pub pattern: String,
+    /// Owners, as written (`@user`, `@org/team`, `user@example.com`)
+    pub owners: Vec<String>,
+}
+
+/// Extractor for CODEOWNERS files.
+pub struct CodeownersExtractor {
+    /// Provider for the path and repo nodes ("github" or "local-git")
+    provider: String,
+}
+
+impl CodeownersExtractor {
+    /// Create an extractor whose path and repo nodes belong to `provider`.
+    pub fn new(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+        }
+    }
+
+    /// Extract edges from the CODEOWNERS file of a local checkout, if it
+    /// has one. `repo_name` identifies the repo node (e.g. "org/repo").
+    pub fn extract(&self, repo_path: &Path, repo_name: &str) -> Result<Vec<ExtractedEdge>> {
+        let Some(path) = CODEOWNERS_PATHS
+            .iter()
+            .map(|p| repo_path.join(p))
+            .find(|p| p.is_file())
+        else {
+            debug!("No CODEOWNERS file in {:?}", repo_path);
+            return Ok(Vec::new());
+        };
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let observed_at = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        Ok(self.extract_from_content(repo_name, &content, observed_at))
+    }
+
+    /// Extract edges from the contents of a CODEOWNERS file.
+    pub fn extract_from_content(
+        &self,
+        repo_name: &str,
+        content: &str,
+        observed_at: DateTime<Utc>,
+    ) -> Vec<ExtractedEdge> {
+        let repo_node = NodeRef::with_name(NodeType::Project, &self.provider, repo_name, repo_name);
+        let mut edges = Vec::new();
+
+        for rule in parse(content) {
+            // Rules without owners only clear ownership set by earlier rules;
+            // owners that are neither handles nor emails are dropped
+            let owners: Vec<NodeRef> = rule.owners.iter().filter_map(|o| owner_node(o)).collect();
+            if owners.is_empty() {
+                continue;
+            }
+
+            let path_node = NodeRef::with_name(
+                NodeType::File,
+                &self.provider,
+                format!("{}:{}", repo_name, rule.pattern),
+                &rule.pattern,
+            );
+            edges.push(ExtractedEdge::new(
+                path_node.clone(),
+                repo_node.clone(),
+                Relation::BelongsTo,
+                observed_at,
+            ));
+            for owner in owners {
+                edges.push(ExtractedEdge::new(owner, path_node.clone(), Relation::OwnerOf, observed_at));
+            }
+        }
+
+        edges
+    }
+}
+
+/// Parse CODEOWNERS content into rules, skipping comments and blank lines.
+pub fn parse(content: &str) -> Vec<CodeownersRule> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // Everything after an unescaped `#` is a comment
+            let line = match line.find('#') {
+                Some(i) if i == 0 || !line[..i].ends_with('\\') => &line[..i],
+                _ => line,
+            };
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next()?.to_string();
+            Some(CodeownersRule {
+                pattern,
+                owners: parts.map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Node for a CODEOWNERS owner, or `None` if it isn't a handle or email.
+fn owner_node(owner: &str) -> Option<NodeRef> {
+    if let Some(handle) = owner.strip_prefix('@') {
+        return match handle.split_once('/') {
+            Some((org, team)) if !org.is_empty() && !team.is_empty() => {
+                Some(NodeRef::with_name(NodeType::Team, "github", handle, team))
+            }
+            None if !handle.is_empty() => Some(NodeRef::with_name(NodeType::User, "github", handle, handle)),
+            _ => None,
+        };
+    }
+    if owner.contains('@') {
+        let email = owner.to_lowercase();
+        return Some(NodeRef::with_name(NodeType::User, "local-git", &email, &email));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+# Default owners
+*       @acme/core
+
+/docs/  @alice Bob@Example.com   # docs team lead
+*.rs    @acme/rust @bob
+/vendor/
+not-an-owner.txt  nobody
+";
+
+    #[test]
+    fn test_parse() {
+        let rules = parse(CODEOWNERS);
+        assert_eq!(rules.len(), 5);
+        assert_eq!(rules[0].pattern, "*");
+        assert_eq!(rules[1].owners, vec!["@alice", "Bob@Example.com"]);
+        assert!(rules[3].owners.is_empty());
+    }
+
+    #[test]
+    fn test_extract_from_content() {
+        let now = Utc::now();
+        let edges = CodeownersExtractor::new("github").extract_from_content("acme/app", CODEOWNERS, now);
+
+        let owned: Vec<(String, String)> = edges
+            .iter()
+            .filter(|e| e.relation == Relation::OwnerOf)
+            .map(|e| (e.from.canonical_id(), e.to.canonical_id()))
+            .collect();
+        assert_eq!(
+            owned,
+            vec![
+                ("team:github:acme/core".to_string(), "file:github:acme/app:*".to_string()),
+                ("user:github:alice".to_string(), "file:github:acme/app:/docs/".to_string()),
+                ("user:local-git:bob@example.com".to_string(), "file:github:acme/app:/docs/".to_string()),
+                ("team:github:acme/rust".to_string(), "file:github:acme/app:*.rs".to_string()),
+                ("user:github:bob".to_string(), "file:github:acme/app:*.rs".to_string()),
+            ]
+        );
+
+        // Every owned pattern belongs to the repo; unowned ones aren't emitted
+        let belongs: Vec<&ExtractedEdge> = edges.iter().filter(|e| e.relation == Relation::BelongsTo).collect();
+        assert_eq!(belongs.len(), 3);
+        assert!(belongs.iter().all(|e| e.to.canonical_id() == "project:github:acme/app"));
+    }
+}
@@ -3,8 +3,13 @@
 //! Scans git history to extract:
 //! - User → File (EditedFile) edges
 //! - User → Project/Repo (CommittedTo) edges
+//! - User → User (CoAuthorOf) edges from `Co-authored-by` trailers
+//! - User → User (CoEditedWith) edges between authors who repeatedly touch
+//!   the same files
 //! - Commit → File relationships
 //!
+//! Co-authors count as authors of the commit's files too.
+//!
 //! Uses a 90-day cutoff for commit history to match ghost edge threshold.
 
 use std::collections::{HashMap, HashSet};
@@ -28,6 +33,11 @@ pub struct LocalGitConfig {
     pub ignore_patterns: Vec<String>,
     /// Maximum files to process per run (default: 10000)
     pub max_files: usize,
+    /// Files two authors must both have edited to be linked (default: 3)
+    pub min_shared_files: usize,
+    /// Files edited by more authors than this (READMEs, changelogs) don't
+    /// link them (default: 10)
+    pub max_file_authors: usize,
 }
 
 impl Default for LocalGitConfig {
@@ -44,6 +54,8 @@ impl Default for LocalGitConfig {
                 "*.min.css".to_string(),
             ],
             max_files: 10000,
+            min_shared_files: 3,
+            max_file_authors: 10,
         }
     }
 }
@@ -57,8 +69,10 @@ pub struct ExtractionResult {
     pub files_processed: usize,
     /// Number of edges extracted
     pub edges_extracted: usize,
-    /// Unique authors found
+    /// Unique authors found, including co-authors
     pub unique_authors: usize,
+    /// Author pairs linked by co-authorship or shared files
+    pub collaborations: usize,
     /// Time taken in milliseconds
     pub duration_ms: u64,
 }
@@ -116,6 +130,8 @@ impl LocalGitExtractor {
 
         // Map: (author_email, file_path) → stats
         let mut author_files: HashMap<(String, String), AuthorFileStats> = HashMap::new();
+        // Map: (author_email, co_author_email) → (commits, last commit)
+        let mut co_authored: HashMap<AuthorPair, (usize, DateTime<Utc>)> = HashMap::new();
         let mut commits_scanned = 0;
         let mut unique_authors: HashSet<String> = HashSet::new();
 
@@ -138,6 +154,21 @@ impl LocalGitExtractor {
 
             let commit_dt = Utc.timestamp_opt(commit_time, 0).single().unwrap_or_else(Utc::now);
 
+            // Co-authors share the commit
+            let mut authors = vec![author_email.clone()];
+            for co_author in co_authors(commit.message().unwrap_or_default()) {
+                if authors.contains(&co_author) {
+                    continue;
+                }
+                unique_authors.insert(co_author.clone());
+                let entry = co_authored
+                    .entry((author_email.clone(), co_author.clone()))
+                    .or_insert((0, commit_dt));
+                entry.0 += 1;
+                entry.1 = entry.1.max(commit_dt);
+                authors.push(co_author);
+            }
+
             // Get diff
             let parent = commit.parent(0).ok();
             let parent_tree = parent.as_ref().and_then(|p| p.tree().ok());
@@ -166,11 +197,13 @@ impl LocalGitExtractor {
                             continue;
                         }
 
-                        let key = (author_email.clone(), path);
-                        let stats = author_files.entry(key).or_default();
-                        stats.commits += 1;
-                        if stats.last_edit.is_none() || Some(commit_dt) > stats.last_edit {
-                            stats.last_edit = Some(commit_dt);
+                        for author in &authors {
+                            let key = (author.clone(), path.clone());
+                            let stats = author_files.entry(key).or_default();
+                            stats.commits += 1;
+                            if stats.last_edit.is_none() || Some(commit_dt) > stats.last_edit {
+                                stats.last_edit = Some(commit_dt);
+                            }
                         }
                     }
                 }
@@ -262,6 +295,22 @@ impl LocalGitExtractor {
             ));
         }
 
+        // User ↔ User (CoAuthorOf, CoEditedWith)
+        let user_node = |email: &str| NodeRef::with_name(NodeType::User, "local-git", email, email);
+        let mut collaborations = 0;
+        for ((author, co_author), (commits, last_commit)) in &co_authored {
+            let mut edge = ExtractedEdge::new(user_node(author), user_node(co_author), Relation::CoAuthorOf, *last_commit);
+            edge.metadata = Some(serde_json::json!({ "commits": commits }));
+            edges.push(edge);
+            collaborations += 1;
+        }
+        for ((a, b), (shared, last_shared)) in self.shared_files(&author_files) {
+            let mut edge = ExtractedEdge::new(user_node(&a), user_node(&b), Relation::CoEditedWith, last_shared);
+            edge.metadata = Some(serde_json::json!({ "shared_files": shared }));
+            edges.push(edge);
+            collaborations += 1;
+        }
+
         let duration_ms = start_time.elapsed().as_millis() as u64;
 
         let result = ExtractionResult {
@@ -269,21 +318,67 @@ impl LocalGitExtractor {
             files_processed: files_processed.len(),
             edges_extracted: edges.len(),
             unique_authors: unique_authors.len(),
+            collaborations,
             duration_ms,
         };
 
         info!(
-            "Extraction complete: {} commits, {} files, {} edges, {} authors in {}ms",
+            "Extraction complete: {} commits, {} files, {} edges, {} authors, {} collaborations in {}ms",
             result.commits_scanned,
             result.files_processed,
             result.edges_extracted,
             result.unique_authors,
+            result.collaborations,
             result.duration_ms
         );
 
         Ok((edges, result))
     }
 
+    /// Pairs of authors (ordered by email) who both edited at least
+    /// `min_shared_files` files, with the number of such files and when
+    /// they last both had edited one. Files edited by more than
+    /// `max_file_authors` authors are left out.
+    fn shared_files(
+        &self,
+        author_files: &HashMap<(String, String), AuthorFileStats>,
+    ) -> Vec<(AuthorPair, (usize, DateTime<Utc>))> {
+        // file → (author, last edit)
+        let mut file_authors: HashMap<&str, Vec<(&str, DateTime<Utc>)>> = HashMap::new();
+        for ((author, file), stats) in author_files {
+            if stats.commits < self.config.min_commits {
+                continue;
+            }
+            if let Some(last_edit) = stats.last_edit {
+                file_authors.entry(file).or_default().push((author, last_edit));
+            }
+        }
+
+        let mut pairs: HashMap<AuthorPair, (usize, DateTime<Utc>)> = HashMap::new();
+        for authors in file_authors.values_mut() {
+            if authors.len() > self.config.max_file_authors {
+                continue;
+            }
+            authors.sort();
+            for (i, (a, a_edit)) in authors.iter().enumerate() {
+                for (b, b_edit) in &authors[i + 1..] {
+                    // Both had touched the file by the later of their edits
+                    let both = *a_edit.max(b_edit);
+                    let entry = pairs.entry((a.to_string(), b.to_string())).or_insert((0, both));
+                    entry.0 += 1;
+                    entry.1 = entry.1.max(both);
+                }
+            }
+        }
+
+        let mut pairs: Vec<_> = pairs
+            .into_iter()
+            .filter(|(_, (shared, _))| *shared >= self.config.min_shared_files)
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
     /// Get collaborators for a specific file.
     ///
     /// Returns a list of (author_email, commit_count, last_edit) for the file.
@@ -419,6 +514,25 @@ impl LocalGitExtractor {
     }
 }
 
+/// Two author emails, in the order their edge runs.
+type AuthorPair = (String, String);
+
+/// Lowercased emails from a commit message's `Co-authored-by: Name <email>`
+/// trailers.
+fn co_authors(message: &str) -> Vec<String> {
+    message
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(':')?;
+            if !key.trim().eq_ignore_ascii_case("co-authored-by") {
+                return None;
+            }
+            let email = value.split_once('<')?.1.split_once('>')?.0.trim();
+            (!email.is_empty()).then(|| email.to_lowercase())
+        })
+        .collect()
+}
+
 impl Default for LocalGitExtractor {
     fn default() -> Self {
         Self::new()
@@ -465,5 +579,48 @@ mod tests {
         assert_eq!(config.history_days, 90);
         assert_eq!(config.min_commits, 1);
         assert_eq!(config.max_files, 10000);
+        assert_eq!(config.min_shared_files, 3);
+    }
+
+    #[test]
+    fn test_co_authors() {
+        let message = "Fix flaky sync test\n\nCo-authored-by: Bob Smith <Bob@Example.com>\nco-authored-by: carol <carol@example.com>\nCo-authored-by: nobody\nSigned-off-by: Alice <alice@example.com>\n";
+        assert_eq!(co_authors(message), vec!["bob@example.com", "carol@example.com"]);
+        assert!(co_authors("Initial commit").is_empty());
+    }
+
+    #[test]
+    fn test_shared_files() {
+        let extractor = LocalGitExtractor::new();
+        let now = Utc::now();
+        let mut author_files = HashMap::new();
+        let mut edit = |author: &str, file: &str, days_ago| {
+            author_files.insert(
+                (author.to_string(), file.to_string()),
+                AuthorFileStats {
+                    commits: 1,
+                    last_edit: Some(now - Duration::days(days_ago)),
+                    ..Default::default()
+                },
+            );
+        };
+        for file in ["a.rs", "b.rs", "c.rs"] {
+            edit("alice@x", file, 5);
+            edit("bob@x", file, 2);
+        }
+        edit("carol@x", "a.rs", 1);
+        edit("carol@x", "b.rs", 1);
+        // Everyone edits the README, which doesn't count
+        for n in 0..11 {
+            edit(&format!("dev{}@x", n), "README.md", 1);
+        }
+        edit("carol@x", "README.md", 1);
+        edit("alice@x", "README.md", 1);
+
+        let pairs = extractor.shared_files(&author_files);
+        assert_eq!(pairs.len(), 1);
+        let ((a, b), (shared, last)) = &pairs[0];
+        assert_eq!((a.as_str(), b.as_str(), *shared), ("alice@x", "bob@x", 3));
+        assert_eq!(*last, now - Duration::days(2));
     }
 }
@@ -0,0 +1,159 @@
+//! Calendar meeting extractor for Gravity Well.
+//!
+//! Turns a calendar event, from any calendar provider, into:
+//! - User → Meeting (AuthorOf) edges for the organizer
+//! - User → Meeting (AttendeeOf) edges for everyone who didn't decline
+//!
+//! Every instance of a recurring meeting maps to the same meeting node, so
+//! each week's standup strengthens the same edges rather than adding a new
+//! node. Smaller meetings say more about closeness: a 1:1 weighs twice as
+//! much as a large meeting. Meeting rooms and other resources are skipped.
+
+use chrono::{DateTime, Utc};
+
+use crate::schema::{ExtractedEdge, NodeRef, NodeType, Relation};
+
+/// Largest meeting that still counts as small.
+pub const SMALL_MEETING: usize = 6;
+
+/// A person or resource invited to a meeting.
+#[derive(Debug, Clone, Default)]
+pub struct MeetingAttendee {
+    pub email: String,
+    pub display_name: Option<String>,
+    /// Declined the invitation
+    pub declined: bool,
+    /// A room or other resource rather than a person
+    pub resource: bool,
+}
+
+/// A calendar event, as needed for edge extraction.
+#[derive(Debug, Clone, Default)]
+pub struct Meeting {
+    /// Event ID, or the series ID for instances of a recurring meeting
+    pub id: String,
+    pub title: Option<String>,
+    pub organizer: Option<MeetingAttendee>,
+    pub attendees: Vec<MeetingAttendee>,
+}
+
+/// Extractor for calendar meetings.
+pub struct MeetingExtractor {
+    /// Provider for attendee user nodes (e.g. "google")
+    user_provider: String,
+    /// Provider for meeting nodes (e.g. "google_calendar")
+    calendar_provider: String,
+}
+
+impl MeetingExtractor {
+    /// Create an extractor for a calendar provider's meetings.
+    pub fn new(user_provider: impl Into<String>, calendar_provider: impl Into<String>) -> Self {
+        Self {
+            user_provider: user_provider.into(),
+            calendar_provider: calendar_provider.into(),
+        }
+    }
+
+    /// Extract organizer and attendee edges for a meeting.
+    pub fn extract(&self, meeting: &Meeting, observed_at: DateTime<Utc>) -> Vec<ExtractedEdge> {
+        let meeting_node = NodeRef::with_name(
+            NodeType::Meeting,
+            &self.calendar_provider,
+            &meeting.id,
+            meeting.title.as_deref().unwrap_or("Meeting"),
+        );
+        let organizer_email = meeting.organizer.as_ref().map(|o| o.email.as_str());
+
+        let people: Vec<&MeetingAttendee> = meeting
+            .attendees
+            .iter()
+            .filter(|a| !a.declined && !a.resource && !a.email.is_empty())
+            .collect();
+        let weight = meeting_weight(people.len().max(1));
+
+        let mut edges = Vec::new();
+
+        // Organizer → Meeting (AuthorOf)
+        if let Some(organizer) = meeting.organizer.as_ref().filter(|o| !o.email.is_empty() && !o.resource) {
+            edges.push(
+                ExtractedEdge::new(self.user_node(organizer), meeting_node.clone(), Relation::AuthorOf, observed_at)
+                    .with_weight(weight),
+            );
+        }
+
+        // Attendees → Meeting (AttendeeOf)
+        for attendee in people {
+            if organizer_email.is_some_and(|o| o.eq_ignore_ascii_case(&attendee.email)) {
+                continue;
+            }
+            edges.push(
+                ExtractedEdge::new(self.user_node(attendee), meeting_node.clone(), Relation::AttendeeOf, observed_at)
+                    .with_weight(weight),
+            );
+        }
+
+        edges
+    }
+
+    fn user_node(&self, person: &MeetingAttendee) -> NodeRef {
+        let name = person.display_name.as_deref().unwrap_or(&person.email);
+        NodeRef::with_name(NodeType::User, &self.user_provider, &person.email, name)
+    }
+}
+
+/// Interaction weight for a meeting with `attendees` people: 1:1s count
+/// double, small meetings one and a half times.
+pub fn meeting_weight(attendees: usize) -> f32 {
+    match attendees {
+        0..=2 => 2.0,
+        n if n <= SMALL_MEETING => 1.5,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(email: &str) -> MeetingAttendee {
+        MeetingAttendee {
+            email: email.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let meeting = Meeting {
+            id: "standup".to_string(),
+            title: Some("Standup".to_string()),
+            organizer: Some(person("Alice@example.com")),
+            attendees: vec![
+                person("alice@example.com"),
+                person("bob@example.com"),
+                MeetingAttendee { declined: true, ..person("carol@example.com") },
+                MeetingAttendee { resource: true, ..person("room-1@resource.calendar.google.com") },
+            ],
+        };
+        let edges = MeetingExtractor::new("google", "google_calendar").extract(&meeting, Utc::now());
+
+        let ids: Vec<(String, Relation)> = edges.iter().map(|e| (e.from.canonical_id(), e.relation)).collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("user:google:Alice@example.com".to_string(), Relation::AuthorOf),
+                ("user:google:bob@example.com".to_string(), Relation::AttendeeOf),
+            ]
+        );
+        assert!(edges.iter().all(|e| e.to.canonical_id() == "meeting:google_calendar:standup"));
+        // Only Alice and Bob are actually meeting: a 1:1
+        assert!(edges.iter().all(|e| e.weight == 2.0));
+    }
+
+    #[test]
+    fn test_meeting_weight() {
+        assert_eq!(meeting_weight(2), 2.0);
+        assert_eq!(meeting_weight(SMALL_MEETING), 1.5);
+        assert_eq!(meeting_weight(40), 1.0);
+    }
+}
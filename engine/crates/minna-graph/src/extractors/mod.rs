@@ -1,10 +1,16 @@
 //! Data extractors for the Gravity Well graph.
 //!
 //! This module provides extractors that scan local data sources
-//! (like git repositories) and extract relationship edges.
+//! (like git repositories) and extract relationship edges, plus extractors
+//! shared by providers with the same kind of data (CODEOWNERS files,
+//! calendar meetings).
 
+pub mod codeowners;
 #[cfg(feature = "local-git")]
 pub mod local_git;
+pub mod meetings;
 
+pub use codeowners::CodeownersExtractor;
 #[cfg(feature = "local-git")]
 pub use local_git::LocalGitExtractor;
+pub use meetings::{Meeting, MeetingAttendee, MeetingExtractor};
@@ -1,16 +1,24 @@
 //! Identity linking service for Gravity Well.
 //!
 //! Automatically links user identities across providers based on email matching.
-//! Provides fuzzy match suggestions for manual review.
+//! Provides fuzzy match suggestions for manual review. Confirmed links are
+//! stored as `SameAs` edges, which the ring engine crosses at no cost.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::Utc;
 use tracing::info;
 
+use crate::schema::GraphNode;
 use crate::storage::GraphStore;
 
+/// Confidence of a link between users sharing an email address.
+const EMAIL_CONFIDENCE: f32 = 0.95;
+
+/// Domain of the commit addresses GitHub hands out to hide users' emails.
+const GITHUB_NOREPLY_DOMAIN: &str = "users.noreply.github.com";
+
 /// A suggested identity link between two users.
 #[derive(Debug, Clone)]
 pub struct IdentityMatch {
@@ -44,67 +52,73 @@ pub enum MatchType {
     Manual,
 }
 
+impl MatchType {
+    /// Provenance recorded on the `SameAs` edge of a link made this way.
+    pub fn provenance(&self) -> &'static str {
+        match self {
+            MatchType::ExactEmail => "email",
+            MatchType::SimilarName => "name",
+            MatchType::Manual => "manual",
+        }
+    }
+}
+
 /// Service for managing user identity linking.
 pub struct IdentityService;
 
 impl IdentityService {
-    /// Auto-link users with exact email matches.
+    /// Auto-link users with matching email addresses.
     ///
-    /// Scans all user nodes and links those with matching emails.
-    /// Returns the number of users linked.
+    /// Collects the addresses known for each user node (a Slack or Linear
+    /// profile email, a Google or git account, a GitHub login through its
+    /// noreply address), normalizes them with [`email_key`], and links users
+    /// sharing one with a high-confidence `SameAs` edge (provenance "email").
+    /// Returns the number of new links.
     pub async fn auto_link_by_email(graph: &GraphStore) -> Result<usize> {
         let users = graph.get_user_nodes().await?;
 
-        // Group users by email
-        let mut by_email: HashMap<String, Vec<(String, String, Option<String>)>> = HashMap::new();
-
+        // Group users by normalized address
+        let mut by_key: HashMap<String, Vec<&GraphNode>> = HashMap::new();
         for user in &users {
-            // Try to extract email from metadata
-            let email = user.metadata
-                .as_ref()
-                .and_then(|m| m.get("email"))
-                .and_then(|e| e.as_str())
-                .map(|e| e.to_lowercase());
-
-            if let Some(email) = email {
-                by_email.entry(email).or_default().push((
-                    user.provider.clone(),
-                    user.external_id.clone(),
-                    user.display_name.clone(),
-                ));
+            for key in identity_keys(user) {
+                by_key.entry(key).or_default().push(user);
             }
         }
+        let mut keys: Vec<_> = by_key
+            .into_iter()
+            .filter(|(_, users)| users.len() >= 2)
+            .collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
 
+        let mut groups = IdentityGroups::load(graph).await?;
         let mut linked = 0;
-
-        // Link users with matching emails
-        for (email, users) in by_email {
-            if users.len() < 2 {
-                continue; // Need at least 2 users to link
+        for (key, users) in keys {
+            let first = users[0];
+            for other in &users[1..] {
+                if groups.same(&first.id, &other.id) {
+                    continue;
+                }
+                info!("Auto-linking {} <-> {} by email {}", first.id, other.id, key);
+                graph
+                    .link_same_as(&first.id, &other.id, MatchType::ExactEmail.provenance(), EMAIL_CONFIDENCE)
+                    .await?;
+                groups.join(&first.id, &other.id);
+                linked += 1;
             }
 
-            // Generate canonical ID (use email as the canonical ID)
-            let canonical_id = format!("user:{}", email.replace('@', "_at_").replace('.', "_"));
-            let display_name = users.iter()
-                .find_map(|(_, _, name)| name.clone());
-
-            info!(
-                "Auto-linking {} users with email {}: {:?}",
-                users.len(),
-                email,
-                users.iter().map(|(p, id, _)| format!("{}:{}", p, id)).collect::<Vec<_>>()
-            );
-
-            // Link each provider user to the canonical ID
-            for (provider, provider_user_id, _) in &users {
-                graph.link_user_identity(
-                    &canonical_id,
-                    Some(&email),
-                    display_name.as_deref(),
-                    provider,
-                    provider_user_id,
-                ).await?;
-                linked += 1;
+            // Keep the canonical identity for addresses
+            if let Some(email) = key.strip_prefix("email:") {
+                let canonical_id = format!("user:{}", email.replace('@', "_at_").replace('.', "_"));
+                let display_name = users.iter().find_map(|user| user.display_name.clone());
+                for user in &users {
+                    graph.link_user_identity(
+                        &canonical_id,
+                        Some(email),
+                        display_name.as_deref(),
+                        &user.provider,
+                        &user.external_id,
+                    ).await?;
+                }
             }
         }
 
@@ -116,8 +130,11 @@ impl IdentityService {
     /// Looks for:
     /// - Users with similar emails (typos, aliases)
     /// - Users with matching display names across providers
+    ///
+    /// Users already linked as the same person are not suggested again.
     pub async fn find_fuzzy_matches(graph: &GraphStore) -> Result<Vec<IdentityMatch>> {
         let users = graph.get_user_nodes().await?;
+        let linked = IdentityGroups::load(graph).await?;
         let mut matches = Vec::new();
 
         // Group by provider
@@ -155,6 +172,9 @@ impl IdentityService {
                         if seen_pairs.contains(&pair_key) {
                             continue;
                         }
+                        if linked.same(&id1, &id2) {
+                            continue;
+                        }
 
                         // Check display name similarity
                         if let (Some(name1), Some(name2)) = (&u1.display_name, &u2.display_name) {
@@ -200,7 +220,8 @@ impl IdentityService {
         Ok(matches)
     }
 
-    /// Manually link two users.
+    /// Manually link two users, recording a `SameAs` edge with provenance
+    /// "manual" between their nodes.
     pub async fn link_users(
         graph: &GraphStore,
         provider1: &str,
@@ -220,6 +241,11 @@ impl IdentityService {
 
         let node1 = graph.get_node(&node1_id).await?;
         let node2 = graph.get_node(&node2_id).await?;
+        for (id, node) in [(&node1_id, &node1), (&node2_id, &node2)] {
+            if node.is_none() {
+                bail!("unknown user {}", id);
+            }
+        }
 
         let email = node1.as_ref()
             .and_then(|n| n.metadata.as_ref())
@@ -237,6 +263,7 @@ impl IdentityService {
         // Link both users
         graph.link_user_identity(&canonical_id, email, display_name, provider1, user_id1).await?;
         graph.link_user_identity(&canonical_id, email, display_name, provider2, user_id2).await?;
+        graph.link_same_as(&node1_id, &node2_id, MatchType::Manual.provenance(), 1.0).await?;
 
         info!("Manually linked users: {}:{} <-> {}:{} as {}",
               provider1, user_id1, provider2, user_id2, canonical_id);
@@ -251,6 +278,82 @@ impl IdentityService {
     }
 }
 
+/// Sets of user nodes linked as the same person (union-find over `SameAs`
+/// edges).
+#[derive(Default)]
+struct IdentityGroups {
+    parent: HashMap<String, String>,
+}
+
+impl IdentityGroups {
+    async fn load(graph: &GraphStore) -> Result<Self> {
+        let mut groups = Self::default();
+        for edge in graph.same_as_edges().await? {
+            groups.join(&edge.from_node, &edge.to_node);
+        }
+        Ok(groups)
+    }
+
+    fn root(&self, id: &str) -> String {
+        let mut id = id.to_string();
+        while let Some(next) = self.parent.get(&id) {
+            id = next.clone();
+        }
+        id
+    }
+
+    fn join(&mut self, a: &str, b: &str) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a != b {
+            self.parent.insert(b, a);
+        }
+    }
+
+    fn same(&self, a: &str, b: &str) -> bool {
+        self.root(a) == self.root(b)
+    }
+}
+
+/// Normalized form of an email address, for matching accounts: lowercased,
+/// without a `+tag`, and for Gmail without dots. A GitHub noreply address
+/// (`12345+login@users.noreply.github.com`) becomes the account's login,
+/// `github:login`. None if `email` isn't an address.
+pub fn email_key(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.rsplit_once('@')?;
+    if local.is_empty() || !domain.contains('.') {
+        return None;
+    }
+    if domain == GITHUB_NOREPLY_DOMAIN {
+        let login = local.split_once('+').map_or(local, |(_, login)| login);
+        return Some(format!("github:{}", login));
+    }
+    let local = local.split('+').next().unwrap_or(local);
+    if domain == "gmail.com" || domain == "googlemail.com" {
+        return Some(format!("email:{}@gmail.com", local.replace('.', "")));
+    }
+    Some(format!("email:{}@{}", local, domain))
+}
+
+/// Keys [`IdentityService::auto_link_by_email`] matches a user node by.
+fn identity_keys(user: &GraphNode) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    let email = user.metadata
+        .as_ref()
+        .and_then(|m| m.get("email"))
+        .and_then(|e| e.as_str());
+    // Google and local git users are keyed by their address
+    for address in email.into_iter().chain(Some(user.external_id.as_str())) {
+        if let Some(key) = email_key(address) {
+            keys.insert(key);
+        }
+    }
+    if user.provider == "github" {
+        keys.insert(format!("github:{}", user.external_id.to_lowercase()));
+    }
+    keys
+}
+
 /// Calculate name similarity using Jaro-Winkler-like algorithm.
 fn name_similarity(a: &str, b: &str) -> f32 {
     let a = a.to_lowercase();
@@ -296,6 +399,21 @@ mod tests {
         assert!(name_similarity("Alice", "Alice") == 1.0);
     }
 
+    #[test]
+    fn test_email_key() {
+        assert_eq!(email_key(" Alice@Example.com ").as_deref(), Some("email:alice@example.com"));
+        assert_eq!(email_key("alice+lists@example.com").as_deref(), Some("email:alice@example.com"));
+        assert_eq!(email_key("Alice.Smith@googlemail.com").as_deref(), Some("email:alicesmith@gmail.com"));
+        assert_eq!(email_key("alice.smith@example.com").as_deref(), Some("email:alice.smith@example.com"));
+        assert_eq!(
+            email_key("1234+Alice@users.noreply.github.com").as_deref(),
+            Some("github:alice")
+        );
+        assert_eq!(email_key("alice@users.noreply.github.com").as_deref(), Some("github:alice"));
+        assert_eq!(email_key("U123"), None);
+        assert_eq!(email_key("alice@localhost"), None);
+    }
+
     #[test]
     fn test_match_type_equality() {
         assert_eq!(MatchType::ExactEmail, MatchType::ExactEmail);
@@ -4,8 +4,10 @@
 //! sync and retrieval system. It includes:
 //!
 //! - **Schema**: Node and relation types for the collaboration graph
-//! - **Storage**: SQLite-backed persistence for nodes and edges
+//! - **Storage**: SQLite-backed persistence for nodes and edges, behind the
+//!   `GraphBackend` trait
 //! - **Ring Engine**: BFS-based ring calculation with temporal decay
+//! - **Centrality**: PageRank and degree per node
 //!
 //! # Example
 //!
@@ -27,6 +29,8 @@
 //! engine.recalculate_rings(&store, "user:slack:U123").await?;
 //! ```
 
+pub mod backend;
+pub mod centrality;
 pub mod extractors;
 pub mod identity;
 pub mod ring_engine;
@@ -34,12 +38,16 @@ pub mod schema;
 pub mod storage;
 
 // Re-export commonly used types
+pub use backend::GraphBackend;
+pub use extractors::{CodeownersExtractor, Meeting, MeetingAttendee, MeetingExtractor};
 pub use identity::{IdentityMatch, IdentityService, MatchType, ProviderUser};
 pub use ring_engine::{RingConfig, RingEngine, RecalculationResult};
 pub use schema::{
-    ExtractedEdge, GraphEdge, GraphNode, NodeRef, NodeType, Relation, Ring, RingAssignment,
+    Collaborator, CollaboratorGroup, EdgeSource, ExtractedEdge, GraphEdge, GraphNode, GraphPath, Neighbor,
+    NodeCentrality, NodeRef, NodeType, Relation, Ring, RingAssignment, RingChange, RingChangeReason, RingDiff, RingTransition,
+    Subgraph, TimeRange,
 };
-pub use storage::GraphStore;
+pub use storage::{GraphStore, GraphStoreStats, ProviderEdgeStats, PrunePolicy, PruneReport};
 
 // Re-export extractors when features enabled
 #[cfg(feature = "local-git")]
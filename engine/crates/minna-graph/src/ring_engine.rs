@@ -2,19 +2,37 @@
 //!
 //! Calculates ring assignments (Core, Ring 1, Ring 2, Beyond) for all nodes
 //! based on graph distance from the user's identity with temporal decay.
+//!
+//! Assignments are sticky: a node only changes ring after several consecutive
+//! recalculations agree (`RingConfig::promote_after` / `demote_after`), so a
+//! single burst of activity or a quiet week doesn't make it flap between
+//! rings. Every change is logged as a [`RingTransition`].
+//!
+//! People are placed by their shortest path to the user. Artifacts (documents,
+//! channels, repos, meetings) are placed by how connected they are: every
+//! person linking to one adds a path, combined like parallel resistors, so a
+//! channel five close colleagues post in is closer than one with a single
+//! close member (`RingConfig::combine_paths`).
 
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::cmp::Ordering;
 
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use tracing::{info, debug};
 
-use crate::schema::{Ring, RingAssignment};
+use crate::centrality;
+use crate::schema::{
+    Collaborator, CollaboratorGroup, GraphEdge, NodeType, Relation, Ring, RingAssignment, RingChangeReason,
+    RingDiff, RingTransition, TimeRange,
+};
 use crate::storage::GraphStore;
 
-/// Configuration for ring calculation.
-#[derive(Debug, Clone)]
+/// Configuration for ring calculation. Missing fields take their defaults
+/// when deserialized, so a config file only needs the ones it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RingConfig {
     /// Decay half-life in days (default: 30)
     pub decay_half_life_days: i64,
@@ -28,6 +46,24 @@ pub struct RingConfig {
     pub ring_2_threshold: f64,
     /// Maximum hops to consider (default: 10)
     pub max_hops: usize,
+    /// How much repeated interactions strengthen an edge: its weight is
+    /// multiplied by `1 + count_weight * ln(count)` (default: 0.5)
+    pub count_weight: f64,
+    /// Consecutive recalculations a node must land in a closer ring before
+    /// it is promoted (default: 2)
+    pub promote_after: u32,
+    /// Consecutive recalculations a node must land in a farther ring before
+    /// it is demoted (default: 3)
+    pub demote_after: u32,
+    /// Place artifacts by the paths through every person linking to them,
+    /// not just the shortest one (default: true)
+    pub combine_paths: bool,
+    /// Edges extracted with less confidence than this are ignored; the rest
+    /// count in proportion to their confidence (default: 0.5)
+    pub min_confidence: f32,
+    /// Only count edges observed in this many days, for rings that reflect
+    /// current collaboration rather than all history (default: all edges)
+    pub window_days: Option<i64>,
 }
 
 impl Default for RingConfig {
@@ -39,10 +75,22 @@ impl Default for RingConfig {
             ring_1_threshold: 2.0,
             ring_2_threshold: 4.0,
             max_hops: 10,
+            count_weight: 0.5,
+            promote_after: 2,
+            demote_after: 3,
+            combine_paths: true,
+            min_confidence: 0.5,
+            window_days: None,
         }
     }
 }
 
+/// Best distance to an artifact through each person linking to it.
+type Approaches = HashMap<String, HashMap<String, f64>>;
+
+/// Score, connections and last interaction of a collaborator being ranked.
+type Tally = (f64, usize, DateTime<Utc>);
+
 /// Ring Engine performs BFS traversal with temporal decay.
 pub struct RingEngine {
     config: RingConfig,
@@ -53,13 +101,16 @@ pub struct RingEngine {
 struct QueueNode {
     node_id: String,
     effective_distance: f64,
+    /// PageRank of the node, to visit the more central of two equally
+    /// distant nodes first
+    centrality: f64,
     hops: usize,
     path: Vec<String>,
 }
 
 impl PartialEq for QueueNode {
     fn eq(&self, other: &Self) -> bool {
-        self.effective_distance == other.effective_distance
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -73,10 +124,12 @@ impl PartialOrd for QueueNode {
 
 impl Ord for QueueNode {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Reverse ordering for min-heap (smallest distance first)
+        // Reverse ordering for min-heap (smallest distance first), then the
+        // most central first
         other.effective_distance
             .partial_cmp(&self.effective_distance)
             .unwrap_or(Ordering::Equal)
+            .then_with(|| self.centrality.partial_cmp(&other.centrality).unwrap_or(Ordering::Equal))
     }
 }
 
@@ -124,6 +177,31 @@ impl RingEngine {
         1.0 / (effective_weight + 0.001)
     }
 
+    /// Weight accumulated by an edge: its interaction strength and relation
+    /// (see [`Relation::weight`]), growing logarithmically with the number
+    /// of times it was observed.
+    pub fn accumulated_weight(&self, edge: &GraphEdge) -> f64 {
+        let repeats = (edge.count.max(1) as f64).ln();
+        edge.weight as f64 * edge.relation.weight() * edge.confidence as f64 * (1.0 + self.config.count_weight * repeats)
+    }
+
+    /// Whether an edge is confident enough to count (see
+    /// `RingConfig::min_confidence`).
+    pub fn is_trusted(&self, edge: &GraphEdge) -> bool {
+        edge.confidence >= self.config.min_confidence
+    }
+
+    /// Cost and hop count of following an edge, from its accumulated
+    /// weight. Identity links (`SameAs`) join the accounts of one person,
+    /// so they are free: the user's linked accounts are all Core, and anyone
+    /// close to one of them is close.
+    fn traverse(&self, edge: &GraphEdge, now: DateTime<Utc>) -> (f64, usize) {
+        if edge.relation == Relation::SameAs {
+            return (0.0, 0);
+        }
+        (self.edge_cost(self.accumulated_weight(edge), edge.observed_at, now), 1)
+    }
+
     /// Determine ring assignment based on effective distance.
     pub fn distance_to_ring(&self, effective_distance: f64) -> Ring {
         if effective_distance <= 0.0 {
@@ -137,6 +215,80 @@ impl RingEngine {
         }
     }
 
+    /// Apply hysteresis to a freshly computed assignment.
+    ///
+    /// Keeps the previous ring until the computed one has been seen enough
+    /// times in a row, tracking progress in `pending_ring`/`pending_count`.
+    /// New nodes and the user's own node take their ring immediately.
+    pub fn settle(&self, previous: Option<&RingAssignment>, mut computed: RingAssignment) -> RingAssignment {
+        computed.pending_ring = None;
+        computed.pending_count = 0;
+        let Some(previous) = previous else {
+            return computed;
+        };
+        if computed.ring == previous.ring || computed.ring == Ring::Core {
+            return computed;
+        }
+
+        let count = if previous.pending_ring == Some(computed.ring) {
+            previous.pending_count + 1
+        } else {
+            1
+        };
+        let required = if computed.ring.is_closer_than(previous.ring) {
+            self.config.promote_after
+        } else {
+            self.config.demote_after
+        };
+        if count >= required {
+            return computed;
+        }
+
+        computed.pending_ring = Some(computed.ring);
+        computed.pending_count = count;
+        computed.ring = previous.ring;
+        computed
+    }
+
+    /// Settle and save an assignment, logging a transition if its ring changed.
+    /// `reason` overrides the reason inferred from the move.
+    async fn commit_assignment(
+        &self,
+        store: &GraphStore,
+        computed: RingAssignment,
+        reason: Option<RingChangeReason>,
+        transitions: &mut Vec<RingTransition>,
+    ) -> Result<RingAssignment> {
+        let previous = store.get_ring_assignment(&computed.node_id).await?;
+        let assignment = self.settle(previous.as_ref(), computed);
+        store.save_ring_assignment(&assignment).await?;
+
+        if let Some(previous) = previous.filter(|p| p.ring != assignment.ring) {
+            let transition = RingTransition {
+                node_id: assignment.node_id.clone(),
+                from: previous.ring,
+                to: assignment.ring,
+                effective_distance: assignment.effective_distance,
+                transitioned_at: assignment.computed_at,
+                reason: reason.unwrap_or(if assignment.distance == 0 {
+                    RingChangeReason::Linked
+                } else {
+                    RingChangeReason::for_move(previous.ring, assignment.ring)
+                }),
+            };
+            debug!(
+                "Ring transition for {}: {:?} -> {:?} ({})",
+                transition.node_id,
+                transition.from,
+                transition.to,
+                transition.reason.as_str()
+            );
+            store.record_ring_transition(&transition).await?;
+            transitions.push(transition);
+        }
+        Ok(assignment)
+    }
+
     /// Recalculate ring assignments for all nodes reachable from the user.
     ///
     /// Uses Dijkstra's algorithm with temporal decay-weighted edges.
@@ -145,23 +297,135 @@ impl RingEngine {
         store: &GraphStore,
         user_node_id: &str,
     ) -> Result<RecalculationResult> {
-        let now = Utc::now();
+        self.recalculate_rings_from(store, &[user_node_id.to_string()]).await
+    }
+
+    /// Recalculate ring assignments from several of the user's nodes at
+    /// once, such as their Slack, GitHub and Google accounts: all of them
+    /// are Core, and every other node is placed by its distance to the
+    /// closest one. With `RingConfig::window_days`, only edges observed in
+    /// that many days count.
+    pub async fn recalculate_rings_from(
+        &self,
+        store: &GraphStore,
+        user_node_ids: &[String],
+    ) -> Result<RecalculationResult> {
         let start_time = std::time::Instant::now();
 
-        info!("Starting ring recalculation from user: {}", user_node_id);
+        info!("Starting ring recalculation from user: {}", user_node_ids.join(", "));
+
+        let range = self.config.window_days.map(TimeRange::last_days).unwrap_or_default();
+        let (computed, visited) = self.compute_rings(store, user_node_ids, range).await?;
+        let now = Utc::now();
+        let mut assignments: HashMap<String, RingAssignment> = HashMap::new();
+        let mut transitions: Vec<RingTransition> = Vec::new();
+
+        // Save assignments (subject to hysteresis)
+        for mut computed in computed {
+            computed.computed_at = now;
+            let assignment = self.commit_assignment(store, computed, None, &mut transitions).await?;
+            debug!(
+                "Assigned {} to {:?} (dist: {:.2}, hops: {})",
+                assignment.node_id, assignment.ring, assignment.effective_distance, assignment.distance
+            );
+            assignments.insert(assignment.node_id.clone(), assignment);
+        }
+
+        // Nodes no longer reachable drift out to Beyond (also subject to hysteresis)
+        for ring in [Ring::Core, Ring::One, Ring::Two, Ring::Beyond] {
+            for node_id in store.nodes_in_ring(ring).await? {
+                if visited.contains(&node_id) {
+                    continue;
+                }
+                let Some(previous) = store.get_ring_assignment(&node_id).await? else {
+                    continue;
+                };
+                let computed = RingAssignment {
+                    ring: Ring::Beyond,
+                    computed_at: now,
+                    ..previous
+                };
+                self.commit_assignment(store, computed, Some(RingChangeReason::Unreachable), &mut transitions)
+                    .await?;
+            }
+        }
+
+        store.refresh_doc_rings().await?;
+
+        let duration = start_time.elapsed();
+        let distribution = store.ring_distribution().await?;
+
+        // Convert Vec to counts for logging
+        let get_count = |ring: Ring| -> i64 {
+            distribution.iter().find(|(r, _)| *r == ring).map(|(_, c)| *c).unwrap_or(0)
+        };
+
+        info!(
+            "Ring recalculation complete: {} nodes processed in {:?}",
+            assignments.len(),
+            duration
+        );
+        info!(
+            "Distribution: Core={}, Ring1={}, Ring2={}, Beyond={}, transitions={}",
+            get_count(Ring::Core),
+            get_count(Ring::One),
+            get_count(Ring::Two),
+            get_count(Ring::Beyond),
+            transitions.len()
+        );
+
+        Ok(RecalculationResult {
+            nodes_processed: assignments.len(),
+            duration_ms: duration.as_millis() as u64,
+            distribution,
+            transitions,
+        })
+    }
+
+    /// The rings nodes would have from only the edges observed in `range`,
+    /// without saving them or applying hysteresis, for comparing recent
+    /// collaboration with the saved rings. Decay is measured from the end
+    /// of the range.
+    pub async fn rings_in(
+        &self,
+        store: &GraphStore,
+        user_node_ids: &[String],
+        range: TimeRange,
+    ) -> Result<Vec<RingAssignment>> {
+        Ok(self.compute_rings(store, user_node_ids, range).await?.0)
+    }
+
+    /// Compute ring assignments from the user's nodes over the edges
+    /// observed in `range`, people first in order of distance, then
+    /// artifacts. Also returns every node reached.
+    async fn compute_rings(
+        &self,
+        store: &GraphStore,
+        user_node_ids: &[String],
+        range: TimeRange,
+    ) -> Result<(Vec<RingAssignment>, HashSet<String>)> {
+        let now = range.until.unwrap_or_else(Utc::now);
 
         // Priority queue for Dijkstra's algorithm
         let mut queue = BinaryHeap::new();
         let mut visited: HashSet<String> = HashSet::new();
-        let mut assignments: HashMap<String, RingAssignment> = HashMap::new();
-
-        // Start with the user node (Core ring, distance 0)
-        queue.push(QueueNode {
-            node_id: user_node_id.to_string(),
-            effective_distance: 0.0,
-            hops: 0,
-            path: vec![],
-        });
+        let mut assignments: Vec<RingAssignment> = Vec::new();
+        let pagerank = store.pagerank_scores().await?;
+        // Artifacts are placed once all paths to them are known
+        let mut artifacts: Vec<RingAssignment> = Vec::new();
+        let mut approaches: Approaches = HashMap::new();
+        let usable = |edge: &GraphEdge| self.is_trusted(edge) && range.contains(edge.observed_at);
+
+        // Start with the user's nodes (Core ring, distance 0)
+        for user_node_id in user_node_ids {
+            queue.push(QueueNode {
+                node_id: user_node_id.clone(),
+                effective_distance: 0.0,
+                centrality: 0.0,
+                hops: 0,
+                path: vec![],
+            });
+        }
 
         while let Some(current) = queue.pop() {
             // Skip if already visited
@@ -176,95 +440,236 @@ impl RingEngine {
             }
 
             // Determine ring and create assignment
-            let ring = self.distance_to_ring(current.effective_distance);
-            let assignment = RingAssignment {
+            let computed = RingAssignment {
                 node_id: current.node_id.clone(),
-                ring,
+                ring: self.distance_to_ring(current.effective_distance),
                 distance: current.hops as i32,
                 effective_distance: current.effective_distance as f32,
                 path: current.path.clone(),
                 computed_at: now,
+                pending_ring: None,
+                pending_count: 0,
             };
+            if self.config.combine_paths && current.hops > 0 && !is_person(&current.node_id) {
+                artifacts.push(computed);
+            } else {
+                assignments.push(computed);
+            }
 
-            // Save assignment
-            store.save_ring_assignment(&assignment).await?;
-            assignments.insert(current.node_id.clone(), assignment);
-
-            debug!(
-                "Assigned {} to {:?} (dist: {:.2}, hops: {})",
-                current.node_id, ring, current.effective_distance, current.hops
-            );
+            // The person this node's paths run through
+            let via = if is_person(&current.node_id) {
+                Some(&current.node_id)
+            } else {
+                current.path.iter().rev().find(|id| is_person(id))
+            };
+            let mut approach = |artifact: &str, distance: f64| {
+                let through = artifact == current.node_id || current.path.iter().any(|id| id == artifact);
+                if let Some(via) = via.filter(|_| !through && !is_person(artifact)) {
+                    let best = approaches.entry(artifact.to_string()).or_default().entry(via.clone()).or_insert(distance);
+                    *best = best.min(distance);
+                }
+            };
 
             // Get outgoing edges and add neighbors to queue
             let edges = store.edges_from(&current.node_id).await?;
-            for edge in edges {
+            for edge in edges.into_iter().filter(|edge| usable(edge)) {
+                // Calculate edge cost with temporal decay
+                let (cost, hops) = self.traverse(&edge, now);
+                let new_distance = current.effective_distance + cost;
+                approach(&edge.to_node, new_distance);
                 if visited.contains(&edge.to_node) {
                     continue;
                 }
 
-                // Calculate edge cost with temporal decay
-                let cost = self.edge_cost(edge.weight as f64, edge.observed_at, now);
-                let new_distance = current.effective_distance + cost;
-
                 let mut new_path = current.path.clone();
                 new_path.push(current.node_id.clone());
 
                 queue.push(QueueNode {
+                    centrality: pagerank.get(&edge.to_node).copied().unwrap_or_default(),
                     node_id: edge.to_node,
                     effective_distance: new_distance,
-                    hops: current.hops + 1,
+                    hops: current.hops + hops,
                     path: new_path,
                 });
             }
 
             // Also traverse incoming edges (graph is conceptually undirected for proximity)
             let incoming = store.edges_to(&current.node_id).await?;
-            for edge in incoming {
+            for edge in incoming.into_iter().filter(|edge| usable(edge)) {
+                let (cost, hops) = self.traverse(&edge, now);
+                let new_distance = current.effective_distance + cost;
+                approach(&edge.from_node, new_distance);
                 if visited.contains(&edge.from_node) {
                     continue;
                 }
 
-                let cost = self.edge_cost(edge.weight as f64, edge.observed_at, now);
-                let new_distance = current.effective_distance + cost;
-
                 let mut new_path = current.path.clone();
                 new_path.push(current.node_id.clone());
 
                 queue.push(QueueNode {
+                    centrality: pagerank.get(&edge.from_node).copied().unwrap_or_default(),
                     node_id: edge.from_node,
                     effective_distance: new_distance,
-                    hops: current.hops + 1,
+                    hops: current.hops + hops,
                     path: new_path,
                 });
             }
         }
 
-        let duration = start_time.elapsed();
-        let distribution = store.ring_distribution().await?;
+        for mut computed in artifacts {
+            if let Some(combined) = approaches.get(&computed.node_id).and_then(|paths| combine_paths(paths.values())) {
+                let effective_distance = combined.min(computed.effective_distance as f64);
+                computed.ring = self.distance_to_ring(effective_distance);
+                computed.effective_distance = effective_distance as f32;
+            }
+            assignments.push(computed);
+        }
 
-        // Convert Vec to counts for logging
-        let get_count = |ring: Ring| -> i64 {
-            distribution.iter().find(|(r, _)| *r == ring).map(|(_, c)| *c).unwrap_or(0)
+        Ok((assignments, visited))
+    }
+
+    /// Compute degree and PageRank for every node and store them. Edges are
+    /// weighted as for ring calculation, including temporal decay, so the
+    /// scores reflect current collaboration; identity links are skipped.
+    /// Returns the number of nodes scored.
+    pub async fn recalculate_centrality(&self, store: &GraphStore) -> Result<usize> {
+        let now = Utc::now();
+        let mut links = Vec::new();
+        let mut after_id = 0;
+        loop {
+            let edges = store.edges_after(after_id, 1000).await?;
+            let Some(last) = edges.last() else {
+                break;
+            };
+            after_id = last.id;
+            for edge in edges {
+                if edge.relation == Relation::SameAs || !self.is_trusted(&edge) {
+                    continue;
+                }
+                let weight = self.accumulated_weight(&edge) * self.calculate_decay(edge.observed_at, now);
+                links.push((edge.from_node, edge.to_node, weight));
+            }
+        }
+
+        let scores = centrality::compute(&links);
+        store.save_centrality(&scores).await?;
+        info!("Centrality recalculated for {} nodes", scores.len());
+        Ok(scores.len())
+    }
+
+    /// The `limit` people `me` (the user's node and its linked accounts)
+    /// interacted with most since `since`, grouped by ring, closest ring
+    /// first. Two people score through each message, issue, channel etc.
+    /// both are connected to, by the product of their edges' decayed
+    /// weights shared among everyone on it, so a DM counts for more than a
+    /// busy channel. Direct edges between people count in full. Linked
+    /// accounts of one person are merged.
+    pub async fn collaborators(
+        &self,
+        store: &GraphStore,
+        me: &[String],
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<CollaboratorGroup>> {
+        let now = Utc::now();
+        let user_prefix = format!("{}:", NodeType::User.as_str());
+        let is_person = |id: &str| id.starts_with(&user_prefix) && !me.iter().any(|m| m == id);
+
+        // Recent edges touching `node`, as (other end, decayed weight, observed)
+        let neighbors = |node: String| async move {
+            let mut out = Vec::new();
+            for edge in store.edges_from(&node).await?.into_iter().chain(store.edges_to(&node).await?) {
+                if edge.relation == Relation::SameAs || edge.observed_at < since || !self.is_trusted(&edge) {
+                    continue;
+                }
+                let weight = self.accumulated_weight(&edge) * self.calculate_decay(edge.observed_at, now);
+                let other = if edge.from_node == node { edge.to_node } else { edge.from_node };
+                out.push((other, weight, edge.observed_at));
+            }
+            anyhow::Ok(out)
         };
 
-        info!(
-            "Ring recalculation complete: {} nodes processed in {:?}",
-            assignments.len(),
-            duration
-        );
-        info!(
-            "Distribution: Core={}, Ring1={}, Ring2={}, Beyond={}",
-            get_count(Ring::Core),
-            get_count(Ring::One),
-            get_count(Ring::Two),
-            get_count(Ring::Beyond)
-        );
+        // person -> (score, shared objects, last interaction)
+        let mut scores: HashMap<String, Tally> = HashMap::new();
+        let mut credit = |person: String, score: f64, at: DateTime<Utc>| {
+            let entry = scores.entry(person).or_insert((0.0, 0, at));
+            entry.0 += score;
+            entry.1 += 1;
+            entry.2 = entry.2.max(at);
+        };
+        let mut objects: HashMap<String, (f64, DateTime<Utc>)> = HashMap::new();
+        for node in me {
+            for (other, weight, at) in neighbors(node.clone()).await? {
+                if me.contains(&other) {
+                    continue;
+                }
+                if is_person(&other) {
+                    credit(other, weight, at);
+                    continue;
+                }
+                let entry = objects.entry(other).or_insert((0.0, at));
+                entry.0 = entry.0.max(weight);
+                entry.1 = entry.1.max(at);
+            }
+        }
+        for (object, (my_weight, my_at)) in objects {
+            let people: Vec<_> = neighbors(object).await?.into_iter().filter(|(id, _, _)| is_person(id)).collect();
+            let count = people.len() as f64;
+            for (person, weight, at) in people {
+                credit(person, my_weight * weight / count, at.min(my_at));
+            }
+        }
 
-        Ok(RecalculationResult {
-            nodes_processed: assignments.len(),
-            duration_ms: duration.as_millis() as u64,
-            distribution,
-        })
+        let mut ranked: Vec<(String, Tally)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1 .0.partial_cmp(&a.1 .0).unwrap_or(Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+
+        let mut people: Vec<Collaborator> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for (node_id, (score, shared, last_interaction)) in ranked {
+            if let Some(&index) = seen.get(&node_id) {
+                let person = &mut people[index];
+                person.score += score;
+                person.shared += shared;
+                person.last_interaction = person.last_interaction.max(last_interaction);
+                if person.avatar_url.is_none() {
+                    person.avatar_url = store.get_node(&node_id).await?.and_then(|node| node.avatar_url);
+                }
+                person.linked_ids.push(node_id);
+                continue;
+            }
+            if people.len() == limit {
+                continue;
+            }
+            for linked in store.linked_identities(&node_id).await? {
+                seen.insert(linked, people.len());
+            }
+            seen.insert(node_id.clone(), people.len());
+            let node = store.get_node(&node_id).await?;
+            let display_name = node.as_ref().and_then(|node| node.display_name.clone());
+            let avatar_url = node.and_then(|node| node.avatar_url);
+            let ring = store.get_ring_assignment(&node_id).await?.map(|a| a.ring).unwrap_or(Ring::Beyond);
+            people.push(Collaborator {
+                node_id,
+                display_name,
+                avatar_url,
+                linked_ids: Vec::new(),
+                ring,
+                score,
+                shared,
+                last_interaction,
+            });
+        }
+
+        let mut groups: Vec<CollaboratorGroup> = Vec::new();
+        for ring in [Ring::Core, Ring::One, Ring::Two, Ring::Beyond] {
+            let mut members: Vec<Collaborator> = people.iter().filter(|p| p.ring == ring).cloned().collect();
+            if members.is_empty() {
+                continue;
+            }
+            members.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            groups.push(CollaboratorGroup { ring, people: members });
+        }
+        Ok(groups)
     }
 
     /// Get ring assignment for a specific node.
@@ -306,6 +711,18 @@ impl RingEngine {
     }
 }
 
+/// Whether a node ID is a person's.
+fn is_person(node_id: &str) -> bool {
+    node_id.strip_prefix(NodeType::User.as_str()).is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Distance of independent paths taken together, like resistors in
+/// parallel: `1 / Σ 1/dᵢ`. `None` without paths.
+fn combine_paths<'a>(distances: impl Iterator<Item = &'a f64>) -> Option<f64> {
+    let conductance: f64 = distances.filter(|d| **d > 0.0).map(|d| 1.0 / d).sum();
+    (conductance > 0.0).then(|| 1.0 / conductance)
+}
+
 impl Default for RingEngine {
     fn default() -> Self {
         Self::new()
@@ -321,12 +738,30 @@ pub struct RecalculationResult {
     pub duration_ms: u64,
     /// Distribution across rings as Vec of (Ring, count)
     pub distribution: Vec<(Ring, i64)>,
+    /// Nodes whose ring changed in this recalculation
+    pub transitions: Vec<RingTransition>,
+}
+
+impl RecalculationResult {
+    /// The nodes this recalculation promoted and demoted.
+    pub fn diff(&self) -> RingDiff {
+        RingDiff::from_transitions(&self.transitions)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_combine_paths() {
+        assert_eq!(combine_paths([3.0].iter()), Some(3.0));
+        assert_eq!(combine_paths([3.0, 3.0, 3.0].iter()), Some(1.0));
+        assert_eq!(combine_paths([].iter()), None);
+        assert!(is_person("user:slack:U1"));
+        assert!(!is_person("channel:slack:C1"));
+    }
+
     #[test]
     fn test_decay_calculation() {
         let engine = RingEngine::new();
@@ -364,6 +799,48 @@ mod tests {
         assert_eq!(engine.distance_to_ring(5.0), Ring::Beyond);
     }
 
+    fn assignment(ring: Ring) -> RingAssignment {
+        RingAssignment {
+            node_id: "user:slack:U1".to_string(),
+            ring,
+            distance: 1,
+            effective_distance: 1.0,
+            path: Vec::new(),
+            computed_at: Utc::now(),
+            pending_ring: None,
+            pending_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_settle_hysteresis() {
+        let engine = RingEngine::new();
+
+        // New nodes take their computed ring immediately
+        assert_eq!(engine.settle(None, assignment(Ring::Two)).ring, Ring::Two);
+
+        // Promotion needs two consecutive recalculations
+        let first = engine.settle(Some(&assignment(Ring::Two)), assignment(Ring::One));
+        assert_eq!(first.ring, Ring::Two);
+        assert_eq!((first.pending_ring, first.pending_count), (Some(Ring::One), 1));
+        let second = engine.settle(Some(&first), assignment(Ring::One));
+        assert_eq!(second.ring, Ring::One);
+        assert_eq!((second.pending_ring, second.pending_count), (None, 0));
+
+        // Demotion needs three, and an interruption resets the count
+        let mut current = assignment(Ring::One);
+        current = engine.settle(Some(&current), assignment(Ring::Two));
+        current = engine.settle(Some(&current), assignment(Ring::Two));
+        current = engine.settle(Some(&current), assignment(Ring::One));
+        assert_eq!((current.ring, current.pending_ring), (Ring::One, None));
+        for _ in 0..2 {
+            current = engine.settle(Some(&current), assignment(Ring::Beyond));
+            assert_eq!(current.ring, Ring::One);
+        }
+        current = engine.settle(Some(&current), assignment(Ring::Beyond));
+        assert_eq!(current.ring, Ring::Beyond);
+    }
+
     #[test]
     fn test_edge_cost() {
         let engine = RingEngine::new();
@@ -378,4 +855,85 @@ mod tests {
         let old_cost = engine.edge_cost(1.0, old, now);
         assert!(old_cost > cost); // Higher cost for older edge
     }
+
+    #[test]
+    fn test_relation_weights() {
+        let engine = RingEngine::new();
+        let now = Utc::now();
+        let edge = |relation| GraphEdge {
+            id: 1,
+            from_node: "user:github:alice".to_string(),
+            to_node: "pull_request:github:a/b/#1".to_string(),
+            relation,
+            provider: "github".to_string(),
+            observed_at: now,
+            weight: 1.0,
+            count: 1,
+            metadata: None,
+            confidence: 1.0,
+        };
+
+        let (authored, _) = engine.traverse(&edge(Relation::AuthorOf), now);
+        let (reviewed, _) = engine.traverse(&edge(Relation::ReviewerOf), now);
+        let (mentioned, hops) = engine.traverse(&edge(Relation::MentionedIn), now);
+        assert!(authored < reviewed && reviewed < mentioned);
+        assert_eq!(hops, 1);
+        assert_eq!(engine.traverse(&edge(Relation::SameAs), now), (0.0, 0));
+    }
+
+    #[test]
+    fn test_accumulated_weights() {
+        let engine = RingEngine::new();
+        let now = Utc::now();
+        let edge = |relation, weight, count| GraphEdge {
+            id: 1,
+            from_node: "user:slack:U1".to_string(),
+            to_node: "message:slack:C1:1".to_string(),
+            relation,
+            provider: "slack".to_string(),
+            observed_at: now,
+            weight,
+            count,
+            metadata: None,
+            confidence: 1.0,
+        };
+
+        // A direct message is closer than a channel post, repeated posts
+        // closer than one, and a reaction further than a review
+        let (dm, _) = engine.traverse(&edge(Relation::AuthorOf, 2.0, 1), now);
+        let (channel, _) = engine.traverse(&edge(Relation::AuthorOf, 1.0, 1), now);
+        let (repeated, _) = engine.traverse(&edge(Relation::AuthorOf, 1.0, 20), now);
+        assert!(dm < channel && repeated < channel);
+        let (reviewed, _) = engine.traverse(&edge(Relation::ReviewerOf, 1.0, 1), now);
+        let (reacted, _) = engine.traverse(&edge(Relation::ReactedTo, 1.0, 1), now);
+        assert!(reviewed < reacted);
+
+        assert_eq!(engine.accumulated_weight(&edge(Relation::AuthorOf, 1.0, 1)), 1.0);
+        assert_eq!(engine.accumulated_weight(&edge(Relation::AuthorOf, 1.0, 0)), 1.0);
+    }
+
+    #[test]
+    fn test_edge_confidence() {
+        let engine = RingEngine::new();
+        let now = Utc::now();
+        let edge = |confidence| GraphEdge {
+            id: 1,
+            from_node: "user:local-git:alice@example.com".to_string(),
+            to_node: "user:local-git:bob@example.com".to_string(),
+            relation: Relation::CoEditedWith,
+            provider: "local-git".to_string(),
+            observed_at: now,
+            weight: 1.0,
+            count: 1,
+            metadata: None,
+            confidence,
+        };
+
+        // Less certain edges are further, and below the threshold ignored
+        let (certain, _) = engine.traverse(&edge(1.0), now);
+        let (likely, _) = engine.traverse(&edge(0.6), now);
+        assert!(certain < likely);
+        assert!(engine.is_trusted(&edge(0.6)));
+        assert!(!engine.is_trusted(&edge(0.3)));
+    }
 }
@@ -33,6 +33,10 @@ pub enum NodeType {
     Commit,
     /// Source file (local git)
     File,
+    /// Slack user group, GitHub team, Linear team
+    Team,
+    /// Calendar meeting; all instances of a recurring meeting share one node
+    Meeting,
 }
 
 impl NodeType {
@@ -48,6 +52,8 @@ impl NodeType {
             NodeType::Thread => "thread",
             NodeType::Commit => "commit",
             NodeType::File => "file",
+            NodeType::Team => "team",
+            NodeType::Meeting => "meeting",
         }
     }
 
@@ -63,6 +69,8 @@ impl NodeType {
             "thread" => Some(NodeType::Thread),
             "commit" => Some(NodeType::Commit),
             "file" => Some(NodeType::File),
+            "team" => Some(NodeType::Team),
+            "meeting" => Some(NodeType::Meeting),
             _ => None,
         }
     }
@@ -81,9 +89,15 @@ pub enum Relation {
     MentionedIn,
     /// User is reviewer on PR
     ReviewerOf,
+    /// User was invited to Meeting
+    AttendeeOf,
+    /// User owns Document/Repository
+    OwnerOf,
+    /// User reacted to Message
+    ReactedTo,
 
     // User ↔ Container
-    /// User is member of Channel/Project
+    /// User is member of Channel/Project/Team
     MemberOf,
 
     // Object ↔ Container
@@ -109,10 +123,18 @@ pub enum Relation {
     EditedFile,
     /// Commit belongs to Project/Repo
     CommittedTo,
+    /// User co-authored a commit with User (`Co-authored-by` trailer)
+    CoAuthorOf,
+    /// User repeatedly edited the same files as User
+    CoEditedWith,
 
     // LSP (Future: Phase 2)
     /// File imports/references another File
     Imports,
+
+    // Identity
+    /// User is the same person as a User on another provider
+    SameAs,
 }
 
 impl Relation {
@@ -122,6 +144,9 @@ impl Relation {
             Relation::AuthorOf => "author_of",
             Relation::MentionedIn => "mentioned_in",
             Relation::ReviewerOf => "reviewer_of",
+            Relation::AttendeeOf => "attendee_of",
+            Relation::OwnerOf => "owner_of",
+            Relation::ReactedTo => "reacted_to",
             Relation::MemberOf => "member_of",
             Relation::BelongsTo => "belongs_to",
             Relation::PostedIn => "posted_in",
@@ -132,7 +157,10 @@ impl Relation {
             Relation::ThreadOf => "thread_of",
             Relation::EditedFile => "edited_file",
             Relation::CommittedTo => "committed_to",
+            Relation::CoAuthorOf => "co_author_of",
+            Relation::CoEditedWith => "co_edited_with",
             Relation::Imports => "imports",
+            Relation::SameAs => "same_as",
         }
     }
 
@@ -142,6 +170,9 @@ impl Relation {
             "author_of" => Some(Relation::AuthorOf),
             "mentioned_in" => Some(Relation::MentionedIn),
             "reviewer_of" => Some(Relation::ReviewerOf),
+            "attendee_of" => Some(Relation::AttendeeOf),
+            "owner_of" => Some(Relation::OwnerOf),
+            "reacted_to" => Some(Relation::ReactedTo),
             "member_of" => Some(Relation::MemberOf),
             "belongs_to" => Some(Relation::BelongsTo),
             "posted_in" => Some(Relation::PostedIn),
@@ -152,10 +183,37 @@ impl Relation {
             "thread_of" => Some(Relation::ThreadOf),
             "edited_file" => Some(Relation::EditedFile),
             "committed_to" => Some(Relation::CommittedTo),
+            "co_author_of" => Some(Relation::CoAuthorOf),
+            "co_edited_with" => Some(Relation::CoEditedWith),
             "imports" => Some(Relation::Imports),
+            "same_as" => Some(Relation::SameAs),
             _ => None,
         }
     }
+
+    /// How strongly the relation ties its nodes together, multiplied into
+    /// the edge weight when rings are calculated. Writing or being assigned
+    /// something is the closest interaction; a reaction the loosest.
+    pub fn weight(&self) -> f64 {
+        match self {
+            Relation::ReviewerOf | Relation::OwnerOf | Relation::CoEditedWith => 0.8,
+            Relation::AttendeeOf => 0.6,
+            Relation::MentionedIn => 0.5,
+            Relation::ReactedTo => 0.3,
+            _ => 1.0,
+        }
+    }
+
+    /// How sure extraction usually is of this relation: explicit authorship,
+    /// assignment or membership is certain, mentions are parsed from text
+    /// and co-editing is inferred from shared files.
+    pub fn confidence(&self) -> f32 {
+        match self {
+            Relation::MentionedIn => 0.8,
+            Relation::CoEditedWith => 0.6,
+            _ => 1.0,
+        }
+    }
 }
 
 /// A reference to a node, used when creating edges.
@@ -165,6 +223,9 @@ pub struct NodeRef {
     pub provider: String,
     pub external_id: String,
     pub display_name: Option<String>,
+    /// Profile picture; kept on the node when later references have none.
+    #[serde(default)]
+    pub avatar_url: Option<String>,
 }
 
 impl NodeRef {
@@ -179,6 +240,7 @@ impl NodeRef {
             provider: provider.into(),
             external_id: external_id.into(),
             display_name: None,
+            avatar_url: None,
         }
     }
 
@@ -194,9 +256,16 @@ impl NodeRef {
             provider: provider.into(),
             external_id: external_id.into(),
             display_name: Some(display_name.into()),
+            avatar_url: None,
         }
     }
 
+    /// Set the node's profile picture.
+    pub fn with_avatar(mut self, avatar_url: impl Into<String>) -> Self {
+        self.avatar_url = Some(avatar_url.into());
+        self
+    }
+
     /// Convenience constructor for user nodes.
     pub fn user(provider: impl Into<String>, external_id: impl Into<String>) -> Self {
         Self::new(NodeType::User, provider, external_id)
@@ -232,6 +301,16 @@ impl NodeRef {
         Self::new(NodeType::PullRequest, provider, external_id)
     }
 
+    /// Convenience constructor for team nodes.
+    pub fn team(provider: impl Into<String>, external_id: impl Into<String>) -> Self {
+        Self::new(NodeType::Team, provider, external_id)
+    }
+
+    /// Convenience constructor for meeting nodes.
+    pub fn meeting(provider: impl Into<String>, external_id: impl Into<String>) -> Self {
+        Self::new(NodeType::Meeting, provider, external_id)
+    }
+
     /// Convenience constructor for thread nodes.
     pub fn thread(provider: impl Into<String>, external_id: impl Into<String>) -> Self {
         Self::new(NodeType::Thread, provider, external_id)
@@ -265,7 +344,13 @@ pub struct ExtractedEdge {
     pub to: NodeRef,
     pub relation: Relation,
     pub observed_at: DateTime<Utc>,
+    /// Strength of the interaction, on top of [`Relation::weight`]: a direct
+    /// message says more about closeness than a post in a busy channel.
+    pub weight: f32,
     pub metadata: Option<serde_json::Value>,
+    /// How sure the extractor is the relationship exists, from 0 to 1
+    /// (defaults to [`Relation::confidence`]).
+    pub confidence: f32,
 }
 
 impl ExtractedEdge {
@@ -276,10 +361,24 @@ impl ExtractedEdge {
             to,
             relation,
             observed_at,
+            weight: 1.0,
             metadata: None,
+            confidence: relation.confidence(),
         }
     }
 
+    /// Set the interaction strength (default 1.0).
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set how sure the extractor is of the edge, clamped to 0..=1.
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence.clamp(0.0, 1.0);
+        self
+    }
+
     /// Create an edge with metadata.
     pub fn with_metadata(
         from: NodeRef,
@@ -293,7 +392,9 @@ impl ExtractedEdge {
             to,
             relation,
             observed_at,
+            weight: 1.0,
             metadata: Some(metadata),
+            confidence: relation.confidence(),
         }
     }
 }
@@ -309,6 +410,25 @@ pub struct GraphNode {
     pub metadata: Option<serde_json::Value>,
     pub first_seen_at: DateTime<Utc>,
     pub last_seen_at: DateTime<Utc>,
+    /// Distinct neighbours, as of the last centrality pass.
+    #[serde(default)]
+    pub degree: i64,
+    /// Weighted PageRank, as of the last centrality pass; the scores of all
+    /// nodes sum to 1.
+    #[serde(default)]
+    pub pagerank: f64,
+    /// Profile picture, for people whose provider has one.
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+}
+
+/// A node's centrality, computed by
+/// [`RingEngine::recalculate_centrality`](crate::RingEngine::recalculate_centrality).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeCentrality {
+    pub node_id: String,
+    pub degree: i64,
+    pub pagerank: f64,
 }
 
 /// A stored edge in the graph.
@@ -321,7 +441,82 @@ pub struct GraphEdge {
     pub provider: String,
     pub observed_at: DateTime<Utc>,
     pub weight: f32,
+    /// Documents the edge was observed in, including those whose sources
+    /// were compacted away (see [`PrunePolicy`](crate::PrunePolicy)).
+    #[serde(default = "one")]
+    pub count: i64,
     pub metadata: Option<serde_json::Value>,
+    /// How sure extraction was of the edge, from 0 to 1; the highest of its
+    /// observations.
+    #[serde(default = "certain")]
+    pub confidence: f32,
+}
+
+fn one() -> i64 {
+    1
+}
+
+fn certain() -> f32 {
+    1.0
+}
+
+/// A node found by [`GraphStore::neighbors`](crate::GraphStore::neighbors).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub node: GraphNode,
+    /// Hops from the starting node.
+    pub depth: usize,
+    /// Relation of the edge the node was first reached by.
+    pub relation: Relation,
+}
+
+/// How two nodes are connected: `nodes[i]` and `nodes[i + 1]` are joined by
+/// `edges[i]`, in either direction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphPath {
+    pub nodes: Vec<String>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// The nodes around a seed node and the edges between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subgraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// A span of time for graph queries and ring calculations, matched
+/// against when edges were last observed. Open ends are unbounded; the
+/// default is all time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// The last `days` days up to now.
+    pub fn last_days(days: i64) -> Self {
+        Self {
+            since: Some(Utc::now() - chrono::Duration::days(days)),
+            until: None,
+        }
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| at >= since) && self.until.is_none_or(|until| at <= until)
+    }
+}
+
+/// A document an edge was extracted from (its provenance).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeSource {
+    pub edge_id: i64,
+    /// `documents.id` of the message, issue or file the edge came from.
+    pub doc_id: i64,
+    pub observed_at: DateTime<Utc>,
 }
 
 /// Ring assignment for proximity-based sync.
@@ -350,6 +545,21 @@ impl Ring {
     pub fn as_int(&self) -> i32 {
         *self as i32
     }
+
+    /// Whether this ring is closer to the user than `other`.
+    pub fn is_closer_than(&self, other: Ring) -> bool {
+        self.as_int() < other.as_int()
+    }
+
+    /// Name shown to the user, e.g. "your Core ring".
+    pub fn label(&self) -> &'static str {
+        match self {
+            Ring::Core => "your Core ring",
+            Ring::One => "Ring 1",
+            Ring::Two => "Ring 2",
+            Ring::Beyond => "Beyond",
+        }
+    }
 }
 
 /// A ring assignment for a node.
@@ -361,12 +571,212 @@ pub struct RingAssignment {
     pub effective_distance: f32,
     pub path: Vec<String>,
     pub computed_at: DateTime<Utc>,
+    /// Ring the node has been computed into without the change taking effect
+    /// yet (see `RingConfig::promote_after`).
+    #[serde(default)]
+    pub pending_ring: Option<Ring>,
+    /// Consecutive recalculations that computed `pending_ring`.
+    #[serde(default)]
+    pub pending_count: u32,
+}
+
+/// Why a node changed rings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RingChangeReason {
+    /// Moved closer through new or more frequent interaction
+    #[default]
+    Interaction,
+    /// Moved out as interactions aged or were pruned
+    Decay,
+    /// Became one of the user's own accounts
+    Linked,
+    /// No path to the user remains
+    Unreachable,
+}
+
+impl RingChangeReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RingChangeReason::Interaction => "interaction",
+            RingChangeReason::Decay => "decay",
+            RingChangeReason::Linked => "linked",
+            RingChangeReason::Unreachable => "unreachable",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "interaction" => Some(RingChangeReason::Interaction),
+            "decay" => Some(RingChangeReason::Decay),
+            "linked" => Some(RingChangeReason::Linked),
+            "unreachable" => Some(RingChangeReason::Unreachable),
+            _ => None,
+        }
+    }
+
+    /// The reason for a move between reachable rings, from its direction.
+    pub fn for_move(from: Ring, to: Ring) -> Self {
+        if to.is_closer_than(from) {
+            RingChangeReason::Interaction
+        } else {
+            RingChangeReason::Decay
+        }
+    }
+}
+
+/// A node moving between rings, recorded when a recalculation changes its
+/// assignment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RingTransition {
+    pub node_id: String,
+    pub from: Ring,
+    pub to: Ring,
+    pub effective_distance: f32,
+    pub transitioned_at: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: RingChangeReason,
+}
+
+impl RingTransition {
+    /// True if the node moved closer to the user.
+    pub fn is_promotion(&self) -> bool {
+        self.to.is_closer_than(self.from)
+    }
+}
+
+/// A node's net ring change over one or more recalculations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RingChange {
+    pub node_id: String,
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    pub from: Ring,
+    pub to: Ring,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl RingChange {
+    /// One line for the UI, e.g. "Alice moved into your Core ring".
+    pub fn describe(&self) -> String {
+        let name = self.display_name.as_deref().unwrap_or(&self.node_id);
+        if self.to.is_closer_than(self.from) {
+            format!("{} moved into {}", name, self.to.label())
+        } else {
+            format!("{} moved out to {}", name, self.to.label())
+        }
+    }
+}
+
+/// Nodes that moved closer to or farther from the user, between two
+/// recalculations or over a period.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RingDiff {
+    pub promoted: Vec<RingChange>,
+    pub demoted: Vec<RingChange>,
+}
+
+impl RingDiff {
+    /// Net changes from transitions in the order they happened: a node
+    /// that moved back to where it started is left out. Display names are
+    /// not filled in (see [`GraphStore::name_ring_diff`](crate::GraphStore::name_ring_diff)).
+    pub fn from_transitions(transitions: &[RingTransition]) -> Self {
+        let mut net: Vec<RingChange> = Vec::new();
+        for transition in transitions {
+            match net.iter_mut().find(|change| change.node_id == transition.node_id) {
+                Some(change) => {
+                    change.to = transition.to;
+                    change.changed_at = transition.transitioned_at;
+                }
+                None => net.push(RingChange {
+                    node_id: transition.node_id.clone(),
+                    display_name: None,
+                    avatar_url: None,
+                    from: transition.from,
+                    to: transition.to,
+                    changed_at: transition.transitioned_at,
+                }),
+            }
+        }
+        let (promoted, demoted) = net
+            .into_iter()
+            .filter(|change| change.from != change.to)
+            .partition(|change| change.to.is_closer_than(change.from));
+        Self { promoted, demoted }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.promoted.is_empty() && self.demoted.is_empty()
+    }
+
+    /// Every change, promotions first.
+    pub fn changes(&self) -> impl Iterator<Item = &RingChange> {
+        self.promoted.iter().chain(&self.demoted)
+    }
+}
+
+/// A person the user recently worked with, found by
+/// [`RingEngine::collaborators`](crate::RingEngine::collaborators).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collaborator {
+    pub node_id: String,
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// Other accounts of the same person that contributed to the score.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked_ids: Vec<String>,
+    pub ring: Ring,
+    /// Weighted, decayed interaction shared with the user.
+    pub score: f64,
+    /// Connections the score came from: direct edges and the messages,
+    /// issues, channels etc. both are connected to.
+    pub shared: usize,
+    pub last_interaction: DateTime<Utc>,
+}
+
+/// Collaborators in one ring, closest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollaboratorGroup {
+    pub ring: Ring,
+    pub people: Vec<Collaborator>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_ring_diff() {
+        let at = Utc::now();
+        let transition = |node: &str, from, to| RingTransition {
+            node_id: node.to_string(),
+            from,
+            to,
+            effective_distance: 1.0,
+            transitioned_at: at,
+            reason: RingChangeReason::for_move(from, to),
+        };
+        let diff = RingDiff::from_transitions(&[
+            transition("user:slack:alice", Ring::One, Ring::Core),
+            transition("user:slack:bob", Ring::One, Ring::Two),
+            transition("user:slack:carol", Ring::Two, Ring::One),
+            transition("user:slack:carol", Ring::One, Ring::Two),
+            transition("user:slack:bob", Ring::Two, Ring::Beyond),
+        ]);
+        assert_eq!(diff.promoted.len(), 1);
+        assert_eq!(diff.demoted.len(), 1);
+        assert_eq!((diff.demoted[0].from, diff.demoted[0].to), (Ring::One, Ring::Beyond));
+
+        let mut alice = diff.promoted[0].clone();
+        assert_eq!(alice.describe(), "user:slack:alice moved into your Core ring");
+        alice.display_name = Some("Alice".to_string());
+        assert_eq!(alice.describe(), "Alice moved into your Core ring");
+        assert_eq!(diff.demoted[0].describe(), "user:slack:bob moved out to Beyond");
+        assert!(RingDiff::from_transitions(&[]).is_empty());
+    }
+
     #[test]
     fn test_node_ref_canonical_id() {
         let node = NodeRef::user("slack", "U123");
@@ -389,6 +799,8 @@ mod tests {
             NodeType::Thread,
             NodeType::Commit,
             NodeType::File,
+            NodeType::Team,
+            NodeType::Meeting,
         ] {
             let s = node_type.as_str();
             let parsed = NodeType::parse(s).unwrap();
@@ -403,6 +815,9 @@ mod tests {
             Relation::AuthorOf,
             Relation::MentionedIn,
             Relation::ReviewerOf,
+            Relation::AttendeeOf,
+            Relation::OwnerOf,
+            Relation::ReactedTo,
             Relation::MemberOf,
             Relation::BelongsTo,
             Relation::PostedIn,
@@ -413,11 +828,15 @@ mod tests {
             Relation::ThreadOf,
             Relation::EditedFile,
             Relation::CommittedTo,
+            Relation::CoAuthorOf,
+            Relation::CoEditedWith,
             Relation::Imports,
+            Relation::SameAs,
         ] {
             let s = relation.as_str();
             let parsed = Relation::parse(s).unwrap();
             assert_eq!(relation, parsed);
+            assert_eq!(serde_json::to_value(relation).unwrap(), s);
         }
     }
 }
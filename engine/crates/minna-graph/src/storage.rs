@@ -3,15 +3,87 @@
 //! This module provides the `GraphStore` struct for persisting and querying
 //! the relationship graph in SQLite.
 
+use std::collections::{HashMap, HashSet};
+
 use anyhow::Result;
-use chrono::{DateTime, Utc};
-use sqlx::SqlitePool;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
 use tracing::instrument;
 
 use crate::schema::{
-    ExtractedEdge, GraphEdge, GraphNode, NodeRef, NodeType, Relation, Ring, RingAssignment,
+    EdgeSource, ExtractedEdge, GraphEdge, GraphNode, GraphPath, Neighbor, NodeCentrality, NodeRef, NodeType, Relation, Ring,
+    RingAssignment, RingChangeReason, RingDiff, RingTransition, Subgraph, TimeRange,
 };
 
+/// Hops [`GraphStore::shortest_path`] searches before giving up.
+pub const MAX_PATH_HOPS: usize = 6;
+
+/// Provider of identity links, which no single provider owns.
+const IDENTITY_PROVIDER: &str = "identity";
+
+/// How [`GraphStore::prune`] keeps the graph from growing without bound.
+/// Missing fields take their defaults when deserialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrunePolicy {
+    /// Edges not observed for this many days are removed; `None` keeps
+    /// them forever (default: 365). Identity links are never pruned.
+    pub max_age_days: Option<i64>,
+    /// Move pruned edges to `graph_edges_archive` instead of deleting them
+    /// (default: true)
+    pub archive: bool,
+    /// Source documents kept per edge; older ones are dropped and only
+    /// counted (default: 20)
+    pub max_sources_per_edge: usize,
+}
+
+impl Default for PrunePolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: Some(365),
+            archive: true,
+            max_sources_per_edge: 20,
+        }
+    }
+}
+
+/// What [`GraphStore::prune`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PruneReport {
+    /// Stale edges moved to the archive
+    pub edges_archived: u64,
+    /// Stale edges deleted outright
+    pub edges_deleted: u64,
+    /// Source rows dropped, from stale edges or beyond the per-edge limit
+    pub sources_dropped: u64,
+}
+
+/// Edges one provider has contributed, from [`GraphStore::stats`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderEdgeStats {
+    pub provider: String,
+    pub edges: i64,
+    /// When an edge was last extracted from this provider's data
+    pub last_extracted_at: Option<DateTime<Utc>>,
+    /// The most recent interaction among its edges
+    pub last_observed_at: Option<DateTime<Utc>>,
+}
+
+/// What the graph holds, from [`GraphStore::stats`]: enough to tell whether
+/// syncs are populating it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphStoreStats {
+    pub nodes: i64,
+    pub edges: i64,
+    /// Node count per type, largest first
+    pub nodes_by_type: Vec<(String, i64)>,
+    /// Edge count per relation, largest first
+    pub edges_by_relation: Vec<(String, i64)>,
+    /// Per provider, most edges first
+    pub providers: Vec<ProviderEdgeStats>,
+}
+
 /// Graph storage backed by SQLite.
 #[derive(Clone)]
 pub struct GraphStore {
@@ -38,12 +110,42 @@ impl GraphStore {
                 metadata JSON,
                 first_seen_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 last_seen_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                degree INTEGER NOT NULL DEFAULT 0,
+                pagerank REAL NOT NULL DEFAULT 0,
+                avatar_url TEXT,
                 UNIQUE(provider, external_id)
             )",
         )
         .execute(pool)
         .await?;
 
+        // Databases created before centrality metrics lack their columns
+        let has_centrality: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('graph_nodes') WHERE name = 'pagerank'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_centrality {
+            sqlx::query("ALTER TABLE graph_nodes ADD COLUMN degree INTEGER NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+            sqlx::query("ALTER TABLE graph_nodes ADD COLUMN pagerank REAL NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+        }
+
+        // Databases created before profile pictures were kept lack the column
+        let has_avatar_url: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('graph_nodes') WHERE name = 'avatar_url'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_avatar_url {
+            sqlx::query("ALTER TABLE graph_nodes ADD COLUMN avatar_url TEXT")
+                .execute(pool)
+                .await?;
+        }
+
         // Graph edges
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS graph_edges (
@@ -54,7 +156,10 @@ impl GraphStore {
                 provider TEXT NOT NULL,
                 observed_at TEXT NOT NULL,
                 weight REAL NOT NULL DEFAULT 1.0,
+                count INTEGER NOT NULL DEFAULT 1,
                 metadata JSON,
+                extracted_at TEXT,
+                confidence REAL NOT NULL DEFAULT 1.0,
                 UNIQUE(from_node, to_node, relation, provider)
             )",
         )
@@ -75,6 +180,93 @@ impl GraphStore {
             .execute(pool)
             .await?;
 
+        // Edges pruned by age, kept for inspection rather than dropped
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS graph_edges_archive (
+                id INTEGER PRIMARY KEY,
+                from_node TEXT NOT NULL,
+                to_node TEXT NOT NULL,
+                relation TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                observed_at TEXT NOT NULL,
+                weight REAL NOT NULL,
+                count INTEGER NOT NULL,
+                metadata JSON,
+                archived_at TEXT NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+
+        // Documents each edge was extracted from
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS graph_edge_sources (
+                edge_id INTEGER NOT NULL,
+                doc_id INTEGER NOT NULL,
+                observed_at TEXT NOT NULL,
+                PRIMARY KEY (edge_id, doc_id)
+            )",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_edge_sources_doc ON graph_edge_sources(doc_id)")
+            .execute(pool)
+            .await?;
+
+        // Databases created before compaction lack the observation count;
+        // until then every source was kept, so they are the count
+        let has_count: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('graph_edges') WHERE name = 'count'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_count {
+            sqlx::query("ALTER TABLE graph_edges ADD COLUMN count INTEGER NOT NULL DEFAULT 1")
+                .execute(pool)
+                .await?;
+            sqlx::query(
+                "UPDATE graph_edges SET count = MAX(1, (SELECT COUNT(*) FROM graph_edge_sources s WHERE s.edge_id = graph_edges.id))",
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        // Databases created before extraction times were kept lack the column;
+        // an edge was extracted no earlier than it was observed
+        let has_extracted_at: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('graph_edges') WHERE name = 'extracted_at'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_extracted_at {
+            sqlx::query("ALTER TABLE graph_edges ADD COLUMN extracted_at TEXT")
+                .execute(pool)
+                .await?;
+            sqlx::query("UPDATE graph_edges SET extracted_at = observed_at")
+                .execute(pool)
+                .await?;
+        }
+
+        // Databases created before confidence was kept lack the column;
+        // their edges count as certain
+        let has_confidence: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('graph_edges') WHERE name = 'confidence'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_confidence {
+            sqlx::query("ALTER TABLE graph_edges ADD COLUMN confidence REAL NOT NULL DEFAULT 1.0")
+                .execute(pool)
+                .await?;
+            sqlx::query(
+                "UPDATE graph_edges SET confidence = COALESCE(json_extract(metadata, '$.confidence'), 1.0)
+                 WHERE relation = ?1",
+            )
+            .bind(Relation::SameAs.as_str())
+            .execute(pool)
+            .await?;
+        }
+
         // User identity linking
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS user_identities (
@@ -111,146 +303,467 @@ impl GraphStore {
         .execute(pool)
         .await?;
 
+        // Databases created before ring hysteresis lack the pending columns
+        let has_pending: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('ring_assignments') WHERE name = 'pending_ring'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_pending {
+            sqlx::query("ALTER TABLE ring_assignments ADD COLUMN pending_ring INTEGER")
+                .execute(pool)
+                .await?;
+            sqlx::query("ALTER TABLE ring_assignments ADD COLUMN pending_count INTEGER NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+        }
+
+        // Ring change log
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS ring_transitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                node_id TEXT NOT NULL,
+                from_ring INTEGER NOT NULL,
+                to_ring INTEGER NOT NULL,
+                effective_distance REAL NOT NULL,
+                transitioned_at TEXT NOT NULL,
+                reason TEXT
+            )",
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_ring_transitions_at ON ring_transitions(transitioned_at)")
+            .execute(pool)
+            .await?;
+        // Transitions logged before reasons were kept infer theirs from direction
+        let has_reason: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('ring_transitions') WHERE name = 'reason'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_reason {
+            sqlx::query("ALTER TABLE ring_transitions ADD COLUMN reason TEXT")
+                .execute(pool)
+                .await?;
+        }
+
+        // Closest ring per document, so search can boost in one lookup
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS doc_rings (
+                doc_id INTEGER PRIMARY KEY,
+                ring INTEGER NOT NULL
+            )",
+        )
+        .execute(pool)
+        .await?;
+        // Fill it for rings calculated before the cache existed
+        let cached: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM doc_rings)")
+            .fetch_one(pool)
+            .await?;
+        if !cached {
+            Self::new(pool.clone()).refresh_doc_rings().await?;
+        }
+
         Ok(())
     }
 
     /// Upsert a node into the graph.
     #[instrument(skip(self))]
     pub async fn upsert_node(&self, node_ref: &NodeRef) -> Result<String> {
-        let id = node_ref.canonical_id();
-        let now = Utc::now().to_rfc3339();
+        let mut conn = self.pool.acquire().await?;
+        upsert_node_on(&mut conn, node_ref).await
+    }
+
+    /// Upsert an edge into the graph (creates nodes if needed). The edge
+    /// keeps the strongest interaction weight it was observed with.
+    #[instrument(skip(self))]
+    pub async fn upsert_edge(&self, edge: &ExtractedEdge) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        upsert_edge_on(&mut conn, edge).await
+    }
+
+    /// Upsert many edges in one transaction, returning their IDs in order.
+    /// Much faster than one [`Self::upsert_edge`] call per edge for the
+    /// thousands of edges a full sync extracts.
+    pub async fn upsert_edges(&self, edges: &[ExtractedEdge]) -> Result<Vec<i64>> {
+        let mut tx = self.pool.begin().await?;
+        let mut ids = Vec::with_capacity(edges.len());
+        for edge in edges {
+            ids.push(upsert_edge_on(&mut tx, edge).await?);
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    /// Upsert an edge extracted from a stored document and record the
+    /// document as one of its sources. A document not seen before for this
+    /// edge adds to its observation count (the first one is the edge itself).
+    pub async fn upsert_edge_from_document(&self, edge: &ExtractedEdge, doc_id: i64) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        let edge_id = upsert_edge_on(&mut conn, edge).await?;
+        record_edge_source_on(&mut conn, edge_id, doc_id, edge.observed_at).await?;
+        Ok(edge_id)
+    }
+
+    /// [`Self::upsert_edge_from_document`] for all edges extracted from a
+    /// document, in one transaction.
+    pub async fn upsert_edges_from_document(&self, edges: &[ExtractedEdge], doc_id: i64) -> Result<Vec<i64>> {
+        let mut tx = self.pool.begin().await?;
+        let mut ids = Vec::with_capacity(edges.len());
+        for edge in edges {
+            let edge_id = upsert_edge_on(&mut tx, edge).await?;
+            record_edge_source_on(&mut tx, edge_id, doc_id, edge.observed_at).await?;
+            ids.push(edge_id);
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    /// Documents an edge was extracted from, most recent first.
+    pub async fn edge_sources(&self, edge_id: i64, limit: usize) -> Result<Vec<EdgeSource>> {
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "SELECT edge_id, doc_id, observed_at FROM graph_edge_sources
+             WHERE edge_id = ?1 ORDER BY observed_at DESC LIMIT ?2",
+        )
+        .bind(edge_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(edge_source_from_row).collect())
+    }
+
+    /// Documents behind any edge between two nodes (either direction), most
+    /// recent first. This is what cites the conversations linking two people.
+    pub async fn sources_between(&self, node_a: &str, node_b: &str, limit: usize) -> Result<Vec<EdgeSource>> {
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "SELECT s.edge_id, s.doc_id, s.observed_at
+             FROM graph_edge_sources s JOIN graph_edges e ON e.id = s.edge_id
+             WHERE (e.from_node = ?1 AND e.to_node = ?2) OR (e.from_node = ?2 AND e.to_node = ?1)
+             ORDER BY s.observed_at DESC LIMIT ?3",
+        )
+        .bind(node_a)
+        .bind(node_b)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(edge_source_from_row).collect())
+    }
+
+    /// Sources of all of `edge_ids`, ordered by edge.
+    pub async fn sources_for_edges(&self, edge_ids: &[i64]) -> Result<Vec<EdgeSource>> {
+        if edge_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "SELECT edge_id, doc_id, observed_at FROM graph_edge_sources
+             WHERE edge_id IN (SELECT value FROM json_each(?1)) ORDER BY edge_id, doc_id",
+        )
+        .bind(serde_json::to_string(edge_ids)?)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(edge_source_from_row).collect())
+    }
+
+    /// Record `doc_id` as a source of an existing edge.
+    pub async fn add_edge_source(&self, source: &EdgeSource) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.add_edge_source_on(&mut conn, source).await
+    }
+
+    /// [`Self::add_edge_source`] on `conn`, inside the caller's transaction.
+    pub async fn add_edge_source_on(&self, conn: &mut SqliteConnection, source: &EdgeSource) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO graph_edge_sources (edge_id, doc_id, observed_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(edge_id, doc_id) DO UPDATE SET
+                observed_at = MAX(observed_at, excluded.observed_at)",
+        )
+        .bind(source.edge_id)
+        .bind(source.doc_id)
+        .bind(source.observed_at.to_rfc3339())
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
 
+    /// Insert a node copied from another graph (e.g. an export bundle),
+    /// merging with an existing node: the display name is kept if the copy
+    /// has none, and the seen range is widened.
+    pub async fn import_node(&self, node: &GraphNode) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.import_node_on(&mut conn, node).await
+    }
+
+    /// [`Self::import_node`] on `conn`, inside the caller's transaction.
+    pub async fn import_node_on(&self, conn: &mut SqliteConnection, node: &GraphNode) -> Result<()> {
         sqlx::query(
-            "INSERT INTO graph_nodes (id, node_type, provider, external_id, display_name, first_seen_at, last_seen_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            "INSERT INTO graph_nodes (id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, avatar_url)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
              ON CONFLICT(id) DO UPDATE SET
                 display_name = COALESCE(excluded.display_name, graph_nodes.display_name),
-                last_seen_at = excluded.last_seen_at",
-        )
-        .bind(&id)
-        .bind(node_ref.node_type.as_str())
-        .bind(&node_ref.provider)
-        .bind(&node_ref.external_id)
-        .bind(&node_ref.display_name)
-        .bind(&now)
-        .execute(&self.pool)
+                metadata = COALESCE(excluded.metadata, graph_nodes.metadata),
+                avatar_url = COALESCE(excluded.avatar_url, graph_nodes.avatar_url),
+                first_seen_at = MIN(graph_nodes.first_seen_at, excluded.first_seen_at),
+                last_seen_at = MAX(graph_nodes.last_seen_at, excluded.last_seen_at)",
+        )
+        .bind(&node.id)
+        .bind(node.node_type.as_str())
+        .bind(&node.provider)
+        .bind(&node.external_id)
+        .bind(&node.display_name)
+        .bind(node.metadata.as_ref().map(|m| m.to_string()))
+        .bind(node.first_seen_at.to_rfc3339())
+        .bind(node.last_seen_at.to_rfc3339())
+        .bind(&node.avatar_url)
+        .execute(conn)
         .await?;
-
-        Ok(id)
+        Ok(())
     }
 
-    /// Upsert an edge into the graph (creates nodes if needed).
-    #[instrument(skip(self))]
-    pub async fn upsert_edge(&self, edge: &ExtractedEdge) -> Result<i64> {
-        // Ensure both nodes exist
-        let from_id = self.upsert_node(&edge.from).await?;
-        let to_id = self.upsert_node(&edge.to).await?;
+    /// Insert an edge copied from another graph, keeping the later
+    /// observation if it already exists. Both nodes must exist. Returns the
+    /// edge's ID in this graph.
+    pub async fn import_edge(&self, edge: &GraphEdge) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        self.import_edge_on(&mut conn, edge).await
+    }
 
-        // Upsert edge
+    /// [`Self::import_edge`] on `conn`, inside the caller's transaction.
+    pub async fn import_edge_on(&self, conn: &mut SqliteConnection, edge: &GraphEdge) -> Result<i64> {
         let id: i64 = sqlx::query_scalar(
-            "INSERT INTO graph_edges (from_node, to_node, relation, provider, observed_at, weight, metadata)
-             VALUES (?1, ?2, ?3, ?4, ?5, 1.0, ?6)
+            "INSERT INTO graph_edges (from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
              ON CONFLICT(from_node, to_node, relation, provider) DO UPDATE SET
-                observed_at = excluded.observed_at,
-                metadata = COALESCE(excluded.metadata, graph_edges.metadata)
+                observed_at = MAX(graph_edges.observed_at, excluded.observed_at),
+                weight = MAX(graph_edges.weight, excluded.weight),
+                count = MAX(graph_edges.count, excluded.count),
+                metadata = COALESCE(graph_edges.metadata, excluded.metadata),
+                confidence = MAX(graph_edges.confidence, excluded.confidence)
              RETURNING id",
         )
-        .bind(&from_id)
-        .bind(&to_id)
+        .bind(&edge.from_node)
+        .bind(&edge.to_node)
         .bind(edge.relation.as_str())
-        .bind(&edge.from.provider) // Use from node's provider as edge provider
+        .bind(&edge.provider)
         .bind(edge.observed_at.to_rfc3339())
+        .bind(edge.weight as f64)
+        .bind(edge.count)
         .bind(edge.metadata.as_ref().map(|m| m.to_string()))
-        .fetch_one(&self.pool)
+        .bind(edge.confidence as f64)
+        .fetch_one(conn)
         .await?;
-
         Ok(id)
     }
 
+    /// Nodes with IDs after `after_id`, in ID order, for paging through the
+    /// whole graph.
+    pub async fn nodes_after(&self, after_id: &str, limit: usize) -> Result<Vec<GraphNode>> {
+        let rows = sqlx::query_as::<_, NodeRow>(
+            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url
+             FROM graph_nodes WHERE id > ?1 ORDER BY id LIMIT ?2",
+        )
+        .bind(after_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(node_from_row).collect())
+    }
+
+    /// Forget documents as edge sources (after they are purged). The edges
+    /// themselves stay; they are removed per provider.
+    pub async fn delete_edge_sources_for_documents(&self, doc_ids: &[i64]) -> Result<u64> {
+        if doc_ids.is_empty() {
+            return Ok(0);
+        }
+        let doc_ids = serde_json::to_string(doc_ids)?;
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "DELETE FROM graph_edge_sources WHERE doc_id IN (SELECT value FROM json_each(?1))",
+        )
+        .bind(&doc_ids)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM doc_rings WHERE doc_id IN (SELECT value FROM json_each(?1))")
+            .bind(&doc_ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Periodic cleanup: drop provenance rows whose edge no longer exists.
+    /// Returns the number removed.
+    pub async fn maintenance(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM graph_edge_sources WHERE edge_id NOT IN (SELECT id FROM graph_edges)",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     /// Get a node by its canonical ID.
     pub async fn get_node(&self, id: &str) -> Result<Option<GraphNode>> {
-        let row = sqlx::query_as::<_, (String, String, String, String, Option<String>, Option<String>, String, String)>(
-            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at
+        let row = sqlx::query_as::<_, NodeRow>(
+            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url
              FROM graph_nodes WHERE id = ?1",
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|(id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at)| {
-            GraphNode {
-                id,
-                node_type: NodeType::parse(&node_type).unwrap_or(NodeType::User),
-                provider,
-                external_id,
-                display_name,
-                metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
-                first_seen_at: DateTime::parse_from_rfc3339(&first_seen_at)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-                last_seen_at: DateTime::parse_from_rfc3339(&last_seen_at)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-            }
-        }))
+        Ok(row.map(node_from_row))
     }
 
     /// Get all edges originating from a node.
     pub async fn edges_from(&self, node_id: &str) -> Result<Vec<GraphEdge>> {
-        let rows = sqlx::query_as::<_, (i64, String, String, String, String, String, f64, Option<String>)>(
-            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, metadata
+        let rows = sqlx::query_as::<_, EdgeRow>(
+            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence
              FROM graph_edges WHERE from_node = ?1",
         )
         .bind(node_id)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|(id, from_node, to_node, relation, provider, observed_at, weight, metadata)| {
-                GraphEdge {
-                    id,
-                    from_node,
-                    to_node,
-                    relation: Relation::parse(&relation).unwrap_or(Relation::References),
-                    provider,
-                    observed_at: DateTime::parse_from_rfc3339(&observed_at)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    weight: weight as f32,
-                    metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
-                }
-            })
-            .collect())
+        Ok(rows.into_iter().map(edge_from_row).collect())
+    }
+
+    /// Edges with IDs above `after_id`, in ID order, for paging through the
+    /// whole graph.
+    pub async fn edges_after(&self, after_id: i64, limit: usize) -> Result<Vec<GraphEdge>> {
+        let rows = sqlx::query_as::<_, EdgeRow>(
+            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence
+             FROM graph_edges WHERE id > ?1 ORDER BY id LIMIT ?2",
+        )
+        .bind(after_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(edge_from_row).collect())
     }
 
     /// Get all edges pointing to a node.
     pub async fn edges_to(&self, node_id: &str) -> Result<Vec<GraphEdge>> {
-        let rows = sqlx::query_as::<_, (i64, String, String, String, String, String, f64, Option<String>)>(
-            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, metadata
+        let rows = sqlx::query_as::<_, EdgeRow>(
+            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence
              FROM graph_edges WHERE to_node = ?1",
         )
         .bind(node_id)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows
+        Ok(rows.into_iter().map(edge_from_row).collect())
+    }
+
+    /// Edges touching a node in either direction, each with the node at its
+    /// other end.
+    async fn adjacent(&self, node_id: &str, range: TimeRange) -> Result<Vec<(GraphEdge, String)>> {
+        let mut adjacent: Vec<(GraphEdge, String)> = self
+            .edges_from(node_id)
+            .await?
             .into_iter()
-            .map(|(id, from_node, to_node, relation, provider, observed_at, weight, metadata)| {
-                GraphEdge {
-                    id,
-                    from_node,
-                    to_node,
-                    relation: Relation::parse(&relation).unwrap_or(Relation::References),
-                    provider,
-                    observed_at: DateTime::parse_from_rfc3339(&observed_at)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    weight: weight as f32,
-                    metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
-                }
+            .filter(|edge| range.contains(edge.observed_at))
+            .map(|edge| {
+                let other = edge.to_node.clone();
+                (edge, other)
             })
-            .collect())
+            .collect();
+        for edge in self.edges_to(node_id).await?.into_iter().filter(|edge| range.contains(edge.observed_at)) {
+            let other = edge.from_node.clone();
+            adjacent.push((edge, other));
+        }
+        Ok(adjacent)
+    }
+
+    /// Nodes within `depth` hops of a node, following edges in either
+    /// direction, nearest first. With `relations`, only edges of those
+    /// relations are followed, and only edges observed in `range`.
+    pub async fn neighbors(
+        &self,
+        node_id: &str,
+        depth: usize,
+        relations: Option<&[Relation]>,
+        range: TimeRange,
+    ) -> Result<Vec<Neighbor>> {
+        let mut seen = HashSet::from([node_id.to_string()]);
+        let mut frontier = vec![node_id.to_string()];
+        let mut neighbors = Vec::new();
+        for hop in 1..=depth {
+            let mut next = Vec::new();
+            for current in &frontier {
+                for (edge, other) in self.adjacent(current, range).await? {
+                    if relations.is_some_and(|relations| !relations.contains(&edge.relation)) {
+                        continue;
+                    }
+                    if !seen.insert(other.clone()) {
+                        continue;
+                    }
+                    if let Some(node) = self.get_node(&other).await? {
+                        neighbors.push(Neighbor {
+                            node,
+                            depth: hop,
+                            relation: edge.relation,
+                        });
+                    }
+                    next.push(other);
+                }
+            }
+            frontier = next;
+        }
+        Ok(neighbors)
+    }
+
+    /// The shortest chain of edges observed in `range` joining two nodes (in
+    /// either direction), or None if they aren't connected within
+    /// [`MAX_PATH_HOPS`].
+    pub async fn shortest_path(&self, from: &str, to: &str, range: TimeRange) -> Result<Option<GraphPath>> {
+        if from == to {
+            return Ok(Some(GraphPath { nodes: vec![from.to_string()], edges: Vec::new() }));
+        }
+        // Node -> (previous node, edge to it)
+        let mut reached: HashMap<String, Option<(String, GraphEdge)>> = HashMap::from([(from.to_string(), None)]);
+        let mut frontier = vec![from.to_string()];
+        for _ in 0..MAX_PATH_HOPS {
+            let mut next = Vec::new();
+            for current in &frontier {
+                for (edge, other) in self.adjacent(current, range).await? {
+                    if reached.contains_key(&other) {
+                        continue;
+                    }
+                    reached.insert(other.clone(), Some((current.clone(), edge)));
+                    if other == to {
+                        return Ok(Some(trace_path(&reached, to)));
+                    }
+                    next.push(other);
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        Ok(None)
+    }
+
+    /// The nodes within `depth` hops of `seed` and every edge between them,
+    /// counting only edges observed in `range`.
+    pub async fn subgraph(&self, seed: &str, depth: usize, range: TimeRange) -> Result<Subgraph> {
+        let neighbors = self.neighbors(seed, depth, None, range).await?;
+        let mut nodes: Vec<GraphNode> = self.get_node(seed).await?.into_iter().collect();
+        nodes.extend(neighbors.into_iter().map(|neighbor| neighbor.node));
+        let ids: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+
+        let mut edges = Vec::new();
+        for node in &nodes {
+            for edge in self.edges_from(&node.id).await? {
+                if ids.contains(edge.to_node.as_str()) && range.contains(edge.observed_at) {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges.sort_by_key(|edge| edge.id);
+        Ok(Subgraph { nodes, edges })
     }
 
     /// Get total node count.
@@ -269,10 +782,45 @@ impl GraphStore {
         Ok(count)
     }
 
+    /// Node and edge counts by type and provider, and when each provider's
+    /// edges were last extracted.
+    pub async fn stats(&self) -> Result<GraphStoreStats> {
+        let edges_by_relation = sqlx::query_as::<_, (String, i64)>(
+            "SELECT relation, COUNT(*) as count FROM graph_edges GROUP BY relation ORDER BY count DESC, relation",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let providers = sqlx::query_as::<_, (String, i64, Option<String>, Option<String>)>(
+            "SELECT provider, COUNT(*) as count, MAX(extracted_at), MAX(observed_at) FROM graph_edges
+             GROUP BY provider ORDER BY count DESC, provider",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let parse = |t: Option<String>| {
+            t.and_then(|t| DateTime::parse_from_rfc3339(&t).ok()).map(|dt| dt.with_timezone(&Utc))
+        };
+
+        Ok(GraphStoreStats {
+            nodes: self.node_count().await?,
+            edges: self.edge_count().await?,
+            nodes_by_type: self.node_count_by_type().await?,
+            edges_by_relation,
+            providers: providers
+                .into_iter()
+                .map(|(provider, edges, extracted_at, observed_at)| ProviderEdgeStats {
+                    provider,
+                    edges,
+                    last_extracted_at: parse(extracted_at),
+                    last_observed_at: parse(observed_at),
+                })
+                .collect(),
+        })
+    }
+
     /// Get node count by type.
     pub async fn node_count_by_type(&self) -> Result<Vec<(String, i64)>> {
         let rows = sqlx::query_as::<_, (String, i64)>(
-            "SELECT node_type, COUNT(*) as count FROM graph_nodes GROUP BY node_type ORDER BY count DESC",
+            "SELECT node_type, COUNT(*) as count FROM graph_nodes GROUP BY node_type ORDER BY count DESC, node_type",
         )
         .fetch_all(&self.pool)
         .await?;
@@ -284,14 +832,16 @@ impl GraphStore {
         let path_json = serde_json::to_string(&assignment.path)?;
 
         sqlx::query(
-            "INSERT INTO ring_assignments (node_id, ring, distance, effective_distance, path, computed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "INSERT INTO ring_assignments (node_id, ring, distance, effective_distance, path, computed_at, pending_ring, pending_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(node_id) DO UPDATE SET
                 ring = excluded.ring,
                 distance = excluded.distance,
                 effective_distance = excluded.effective_distance,
                 path = excluded.path,
-                computed_at = excluded.computed_at",
+                computed_at = excluded.computed_at,
+                pending_ring = excluded.pending_ring,
+                pending_count = excluded.pending_count",
         )
         .bind(&assignment.node_id)
         .bind(assignment.ring.as_int())
@@ -299,6 +849,8 @@ impl GraphStore {
         .bind(assignment.effective_distance)
         .bind(&path_json)
         .bind(assignment.computed_at.to_rfc3339())
+        .bind(assignment.pending_ring.map(|r| r.as_int()))
+        .bind(assignment.pending_count as i64)
         .execute(&self.pool)
         .await?;
 
@@ -307,15 +859,15 @@ impl GraphStore {
 
     /// Get ring assignment for a node.
     pub async fn get_ring_assignment(&self, node_id: &str) -> Result<Option<RingAssignment>> {
-        let row = sqlx::query_as::<_, (String, i32, i32, f64, String, String)>(
-            "SELECT node_id, ring, distance, effective_distance, path, computed_at
+        let row = sqlx::query_as::<_, (String, i32, i32, f64, String, String, Option<i32>, i64)>(
+            "SELECT node_id, ring, distance, effective_distance, path, computed_at, pending_ring, pending_count
              FROM ring_assignments WHERE node_id = ?1",
         )
         .bind(node_id)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|(node_id, ring, distance, effective_distance, path, computed_at)| {
+        Ok(row.map(|(node_id, ring, distance, effective_distance, path, computed_at, pending_ring, pending_count)| {
             RingAssignment {
                 node_id,
                 ring: Ring::from_int(ring),
@@ -325,10 +877,77 @@ impl GraphStore {
                 computed_at: DateTime::parse_from_rfc3339(&computed_at)
                     .map(|dt| dt.with_timezone(&Utc))
                     .unwrap_or_else(|_| Utc::now()),
+                pending_ring: pending_ring.map(Ring::from_int),
+                pending_count: pending_count.max(0) as u32,
             }
         }))
     }
 
+    /// Append a ring change to the transition log.
+    pub async fn record_ring_transition(&self, transition: &RingTransition) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ring_transitions (node_id, from_ring, to_ring, effective_distance, transitioned_at, reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(&transition.node_id)
+        .bind(transition.from.as_int())
+        .bind(transition.to.as_int())
+        .bind(transition.effective_distance)
+        .bind(transition.transitioned_at.to_rfc3339())
+        .bind(transition.reason.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Ring changes since `since`, oldest first.
+    pub async fn ring_transitions_since(&self, since: DateTime<Utc>) -> Result<Vec<RingTransition>> {
+        let rows = sqlx::query_as::<_, (String, i32, i32, f64, String, Option<String>)>(
+            "SELECT node_id, from_ring, to_ring, effective_distance, transitioned_at, reason
+             FROM ring_transitions WHERE transitioned_at >= ?1 ORDER BY id",
+        )
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(node_id, from, to, effective_distance, transitioned_at, reason)| {
+                let (from, to) = (Ring::from_int(from), Ring::from_int(to));
+                RingTransition {
+                    node_id,
+                    from,
+                    to,
+                    effective_distance: effective_distance as f32,
+                    transitioned_at: DateTime::parse_from_rfc3339(&transitioned_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    reason: reason
+                        .as_deref()
+                        .and_then(RingChangeReason::parse)
+                        .unwrap_or_else(|| RingChangeReason::for_move(from, to)),
+                }
+            })
+            .collect())
+    }
+
+    /// Net ring changes since `since`, with display names.
+    pub async fn ring_diff_since(&self, since: DateTime<Utc>) -> Result<RingDiff> {
+        let transitions = self.ring_transitions_since(since).await?;
+        self.name_ring_diff(RingDiff::from_transitions(&transitions)).await
+    }
+
+    /// Fill in the display names and avatars of the nodes in `diff`.
+    pub async fn name_ring_diff(&self, mut diff: RingDiff) -> Result<RingDiff> {
+        for change in diff.promoted.iter_mut().chain(diff.demoted.iter_mut()) {
+            if let Some(node) = self.get_node(&change.node_id).await? {
+                change.display_name = node.display_name;
+                change.avatar_url = node.avatar_url;
+            }
+        }
+        Ok(diff)
+    }
+
     /// Get all nodes in a specific ring.
     pub async fn nodes_in_ring(&self, ring: Ring) -> Result<Vec<String>> {
         let rows = sqlx::query_as::<_, (String,)>(
@@ -341,60 +960,312 @@ impl GraphStore {
         Ok(rows.into_iter().map(|(id,)| id).collect())
     }
 
-    /// Clear all ring assignments (before recomputation).
-    pub async fn clear_ring_assignments(&self) -> Result<()> {
-        sqlx::query("DELETE FROM ring_assignments")
-            .execute(&self.pool)
-            .await?;
-        Ok(())
-    }
-
-    /// Get ring distribution (count per ring).
-    pub async fn ring_distribution(&self) -> Result<Vec<(Ring, i64)>> {
-        let rows = sqlx::query_as::<_, (i32, i64)>(
-            "SELECT ring, COUNT(*) as count FROM ring_assignments GROUP BY ring ORDER BY ring",
+    /// Documents that are the source of an edge touching a node in `ring`,
+    /// e.g. everything the user wrote or was mentioned in for `Ring::Core`.
+    pub async fn document_ids_in_ring(&self, ring: Ring) -> Result<Vec<i64>> {
+        let ids = sqlx::query_scalar(
+            "SELECT DISTINCT s.doc_id FROM graph_edge_sources s
+             JOIN graph_edges e ON e.id = s.edge_id
+             JOIN ring_assignments r ON r.node_id IN (e.from_node, e.to_node)
+             WHERE r.ring = ?1
+             ORDER BY s.doc_id",
         )
+        .bind(ring.as_int())
         .fetch_all(&self.pool)
         .await?;
+        Ok(ids)
+    }
 
-        Ok(rows
-            .into_iter()
-            .map(|(ring, count)| (Ring::from_int(ring), count))
-            .collect())
+    /// Recompute each document's closest ring (see [`Self::rings_for_documents`])
+    /// from the current ring assignments. Returns the number of documents
+    /// with a ring.
+    pub async fn refresh_doc_rings(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM doc_rings").execute(&mut *tx).await?;
+        let result = sqlx::query(
+            "INSERT INTO doc_rings (doc_id, ring)
+             SELECT s.doc_id, MIN(r.ring) FROM graph_edge_sources s
+             JOIN graph_edges e ON e.id = s.edge_id
+             JOIN ring_assignments r ON r.node_id IN (e.from_node, e.to_node)
+             GROUP BY s.doc_id",
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
     }
 
-    /// Get all user nodes.
-    pub async fn get_user_nodes(&self) -> Result<Vec<GraphNode>> {
-        let rows = sqlx::query_as::<_, (String, String, String, String, Option<String>, Option<String>, String, String)>(
-            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at
-             FROM graph_nodes WHERE node_type = 'user'",
+    /// The closest ring of any node on an edge sourced from each document:
+    /// its author, but also the channel it was posted in or the project it
+    /// belongs to. Documents without such a node are left out.
+    ///
+    /// Read from a cache refreshed by every ring recalculation, so edges
+    /// stored since the last one don't count yet.
+    pub async fn rings_for_documents(&self, doc_ids: &[i64]) -> Result<HashMap<i64, Ring>> {
+        let rows = sqlx::query_as::<_, (i64, i32)>(
+            "SELECT doc_id, ring FROM doc_rings WHERE doc_id IN (SELECT value FROM json_each(?1))",
         )
+        .bind(serde_json::to_string(doc_ids)?)
         .fetch_all(&self.pool)
         .await?;
+        Ok(rows.into_iter().map(|(doc_id, ring)| (doc_id, Ring::from_int(ring))).collect())
+    }
 
-        Ok(rows
-            .into_iter()
-            .map(|(id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at)| {
-                GraphNode {
-                    id,
-                    node_type: NodeType::parse(&node_type).unwrap_or(NodeType::User),
-                    provider,
-                    external_id,
-                    display_name,
-                    metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
-                    first_seen_at: DateTime::parse_from_rfc3339(&first_seen_at)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    last_seen_at: DateTime::parse_from_rfc3339(&last_seen_at)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                }
-            })
-            .collect())
+    /// Documents sharing a node with `doc_id` through the edges extracted
+    /// from them, e.g. a PR's issue and the thread discussing it. People and
+    /// teams don't count, or everything an author wrote would be related.
+    /// Returns `(doc_id, shared nodes)`, most shared first.
+    pub async fn related_documents(&self, doc_id: i64, limit: usize) -> Result<Vec<(i64, i64)>> {
+        let rows = sqlx::query_as::<_, (i64, i64)>(
+            "WITH doc_nodes AS (
+                SELECT e.from_node AS node FROM graph_edge_sources s
+                JOIN graph_edges e ON e.id = s.edge_id WHERE s.doc_id = ?1
+                UNION
+                SELECT e.to_node FROM graph_edge_sources s
+                JOIN graph_edges e ON e.id = s.edge_id WHERE s.doc_id = ?1
+             ),
+             shared AS (
+                SELECT d.node FROM doc_nodes d JOIN graph_nodes n ON n.id = d.node
+                WHERE n.node_type NOT IN ('user', 'team')
+             )
+             SELECT s.doc_id, COUNT(DISTINCT sh.node) AS nodes FROM graph_edge_sources s
+             JOIN graph_edges e ON e.id = s.edge_id
+             JOIN shared sh ON sh.node IN (e.from_node, e.to_node)
+             WHERE s.doc_id != ?1
+             GROUP BY s.doc_id
+             ORDER BY nodes DESC, s.doc_id DESC
+             LIMIT ?2",
+        )
+        .bind(doc_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
     }
 
-    /// Link a user identity across providers.
-    pub async fn link_user_identity(
+    /// Delete all edges observed from a provider. Returns the number removed.
+    ///
+    /// Nodes are kept since they may be shared with other providers through
+    /// identity links; rings are recomputed from the remaining edges.
+    pub async fn delete_edges_by_provider(&self, provider: &str) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM graph_edge_sources
+             WHERE edge_id IN (SELECT id FROM graph_edges WHERE provider = ?1)",
+        )
+        .bind(provider)
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query("DELETE FROM graph_edges WHERE provider = ?1")
+            .bind(provider)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Apply `policy`: remove edges older than its window (archiving them
+    /// if asked) and trim each edge's sources to the most recent ones. The
+    /// edge's count still includes the trimmed sources.
+    pub async fn prune(&self, policy: &PrunePolicy) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(days) = policy.max_age_days {
+            let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+            let stale = "SELECT id FROM graph_edges WHERE observed_at < ?1 AND relation != ?2";
+            report.sources_dropped += sqlx::query(&format!(
+                "DELETE FROM graph_edge_sources WHERE edge_id IN ({stale})"
+            ))
+            .bind(&cutoff)
+            .bind(Relation::SameAs.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if policy.archive {
+                sqlx::query(&format!(
+                    "INSERT OR REPLACE INTO graph_edges_archive
+                        (id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, archived_at)
+                     SELECT id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, ?3
+                     FROM graph_edges WHERE id IN ({stale})"
+                ))
+                .bind(&cutoff)
+                .bind(Relation::SameAs.as_str())
+                .bind(Utc::now().to_rfc3339())
+                .execute(&mut *tx)
+                .await?;
+            }
+            let removed = sqlx::query(&format!("DELETE FROM graph_edges WHERE id IN ({stale})"))
+                .bind(&cutoff)
+                .bind(Relation::SameAs.as_str())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if policy.archive {
+                report.edges_archived = removed;
+            } else {
+                report.edges_deleted = removed;
+            }
+        }
+
+        report.sources_dropped += sqlx::query(
+            "DELETE FROM graph_edge_sources WHERE (edge_id, doc_id) IN (
+                SELECT edge_id, doc_id FROM (
+                    SELECT edge_id, doc_id, ROW_NUMBER() OVER (
+                        PARTITION BY edge_id ORDER BY observed_at DESC, doc_id DESC
+                    ) AS rank
+                    FROM graph_edge_sources
+                ) WHERE rank > ?1
+            )",
+        )
+        .bind(policy.max_sources_per_edge as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Replace every node's centrality with `scores`; nodes not in it get
+    /// zero. Returns the number of nodes updated.
+    pub async fn save_centrality(&self, scores: &[NodeCentrality]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE graph_nodes SET degree = 0, pagerank = 0")
+            .execute(&mut *tx)
+            .await?;
+        let mut updated = 0;
+        for score in scores {
+            updated += sqlx::query("UPDATE graph_nodes SET degree = ?2, pagerank = ?3 WHERE id = ?1")
+                .bind(&score.node_id)
+                .bind(score.degree)
+                .bind(score.pagerank)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    /// PageRank of every node with one, as of the last centrality pass.
+    pub async fn pagerank_scores(&self) -> Result<HashMap<String, f64>> {
+        let rows = sqlx::query_as::<_, (String, f64)>("SELECT id, pagerank FROM graph_nodes WHERE pagerank > 0")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// The most central nodes by PageRank, optionally of one type.
+    pub async fn most_central(&self, node_type: Option<NodeType>, limit: usize) -> Result<Vec<GraphNode>> {
+        let rows = sqlx::query_as::<_, NodeRow>(
+            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url
+             FROM graph_nodes WHERE pagerank > 0 AND (?1 IS NULL OR node_type = ?1)
+             ORDER BY pagerank DESC, degree DESC, id LIMIT ?2",
+        )
+        .bind(node_type.map(|t| t.as_str()))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(node_from_row).collect())
+    }
+
+    /// Clear all ring assignments (before recomputation).
+    pub async fn clear_ring_assignments(&self) -> Result<()> {
+        sqlx::query("DELETE FROM ring_assignments")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM doc_rings").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Get ring distribution (count per ring).
+    pub async fn ring_distribution(&self) -> Result<Vec<(Ring, i64)>> {
+        let rows = sqlx::query_as::<_, (i32, i64)>(
+            "SELECT ring, COUNT(*) as count FROM ring_assignments GROUP BY ring ORDER BY ring",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(ring, count)| (Ring::from_int(ring), count))
+            .collect())
+    }
+
+    /// Get all user nodes.
+    pub async fn get_user_nodes(&self) -> Result<Vec<GraphNode>> {
+        let rows = sqlx::query_as::<_, NodeRow>(
+            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url
+             FROM graph_nodes WHERE node_type = 'user'",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(node_from_row).collect())
+    }
+
+    /// People whose handle, email or name matches `query` (case-insensitive,
+    /// a leading `@` ignored): exact matches first, then names containing it.
+    pub async fn find_people(&self, query: &str, limit: usize) -> Result<Vec<GraphNode>> {
+        let query = query.trim().trim_start_matches('@').to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query_as::<_, NodeRow>(
+            "SELECT id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url
+             FROM graph_nodes
+             WHERE node_type = 'user' AND (
+                lower(external_id) = ?1
+                OR lower(json_extract(metadata, '$.email')) = ?1
+                OR instr(lower(display_name), ?1) > 0
+             )
+             ORDER BY lower(external_id) = ?1 OR lower(json_extract(metadata, '$.email')) = ?1
+                OR lower(display_name) = ?1 DESC, last_seen_at DESC
+             LIMIT ?2",
+        )
+        .bind(&query)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(node_from_row).collect())
+    }
+
+    /// Documents behind edges between one of `people` and either one of `me`
+    /// or something `me` is connected to (a PR, an issue, a thread), most
+    /// recent first: what a person and the user have worked on together.
+    pub async fn shared_sources(&self, people: &[String], me: &[String], limit: usize) -> Result<Vec<EdgeSource>> {
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "WITH people(id) AS (SELECT value FROM json_each(?1)),
+             me(id) AS (SELECT value FROM json_each(?2)),
+             mine(id) AS (
+                SELECT id FROM me
+                UNION
+                SELECT CASE WHEN e.from_node IN me THEN e.to_node ELSE e.from_node END
+                FROM graph_edges e
+                WHERE (e.from_node IN me OR e.to_node IN me) AND e.relation != ?3
+             )
+             SELECT s.edge_id, s.doc_id, MAX(s.observed_at)
+             FROM graph_edge_sources s JOIN graph_edges e ON e.id = s.edge_id
+             WHERE e.relation != ?3 AND (
+                (e.from_node IN people AND e.to_node IN mine)
+                OR (e.to_node IN people AND e.from_node IN mine)
+             )
+             GROUP BY s.doc_id
+             ORDER BY MAX(s.observed_at) DESC
+             LIMIT ?4",
+        )
+        .bind(serde_json::to_string(people)?)
+        .bind(serde_json::to_string(me)?)
+        .bind(Relation::SameAs.as_str())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(edge_source_from_row).collect())
+    }
+
+    /// Link a user identity across providers.
+    pub async fn link_user_identity(
         &self,
         canonical_id: &str,
         email: Option<&str>,
@@ -448,11 +1319,279 @@ impl GraphStore {
 
         Ok(row.map(|(id,)| id))
     }
+
+    /// Record the email address of a user node, which identity matching
+    /// links accounts by. Does nothing if the node doesn't exist.
+    pub async fn set_node_email(&self, node_id: &str, email: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE graph_nodes SET metadata = json_set(COALESCE(metadata, '{}'), '$.email', ?2)
+             WHERE id = ?1",
+        )
+        .bind(node_id)
+        .bind(email)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the profile picture of a node, for showing people rather than
+    /// IDs. Does nothing if the node doesn't exist.
+    pub async fn set_node_avatar(&self, node_id: &str, avatar_url: &str) -> Result<()> {
+        sqlx::query("UPDATE graph_nodes SET avatar_url = ?2 WHERE id = ?1")
+            .bind(node_id)
+            .bind(avatar_url)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record that two user nodes are the same person, with how that was
+    /// established (`provenance`, e.g. "manual") and the confidence of the
+    /// match. Each pair is linked once, under provider "identity" so that
+    /// resetting either provider keeps the link. A manual link is never
+    /// replaced by an automatic one.
+    pub async fn link_same_as(
+        &self,
+        node_a: &str,
+        node_b: &str,
+        provenance: &str,
+        confidence: f32,
+    ) -> Result<i64> {
+        let (from_node, to_node) = if node_a <= node_b { (node_a, node_b) } else { (node_b, node_a) };
+        let metadata = serde_json::json!({ "provenance": provenance, "confidence": confidence });
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO graph_edges (from_node, to_node, relation, provider, observed_at, weight, metadata, extracted_at, confidence)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1.0, ?6, ?5, ?7)
+             ON CONFLICT(from_node, to_node, relation, provider) DO UPDATE SET
+                observed_at = excluded.observed_at,
+                extracted_at = excluded.extracted_at,
+                metadata = CASE
+                    WHEN json_extract(graph_edges.metadata, '$.provenance') = 'manual' THEN graph_edges.metadata
+                    ELSE excluded.metadata
+                END,
+                confidence = CASE
+                    WHEN json_extract(graph_edges.metadata, '$.provenance') = 'manual' THEN graph_edges.confidence
+                    ELSE excluded.confidence
+                END
+             RETURNING id",
+        )
+        .bind(from_node)
+        .bind(to_node)
+        .bind(Relation::SameAs.as_str())
+        .bind(IDENTITY_PROVIDER)
+        .bind(Utc::now().to_rfc3339())
+        .bind(metadata.to_string())
+        .bind(confidence as f64)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// All identity links (`SameAs` edges).
+    pub async fn same_as_edges(&self) -> Result<Vec<GraphEdge>> {
+        let rows = sqlx::query_as::<_, EdgeRow>(
+            "SELECT id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence
+             FROM graph_edges WHERE relation = ?1 ORDER BY id",
+        )
+        .bind(Relation::SameAs.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(edge_from_row).collect())
+    }
+
+    /// Nodes linked to `node_id` as the same person, directly or through
+    /// other links, including `node_id` itself.
+    pub async fn linked_identities(&self, node_id: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar::<_, String>(
+            "WITH RECURSIVE linked(id) AS (
+                SELECT ?1
+                UNION
+                SELECT CASE WHEN e.from_node = linked.id THEN e.to_node ELSE e.from_node END
+                FROM graph_edges e JOIN linked ON e.from_node = linked.id OR e.to_node = linked.id
+                WHERE e.relation = ?2
+             )
+             SELECT id FROM linked ORDER BY id",
+        )
+        .bind(node_id)
+        .bind(Relation::SameAs.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+}
+
+/// Upsert a node on `conn`, returning its canonical ID.
+async fn upsert_node_on(conn: &mut SqliteConnection, node_ref: &NodeRef) -> Result<String> {
+    let id = node_ref.canonical_id();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO graph_nodes (id, node_type, provider, external_id, display_name, first_seen_at, last_seen_at, avatar_url)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            display_name = COALESCE(excluded.display_name, graph_nodes.display_name),
+            avatar_url = COALESCE(excluded.avatar_url, graph_nodes.avatar_url),
+            last_seen_at = excluded.last_seen_at",
+    )
+    .bind(&id)
+    .bind(node_ref.node_type.as_str())
+    .bind(&node_ref.provider)
+    .bind(&node_ref.external_id)
+    .bind(&node_ref.display_name)
+    .bind(&now)
+    .bind(&node_ref.avatar_url)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// Upsert an edge and both of its nodes on `conn`, returning the edge ID.
+async fn upsert_edge_on(conn: &mut SqliteConnection, edge: &ExtractedEdge) -> Result<i64> {
+    // Ensure both nodes exist
+    let from_id = upsert_node_on(conn, &edge.from).await?;
+    let to_id = upsert_node_on(conn, &edge.to).await?;
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO graph_edges (from_node, to_node, relation, provider, observed_at, weight, metadata, extracted_at, confidence)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(from_node, to_node, relation, provider) DO UPDATE SET
+            observed_at = excluded.observed_at,
+            weight = MAX(graph_edges.weight, excluded.weight),
+            metadata = COALESCE(excluded.metadata, graph_edges.metadata),
+            extracted_at = excluded.extracted_at,
+            confidence = MAX(graph_edges.confidence, excluded.confidence)
+         RETURNING id",
+    )
+    .bind(&from_id)
+    .bind(&to_id)
+    .bind(edge.relation.as_str())
+    .bind(&edge.from.provider) // Use from node's provider as edge provider
+    .bind(edge.observed_at.to_rfc3339())
+    .bind(edge.weight as f64)
+    .bind(edge.metadata.as_ref().map(|m| m.to_string()))
+    .bind(Utc::now().to_rfc3339())
+    .bind(edge.confidence as f64)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// Record `doc_id` as a source of an edge on `conn`, counting it as a
+/// further observation if the edge had other sources already.
+async fn record_edge_source_on(
+    conn: &mut SqliteConnection,
+    edge_id: i64,
+    doc_id: i64,
+    observed_at: DateTime<Utc>,
+) -> Result<()> {
+    let observed_at = observed_at.to_rfc3339();
+    let inserted = sqlx::query(
+        "INSERT INTO graph_edge_sources (edge_id, doc_id, observed_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(edge_id, doc_id) DO NOTHING",
+    )
+    .bind(edge_id)
+    .bind(doc_id)
+    .bind(&observed_at)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if inserted {
+        sqlx::query(
+            "UPDATE graph_edges SET count = count + 1
+             WHERE id = ?1 AND (SELECT COUNT(*) FROM graph_edge_sources WHERE edge_id = ?1) > 1",
+        )
+        .bind(edge_id)
+        .execute(&mut *conn)
+        .await?;
+    } else {
+        sqlx::query("UPDATE graph_edge_sources SET observed_at = ?3 WHERE edge_id = ?1 AND doc_id = ?2")
+            .bind(edge_id)
+            .bind(doc_id)
+            .bind(&observed_at)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Walk back from `to` through [`GraphStore::shortest_path`]'s search.
+fn trace_path(reached: &HashMap<String, Option<(String, GraphEdge)>>, to: &str) -> GraphPath {
+    let mut nodes = vec![to.to_string()];
+    let mut edges = Vec::new();
+    let mut current = to.to_string();
+    while let Some(Some((previous, edge))) = reached.get(&current) {
+        edges.push(edge.clone());
+        nodes.push(previous.clone());
+        current = previous.clone();
+    }
+    nodes.reverse();
+    edges.reverse();
+    GraphPath { nodes, edges }
+}
+
+type NodeRow = (String, String, String, String, Option<String>, Option<String>, String, String, i64, f64, Option<String>);
+
+fn node_from_row(
+    (id, node_type, provider, external_id, display_name, metadata, first_seen_at, last_seen_at, degree, pagerank, avatar_url): NodeRow,
+) -> GraphNode {
+    GraphNode {
+        id,
+        node_type: NodeType::parse(&node_type).unwrap_or(NodeType::User),
+        provider,
+        external_id,
+        display_name,
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        first_seen_at: DateTime::parse_from_rfc3339(&first_seen_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        last_seen_at: DateTime::parse_from_rfc3339(&last_seen_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        degree,
+        pagerank,
+        avatar_url,
+    }
+}
+
+type EdgeRow = (i64, String, String, String, String, String, f64, i64, Option<String>, f64);
+
+fn edge_from_row(
+    (id, from_node, to_node, relation, provider, observed_at, weight, count, metadata, confidence): EdgeRow,
+) -> GraphEdge {
+    GraphEdge {
+        id,
+        from_node,
+        to_node,
+        relation: Relation::parse(&relation).unwrap_or(Relation::References),
+        provider,
+        observed_at: DateTime::parse_from_rfc3339(&observed_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        weight: weight as f32,
+        count,
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        confidence: confidence as f32,
+    }
+}
+
+fn edge_source_from_row((edge_id, doc_id, observed_at): (i64, i64, String)) -> EdgeSource {
+    EdgeSource {
+        edge_id,
+        doc_id,
+        observed_at: DateTime::parse_from_rfc3339(&observed_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::Collaborator;
     use sqlx::sqlite::SqlitePoolOptions;
 
     async fn setup_test_db() -> SqlitePool {
@@ -497,6 +1636,270 @@ mod tests {
         assert_eq!(store.edge_count().await.unwrap(), 1);
     }
 
+    #[tokio::test]
+    async fn test_upsert_edges() {
+        let store = GraphStore::new(setup_test_db().await);
+        let now = Utc::now();
+        let channel = NodeRef::channel("slack", "C1");
+        let edges: Vec<ExtractedEdge> = ["U1", "U2", "U3", "U1"]
+            .iter()
+            .map(|user| ExtractedEdge::new(NodeRef::user("slack", *user), channel.clone(), Relation::MemberOf, now))
+            .collect();
+
+        let ids = store.upsert_edges(&edges).await.unwrap();
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[0], ids[3]);
+        assert_eq!((store.node_count().await.unwrap(), store.edge_count().await.unwrap()), (4, 3));
+        assert!(store.upsert_edges(&[]).await.unwrap().is_empty());
+
+        // Each further document counts once per edge
+        store.upsert_edges_from_document(&edges[..2], 1).await.unwrap();
+        store.upsert_edges_from_document(&edges[..2], 1).await.unwrap();
+        store.upsert_edges_from_document(&edges[..1], 2).await.unwrap();
+        let counts: Vec<i64> = store.edges_to(&channel.canonical_id()).await.unwrap().iter().map(|e| e.count).collect();
+        assert_eq!(counts.iter().sum::<i64>(), 4);
+        assert_eq!(store.edge_sources(ids[0], 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_edges_by_provider() {
+        let pool = setup_test_db().await;
+        let store = GraphStore::new(pool);
+
+        let user = NodeRef::user("slack", "U123");
+        store
+            .upsert_edge(&ExtractedEdge::new(
+                user.clone(),
+                NodeRef::message("slack", "msg1"),
+                Relation::AuthorOf,
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+        store
+            .upsert_edge(&ExtractedEdge::new(
+                NodeRef::user("linear", "L1"),
+                NodeRef::issue("linear", "ISSUE-1"),
+                Relation::AuthorOf,
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(store.delete_edges_by_provider("slack").await.unwrap(), 1);
+        assert_eq!(store.edge_count().await.unwrap(), 1);
+        assert!(store.edges_from(&user.canonical_id()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edge_provenance() {
+        let pool = setup_test_db().await;
+        let store = GraphStore::new(pool);
+
+        let alice = NodeRef::user("slack", "U1");
+        let bob = NodeRef::user("slack", "U2");
+        let edge = ExtractedEdge::new(alice.clone(), bob.clone(), Relation::MentionedIn, Utc::now());
+
+        // The same relationship seen in two conversations keeps one edge, two sources
+        let edge_id = store.upsert_edge_from_document(&edge, 10).await.unwrap();
+        assert_eq!(store.upsert_edge_from_document(&edge, 11).await.unwrap(), edge_id);
+        assert_eq!(store.edge_count().await.unwrap(), 1);
+        assert_eq!(store.edge_sources(edge_id, 10).await.unwrap().len(), 2);
+
+        let between = store
+            .sources_between(&bob.canonical_id(), &alice.canonical_id(), 10)
+            .await
+            .unwrap();
+        let mut doc_ids: Vec<i64> = between.iter().map(|s| s.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![10, 11]);
+
+        assert_eq!(store.delete_edge_sources_for_documents(&[10]).await.unwrap(), 1);
+        assert_eq!(store.edge_sources(edge_id, 10).await.unwrap()[0].doc_id, 11);
+
+        store.delete_edges_by_provider("slack").await.unwrap();
+        assert!(store.edge_sources(edge_id, 10).await.unwrap().is_empty());
+
+        // Sources left behind by a missing edge are cleaned up by maintenance
+        let orphan = EdgeSource {
+            edge_id: edge_id + 100,
+            doc_id: 12,
+            observed_at: Utc::now(),
+        };
+        store.add_edge_source(&orphan).await.unwrap();
+        assert_eq!(store.maintenance().await.unwrap(), 1);
+        assert_eq!(store.maintenance().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let store = GraphStore::new(setup_test_db().await);
+        let alice = NodeRef::user("slack", "U1");
+        let bob = NodeRef::user("slack", "U2");
+        let now = Utc::now();
+        let old = now - Duration::days(400);
+
+        // Each new document counts, repeats don't
+        let recent = ExtractedEdge::new(alice.clone(), bob.clone(), Relation::MentionedIn, now);
+        let recent_id = store.upsert_edge_from_document(&recent, 1).await.unwrap();
+        for doc_id in 1..=4 {
+            store.upsert_edge_from_document(&recent, doc_id).await.unwrap();
+        }
+        let stale = ExtractedEdge::new(alice.clone(), NodeRef::channel("slack", "C1"), Relation::MemberOf, old);
+        store.upsert_edge_from_document(&stale, 5).await.unwrap();
+        store
+            .upsert_edge(&ExtractedEdge::new(alice.clone(), NodeRef::user("github", "alice"), Relation::SameAs, old))
+            .await
+            .unwrap();
+
+        let policy = PrunePolicy {
+            max_sources_per_edge: 2,
+            ..PrunePolicy::default()
+        };
+        let report = store.prune(&policy).await.unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                edges_archived: 1,
+                edges_deleted: 0,
+                sources_dropped: 3,
+            }
+        );
+        assert_eq!(store.edge_count().await.unwrap(), 2);
+        assert_eq!(store.edge_sources(recent_id, 10).await.unwrap().len(), 2);
+        let edges = store.edges_from(&alice.canonical_id()).await.unwrap();
+        let kept = edges.iter().find(|e| e.id == recent_id).unwrap();
+        assert_eq!(kept.count, 4);
+        let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM graph_edges_archive")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+
+        // Nothing left to prune
+        assert_eq!(store.prune(&policy).await.unwrap(), PruneReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_centrality() {
+        use crate::RingEngine;
+
+        let store = GraphStore::new(setup_test_db().await);
+        let now = Utc::now();
+        let channel = NodeRef::channel("slack", "C1");
+        for user in ["U1", "U2", "U3"] {
+            store
+                .upsert_edge(&ExtractedEdge::new(NodeRef::user("slack", user), channel.clone(), Relation::MemberOf, now))
+                .await
+                .unwrap();
+        }
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("slack", "U1"), NodeRef::user("slack", "U2"), Relation::MentionedIn, now))
+            .await
+            .unwrap();
+
+        assert_eq!(RingEngine::new().recalculate_centrality(&store).await.unwrap(), 4);
+        let central = store.most_central(None, 2).await.unwrap();
+        assert_eq!(central[0].id, channel.canonical_id());
+        assert_eq!(central[0].degree, 3);
+        let users = store.most_central(Some(NodeType::User), 10).await.unwrap();
+        assert_eq!(users.len(), 3);
+        assert_eq!(users[2].id, "user:slack:U3");
+        assert_eq!(store.pagerank_scores().await.unwrap().len(), 4);
+
+        // Stale scores are reset by the next pass
+        store.delete_edges_by_provider("slack").await.unwrap();
+        assert_eq!(RingEngine::new().recalculate_centrality(&store).await.unwrap(), 0);
+        assert!(store.most_central(None, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_collaborators() {
+        use crate::{IdentityService, RingEngine};
+
+        let store = GraphStore::new(setup_test_db().await);
+        let now = Utc::now();
+        let me = NodeRef::user("slack", "ME");
+        let member = |user: &str, container: &NodeRef, weight: f32, at| {
+            ExtractedEdge::new(NodeRef::user("slack", user), container.clone(), Relation::MemberOf, at).with_weight(weight)
+        };
+
+        // A DM with Bob, a busy channel with everyone, an old one with Eve
+        let dm = NodeRef::channel("slack", "D1");
+        let general = NodeRef::channel("slack", "C1");
+        let old = NodeRef::channel("slack", "C2");
+        let mut edges = vec![member("ME", &dm, 2.0, now), member("BOB", &dm, 2.0, now)];
+        for user in ["ME", "BOB", "CAROL", "DAN", "EVE"] {
+            edges.push(member(user, &general, 1.0, now));
+        }
+        edges.push(member("ME", &old, 1.0, now - Duration::days(200)));
+        edges.push(member("EVE", &old, 1.0, now - Duration::days(200)));
+        for edge in &edges {
+            store.upsert_edge(edge).await.unwrap();
+        }
+        // Carol also has a GitHub account that reviewed my PR
+        let pr = NodeRef::new(NodeType::PullRequest, "github", "a/b#1");
+        store.upsert_edge(&ExtractedEdge::new(NodeRef::user("github", "me"), pr.clone(), Relation::AuthorOf, now)).await.unwrap();
+        store.upsert_edge(&ExtractedEdge::new(NodeRef::user("github", "carol"), pr, Relation::ReviewerOf, now)).await.unwrap();
+        IdentityService::link_users(&store, "slack", "ME", "github", "me").await.unwrap();
+        IdentityService::link_users(&store, "slack", "CAROL", "github", "carol").await.unwrap();
+
+        let engine = RingEngine::new();
+        engine.recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        let mine = store.linked_identities(&me.canonical_id()).await.unwrap();
+        let groups = engine.collaborators(&store, &mine, now - Duration::days(90), 3).await.unwrap();
+        let people: Vec<&Collaborator> = groups.iter().flat_map(|g| &g.people).collect();
+        let ids: Vec<&str> = people.iter().map(|p| p.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["user:slack:BOB", "user:github:carol", "user:slack:DAN"]);
+        assert_eq!(people[1].linked_ids, vec!["user:slack:CAROL".to_string()]);
+        assert_eq!(people[1].shared, 2);
+        assert!(groups.windows(2).all(|w| w[0].ring.is_closer_than(w[1].ring)));
+
+        let groups = engine.collaborators(&store, &mine, now - Duration::days(90), 1).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].people[0].node_id, "user:slack:BOB");
+    }
+
+    #[tokio::test]
+    async fn test_import_graph() {
+        let source = GraphStore::new(setup_test_db().await);
+        let edge = ExtractedEdge::new(
+            NodeRef::with_name(NodeType::User, "slack", "U123", "Ada"),
+            NodeRef::channel("slack", "C1"),
+            Relation::MemberOf,
+            Utc::now(),
+        );
+        let edge_id = source.upsert_edge_from_document(&edge, 7).await.unwrap();
+
+        let target = GraphStore::new(setup_test_db().await);
+        // The target already knows the channel from its own sync
+        target.upsert_node(&NodeRef::channel("slack", "C1")).await.unwrap();
+        for node in source.nodes_after("", 10).await.unwrap() {
+            target.import_node(&node).await.unwrap();
+        }
+        let edges = source.edges_after(0, 10).await.unwrap();
+        assert_eq!(edges.len(), 1);
+        let imported_id = target.import_edge(&edges[0]).await.unwrap();
+        assert_eq!(target.import_edge(&edges[0]).await.unwrap(), imported_id);
+
+        let sources = source.sources_for_edges(&[edge_id]).await.unwrap();
+        assert_eq!(sources.len(), 1);
+        target
+            .add_edge_source(&EdgeSource {
+                edge_id: imported_id,
+                doc_id: 42,
+                observed_at: sources[0].observed_at,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(target.node_count().await.unwrap(), 2);
+        let user = target.get_node("user:slack:U123").await.unwrap().unwrap();
+        assert_eq!(user.display_name.as_deref(), Some("Ada"));
+        let cited = target.edge_sources(imported_id, 10).await.unwrap();
+        assert_eq!(cited[0].doc_id, 42);
+    }
+
     #[tokio::test]
     async fn test_edges_from_to() {
         let pool = setup_test_db().await;
@@ -530,6 +1933,12 @@ mod tests {
 
         let to_edges = store.edges_to(&msg1.canonical_id()).await.unwrap();
         assert_eq!(to_edges.len(), 1);
+
+        let first_page = store.edges_after(0, 1).await.unwrap();
+        assert_eq!(first_page.len(), 1);
+        let rest = store.edges_after(first_page[0].id, 10).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_ne!(rest[0].to_node, first_page[0].to_node);
     }
 
     #[tokio::test]
@@ -548,6 +1957,8 @@ mod tests {
             effective_distance: 1.05,
             path: vec!["user:self".to_string()],
             computed_at: Utc::now(),
+            pending_ring: Some(Ring::Two),
+            pending_count: 1,
         };
 
         store.save_ring_assignment(&assignment).await.unwrap();
@@ -559,5 +1970,549 @@ mod tests {
             .unwrap();
         assert_eq!(loaded.ring, Ring::One);
         assert_eq!(loaded.distance, 1);
+        assert_eq!(loaded.pending_ring, Some(Ring::Two));
+        assert_eq!(loaded.pending_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_document_ids_in_ring() {
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let other = NodeRef::user("slack", "U2");
+        let third = NodeRef::user("slack", "U3");
+        let now = Utc::now();
+        store
+            .upsert_edge_from_document(&ExtractedEdge::new(me.clone(), other.clone(), Relation::MentionedIn, now), 10)
+            .await
+            .unwrap();
+        store
+            .upsert_edge_from_document(&ExtractedEdge::new(other.clone(), third.clone(), Relation::MentionedIn, now), 11)
+            .await
+            .unwrap();
+        assert!(store.document_ids_in_ring(Ring::Core).await.unwrap().is_empty());
+
+        store
+            .save_ring_assignment(&RingAssignment {
+                node_id: me.canonical_id(),
+                ring: Ring::Core,
+                distance: 0,
+                effective_distance: 0.0,
+                path: vec![],
+                computed_at: now,
+                pending_ring: None,
+                pending_count: 0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(store.document_ids_in_ring(Ring::Core).await.unwrap(), vec![10]);
+    }
+
+    #[tokio::test]
+    async fn test_rings_for_documents() {
+        use crate::RingEngine;
+
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let stranger = NodeRef::user("slack", "U9");
+        let channel = NodeRef::channel("slack", "C1");
+        let message = NodeRef::message("slack", "C1:1.0");
+        let now = Utc::now();
+        let edges = [
+            (ExtractedEdge::new(me.clone(), channel.clone(), Relation::MemberOf, now), 1),
+            (ExtractedEdge::new(stranger.clone(), message.clone(), Relation::AuthorOf, now), 20),
+            (ExtractedEdge::new(message, channel, Relation::PostedIn, now), 20),
+            (ExtractedEdge::new(stranger, NodeRef::user("github", "x"), Relation::MentionedIn, now), 30),
+        ];
+        for (edge, doc_id) in &edges {
+            store.upsert_edge_from_document(edge, *doc_id).await.unwrap();
+        }
+        RingEngine::new().recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+
+        // A stranger's message counts as close because of the channel
+        let rings = store.rings_for_documents(&[1, 20, 30, 40]).await.unwrap();
+        assert_eq!(rings.get(&1), Some(&Ring::Core));
+        assert_eq!(rings.get(&20), Some(&Ring::One));
+        assert_eq!(rings.get(&30), Some(&Ring::Two));
+        assert_eq!(rings.get(&40), None);
+
+        // The cache follows recalculations and purges
+        let late = ExtractedEdge::new(me.clone(), NodeRef::issue("linear", "1"), Relation::AuthorOf, now);
+        store.upsert_edge_from_document(&late, 40).await.unwrap();
+        assert_eq!(store.rings_for_documents(&[40]).await.unwrap().get(&40), None);
+        assert_eq!(store.refresh_doc_rings().await.unwrap(), 4);
+        assert_eq!(store.rings_for_documents(&[40]).await.unwrap().get(&40), Some(&Ring::Core));
+        store.delete_edge_sources_for_documents(&[40]).await.unwrap();
+        assert!(store.rings_for_documents(&[40]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_related_documents() {
+        let store = GraphStore::new(setup_test_db().await);
+        let alice = NodeRef::user("github", "alice");
+        let pr = NodeRef::pull_request("github", "acme/api#7");
+        let issue = NodeRef::issue("linear", "ENG-1");
+        let now = Utc::now();
+        let edges = [
+            (ExtractedEdge::new(alice.clone(), pr.clone(), Relation::AuthorOf, now), 1),
+            (ExtractedEdge::new(pr.clone(), issue.clone(), Relation::MentionedIn, now), 1),
+            (ExtractedEdge::new(alice.clone(), issue.clone(), Relation::AssignedTo, now), 2),
+            (ExtractedEdge::new(pr.clone(), issue, Relation::MentionedIn, now), 3),
+            (ExtractedEdge::new(alice.clone(), pr, Relation::MentionedIn, now), 3),
+            (ExtractedEdge::new(alice, NodeRef::issue("linear", "ENG-2"), Relation::AuthorOf, now), 4),
+        ];
+        for (edge, doc_id) in &edges {
+            store.upsert_edge_from_document(edge, *doc_id).await.unwrap();
+        }
+
+        // Sharing only the author doesn't make document 4 related
+        let related = store.related_documents(1, 10).await.unwrap();
+        assert_eq!(related, vec![(3, 2), (2, 1)]);
+        assert_eq!(store.related_documents(1, 1).await.unwrap(), vec![(3, 2)]);
+        assert!(store.related_documents(99, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_people_and_shared_sources() {
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("github", "me");
+        let alice = NodeRef::with_name(NodeType::User, "github", "alice-dev", "Alice Smith");
+        let alicia = NodeRef::with_name(NodeType::User, "slack", "U2", "Alicia Jones");
+        let pr = NodeRef::pull_request("github", "acme/api#7");
+        let now = Utc::now();
+        let hour = Duration::hours(1);
+        let edges = [
+            (ExtractedEdge::new(alice.clone(), pr.clone(), Relation::AuthorOf, now - hour * 3), 1),
+            (ExtractedEdge::new(me.clone(), pr.clone(), Relation::ReviewerOf, now - hour * 2), 2),
+            (ExtractedEdge::new(alice.clone(), me.clone(), Relation::MentionedIn, now - hour), 3),
+            (ExtractedEdge::new(alice.clone(), NodeRef::issue("linear", "ENG-9"), Relation::AuthorOf, now), 4),
+            (ExtractedEdge::new(alicia.clone(), pr, Relation::MentionedIn, now), 5),
+        ];
+        for (edge, doc_id) in &edges {
+            store.upsert_edge_from_document(edge, *doc_id).await.unwrap();
+        }
+        store.set_node_email(&alicia.canonical_id(), "alicia@example.com").await.unwrap();
+
+        let ids = |nodes: Vec<GraphNode>| nodes.into_iter().map(|node| node.id).collect::<Vec<_>>();
+        assert_eq!(ids(store.find_people("@alice-dev", 5).await.unwrap()), vec![alice.canonical_id()]);
+        assert_eq!(ids(store.find_people("ALICIA@example.com", 5).await.unwrap()), vec![alicia.canonical_id()]);
+        assert_eq!(store.find_people("ali", 5).await.unwrap().len(), 2);
+        assert!(store.find_people("bob", 5).await.unwrap().is_empty());
+
+        // The PR both touched and the direct mention, not Alice's own issue
+        let shared = store
+            .shared_sources(&[alice.canonical_id()], &[me.canonical_id()], 10)
+            .await
+            .unwrap();
+        assert_eq!(shared.iter().map(|s| s.doc_id).collect::<Vec<_>>(), vec![3, 1]);
+        assert!(store.shared_sources(&[alice.canonical_id()], &[], 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_same_as_links() {
+        use crate::{IdentityService, RingEngine};
+
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::with_name(NodeType::User, "slack", "U1", "Alice Smith");
+        let github = NodeRef::with_name(NodeType::User, "github", "alice", "Alice Smith");
+        let now = Utc::now();
+        store.upsert_node(&me).await.unwrap();
+        store
+            .upsert_edge(&ExtractedEdge::new(github.clone(), NodeRef::issue("github", "42"), Relation::AuthorOf, now))
+            .await
+            .unwrap();
+        assert_eq!(IdentityService::find_fuzzy_matches(&store).await.unwrap().len(), 1);
+
+        IdentityService::link_users(&store, "slack", "U1", "github", "alice").await.unwrap();
+        assert!(IdentityService::link_users(&store, "slack", "U1", "github", "nobody").await.is_err());
+        let links = store.same_as_edges().await.unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].metadata.as_ref().unwrap()["provenance"], "manual");
+        assert_eq!(
+            store.linked_identities(&me.canonical_id()).await.unwrap(),
+            vec![github.canonical_id(), me.canonical_id()]
+        );
+        assert!(IdentityService::find_fuzzy_matches(&store).await.unwrap().is_empty());
+
+        // Both accounts are the ego; resetting a provider keeps the link
+        RingEngine::new().recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        let ring = store.get_ring_assignment(&github.canonical_id()).await.unwrap().unwrap();
+        assert_eq!((ring.ring, ring.distance), (Ring::Core, 0));
+        let issue = store.get_ring_assignment("issue:github:42").await.unwrap().unwrap();
+        assert_eq!((issue.ring, issue.distance), (Ring::One, 1));
+        store.delete_edges_by_provider("github").await.unwrap();
+        assert_eq!(store.same_as_edges().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_user_nodes() {
+        use crate::RingEngine;
+
+        let store = GraphStore::new(setup_test_db().await);
+        let slack = NodeRef::user("slack", "U1");
+        let github = NodeRef::user("github", "alice");
+        let now = Utc::now();
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("slack", "U2"), slack.clone(), Relation::CoAuthorOf, now))
+            .await
+            .unwrap();
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("github", "bob"), github.clone(), Relation::CoAuthorOf, now))
+            .await
+            .unwrap();
+
+        // Not linked as the same person, but both are the user's accounts
+        let users = vec![slack.canonical_id(), github.canonical_id()];
+        let result = RingEngine::new().recalculate_rings_from(&store, &users).await.unwrap();
+        assert_eq!(result.nodes_processed, 4);
+        for user in &users {
+            assert_eq!(store.get_ring_assignment(user).await.unwrap().unwrap().ring, Ring::Core);
+        }
+        for colleague in ["user:slack:U2", "user:github:bob"] {
+            let ring = store.get_ring_assignment(colleague).await.unwrap().unwrap();
+            assert_eq!((ring.ring, ring.distance), (Ring::One, 1));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edge_confidence() {
+        let store = GraphStore::new(setup_test_db().await);
+        let alice = NodeRef::user("slack", "U1");
+        let bob = NodeRef::user("slack", "U2");
+        let now = Utc::now();
+
+        // Mentions default to less than certain; a surer observation wins
+        let mention = ExtractedEdge::new(alice.clone(), bob.clone(), Relation::MentionedIn, now);
+        assert_eq!(mention.confidence, Relation::MentionedIn.confidence());
+        store.upsert_edge(&mention.clone().with_confidence(0.4)).await.unwrap();
+        store.upsert_edge(&mention).await.unwrap();
+        store.upsert_edge(&mention.clone().with_confidence(0.2)).await.unwrap();
+        let edges = store.edges_from(&alice.canonical_id()).await.unwrap();
+        assert_eq!(edges[0].confidence, 0.8);
+
+        // Identity links keep the confidence of the match
+        let github = NodeRef::user("github", "alice");
+        store.upsert_node(&github).await.unwrap();
+        store.link_same_as(&alice.canonical_id(), &github.canonical_id(), "email", 0.95).await.unwrap();
+        let links = store.same_as_edges().await.unwrap();
+        assert_eq!(links[0].confidence, 0.95);
+    }
+
+    #[tokio::test]
+    async fn test_node_avatar() {
+        let store = GraphStore::new(setup_test_db().await);
+        let avatar = "https://avatars.githubusercontent.com/u/1";
+        let alice = NodeRef::with_name(NodeType::User, "github", "alice", "alice").with_avatar(avatar);
+        let id = store.upsert_node(&alice).await.unwrap();
+        assert_eq!(store.get_node(&id).await.unwrap().unwrap().avatar_url.as_deref(), Some(avatar));
+
+        // References without an avatar keep the known one
+        store.upsert_node(&NodeRef::user("github", "alice")).await.unwrap();
+        assert_eq!(store.get_node(&id).await.unwrap().unwrap().avatar_url.as_deref(), Some(avatar));
+
+        store.set_node_avatar(&id, "https://example.com/alice.png").await.unwrap();
+        let node = store.get_node(&id).await.unwrap().unwrap();
+        assert_eq!(node.avatar_url.as_deref(), Some("https://example.com/alice.png"));
+        assert_eq!(node.display_name.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_ring_change_reasons() {
+        use crate::{RingConfig, RingEngine};
+
+        let store = GraphStore::new(setup_test_db().await);
+        let engine = RingEngine::with_config(RingConfig {
+            promote_after: 1,
+            demote_after: 1,
+            ..Default::default()
+        });
+        let slack = NodeRef::user("slack", "U1");
+        let github = NodeRef::user("github", "alice");
+        let now = Utc::now();
+        store
+            .upsert_edge(&ExtractedEdge::new(slack.clone(), github.clone(), Relation::CoAuthorOf, now))
+            .await
+            .unwrap();
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("github", "bob"), github.clone(), Relation::CoAuthorOf, now))
+            .await
+            .unwrap();
+        engine.recalculate_rings(&store, &slack.canonical_id()).await.unwrap();
+
+        // The GitHub account turns out to be the user's own
+        let users = vec![slack.canonical_id(), github.canonical_id()];
+        let result = engine.recalculate_rings_from(&store, &users).await.unwrap();
+        let alice = result.transitions.iter().find(|t| t.node_id == "user:github:alice").unwrap();
+        assert_eq!((alice.to, alice.reason), (Ring::Core, RingChangeReason::Linked));
+
+        // Without GitHub edges, Bob can't be reached at all
+        store.delete_edges_by_provider("github").await.unwrap();
+        let result = engine.recalculate_rings_from(&store, &users[..1]).await.unwrap();
+        let bob = result.transitions.iter().find(|t| t.node_id == "user:github:bob").unwrap();
+        assert_eq!((bob.to, bob.reason), (Ring::Beyond, RingChangeReason::Unreachable));
+    }
+
+    #[tokio::test]
+    async fn test_windowed_rings() {
+        use crate::{RingConfig, RingEngine};
+
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let recent = NodeRef::user("slack", "U2");
+        let old = NodeRef::user("slack", "U3");
+        let now = Utc::now();
+        for (other, at) in [(&recent, now), (&old, now - chrono::Duration::days(90))] {
+            store
+                .upsert_edge(&ExtractedEdge::new(me.clone(), other.clone(), Relation::CoAuthorOf, at))
+                .await
+                .unwrap();
+        }
+        let users = vec![me.canonical_id()];
+        let placed = |assignments: Vec<RingAssignment>| -> Vec<String> {
+            let mut ids: Vec<String> = assignments.into_iter().map(|a| a.node_id).collect();
+            ids.sort();
+            ids
+        };
+
+        let engine = RingEngine::new();
+        assert_eq!(
+            placed(engine.rings_in(&store, &users, TimeRange::default()).await.unwrap()),
+            vec![me.canonical_id(), recent.canonical_id(), old.canonical_id()]
+        );
+        assert_eq!(
+            placed(engine.rings_in(&store, &users, TimeRange::last_days(30)).await.unwrap()),
+            vec![me.canonical_id(), recent.canonical_id()]
+        );
+        // Previewing doesn't save anything
+        assert!(store.get_ring_assignment(&recent.canonical_id()).await.unwrap().is_none());
+
+        let engine = RingEngine::with_config(RingConfig { window_days: Some(30), ..Default::default() });
+        engine.recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        assert!(store.get_ring_assignment(&recent.canonical_id()).await.unwrap().is_some());
+        assert!(store.get_ring_assignment(&old.canonical_id()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_team_membership_rings() {
+        use crate::RingEngine;
+
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let team = NodeRef::team("slack", "S1");
+        let now = Utc::now();
+        for user in ["U1", "U2", "U3"] {
+            store
+                .upsert_edge(&ExtractedEdge::new(NodeRef::user("slack", user), team.clone(), Relation::MemberOf, now))
+                .await
+                .unwrap();
+        }
+
+        // Teammates are close without ever having talked to each other
+        RingEngine::new().recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        let team_ring = store.get_ring_assignment(&team.canonical_id()).await.unwrap().unwrap();
+        assert_eq!((team_ring.ring, team_ring.distance), (Ring::One, 1));
+        let teammate = store.get_ring_assignment("user:slack:U3").await.unwrap().unwrap();
+        assert_eq!((teammate.ring, teammate.distance), (Ring::One, 2));
+        assert_eq!(store.node_count_by_type().await.unwrap(), vec![("user".to_string(), 3), ("team".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_artifact_rings() {
+        use crate::{RingConfig, RingEngine};
+
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let now = Utc::now();
+        let busy = NodeRef::channel("slack", "C-busy");
+        let quiet = NodeRef::channel("slack", "C-quiet");
+        for user in ["U2", "U3", "U4"] {
+            let colleague = NodeRef::user("slack", user);
+            store.upsert_edge(&ExtractedEdge::new(me.clone(), colleague.clone(), Relation::CoAuthorOf, now)).await.unwrap();
+            store.upsert_edge(&ExtractedEdge::new(colleague, busy.clone(), Relation::MentionedIn, now)).await.unwrap();
+        }
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("slack", "U2"), quiet.clone(), Relation::MentionedIn, now))
+            .await
+            .unwrap();
+
+        // Three colleagues link to the busy channel, one to the quiet one
+        RingEngine::new().recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        let busy_ring = store.get_ring_assignment(&busy.canonical_id()).await.unwrap().unwrap();
+        let quiet_ring = store.get_ring_assignment(&quiet.canonical_id()).await.unwrap().unwrap();
+        assert_eq!((busy_ring.ring, busy_ring.distance), (Ring::One, 2));
+        assert_eq!((quiet_ring.ring, quiet_ring.distance), (Ring::Two, 2));
+        assert!((busy_ring.effective_distance * 3.0 - quiet_ring.effective_distance).abs() < 1e-3);
+        // People keep their shortest-path distance
+        let colleague = store.get_ring_assignment("user:slack:U3").await.unwrap().unwrap();
+        assert!((colleague.effective_distance - 1.0).abs() < 1e-2);
+
+        // Without combining, both channels are only as close as their nearest member
+        let engine = RingEngine::with_config(RingConfig { combine_paths: false, promote_after: 1, demote_after: 1, ..Default::default() });
+        engine.recalculate_rings(&store, &me.canonical_id()).await.unwrap();
+        let busy_ring = store.get_ring_assignment(&busy.canonical_id()).await.unwrap().unwrap();
+        assert_eq!(busy_ring.ring, Ring::Two);
+    }
+
+    #[tokio::test]
+    async fn test_auto_link_by_email() {
+        use crate::IdentityService;
+
+        let store = GraphStore::new(setup_test_db().await);
+        let google = NodeRef::user("google", "alice.smith@gmail.com");
+        let slack = NodeRef::user("slack", "U1");
+        let git = NodeRef::user("local-git", "1234+alice@users.noreply.github.com");
+        let github = NodeRef::user("github", "Alice");
+        let other = NodeRef::user("slack", "U2");
+        for node in [&google, &slack, &git, &github, &other] {
+            store.upsert_node(node).await.unwrap();
+        }
+        store.set_node_email(&slack.canonical_id(), "AliceSmith+work@gmail.com").await.unwrap();
+        store.set_node_email(&other.canonical_id(), "bob@example.com").await.unwrap();
+        // Already linked by hand
+        store.link_same_as(&git.canonical_id(), &github.canonical_id(), "manual", 1.0).await.unwrap();
+
+        assert_eq!(IdentityService::auto_link_by_email(&store).await.unwrap(), 1);
+        assert_eq!(IdentityService::auto_link_by_email(&store).await.unwrap(), 0);
+        assert_eq!(
+            store.linked_identities(&slack.canonical_id()).await.unwrap(),
+            vec![google.canonical_id(), slack.canonical_id()]
+        );
+        let links = store.same_as_edges().await.unwrap();
+        let provenance: Vec<_> = links
+            .iter()
+            .map(|edge| edge.metadata.as_ref().unwrap()["provenance"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(provenance, vec!["manual", "email"]);
+
+        // An automatic match doesn't replace a manual link
+        store.link_same_as(&github.canonical_id(), &git.canonical_id(), "email", 0.95).await.unwrap();
+        let links = store.same_as_edges().await.unwrap();
+        assert_eq!(links[0].metadata.as_ref().unwrap()["provenance"], "manual");
+    }
+
+    #[tokio::test]
+    async fn test_graph_queries() {
+        let store = GraphStore::new(setup_test_db().await);
+        let me = NodeRef::user("slack", "U1");
+        let bob = NodeRef::user("slack", "U2");
+        let message = NodeRef::message("slack", "C1:1");
+        let channel = NodeRef::channel("slack", "C1");
+        let now = Utc::now();
+        let months_ago = now - chrono::Duration::days(60);
+        for (from, to, relation, at) in [
+            (&me, &message, Relation::AuthorOf, now),
+            (&message, &channel, Relation::PostedIn, now),
+            (&bob, &channel, Relation::MemberOf, months_ago),
+        ] {
+            store.upsert_edge(&ExtractedEdge::new(from.clone(), to.clone(), relation, at)).await.unwrap();
+        }
+        let all = TimeRange::default();
+        let recent = TimeRange::last_days(30);
+        let loner = store.upsert_node(&NodeRef::user("slack", "U3")).await.unwrap();
+
+        let ids = |neighbors: Vec<Neighbor>| -> Vec<(String, usize)> {
+            neighbors.into_iter().map(|n| (n.node.id, n.depth)).collect()
+        };
+        assert_eq!(
+            ids(store.neighbors(&me.canonical_id(), 2, None, all).await.unwrap()),
+            vec![(message.canonical_id(), 1), (channel.canonical_id(), 2)]
+        );
+        assert_eq!(
+            ids(store.neighbors(&me.canonical_id(), 3, Some(&[Relation::AuthorOf]), all).await.unwrap()),
+            vec![(message.canonical_id(), 1)]
+        );
+
+        let path = store.shortest_path(&me.canonical_id(), &bob.canonical_id(), all).await.unwrap().unwrap();
+        assert_eq!(
+            path.nodes,
+            vec![me.canonical_id(), message.canonical_id(), channel.canonical_id(), bob.canonical_id()]
+        );
+        let relations: Vec<Relation> = path.edges.iter().map(|edge| edge.relation).collect();
+        assert_eq!(relations, vec![Relation::AuthorOf, Relation::PostedIn, Relation::MemberOf]);
+        assert!(store.shortest_path(&me.canonical_id(), &loner, all).await.unwrap().is_none());
+
+        let subgraph = store.subgraph(&channel.canonical_id(), 1, all).await.unwrap();
+        assert_eq!(subgraph.nodes.len(), 3);
+        assert_eq!(subgraph.edges.len(), 2);
+
+        // Bob's membership is older than the window
+        assert!(store.shortest_path(&me.canonical_id(), &bob.canonical_id(), recent).await.unwrap().is_none());
+        let subgraph = store.subgraph(&channel.canonical_id(), 1, recent).await.unwrap();
+        assert_eq!(subgraph.nodes.len(), 2);
+        assert_eq!(subgraph.edges.len(), 1);
+        let old = TimeRange { since: None, until: Some(now - chrono::Duration::days(30)) };
+        assert_eq!(
+            ids(store.neighbors(&channel.canonical_id(), 1, None, old).await.unwrap()),
+            vec![(bob.canonical_id(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let store = GraphStore::new(setup_test_db().await);
+        let week_ago = Utc::now() - chrono::Duration::days(7);
+        let me = NodeRef::user("slack", "U1");
+        for channel in ["C1", "C2"] {
+            store
+                .upsert_edge(&ExtractedEdge::new(me.clone(), NodeRef::channel("slack", channel), Relation::MemberOf, week_ago))
+                .await
+                .unwrap();
+        }
+        store
+            .upsert_edge(&ExtractedEdge::new(NodeRef::user("github", "alice"), NodeRef::issue("github", "42"), Relation::AuthorOf, week_ago))
+            .await
+            .unwrap();
+
+        let stats = store.stats().await.unwrap();
+        assert_eq!((stats.nodes, stats.edges), (5, 3));
+        assert_eq!(
+            stats.nodes_by_type,
+            vec![("channel".to_string(), 2), ("user".to_string(), 2), ("issue".to_string(), 1)]
+        );
+        assert_eq!(
+            stats.edges_by_relation,
+            vec![("member_of".to_string(), 2), ("author_of".to_string(), 1)]
+        );
+        let providers: Vec<(&str, i64)> = stats.providers.iter().map(|p| (p.provider.as_str(), p.edges)).collect();
+        assert_eq!(providers, vec![("slack", 2), ("github", 1)]);
+        // Extracted just now, from interactions a week old
+        let slack = &stats.providers[0];
+        assert!(slack.last_extracted_at.unwrap() > Utc::now() - chrono::Duration::minutes(1));
+        assert_eq!(slack.last_observed_at.unwrap().timestamp(), week_ago.timestamp());
+    }
+
+    #[tokio::test]
+    async fn test_ring_transitions_log() {
+        let pool = setup_test_db().await;
+        let store = GraphStore::new(pool);
+
+        let since = Utc::now() - chrono::Duration::seconds(1);
+        let transition = RingTransition {
+            node_id: "user:slack:U123".to_string(),
+            from: Ring::Two,
+            to: Ring::One,
+            effective_distance: 1.5,
+            transitioned_at: Utc::now(),
+            reason: RingChangeReason::Linked,
+        };
+        store.record_ring_transition(&transition).await.unwrap();
+
+        let logged = store.ring_transitions_since(since).await.unwrap();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].node_id, transition.node_id);
+        assert_eq!(logged[0].reason, RingChangeReason::Linked);
+        assert!(logged[0].is_promotion());
+
+        store
+            .upsert_node(&NodeRef::with_name(NodeType::User, "slack", "U123", "Alice"))
+            .await
+            .unwrap();
+        let diff = store.ring_diff_since(since).await.unwrap();
+        assert_eq!(diff.promoted[0].describe(), "Alice moved into Ring 1");
+        assert!(diff.demoted.is_empty());
+        assert!(store
+            .ring_transitions_since(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap()
+            .is_empty());
     }
 }
@@ -0,0 +1,291 @@
+//! Small file attachments referenced from documents.
+//!
+//! Providers store attachments (a Slack snippet, an email's PDF) next to the
+//! document they belong to. Contents are content-addressed: `attachment_blobs`
+//! holds each distinct file once, keyed by SHA-256, and `attachments` maps a
+//! `(doc_id, name)` to a blob. An attachment is addressed as
+//! `attachment://<id>`, which `read_resource` accepts.
+//!
+//! Attachments go with their document when its tombstone is purged; blobs no
+//! longer referenced go with them.
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// URI scheme of attachments.
+pub const URI_SCHEME: &str = "attachment://";
+
+/// Larger files aren't stored; index their metadata in the document instead.
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub doc_id: i64,
+    pub name: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub sha256: String,
+}
+
+impl Attachment {
+    pub fn uri(&self) -> String {
+        format!("{}{}", URI_SCHEME, self.id)
+    }
+}
+
+/// Attachment ID in an `attachment://<id>` URI.
+pub fn parse_uri(uri: &str) -> Option<i64> {
+    uri.strip_prefix(URI_SCHEME)?.parse().ok()
+}
+
+pub(crate) async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS attachment_blobs (\
+            sha256 TEXT PRIMARY KEY,\
+            size INTEGER NOT NULL,\
+            data BLOB NOT NULL\
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS attachments (\
+            id INTEGER PRIMARY KEY AUTOINCREMENT,\
+            doc_id INTEGER NOT NULL,\
+            name TEXT NOT NULL,\
+            mime_type TEXT NOT NULL,\
+            sha256 TEXT NOT NULL,\
+            created_at TEXT NOT NULL,\
+            UNIQUE(doc_id, name)\
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_attachments_sha256 ON attachments(sha256)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Store `data` as `name` on `doc_id`, replacing an attachment of the same
+/// name.
+pub(crate) async fn store(
+    pool: &SqlitePool,
+    doc_id: i64,
+    name: &str,
+    mime_type: &str,
+    data: &[u8],
+) -> Result<Attachment> {
+    if data.len() > MAX_ATTACHMENT_BYTES {
+        bail!(
+            "attachment too large ({} bytes, limit {})",
+            data.len(),
+            MAX_ATTACHMENT_BYTES
+        );
+    }
+    let sha256 = format!("{:x}", Sha256::digest(data));
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT OR IGNORE INTO attachment_blobs (sha256, size, data) VALUES (?1, ?2, ?3)")
+        .bind(&sha256)
+        .bind(data.len() as i64)
+        .bind(data)
+        .execute(&mut *tx)
+        .await?;
+    let previous: Option<String> =
+        sqlx::query_scalar("SELECT sha256 FROM attachments WHERE doc_id = ?1 AND name = ?2")
+            .bind(doc_id)
+            .bind(name)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO attachments (doc_id, name, mime_type, sha256, created_at) \
+        VALUES (?1, ?2, ?3, ?4, ?5) \
+        ON CONFLICT(doc_id, name) DO UPDATE SET \
+            mime_type=excluded.mime_type, \
+            sha256=excluded.sha256 \
+        RETURNING id",
+    )
+    .bind(doc_id)
+    .bind(name)
+    .bind(mime_type)
+    .bind(&sha256)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_one(&mut *tx)
+    .await?;
+    if let Some(previous) = previous.filter(|previous| *previous != sha256) {
+        delete_unreferenced_blobs(&mut tx, &[previous]).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Attachment {
+        id,
+        doc_id,
+        name: name.to_string(),
+        mime_type: mime_type.to_string(),
+        size: data.len() as i64,
+        sha256,
+    })
+}
+
+const SELECT: &str = "SELECT a.id, a.doc_id, a.name, a.mime_type, b.size, a.sha256 \
+    FROM attachments a JOIN attachment_blobs b ON b.sha256 = a.sha256";
+
+type Row = (i64, i64, String, String, i64, String);
+
+/// A [`Row`] followed by the blob's contents.
+type RowWithData = (i64, i64, String, String, i64, String, Vec<u8>);
+
+fn from_row((id, doc_id, name, mime_type, size, sha256): Row) -> Attachment {
+    Attachment {
+        id,
+        doc_id,
+        name,
+        mime_type,
+        size,
+        sha256,
+    }
+}
+
+/// An attachment and its contents, unless its document is deleted.
+pub(crate) async fn get(pool: &SqlitePool, id: i64) -> Result<Option<(Attachment, Vec<u8>)>> {
+    let row: Option<RowWithData> = sqlx::query_as(
+        "SELECT a.id, a.doc_id, a.name, a.mime_type, b.size, a.sha256, b.data \
+        FROM attachments a JOIN attachment_blobs b ON b.sha256 = a.sha256 \
+        JOIN documents d ON d.id = a.doc_id \
+        WHERE a.id = ?1 AND d.deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(id, doc_id, name, mime_type, size, sha256, data)| {
+        (from_row((id, doc_id, name, mime_type, size, sha256)), data)
+    }))
+}
+
+pub(crate) async fn list(pool: &SqlitePool, doc_id: i64) -> Result<Vec<Attachment>> {
+    let rows: Vec<Row> = sqlx::query_as(&format!("{} WHERE a.doc_id = ?1 ORDER BY a.name", SELECT))
+        .bind(doc_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Remove the attachments of `doc_ids` and blobs nothing else references.
+pub(crate) async fn delete_for_documents(conn: &mut SqliteConnection, doc_ids: &[i64]) -> Result<()> {
+    let ids = serde_json::to_string(doc_ids)?;
+    let hashes: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT sha256 FROM attachments WHERE doc_id IN (SELECT value FROM json_each(?1))",
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    if hashes.is_empty() {
+        return Ok(());
+    }
+    sqlx::query("DELETE FROM attachments WHERE doc_id IN (SELECT value FROM json_each(?1))")
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?;
+    delete_unreferenced_blobs(conn, &hashes).await
+}
+
+async fn delete_unreferenced_blobs(conn: &mut SqliteConnection, hashes: &[String]) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM attachment_blobs WHERE sha256 IN (SELECT value FROM json_each(?1)) \
+        AND sha256 NOT IN (SELECT sha256 FROM attachments)",
+    )
+    .bind(serde_json::to_string(hashes)?)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    async fn blob_count(ingest: &IngestionEngine) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM attachment_blobs")
+            .fetch_one(ingest.pool())
+            .await
+            .unwrap()
+    }
+
+    async fn engine_with_doc(dir: &tempfile::TempDir, uri: &str) -> (IngestionEngine, i64) {
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let id = insert_doc(&ingest, uri).await;
+        (ingest, id)
+    }
+
+    async fn insert_doc(ingest: &IngestionEngine, uri: &str) -> i64 {
+        let doc = Document {
+            id: None,
+            uri: uri.to_string(),
+            source: "slack".to_string(),
+            title: None,
+            body: "see attached".to_string(),
+            updated_at: Utc::now(),
+        };
+        ingest.upsert_document(&doc).await.unwrap()
+    }
+
+    #[test]
+    fn test_parse_uri() {
+        assert_eq!(parse_uri("attachment://42"), Some(42));
+        assert_eq!(parse_uri("attachment://x"), None);
+        assert_eq!(parse_uri("slack://42"), None);
+    }
+
+    #[tokio::test]
+    async fn test_blobs_are_shared_and_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ingest, first) = engine_with_doc(&dir, "slack://C1/1").await;
+        let second = insert_doc(&ingest, "slack://C1/2").await;
+
+        let a = ingest.store_attachment(first, "notes.txt", "text/plain", b"v1").await.unwrap();
+        let b = ingest.store_attachment(second, "copy.txt", "text/plain", b"v1").await.unwrap();
+        assert_eq!(a.sha256, b.sha256);
+        assert_eq!(blob_count(&ingest).await, 1);
+
+        // Same name replaces in place; the old blob stays while another attachment uses it
+        let replaced = ingest.store_attachment(first, "notes.txt", "text/markdown", b"v2").await.unwrap();
+        assert_eq!(replaced.id, a.id);
+        assert_eq!(blob_count(&ingest).await, 2);
+        let (stored, data) = ingest.get_attachment(a.id).await.unwrap().unwrap();
+        assert_eq!((stored.mime_type.as_str(), data.as_slice()), ("text/markdown", b"v2".as_slice()));
+
+        ingest.store_attachment(second, "copy.txt", "text/plain", b"v3").await.unwrap();
+        assert_eq!(blob_count(&ingest).await, 2);
+        assert_eq!(ingest.list_attachments(first).await.unwrap().len(), 1);
+
+        let err = ingest
+            .store_attachment(first, "big.bin", "application/octet-stream", &vec![0; MAX_ATTACHMENT_BYTES + 1])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_attachments_follow_their_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ingest, doc_id) = engine_with_doc(&dir, "slack://C1/1").await;
+        let attachment = ingest.store_attachment(doc_id, "a.txt", "text/plain", b"hello").await.unwrap();
+        assert_eq!(attachment.uri(), format!("attachment://{}", attachment.id));
+
+        // Hidden once the document is deleted, gone once its tombstone is purged
+        ingest.delete_documents(&[doc_id]).await.unwrap();
+        assert!(ingest.get_attachment(attachment.id).await.unwrap().is_none());
+        assert_eq!(ingest.purge_tombstones(&[doc_id]).await.unwrap(), vec![doc_id]);
+        assert!(ingest.list_attachments(doc_id).await.unwrap().is_empty());
+        assert_eq!(blob_count(&ingest).await, 0);
+    }
+}
@@ -0,0 +1,276 @@
+//! Near-duplicate detection.
+//!
+//! Forwarded emails, cross-posted Slack messages and issues mirrored between
+//! Jira and GitHub carry the same text under different URIs. Each stored
+//! document gets a 64-bit SimHash of its word shingles; a document whose
+//! fingerprint is within [`MAX_DISTANCE`] bits of an earlier one is recorded
+//! as an alternate of that document's canonical copy. Both stay stored and
+//! searchable; retrieval collapses alternates into their canonical document.
+//!
+//! Candidates are found by splitting fingerprints into four 16-bit bands: two
+//! fingerprints within three bits of each other share at least one band.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// Fingerprints differing in at most this many bits are near-duplicates.
+pub const MAX_DISTANCE: u32 = 3;
+
+/// Shorter texts ("thanks!", "+1") aren't fingerprinted; they'd collide.
+const MIN_WORDS: usize = 12;
+
+const SHINGLE_WORDS: usize = 3;
+
+pub(crate) async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'document_fingerprints'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS document_fingerprints (\
+            doc_id INTEGER PRIMARY KEY,\
+            simhash INTEGER NOT NULL,\
+            band0 INTEGER NOT NULL,\
+            band1 INTEGER NOT NULL,\
+            band2 INTEGER NOT NULL,\
+            band3 INTEGER NOT NULL\
+        )",
+    )
+    .execute(pool)
+    .await?;
+    for band in 0..4 {
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_document_fingerprints_band{0} \
+            ON document_fingerprints(band{0})",
+            band
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS document_duplicates (\
+            doc_id INTEGER PRIMARY KEY,\
+            canonical_id INTEGER NOT NULL,\
+            distance INTEGER NOT NULL\
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_document_duplicates_canonical \
+        ON document_duplicates(canonical_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    if !exists {
+        backfill(pool).await?;
+    }
+    Ok(())
+}
+
+/// Fingerprint documents stored before duplicates were tracked, oldest
+/// first so the earliest copy becomes canonical.
+async fn backfill(pool: &SqlitePool) -> Result<()> {
+    let mut last_id = 0i64;
+    loop {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT id, body FROM documents WHERE id > ?1 AND deleted_at IS NULL ORDER BY id LIMIT 500",
+        )
+        .bind(last_id)
+        .fetch_all(pool)
+        .await?;
+        let Some((id, _)) = rows.last() else { break };
+        last_id = *id;
+        let mut tx = pool.begin().await?;
+        for (id, body) in &rows {
+            index(&mut tx, *id, body).await?;
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// 64-bit SimHash over three-word shingles of the lowercased words in
+/// `text`, or None for texts too short to compare.
+pub fn simhash(text: &str) -> Option<u64> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+    let mut weights = [0i32; 64];
+    for shingle in words.windows(SHINGLE_WORDS) {
+        let hash = fnv1a(shingle);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0u64, |hash, (bit, _)| hash | (1 << bit)),
+    )
+}
+
+/// FNV-1a, which unlike std's hasher is stable across releases; the
+/// fingerprints are stored.
+fn fnv1a(words: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for word in words {
+        for byte in word.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+fn bands(hash: u64) -> [i64; 4] {
+    [0, 1, 2, 3].map(|band| ((hash >> (16 * band)) & 0xffff) as i64)
+}
+
+/// Fingerprint `doc_id` and record it as an alternate if it nearly
+/// duplicates another live document. Returns the canonical document's ID in
+/// that case.
+pub(crate) async fn index(conn: &mut SqliteConnection, doc_id: i64, body: &str) -> Result<Option<i64>> {
+    // Its content may have changed since it was last matched
+    sqlx::query("DELETE FROM document_duplicates WHERE doc_id = ?1")
+        .bind(doc_id)
+        .execute(&mut *conn)
+        .await?;
+    let Some(hash) = simhash(body) else {
+        sqlx::query("DELETE FROM document_fingerprints WHERE doc_id = ?1")
+            .bind(doc_id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(None);
+    };
+    let [b0, b1, b2, b3] = bands(hash);
+    sqlx::query(
+        "INSERT INTO document_fingerprints (doc_id, simhash, band0, band1, band2, band3) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+        ON CONFLICT(doc_id) DO UPDATE SET simhash=excluded.simhash, band0=excluded.band0, \
+            band1=excluded.band1, band2=excluded.band2, band3=excluded.band3",
+    )
+    .bind(doc_id)
+    .bind(hash as i64)
+    .bind(b0)
+    .bind(b1)
+    .bind(b2)
+    .bind(b3)
+    .execute(&mut *conn)
+    .await?;
+
+    // Documents that are themselves canonical for this one are skipped, so
+    // a chain of edits can't make two documents alternates of each other
+    let candidates: Vec<(i64, i64, Option<i64>)> = sqlx::query_as(
+        "SELECT f.doc_id, f.simhash, dup.canonical_id FROM document_fingerprints f \
+        JOIN documents d ON d.id = f.doc_id \
+        LEFT JOIN document_duplicates dup ON dup.doc_id = f.doc_id \
+        WHERE (f.band0 = ?2 OR f.band1 = ?3 OR f.band2 = ?4 OR f.band3 = ?5) \
+        AND f.doc_id != ?1 AND d.deleted_at IS NULL \
+        AND f.doc_id NOT IN (SELECT doc_id FROM document_duplicates WHERE canonical_id = ?1)",
+    )
+    .bind(doc_id)
+    .bind(b0)
+    .bind(b1)
+    .bind(b2)
+    .bind(b3)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let best = candidates
+        .into_iter()
+        .map(|(id, other, canonical)| (distance(hash, other as u64), id, canonical))
+        .filter(|(distance, _, _)| *distance <= MAX_DISTANCE)
+        .min_by_key(|(distance, id, _)| (*distance, *id));
+    let Some((distance, matched, canonical)) = best else {
+        return Ok(None);
+    };
+    let canonical = canonical.unwrap_or(matched);
+    if canonical == doc_id {
+        return Ok(None);
+    }
+    sqlx::query("INSERT INTO document_duplicates (doc_id, canonical_id, distance) VALUES (?1, ?2, ?3)")
+        .bind(doc_id)
+        .bind(canonical)
+        .bind(distance as i64)
+        .execute(&mut *conn)
+        .await?;
+    Ok(Some(canonical))
+}
+
+/// Canonical document of each of `ids` that is a near-duplicate of a live
+/// document.
+pub(crate) async fn canonical_ids(pool: &SqlitePool, ids: &[i64]) -> Result<HashMap<i64, i64>> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT dup.doc_id, dup.canonical_id FROM document_duplicates dup \
+        JOIN documents c ON c.id = dup.canonical_id \
+        WHERE dup.doc_id IN (SELECT value FROM json_each(?1)) AND c.deleted_at IS NULL",
+    )
+    .bind(serde_json::to_string(ids)?)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// URIs of the live alternates of each of `canonical_ids`.
+pub(crate) async fn alternates(pool: &SqlitePool, canonical_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+    if canonical_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT dup.canonical_id, d.uri FROM document_duplicates dup \
+        JOIN documents d ON d.id = dup.doc_id \
+        WHERE dup.canonical_id IN (SELECT value FROM json_each(?1)) AND d.deleted_at IS NULL \
+        ORDER BY dup.canonical_id, d.id",
+    )
+    .bind(serde_json::to_string(canonical_ids)?)
+    .fetch_all(pool)
+    .await?;
+    let mut alternates: HashMap<i64, Vec<String>> = HashMap::new();
+    for (canonical, uri) in rows {
+        alternates.entry(canonical).or_default().push(uri);
+    }
+    Ok(alternates)
+}
+
+/// Drop fingerprints and duplicate records involving purged documents.
+/// Alternates of a purged canonical document become standalone.
+pub(crate) async fn forget(conn: &mut SqliteConnection, doc_ids: &[i64]) -> Result<()> {
+    let ids = serde_json::to_string(doc_ids)?;
+    sqlx::query("DELETE FROM document_fingerprints WHERE doc_id IN (SELECT value FROM json_each(?1))")
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "DELETE FROM document_duplicates WHERE doc_id IN (SELECT value FROM json_each(?1)) \
+        OR canonical_id IN (SELECT value FROM json_each(?1))",
+    )
+    .bind(&ids)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
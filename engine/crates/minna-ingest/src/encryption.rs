@@ -0,0 +1,190 @@
+//! Encryption at rest with SQLCipher.
+//!
+//! With `MINNA_ENCRYPT_DB=1` the daemon opens `minna.db` with a raw 256-bit
+//! key held in the keychain, so synced DMs and emails aren't readable from
+//! disk. This needs a SQLCipher build of SQLite (the `sqlcipher` feature);
+//! opening with a key on plain SQLite fails instead of silently storing
+//! plaintext. An existing plaintext database is encrypted in place the first
+//! time it's opened with a key.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use tracing::info;
+
+/// First 16 bytes of every unencrypted SQLite database.
+const PLAINTEXT_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Add the SQLCipher `key` pragma for a 64-hex-character raw key.
+pub fn apply_key(options: SqliteConnectOptions, key: &str) -> Result<SqliteConnectOptions> {
+    Ok(options.pragma("key", key_pragma(key)?))
+}
+
+/// Value of `PRAGMA key` for a raw key, e.g. `"x'00ff...'"`.
+pub fn key_pragma(key: &str) -> Result<String> {
+    if key.len() != 64 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("database key must be 64 hex characters"));
+    }
+    Ok(format!("\"x'{}'\"", key))
+}
+
+/// Whether `db_path` is an existing, unencrypted SQLite database.
+pub fn is_plaintext(db_path: &Path) -> Result<bool> {
+    use std::io::Read;
+    let mut file = match std::fs::File::open(db_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let mut header = [0u8; 16];
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == PLAINTEXT_HEADER),
+        // Empty or truncated files have nothing to migrate
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Fail unless SQLite was built with SQLCipher.
+pub async fn ensure_sqlcipher(pool: &SqlitePool) -> Result<()> {
+    let version: Option<String> = sqlx::query_scalar("PRAGMA cipher_version")
+        .fetch_optional(pool)
+        .await?;
+    if version.is_none_or(|v| v.is_empty()) {
+        return Err(anyhow!(
+            "MINNA_ENCRYPT_DB is set but this build has no SQLCipher support (enable the `sqlcipher` feature)"
+        ));
+    }
+    Ok(())
+}
+
+/// Rewrite a plaintext database as an encrypted one with `key`, replacing
+/// the original file.
+pub async fn encrypt_in_place(db_path: &Path, key: &str) -> Result<()> {
+    let pragma = key_pragma(key)?;
+    let encrypted = sibling(db_path, "encrypting");
+    // ATTACH inherits the connection's open flags, which don't include
+    // create, so the target has to exist (an empty file is a new database)
+    std::fs::File::create(&encrypted)?;
+
+    let options = SqliteConnectOptions::from_str("sqlite:")?.filename(db_path);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    ensure_sqlcipher(&pool).await?;
+    info!("[ENCRYPT] Encrypting existing database {}", db_path.display());
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&pool).await?;
+    let attach = format!(
+        "ATTACH DATABASE '{}' AS encrypted KEY {}",
+        encrypted.display().to_string().replace('\'', "''"),
+        pragma
+    );
+    sqlx::query(&attach).execute(&pool).await?;
+    sqlx::query("SELECT sqlcipher_export('encrypted')")
+        .execute(&pool)
+        .await
+        .context("sqlcipher_export failed")?;
+    sqlx::query("DETACH DATABASE encrypted").execute(&pool).await?;
+    pool.close().await;
+
+    std::fs::rename(&encrypted, db_path)?;
+    // Journal files of the plaintext database must not be replayed
+    for suffix in ["wal", "shm"] {
+        let _ = std::fs::remove_file(sibling(db_path, suffix));
+    }
+    Ok(())
+}
+
+/// `minna.db` -> `minna.db-<suffix>`, like SQLite's own journal files.
+fn sibling(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push("-");
+    name.push(suffix);
+    db_path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    const KEY: &str = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+
+    async fn plaintext_db(path: &Path) {
+        let ingest = IngestionEngine::new(path).await.unwrap();
+        let doc = Document {
+            id: None,
+            uri: "slack://D1/1".to_string(),
+            source: "slack".to_string(),
+            title: None,
+            body: "a private message".to_string(),
+            updated_at: chrono::Utc::now(),
+        };
+        ingest.upsert_document(&doc).await.unwrap();
+        ingest.pool().close().await;
+    }
+
+    #[test]
+    fn test_key_pragma() {
+        assert_eq!(key_pragma(KEY).unwrap(), format!("\"x'{}'\"", KEY));
+        assert!(key_pragma("abc").is_err());
+        // Right length, but not hex: never interpolated into the pragma
+        assert!(key_pragma(&format!("{}'; --", &KEY[..59])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_is_plaintext() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minna.db");
+        assert!(!is_plaintext(&path).unwrap());
+        std::fs::write(&path, b"").unwrap();
+        assert!(!is_plaintext(&path).unwrap());
+        std::fs::write(&path, [7u8; 64]).unwrap();
+        assert!(!is_plaintext(&path).unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+        plaintext_db(&path).await;
+        assert!(is_plaintext(&path).unwrap());
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_key_without_sqlcipher_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let fresh = dir.path().join("fresh.db");
+        let err = IngestionEngine::open(&fresh, Some(KEY)).await.err().unwrap();
+        assert!(err.to_string().contains("no SQLCipher support"), "{err}");
+
+        // An existing database is left readable rather than half-migrated
+        let existing = dir.path().join("minna.db");
+        plaintext_db(&existing).await;
+        assert!(IngestionEngine::open(&existing, Some(KEY)).await.is_err());
+        assert!(is_plaintext(&existing).unwrap());
+        let ingest = IngestionEngine::new(&existing).await.unwrap();
+        assert!(ingest.get_document_by_uri("slack://D1/1").await.unwrap().is_some());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_existing_database_is_encrypted_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minna.db");
+        plaintext_db(&path).await;
+
+        let ingest = IngestionEngine::open(&path, Some(KEY)).await.unwrap();
+        assert!(ingest.get_document_by_uri("slack://D1/1").await.unwrap().is_some());
+        ingest.pool().close().await;
+        assert!(!is_plaintext(&path).unwrap());
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(17).any(|w| w == b"a private message"));
+
+        let wrong = KEY.replace('0', "f");
+        assert!(IngestionEngine::open(&path, Some(&wrong)).await.is_err());
+        assert!(IngestionEngine::new(&path).await.is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! Text extraction for binary file formats.
+//!
+//! Providers that fetch raw file contents (Drive, Dropbox, local folders) run
+//! them through [`extract_text`] so documents are indexed with real content
+//! instead of metadata-only bodies. Images and image-only PDFs go through
+//! [`crate::ocr`] when OCR is enabled; audio goes through `crate::transcribe`
+//! when built with the `transcription` feature.
+
+use std::io::{Cursor, Read};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+
+use crate::ocr::{self, OcrConfig};
+
+static SHARED_STRING_RE: OnceLock<Regex> = OnceLock::new();
+static ROW_RE: OnceLock<Regex> = OnceLock::new();
+static CELL_RE: OnceLock<Regex> = OnceLock::new();
+static VALUE_RE: OnceLock<Regex> = OnceLock::new();
+static ENTITY_RE: OnceLock<Regex> = OnceLock::new();
+static T_RE: OnceLock<Regex> = OnceLock::new();
+static WORD_PARA_RE: OnceLock<Regex> = OnceLock::new();
+static WORD_TEXT_RE: OnceLock<Regex> = OnceLock::new();
+static SLIDE_PARA_RE: OnceLock<Regex> = OnceLock::new();
+static SLIDE_TEXT_RE: OnceLock<Regex> = OnceLock::new();
+
+fn cached_regex(lock: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    lock.get_or_init(|| Regex::new(pattern).expect("valid regex"))
+}
+
+/// Files larger than this are indexed by metadata only.
+pub const MAX_EXTRACT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Extracted text is capped to keep FTS rows and embeddings reasonable.
+pub const MAX_EXTRACTED_CHARS: usize = 200_000;
+
+/// Decompressed size cap for a single entry inside an Office archive.
+const MAX_ARCHIVE_ENTRY_BYTES: u64 = 50 * 1024 * 1024;
+
+/// MIME type for PDF files.
+pub const PDF_MIME: &str = "application/pdf";
+
+/// MIME type for Word documents.
+pub const DOCX_MIME: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// MIME type for PowerPoint presentations.
+pub const PPTX_MIME: &str = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+
+/// MIME type for Excel spreadsheets.
+pub const XLSX_MIME: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Whether we know how to extract text from this MIME type.
+pub fn is_supported(mime_type: &str) -> bool {
+    matches!(mime_type, PDF_MIME | DOCX_MIME | PPTX_MIME | XLSX_MIME)
+        || (ocr::is_image(mime_type) && OcrConfig::global().is_some())
+        || is_transcribable(mime_type)
+}
+
+#[cfg(feature = "transcription")]
+fn is_transcribable(mime_type: &str) -> bool {
+    crate::transcribe::is_audio(mime_type) && crate::transcribe::TranscribeConfig::global().is_some()
+}
+
+#[cfg(not(feature = "transcription"))]
+fn is_transcribable(_mime_type: &str) -> bool {
+    false
+}
+
+/// Extract plain text from file bytes based on MIME type.
+///
+/// Returns `Ok(None)` for unsupported types. CPU-bound; call from
+/// `spawn_blocking` in async contexts.
+pub fn extract_text(mime_type: &str, bytes: &[u8]) -> Result<Option<String>> {
+    if bytes.len() > MAX_EXTRACT_BYTES {
+        return Err(anyhow!(
+            "file too large for extraction ({} bytes, limit {})",
+            bytes.len(),
+            MAX_EXTRACT_BYTES
+        ));
+    }
+
+    let text = match mime_type {
+        PDF_MIME => {
+            let text = extract_pdf_text(bytes)?;
+            match OcrConfig::global() {
+                // No text layer: probably a scan
+                Some(ocr) if text.trim().is_empty() => ocr.pdf_text(bytes)?,
+                _ => text,
+            }
+        }
+        DOCX_MIME => extract_docx_text(bytes)?,
+        PPTX_MIME => extract_pptx_text(bytes)?,
+        XLSX_MIME => extract_xlsx_text(bytes)?,
+        mime if ocr::is_image(mime) => match OcrConfig::global() {
+            Some(ocr) => ocr.image_text(bytes)?,
+            None => return Ok(None),
+        },
+        #[cfg(feature = "transcription")]
+        mime if crate::transcribe::is_audio(mime) => match crate::transcribe::TranscribeConfig::global() {
+            Some(whisper) => whisper.transcribe(mime, bytes)?,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(normalize(&text)))
+}
+
+/// Extract text from a PDF.
+///
+/// pdf-extract can panic on malformed input, so the panic is caught and
+/// reported as an error rather than taking down the sync.
+pub fn extract_pdf_text(bytes: &[u8]) -> Result<String> {
+    let result = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes));
+    match result {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(err)) => Err(anyhow!("PDF extraction failed: {}", err)),
+        Err(_) => Err(anyhow!("PDF extraction panicked on malformed input")),
+    }
+}
+
+/// Extract paragraph text from a Word document.
+pub fn extract_docx_text(bytes: &[u8]) -> Result<String> {
+    let mut archive = open_archive(bytes)?;
+    let xml = read_entry(&mut archive, "word/document.xml")?;
+    Ok(paragraph_text(
+        &xml,
+        cached_regex(&WORD_PARA_RE, r"(?s)<w:p[ >](.*?)</w:p>"),
+        cached_regex(&WORD_TEXT_RE, r"(?s)<w:t(?:\s[^>]*)?>(.*?)</w:t>"),
+    ))
+}
+
+/// Extract slide text from a PowerPoint presentation, one section per slide.
+pub fn extract_pptx_text(bytes: &[u8]) -> Result<String> {
+    let mut archive = open_archive(bytes)?;
+    let mut slides: Vec<(u32, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let num = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((num, name.to_string()))
+        })
+        .collect();
+    slides.sort_unstable();
+
+    let mut out = String::new();
+    for (num, name) in slides {
+        let xml = read_entry(&mut archive, &name)?;
+        out.push_str(&format!("## Slide {}\n\n", num));
+        out.push_str(&paragraph_text(
+            &xml,
+            cached_regex(&SLIDE_PARA_RE, r"(?s)<a:p[ >](.*?)</a:p>"),
+            cached_regex(&SLIDE_TEXT_RE, r"(?s)<a:t(?:\s[^>]*)?>(.*?)</a:t>"),
+        ));
+        out.push_str("\n\n");
+    }
+    Ok(out)
+}
+
+/// Extract cell values from an Excel workbook, one tab-separated line per row.
+pub fn extract_xlsx_text(bytes: &[u8]) -> Result<String> {
+    let mut archive = open_archive(bytes)?;
+
+    let shared: Vec<String> = match read_entry(&mut archive, "xl/sharedStrings.xml") {
+        Ok(xml) => cached_regex(&SHARED_STRING_RE, r"(?s)<si>(.*?)</si>")
+            .captures_iter(&xml)
+            .map(|si| text_runs(&si[1], t_regex()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let mut sheets: Vec<(u32, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let num = name
+                .strip_prefix("xl/worksheets/sheet")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((num, name.to_string()))
+        })
+        .collect();
+    sheets.sort_unstable();
+
+    let mut out = String::new();
+    for (num, name) in sheets {
+        let xml = read_entry(&mut archive, &name)?;
+        out.push_str(&format!("## Sheet {}\n\n", num));
+        for row in cached_regex(&ROW_RE, r"(?s)<row\b[^>]*>(.*?)</row>").captures_iter(&xml) {
+            let cells: Vec<String> = cached_regex(&CELL_RE, r"(?s)<c\b([^>]*?)(?:/>|>(.*?)</c>)")
+                .captures_iter(&row[1])
+                .filter_map(|cell| {
+                    let attrs = cell.get(1).map_or("", |m| m.as_str());
+                    let body = cell.get(2).map_or("", |m| m.as_str());
+                    cell_value(attrs, body, &shared)
+                })
+                .collect();
+            if !cells.is_empty() {
+                out.push_str(&cells.join("\t"));
+                out.push('\n');
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn cell_value(attrs: &str, body: &str, shared: &[String]) -> Option<String> {
+    if attrs.contains(r#"t="inlineStr""#) {
+        return Some(text_runs(body, t_regex()));
+    }
+    let value = cached_regex(&VALUE_RE, r"(?s)<v>(.*?)</v>").captures(body)?;
+    let value = unescape_xml(&value[1]);
+    if attrs.contains(r#"t="s""#) {
+        return value.parse::<usize>().ok().and_then(|i| shared.get(i).cloned());
+    }
+    Some(value)
+}
+
+type Archive<'a> = zip::ZipArchive<Cursor<&'a [u8]>>;
+
+fn open_archive(bytes: &[u8]) -> Result<Archive<'_>> {
+    zip::ZipArchive::new(Cursor::new(bytes)).context("not a valid Office archive")
+}
+
+fn read_entry(archive: &mut Archive<'_>, name: &str) -> Result<String> {
+    let entry = archive
+        .by_name(name)
+        .with_context(|| format!("missing {} in Office archive", name))?;
+    let mut xml = String::new();
+    entry.take(MAX_ARCHIVE_ENTRY_BYTES).read_to_string(&mut xml)?;
+    Ok(xml)
+}
+
+fn t_regex() -> &'static Regex {
+    cached_regex(&T_RE, r"(?s)<t(?:\s[^>]*)?>(.*?)</t>")
+}
+
+/// Text of each paragraph matched by `para`, one per line.
+fn paragraph_text(xml: &str, para: &Regex, run: &Regex) -> String {
+    para.captures_iter(xml)
+        .map(|p| text_runs(&p[1], run))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Concatenated, unescaped contents of every text run matched by `run`.
+fn text_runs(xml: &str, run: &Regex) -> String {
+    run.captures_iter(xml).map(|r| unescape_xml(&r[1])).collect()
+}
+
+fn unescape_xml(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    cached_regex(&ENTITY_RE, r"&(#x[0-9a-fA-F]+|#[0-9]+|amp|lt|gt|quot|apos);")
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            match entity {
+                "amp" => "&".to_string(),
+                "lt" => "<".to_string(),
+                "gt" => ">".to_string(),
+                "quot" => "\"".to_string(),
+                "apos" => "'".to_string(),
+                _ => {
+                    let code = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity[1..].parse().ok(),
+                    };
+                    code.and_then(char::from_u32).map(String::from).unwrap_or_default()
+                }
+            }
+        })
+        .into_owned()
+}
+
+/// Collapse runs of blank lines and trailing whitespace, then cap the length.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len().min(MAX_EXTRACTED_CHARS));
+    let mut blank_run = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    let trimmed = out.trim();
+    match trimmed.char_indices().nth(MAX_EXTRACTED_CHARS) {
+        Some((idx, _)) => trimmed[..idx].to_string(),
+        None => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn office_archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Single-page PDF with one line of Helvetica text and a valid xref table.
+    fn pdf_with_text(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>"
+                .to_string(),
+            format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, body).as_bytes());
+        }
+        let xref = pdf.len();
+        pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        pdf.extend_from_slice(
+            format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+        );
+        pdf
+    }
+
+    #[test]
+    fn test_pdf_text_is_extracted() {
+        let text = extract_text(PDF_MIME, &pdf_with_text("Quarterly roadmap review")).unwrap().unwrap();
+        assert!(text.contains("Quarterly roadmap review"), "{text:?}");
+    }
+
+    #[test]
+    fn test_malformed_pdf_is_an_error() {
+        assert!(extract_pdf_text(b"%PDF-1.4\nnot really a pdf").is_err());
+        assert!(extract_text(PDF_MIME, b"").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_types_are_skipped() {
+        assert!(!is_supported("text/html"));
+        assert!(extract_text("text/html", b"<p>hi</p>").unwrap().is_none());
+        assert!(extract_text("application/octet-stream", b"\x00\x01").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_oversized_input_is_rejected() {
+        let err = extract_text(PDF_MIME, &vec![0; MAX_EXTRACT_BYTES + 1]).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+
+    #[test]
+    fn test_normalize_collapses_blank_lines() {
+        assert_eq!(normalize("  \n\nfirst  \n\n\n\nsecond\t\n\n"), "first\n\nsecond");
+        assert_eq!(normalize(&"x".repeat(MAX_EXTRACTED_CHARS + 10)).len(), MAX_EXTRACTED_CHARS);
+    }
+
+    #[test]
+    fn test_docx_paragraphs_and_entities() {
+        let docx = office_archive(&[(
+            "word/document.xml",
+            r#"<w:document><w:body>
+                <w:p><w:r><w:t>Fish &amp; chips</w:t></w:r><w:r><w:t xml:space="preserve"> &lt;today&gt;</w:t></w:r></w:p>
+                <w:p><w:pPr/><w:r><w:t>caf&#233; &#x2713; &quot;ok&quot; it&apos;s</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+        let text = extract_text(DOCX_MIME, &docx).unwrap().unwrap();
+        assert_eq!(text, "Fish & chips <today>\ncafé ✓ \"ok\" it's");
+    }
+
+    #[test]
+    fn test_pptx_slides_are_in_numeric_order() {
+        let slide = |text: &str| format!("<p:sld><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:sld>", text);
+        let (two, ten, one) = (slide("second"), slide("tenth"), slide("first"));
+        // Archive order and lexical order both differ from slide order
+        let pptx = office_archive(&[
+            ("ppt/slides/slide10.xml", &ten),
+            ("ppt/slides/slide2.xml", &two),
+            ("ppt/slides/slide1.xml", &one),
+            ("ppt/slides/_rels/slide1.xml.rels", "<Relationships/>"),
+        ]);
+        let text = extract_text(PPTX_MIME, &pptx).unwrap().unwrap();
+        assert_eq!(text, "## Slide 1\n\nfirst\n\n## Slide 2\n\nsecond\n\n## Slide 10\n\ntenth");
+    }
+
+    #[test]
+    fn test_xlsx_shared_and_inline_strings() {
+        let xlsx = office_archive(&[
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>Name</t></si><si><r><t>Tom </t></r><r><t>&amp; Jerry</t></r></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="inlineStr"><is><t>Score</t></is></c></row>
+                    <row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2"><v>42</v></c><c r="C2"/></row>
+                    <row r="3"><c r="A3" t="s"><v>99</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+        let text = extract_text(XLSX_MIME, &xlsx).unwrap().unwrap();
+        assert_eq!(text, "## Sheet 1\n\nName\tScore\nTom & Jerry\t42");
+    }
+
+    #[test]
+    fn test_office_archive_errors() {
+        assert!(extract_docx_text(b"not a zip").is_err());
+        let err = extract_docx_text(&office_archive(&[("word/other.xml", "")])).unwrap_err();
+        assert!(err.to_string().contains("missing word/document.xml"), "{err}");
+    }
+}
@@ -0,0 +1,435 @@
+//! Full-text index configuration.
+//!
+//! `documents_fts` is an external-content FTS5 table, so changing its
+//! tokenizer only means recreating the virtual table and rebuilding it from
+//! `documents`. Select a tokenizer with `MINNA_FTS_TOKENIZER`:
+//!
+//! - `unicode61` (default): unicode word tokens, diacritics folded (`café` matches `cafe`)
+//! - `porter`: as above plus English stemming (`syncing` matches `synced`)
+//! - `trigram`: substring matching, for CJK text and partial identifiers
+//!
+//! User queries never reach `MATCH` verbatim: [`SearchQuery::parse`] quotes
+//! every term so punctuation and FTS5 operators (`-`, `"`, `NEAR`, `OR`, ...)
+//! are searched literally, and supports a small explicit syntax:
+//!
+//! - `"exact phrase"`: the words in order
+//! - `source:slack`: only documents from a source (`-source:slack` excludes it)
+//! - `-term` / `-"some phrase"`: documents without the term
+//! - `tag:minna-core`: only documents with a tag (see [`crate::IngestionEngine::add_tag`])
+//! - `lang:deu`: only documents in a language (see [`crate::language`])
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::Document;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FtsTokenizer {
+    #[default]
+    Unicode61,
+    Porter,
+    Trigram,
+}
+
+impl FtsTokenizer {
+    /// Read the tokenizer from `MINNA_FTS_TOKENIZER`, defaulting to unicode61.
+    pub fn from_env() -> Self {
+        match std::env::var("MINNA_FTS_TOKENIZER") {
+            Ok(value) => Self::parse(&value).unwrap_or_else(|| {
+                warn!("Unknown MINNA_FTS_TOKENIZER '{}', using unicode61", value);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "unicode61" | "default" => Some(Self::Unicode61),
+            "porter" | "stemming" => Some(Self::Porter),
+            "trigram" => Some(Self::Trigram),
+            _ => None,
+        }
+    }
+
+    /// FTS5 `tokenize` option.
+    pub fn spec(&self) -> &'static str {
+        match self {
+            Self::Unicode61 => "unicode61 remove_diacritics 2",
+            Self::Porter => "porter unicode61 remove_diacritics 2",
+            Self::Trigram => "trigram",
+        }
+    }
+}
+
+fn create_sql(tokenizer: FtsTokenizer) -> String {
+    format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(\
+            uri, title, body,\
+            content='documents',\
+            content_rowid='id',\
+            tokenize='{}'\
+        )",
+        tokenizer.spec()
+    )
+}
+
+pub(crate) async fn create_table(pool: &SqlitePool, tokenizer: FtsTokenizer) -> Result<()> {
+    sqlx::query(&create_sql(tokenizer)).execute(pool).await?;
+    Ok(())
+}
+
+/// Tokenizer spec of the existing index ("unicode61" if none was given).
+pub(crate) async fn current_spec(pool: &SqlitePool) -> Result<Option<String>> {
+    let sql: Option<String> =
+        sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'documents_fts'")
+            .fetch_optional(pool)
+            .await?;
+    Ok(sql.map(|sql| {
+        sql.split_once("tokenize='")
+            .and_then(|(_, rest)| rest.split_once('\''))
+            .map(|(spec, _)| spec.to_string())
+            .unwrap_or_else(|| "unicode61".to_string())
+    }))
+}
+
+/// Recreate the index with `tokenizer` if it uses a different one.
+/// Returns true if the index was rebuilt.
+pub(crate) async fn ensure_tokenizer(pool: &SqlitePool, tokenizer: FtsTokenizer) -> Result<bool> {
+    if current_spec(pool).await?.as_deref() == Some(tokenizer.spec()) {
+        return Ok(false);
+    }
+    info!("[FTS] Rebuilding full-text index with tokenizer '{}'", tokenizer.spec());
+
+    // Triggers refer to the table by name and keep working once it's recreated
+    let mut tx = pool.begin().await?;
+    sqlx::query("DROP TABLE IF EXISTS documents_fts").execute(&mut *tx).await?;
+    sqlx::query(&create_sql(tokenizer)).execute(&mut *tx).await?;
+    sqlx::query("INSERT INTO documents_fts(documents_fts) VALUES('rebuild')")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Rebuild the index contents from `documents` without changing its tokenizer.
+pub(crate) async fn rebuild(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("INSERT INTO documents_fts(documents_fts) VALUES('rebuild')")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A parsed user search query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub phrases: Vec<String>,
+    pub excluded: Vec<String>,
+    pub sources: Vec<String>,
+    pub excluded_sources: Vec<String>,
+    pub tags: Vec<String>,
+    /// ISO 639-3 codes.
+    pub languages: Vec<String>,
+    /// Only documents updated at or after this time.
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Only documents updated before this time.
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Only documents whose URI starts with this, e.g. `slack://C123/`.
+    pub uri_prefix: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut chars = input.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+            let negated = chars.next_if_eq(&'-').is_some();
+
+            if chars.next_if_eq(&'"').is_some() {
+                // An unterminated quote runs to the end of the input
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !is_searchable(&phrase) {
+                    continue;
+                }
+                if negated {
+                    query.excluded.push(phrase);
+                } else if phrase.contains(' ') {
+                    query.phrases.push(phrase);
+                } else {
+                    query.terms.push(phrase);
+                }
+                continue;
+            }
+
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                word.push(c);
+            }
+            if let Some(source) = word.strip_prefix("source:") {
+                let sources = source
+                    .split(',')
+                    .map(|s| s.trim().to_ascii_lowercase())
+                    .filter(|s| !s.is_empty());
+                if negated {
+                    query.excluded_sources.extend(sources);
+                } else {
+                    query.sources.extend(sources);
+                }
+            } else if let Some(tag) = word.strip_prefix("tag:") {
+                // Tags only narrow results; `-tag:` isn't supported
+                if !negated {
+                    query.tags.extend(tag.split(',').filter_map(crate::normalize_tag));
+                }
+            } else if let Some(language) = word.strip_prefix("lang:") {
+                if !negated {
+                    query
+                        .languages
+                        .extend(language.split(',').filter_map(crate::language::normalize));
+                }
+            } else if is_searchable(&word) {
+                if negated {
+                    query.excluded.push(word);
+                } else {
+                    query.terms.push(word);
+                }
+            }
+        }
+        query
+    }
+
+    /// FTS5 `MATCH` expression, or None if nothing positive is searched for
+    /// (FTS5 can't evaluate a bare `NOT`).
+    pub fn match_expr(&self) -> Option<String> {
+        let positive: Vec<String> = self
+            .terms
+            .iter()
+            .chain(&self.phrases)
+            .map(|t| quote(t))
+            .collect();
+        if positive.is_empty() {
+            return None;
+        }
+        let mut expr = positive.join(" ");
+        for term in &self.excluded {
+            expr = format!("({}) NOT {}", expr, quote(term));
+        }
+        Some(expr)
+    }
+
+    /// Free text of the query (terms and phrases), e.g. for semantic search.
+    pub fn text(&self) -> String {
+        self.terms
+            .iter()
+            .chain(&self.phrases)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether a document passes the source, time and URI filters and the
+    /// exclusions. Used to filter results that didn't come from FTS.
+    /// Like the `NOT` of [`Self::match_expr`], an exclusion matches whole
+    /// words of the URI, title or body, in order.
+    pub fn admits(&self, doc: &Document) -> bool {
+        if self.updated_after.is_some_and(|after| doc.updated_at < after)
+            || self.updated_before.is_some_and(|before| doc.updated_at >= before)
+        {
+            return false;
+        }
+        if let Some(prefix) = &self.uri_prefix {
+            if !doc.uri.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        let source = doc.source.to_ascii_lowercase();
+        if !self.sources.is_empty() && !self.sources.contains(&source) {
+            return false;
+        }
+        if self.excluded_sources.contains(&source) {
+            return false;
+        }
+        if self.excluded.is_empty() {
+            return true;
+        }
+        let columns = [doc.uri.as_str(), doc.title.as_deref().unwrap_or(""), &doc.body].map(tokens);
+        !self.excluded.iter().any(|term| {
+            let term = tokens(term);
+            columns
+                .iter()
+                .any(|column| column.windows(term.len()).any(|words| words == term.as_slice()))
+        })
+    }
+}
+
+/// Lowercased words of `text`, split as the unicode61 tokenizer does.
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Terms without any letters or digits can't match a token.
+fn is_searchable(term: &str) -> bool {
+    term.chars().any(|c| c.is_alphanumeric())
+}
+
+/// Quote a term as an FTS5 string, so operators and punctuation are literal.
+fn quote(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    async fn engine_with(dir: &tempfile::TempDir, bodies: &[&str]) -> IngestionEngine {
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        for (n, body) in bodies.iter().enumerate() {
+            let doc = Document {
+                id: None,
+                uri: format!("mock://{}", n),
+                source: "mock".to_string(),
+                title: None,
+                body: body.to_string(),
+                updated_at: Utc::now(),
+            };
+            ingest.upsert_document(&doc).await.unwrap();
+        }
+        ingest
+    }
+
+    async fn hits(ingest: &IngestionEngine, query: &str) -> Vec<String> {
+        let hits = ingest.search_keyword(query, 10).await.unwrap();
+        hits.into_iter().map(|hit| hit.document.uri).collect()
+    }
+
+    #[test]
+    fn test_parse_advanced_syntax() {
+        let query = SearchQuery::parse(
+            r#"deploy "release  train" -staging source:Slack,github -source:jira tag:Minna lang:deu"#,
+        );
+        assert_eq!(query.terms, vec!["deploy"]);
+        assert_eq!(query.phrases, vec!["release train"]);
+        assert_eq!(query.excluded, vec!["staging"]);
+        assert_eq!(query.sources, vec!["slack", "github"]);
+        assert_eq!(query.excluded_sources, vec!["jira"]);
+        assert_eq!(query.tags, vec!["minna"]);
+        assert_eq!(query.languages, vec!["deu"]);
+        assert_eq!(query.text(), "deploy release train");
+    }
+
+    #[test]
+    fn test_parse_edge_cases() {
+        // A quoted single word is a term; an unterminated quote runs to the end
+        let query = SearchQuery::parse(r#""rollback" -"load test" "half open"#);
+        assert_eq!(query.terms, vec!["rollback"]);
+        assert_eq!(query.excluded, vec!["load test"]);
+        assert_eq!(query.phrases, vec!["half open"]);
+        // Bare punctuation and empty quotes are dropped
+        assert_eq!(SearchQuery::parse(r#"- "" ** ()"#), SearchQuery::default());
+    }
+
+    #[test]
+    fn test_match_expr_quotes_operators() {
+        let query = SearchQuery::parse(r#"NEAR(a b) OR c++ -"it's" say "hi "#);
+        assert_eq!(
+            query.match_expr().as_deref(),
+            Some(r#"("NEAR(a" "b)" "OR" "c++" "say" "hi") NOT "it's""#)
+        );
+        assert_eq!(SearchQuery::parse(r#"x"y"#).match_expr().as_deref(), Some(r#""x""y""#));
+        // Only exclusions: nothing FTS5 can evaluate
+        assert_eq!(SearchQuery::parse("-staging source:slack").match_expr(), None);
+    }
+
+    #[test]
+    fn test_admits_applies_filters() {
+        let doc = Document {
+            id: Some(1),
+            uri: "slack://C1/1".to_string(),
+            source: "Slack".to_string(),
+            title: Some("Release notes".to_string()),
+            body: "Deploy went out to Staging".to_string(),
+            updated_at: Utc::now(),
+        };
+        assert!(SearchQuery::parse("deploy source:slack").admits(&doc));
+        assert!(!SearchQuery::parse("deploy source:github").admits(&doc));
+        assert!(!SearchQuery::parse("deploy -source:slack").admits(&doc));
+        assert!(!SearchQuery::parse("deploy -staging").admits(&doc));
+        // Exclusions match whole words, in the title too
+        assert!(SearchQuery::parse("deploy -stag").admits(&doc));
+        assert!(!SearchQuery::parse("deploy -release").admits(&doc));
+        assert!(!SearchQuery::parse(r#"deploy -"went out""#).admits(&doc));
+        assert!(SearchQuery::parse(r#"deploy -"out went""#).admits(&doc));
+
+        let mut query = SearchQuery::parse("deploy");
+        query.uri_prefix = Some("slack://C2/".to_string());
+        assert!(!query.admits(&doc));
+        query.uri_prefix = None;
+        query.updated_after = Some(Utc::now() + chrono::Duration::hours(1));
+        assert!(!query.admits(&doc));
+    }
+
+    #[tokio::test]
+    async fn test_punctuation_queries_do_not_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = engine_with(&dir, &["what's the c++ build status?", "staging deploy notes"]).await;
+        for query in [r#"what's"#, "c++ AND", "NEAR(", r#"""#, "-", "*", "status?)", "build OR NOT"] {
+            ingest.search_keyword(query, 10).await.unwrap();
+        }
+        assert_eq!(hits(&ingest, "c++ status?").await, vec!["mock://0"]);
+        assert_eq!(hits(&ingest, "deploy -staging").await, Vec::<String>::new());
+        assert_eq!(hits(&ingest, r#""deploy notes""#).await, vec!["mock://1"]);
+        assert!(hits(&ingest, r#""notes deploy""#).await.is_empty());
+    }
+
+    #[test]
+    fn test_tokenizer_parse() {
+        assert_eq!(FtsTokenizer::parse(" Porter "), Some(FtsTokenizer::Porter));
+        assert_eq!(FtsTokenizer::parse("stemming"), Some(FtsTokenizer::Porter));
+        assert_eq!(FtsTokenizer::parse("default"), Some(FtsTokenizer::Unicode61));
+        assert_eq!(FtsTokenizer::parse("trigram"), Some(FtsTokenizer::Trigram));
+        assert_eq!(FtsTokenizer::parse("icu"), None);
+    }
+
+    #[tokio::test]
+    async fn test_tokenizer_switch_rebuilds_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = engine_with(&dir, &["Syncing the café calendar", "deploy checklist for kubernetes"]).await;
+        assert_eq!(ingest.fts_tokenizer().await.unwrap().as_deref(), Some(FtsTokenizer::Unicode61.spec()));
+        // Diacritics fold by default, but there's no stemming
+        assert_eq!(hits(&ingest, "cafe").await, vec!["mock://0"]);
+        assert!(hits(&ingest, "synced").await.is_empty());
+
+        assert!(ingest.configure_fts(FtsTokenizer::Porter).await.unwrap());
+        assert!(!ingest.configure_fts(FtsTokenizer::Porter).await.unwrap());
+        assert_eq!(hits(&ingest, "synced").await, vec!["mock://0"]);
+
+        assert!(ingest.configure_fts(FtsTokenizer::Trigram).await.unwrap());
+        assert_eq!(hits(&ingest, "bernet").await, vec!["mock://1"]);
+    }
+
+    #[tokio::test]
+    async fn test_existing_index_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = engine_with(&dir, &["syncing calendars"]).await;
+        ingest.configure_fts(FtsTokenizer::Porter).await.unwrap();
+        drop(ingest);
+
+        // Reopening doesn't reset the tokenizer back to the default
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        assert_eq!(ingest.fts_tokenizer().await.unwrap().as_deref(), Some(FtsTokenizer::Porter.spec()));
+        assert_eq!(hits(&ingest, "synced").await, vec!["mock://0"]);
+    }
+}
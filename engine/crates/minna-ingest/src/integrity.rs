@@ -0,0 +1,282 @@
+//! Append-only hash chain over document mutations.
+//!
+//! When enabled, every document upsert or delete appends an entry of
+//! `(doc_id, op, content_hash, recorded_at)` linked to the previous entry's
+//! hash. `verify` walks the chain and compares it against the live
+//! `documents` table, so edits made outside Minna (or a rewritten chain)
+//! show up as integrity failures.
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::Document;
+
+/// `prev_hash` of the first entry in the chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+pub(crate) async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS integrity_chain (\
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,\
+            doc_id INTEGER NOT NULL,\
+            op TEXT NOT NULL,\
+            content_hash TEXT NOT NULL,\
+            recorded_at TEXT NOT NULL,\
+            prev_hash TEXT NOT NULL,\
+            entry_hash TEXT NOT NULL\
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_integrity_chain_doc ON integrity_chain(doc_id, seq)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Hash of a document's stored fields.
+pub fn content_hash(doc: &Document) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(doc.uri.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.source.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.title.as_deref().unwrap_or("").as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.body.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.updated_at.to_rfc3339().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn entry_hash(prev_hash: &str, doc_id: i64, op: &str, content_hash: &str, recorded_at: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(doc_id.to_le_bytes());
+    hasher.update(op.as_bytes());
+    hasher.update([0]);
+    hasher.update(content_hash.as_bytes());
+    hasher.update([0]);
+    hasher.update(recorded_at.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Append a chain entry. Must run inside an immediate transaction on `conn`
+/// so concurrent writers can't fork the chain.
+///
+/// Upserts that don't change the document's content are skipped.
+pub(crate) async fn append(conn: &mut SqliteConnection, doc_id: i64, op: &str, content_hash: &str) -> Result<()> {
+    let last_for_doc: Option<(String, String)> = sqlx::query_as(
+        "SELECT op, content_hash FROM integrity_chain WHERE doc_id = ?1 ORDER BY seq DESC LIMIT 1",
+    )
+    .bind(doc_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some((last_op, last_hash)) = last_for_doc {
+        if last_op == op && last_hash == content_hash {
+            return Ok(());
+        }
+    }
+
+    let prev_hash: Option<String> =
+        sqlx::query_scalar("SELECT entry_hash FROM integrity_chain ORDER BY seq DESC LIMIT 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let prev_hash = prev_hash.unwrap_or_else(|| GENESIS_HASH.to_string());
+    let recorded_at = Utc::now().to_rfc3339();
+    let hash = entry_hash(&prev_hash, doc_id, op, content_hash, &recorded_at);
+
+    sqlx::query(
+        "INSERT INTO integrity_chain (doc_id, op, content_hash, recorded_at, prev_hash, entry_hash) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )
+    .bind(doc_id)
+    .bind(op)
+    .bind(content_hash)
+    .bind(&recorded_at)
+    .bind(&prev_hash)
+    .bind(&hash)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Result of verifying the chain against the documents table.
+#[derive(Debug, Default, Serialize)]
+pub struct VerifyReport {
+    /// Number of chain entries checked.
+    pub entries: usize,
+    /// First sequence number whose hash or link doesn't verify.
+    pub broken_at: Option<i64>,
+    /// Documents whose content differs from their last chain entry.
+    pub modified: Vec<i64>,
+    /// Documents the chain says exist but are gone from the table.
+    pub missing: Vec<i64>,
+    /// Documents with no chain entry (indexed before the chain was enabled).
+    pub untracked: usize,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.broken_at.is_none() && self.modified.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Verify chain linkage and compare the latest entry per document with the
+/// live table.
+pub async fn verify(pool: &SqlitePool) -> Result<VerifyReport> {
+    let rows = sqlx::query_as::<_, (i64, i64, String, String, String, String, String)>(
+        "SELECT seq, doc_id, op, content_hash, recorded_at, prev_hash, entry_hash \
+        FROM integrity_chain ORDER BY seq",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut report = VerifyReport {
+        entries: rows.len(),
+        ..Default::default()
+    };
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut latest: std::collections::HashMap<i64, (String, String)> = std::collections::HashMap::new();
+    for (seq, doc_id, op, content_hash, recorded_at, prev_hash, hash) in rows {
+        let recomputed = entry_hash(&prev_hash, doc_id, &op, &content_hash, &recorded_at);
+        if prev_hash != expected_prev || recomputed != hash {
+            report.broken_at = Some(seq);
+            return Ok(report);
+        }
+        expected_prev = hash;
+        latest.insert(doc_id, (op, content_hash));
+    }
+
+    let docs = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
+        "SELECT id, uri, source, title, body, updated_at FROM documents WHERE deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (id, uri, source, title, body, updated_at) in docs {
+        let doc = Document {
+            id: Some(id),
+            uri,
+            source,
+            title,
+            body,
+            updated_at: chrono::DateTime::parse_from_rfc3339(&updated_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        };
+        match latest.remove(&id) {
+            Some((op, hash)) if op == "upsert" && hash == content_hash(&doc) => {}
+            Some(_) => report.modified.push(id),
+            None => report.untracked += 1,
+        }
+    }
+
+    // Whatever is left was last recorded as present but no longer exists
+    report.missing = latest
+        .into_iter()
+        .filter(|(_, (op, _))| op == "upsert")
+        .map(|(id, _)| id)
+        .collect();
+    report.missing.sort_unstable();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IngestionEngine;
+
+    fn doc(uri: &str, body: &str) -> Document {
+        Document {
+            id: None,
+            uri: uri.to_string(),
+            source: "mock".to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    async fn chained_engine(dir: &tempfile::TempDir) -> IngestionEngine {
+        IngestionEngine::new(&dir.path().join("minna.db"))
+            .await
+            .unwrap()
+            .with_integrity_chain(true)
+    }
+
+    #[tokio::test]
+    async fn test_untouched_chain_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = chained_engine(&dir).await;
+        let first = doc("mock://1", "first");
+        ingest.upsert_document(&first).await.unwrap();
+        // Re-upserting identical content doesn't grow the chain
+        ingest.upsert_document(&first).await.unwrap();
+        let second = Document {
+            source: "other".to_string(),
+            ..doc("other://2", "second")
+        };
+        ingest.upsert_document(&second).await.unwrap();
+        ingest.delete_documents_by_source("other").await.unwrap();
+
+        let report = verify(ingest.pool()).await.unwrap();
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.entries, 3);
+        assert_eq!(report.untracked, 0);
+    }
+
+    #[tokio::test]
+    async fn test_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = chained_engine(&dir).await;
+        let edited = ingest.upsert_document(&doc("mock://1", "original")).await.unwrap();
+        let removed = ingest.upsert_document(&doc("mock://2", "second")).await.unwrap();
+        ingest.upsert_document(&doc("mock://3", "third")).await.unwrap();
+
+        // Edits made behind Minna's back
+        sqlx::query("UPDATE documents SET body = 'rewritten' WHERE id = ?1")
+            .bind(edited)
+            .execute(ingest.pool())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM documents WHERE id = ?1")
+            .bind(removed)
+            .execute(ingest.pool())
+            .await
+            .unwrap();
+        let report = verify(ingest.pool()).await.unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.broken_at, None);
+        assert_eq!(report.modified, vec![edited]);
+        assert_eq!(report.missing, vec![removed]);
+
+        // Rewriting a chain entry to match breaks the link from that point on
+        sqlx::query("UPDATE integrity_chain SET content_hash = 'forged' WHERE seq = 2")
+            .execute(ingest.pool())
+            .await
+            .unwrap();
+        assert_eq!(verify(ingest.pool()).await.unwrap().broken_at, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_documents_before_chain_are_untracked() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        ingest.upsert_document(&doc("mock://1", "before")).await.unwrap();
+        let ingest = ingest.with_integrity_chain(true);
+        ingest.upsert_document(&doc("mock://2", "after")).await.unwrap();
+
+        let report = verify(ingest.pool()).await.unwrap();
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!((report.entries, report.untracked), (1, 1));
+    }
+}
@@ -0,0 +1,88 @@
+//! Document language detection.
+//!
+//! Each document's language is detected when it is stored and kept in the
+//! `language` column as an ISO 639-3 code (`eng`, `deu`, `jpn`, ...), the
+//! same codes `MINNA_OCR_LANGS` uses. Text too short or too mixed to call
+//! reliably is left without a language.
+
+use whatlang::Lang;
+
+/// Detection looks at the start of long documents only.
+const SAMPLE_BYTES: usize = 4096;
+
+/// ISO 639-3 code of the language `text` is written in, if it can be told.
+pub fn detect(text: &str) -> Option<String> {
+    let mut end = text.len().min(SAMPLE_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let info = whatlang::detect(&text[..end])?;
+    info.is_reliable().then(|| info.lang().code().to_string())
+}
+
+/// Normalize a user-supplied language: an ISO 639-3 code or an English
+/// name (`German`), case-insensitive. None if it isn't a known language.
+pub fn normalize(input: &str) -> Option<String> {
+    let input = input.trim().to_lowercase();
+    if let Some(lang) = Lang::from_code(input.as_str()) {
+        return Some(lang.code().to_string());
+    }
+    Lang::all()
+        .iter()
+        .find(|lang| lang.eng_name().eq_ignore_ascii_case(&input))
+        .map(|lang| lang.code().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    const ENGLISH: &str = "The deployment was rolled back after the error rate doubled during the evening traffic peak.";
+    const GERMAN: &str = "Die Bereitstellung wurde zurückgesetzt, nachdem sich die Fehlerrate am Abend verdoppelt hatte.";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(ENGLISH).as_deref(), Some("eng"));
+        assert_eq!(detect(GERMAN).as_deref(), Some("deu"));
+        assert_eq!(detect("ok"), None);
+        // The sample cut lands inside a multi-byte character
+        let long = format!("a{}", "Überprüfung der Fehlerrate am Abend. ".repeat(200));
+        assert_eq!(detect(&long).as_deref(), Some("deu"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" DEU ").as_deref(), Some("deu"));
+        assert_eq!(normalize("german").as_deref(), Some("deu"));
+        assert_eq!(normalize("Japanese").as_deref(), Some("jpn"));
+        assert_eq!(normalize("klingon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_languages_are_stored_and_filterable() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let mut ids = Vec::new();
+        for (n, body) in [ENGLISH, GERMAN, "ok"].iter().enumerate() {
+            let doc = Document {
+                id: None,
+                uri: format!("mock://{}", n),
+                source: "mock".to_string(),
+                title: None,
+                body: body.to_string(),
+                updated_at: chrono::Utc::now(),
+            };
+            ids.push(ingest.upsert_document(&doc).await.unwrap());
+        }
+
+        let languages = ingest.document_languages(&ids).await.unwrap();
+        assert_eq!(languages.len(), 2);
+        assert_eq!((languages[&ids[0]].as_str(), languages[&ids[1]].as_str()), ("eng", "deu"));
+        assert_eq!(ingest.doc_ids_with_languages(&["deu".to_string()]).await.unwrap(), vec![ids[1]]);
+
+        let hits = ingest.search_keyword("Fehlerrate lang:German", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(ingest.search_keyword("Fehlerrate lang:eng", 10).await.unwrap().is_empty());
+    }
+}
@@ -1,11 +1,37 @@
+pub mod attachments;
+pub mod dedup;
+pub mod encryption;
+pub mod extractors;
+pub mod fts;
+pub mod integrity;
+pub mod language;
+pub mod maintenance;
+pub mod ocr;
+pub mod redact;
+pub mod references;
+pub mod short_id;
+pub mod store;
+pub mod transform;
+#[cfg(feature = "transcription")]
+pub mod transcribe;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions, SqlitePool};
+use sqlx::{sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions, SqliteConnection, SqlitePool};
 use std::str::FromStr;
-use tracing::instrument;
+use std::sync::Arc;
+use tracing::{debug, info, instrument};
+
+pub use attachments::Attachment;
+pub use redact::{RedactionConfig, Redactor};
+pub use references::DocumentReference;
+pub use store::DocumentStore;
+pub use transform::{Transform, TransformChain, TransformConfig};
 
 // Re-export graph types for convenience
 pub use minna_graph::{GraphStore, ExtractedEdge, NodeRef, Relation, NodeType, Ring};
@@ -20,36 +46,201 @@ pub struct Document {
     pub updated_at: DateTime<Utc>,
 }
 
+impl Document {
+    /// Short human-friendly ID (e.g. `sl-4f2a`), once the document is stored.
+    pub fn short_id(&self) -> Option<String> {
+        self.id.map(|id| short_id::encode(&self.source, id))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClusterRecord {
     pub id: Option<i64>,
     pub label: String,
     pub doc_ids: Vec<i64>,
     pub created_at: DateTime<Utc>,
+    /// What the documents have in common, written by an LLM if one is
+    /// configured: a short title, then a sentence.
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// A keyword search result with the matching passage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordHit {
+    pub document: Document,
+    /// Excerpt of the body around the matched terms, with matches wrapped
+    /// in `**` (from FTS5 `snippet()`).
+    pub snippet: String,
+}
+
+/// A document as written by [`IngestionEngine::export_all`], one per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedDocument {
+    /// ID in the exporting database, referenced by exported embeddings and
+    /// edge sources.
+    pub id: i64,
+    pub uri: String,
+    pub source: String,
+    pub title: Option<String>,
+    pub body: String,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A soft-deleted document awaiting cleanup of its embeddings, graph edges
+/// and cluster references.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub doc_id: i64,
+    pub uri: String,
+    pub source: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// A channel, repo, or space detected as archived upstream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedResource {
+    pub provider: String,
+    pub resource_id: String,
+    pub name: Option<String>,
+    /// Documents whose URI starts with this prefix belong to the resource.
+    pub uri_prefix: String,
+    pub detected_at: DateTime<Utc>,
 }
 
 #[derive(Clone)]
 pub struct IngestionEngine {
     pool: SqlitePool,
+    /// Record document mutations in the integrity hash chain.
+    integrity_chain: bool,
+    /// Applied to every document before it is stored (see [`redact`]).
+    redactor: Option<Arc<Redactor>>,
+    /// Per-source clean-up, applied before redaction (see [`transform`]).
+    transforms: Option<Arc<TransformChain>>,
 }
 
 impl IngestionEngine {
     pub async fn new(db_path: &Path) -> Result<Self> {
+        Self::open(db_path, None).await
+    }
+
+    /// Open the database, encrypted with `key` if given (see [`encryption`]).
+    pub async fn open(db_path: &Path, key: Option<&str>) -> Result<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let options = SqliteConnectOptions::from_str("sqlite:")?
+        if let Some(key) = key {
+            if encryption::is_plaintext(db_path)? {
+                encryption::encrypt_in_place(db_path, key).await?;
+            }
+        }
+        let mut options = SqliteConnectOptions::from_str("sqlite:")?
             .filename(db_path)
             .create_if_missing(true);
+        if let Some(key) = key {
+            options = encryption::apply_key(options, key)?;
+        }
         let pool = SqlitePoolOptions::new()
             .max_connections(4)
             .connect_with(options)
             .await?;
-        let engine = Self { pool };
+        if key.is_some() {
+            encryption::ensure_sqlcipher(&pool).await?;
+        }
+        let engine = Self {
+            pool,
+            integrity_chain: false,
+            redactor: None,
+            transforms: None,
+        };
         engine.init_schema().await?;
         Ok(engine)
     }
 
+    /// Enable or disable the integrity hash chain (see [`integrity`]).
+    pub fn with_integrity_chain(mut self, enabled: bool) -> Self {
+        self.integrity_chain = enabled;
+        self
+    }
+
+    pub fn integrity_chain_enabled(&self) -> bool {
+        self.integrity_chain
+    }
+
+    /// Redact documents with `redactor` before storing them.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Clean documents with per-source `transforms` before storing them.
+    pub fn with_transforms(mut self, transforms: TransformChain) -> Self {
+        self.transforms = Some(Arc::new(transforms));
+        self
+    }
+
+    /// The document as it will be stored: run through its source's
+    /// transforms (see [`transform`]), then redacted (see [`redact`]).
+    pub fn prepare<'d>(&self, doc: &'d Document) -> Cow<'d, Document> {
+        let transformed = self.transforms.as_ref().and_then(|chain| chain.apply(doc));
+        let doc = match transformed {
+            Some(body) => {
+                debug!("[TRANSFORM] Cleaned {}", doc.uri);
+                Cow::Owned(Document {
+                    id: doc.id,
+                    uri: doc.uri.clone(),
+                    source: doc.source.clone(),
+                    title: doc.title.clone(),
+                    body,
+                    updated_at: doc.updated_at,
+                })
+            }
+            None => Cow::Borrowed(doc),
+        };
+        let Some(redactor) = &self.redactor else {
+            return doc;
+        };
+        let redacted = match redactor.redact_document(&doc) {
+            Cow::Owned(redacted) => redacted,
+            Cow::Borrowed(_) => return doc,
+        };
+        debug!("[REDACT] Redacted {}", redacted.uri);
+        Cow::Owned(redacted)
+    }
+
+    /// Verify the integrity hash chain against stored documents.
+    pub async fn verify_integrity(&self) -> Result<integrity::VerifyReport> {
+        integrity::verify(&self.pool).await
+    }
+
+    /// Switch the full-text index to `tokenizer`, rebuilding it if the
+    /// existing index was built with a different one. Returns true if rebuilt.
+    pub async fn configure_fts(&self, tokenizer: fts::FtsTokenizer) -> Result<bool> {
+        fts::ensure_tokenizer(&self.pool, tokenizer).await
+    }
+
+    /// Tokenizer spec the full-text index was built with.
+    pub async fn fts_tokenizer(&self) -> Result<Option<String>> {
+        fts::current_spec(&self.pool).await
+    }
+
+    /// Rebuild the full-text index from stored documents.
+    pub async fn rebuild_fts(&self) -> Result<()> {
+        fts::rebuild(&self.pool).await
+    }
+
+    /// Check integrity, rebuild the full-text index and vacuum, as selected
+    /// (see [`maintenance`]). Covers the whole database file, including the
+    /// vector and graph tables.
+    pub async fn maintenance(
+        &self,
+        options: &maintenance::MaintenanceOptions,
+    ) -> Result<maintenance::MaintenanceReport> {
+        maintenance::run(&self.pool, options).await
+    }
+
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -71,22 +262,83 @@ impl IngestionEngine {
                 source TEXT NOT NULL,\
                 title TEXT,\
                 body TEXT NOT NULL,\
-                updated_at TEXT NOT NULL\
+                updated_at TEXT NOT NULL,\
+                deleted_at TEXT,\
+                pinned INTEGER NOT NULL DEFAULT 0\
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Databases created before soft deletion lack the column
+        let has_deleted_at: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'deleted_at'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if !has_deleted_at {
+            sqlx::query("ALTER TABLE documents ADD COLUMN deleted_at TEXT")
+                .execute(&self.pool)
+                .await?;
+        }
+        let has_pinned: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'pinned'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if !has_pinned {
+            sqlx::query("ALTER TABLE documents ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0")
+                .execute(&self.pool)
+                .await?;
+        }
+
+        // ISO 639-3 code (see [`language`]); older databases get it backfilled
+        let has_language: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'language'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if !has_language {
+            sqlx::query("ALTER TABLE documents ADD COLUMN language TEXT")
+                .execute(&self.pool)
+                .await?;
+            self.detect_languages().await?;
+        }
+
+        // Timeline queries ("everything from Slack this week")
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_documents_source_updated_at ON documents(source, updated_at)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS document_tombstones (\
+                doc_id INTEGER PRIMARY KEY,\
+                uri TEXT NOT NULL,\
+                source TEXT NOT NULL,\
+                deleted_at TEXT NOT NULL\
             )",
         )
         .execute(&self.pool)
         .await?;
 
         sqlx::query(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(\
-                uri, title, body,\
-                content='documents',\
-                content_rowid='id'\
+            "CREATE TABLE IF NOT EXISTS document_tags (\
+                doc_id INTEGER NOT NULL,\
+                tag TEXT NOT NULL,\
+                PRIMARY KEY (doc_id, tag)\
             )",
         )
         .execute(&self.pool)
         .await?;
 
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_document_tags_tag ON document_tags(tag)")
+            .execute(&self.pool)
+            .await?;
+
+        fts::create_table(&self.pool, fts::FtsTokenizer::default()).await?;
+
         sqlx::query(
             "CREATE TRIGGER IF NOT EXISTS documents_ai AFTER INSERT ON documents BEGIN\n\
                 INSERT INTO documents_fts(rowid, uri, title, body) VALUES (new.id, new.uri, new.title, new.body);\n\
@@ -117,12 +369,25 @@ impl IngestionEngine {
                 id INTEGER PRIMARY KEY AUTOINCREMENT,\
                 label TEXT NOT NULL,\
                 doc_ids TEXT NOT NULL,\
-                created_at TEXT NOT NULL\
+                created_at TEXT NOT NULL,\
+                summary TEXT\
             )",
         )
         .execute(&self.pool)
         .await?;
 
+        // Databases created before cluster summaries lack the column
+        let has_summary: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('clusters') WHERE name = 'summary'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if !has_summary {
+            sqlx::query("ALTER TABLE clusters ADD COLUMN summary TEXT")
+                .execute(&self.pool)
+                .await?;
+        }
+
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS sync_state (\
                 provider TEXT PRIMARY KEY,\
@@ -133,22 +398,101 @@ impl IngestionEngine {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS archived_resources (\
+                provider TEXT NOT NULL,\
+                resource_id TEXT NOT NULL,\
+                name TEXT,\
+                uri_prefix TEXT NOT NULL,\
+                detected_at TEXT NOT NULL,\
+                PRIMARY KEY (provider, resource_id)\
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        integrity::init_schema(&self.pool).await?;
+        attachments::init_schema(&self.pool).await?;
+        references::init_schema(&self.pool).await?;
+        dedup::init_schema(&self.pool).await?;
+
         // Initialize graph schema (Gravity Well)
         GraphStore::init_schema(&self.pool).await?;
 
         Ok(())
     }
 
-    #[instrument(skip(self))]
+    // The body stays out of the span: it isn't redacted yet
+    #[instrument(skip(self, doc), fields(uri = %doc.uri))]
     pub async fn upsert_document(&self, doc: &Document) -> Result<i64> {
+        let doc = self.prepare(doc);
+        let doc = doc.as_ref();
+        if !self.integrity_chain {
+            let mut conn = self.pool.acquire().await?;
+            return Self::upsert_on(&mut conn, doc).await;
+        }
+
+        // Immediate transaction so the upsert and its chain entry land together
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = match Self::upsert_on(&mut conn, doc).await {
+            Ok(id) => integrity::append(&mut conn, id, "upsert", &integrity::content_hash(doc))
+                .await
+                .map(|_| id),
+            Err(err) => Err(err),
+        };
+        Self::finish_tx(&mut conn, result).await
+    }
+
+    /// [`Self::upsert_document`] on `conn`, inside the caller's transaction.
+    pub async fn upsert_document_on(&self, conn: &mut SqliteConnection, doc: &Document) -> Result<i64> {
+        let doc = self.prepare(doc);
+        let id = Self::upsert_on(conn, doc.as_ref()).await?;
+        if self.integrity_chain {
+            integrity::append(conn, id, "upsert", &integrity::content_hash(doc.as_ref())).await?;
+        }
+        Ok(id)
+    }
+
+    /// Upsert many documents in one transaction, returning their IDs in
+    /// order. Much faster than [`Self::upsert_document`] per document on
+    /// large syncs: there's a single commit, and FTS5 buffers the index
+    /// updates until it. Either all documents are stored or none are.
+    #[instrument(skip_all, fields(count = docs.len()))]
+    pub async fn upsert_documents_batch(&self, docs: &[Document]) -> Result<Vec<i64>> {
+        if docs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let docs: Vec<Cow<'_, Document>> = docs.iter().map(|doc| self.prepare(doc)).collect();
+
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = async {
+            let mut ids = Vec::with_capacity(docs.len());
+            for doc in &docs {
+                let id = Self::upsert_on(&mut conn, doc).await?;
+                if self.integrity_chain {
+                    integrity::append(&mut conn, id, "upsert", &integrity::content_hash(doc)).await?;
+                }
+                ids.push(id);
+            }
+            Ok(ids)
+        }
+        .await;
+        Self::finish_tx(&mut conn, result).await
+    }
+
+    async fn upsert_on(conn: &mut sqlx::SqliteConnection, doc: &Document) -> Result<i64> {
         let id: i64 = sqlx::query_scalar(
-            "INSERT INTO documents (uri, source, title, body, updated_at) \
-            VALUES (?1, ?2, ?3, ?4, ?5) \
+            "INSERT INTO documents (uri, source, title, body, updated_at, language) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
             ON CONFLICT(uri) DO UPDATE SET \
                 source=excluded.source, \
                 title=excluded.title, \
                 body=excluded.body, \
-                updated_at=excluded.updated_at \
+                updated_at=excluded.updated_at, \
+                language=excluded.language, \
+                deleted_at=NULL \
             RETURNING id",
         )
         .bind(&doc.uri)
@@ -156,14 +500,40 @@ impl IngestionEngine {
         .bind(&doc.title)
         .bind(&doc.body)
         .bind(doc.updated_at.to_rfc3339())
-        .fetch_one(&self.pool)
+        .bind(language::detect(&doc.body))
+        .fetch_one(&mut *conn)
         .await?;
+
+        // A re-synced document is no longer pending cleanup
+        sqlx::query("DELETE FROM document_tombstones WHERE doc_id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        references::replace_links(conn, id, &doc.body).await?;
+        if let Some(canonical) = dedup::index(conn, id, &doc.body).await? {
+            debug!("[DEDUP] {} is a near-duplicate of document {}", doc.uri, canonical);
+        }
         Ok(id)
     }
 
+    /// Commit on success, roll back on error.
+    async fn finish_tx<T>(conn: &mut sqlx::SqliteConnection, result: Result<T>) -> Result<T> {
+        match result {
+            Ok(value) => {
+                sqlx::query("COMMIT").execute(&mut *conn).await?;
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+                Err(err)
+            }
+        }
+    }
+
     pub async fn get_document_by_uri(&self, uri: &str) -> Result<Option<Document>> {
         let row = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
-            "SELECT id, uri, source, title, body, updated_at FROM documents WHERE uri = ?1",
+            "SELECT id, uri, source, title, body, updated_at FROM documents \
+            WHERE uri = ?1 AND deleted_at IS NULL",
         )
         .bind(uri)
         .fetch_optional(&self.pool)
@@ -181,13 +551,139 @@ impl IngestionEngine {
         }))
     }
 
+    /// Look up a document by short ID (see [`short_id`]).
+    pub async fn get_document_by_short_id(&self, short: &str) -> Result<Option<Document>> {
+        let Some((prefix, id)) = short_id::decode(short) else {
+            return Ok(None);
+        };
+        let doc = self.fetch_documents_by_ids(&[id]).await?.into_iter().next();
+        Ok(doc.filter(|doc| short_id::source_prefix(&doc.source) == prefix))
+    }
+
+    /// Attach a file to a document (see [`attachments`]). Text attachments
+    /// are redacted like document bodies.
+    pub async fn store_attachment(
+        &self,
+        doc_id: i64,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<Attachment> {
+        let redacted = match (&self.redactor, std::str::from_utf8(data)) {
+            (Some(redactor), Ok(text)) if mime_type.starts_with("text/") => {
+                match redactor.redact(text) {
+                    Cow::Owned(text) => Some(text.into_bytes()),
+                    Cow::Borrowed(_) => None,
+                }
+            }
+            _ => None,
+        };
+        let data = redacted.as_deref().unwrap_or(data);
+        attachments::store(&self.pool, doc_id, name, mime_type, data).await
+    }
+
+    /// An attachment and its contents by ID, unless its document is deleted.
+    pub async fn get_attachment(&self, id: i64) -> Result<Option<(Attachment, Vec<u8>)>> {
+        attachments::get(&self.pool, id).await
+    }
+
+    pub async fn list_attachments(&self, doc_id: i64) -> Result<Vec<Attachment>> {
+        attachments::list(&self.pool, doc_id).await
+    }
+
+    /// Links between `doc_id` and other live documents, in both directions
+    /// (see [`references`]).
+    pub async fn document_references(&self, doc_id: i64) -> Result<Vec<DocumentReference>> {
+        references::for_document(&self.pool, doc_id).await
+    }
+
+    /// Canonical document of each of `ids` that is a near-duplicate of a live
+    /// document (see [`dedup`]).
+    pub async fn canonical_ids(&self, ids: &[i64]) -> Result<HashMap<i64, i64>> {
+        dedup::canonical_ids(&self.pool, ids).await
+    }
+
+    /// URIs of the live near-duplicates recorded against each of
+    /// `canonical_ids`.
+    pub async fn document_alternates(&self, canonical_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+        dedup::alternates(&self.pool, canonical_ids).await
+    }
+
+    /// Look up a document by URI or short ID.
+    pub async fn resolve_document(&self, reference: &str) -> Result<Option<Document>> {
+        if let Some(doc) = self.get_document_by_uri(reference).await? {
+            return Ok(Some(doc));
+        }
+        self.get_document_by_short_id(reference).await
+    }
+
+    /// Pin (or unpin) a document with `minna pin`. Pinned documents are
+    /// never evicted.
+    pub async fn set_pinned(&self, doc_id: i64, pinned: bool) -> Result<()> {
+        sqlx::query("UPDATE documents SET pinned = ?2 WHERE id = ?1")
+            .bind(doc_id)
+            .bind(pinned)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Pinned documents, most recently updated first.
+    pub async fn list_pinned(&self, limit: usize) -> Result<Vec<Document>> {
+        let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
+            "SELECT id, uri, source, title, body, updated_at FROM documents \
+            WHERE pinned = 1 AND deleted_at IS NULL ORDER BY updated_at DESC, id DESC LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, uri, source, title, body, updated_at)| Document {
+                id: Some(id),
+                uri,
+                source,
+                title,
+                body,
+                updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
+    /// Most recently updated documents.
+    pub async fn recent_documents(&self, limit: usize) -> Result<Vec<Document>> {
+        let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
+            "SELECT id, uri, source, title, body, updated_at FROM documents \
+            WHERE deleted_at IS NULL ORDER BY updated_at DESC, id DESC LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, uri, source, title, body, updated_at)| Document {
+                id: Some(id),
+                uri,
+                source,
+                title,
+                body,
+                updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
     pub async fn fetch_documents_by_ids(&self, ids: &[i64]) -> Result<Vec<Document>> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
         let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let query = format!(
-            "SELECT id, uri, source, title, body, updated_at FROM documents WHERE id IN ({})",
+            "SELECT id, uri, source, title, body, updated_at FROM documents \
+            WHERE id IN ({}) AND deleted_at IS NULL",
             placeholders
         );
         let mut q = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(&query);
@@ -210,15 +706,409 @@ impl IngestionEngine {
             .collect())
     }
 
-    pub async fn search_keyword(&self, query: &str, limit: usize) -> Result<Vec<Document>> {
-        let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
-            "SELECT d.id, d.uri, d.source, d.title, d.body, d.updated_at\
-            FROM documents_fts f JOIN documents d ON d.id = f.rowid\
-            WHERE documents_fts MATCH ?1\
-            ORDER BY bm25(documents_fts)\
+    /// Write every live document, with its tags, to `out` as JSON lines.
+    /// Returns the number of documents written.
+    pub async fn export_all<W: std::io::Write>(&self, out: &mut W) -> Result<usize> {
+        const PAGE: i64 = 500;
+        let mut after = 0;
+        let mut written = 0;
+        loop {
+            let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String, Option<String>)>(
+                "SELECT d.id, d.uri, d.source, d.title, d.body, d.updated_at, \
+                (SELECT json_group_array(tag) FROM document_tags t WHERE t.doc_id = d.id) \
+                FROM documents d WHERE d.id > ?1 AND d.deleted_at IS NULL \
+                ORDER BY d.id LIMIT ?2",
+            )
+            .bind(after)
+            .bind(PAGE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some((last, ..)) = rows.last() else {
+                break;
+            };
+            after = *last;
+
+            for (id, uri, source, title, body, updated_at, tags) in rows {
+                let doc = ExportedDocument {
+                    id,
+                    uri,
+                    source,
+                    title,
+                    body,
+                    updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    tags: tags
+                        .and_then(|tags| serde_json::from_str(&tags).ok())
+                        .unwrap_or_default(),
+                };
+                serde_json::to_writer(&mut *out, &doc)?;
+                out.write_all(b"\n")?;
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Keyword search over user input (see [`fts::SearchQuery`] for the syntax).
+    pub async fn search_keyword(&self, query: &str, limit: usize) -> Result<Vec<KeywordHit>> {
+        self.search_query(&fts::SearchQuery::parse(query), limit).await
+    }
+
+    /// Keyword search with an already parsed query.
+    pub async fn search_query(&self, query: &fts::SearchQuery, limit: usize) -> Result<Vec<KeywordHit>> {
+        let Some(expr) = query.match_expr() else {
+            return Ok(Vec::new());
+        };
+        let sources = (!query.sources.is_empty())
+            .then(|| serde_json::to_string(&query.sources))
+            .transpose()?;
+        let excluded_sources = (!query.excluded_sources.is_empty())
+            .then(|| serde_json::to_string(&query.excluded_sources))
+            .transpose()?;
+        let tags = (!query.tags.is_empty())
+            .then(|| serde_json::to_string(&query.tags))
+            .transpose()?;
+        let languages = (!query.languages.is_empty())
+            .then(|| serde_json::to_string(&query.languages))
+            .transpose()?;
+
+        // Column 2 is the body; ~24 tokens of context around the best match
+        let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String, String)>(
+            "SELECT d.id, d.uri, d.source, d.title, d.body, d.updated_at, \
+            snippet(documents_fts, 2, '**', '**', '…', 24) \
+            FROM documents_fts f JOIN documents d ON d.id = f.rowid \
+            WHERE documents_fts MATCH ?1 AND d.deleted_at IS NULL \
+            AND (?3 IS NULL OR d.source IN (SELECT value FROM json_each(?3))) \
+            AND (?4 IS NULL OR d.source NOT IN (SELECT value FROM json_each(?4))) \
+            AND (?5 IS NULL OR d.id IN (SELECT doc_id FROM document_tags \
+                WHERE tag IN (SELECT value FROM json_each(?5)))) \
+            AND (?6 IS NULL OR d.language IN (SELECT value FROM json_each(?6))) \
+            AND (?7 IS NULL OR d.updated_at >= ?7) \
+            AND (?8 IS NULL OR d.updated_at < ?8) \
+            AND (?9 IS NULL OR substr(d.uri, 1, length(?9)) = ?9) \
+            ORDER BY bm25(documents_fts) \
             LIMIT ?2",
         )
-        .bind(query)
+        .bind(expr)
+        .bind(limit as i64)
+        .bind(sources)
+        .bind(excluded_sources)
+        .bind(tags)
+        .bind(languages)
+        .bind(query.updated_after.map(|t| t.to_rfc3339()))
+        .bind(query.updated_before.map(|t| t.to_rfc3339()))
+        .bind(query.uri_prefix.as_deref())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, uri, source, title, body, updated_at, snippet)| KeywordHit {
+                document: Document {
+                    id: Some(id),
+                    uri,
+                    source,
+                    title,
+                    body,
+                    updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                },
+                snippet,
+            })
+            .collect())
+    }
+
+    /// Soft-delete every document from a source.
+    ///
+    /// Documents disappear from reads immediately; each gets a tombstone so
+    /// its embedding, graph edges and cluster references can be cleaned up
+    /// later via [`Self::pending_tombstones`] and [`Self::purge_tombstones`].
+    /// Returns the number of documents deleted.
+    pub async fn delete_documents_by_source(&self, source: &str) -> Result<u64> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = Self::delete_by_source_on(&mut conn, source, self.integrity_chain).await;
+        Self::finish_tx(&mut conn, result).await
+    }
+
+    async fn delete_by_source_on(
+        conn: &mut sqlx::SqliteConnection,
+        source: &str,
+        integrity_chain: bool,
+    ) -> Result<u64> {
+        let deleted_at = Utc::now().to_rfc3339();
+        let ids: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM documents WHERE source = ?1 AND deleted_at IS NULL")
+                .bind(source)
+                .fetch_all(&mut *conn)
+                .await?;
+        sqlx::query(
+            "INSERT INTO document_tombstones (doc_id, uri, source, deleted_at) \
+            SELECT id, uri, source, ?2 FROM documents WHERE source = ?1 AND deleted_at IS NULL \
+            ON CONFLICT(doc_id) DO NOTHING",
+        )
+        .bind(source)
+        .bind(&deleted_at)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("UPDATE documents SET deleted_at = ?2 WHERE source = ?1 AND deleted_at IS NULL")
+            .bind(source)
+            .bind(&deleted_at)
+            .execute(&mut *conn)
+            .await?;
+        if integrity_chain {
+            for id in &ids {
+                integrity::append(conn, *id, "delete", "").await?;
+            }
+        }
+        Ok(ids.len() as u64)
+    }
+
+    /// Soft-delete documents by ID, leaving tombstones like
+    /// [`Self::delete_documents_by_source`]. Returns the number deleted.
+    pub async fn delete_documents(&self, doc_ids: &[i64]) -> Result<u64> {
+        if doc_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = Self::delete_ids_on(&mut conn, doc_ids, self.integrity_chain).await;
+        Self::finish_tx(&mut conn, result).await
+    }
+
+    async fn delete_ids_on(
+        conn: &mut sqlx::SqliteConnection,
+        doc_ids: &[i64],
+        integrity_chain: bool,
+    ) -> Result<u64> {
+        let deleted_at = Utc::now().to_rfc3339();
+        let ids_json = serde_json::to_string(doc_ids)?;
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM documents WHERE id IN (SELECT value FROM json_each(?1)) AND deleted_at IS NULL",
+        )
+        .bind(&ids_json)
+        .fetch_all(&mut *conn)
+        .await?;
+        sqlx::query(
+            "INSERT INTO document_tombstones (doc_id, uri, source, deleted_at) \
+            SELECT id, uri, source, ?2 FROM documents \
+            WHERE id IN (SELECT value FROM json_each(?1)) AND deleted_at IS NULL \
+            ON CONFLICT(doc_id) DO NOTHING",
+        )
+        .bind(&ids_json)
+        .bind(&deleted_at)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            "UPDATE documents SET deleted_at = ?2 \
+            WHERE id IN (SELECT value FROM json_each(?1)) AND deleted_at IS NULL",
+        )
+        .bind(&ids_json)
+        .bind(&deleted_at)
+        .execute(&mut *conn)
+        .await?;
+        if integrity_chain {
+            for id in &ids {
+                integrity::append(conn, *id, "delete", "").await?;
+            }
+        }
+        Ok(ids.len() as u64)
+    }
+
+    /// Evict the oldest-updated documents of `source` until it holds at most
+    /// `max_documents` documents and `max_bytes` of body text. Documents in
+    /// `keep` or pinned are never evicted, though they count toward the caps.
+    /// Returns the IDs evicted (soft-deleted, so cleanup follows the
+    /// tombstones).
+    pub async fn evict_oldest(
+        &self,
+        source: &str,
+        max_documents: Option<u64>,
+        max_bytes: Option<u64>,
+        keep: &HashSet<i64>,
+    ) -> Result<Vec<i64>> {
+        let rows = sqlx::query_as::<_, (i64, i64, bool)>(
+            "SELECT id, LENGTH(CAST(body AS BLOB)), pinned FROM documents \
+            WHERE source = ?1 AND deleted_at IS NULL ORDER BY updated_at, id",
+        )
+        .bind(source)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut count = rows.len() as u64;
+        let mut bytes: u64 = rows.iter().map(|(_, len, _)| *len as u64).sum();
+        let over = |count: u64, bytes: u64| {
+            max_documents.is_some_and(|max| count > max) || max_bytes.is_some_and(|max| bytes > max)
+        };
+        let mut evicted = Vec::new();
+        for (id, len, pinned) in rows {
+            if !over(count, bytes) {
+                break;
+            }
+            if pinned || keep.contains(&id) {
+                continue;
+            }
+            evicted.push(id);
+            count -= 1;
+            bytes -= len as u64;
+        }
+        self.delete_documents(&evicted).await?;
+        Ok(evicted)
+    }
+
+    /// Oldest tombstones awaiting cleanup.
+    pub async fn pending_tombstones(&self, limit: usize) -> Result<Vec<Tombstone>> {
+        let rows = sqlx::query_as::<_, (i64, String, String, String)>(
+            "SELECT doc_id, uri, source, deleted_at FROM document_tombstones \
+            ORDER BY deleted_at, doc_id LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(doc_id, uri, source, deleted_at)| Tombstone {
+                doc_id,
+                uri,
+                source,
+                deleted_at: DateTime::parse_from_rfc3339(&deleted_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
+    /// Get number of tombstones awaiting cleanup
+    pub async fn tombstone_count(&self) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM document_tombstones")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    /// Permanently remove soft-deleted documents once their dependents are
+    /// cleaned up: drops the rows, their tombstones and tags, and their cluster
+    /// references.
+    ///
+    /// Documents revived by a sync since they were selected are left alone.
+    /// Returns the IDs actually purged.
+    pub async fn purge_tombstones(&self, doc_ids: &[i64]) -> Result<Vec<i64>> {
+        if doc_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = doc_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+        let mut tx = self.pool.begin().await?;
+        let query = format!(
+            "DELETE FROM documents WHERE id IN ({}) AND deleted_at IS NOT NULL RETURNING id",
+            placeholders
+        );
+        let mut q = sqlx::query_scalar::<_, i64>(&query);
+        for id in doc_ids {
+            q = q.bind(id);
+        }
+        let purged = q.fetch_all(&mut *tx).await?;
+
+        // Revived documents no longer have a tombstone; clearing all of them
+        // keeps a vanished row from being selected again
+        let query = format!("DELETE FROM document_tombstones WHERE doc_id IN ({})", placeholders);
+        let mut q = sqlx::query(&query);
+        for id in doc_ids {
+            q = q.bind(id);
+        }
+        q.execute(&mut *tx).await?;
+        if purged.is_empty() {
+            tx.commit().await?;
+            return Ok(purged);
+        }
+
+        let placeholders = purged.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        for table in ["document_tags", "document_links"] {
+            let query = format!("DELETE FROM {} WHERE doc_id IN ({})", table, placeholders);
+            let mut q = sqlx::query(&query);
+            for id in &purged {
+                q = q.bind(id);
+            }
+            q.execute(&mut *tx).await?;
+        }
+        attachments::delete_for_documents(&mut tx, &purged).await?;
+        dedup::forget(&mut tx, &purged).await?;
+
+        let clusters = sqlx::query_as::<_, (i64, String)>("SELECT id, doc_ids FROM clusters")
+            .fetch_all(&mut *tx)
+            .await?;
+        for (id, ids) in clusters {
+            let ids: Vec<i64> = serde_json::from_str(&ids).unwrap_or_default();
+            if !ids.iter().any(|doc_id| purged.contains(doc_id)) {
+                continue;
+            }
+            let kept: Vec<i64> = ids.into_iter().filter(|doc_id| !purged.contains(doc_id)).collect();
+            sqlx::query("UPDATE clusters SET doc_ids = ?1 WHERE id = ?2")
+                .bind(serde_json::to_string(&kept)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    /// Tag a document (e.g. with its repo, channel or project). Tags are
+    /// case-insensitive; adding one twice is a no-op. Blank tags are ignored.
+    pub async fn add_tag(&self, doc_id: i64, tag: &str) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.add_tag_on(&mut conn, doc_id, tag).await
+    }
+
+    /// [`Self::add_tag`] on `conn`, inside the caller's transaction.
+    pub async fn add_tag_on(&self, conn: &mut SqliteConnection, doc_id: i64, tag: &str) -> Result<()> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(());
+        };
+        sqlx::query("INSERT OR IGNORE INTO document_tags (doc_id, tag) VALUES (?1, ?2)")
+            .bind(doc_id)
+            .bind(tag)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_tag(&self, doc_id: i64, tag: &str) -> Result<()> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(());
+        };
+        sqlx::query("DELETE FROM document_tags WHERE doc_id = ?1 AND tag = ?2")
+            .bind(doc_id)
+            .bind(tag)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn tags_for(&self, doc_id: i64) -> Result<Vec<String>> {
+        let tags = sqlx::query_scalar("SELECT tag FROM document_tags WHERE doc_id = ?1 ORDER BY tag")
+            .bind(doc_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+
+    /// Live documents carrying `tag`, most recently updated first.
+    pub async fn list_by_tag(&self, tag: &str, limit: usize) -> Result<Vec<Document>> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(Vec::new());
+        };
+        let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(
+            "SELECT d.id, d.uri, d.source, d.title, d.body, d.updated_at \
+            FROM document_tags t JOIN documents d ON d.id = t.doc_id \
+            WHERE t.tag = ?1 AND d.deleted_at IS NULL \
+            ORDER BY d.updated_at DESC LIMIT ?2",
+        )
+        .bind(tag)
         .bind(limit as i64)
         .fetch_all(&self.pool)
         .await?;
@@ -238,67 +1128,242 @@ impl IngestionEngine {
             .collect())
     }
 
-    pub async fn delete_documents_by_source(&self, source: &str) -> Result<()> {
-        sqlx::query("DELETE FROM documents WHERE source = ?1")
-            .bind(source)
-            .execute(&self.pool)
+    /// Live documents, most recently updated first, optionally from one
+    /// `source` and updated within `[updated_after, updated_before)`.
+    pub async fn fetch_documents(
+        &self,
+        source: Option<&str>,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Document>> {
+        // Only the given bounds go into the query so it can use the index
+        let mut query = String::from(
+            "SELECT id, uri, source, title, body, updated_at FROM documents WHERE deleted_at IS NULL",
+        );
+        if source.is_some() {
+            query.push_str(" AND source = ?");
+        }
+        if updated_after.is_some() {
+            query.push_str(" AND updated_at >= ?");
+        }
+        if updated_before.is_some() {
+            query.push_str(" AND updated_at < ?");
+        }
+        query.push_str(" ORDER BY updated_at DESC, id DESC LIMIT ?");
+
+        let mut q = sqlx::query_as::<_, (i64, String, String, Option<String>, String, String)>(&query);
+        if let Some(source) = source {
+            q = q.bind(source);
+        }
+        if let Some(after) = updated_after {
+            q = q.bind(after.to_rfc3339());
+        }
+        if let Some(before) = updated_before {
+            q = q.bind(before.to_rfc3339());
+        }
+        let rows = q.bind(limit as i64).fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, uri, source, title, body, updated_at)| Document {
+                id: Some(id),
+                uri,
+                source,
+                title,
+                body,
+                updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
+    /// Tags ranked by how many live documents carrying them were updated
+    /// since `since`.
+    pub async fn tag_counts_updated_since(&self, since: DateTime<Utc>, limit: usize) -> Result<Vec<(String, i64)>> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            "SELECT t.tag, COUNT(*) FROM document_tags t JOIN documents d ON d.id = t.doc_id \
+            WHERE d.deleted_at IS NULL AND d.updated_at >= ?1 \
+            GROUP BY t.tag ORDER BY COUNT(*) DESC, t.tag LIMIT ?2",
+        )
+        .bind(since.to_rfc3339())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// IDs of documents carrying any of `tags`.
+    pub async fn doc_ids_with_tags(&self, tags: &[String]) -> Result<Vec<i64>> {
+        let tags: Vec<String> = tags.iter().filter_map(|tag| normalize_tag(tag)).collect();
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids = sqlx::query_scalar(
+            "SELECT DISTINCT doc_id FROM document_tags WHERE tag IN (SELECT value FROM json_each(?1))",
+        )
+        .bind(serde_json::to_string(&tags)?)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+
+    /// IDs of live documents in any of `languages` (ISO 639-3 codes).
+    pub async fn doc_ids_with_languages(&self, languages: &[String]) -> Result<Vec<i64>> {
+        if languages.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids = sqlx::query_scalar(
+            "SELECT id FROM documents WHERE deleted_at IS NULL \
+            AND language IN (SELECT value FROM json_each(?1))",
+        )
+        .bind(serde_json::to_string(languages)?)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+
+    /// Detected language of each of `ids` that has one.
+    pub async fn document_languages(&self, ids: &[i64]) -> Result<HashMap<i64, String>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT id, language FROM documents \
+            WHERE id IN (SELECT value FROM json_each(?1)) AND language IS NOT NULL",
+        )
+        .bind(serde_json::to_string(ids)?)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Detect the language of every document that hasn't got one yet.
+    /// Returns the number of documents updated.
+    async fn detect_languages(&self) -> Result<usize> {
+        let mut last_id = 0i64;
+        let mut updated = 0;
+        loop {
+            let rows: Vec<(i64, String)> = sqlx::query_as(
+                "SELECT id, body FROM documents WHERE id > ?1 AND language IS NULL \
+                ORDER BY id LIMIT 500",
+            )
+            .bind(last_id)
+            .fetch_all(&self.pool)
             .await?;
-        Ok(())
+            let Some((id, _)) = rows.last() else { break };
+            last_id = *id;
+            let mut tx = self.pool.begin().await?;
+            for (id, body) in &rows {
+                if let Some(language) = language::detect(body) {
+                    sqlx::query("UPDATE documents SET language = ?2 WHERE id = ?1")
+                        .bind(id)
+                        .bind(language)
+                        .execute(&mut *tx)
+                        .await?;
+                    updated += 1;
+                }
+            }
+            tx.commit().await?;
+        }
+        if updated > 0 {
+            info!("[LANGUAGE] Detected the language of {} existing documents", updated);
+        }
+        Ok(updated)
     }
 
+    /// Insert clusters without an `id` and overwrite the rows of those with
+    /// one, in a single transaction.
     pub async fn store_clusters(&self, clusters: &[ClusterRecord]) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = self.store_clusters_on(&mut conn, clusters).await;
+        Self::finish_tx(&mut conn, result).await
+    }
+
+    /// [`Self::store_clusters`] on `conn`, inside the caller's transaction.
+    pub async fn store_clusters_on(
+        &self,
+        conn: &mut SqliteConnection,
+        clusters: &[ClusterRecord],
+    ) -> Result<()> {
         for cluster in clusters {
             let doc_ids = serde_json::to_string(&cluster.doc_ids)?;
             sqlx::query(
-                "INSERT INTO clusters (label, doc_ids, created_at) VALUES (?1, ?2, ?3)",
+                "INSERT INTO clusters (id, label, doc_ids, created_at, summary) VALUES (?1, ?2, ?3, ?4, ?5) \
+                ON CONFLICT(id) DO UPDATE SET label = excluded.label, doc_ids = excluded.doc_ids, \
+                created_at = excluded.created_at, summary = excluded.summary",
             )
+            .bind(cluster.id)
             .bind(&cluster.label)
             .bind(doc_ids)
             .bind(cluster.created_at.to_rfc3339())
-            .execute(&self.pool)
+            .bind(&cluster.summary)
+            .execute(&mut *conn)
             .await?;
         }
         Ok(())
     }
 
     pub async fn get_cluster_doc_ids(&self, label: &str) -> Result<Vec<i64>> {
-        let row = sqlx::query_as::<_, (String,)>(
-            "SELECT doc_ids FROM clusters WHERE label = ?1 ORDER BY id DESC LIMIT 1",
+        Ok(self.get_cluster(label).await?.map(|cluster| cluster.doc_ids).unwrap_or_default())
+    }
+
+    /// The most recent cluster stored under `label`.
+    pub async fn get_cluster(&self, label: &str) -> Result<Option<ClusterRecord>> {
+        let row = sqlx::query_as::<_, ClusterRow>(
+            "SELECT id, label, doc_ids, created_at, summary FROM clusters \
+            WHERE label = ?1 ORDER BY id DESC LIMIT 1",
         )
         .bind(label)
         .fetch_optional(&self.pool)
         .await?;
-
-        if let Some((doc_ids,)) = row {
-            let ids: Vec<i64> = serde_json::from_str(&doc_ids).unwrap_or_default();
-            Ok(ids)
-        } else {
-            Ok(Vec::new())
-        }
+        Ok(row.map(cluster_from_row))
     }
 
     pub async fn list_clusters(&self, limit: usize) -> Result<Vec<ClusterRecord>> {
-        let rows = sqlx::query_as::<_, (i64, String, String, String)>(
-            "SELECT id, label, doc_ids, created_at FROM clusters ORDER BY id DESC LIMIT ?1",
+        let rows = sqlx::query_as::<_, ClusterRow>(
+            "SELECT id, label, doc_ids, created_at, summary FROM clusters ORDER BY id DESC LIMIT ?1",
         )
         .bind(limit as i64)
         .fetch_all(&self.pool)
         .await?;
+        Ok(rows.into_iter().map(cluster_from_row).collect())
+    }
 
-        Ok(rows
-            .into_iter()
-            .map(|(id, label, doc_ids, created_at)| ClusterRecord {
-                id: Some(id),
-                label,
-                doc_ids: serde_json::from_str(&doc_ids).unwrap_or_default(),
-                created_at: DateTime::parse_from_rfc3339(&created_at)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-            })
-            .collect())
+    /// The most recent cluster of each label, newest first: the packs
+    /// `get_context` can search within.
+    pub async fn latest_clusters(&self, limit: usize) -> Result<Vec<ClusterRecord>> {
+        let rows = sqlx::query_as::<_, ClusterRow>(
+            "SELECT id, label, doc_ids, created_at, summary FROM clusters \
+            WHERE id IN (SELECT MAX(id) FROM clusters GROUP BY label) ORDER BY id DESC LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(cluster_from_row).collect())
+    }
+
+    pub async fn cluster_count(&self) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM clusters")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
     }
 
     pub async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.set_sync_cursor_on(&mut conn, provider, cursor).await
+    }
+
+    /// [`Self::set_sync_cursor`] on `conn`, inside the caller's transaction.
+    pub async fn set_sync_cursor_on(
+        &self,
+        conn: &mut SqliteConnection,
+        provider: &str,
+        cursor: &str,
+    ) -> Result<()> {
         sqlx::query(
             "INSERT INTO sync_state (provider, cursor, updated_at) VALUES (?1, ?2, ?3)\
             ON CONFLICT(provider) DO UPDATE SET cursor=excluded.cursor, updated_at=excluded.updated_at",
@@ -306,7 +1371,7 @@ impl IngestionEngine {
         .bind(provider)
         .bind(cursor)
         .bind(Utc::now().to_rfc3339())
-        .execute(&self.pool)
+        .execute(conn)
         .await?;
         Ok(())
     }
@@ -322,24 +1387,68 @@ impl IngestionEngine {
         Ok(row.and_then(|(cursor,)| cursor))
     }
 
+    /// Every stored sync cursor as `(provider, cursor)`.
+    pub async fn list_sync_cursors(&self) -> Result<Vec<(String, String)>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "SELECT provider, cursor FROM sync_state WHERE cursor IS NOT NULL ORDER BY provider",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
     /// Get total document count
     pub async fn document_count(&self) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM documents")
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM documents WHERE deleted_at IS NULL")
             .fetch_one(&self.pool)
             .await?;
         Ok(count)
     }
 
+    /// Size of the database in bytes, free pages included.
+    pub async fn database_size(&self) -> Result<i64> {
+        maintenance::database_size(&self.pool).await
+    }
+
     /// Get document count per source
     pub async fn document_counts_by_source(&self) -> Result<Vec<(String, i64)>> {
         let rows = sqlx::query_as::<_, (String, i64)>(
-            "SELECT source, COUNT(*) FROM documents GROUP BY source",
+            "SELECT source, COUNT(*) FROM documents WHERE deleted_at IS NULL GROUP BY source",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Get count of documents updated since a time, per source
+    pub async fn document_counts_updated_since(&self, since: DateTime<Utc>) -> Result<Vec<(String, i64)>> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            "SELECT source, COUNT(*) FROM documents \
+            WHERE deleted_at IS NULL AND updated_at >= ?1 GROUP BY source ORDER BY COUNT(*) DESC",
         )
+        .bind(since.to_rfc3339())
         .fetch_all(&self.pool)
         .await?;
         Ok(rows)
     }
 
+    /// ID and update time of each live document updated since `since`.
+    pub async fn document_times_updated_since(&self, since: DateTime<Utc>) -> Result<Vec<(i64, DateTime<Utc>)>> {
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            "SELECT id, updated_at FROM documents WHERE deleted_at IS NULL AND updated_at >= ?1",
+        )
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, updated_at)| {
+                let updated_at = DateTime::parse_from_rfc3339(&updated_at).ok()?;
+                Some((id, updated_at.with_timezone(&Utc)))
+            })
+            .collect())
+    }
+
     /// Get last sync time per provider
     pub async fn get_sync_times(&self) -> Result<Vec<(String, DateTime<Utc>)>> {
         let rows = sqlx::query_as::<_, (String, String)>(
@@ -357,4 +1466,322 @@ impl IngestionEngine {
             })
             .collect())
     }
+
+    /// Record a resource as archived upstream. Re-marking keeps the original detection time.
+    pub async fn mark_archived(
+        &self,
+        provider: &str,
+        resource_id: &str,
+        name: Option<&str>,
+        uri_prefix: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO archived_resources (provider, resource_id, name, uri_prefix, detected_at) \
+            VALUES (?1, ?2, ?3, ?4, ?5) \
+            ON CONFLICT(provider, resource_id) DO UPDATE SET name=excluded.name, uri_prefix=excluded.uri_prefix",
+        )
+        .bind(provider)
+        .bind(resource_id)
+        .bind(name)
+        .bind(uri_prefix)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Clear the archived flag (e.g. a channel was unarchived).
+    pub async fn unmark_archived(&self, provider: &str, resource_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM archived_resources WHERE provider = ?1 AND resource_id = ?2")
+            .bind(provider)
+            .bind(resource_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// List archived resources, optionally for a single provider.
+    pub async fn list_archived(&self, provider: Option<&str>) -> Result<Vec<ArchivedResource>> {
+        let rows = sqlx::query_as::<_, (String, String, Option<String>, String, String)>(
+            "SELECT provider, resource_id, name, uri_prefix, detected_at FROM archived_resources \
+            WHERE ?1 IS NULL OR provider = ?1 ORDER BY provider, resource_id",
+        )
+        .bind(provider)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(provider, resource_id, name, uri_prefix, detected_at)| ArchivedResource {
+                provider,
+                resource_id,
+                name,
+                uri_prefix,
+                detected_at: DateTime::parse_from_rfc3339(&detected_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
+    /// URI prefixes of all archived resources, for ranking.
+    pub async fn archived_uri_prefixes(&self) -> Result<Vec<String>> {
+        let prefixes = sqlx::query_scalar("SELECT uri_prefix FROM archived_resources")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(prefixes)
+    }
+}
+
+/// Canonical form of a tag: trimmed and lowercased, or None if blank.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    (!tag.is_empty()).then(|| tag.to_lowercase())
+}
+
+type ClusterRow = (i64, String, String, String, Option<String>);
+
+fn cluster_from_row((id, label, doc_ids, created_at, summary): ClusterRow) -> ClusterRecord {
+    ClusterRecord {
+        id: Some(id),
+        label,
+        doc_ids: serde_json::from_str(&doc_ids).unwrap_or_default(),
+        created_at: DateTime::parse_from_rfc3339(&created_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(uri: &str, source: &str, body: &str) -> Document {
+        Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_skips_revived_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let gone = ingest.upsert_document(&doc("mock://1", "mock", "first")).await.unwrap();
+        let revived = ingest.upsert_document(&doc("mock://2", "mock", "second")).await.unwrap();
+        ingest.add_tag(gone, "old").await.unwrap();
+        ingest.add_tag(revived, "kept").await.unwrap();
+
+        assert_eq!(ingest.delete_documents_by_source("mock").await.unwrap(), 2);
+        let ids: Vec<i64> = ingest
+            .pending_tombstones(10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|tombstone| tombstone.doc_id)
+            .collect();
+        assert_eq!(ids.len(), 2);
+
+        // A sync brings one back before the purge runs
+        ingest.upsert_document(&doc("mock://2", "mock", "second, edited")).await.unwrap();
+        assert_eq!(ingest.purge_tombstones(&ids).await.unwrap(), vec![gone]);
+
+        assert_eq!(ingest.tombstone_count().await.unwrap(), 0);
+        assert!(ingest.get_document_by_uri("mock://1").await.unwrap().is_none());
+        let kept = ingest.get_document_by_uri("mock://2").await.unwrap().unwrap();
+        assert_eq!(kept.id, Some(revived));
+        assert_eq!(ingest.tags_for(revived).await.unwrap(), vec!["kept"]);
+        assert!(ingest.tags_for(gone).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keyword_hits_carry_highlighted_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let filler = "Standup notes moved a few tickets along the board. ".repeat(20);
+        let body = format!("{}The p99 latency regression came from the cache.{}", filler, filler);
+        ingest.upsert_document(&doc("mock://1", "mock", &body)).await.unwrap();
+
+        let hits = ingest.search_keyword("latency cache", 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        let snippet = &hits[0].snippet;
+        assert!(snippet.contains("**latency**") && snippet.contains("**cache**"), "{snippet}");
+        // Long bodies are cut down to the matching region
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'), "{snippet}");
+        assert!(snippet.len() < body.len() / 4, "{snippet}");
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let mut older = doc("github://acme/api/1", "github", "retry budget for the payments api");
+        older.updated_at = Utc::now() - chrono::Duration::days(1);
+        let older = ingest.upsert_document(&older).await.unwrap();
+        let newer = ingest.upsert_document(&doc("slack://C1/1", "slack", "payments api incident")).await.unwrap();
+        let untagged = ingest.upsert_document(&doc("slack://C2/1", "slack", "payments api lunch")).await.unwrap();
+
+        // Tags are normalized, duplicates and blanks ignored
+        ingest.add_tag(older, " Acme/API ").await.unwrap();
+        ingest.add_tag(older, "acme/api").await.unwrap();
+        ingest.add_tag(older, "  ").await.unwrap();
+        ingest.add_tag(newer, "acme/api").await.unwrap();
+        ingest.add_tag(newer, "incident").await.unwrap();
+        assert_eq!(ingest.tags_for(older).await.unwrap(), vec!["acme/api"]);
+        assert_eq!(ingest.tags_for(newer).await.unwrap(), vec!["acme/api", "incident"]);
+
+        let listed: Vec<_> = ingest.list_by_tag("ACME/api", 10).await.unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(listed, vec![Some(newer), Some(older)]);
+        let mut ids = ingest.doc_ids_with_tags(&["incident".to_string(), "acme/api".to_string()]).await.unwrap();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![older, newer]);
+
+        // `tag:` narrows keyword search
+        let hits = ingest.search_keyword("payments tag:incident", 10).await.unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.document.id).collect::<Vec<_>>(), vec![Some(newer)]);
+        assert_eq!(ingest.search_keyword("payments", 10).await.unwrap().len(), 3);
+
+        ingest.remove_tag(newer, "Incident").await.unwrap();
+        assert_eq!(ingest.tags_for(newer).await.unwrap(), vec!["acme/api"]);
+        ingest.delete_documents(&[newer]).await.unwrap();
+        let listed: Vec<_> = ingest.list_by_tag("acme/api", 10).await.unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(listed, vec![Some(older)]);
+        assert!(ingest.tags_for(untagged).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_document_by_short_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let id = ingest.upsert_document(&doc("slack://C1/1", "slack", "standup")).await.unwrap();
+        let short = short_id::encode("slack", id);
+
+        let by_short = ingest.resolve_document(&short).await.unwrap().unwrap();
+        assert_eq!(by_short.id, Some(id));
+        assert_eq!(by_short.short_id(), Some(short));
+        let by_uri = ingest.resolve_document("slack://C1/1").await.unwrap().unwrap();
+        assert_eq!(by_uri.id, Some(id));
+        // The same row under another source's prefix doesn't resolve
+        assert!(ingest.resolve_document(&short_id::encode("github", id)).await.unwrap().is_none());
+        assert!(ingest.resolve_document("sl-ffff").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let pinned = ingest.upsert_document(&doc("mock://1", "mock", "keep")).await.unwrap();
+        ingest.upsert_document(&doc("mock://2", "mock", "other")).await.unwrap();
+        ingest.set_pinned(pinned, true).await.unwrap();
+        // Syncing the document again keeps the pin
+        ingest.upsert_document(&doc("mock://1", "mock", "keep, edited")).await.unwrap();
+
+        let ids = |docs: Vec<Document>| docs.into_iter().filter_map(|doc| doc.id).collect::<Vec<_>>();
+        assert_eq!(ids(ingest.list_pinned(10).await.unwrap()), vec![pinned]);
+        assert_eq!(ids(ingest.recent_documents(1).await.unwrap()), vec![pinned]);
+        ingest.set_pinned(pinned, false).await.unwrap();
+        assert!(ingest.list_pinned(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_documents_are_not_evicted() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let mut ids = Vec::new();
+        for n in 0..3 {
+            let mut old = doc(&format!("mock://{}", n), "mock", "body");
+            old.updated_at = Utc::now() - chrono::Duration::days(3 - n);
+            ids.push(ingest.upsert_document(&old).await.unwrap());
+        }
+        ingest.set_pinned(ids[0], true).await.unwrap();
+
+        let evicted = ingest.evict_oldest("mock", Some(2), None, &HashSet::new()).await.unwrap();
+        assert_eq!(evicted, vec![ids[1]]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_documents_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (n, source) in ["slack", "github", "slack", "slack"].iter().enumerate() {
+            let mut d = doc(&format!("{}://{}", source, n), source, "body");
+            d.updated_at = now - chrono::Duration::days(4 - n as i64);
+            ids.push(ingest.upsert_document(&d).await.unwrap());
+        }
+        ingest.delete_documents(&[ids[3]]).await.unwrap();
+        let fetched = |docs: Vec<Document>| docs.into_iter().map(|d| d.id.unwrap()).collect::<Vec<_>>();
+
+        // Newest first, deleted rows skipped
+        let all = ingest.fetch_documents(None, None, None, 10).await.unwrap();
+        assert_eq!(fetched(all), vec![ids[2], ids[1], ids[0]]);
+        let slack = ingest.fetch_documents(Some("slack"), None, None, 10).await.unwrap();
+        assert_eq!(fetched(slack), vec![ids[2], ids[0]]);
+        assert_eq!(fetched(ingest.fetch_documents(None, None, None, 1).await.unwrap()), vec![ids[2]]);
+
+        // `updated_after` is inclusive, `updated_before` exclusive
+        let after = now - chrono::Duration::days(3);
+        let before = now - chrono::Duration::days(2);
+        let window = ingest.fetch_documents(None, Some(after), Some(before), 10).await.unwrap();
+        assert_eq!(fetched(window), vec![ids[1]]);
+        let recent = ingest.fetch_documents(Some("github"), Some(after), None, 10).await.unwrap();
+        assert_eq!(fetched(recent), vec![ids[1]]);
+        assert!(ingest.fetch_documents(Some("github"), None, Some(after), 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_documents_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let existing = ingest.upsert_document(&doc("mock://1", "mock", "old body")).await.unwrap();
+        assert!(ingest.upsert_documents_batch(&[]).await.unwrap().is_empty());
+
+        let docs = [doc("mock://0", "mock", "quarterly roadmap"), doc("mock://1", "mock", "roadmap draft")];
+        let ids = ingest.upsert_documents_batch(&docs).await.unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[1], existing);
+        assert_eq!(ingest.get_document_by_uri("mock://1").await.unwrap().unwrap().body, "roadmap draft");
+        assert_eq!(ingest.search_keyword("roadmap", 10).await.unwrap().len(), 2);
+
+        // One failing row rolls back the whole batch
+        sqlx::query(
+            "CREATE TRIGGER reject_bad BEFORE INSERT ON documents WHEN NEW.uri = 'mock://bad' \
+            BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+        )
+        .execute(&ingest.pool)
+        .await
+        .unwrap();
+        let docs = [doc("mock://2", "mock", "roadmap review"), doc("mock://bad", "mock", "roadmap")];
+        assert!(ingest.upsert_documents_batch(&docs).await.is_err());
+        assert!(ingest.get_document_by_uri("mock://2").await.unwrap().is_none());
+        assert_eq!(ingest.search_keyword("roadmap", 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_store_clusters_updates_by_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let cluster = ClusterRecord {
+            id: None,
+            label: "launch".to_string(),
+            doc_ids: vec![1, 2],
+            created_at: Utc::now(),
+            summary: None,
+        };
+        ingest.store_clusters(&[cluster]).await.unwrap();
+
+        // A cluster that grew is written back over its row
+        let mut stored = ingest.get_cluster("launch").await.unwrap().unwrap();
+        stored.doc_ids.push(3);
+        ingest.store_clusters(&[stored.clone()]).await.unwrap();
+        let clusters = ingest.list_clusters(10).await.unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!((clusters[0].id, clusters[0].doc_ids.clone()), (stored.id, vec![1, 2, 3]));
+    }
 }
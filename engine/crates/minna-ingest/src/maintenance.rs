@@ -0,0 +1,189 @@
+//! Housekeeping for the database file.
+//!
+//! Long-lived local databases bloat as documents are replaced and purged, and
+//! the occasional crash can leave the file or the full-text index damaged.
+//! `run` checks integrity (SQLite's and FTS5's own), rebuilds the full-text
+//! index and vacuums, reporting what it found.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::fts;
+
+/// Problems reported by `PRAGMA integrity_check` beyond this are dropped.
+const MAX_INTEGRITY_ERRORS: i64 = 100;
+
+/// Which maintenance steps to run. Everything is on by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceOptions {
+    pub integrity_check: bool,
+    pub rebuild_fts: bool,
+    pub vacuum: bool,
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> Self {
+        Self {
+            integrity_check: true,
+            rebuild_fts: true,
+            vacuum: true,
+        }
+    }
+}
+
+/// What a maintenance run found and did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Result of `PRAGMA integrity_check`, if it ran.
+    pub integrity_ok: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub integrity_errors: Vec<String>,
+    /// Result of the FTS5 index's own consistency check, if it ran.
+    pub fts_ok: Option<bool>,
+    pub fts_rebuilt: bool,
+    pub vacuumed: bool,
+    /// Database size before and after, in bytes.
+    pub size_before: i64,
+    pub size_after: i64,
+}
+
+impl MaintenanceReport {
+    /// False if a check found a problem that wasn't repaired. A damaged
+    /// full-text index is repaired by the rebuild.
+    pub fn is_ok(&self) -> bool {
+        self.integrity_ok != Some(false) && (self.fts_ok != Some(false) || self.fts_rebuilt)
+    }
+}
+
+/// Run the steps selected in `options`, in order: integrity checks, FTS
+/// rebuild, vacuum.
+pub async fn run(pool: &SqlitePool, options: &MaintenanceOptions) -> Result<MaintenanceReport> {
+    let mut report = MaintenanceReport {
+        size_before: database_size(pool).await?,
+        ..Default::default()
+    };
+
+    if options.integrity_check {
+        let rows: Vec<String> = sqlx::query_scalar(&format!(
+            "PRAGMA integrity_check({})",
+            MAX_INTEGRITY_ERRORS
+        ))
+        .fetch_all(pool)
+        .await?;
+        let ok = rows.len() == 1 && rows[0] == "ok";
+        if !ok {
+            warn!("[MAINTENANCE] Integrity check failed: {} problems", rows.len());
+            report.integrity_errors = rows;
+        }
+        report.integrity_ok = Some(ok);
+
+        // FTS5 reports a mismatch between the index and its content as an
+        // error; rank 1 compares against the external `documents` table too
+        let mut conn = pool.acquire().await?;
+        let fts_check = sqlx::query("INSERT INTO documents_fts(documents_fts, rank) VALUES('integrity-check', 1)")
+            .execute(&mut *conn)
+            .await;
+        if let Err(err) = &fts_check {
+            warn!("[MAINTENANCE] Full-text index check failed: {}", err);
+            // The failed check leaves its connection holding a read lock,
+            // which would block the vacuum and checkpoint below
+            let _ = conn.close().await;
+        }
+        report.fts_ok = Some(fts_check.is_ok());
+    }
+
+    if options.rebuild_fts {
+        fts::rebuild(pool).await?;
+        report.fts_rebuilt = true;
+    }
+
+    if options.vacuum {
+        sqlx::query("VACUUM").execute(pool).await?;
+        // In WAL mode the vacuumed pages land in the WAL until checkpointed
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(pool).await?;
+        report.vacuumed = true;
+    }
+
+    report.size_after = database_size(pool).await?;
+    info!(
+        "[MAINTENANCE] Done: integrity={:?} fts={:?} size {} -> {} bytes",
+        report.integrity_ok, report.fts_ok, report.size_before, report.size_after
+    );
+    Ok(report)
+}
+
+pub(crate) async fn database_size(pool: &SqlitePool) -> Result<i64> {
+    let pages: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(pool).await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(pool).await?;
+    Ok(pages * page_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    async fn engine_with_docs(dir: &tempfile::TempDir, count: usize) -> IngestionEngine {
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let docs: Vec<Document> = (0..count)
+            .map(|n| Document {
+                id: None,
+                uri: format!("mock://{}", n),
+                source: "mock".to_string(),
+                title: None,
+                body: format!("document {} {}", n, "padding text for the page count ".repeat(40)),
+                updated_at: chrono::Utc::now(),
+            })
+            .collect();
+        for doc in &docs {
+            ingest.upsert_document(doc).await.unwrap();
+        }
+        ingest
+    }
+
+    #[tokio::test]
+    async fn test_damaged_fts_index_is_repaired() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = engine_with_docs(&dir, 3).await;
+        // An index entry with no document behind it
+        sqlx::query("INSERT INTO documents_fts(rowid, uri, title, body) VALUES (999, 'mock://ghost', '', 'ghost')")
+            .execute(ingest.pool())
+            .await
+            .unwrap();
+
+        let checks_only = MaintenanceOptions {
+            rebuild_fts: false,
+            vacuum: false,
+            ..Default::default()
+        };
+        let report = ingest.maintenance(&checks_only).await.unwrap();
+        assert_eq!((report.integrity_ok, report.fts_ok), (Some(true), Some(false)));
+        assert!(!report.fts_rebuilt && !report.vacuumed);
+        assert!(!report.is_ok());
+
+        let report = ingest.maintenance(&MaintenanceOptions::default()).await.unwrap();
+        assert!(report.fts_rebuilt && report.is_ok(), "{report:?}");
+        let report = ingest.maintenance(&checks_only).await.unwrap();
+        assert_eq!(report.fts_ok, Some(true));
+        assert_eq!(ingest.search_keyword("document", 10).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_vacuum_reclaims_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = engine_with_docs(&dir, 200).await;
+        sqlx::query("DELETE FROM documents WHERE id > 1")
+            .execute(ingest.pool())
+            .await
+            .unwrap();
+
+        let report = ingest.maintenance(&MaintenanceOptions::default()).await.unwrap();
+        assert!(report.vacuumed && report.is_ok(), "{report:?}");
+        assert!(report.size_after < report.size_before / 2, "{report:?}");
+        assert_eq!(report.size_after, database_size(ingest.pool()).await.unwrap());
+        assert_eq!(ingest.search_keyword("document", 10).await.unwrap().len(), 1);
+    }
+}
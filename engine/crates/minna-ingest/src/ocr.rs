@@ -0,0 +1,264 @@
+//! Optional OCR for images and image-only PDFs.
+//!
+//! OCR shells out to the `tesseract` CLI (and `pdftoppm` from poppler for
+//! rasterizing PDFs) rather than linking an OCR library, so it costs nothing
+//! when disabled. Enable it with `MINNA_OCR=tesseract`; override binary
+//! locations with `MINNA_TESSERACT_PATH` / `MINNA_PDFTOPPM_PATH`. Each
+//! subprocess is killed after `MINNA_OCR_TIMEOUT_SECS` (default 120).
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use tracing::{info, warn};
+
+/// Image types worth running through OCR.
+const IMAGE_MIMES: &[&str] = &["image/png", "image/jpeg", "image/jpg", "image/gif", "image/bmp", "image/tiff", "image/webp"];
+
+/// Pages rasterized from an image-only PDF.
+const MAX_PDF_PAGES: u32 = 20;
+
+/// Default limit for a single tesseract or pdftoppm run.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+static CONFIG: OnceLock<Option<OcrConfig>> = OnceLock::new();
+static SCRATCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub struct OcrConfig {
+    pub tesseract: PathBuf,
+    pub pdftoppm: PathBuf,
+    /// Tesseract language codes, e.g. "eng" or "eng+deu".
+    pub languages: String,
+    /// How long a single subprocess may run before it is killed.
+    pub timeout: Duration,
+}
+
+impl OcrConfig {
+    /// Read OCR settings from the environment. Returns None if OCR is off.
+    pub fn from_env() -> Option<Self> {
+        let backend = std::env::var("MINNA_OCR").ok()?;
+        if !backend.eq_ignore_ascii_case("tesseract") {
+            if !backend.is_empty() && backend != "0" && !backend.eq_ignore_ascii_case("off") {
+                warn!("Unsupported MINNA_OCR backend '{}', OCR disabled", backend);
+            }
+            return None;
+        }
+        let config = Self {
+            tesseract: env_path("MINNA_TESSERACT_PATH", "tesseract"),
+            pdftoppm: env_path("MINNA_PDFTOPPM_PATH", "pdftoppm"),
+            languages: std::env::var("MINNA_OCR_LANGS").unwrap_or_else(|_| "eng".to_string()),
+            timeout: env_duration("MINNA_OCR_TIMEOUT_SECS", DEFAULT_TIMEOUT),
+        };
+        info!("OCR enabled via {}", config.tesseract.display());
+        Some(config)
+    }
+
+    /// Process-wide OCR configuration, read once from the environment.
+    pub fn global() -> Option<&'static OcrConfig> {
+        CONFIG.get_or_init(Self::from_env).as_ref()
+    }
+
+    /// OCR a single image.
+    pub fn image_text(&self, bytes: &[u8]) -> Result<String> {
+        let scratch = ScratchDir::new()?;
+        let input = scratch.path().join("image");
+        std::fs::write(&input, bytes)?;
+        self.run_tesseract(&input)
+    }
+
+    /// Rasterize a PDF and OCR each page.
+    pub fn pdf_text(&self, bytes: &[u8]) -> Result<String> {
+        let scratch = ScratchDir::new()?;
+        let input = scratch.path().join("doc.pdf");
+        std::fs::write(&input, bytes)?;
+
+        let mut command = Command::new(&self.pdftoppm);
+        command
+            .args(["-r", "200", "-png", "-l", &MAX_PDF_PAGES.to_string()])
+            .arg(&input)
+            .arg(scratch.path().join("page"));
+        let output = run_with_timeout(&mut command, self.timeout)
+            .with_context(|| format!("failed to run {}", self.pdftoppm.display()))?;
+        if !output.status.success() {
+            return Err(anyhow!("pdftoppm exited with {}", output.status));
+        }
+
+        let mut pages: Vec<PathBuf> = std::fs::read_dir(scratch.path())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+            .collect();
+        pages.sort();
+
+        let mut out = String::new();
+        for page in pages {
+            out.push_str(&self.run_tesseract(&page)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    fn run_tesseract(&self, input: &Path) -> Result<String> {
+        let mut command = Command::new(&self.tesseract);
+        command.arg(input).arg("stdout").args(["-l", &self.languages]);
+        let output = run_with_timeout(&mut command, self.timeout)
+            .with_context(|| format!("failed to run {}", self.tesseract.display()))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "tesseract exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Whether this MIME type is an image we can OCR.
+pub fn is_image(mime_type: &str) -> bool {
+    IMAGE_MIMES.contains(&mime_type)
+}
+
+pub(crate) fn env_path(var: &str, default: &str) -> PathBuf {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(default))
+}
+
+pub(crate) fn env_duration(var: &str, default: Duration) -> Duration {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(default)
+}
+
+/// Run a command to completion, killing it if it outlives `timeout`.
+///
+/// Output is drained on separate threads so a chatty child can't block on a
+/// full pipe while we wait.
+pub(crate) fn run_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("timed out after {}s", timeout.as_secs_f32()));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Temporary directory removed on drop.
+pub(crate) struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub(crate) fn new() -> Result<Self> {
+        let n = SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("minna-scratch-{}-{}", std::process::id(), n));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(tesseract: PathBuf, timeout: Duration) -> OcrConfig {
+        OcrConfig {
+            tesseract,
+            pdftoppm: PathBuf::from("/nonexistent/pdftoppm"),
+            languages: "eng".to_string(),
+            timeout,
+        }
+    }
+
+    #[cfg(unix)]
+    fn script(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("fake-tesseract");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_missing_binary_is_an_error() {
+        let ocr = config(PathBuf::from("/nonexistent/tesseract"), DEFAULT_TIMEOUT);
+        let err = ocr.image_text(b"png").unwrap_err();
+        assert!(err.to_string().contains("failed to run /nonexistent/tesseract"), "{err:#}");
+
+        let err = ocr.pdf_text(b"%PDF").unwrap_err();
+        assert!(err.to_string().contains("failed to run /nonexistent/pdftoppm"), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_image_text_reads_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        let ocr = config(script(dir.path(), "echo \"text from $2\""), DEFAULT_TIMEOUT);
+        assert_eq!(ocr.image_text(b"png").unwrap(), "text from stdout\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_run_reports_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let ocr = config(script(dir.path(), "echo 'bad image' >&2; exit 1"), DEFAULT_TIMEOUT);
+        let err = ocr.image_text(b"png").unwrap_err();
+        assert!(err.to_string().contains("bad image"), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hung_binary_is_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let ocr = config(script(dir.path(), "exec sleep 30"), Duration::from_millis(200));
+        let started = Instant::now();
+        let err = ocr.image_text(b"png").unwrap_err();
+        assert!(format!("{err:#}").contains("timed out"), "{err:#}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
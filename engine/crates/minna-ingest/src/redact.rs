@@ -0,0 +1,171 @@
+//! Redaction of sensitive text before it is stored or embedded.
+//!
+//! Configured in the `[redaction]` section of `providers.toml`:
+//!
+//! ```toml
+//! [redaction]
+//! builtin = ["api_keys", "credit_cards"]
+//!
+//! [[redaction.patterns]]
+//! name = "employee_id"
+//! regex = "EMP-[0-9]{6}"
+//! ```
+//!
+//! Each match is replaced with `[REDACTED:<name>]`. Redaction happens in
+//! [`IngestionEngine::upsert_document`](crate::IngestionEngine::upsert_document),
+//! so the original text never reaches the database, the full-text index or
+//! (through [`DocumentStore::prepare`](crate::DocumentStore::prepare)) the
+//! embeddings.
+
+use std::borrow::Cow;
+
+use anyhow::{bail, Context, Result};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+
+use crate::Document;
+
+/// Access tokens and keys for common services, plus PEM private keys.
+const API_KEYS: &str = concat!(
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    r"|\bgh[pousr]_[A-Za-z0-9]{36,}\b",
+    r"|\bgithub_pat_[A-Za-z0-9_]{22,}\b",
+    r"|\bxox[abposr]-[A-Za-z0-9-]{10,}",
+    r"|\bsk-(?:ant-|proj-)?[A-Za-z0-9_-]{20,}",
+    r"|\b[rs]k_(?:live|test)_[A-Za-z0-9]{16,}\b",
+    r"|\bAIza[0-9A-Za-z_-]{35}\b",
+    r"|\blin_api_[A-Za-z0-9]{32,}\b",
+    r"|-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+);
+
+/// 13-19 digits, optionally grouped by spaces or dashes. Matches must also
+/// pass the Luhn check, so order numbers and the like are left alone.
+const CREDIT_CARDS: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+/// The `[redaction]` section of `providers.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RedactionConfig {
+    /// Built-in pattern sets to enable: `api_keys`, `credit_cards`.
+    #[serde(default)]
+    pub builtin: Vec<String>,
+    /// Additional named regular expressions.
+    #[serde(default)]
+    pub patterns: Vec<RedactionPattern>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub regex: String,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    name: String,
+    regex: Regex,
+    luhn: bool,
+}
+
+/// Compiled redaction rules, applied in configuration order.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    /// Compile `config`. Returns `None` when nothing is configured, and an
+    /// error for an unknown built-in or an invalid regex.
+    pub fn from_config(config: &RedactionConfig) -> Result<Option<Self>> {
+        let mut rules = Vec::new();
+        for name in &config.builtin {
+            let (label, pattern, luhn) = match name.as_str() {
+                "api_keys" => ("api_key", API_KEYS, false),
+                "credit_cards" => ("credit_card", CREDIT_CARDS, true),
+                other => bail!("Unknown built-in redaction '{}'", other),
+            };
+            rules.push(Rule {
+                name: label.to_string(),
+                regex: Regex::new(pattern)?,
+                luhn,
+            });
+        }
+        for pattern in &config.patterns {
+            let regex = Regex::new(&pattern.regex)
+                .with_context(|| format!("Invalid redaction pattern '{}'", pattern.name))?;
+            rules.push(Rule {
+                name: pattern.name.clone(),
+                regex,
+                luhn: false,
+            });
+        }
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { rules }))
+    }
+
+    /// Names used in the `[REDACTED:<name>]` markers, in order.
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.name.as_str()).collect()
+    }
+
+    /// Replace every match in `text`; borrows when nothing matched.
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for rule in &self.rules {
+            let replaced = rule.regex.replace_all(&text, |caps: &Captures| {
+                let matched = &caps[0];
+                if rule.luhn && !luhn_valid(matched) {
+                    matched.to_string()
+                } else {
+                    format!("[REDACTED:{}]", rule.name)
+                }
+            });
+            if let Cow::Owned(replaced) = replaced {
+                if replaced != *text {
+                    text = Cow::Owned(replaced);
+                }
+            }
+        }
+        text
+    }
+
+    /// Redact a document's title and body.
+    pub fn redact_document<'d>(&self, doc: &'d Document) -> Cow<'d, Document> {
+        let title = doc.title.as_deref().map(|title| self.redact(title));
+        let body = self.redact(&doc.body);
+        let title_changed = matches!(title, Some(Cow::Owned(_)));
+        if !title_changed && matches!(body, Cow::Borrowed(_)) {
+            return Cow::Borrowed(doc);
+        }
+        Cow::Owned(Document {
+            id: doc.id,
+            uri: doc.uri.clone(),
+            source: doc.source.clone(),
+            title: title.map(Cow::into_owned),
+            body: body.into_owned(),
+            updated_at: doc.updated_at,
+        })
+    }
+}
+
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
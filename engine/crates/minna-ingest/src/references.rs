@@ -0,0 +1,238 @@
+//! Links between indexed documents.
+//!
+//! Every URL in a stored document's body is kept in `document_links`. A link
+//! is a reference when another live document has that URL as its URI, so
+//! "this Slack thread links to that PR" resolves whichever of the two was
+//! synced first. URLs are matched as written and without their query and
+//! fragment, since permalinks often carry tracking or thread parameters.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+static URL_RE: OnceLock<Regex> = OnceLock::new();
+
+/// More links than this in one document aren't recorded.
+const MAX_LINKS_PER_DOCUMENT: usize = 200;
+
+/// One document linking to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentReference {
+    pub from_id: i64,
+    pub from_uri: String,
+    pub from_source: String,
+    pub to_id: i64,
+    pub to_uri: String,
+    pub to_source: String,
+}
+
+pub(crate) async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'document_links'",
+    )
+    .fetch_one(pool)
+    .await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS document_links (\
+            doc_id INTEGER NOT NULL,\
+            url TEXT NOT NULL,\
+            base_url TEXT NOT NULL,\
+            PRIMARY KEY (doc_id, url)\
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_document_links_url ON document_links(url)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_document_links_base_url ON document_links(base_url)")
+        .execute(pool)
+        .await?;
+
+    if !exists {
+        backfill(pool).await?;
+    }
+    Ok(())
+}
+
+/// Record the links of documents stored before links were tracked.
+async fn backfill(pool: &SqlitePool) -> Result<()> {
+    let mut last_id = 0i64;
+    loop {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT id, body FROM documents WHERE id > ?1 AND deleted_at IS NULL ORDER BY id LIMIT 500",
+        )
+        .bind(last_id)
+        .fetch_all(pool)
+        .await?;
+        let Some((id, _)) = rows.last() else { break };
+        last_id = *id;
+        let mut tx = pool.begin().await?;
+        for (id, body) in &rows {
+            replace_links(&mut tx, *id, body).await?;
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// `(url, base_url)` for each distinct URL in `text`, where `base_url` drops
+/// the query and fragment. Any scheme counts, since some sources use their
+/// own (`slack://`, `notion://`) for document URIs.
+pub fn extract_urls(text: &str) -> Vec<(String, String)> {
+    // Stops at whitespace, brackets, quotes and Slack's `<url|label>` pipe
+    let re = URL_RE.get_or_init(|| {
+        Regex::new(r#"\b[a-z][a-z0-9+.-]*://[^\s<>()\[\]{}"'`|]+"#).expect("valid regex")
+    });
+    let mut seen = HashSet::new();
+    let mut urls = Vec::new();
+    for found in re.find_iter(text) {
+        let url = found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_', '~']);
+        if !seen.insert(url) {
+            continue;
+        }
+        let base = url.split(['?', '#']).next().unwrap_or(url).trim_end_matches('/');
+        urls.push((url.to_string(), base.to_string()));
+        if urls.len() == MAX_LINKS_PER_DOCUMENT {
+            break;
+        }
+    }
+    urls
+}
+
+/// Replace the recorded links of `doc_id` with the URLs in `body`.
+pub(crate) async fn replace_links(conn: &mut SqliteConnection, doc_id: i64, body: &str) -> Result<()> {
+    sqlx::query("DELETE FROM document_links WHERE doc_id = ?1")
+        .bind(doc_id)
+        .execute(&mut *conn)
+        .await?;
+    for (url, base_url) in extract_urls(body) {
+        sqlx::query("INSERT OR IGNORE INTO document_links (doc_id, url, base_url) VALUES (?1, ?2, ?3)")
+            .bind(doc_id)
+            .bind(url)
+            .bind(base_url)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// References from or to `doc_id` between live documents.
+pub(crate) async fn for_document(pool: &SqlitePool, doc_id: i64) -> Result<Vec<DocumentReference>> {
+    let rows: Vec<(i64, String, String, i64, String, String)> = sqlx::query_as(
+        "SELECT f.id, f.uri, f.source, t.id, t.uri, t.source \
+        FROM document_links l \
+        JOIN documents f ON f.id = l.doc_id \
+        JOIN documents t ON t.uri IN (l.url, l.base_url) \
+        WHERE l.doc_id = ?1 AND t.id != ?1 AND t.deleted_at IS NULL \
+        AND f.deleted_at IS NULL \
+        UNION \
+        SELECT f.id, f.uri, f.source, t.id, t.uri, t.source \
+        FROM documents t \
+        JOIN document_links l ON t.uri IN (l.url, l.base_url) \
+        JOIN documents f ON f.id = l.doc_id \
+        WHERE t.id = ?1 AND f.id != ?1 AND f.deleted_at IS NULL \
+        AND t.deleted_at IS NULL \
+        ORDER BY 1, 4",
+    )
+    .bind(doc_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(from_id, from_uri, from_source, to_id, to_uri, to_source)| DocumentReference {
+            from_id,
+            from_uri,
+            from_source,
+            to_id,
+            to_uri,
+            to_source,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, IngestionEngine};
+
+    fn doc(uri: &str, source: &str, body: &str) -> Document {
+        Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_extract_urls() {
+        let urls = extract_urls(
+            "See <https://github.com/acme/api/pull/7|PR 7>, and (https://github.com/acme/api/pull/7). \
+            Thread: slack://C1/123?thread=1#reply. Docs: https://notion.so/page/ Done!",
+        );
+        assert_eq!(
+            urls,
+            vec![
+                ("https://github.com/acme/api/pull/7".to_string(), "https://github.com/acme/api/pull/7".to_string()),
+                ("slack://C1/123?thread=1#reply".to_string(), "slack://C1/123".to_string()),
+                ("https://notion.so/page/".to_string(), "https://notion.so/page".to_string()),
+            ]
+        );
+        assert!(extract_urls("no links, just text: a://").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_references_resolve_in_either_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        // The thread links to the PR before the PR is synced, with tracking params
+        let thread = ingest
+            .upsert_document(&doc("slack://C1/1", "slack", "fix is in https://github.com/acme/api/pull/7?utm=x"))
+            .await
+            .unwrap();
+        assert!(ingest.document_references(thread).await.unwrap().is_empty());
+        let pr = ingest
+            .upsert_document(&doc("https://github.com/acme/api/pull/7", "github", "Fix retries"))
+            .await
+            .unwrap();
+
+        let expected = vec![DocumentReference {
+            from_id: thread,
+            from_uri: "slack://C1/1".to_string(),
+            from_source: "slack".to_string(),
+            to_id: pr,
+            to_uri: "https://github.com/acme/api/pull/7".to_string(),
+            to_source: "github".to_string(),
+        }];
+        assert_eq!(ingest.document_references(thread).await.unwrap(), expected);
+        assert_eq!(ingest.document_references(pr).await.unwrap(), expected);
+
+        // Editing the link away drops the reference
+        ingest.upsert_document(&doc("slack://C1/1", "slack", "never mind")).await.unwrap();
+        assert!(ingest.document_references(pr).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_documents_are_not_referenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let pr = ingest.upsert_document(&doc("github://acme/api/7", "github", "PR")).await.unwrap();
+        let thread = ingest
+            .upsert_document(&doc("slack://C1/1", "slack", "see github://acme/api/7 and slack://C1/1"))
+            .await
+            .unwrap();
+        // A document linking to itself isn't a reference
+        assert_eq!(ingest.document_references(thread).await.unwrap().len(), 1);
+
+        ingest.delete_documents(&[pr]).await.unwrap();
+        assert!(ingest.document_references(thread).await.unwrap().is_empty());
+    }
+}
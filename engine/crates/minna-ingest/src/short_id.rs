@@ -0,0 +1,86 @@
+//! Short, human-friendly document IDs such as `sl-4f2a`.
+//!
+//! A short ID is a two-letter source prefix followed by the document's row ID
+//! in hex. Row IDs survive re-syncs (upserts keep the row), so short IDs are
+//! stable for as long as the document is indexed.
+
+/// Two-letter prefix for a source.
+pub fn source_prefix(source: &str) -> String {
+    let known = match source {
+        "slack" => "sl",
+        "github" => "gh",
+        "linear" => "ln",
+        "notion" => "no",
+        "jira" => "ji",
+        "confluence" => "cf",
+        "google_drive" => "gd",
+        "google_calendar" => "gc",
+        "gmail" => "gm",
+        "minna" => "mn",
+        _ => "",
+    };
+    if !known.is_empty() {
+        return known.to_string();
+    }
+    let prefix: String = source
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(2)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if prefix.len() == 2 {
+        prefix
+    } else {
+        "dc".to_string()
+    }
+}
+
+/// Short ID for a document.
+pub fn encode(source: &str, doc_id: i64) -> String {
+    format!("{}-{:04x}", source_prefix(source), doc_id)
+}
+
+/// Split a short ID into its prefix and row ID. Returns None for anything
+/// that isn't shaped like a short ID (e.g. a URI).
+pub fn decode(short_id: &str) -> Option<(&str, i64)> {
+    let (prefix, hex) = short_id.split_once('-')?;
+    if prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+        return None;
+    }
+    if hex.len() < 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let id = i64::from_str_radix(hex, 16).ok()?;
+    Some((prefix, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(encode("slack", 0x4f2a), "sl-4f2a");
+        assert_eq!(encode("github", 7), "gh-0007");
+        assert_eq!(encode("mock", 0x12345), "mo-12345");
+        assert_eq!(encode("-", 1), "dc-0001");
+        assert_eq!(decode("sl-4f2a"), Some(("sl", 0x4f2a)));
+        assert_eq!(decode(&encode("gmail", 123_456)), Some(("gm", 123_456)));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_references() {
+        for reference in ["https://slack.com/archives/C1/p1", "sl-4f2", "slack-4f2a", "sl-4f2g", "SL-4f2a", "sl4f2a", ""] {
+            assert_eq!(decode(reference), None, "{}", reference);
+        }
+    }
+
+    #[test]
+    fn test_prefix_disambiguates_row_ids() {
+        // The same row ID under another source's prefix is another short ID
+        let (prefix, id) = decode("gh-4f2a").unwrap();
+        assert_eq!(id, 0x4f2a);
+        assert_ne!(prefix, source_prefix("slack"));
+        assert_eq!(source_prefix("Mock Data"), "mo");
+    }
+}
@@ -0,0 +1,186 @@
+//! Storage interface for documents.
+//!
+//! Providers write through [`DocumentStore`] rather than
+//! [`IngestionEngine`](crate::IngestionEngine) directly, so a backend other
+//! than the local SQLite database (e.g. Postgres for a team-shared
+//! deployment) can be plugged in. `IngestionEngine` is the SQLite
+//! implementation and the default.
+//!
+//! `minna_core::Storage` goes through the same trait for indexing, search,
+//! re-embedding and tombstone/limit maintenance.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::fts::SearchQuery;
+use crate::{Attachment, Document, DocumentReference, IngestionEngine, KeywordHit, Tombstone};
+
+#[async_trait]
+pub trait DocumentStore: Send + Sync {
+    /// Insert or replace a document by URI, returning its ID. The document
+    /// is prepared (transformed and redacted) first.
+    async fn upsert_document(&self, doc: &Document) -> Result<i64>;
+
+    /// Upsert many documents at once, all or nothing, returning their IDs in
+    /// order. Prepares them like `upsert_document`.
+    async fn upsert_documents_batch(&self, docs: &[Document]) -> Result<Vec<i64>>;
+
+    /// The document as `upsert_document` will store it. Embed this rather
+    /// than the original so noise and redacted text stay out of the vector
+    /// index.
+    fn prepare<'d>(&self, doc: &'d Document) -> Cow<'d, Document>;
+
+    async fn get_document_by_uri(&self, uri: &str) -> Result<Option<Document>>;
+
+    async fn fetch_documents_by_ids(&self, ids: &[i64]) -> Result<Vec<Document>>;
+
+    /// Links between a document and other stored documents, both directions.
+    async fn document_references(&self, doc_id: i64) -> Result<Vec<DocumentReference>>;
+
+    /// Keyword search ranked by relevance.
+    async fn search_query(&self, query: &SearchQuery, limit: usize) -> Result<Vec<KeywordHit>>;
+
+    async fn add_tag(&self, doc_id: i64, tag: &str) -> Result<()>;
+
+    /// Attach a small file to a stored document, replacing one of the same name.
+    async fn store_attachment(
+        &self,
+        doc_id: i64,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<Attachment>;
+
+    async fn get_sync_cursor(&self, provider: &str) -> Result<Option<String>>;
+
+    async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()>;
+
+    async fn mark_archived(
+        &self,
+        provider: &str,
+        resource_id: &str,
+        name: Option<&str>,
+        uri_prefix: &str,
+    ) -> Result<()>;
+
+    async fn unmark_archived(&self, provider: &str, resource_id: &str) -> Result<()>;
+
+    /// Soft-delete every document from `source`, returning how many.
+    async fn delete_documents_by_source(&self, source: &str) -> Result<u64>;
+
+    /// Soft-deleted documents awaiting purge, oldest first.
+    async fn pending_tombstones(&self, limit: usize) -> Result<Vec<Tombstone>>;
+
+    /// Purge soft-deleted documents and their tombstones, returning the IDs
+    /// actually purged: a document revived since its tombstone is kept.
+    async fn purge_tombstones(&self, doc_ids: &[i64]) -> Result<Vec<i64>>;
+
+    /// Live documents per source.
+    async fn document_counts_by_source(&self) -> Result<Vec<(String, i64)>>;
+
+    /// Soft-delete the oldest-updated documents of `source` until it is
+    /// within the limits, skipping `keep`. Returns the evicted IDs.
+    async fn evict_oldest(
+        &self,
+        source: &str,
+        max_documents: Option<u64>,
+        max_bytes: Option<u64>,
+        keep: &HashSet<i64>,
+    ) -> Result<Vec<i64>>;
+}
+
+#[async_trait]
+impl DocumentStore for IngestionEngine {
+    async fn upsert_document(&self, doc: &Document) -> Result<i64> {
+        IngestionEngine::upsert_document(self, doc).await
+    }
+
+    async fn upsert_documents_batch(&self, docs: &[Document]) -> Result<Vec<i64>> {
+        IngestionEngine::upsert_documents_batch(self, docs).await
+    }
+
+    fn prepare<'d>(&self, doc: &'d Document) -> Cow<'d, Document> {
+        IngestionEngine::prepare(self, doc)
+    }
+
+    async fn get_document_by_uri(&self, uri: &str) -> Result<Option<Document>> {
+        IngestionEngine::get_document_by_uri(self, uri).await
+    }
+
+    async fn fetch_documents_by_ids(&self, ids: &[i64]) -> Result<Vec<Document>> {
+        IngestionEngine::fetch_documents_by_ids(self, ids).await
+    }
+
+    async fn document_references(&self, doc_id: i64) -> Result<Vec<DocumentReference>> {
+        IngestionEngine::document_references(self, doc_id).await
+    }
+
+    async fn search_query(&self, query: &SearchQuery, limit: usize) -> Result<Vec<KeywordHit>> {
+        IngestionEngine::search_query(self, query, limit).await
+    }
+
+    async fn add_tag(&self, doc_id: i64, tag: &str) -> Result<()> {
+        IngestionEngine::add_tag(self, doc_id, tag).await
+    }
+
+    async fn store_attachment(
+        &self,
+        doc_id: i64,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<Attachment> {
+        IngestionEngine::store_attachment(self, doc_id, name, mime_type, data).await
+    }
+
+    async fn get_sync_cursor(&self, provider: &str) -> Result<Option<String>> {
+        IngestionEngine::get_sync_cursor(self, provider).await
+    }
+
+    async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()> {
+        IngestionEngine::set_sync_cursor(self, provider, cursor).await
+    }
+
+    async fn mark_archived(
+        &self,
+        provider: &str,
+        resource_id: &str,
+        name: Option<&str>,
+        uri_prefix: &str,
+    ) -> Result<()> {
+        IngestionEngine::mark_archived(self, provider, resource_id, name, uri_prefix).await
+    }
+
+    async fn unmark_archived(&self, provider: &str, resource_id: &str) -> Result<()> {
+        IngestionEngine::unmark_archived(self, provider, resource_id).await
+    }
+
+    async fn delete_documents_by_source(&self, source: &str) -> Result<u64> {
+        IngestionEngine::delete_documents_by_source(self, source).await
+    }
+
+    async fn pending_tombstones(&self, limit: usize) -> Result<Vec<Tombstone>> {
+        IngestionEngine::pending_tombstones(self, limit).await
+    }
+
+    async fn purge_tombstones(&self, doc_ids: &[i64]) -> Result<Vec<i64>> {
+        IngestionEngine::purge_tombstones(self, doc_ids).await
+    }
+
+    async fn document_counts_by_source(&self) -> Result<Vec<(String, i64)>> {
+        IngestionEngine::document_counts_by_source(self).await
+    }
+
+    async fn evict_oldest(
+        &self,
+        source: &str,
+        max_documents: Option<u64>,
+        max_bytes: Option<u64>,
+        keep: &HashSet<i64>,
+    ) -> Result<Vec<i64>> {
+        IngestionEngine::evict_oldest(self, source, max_documents, max_bytes, keep).await
+    }
+}
@@ -0,0 +1,170 @@
+//! Audio transcription via whisper.cpp (`transcription` feature).
+//!
+//! Like OCR, this shells out to the whisper.cpp CLI instead of linking it.
+//! Non-WAV input is converted to 16 kHz mono WAV with ffmpeg first.
+//!
+//! Configure with `MINNA_WHISPER_MODEL` (path to a ggml model, required),
+//! plus optional `MINNA_WHISPER_PATH`, `MINNA_FFMPEG_PATH` and
+//! `MINNA_WHISPER_TIMEOUT_SECS` (default 1800).
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use tracing::info;
+
+use crate::ocr::{env_duration, env_path, run_with_timeout, ScratchDir};
+
+/// Default limit for a single ffmpeg or whisper run; long meetings take a while.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Audio types we can transcribe.
+const AUDIO_MIMES: &[&str] = &[
+    "audio/wav",
+    "audio/x-wav",
+    "audio/mpeg",
+    "audio/mp4",
+    "audio/x-m4a",
+    "audio/m4a",
+    "audio/aac",
+    "audio/ogg",
+    "audio/webm",
+    "audio/flac",
+];
+
+static CONFIG: OnceLock<Option<TranscribeConfig>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct TranscribeConfig {
+    pub whisper: PathBuf,
+    pub ffmpeg: PathBuf,
+    pub model: PathBuf,
+    /// How long a single subprocess may run before it is killed.
+    pub timeout: Duration,
+}
+
+impl TranscribeConfig {
+    /// Read settings from the environment. Returns None if no model is configured.
+    pub fn from_env() -> Option<Self> {
+        let model = PathBuf::from(std::env::var_os("MINNA_WHISPER_MODEL")?);
+        let config = Self {
+            whisper: env_path("MINNA_WHISPER_PATH", "whisper-cli"),
+            ffmpeg: env_path("MINNA_FFMPEG_PATH", "ffmpeg"),
+            model,
+            timeout: env_duration("MINNA_WHISPER_TIMEOUT_SECS", DEFAULT_TIMEOUT),
+        };
+        info!("Audio transcription enabled with model {}", config.model.display());
+        Some(config)
+    }
+
+    /// Process-wide configuration, read once from the environment.
+    pub fn global() -> Option<&'static TranscribeConfig> {
+        CONFIG.get_or_init(Self::from_env).as_ref()
+    }
+
+    /// Transcribe an audio file to plain text.
+    pub fn transcribe(&self, mime_type: &str, bytes: &[u8]) -> Result<String> {
+        let scratch = ScratchDir::new()?;
+        let input = scratch.path().join("input");
+        std::fs::write(&input, bytes)?;
+
+        let wav = if matches!(mime_type, "audio/wav" | "audio/x-wav") {
+            input
+        } else {
+            let wav = scratch.path().join("audio.wav");
+            let mut command = Command::new(&self.ffmpeg);
+            command
+                .args(["-nostdin", "-loglevel", "error", "-i"])
+                .arg(&input)
+                .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+                .arg(&wav);
+            let output = run_with_timeout(&mut command, self.timeout)
+                .with_context(|| format!("failed to run {}", self.ffmpeg.display()))?;
+            if !output.status.success() {
+                return Err(anyhow!("ffmpeg exited with {}", output.status));
+            }
+            wav
+        };
+
+        let mut command = Command::new(&self.whisper);
+        command
+            .arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(&wav)
+            .args(["--no-timestamps", "--no-prints"]);
+        let output = run_with_timeout(&mut command, self.timeout)
+            .with_context(|| format!("failed to run {}", self.whisper.display()))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "whisper exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Whether this MIME type is audio we can transcribe.
+pub fn is_audio(mime_type: &str) -> bool {
+    AUDIO_MIMES.contains(&mime_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Instant;
+
+    use super::*;
+
+    fn config(whisper: PathBuf, timeout: Duration) -> TranscribeConfig {
+        TranscribeConfig {
+            whisper,
+            ffmpeg: PathBuf::from("/nonexistent/ffmpeg"),
+            model: PathBuf::from("/nonexistent/ggml-base.bin"),
+            timeout,
+        }
+    }
+
+    #[cfg(unix)]
+    fn script(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("fake-whisper");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_missing_binary_is_an_error() {
+        let whisper = config(PathBuf::from("/nonexistent/whisper-cli"), DEFAULT_TIMEOUT);
+        let err = whisper.transcribe("audio/wav", b"RIFF").unwrap_err();
+        assert!(err.to_string().contains("failed to run /nonexistent/whisper-cli"), "{err:#}");
+
+        // Non-WAV input goes through ffmpeg first
+        let err = whisper.transcribe("audio/mpeg", b"ID3").unwrap_err();
+        assert!(err.to_string().contains("failed to run /nonexistent/ffmpeg"), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wav_is_passed_straight_to_whisper() {
+        let dir = tempfile::tempdir().unwrap();
+        let whisper = config(script(dir.path(), "echo \" hello meeting\""), DEFAULT_TIMEOUT);
+        assert_eq!(whisper.transcribe("audio/wav", b"RIFF").unwrap(), " hello meeting\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hung_binary_is_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let whisper = config(script(dir.path(), "exec sleep 30"), Duration::from_millis(200));
+        let started = Instant::now();
+        let err = whisper.transcribe("audio/wav", b"RIFF").unwrap_err();
+        assert!(format!("{err:#}").contains("timed out"), "{err:#}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,248 @@
+//! Clean-up transforms applied to documents before they are stored.
+//!
+//! Each source gets a chain of transforms, configured in the `[transforms]`
+//! section of `providers.toml` by source name, with `"*"` covering sources
+//! that aren't listed:
+//!
+//! ```toml
+//! [transforms]
+//! "*" = ["normalize_whitespace"]
+//! gmail = ["collapse_quotes", "strip_signatures", "normalize_whitespace"]
+//! ```
+//!
+//! The chain runs before redaction (see [`crate::redact`]), in
+//! [`IngestionEngine::prepare`](crate::IngestionEngine::prepare), so the
+//! cleaned text is what gets stored and embedded. A document may pass through
+//! the chain more than once (when indexed, then again on upsert), so
+//! transforms must be idempotent.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{bail, Result};
+use regex::Regex;
+
+use crate::Document;
+
+static BLANK_LINES_RE: OnceLock<Regex> = OnceLock::new();
+static ATTRIBUTION_RE: OnceLock<Regex> = OnceLock::new();
+static FORWARD_HEADER_RE: OnceLock<Regex> = OnceLock::new();
+static SENT_FROM_RE: OnceLock<Regex> = OnceLock::new();
+
+fn cached_regex(lock: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    lock.get_or_init(|| Regex::new(pattern).expect("valid regex"))
+}
+
+/// A signature after its `-- ` delimiter longer than this is probably not a
+/// signature, so it's kept.
+const MAX_SIGNATURE_LINES: usize = 12;
+
+/// Replaces quoted history removed by `collapse_quotes`.
+pub const QUOTE_MARKER: &str = "[quoted text collapsed]";
+
+/// One step of a transform chain.
+pub trait Transform: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// The transformed body, or None to leave it unchanged.
+    fn apply(&self, body: &str) -> Option<String>;
+}
+
+/// Transform names by source, as in the `[transforms]` section.
+pub type TransformConfig = HashMap<String, Vec<String>>;
+
+/// Source name covering sources without their own chain.
+pub const ALL_SOURCES: &str = "*";
+
+/// A transform by its configuration name.
+pub fn builtin(name: &str) -> Option<Arc<dyn Transform>> {
+    let transform: Arc<dyn Transform> = match name {
+        "normalize_whitespace" => Arc::new(NormalizeWhitespace),
+        "strip_signatures" => Arc::new(StripSignatures),
+        "collapse_quotes" => Arc::new(CollapseQuotes),
+        _ => return None,
+    };
+    Some(transform)
+}
+
+/// Transform chains by source.
+#[derive(Clone, Default)]
+pub struct TransformChain {
+    chains: HashMap<String, Vec<Arc<dyn Transform>>>,
+}
+
+impl TransformChain {
+    /// Build the chains in `config`. Errors on an unknown transform name.
+    pub fn from_config(config: &TransformConfig) -> Result<Self> {
+        let mut chain = Self::default();
+        for (source, names) in config {
+            for name in names {
+                match builtin(name) {
+                    Some(transform) => chain = chain.with(source, transform),
+                    None => bail!("Unknown transform '{}' for source '{}'", name, source),
+                }
+            }
+        }
+        Ok(chain)
+    }
+
+    /// Append `transform` to the chain of `source` (or [`ALL_SOURCES`]).
+    pub fn with(mut self, source: &str, transform: Arc<dyn Transform>) -> Self {
+        self.chains.entry(source.to_string()).or_default().push(transform);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chains.values().all(Vec::is_empty)
+    }
+
+    /// Names of the transforms applied to documents from `source`.
+    pub fn names_for(&self, source: &str) -> Vec<&str> {
+        self.chain_for(source).iter().map(|t| t.name()).collect()
+    }
+
+    fn chain_for(&self, source: &str) -> &[Arc<dyn Transform>] {
+        self.chains
+            .get(source)
+            .or_else(|| self.chains.get(ALL_SOURCES))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Run the document's body through its source's chain. None if nothing
+    /// changed.
+    pub fn apply(&self, doc: &Document) -> Option<String> {
+        let mut body: Option<String> = None;
+        for transform in self.chain_for(&doc.source) {
+            let current = body.as_deref().unwrap_or(&doc.body);
+            if let Some(next) = transform.apply(current) {
+                body = Some(next);
+            }
+        }
+        body.filter(|body| *body != doc.body)
+    }
+}
+
+/// Unify line endings and odd spaces, drop trailing whitespace and collapse
+/// runs of blank lines. Indentation is left alone.
+pub struct NormalizeWhitespace;
+
+impl Transform for NormalizeWhitespace {
+    fn name(&self) -> &str {
+        "normalize_whitespace"
+    }
+
+    fn apply(&self, body: &str) -> Option<String> {
+        let text = body
+            .replace("\r\n", "\n")
+            .replace(['\r', '\u{a0}'], " ")
+            .replace(['\u{200b}', '\u{feff}'], "");
+        let text = text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+        let text = cached_regex(&BLANK_LINES_RE, r"\n{3,}").replace_all(&text, "\n\n");
+        let text = text.trim();
+        (text != body).then(|| text.to_string())
+    }
+}
+
+/// Cut a trailing email signature: everything after a `-- ` delimiter line
+/// when it's short, and "Sent from my iPhone" style footers.
+pub struct StripSignatures;
+
+impl Transform for StripSignatures {
+    fn name(&self) -> &str {
+        "strip_signatures"
+    }
+
+    fn apply(&self, body: &str) -> Option<String> {
+        let sent_from = cached_regex(
+            &SENT_FROM_RE,
+            r"(?i)^(sent from my \w+|sent from mail for windows|get outlook for (ios|android))\b",
+        );
+        let mut lines: Vec<&str> = body.lines().collect();
+        let original = lines.len();
+
+        if let Some(delimiter) = lines.iter().rposition(|line| *line == "-- " || *line == "--") {
+            if lines.len() - delimiter - 1 <= MAX_SIGNATURE_LINES {
+                lines.truncate(delimiter);
+            }
+        }
+        lines.retain(|line| !sent_from.is_match(line.trim()));
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        (lines.len() != original).then(|| lines.join("\n"))
+    }
+}
+
+/// Replace quoted email history (`>` lines with their "On ... wrote:"
+/// attribution, or everything from a forwarded/original message header on)
+/// with a single marker line.
+pub struct CollapseQuotes;
+
+impl Transform for CollapseQuotes {
+    fn name(&self) -> &str {
+        "collapse_quotes"
+    }
+
+    fn apply(&self, body: &str) -> Option<String> {
+        let attribution = cached_regex(&ATTRIBUTION_RE, r"^On .{4,200} wrote:$");
+        let forward_header = cached_regex(
+            &FORWARD_HEADER_RE,
+            r"^(-{2,}\s*(Original Message|Forwarded message)\s*-{2,}|From: .+)$",
+        );
+
+        let lines: Vec<&str> = body.lines().collect();
+        let mut kept: Vec<&str> = Vec::with_capacity(lines.len());
+        let mut changed = false;
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i].trim();
+            // Outlook-style history runs to the end of the message
+            if forward_header.is_match(line) && is_header_block(&lines[i..]) {
+                kept.push(QUOTE_MARKER);
+                changed = true;
+                break;
+            }
+            let quote_start = if attribution.is_match(line) {
+                lines.get(i + 1).map(|next| next.trim_start().starts_with('>'))
+                    .filter(|quoted| *quoted)
+                    .map(|_| i + 1)
+            } else if line.starts_with('>') {
+                Some(i)
+            } else {
+                None
+            };
+            match quote_start {
+                Some(start) => {
+                    let mut end = start;
+                    while end < lines.len() && lines[end].trim_start().starts_with('>') {
+                        end += 1;
+                    }
+                    if kept.last() != Some(&QUOTE_MARKER) {
+                        kept.push(QUOTE_MARKER);
+                    }
+                    changed = true;
+                    i = end;
+                }
+                None => {
+                    kept.push(lines[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        changed.then(|| kept.join("\n"))
+    }
+}
+
+/// A forwarded/original message header: the delimiter or `From:` line
+/// followed within a few lines by `Sent:`/`Date:` and `Subject:`.
+fn is_header_block(lines: &[&str]) -> bool {
+    let head: Vec<&str> = lines.iter().take(6).map(|line| line.trim()).collect();
+    if head.first().is_some_and(|line| line.starts_with("--")) {
+        return true;
+    }
+    let has = |prefix: &str| head.iter().any(|line| line.starts_with(prefix));
+    (has("Sent:") || has("Date:")) && has("Subject:")
+}
@@ -0,0 +1,216 @@
+//! Tool alias routing for the MCP handler.
+//!
+//! Different agent ecosystems expect different tool names for the same
+//! capability. Aliases expose an existing tool under another name, optionally
+//! with default parameters and a deprecation note.
+//!
+//! Aliases are configured via `tool_aliases.toml` in the Minna data directory:
+//!
+//! A project can layer its own file on top by pointing `MINNA_TOOL_ALIASES` at
+//! it; its entries replace same-named user and built-in aliases.
+//!
+//! ```toml
+//! [aliases.search_memory]
+//! tool = "get_context"
+//! defaults = { limit = 10 }
+//!
+//! [aliases.recall]
+//! tool = "get_context"
+//! deprecated = "use get_context instead"
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// A single alias entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolAlias {
+    /// Name of the tool this alias routes to.
+    pub tool: String,
+
+    /// Parameters applied when the caller doesn't provide them.
+    #[serde(default)]
+    pub defaults: serde_json::Map<String, serde_json::Value>,
+
+    /// Deprecation note. When set, responses carry a warning.
+    #[serde(default)]
+    pub deprecated: Option<String>,
+}
+
+impl ToolAlias {
+    /// Create an alias with no defaults.
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            defaults: serde_json::Map::new(),
+            deprecated: None,
+        }
+    }
+
+    /// Merge alias defaults into request params. Caller-provided values win.
+    ///
+    /// Non-object params (e.g. a bare query string) are passed through untouched.
+    pub fn apply_defaults(&self, params: serde_json::Value) -> serde_json::Value {
+        if self.defaults.is_empty() {
+            return params;
+        }
+        match params {
+            serde_json::Value::Object(mut map) => {
+                for (key, value) in &self.defaults {
+                    map.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+                serde_json::Value::Object(map)
+            }
+            serde_json::Value::Null => serde_json::Value::Object(self.defaults.clone()),
+            other => other,
+        }
+    }
+}
+
+/// Routing table mapping alias names to tools.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolAliases {
+    #[serde(default)]
+    pub aliases: HashMap<String, ToolAlias>,
+}
+
+impl ToolAliases {
+    /// Load aliases from TOML files, falling back to the built-in table.
+    ///
+    /// Files are layered in order on top of the defaults, so a later file
+    /// replaces same-named aliases from earlier ones. Missing files are skipped.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut table = Self::with_defaults();
+        for path in paths {
+            table.layer(path)?;
+        }
+        Ok(table)
+    }
+
+    /// Alias files for a daemon rooted at `base_dir`: the user file, then the
+    /// project file from `MINNA_TOOL_ALIASES` if set.
+    pub fn default_paths(base_dir: &Path) -> Vec<PathBuf> {
+        let mut paths = vec![base_dir.join("tool_aliases.toml")];
+        if let Some(project) = std::env::var_os("MINNA_TOOL_ALIASES") {
+            paths.push(PathBuf::from(project));
+        }
+        paths
+    }
+
+    fn layer(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read alias file: {}", path.display()))?;
+        let parsed: ToolAliases = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse alias file: {}", path.display()))?;
+
+        self.aliases.extend(parsed.aliases);
+        Ok(())
+    }
+
+    /// Built-in aliases for common agent conventions.
+    pub fn with_defaults() -> Self {
+        let mut aliases = HashMap::new();
+        aliases.insert("search_memory".to_string(), ToolAlias::new("get_context"));
+        aliases.insert("read_document".to_string(), ToolAlias::new("read_resource"));
+        Self { aliases }
+    }
+
+    /// Look up an alias by name.
+    pub fn resolve(&self, name: &str) -> Option<&ToolAlias> {
+        self.aliases.get(name)
+    }
+
+    /// Register or replace an alias.
+    pub fn insert(&mut self, name: impl Into<String>, alias: ToolAlias) {
+        self.aliases.insert(name.into(), alias);
+    }
+}
+
+impl Default for ToolAliases {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn alias_with_defaults(defaults: serde_json::Value) -> ToolAlias {
+        let mut alias = ToolAlias::new("get_context");
+        alias.defaults = defaults.as_object().unwrap().clone();
+        alias
+    }
+
+    #[test]
+    fn test_apply_defaults_fills_missing_params() {
+        let alias = alias_with_defaults(json!({"limit": 10, "mode": "hybrid"}));
+        let params = alias.apply_defaults(json!({"query": "roadmap", "limit": 3}));
+        assert_eq!(params, json!({"query": "roadmap", "limit": 3, "mode": "hybrid"}));
+    }
+
+    #[test]
+    fn test_apply_defaults_non_object_params() {
+        let alias = alias_with_defaults(json!({"limit": 10}));
+        assert_eq!(alias.apply_defaults(serde_json::Value::Null), json!({"limit": 10}));
+        assert_eq!(alias.apply_defaults(json!("bare query")), json!("bare query"));
+        assert_eq!(ToolAlias::new("get_context").apply_defaults(json!({})), json!({}));
+    }
+
+    #[test]
+    fn test_load_layers_user_then_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("user.toml");
+        let project = dir.path().join("project.toml");
+        std::fs::write(
+            &user,
+            r#"
+[aliases.search_memory]
+tool = "get_context"
+defaults = { limit = 5 }
+
+[aliases.recall]
+tool = "get_context"
+deprecated = "use get_context instead"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"
+[aliases.search_memory]
+tool = "get_context"
+defaults = { limit = 20 }
+"#,
+        )
+        .unwrap();
+
+        let table = ToolAliases::load(&[user.clone(), project, dir.path().join("missing.toml")]).unwrap();
+        // Built-in alias untouched by either file
+        assert_eq!(table.resolve("read_document").unwrap().tool, "read_resource");
+        // User alias survives, project overrides the shared name
+        assert!(table.resolve("recall").unwrap().deprecated.is_some());
+        assert_eq!(table.resolve("search_memory").unwrap().defaults["limit"], json!(20));
+
+        let user_only = ToolAliases::load(&[user]).unwrap();
+        assert_eq!(user_only.resolve("search_memory").unwrap().defaults["limit"], json!(5));
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool_aliases.toml");
+        std::fs::write(&path, "[aliases.broken]\ndefaults = 3").unwrap();
+        let err = ToolAliases::load(&[path]).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse alias file"), "{err}");
+    }
+}
@@ -0,0 +1,229 @@
+//! Source freshness reporting and agent-initiated syncs.
+//!
+//! `check_freshness` tells an agent how old each source's index is and whether
+//! it may ask for a refresh right now; `request_sync` kicks off that refresh,
+//! and `trigger_sync` one of a chosen [`SyncMode`] for allowlisted providers.
+//! Whether a sync is allowed is decided by [`FreshnessPolicy`] together with
+//! the scheduler budget exposed through a [`SyncController`].
+
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// Resolves when an accepted sync finishes, with its summary or error message.
+pub type SyncCompletion = oneshot::Receiver<std::result::Result<serde_json::Value, String>>;
+
+/// Bridge to whatever owns the sync scheduler (the daemon).
+#[async_trait]
+pub trait SyncController: Send + Sync {
+    /// Sources that can be synced on demand.
+    fn sources(&self) -> Vec<String>;
+
+    /// Remaining and total API budget for the current hour.
+    async fn budget(&self) -> (u32, u32);
+
+    /// Whether a sync for this source is queued or running.
+    async fn is_syncing(&self, source: &str) -> bool;
+
+    /// Start a sync in the background. Returns once the sync has been accepted.
+    async fn start_sync(&self, source: &str, mode: SyncMode) -> Result<SyncCompletion>;
+}
+
+/// How much an on-demand sync fetches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// What the scheduler fetches for close sources: the last week, in full.
+    #[default]
+    Recent,
+    /// Everything new since the source's cursor.
+    Incremental,
+    /// The last 90 days, ignoring the cursor.
+    Full,
+}
+
+/// How old a source's index is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Staleness {
+    /// Synced within `fresh_within`.
+    Fresh,
+    /// Older than `fresh_within` but within `stale_after`.
+    Aging,
+    /// Older than `stale_after`.
+    Stale,
+    /// Never synced.
+    Never,
+}
+
+/// Thresholds that govern staleness and on-demand sync.
+#[derive(Debug, Clone)]
+pub struct FreshnessPolicy {
+    /// Sources synced more recently than this are fresh (default: 1 hour).
+    pub fresh_within: Duration,
+
+    /// Sources older than this are stale (default: 24 hours).
+    pub stale_after: Duration,
+
+    /// Minimum time between on-demand syncs of the same source (default: 5 minutes).
+    pub min_sync_interval: Duration,
+
+    /// Budget that must remain before an on-demand sync is allowed (default: 50 calls).
+    pub min_budget: u32,
+}
+
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self {
+            fresh_within: Duration::from_secs(60 * 60),
+            stale_after: Duration::from_secs(24 * 60 * 60),
+            min_sync_interval: Duration::from_secs(5 * 60),
+            min_budget: 50,
+        }
+    }
+}
+
+impl FreshnessPolicy {
+    /// Classify a source by its last sync time.
+    pub fn classify(&self, last_sync: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Staleness {
+        let Some(last) = last_sync else {
+            return Staleness::Never;
+        };
+        let age = (now - last).to_std().unwrap_or_default();
+        if age <= self.fresh_within {
+            Staleness::Fresh
+        } else if age <= self.stale_after {
+            Staleness::Aging
+        } else {
+            Staleness::Stale
+        }
+    }
+
+    /// Decide whether an on-demand sync may run. Returns the refusal reason if not.
+    pub fn check_sync(
+        &self,
+        last_sync: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        syncing: bool,
+        budget_remaining: u32,
+    ) -> Option<String> {
+        if syncing {
+            return Some("sync already in progress".to_string());
+        }
+        if budget_remaining < self.min_budget {
+            return Some(format!(
+                "hourly budget nearly exhausted ({} calls remaining)",
+                budget_remaining
+            ));
+        }
+        if let Some(last) = last_sync {
+            let age = (now - last).to_std().unwrap_or_default();
+            if age < self.min_sync_interval {
+                return Some(format!(
+                    "synced {}s ago, minimum interval is {}s",
+                    age.as_secs(),
+                    self.min_sync_interval.as_secs()
+                ));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CheckFreshnessParams {
+    /// Sources to check. Empty means all syncable sources.
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequestSyncParams {
+    pub source: String,
+    /// Stream progress events until the sync finishes.
+    #[serde(default)]
+    pub stream: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TriggerSyncParams {
+    pub provider: String,
+    #[serde(default = "default_trigger_mode")]
+    pub mode: SyncMode,
+}
+
+fn default_trigger_mode() -> SyncMode {
+    SyncMode::Incremental
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TriggerSyncResult {
+    pub provider: String,
+    pub mode: SyncMode,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceFreshness {
+    pub source: String,
+    pub last_sync: Option<DateTime<Utc>>,
+    pub age_secs: Option<i64>,
+    pub staleness: Staleness,
+    pub sync_allowed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FreshnessResult {
+    pub sources: Vec<SourceFreshness>,
+    pub budget_remaining: Option<u32>,
+    pub budget_total: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequestSyncResult {
+    pub source: String,
+    pub status: String,
+}
+
+/// Whether a progress event's provider belongs to a source.
+///
+/// Providers report sub-sources under their own names (e.g. `google_drive`,
+/// `jira`), so match those as well.
+pub fn progress_matches(source: &str, provider: &str) -> bool {
+    if provider == source {
+        return true;
+    }
+    match source {
+        "atlassian" => provider == "jira" || provider == "confluence",
+        _ => provider
+            .strip_prefix(source)
+            .is_some_and(|rest| rest.starts_with('_')),
+    }
+}
+
+/// Cursor keys written to `sync_state` by each provider.
+///
+/// Most providers store a single cursor under their own name; a few split it.
+pub fn cursor_keys(source: &str) -> Vec<&str> {
+    match source {
+        "google" => vec!["google_drive", "google_calendar", "gmail"],
+        "github" => vec!["github_cursor"],
+        other => vec![other],
+    }
+}
+
+/// Last sync time for a source, taking the oldest of its cursors.
+pub fn last_sync_for(source: &str, sync_times: &[(String, DateTime<Utc>)]) -> Option<DateTime<Utc>> {
+    let keys = cursor_keys(source);
+    sync_times
+        .iter()
+        .filter(|(key, _)| keys.contains(&key.as_str()))
+        .map(|(_, time)| *time)
+        .min()
+}
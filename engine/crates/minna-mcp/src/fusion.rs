@@ -0,0 +1,155 @@
+//! How `get_context` merges its semantic and keyword rankings.
+//!
+//! Reciprocal Rank Fusion scores a document by its position in each list
+//! rather than by the raw scores, which live on unrelated scales (cosine
+//! similarity vs. BM25). Weighted fusion keeps the older behaviour: the
+//! semantic score plus a bonus that shrinks with the keyword rank.
+
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FusionMethod {
+    /// `weight / (k + rank)` summed over both lists.
+    Rrf,
+    /// `weight * similarity` plus `weight / rank` for keyword hits.
+    Weighted,
+}
+
+/// Settings for merging semantic and keyword results.
+#[derive(Debug, Clone)]
+pub struct FusionConfig {
+    /// Fusion method (default: RRF)
+    pub method: FusionMethod,
+    /// RRF rank constant; larger values flatten the difference between
+    /// top and lower ranks (default: 60)
+    pub rrf_k: f32,
+    /// Weight of the semantic ranking (default: 1.0)
+    pub semantic_weight: f32,
+    /// Weight of the keyword ranking (default: 1.0)
+    pub keyword_weight: f32,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            method: FusionMethod::Rrf,
+            rrf_k: 60.0,
+            semantic_weight: 1.0,
+            keyword_weight: 1.0,
+        }
+    }
+}
+
+impl FusionConfig {
+    /// The pre-RRF scoring: `0.7 * similarity + 0.3 / rank`.
+    pub fn weighted() -> Self {
+        Self {
+            method: FusionMethod::Weighted,
+            rrf_k: 60.0,
+            semantic_weight: 0.7,
+            keyword_weight: 0.3,
+        }
+    }
+
+    /// Read `MINNA_FUSION` (`rrf` or `weighted`), `MINNA_FUSION_K`,
+    /// `MINNA_FUSION_SEMANTIC_WEIGHT` and `MINNA_FUSION_KEYWORD_WEIGHT`,
+    /// falling back to the method's defaults.
+    pub fn from_env() -> Self {
+        let mut config = match std::env::var("MINNA_FUSION").ok().as_deref() {
+            Some(method) if method.eq_ignore_ascii_case("weighted") => Self::weighted(),
+            Some(method) if !method.eq_ignore_ascii_case("rrf") => {
+                warn!("Unknown MINNA_FUSION {:?}, using rrf", method);
+                Self::default()
+            }
+            _ => Self::default(),
+        };
+        if let Some(k) = env_f32("MINNA_FUSION_K") {
+            config.rrf_k = k;
+        }
+        if let Some(weight) = env_f32("MINNA_FUSION_SEMANTIC_WEIGHT") {
+            config.semantic_weight = weight;
+        }
+        if let Some(weight) = env_f32("MINNA_FUSION_KEYWORD_WEIGHT") {
+            config.keyword_weight = weight;
+        }
+        config
+    }
+
+    /// Contribution of the semantic hit at `rank` (0-based) with cosine
+    /// similarity `similarity`.
+    pub fn semantic(&self, rank: usize, similarity: f32) -> f32 {
+        match self.method {
+            FusionMethod::Rrf => self.semantic_weight / (self.rrf_k + rank as f32 + 1.0),
+            FusionMethod::Weighted => self.semantic_weight * similarity,
+        }
+    }
+
+    /// Contribution of the keyword hit at `rank` (0-based).
+    pub fn keyword(&self, rank: usize) -> f32 {
+        match self.method {
+            FusionMethod::Rrf => self.keyword_weight / (self.rrf_k + rank as f32 + 1.0),
+            FusionMethod::Weighted => self.keyword_weight / (rank as f32 + 1.0),
+        }
+    }
+}
+
+fn env_f32(name: &str) -> Option<f32> {
+    let value = std::env::var(name).ok()?;
+    match value.parse::<f32>() {
+        Ok(parsed) if parsed.is_finite() && parsed >= 0.0 => Some(parsed),
+        _ => {
+            warn!("Ignoring invalid {} {:?}", name, value);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fused ranking of two 0-based ranked lists of document ids.
+    fn fuse(config: &FusionConfig, semantic: &[(&'static str, f32)], keyword: &[&'static str]) -> Vec<&'static str> {
+        let mut scores: Vec<(&str, f32)> = Vec::new();
+        let mut add = |id, score| match scores.iter_mut().find(|(seen, _)| *seen == id) {
+            Some((_, total)) => *total += score,
+            None => scores.push((id, score)),
+        };
+        for (rank, (id, similarity)) in semantic.iter().enumerate() {
+            add(*id, config.semantic(rank, *similarity));
+        }
+        for (rank, id) in keyword.iter().enumerate() {
+            add(*id, config.keyword(rank));
+        }
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_rrf_scores() {
+        let rrf = FusionConfig::default();
+        assert_eq!(rrf.semantic(0, 0.9), 1.0 / 61.0);
+        assert_eq!(rrf.keyword(2), 1.0 / 63.0);
+        // Raw similarity doesn't matter, only the rank
+        assert_eq!(rrf.semantic(4, 0.1), rrf.semantic(4, 0.99));
+
+        let tuned = FusionConfig { rrf_k: 10.0, keyword_weight: 2.0, ..FusionConfig::default() };
+        assert_eq!(tuned.keyword(0), 2.0 / 11.0);
+    }
+
+    #[test]
+    fn test_weighted_matches_legacy_scoring() {
+        let weighted = FusionConfig::weighted();
+        assert!((weighted.semantic(3, 0.8) - 0.7 * 0.8).abs() < 1e-6);
+        assert!((weighted.keyword(1) - 0.3 / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rrf_favours_documents_in_both_lists() {
+        let semantic = [("a", 0.95), ("b", 0.94), ("c", 0.60)];
+        let keyword = ["d", "c"];
+        // A high similarity dominates weighted fusion; RRF rewards agreement
+        assert_eq!(fuse(&FusionConfig::weighted(), &semantic, &keyword), vec!["a", "b", "c", "d"]);
+        assert_eq!(fuse(&FusionConfig::default(), &semantic, &keyword), vec!["c", "a", "d", "b"]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Localhost HTTP transport for the MCP handler.
+//!
+//! For clients and browser-based agents that can't open unix sockets, one
+//! endpoint, `/mcp`, follows the MCP Streamable HTTP transport:
+//!
+//! - `POST /mcp` with a JSON-RPC message returns its response as JSON;
+//!   notifications get `202 Accepted`.
+//! - `POST /mcp` with a `{tool, params}` request returns its response as
+//!   JSON, or, when the client accepts `text/event-stream`, every response
+//!   (progress events of a streamed sync included) as Server-Sent Events.
+//! - `OPTIONS` answers CORS preflights.
+//!
+//! Browsers may only call it from localhost pages and the configured
+//! origins, so other websites can't read the user's memory. Each connection
+//! carries one request.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tracing::debug;
+
+use crate::protocol::{self, JsonRpcRequest, JsonRpcResponse};
+use crate::{McpHandler, ToolRequest, ToolResponse};
+
+/// Largest request body accepted.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// Most header lines accepted in one request.
+const MAX_HEADERS: usize = 100;
+/// Largest request line plus headers accepted.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+/// How long a client may take to send its whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Header names are lowercase.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Serve MCP over HTTP on `listener` until it fails. `allowed_origins` are
+/// the browser origins allowed besides localhost, e.g.
+/// `https://agent.example.com`.
+pub async fn serve(listener: TcpListener, handler: Arc<McpHandler>, allowed_origins: Vec<String>) -> Result<()> {
+    let allowed_origins = Arc::new(allowed_origins);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let handler = handler.clone();
+        let allowed_origins = allowed_origins.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, &handler, &allowed_origins).await {
+                debug!("[MCP-HTTP] Connection failed: {}", err);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, handler: &McpHandler, allowed_origins: &[String]) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let request = match read_request(&mut reader, READ_TIMEOUT).await {
+        Ok(request) => request,
+        Err(err) => {
+            let body = format!("{}\n", err);
+            return respond(&mut writer, "400 Bad Request", &[("Content-Type", "text/plain".to_string())], body.as_bytes())
+                .await;
+        }
+    };
+
+    // Only localhost pages and configured origins may call us from a browser
+    let mut cors = Vec::new();
+    if let Some(origin) = request.header("origin") {
+        if !origin_allowed(origin, allowed_origins) {
+            return respond(&mut writer, "403 Forbidden", &[], b"origin not allowed\n").await;
+        }
+        cors.push(("Access-Control-Allow-Origin", origin.to_string()));
+        cors.push(("Vary", "Origin".to_string()));
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    if path != "/mcp" {
+        return respond(&mut writer, "404 Not Found", &cors, b"not found\n").await;
+    }
+    match request.method.as_str() {
+        "OPTIONS" => {
+            cors.push(("Access-Control-Allow-Methods", "POST, OPTIONS".to_string()));
+            cors.push((
+                "Access-Control-Allow-Headers",
+                "Content-Type, Accept, Mcp-Session-Id, Mcp-Protocol-Version".to_string(),
+            ));
+            cors.push(("Access-Control-Max-Age", "600".to_string()));
+            respond(&mut writer, "204 No Content", &cors, b"").await
+        }
+        "POST" => handle_post(&mut writer, handler, request, cors).await,
+        // We never push messages the client didn't ask for
+        _ => {
+            cors.push(("Allow", "POST, OPTIONS".to_string()));
+            respond(&mut writer, "405 Method Not Allowed", &cors, b"").await
+        }
+    }
+}
+
+async fn handle_post(
+    writer: &mut OwnedWriteHalf,
+    handler: &McpHandler,
+    request: HttpRequest,
+    cors: Vec<(&'static str, String)>,
+) -> Result<()> {
+    let mut headers = cors.clone();
+    headers.push(("Content-Type", "application/json".to_string()));
+    let message: Value = match serde_json::from_slice(&request.body) {
+        Ok(message) => message,
+        Err(err) => {
+            let response =
+                JsonRpcResponse::error(Value::Null, protocol::PARSE_ERROR, format!("invalid JSON: {}", err));
+            return respond(writer, "400 Bad Request", &headers, &serde_json::to_vec(&response)?).await;
+        }
+    };
+
+    if protocol::is_jsonrpc(&message) {
+        let id = message.get("id").cloned().unwrap_or_default();
+        let response = match serde_json::from_value::<JsonRpcRequest>(message) {
+            Ok(request) => handler.handle_jsonrpc(request).await,
+            Err(err) => Some(JsonRpcResponse::error(id, protocol::INVALID_REQUEST, format!("invalid request: {}", err))),
+        };
+        return match response {
+            Some(response) => respond(writer, "200 OK", &headers, &serde_json::to_vec(&response)?).await,
+            None => respond(writer, "202 Accepted", &cors, b"").await,
+        };
+    }
+
+    let tool_request: ToolRequest = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(err) => {
+            let response = ToolResponse {
+                id: None,
+                ok: false,
+                result: None,
+                error: Some(format!("invalid request: {}", err)),
+                warning: None,
+                event: None,
+            };
+            return respond(writer, "400 Bad Request", &headers, &serde_json::to_vec(&response)?).await;
+        }
+    };
+    let streaming = request.header("accept").is_some_and(|accept| accept.contains("text/event-stream"));
+    if !streaming {
+        let response = handler.handle(tool_request).await;
+        return respond(writer, "200 OK", &headers, &serde_json::to_vec(&response)?).await;
+    }
+
+    // Each response is one event; the stream ends with the final result
+    let mut headers = cors;
+    headers.push(("Content-Type", "text/event-stream".to_string()));
+    headers.push(("Cache-Control", "no-cache".to_string()));
+    write_head(writer, "200 OK", &headers, None).await?;
+    let (tx, mut rx) = mpsc::unbounded_channel::<ToolResponse>();
+    let forward = async {
+        while let Some(response) = rx.recv().await {
+            let event = format!("data: {}\n\n", serde_json::to_string(&response)?);
+            writer.write_all(event.as_bytes()).await?;
+            writer.flush().await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    let (_, forwarded) = tokio::join!(handler.handle_streaming(tool_request, tx), forward);
+    forwarded?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Read one request, giving up after `timeout` so a stalled client can't
+/// hold the connection open.
+async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R, timeout: Duration) -> Result<HttpRequest> {
+    tokio::time::timeout(timeout, read_request_inner(reader))
+        .await
+        .map_err(|_| anyhow!("timed out after {}s reading the request", timeout.as_secs()))?
+}
+
+async fn read_request_inner<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<HttpRequest> {
+    let mut head_budget = MAX_HEAD_BYTES;
+    let line = read_head_line(reader, &mut head_budget).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    for lines in 0.. {
+        let line = read_head_line(reader, &mut head_budget).await?;
+        if line.is_empty() {
+            return Err(anyhow!("connection closed in headers"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if lines >= MAX_HEADERS {
+            return Err(anyhow!("too many headers"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    if headers.contains_key("transfer-encoding") {
+        return Err(anyhow!("chunked request bodies are not supported; send Content-Length"));
+    }
+    let length: usize = match headers.get("content-length") {
+        Some(length) => length.parse().map_err(|_| anyhow!("invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(anyhow!("request body over {} bytes", MAX_BODY_BYTES));
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+    Ok(HttpRequest { method, path, headers, body })
+}
+
+/// Read a line of the request head, at most `budget` bytes, and take it
+/// out of the budget. Empty at end of input.
+async fn read_head_line<R: AsyncBufRead + Unpin>(reader: &mut R, budget: &mut usize) -> Result<String> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(*budget as u64).read_line(&mut line).await?;
+    *budget -= read;
+    if *budget == 0 && !line.ends_with('\n') {
+        return Err(anyhow!("request head over {} bytes", MAX_HEAD_BYTES));
+    }
+    Ok(line)
+}
+
+/// Whether a browser origin may call the endpoint: any localhost page, or
+/// one of `allowed` exactly.
+fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    if allowed.iter().any(|allowed| allowed.trim_end_matches('/') == origin) {
+        return true;
+    }
+    let Some(authority) = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+async fn write_head(
+    writer: &mut OwnedWriteHalf,
+    status: &str,
+    headers: &[(&str, String)],
+    content_length: Option<usize>,
+) -> Result<()> {
+    let mut head = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    if let Some(length) = content_length {
+        head.push_str(&format!("Content-Length: {}\r\n", length));
+    }
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes()).await?;
+    Ok(())
+}
+
+async fn respond(writer: &mut OwnedWriteHalf, status: &str, headers: &[(&str, String)], body: &[u8]) -> Result<()> {
+    write_head(writer, status, headers, Some(body.len())).await?;
+    writer.write_all(body).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(raw: &[u8]) -> Result<HttpRequest> {
+        read_request(&mut BufReader::new(raw), READ_TIMEOUT).await
+    }
+
+    #[tokio::test]
+    async fn test_read_request() {
+        let request = read(b"POST /mcp HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}")
+            .await
+            .unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/mcp"));
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(request.body, b"{}");
+
+        assert!(read(b"POST /mcp HTTP/1.1\r\nHost: localhost\r\n").await.is_err());
+        assert!(read(b"POST /mcp HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_request_limits() {
+        // One endless header line
+        let long = format!("POST /mcp HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEAD_BYTES));
+        let err = read(long.as_bytes()).await.err().unwrap();
+        assert!(err.to_string().contains("request head over"), "{err}");
+
+        // Many short ones
+        let many = format!("POST /mcp HTTP/1.1\r\n{}\r\n", "X-Padding: a\r\n".repeat(MAX_HEADERS + 1));
+        assert_eq!(read(many.as_bytes()).await.err().unwrap().to_string(), "too many headers");
+
+        let huge = format!("POST /mcp HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+        assert!(read(huge.as_bytes()).await.is_err());
+
+        // A client that stops sending partway through
+        let (client, server) = tokio::io::duplex(64);
+        let mut reader = BufReader::new(server);
+        let err = read_request(&mut reader, Duration::from_millis(50)).await.err().unwrap();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        drop(client);
+    }
+
+    #[test]
+    fn test_origin_allowed() {
+        let allowed = vec!["https://agent.example.com/".to_string()];
+        for origin in ["http://localhost:3000", "https://127.0.0.1", "http://[::1]:8080", "https://agent.example.com"] {
+            assert!(origin_allowed(origin, &allowed), "{origin}");
+        }
+        for origin in [
+            "http://localhost.evil.com",
+            "null",
+            "http://evil.com",
+            "https://agent.example.com.evil.com",
+            "file://localhost",
+        ] {
+            assert!(!origin_allowed(origin, &allowed), "{origin}");
+        }
+        assert!(!origin_allowed("https://agent.example.com", &[]));
+    }
+}
@@ -1,17 +1,43 @@
+pub mod aliases;
+pub mod freshness;
+pub mod fusion;
+pub mod http;
+pub mod pages;
+pub mod protocol;
+pub mod threads;
+
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
-use tracing::debug;
+use tokio::sync::{mpsc, RwLock};
+use tracing::{debug, warn};
 
 use minna_auth_bridge::{Provider, TokenStore};
-use minna_core::{Checkpoint, CheckpointStore, LoadQuery};
-use minna_graph::{GraphStore, Ring};
-use minna_ingest::{Document, IngestionEngine};
-use minna_vector::{Embedder, VectorStore};
+use minna_core::progress::{subscribe_progress, InternalEvent};
+use minna_core::clusters::ChatSummarizer;
+use minna_core::{Checkpoint, CheckpointDiff, CheckpointStore, LoadQuery};
+use minna_graph::{CollaboratorGroup, GraphStore, GraphStoreStats, Ring, RingEngine};
+use minna_ingest::fts::SearchQuery;
+use minna_ingest::{attachments, extractors, language, normalize_tag, ClusterRecord, Document, IngestionEngine};
+use minna_vector::{embed_document, Embedder, Reranker, SearchFilter, VectorStore};
+
+pub use aliases::{ToolAlias, ToolAliases};
+pub use freshness::{FreshnessPolicy, Staleness, SyncCompletion, SyncController, SyncMode};
+pub use fusion::{FusionConfig, FusionMethod};
+pub use protocol::{JsonRpcRequest, JsonRpcResponse};
+
+use freshness::{
+    CheckFreshnessParams, FreshnessResult, RequestSyncParams, RequestSyncResult, SourceFreshness, TriggerSyncParams,
+    TriggerSyncResult,
+};
+use pages::{ContextPages, Ranking};
+use threads::{SummarizeThreadParams, ThreadSummary};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolRequest {
@@ -32,6 +58,45 @@ pub struct ToolResponse {
     pub result: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Non-fatal notice, e.g. the tool was called through a deprecated alias.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Progress event for streamed responses (see [`McpHandler::handle_streaming`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<InternalEvent>,
+}
+
+fn unknown_tool(id: Option<String>) -> ToolResponse {
+    ToolResponse {
+        id,
+        ok: false,
+        result: None,
+        error: Some("unknown tool".to_string()),
+        warning: None,
+        event: None,
+    }
+}
+
+/// The response to request `id` for a tool's result or error.
+fn respond<T: Serialize>(id: Option<String>, result: Result<T>) -> ToolResponse {
+    match result {
+        Ok(result) => ToolResponse {
+            id,
+            ok: true,
+            result: Some(serde_json::to_value(result).unwrap_or_default()),
+            error: None,
+            warning: None,
+            event: None,
+        },
+        Err(err) => ToolResponse {
+            id,
+            ok: false,
+            result: None,
+            error: Some(err.to_string()),
+            warning: None,
+            event: None,
+        },
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,10 +104,45 @@ pub struct GetContextParams {
     pub query: String,
     pub pack: Option<String>,
     pub limit: Option<usize>,
+    /// Only return documents carrying one of these tags (also `tag:x` in the query).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only return documents in one of these languages: ISO 639-3 codes or
+    /// English names (also `lang:deu` in the query).
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Rank documents in this language higher without excluding others.
+    #[serde(default)]
+    pub prefer_language: Option<String>,
+    /// Fetch the top result live when every hit is a metadata stub
+    /// (defaults to `McpContext::deep_fetch`).
+    #[serde(default)]
+    pub deep_fetch: Option<bool>,
+    /// Short IDs or URIs of documents to leave out, e.g. ones already shown
+    /// earlier in the conversation.
+    #[serde(default)]
+    pub exclude_doc_ids: Vec<String>,
+    /// Only return documents from these sources (also `source:x` in the query).
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Only return documents updated at or after this time (RFC 3339).
+    #[serde(default)]
+    pub after: Option<DateTime<Utc>>,
+    /// Only return documents updated before this time (RFC 3339).
+    #[serde(default)]
+    pub before: Option<DateTime<Utc>>,
+    /// Only return documents whose URI starts with this, e.g. one channel.
+    #[serde(default)]
+    pub uri_prefix: Option<String>,
+    /// `next_cursor` of an earlier call with the same query: returns the
+    /// next page of its results. Filters are those of the first call.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReadResourceParams {
+    /// Document URI or short ID (e.g. `sl-4f2a`).
     pub uri: String,
 }
 
@@ -68,21 +168,305 @@ pub struct LoadStateParams {
     pub version: Option<u32>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListCheckpointsParams {
+    /// Only versions of this title.
+    pub title: Option<String>,
+    /// Checkpoints to return (default 20).
+    pub limit: Option<usize>,
+}
+
+/// A saved checkpoint without its contents; `load_state` reads one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub title: String,
+    pub version: u32,
+    pub trigger: String,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListCheckpointsResult {
+    /// Newest first.
+    pub checkpoints: Vec<CheckpointSummary>,
+    /// Checkpoints matching, including those past `limit`.
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchCheckpointsParams {
+    /// Words that must all appear in the checkpoint.
+    pub query: String,
+    /// Checkpoints to return (default 10).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointHit {
+    #[serde(flatten)]
+    pub checkpoint: CheckpointSummary,
+    pub score: usize,
+    /// The first line mentioning a query term.
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchCheckpointsResult {
+    /// Best matches first.
+    pub checkpoints: Vec<CheckpointHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffCheckpointsParams {
+    pub title: String,
+    /// Older version (default the one before `to`).
+    pub from: Option<u32>,
+    /// Newer version (default the latest).
+    pub to: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteCheckpointParams {
+    pub title: String,
+    /// Only this version; every version of the title if omitted.
+    pub version: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteCheckpointResult {
+    pub deleted: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WhoDoIWorkWithParams {
+    /// People to return (default 10).
+    pub limit: Option<usize>,
+    /// Only count interactions from the last this many days (default 30).
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WhoDoIWorkWithResult {
+    /// Closest ring first, each with its people by score.
+    pub groups: Vec<CollaboratorGroup>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListPacksParams {
+    /// Packs to return (default 50).
+    pub limit: Option<usize>,
+}
+
+/// A stored cluster, which `get_context` can search within as a pack.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackSummary {
+    pub label: String,
+    /// What the documents have in common, if clusters are summarized.
+    pub summary: Option<String>,
+    pub documents: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListPacksResult {
+    /// Most recently built first.
+    pub packs: Vec<PackSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPackParams {
+    pub label: String,
+    /// Documents to return (default 20).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackResult {
+    #[serde(flatten)]
+    pub pack: PackSummary,
+    /// Most recently updated first.
+    pub items: Vec<DocumentEntry>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GetTimelineParams {
+    /// How far back to look (default 7).
+    pub days: Option<i64>,
+    /// Only these sources; a provider includes its sub-sources (any when empty).
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Most documents to return (default 100); the most recent are kept.
+    pub limit: Option<usize>,
+}
+
+/// A document listed by title and opening, without a relevance score.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentEntry {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
+    pub source: String,
+    pub title: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    pub snippet: String,
+}
+
+/// Documents updated on one day (UTC), oldest first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelineDay {
+    pub date: chrono::NaiveDate,
+    pub items: Vec<DocumentEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelineResult {
+    pub since: DateTime<Utc>,
+    /// Oldest day first.
+    pub days: Vec<TimelineDay>,
+    pub total: usize,
+    /// More documents were updated in the period than `limit`.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WhoIsParams {
+    /// A name, handle (`@alice`) or email address.
+    pub name_or_handle: String,
+    /// Shared documents to return per person (default 5).
+    pub limit: Option<usize>,
+}
+
+/// One of a person's accounts.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonIdentity {
+    pub node_id: String,
+    pub provider: String,
+    pub handle: String,
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// A document a person and the user both have a part in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedArtifact {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
+    pub source: String,
+    pub title: Option<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonProfile {
+    pub node_id: String,
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// Closest ring of any of their accounts; absent until rings are calculated.
+    pub ring: Option<Ring>,
+    /// Their accounts across providers, linked as the same person.
+    pub identities: Vec<PersonIdentity>,
+    /// Most recent first; empty until rings are calculated.
+    pub shared: Vec<SharedArtifact>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WhoIsResult {
+    /// Everyone matching, best match first.
+    pub people: Vec<PersonProfile>,
+}
+
+/// A provider or document source and how much of it is indexed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceSummary {
+    pub source: String,
+    /// Enabled and available to sync.
+    pub connected: bool,
+    /// Indexed documents, including sub-sources such as `gmail` for `google`.
+    pub documents: i64,
+    pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSourcesResult {
+    pub sources: Vec<SourceSummary>,
+    pub total_documents: i64,
+}
+
+/// How big the index is, for agents and dashboards reporting its health.
+#[derive(Debug, Serialize)]
+pub struct StatsResult {
+    pub documents: i64,
+    /// Stored embeddings (one per chunk for long documents).
+    pub vectors: i64,
+    pub clusters: i64,
+    /// Absent when the relationship graph is unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<GraphStoreStats>,
+    pub db_size_bytes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetRelatedParams {
+    /// Document URI or short ID.
+    pub uri: String,
+    /// Documents to return per list (default 10).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelatedItem {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
+    pub source: String,
+    pub title: Option<String>,
+    /// `links_to`, `linked_from`, `graph` (shares an issue, PR, channel...)
+    /// or `similar`.
+    pub relation: String,
+    /// Cosine similarity for `similar`; otherwise the strength of the
+    /// connection relative to the strongest, 1.0 for explicit links.
+    pub score: f32,
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelatedResult {
+    pub uri: String,
+    /// Linked documents and ones sharing a node in the relationship graph.
+    pub connected: Vec<RelatedItem>,
+    /// Documents with similar embeddings that aren't already connected.
+    pub similar: Vec<RelatedItem>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContextItem {
     pub uri: String,
+    /// Short ID accepted by `read_resource` in place of the URI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<String>,
     pub source: String,
     pub title: Option<String>,
     pub score: f32,
     pub snippet: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    /// URIs of near-duplicates of this document (a forward, a cross-post),
+    /// which are left out of the results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContextResult {
     pub mode: String,
     pub items: Vec<ContextItem>,
+    /// Pass as `cursor` to get the next page; absent on the last one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -90,6 +474,21 @@ pub struct ResourceResult {
     pub uri: String,
     pub source: String,
     pub body: String,
+    /// Set for attachments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// `base64` when `body` holds an attachment's raw bytes rather than text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// `attachment://` URIs of the document's attachments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// URIs of indexed documents this document links to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// URIs of indexed documents linking to this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_by: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -99,6 +498,24 @@ pub struct McpContext {
     pub auth_store: Arc<RwLock<TokenStore>>,
     pub embedder: Arc<dyn Embedder>,
     pub graph: Option<Arc<GraphStore>>,
+    pub aliases: Arc<ToolAliases>,
+    pub sync: Option<Arc<dyn SyncController>>,
+    /// Providers `request_sync` and `trigger_sync` may sync; `*` allows all.
+    /// Empty disables on-demand sync.
+    pub sync_allowlist: Vec<String>,
+    pub freshness: FreshnessPolicy,
+    /// How get_context merges semantic and keyword rankings.
+    pub fusion: FusionConfig,
+    /// Allow get_context to live-fetch a stub result (one request per call).
+    pub deep_fetch: bool,
+    /// Cross-encoder applied to the top `rerank_candidates` hybrid results.
+    pub reranker: Option<Arc<dyn Reranker>>,
+    pub rerank_candidates: usize,
+    /// Writes `summarize_thread` summaries; extractive only without one.
+    pub summarizer: Option<Arc<ChatSummarizer>>,
+    /// `get_context` rankings for `next_cursor`; share one across handlers
+    /// so cursors survive a reconnect.
+    pub pages: Arc<ContextPages>,
 }
 
 impl McpContext {
@@ -114,6 +531,16 @@ impl McpContext {
             auth_store: Arc::new(RwLock::new(auth_store)),
             embedder,
             graph: None,
+            aliases: Arc::new(ToolAliases::with_defaults()),
+            sync: None,
+            sync_allowlist: Vec::new(),
+            freshness: FreshnessPolicy::default(),
+            fusion: FusionConfig::default(),
+            deep_fetch: true,
+            reranker: None,
+            summarizer: None,
+            pages: Arc::default(),
+            rerank_candidates: DEFAULT_RERANK_CANDIDATES,
         }
     }
 
@@ -131,8 +558,65 @@ impl McpContext {
             auth_store: Arc::new(RwLock::new(auth_store)),
             embedder,
             graph: Some(Arc::new(graph)),
+            aliases: Arc::new(ToolAliases::with_defaults()),
+            sync: None,
+            sync_allowlist: Vec::new(),
+            freshness: FreshnessPolicy::default(),
+            fusion: FusionConfig::default(),
+            deep_fetch: true,
+            reranker: None,
+            summarizer: None,
+            pages: Arc::default(),
+            rerank_candidates: DEFAULT_RERANK_CANDIDATES,
         }
     }
+
+    /// Enable or disable live fetching of stub results in get_context.
+    pub fn with_deep_fetch(mut self, enabled: bool) -> Self {
+        self.deep_fetch = enabled;
+        self
+    }
+
+    /// Merge get_context's semantic and keyword rankings with `fusion`.
+    pub fn with_fusion(mut self, fusion: FusionConfig) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Rerank the top `candidates` get_context results with a cross-encoder.
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker>, candidates: usize) -> Self {
+        self.reranker = Some(reranker);
+        self.rerank_candidates = candidates.max(1);
+        self
+    }
+
+    /// Summarize threads with a chat model.
+    pub fn with_summarizer(mut self, summarizer: Arc<ChatSummarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Replace the tool alias routing table.
+    pub fn with_aliases(mut self, aliases: ToolAliases) -> Self {
+        self.aliases = Arc::new(aliases);
+        self
+    }
+
+    /// Enable `request_sync` and budget reporting via the given controller.
+    pub fn with_sync_controller(mut self, sync: Arc<dyn SyncController>) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+
+    pub fn with_sync_allowlist(mut self, providers: Vec<String>) -> Self {
+        self.sync_allowlist = providers;
+        self
+    }
+
+    pub fn with_pages(mut self, pages: Arc<ContextPages>) -> Self {
+        self.pages = pages;
+        self
+    }
 }
 
 pub struct McpHandler {
@@ -147,72 +631,104 @@ impl McpHandler {
     }
 
     pub async fn handle(&self, request: ToolRequest) -> ToolResponse {
-        let tool = request.tool.clone().or(request.method.clone());
         let id = request.id.clone();
-        match tool.as_deref() {
-            Some("get_context") => match self.handle_get_context(request.params).await {
-                Ok(result) => ToolResponse {
-                    id,
-                    ok: true,
-                    result: Some(serde_json::to_value(result).unwrap_or_default()),
-                    error: None,
-                },
-                Err(err) => ToolResponse {
-                    id,
-                    ok: false,
-                    result: None,
-                    error: Some(err.to_string()),
-                },
-            },
-            Some("read_resource") => match self.handle_read_resource(request.params).await {
-                Ok(result) => ToolResponse {
-                    id,
-                    ok: true,
-                    result: Some(serde_json::to_value(result).unwrap_or_default()),
-                    error: None,
-                },
-                Err(err) => ToolResponse {
-                    id,
-                    ok: false,
-                    result: None,
-                    error: Some(err.to_string()),
-                },
-            },
-            Some("save_state") => match self.handle_save_state(request.params).await {
-                Ok(result) => ToolResponse {
-                    id,
-                    ok: true,
-                    result: Some(serde_json::json!({ "message": result })),
-                    error: None,
-                },
-                Err(err) => ToolResponse {
-                    id,
-                    ok: false,
-                    result: None,
-                    error: Some(err.to_string()),
-                },
-            },
-            Some("load_state") => match self.handle_load_state(request.params).await {
-                Ok(result) => ToolResponse {
-                    id,
-                    ok: true,
-                    result: Some(serde_json::json!({ "content": result })),
-                    error: None,
-                },
-                Err(err) => ToolResponse {
-                    id,
-                    ok: false,
-                    result: None,
-                    error: Some(err.to_string()),
-                },
-            },
-            _ => ToolResponse {
-                id,
-                ok: false,
-                result: None,
-                error: Some("unknown tool".to_string()),
-            },
+        self.try_handle(request).await.unwrap_or_else(|| unknown_tool(id))
+    }
+
+    /// Like [`Self::handle`], but None if the tool doesn't exist.
+    pub async fn try_handle(&self, request: ToolRequest) -> Option<ToolResponse> {
+        let (tool, request, warning) = self.resolve_alias(request);
+        let mut response = self.dispatch(tool.as_deref(), request).await?;
+        response.warning = warning;
+        Some(response)
+    }
+
+    /// Handle a request that may produce several responses.
+    ///
+    /// `request_sync` with `stream: true` sends an initial acknowledgement, then
+    /// one response per progress event, then the final result. All other
+    /// requests send exactly one response.
+    pub async fn handle_streaming(&self, request: ToolRequest, tx: mpsc::UnboundedSender<ToolResponse>) {
+        let (tool, request, warning) = self.resolve_alias(request);
+        let stream = request
+            .params
+            .get("stream")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if stream && tool.as_deref() == Some("request_sync") {
+            self.stream_request_sync(request, warning, tx).await;
+            return;
         }
+
+        let id = request.id.clone();
+        let mut response = self
+            .dispatch(tool.as_deref(), request)
+            .await
+            .unwrap_or_else(|| unknown_tool(id));
+        response.warning = warning;
+        let _ = tx.send(response);
+    }
+
+    /// Resolve a tool alias, applying its defaults. Returns the target tool and
+    /// a deprecation warning if the alias is deprecated.
+    fn resolve_alias(&self, mut request: ToolRequest) -> (Option<String>, ToolRequest, Option<String>) {
+        let name = request.tool.clone().or(request.method.clone());
+        let mut warning = None;
+        let alias = name
+            .as_deref()
+            .and_then(|n| self.ctx.aliases.resolve(n).map(|a| (n, a)));
+        let tool = match alias {
+            Some((alias_name, alias)) => {
+                request.params = alias.apply_defaults(request.params);
+                if let Some(note) = &alias.deprecated {
+                    warn!("Deprecated tool alias used: {} -> {}", alias_name, alias.tool);
+                    warning = Some(format!("'{}' is deprecated: {}", alias_name, note));
+                }
+                Some(alias.tool.clone())
+            }
+            None => name,
+        };
+        (tool, request, warning)
+    }
+
+    /// Run `tool`; None if there is no such tool.
+    async fn dispatch(&self, tool: Option<&str>, request: ToolRequest) -> Option<ToolResponse> {
+        let id = request.id.clone();
+        let response = match tool {
+            Some("list_checkpoints") => respond(id, self.handle_list_checkpoints(request.params).await),
+            Some("search_checkpoints") => respond(id, self.handle_search_checkpoints(request.params).await),
+            Some("diff_checkpoints") => respond(id, self.handle_diff_checkpoints(request.params).await),
+            Some("delete_checkpoint") => respond(id, self.handle_delete_checkpoint(request.params).await),
+            Some("get_context") => respond(id, self.handle_get_context(request.params).await),
+            Some("get_related") => respond(id, self.handle_get_related(request.params).await),
+            Some("summarize_thread") => respond(id, self.handle_summarize_thread(request.params).await),
+            Some("read_resource") => respond(id, self.handle_read_resource(request.params).await),
+            Some("save_state") => respond(
+                id,
+                self.handle_save_state(request.params)
+                    .await
+                    .map(|message| serde_json::json!({ "message": message })),
+            ),
+            Some("load_state") => respond(
+                id,
+                self.handle_load_state(request.params)
+                    .await
+                    .map(|content| serde_json::json!({ "content": content })),
+            ),
+            Some("check_freshness") => respond(id, self.handle_check_freshness(request.params).await),
+            Some("request_sync") => respond(id, self.handle_request_sync(request.params).await),
+            Some("who_do_i_work_with") => respond(id, self.handle_who_do_i_work_with(request.params).await),
+            Some("who_is") => respond(id, self.handle_who_is(request.params).await),
+            Some("trigger_sync") => respond(id, self.handle_trigger_sync(request.params).await),
+            Some("list_sources") => respond(id, self.handle_list_sources().await),
+            Some("get_pack") => respond(id, self.handle_get_pack(request.params).await),
+            Some("list_packs") => respond(id, self.handle_list_packs(request.params).await),
+            Some("get_timeline") => respond(id, self.handle_get_timeline(request.params).await),
+            Some("get_stats") => respond(id, self.handle_get_stats().await),
+            _ => return None,
+        };
+        Some(response)
     }
 
     async fn handle_save_state(&self, params: serde_json::Value) -> Result<String> {
@@ -253,119 +769,1223 @@ impl McpHandler {
         }
     }
 
+    async fn handle_list_checkpoints(&self, params: serde_json::Value) -> Result<ListCheckpointsResult> {
+        let params: ListCheckpointsParams = if params.is_null() {
+            ListCheckpointsParams::default()
+        } else {
+            serde_json::from_value(params).map_err(|e| anyhow!("invalid list_checkpoints params: {}", e))?
+        };
+
+        let store = CheckpointStore::default_path();
+        let checkpoints = store.list(params.title.as_deref())?;
+        let total = checkpoints.len();
+        let checkpoints = checkpoints
+            .into_iter()
+            .take(params.limit.unwrap_or(20))
+            .map(checkpoint_summary)
+            .collect();
+        Ok(ListCheckpointsResult { checkpoints, total })
+    }
+
+    async fn handle_search_checkpoints(&self, params: serde_json::Value) -> Result<SearchCheckpointsResult> {
+        let params: SearchCheckpointsParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid search_checkpoints params: {}", e))?;
+
+        let store = CheckpointStore::default_path();
+        let checkpoints = store
+            .search(&params.query, params.limit.unwrap_or(10))?
+            .into_iter()
+            .map(|found| CheckpointHit {
+                checkpoint: checkpoint_summary(found.checkpoint),
+                score: found.score,
+                snippet: truncate(&found.snippet, 200),
+            })
+            .collect();
+        Ok(SearchCheckpointsResult { checkpoints })
+    }
+
+    async fn handle_diff_checkpoints(&self, params: serde_json::Value) -> Result<CheckpointDiff> {
+        let params: DiffCheckpointsParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid diff_checkpoints params: {}", e))?;
+
+        let store = CheckpointStore::default_path();
+        store.diff(&params.title, params.from, params.to)
+    }
+
+    async fn handle_delete_checkpoint(&self, params: serde_json::Value) -> Result<DeleteCheckpointResult> {
+        let params: DeleteCheckpointParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid delete_checkpoint params: {}", e))?;
+
+        let store = CheckpointStore::default_path();
+        match store.delete(&params.title, params.version)? {
+            0 => Err(anyhow!("no checkpoint found")),
+            deleted => Ok(DeleteCheckpointResult { deleted }),
+        }
+    }
+
     async fn handle_get_context(&self, params: serde_json::Value) -> Result<ContextResult> {
         let params = parse_get_context_params(params)?;
         let (query, inline_pack) = extract_pack(&params.query);
-        let pack = params.pack.or(inline_pack);
+        let pack = params.pack.clone().or(inline_pack);
+        if let Some(cursor) = &params.cursor {
+            return self.context_page(&params, cursor).await;
+        }
 
         if let Some(sync) = self.router.try_sync(&query).await? {
             return Ok(ContextResult {
                 mode: "instant_recall".to_string(),
                 items: vec![ContextItem {
                     uri: sync.url.clone(),
+                    short_id: None,
                     source: sync.source,
                     title: sync.title,
                     score: 1.0,
                     snippet: truncate(&sync.markdown, 240),
                     content: Some(sync.markdown),
+                    alternates: Vec::new(),
                 }],
+                next_cursor: None,
+            });
+        }
+
+        let limit = params.limit.unwrap_or(6);
+        let mut search = SearchQuery::parse(&query);
+        search
+            .tags
+            .extend(params.tags.iter().filter_map(|tag| normalize_tag(tag)));
+        search
+            .languages
+            .extend(params.languages.iter().filter_map(|lang| language::normalize(lang)));
+        search.sources.extend(
+            params
+                .sources
+                .iter()
+                .map(|source| source.trim().to_ascii_lowercase())
+                .filter(|source| !source.is_empty()),
+        );
+        search.updated_after = params.after;
+        search.updated_before = params.before;
+        search.uri_prefix = params.uri_prefix.clone().filter(|prefix| !prefix.is_empty());
+
+        let mut allowed_ids = if let Some(pack) = &pack {
+            let ids = self.ctx.ingest.get_cluster_doc_ids(pack).await?;
+            Some(ids.into_iter().collect::<HashSet<_>>())
+        } else {
+            None
+        };
+        if !search.tags.is_empty() {
+            let tagged: HashSet<i64> = self
+                .ctx
+                .ingest
+                .doc_ids_with_tags(&search.tags)
+                .await?
+                .into_iter()
+                .collect();
+            allowed_ids = Some(match allowed_ids {
+                Some(ids) => ids.intersection(&tagged).copied().collect(),
+                None => tagged,
+            });
+        }
+        if !search.languages.is_empty() {
+            let in_language: HashSet<i64> = self
+                .ctx
+                .ingest
+                .doc_ids_with_languages(&search.languages)
+                .await?
+                .into_iter()
+                .collect();
+            allowed_ids = Some(match allowed_ids {
+                Some(ids) => ids.intersection(&in_language).copied().collect(),
+                None => in_language,
+            });
+        }
+
+        let mut excluded = HashSet::new();
+        for reference in &params.exclude_doc_ids {
+            let doc = self.ctx.ingest.resolve_document(reference).await?;
+            if let Some(id) = doc.and_then(|doc| doc.id) {
+                excluded.insert(id);
+            }
+        }
+
+        let text = search.text();
+        let semantic = if text.is_empty() {
+            Vec::new()
+        } else {
+            // Filter in the vector query, so excluded documents don't take
+            // up the candidate budget
+            let filter = SearchFilter {
+                sources: search.sources.clone(),
+                excluded_sources: search.excluded_sources.clone(),
+                doc_ids: allowed_ids.as_ref().map(|ids| ids.iter().copied().collect()),
+                excluded_doc_ids: excluded.iter().copied().collect(),
+                updated_after: search.updated_after,
+                updated_before: search.updated_before,
+                uri_prefix: search.uri_prefix.clone(),
+            };
+            self.ctx
+                .vector
+                .search_semantic_filtered(&*self.ctx.embedder, &text, limit * 3, &filter)
+                .await?
+        };
+        let keyword = self.ctx.ingest.search_query(&search, limit * 3).await?;
+
+        let mut scores: HashMap<i64, f32> = HashMap::new();
+        let fusion = &self.ctx.fusion;
+        for (rank, (doc_id, similarity)) in semantic.into_iter().enumerate() {
+            if let Some(filter) = &allowed_ids {
+                if !filter.contains(&doc_id) {
+                    continue;
+                }
+            }
+            scores.insert(doc_id, fusion.semantic(rank, similarity));
+        }
+        let mut snippets: HashMap<i64, String> = HashMap::new();
+        for (rank, hit) in keyword.into_iter().enumerate() {
+            if let Some(doc_id) = hit.document.id {
+                if let Some(filter) = &allowed_ids {
+                    if !filter.contains(&doc_id) {
+                        continue;
+                    }
+                }
+                *scores.entry(doc_id).or_insert(0.0) += fusion.keyword(rank);
+                snippets.insert(doc_id, hit.snippet);
+            }
+        }
+
+        // Apply ring boost if GraphStore is available (Gravity Well)
+        if let Some(graph) = &self.ctx.graph {
+            scores = self.apply_ring_boost(graph, scores).await;
+        }
+
+        scores = self.apply_archive_penalty(scores).await;
+        if let Some(preferred) = params.prefer_language.as_deref().and_then(language::normalize) {
+            scores = self.apply_language_boost(&preferred, scores).await;
+        }
+        scores = self
+            .collapse_duplicates(scores, allowed_ids.as_ref(), &mut snippets)
+            .await;
+        // After collapsing, so an excluded document's duplicates stay hidden too
+        scores.retain(|id, _| !excluded.contains(id));
+
+        let mut scored: Vec<(i64, f32)> = scores.into_iter().collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let doc_ids: Vec<i64> = scored.iter().map(|(id, _)| *id).collect();
+        let docs = self.ctx.ingest.fetch_documents_by_ids(&doc_ids).await?;
+        let doc_map: HashMap<i64, Document> = docs
+            .into_iter()
+            .filter_map(|doc| doc.id.map(|id| (id, doc)))
+            .collect();
+
+        // Semantic hits ignore the query's term exclusions
+        let rerank = self.ctx.reranker.is_some() && !text.is_empty();
+        let candidates = if rerank {
+            limit.max(self.ctx.rerank_candidates)
+        } else {
+            limit
+        };
+        let mut ranked = scored
+            .into_iter()
+            .filter_map(|(id, score)| doc_map.get(&id).map(|doc| (doc.clone(), score)))
+            .filter(|(doc, _)| search.admits(doc))
+            .collect::<Vec<_>>();
+        if rerank {
+            let rest = ranked.split_off(candidates.min(ranked.len()));
+            ranked = self.rerank(&text, ranked).await;
+            ranked.extend(rest);
+        }
+
+        let rest = ranked.split_off(limit.min(ranked.len()));
+        // Nothing but metadata stubs: fetch the top candidate live, once
+        let deep_fetch = params.deep_fetch.unwrap_or(self.ctx.deep_fetch);
+        let enriched = if deep_fetch { self.deep_fetch_stubs(&mut ranked).await } else { None };
+        let mode = if enriched.is_some() { "hybrid_deep_fetch" } else { "hybrid" };
+
+        // Keep the rest of the ranking for later pages
+        let next_cursor = (!rest.is_empty()).then(|| {
+            let hits: Vec<(i64, f32)> = ranked
+                .iter()
+                .chain(&rest)
+                .filter_map(|(doc, score)| doc.id.map(|id| (id, *score)))
+                .collect();
+            let snippets = rest
+                .iter()
+                .filter_map(|(doc, _)| doc.id)
+                .filter_map(|id| snippets.remove(&id).map(|snippet| (id, snippet)))
+                .collect();
+            let ranking = Ranking {
+                query: params.query.clone(),
+                hits,
+                snippets,
+                mode: mode.to_string(),
+                deep_fetch,
+            };
+            self.ctx.pages.store(ranking, limit)
+        });
+
+        let ranked_ids: Vec<i64> = ranked.iter().filter_map(|(doc, _)| doc.id).collect();
+        let mut alternates = self.ctx.ingest.document_alternates(&ranked_ids).await?;
+
+        let items = ranked
+            .into_iter()
+            .map(|(doc, score)| {
+                let deep = enriched.is_some() && doc.id == enriched;
+                ContextItem {
+                    alternates: doc
+                        .id
+                        .and_then(|id| alternates.remove(&id))
+                        .unwrap_or_default(),
+                    short_id: doc.short_id(),
+                    // Keyword hits show the highlighted match; semantic-only hits the opening
+                    snippet: doc
+                        .id
+                        .filter(|_| !deep)
+                        .and_then(|id| snippets.remove(&id))
+                        .unwrap_or_else(|| truncate(&doc.body, 240)),
+                    content: deep.then(|| doc.body.clone()),
+                    uri: doc.uri,
+                    source: doc.source,
+                    title: doc.title,
+                    score,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(ContextResult {
+            mode: mode.to_string(),
+            items,
+            next_cursor,
+        })
+    }
+
+    /// A later page of a `get_context` ranking, read from the stored ranking.
+    async fn context_page(&self, params: &GetContextParams, cursor: &str) -> Result<ContextResult> {
+        let page = self.ctx.pages.get(cursor)?;
+        let (ranking, offset) = (&page.ranking, page.offset);
+        if ranking.query != params.query {
+            return Err(anyhow!("cursor belongs to another query"));
+        }
+
+        let limit = params.limit.unwrap_or(6);
+        let end = ranking.hits.len().min(offset.saturating_add(limit));
+        let hits = ranking.hits.get(offset..end).unwrap_or_default();
+        let ids: Vec<i64> = hits.iter().map(|(id, _)| *id).collect();
+        let mut docs: HashMap<i64, Document> = self
+            .ctx
+            .ingest
+            .fetch_documents_by_ids(&ids)
+            .await?
+            .into_iter()
+            .filter_map(|doc| doc.id.map(|id| (id, doc)))
+            .collect();
+        let mut alternates = self.ctx.ingest.document_alternates(&ids).await?;
+
+        // Documents deleted since the first page are skipped
+        let mut ranked: Vec<(Document, f32)> = hits
+            .iter()
+            .filter_map(|(id, score)| docs.remove(id).map(|doc| (doc, *score)))
+            .collect();
+        let enriched = if ranking.deep_fetch { self.deep_fetch_stubs(&mut ranked).await } else { None };
+
+        let items = ranked
+            .into_iter()
+            .map(|(doc, score)| {
+                let deep = enriched.is_some() && doc.id == enriched;
+                ContextItem {
+                    alternates: doc
+                        .id
+                        .and_then(|id| alternates.remove(&id))
+                        .unwrap_or_default(),
+                    short_id: doc.short_id(),
+                    snippet: doc
+                        .id
+                        .filter(|_| !deep)
+                        .and_then(|id| ranking.snippets.get(&id).cloned())
+                        .unwrap_or_else(|| truncate(&doc.body, 240)),
+                    content: deep.then(|| doc.body.clone()),
+                    uri: doc.uri,
+                    source: doc.source,
+                    title: doc.title,
+                    score,
+                }
+            })
+            .collect();
+
+        Ok(ContextResult {
+            mode: ranking.mode.clone(),
+            items,
+            next_cursor: (end < ranking.hits.len()).then(|| page.cursor_at(end)),
+        })
+    }
+
+    /// When every result is a metadata stub, fetch the top one live in
+    /// place, returning its ID if that worked.
+    async fn deep_fetch_stubs(&self, ranked: &mut [(Document, f32)]) -> Option<i64> {
+        if ranked.is_empty() || !ranked.iter().all(|(doc, _)| is_metadata_stub(doc)) {
+            return None;
+        }
+        let doc = self.deep_fetch(&ranked[0].0).await?;
+        ranked[0].0 = doc;
+        ranked[0].0.id
+    }
+
+    /// Fetch a stub document's content from its provider and store it, so the
+    /// next search finds the real text. Failures are logged and ignored.
+    async fn deep_fetch(&self, doc: &Document) -> Option<Document> {
+        let fetched = match self.router.fetch_url(&doc.uri).await {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => return None,
+            Err(err) => {
+                warn!("[DEEP_FETCH] {} failed: {}", doc.uri, err);
+                return None;
+            }
+        };
+
+        let title = doc.title.clone().or(fetched.title);
+        let body = match &title {
+            Some(title) if !fetched.markdown.starts_with('#') => {
+                format!("# {}\n\n{}", title, fetched.markdown)
+            }
+            _ => fetched.markdown,
+        };
+        let enriched = Document {
+            id: doc.id,
+            uri: doc.uri.clone(),
+            source: doc.source.clone(),
+            title,
+            body,
+            updated_at: doc.updated_at,
+        };
+        let mut enriched = self.ctx.ingest.prepare(&enriched).into_owned();
+
+        let stored = async {
+            let id = self.ctx.ingest.upsert_document(&enriched).await?;
+            embed_document(
+                self.ctx.embedder.as_ref(),
+                &*self.ctx.vector,
+                id,
+                &enriched.source,
+                &enriched.body,
+            )
+            .await?;
+            Ok::<_, anyhow::Error>(id)
+        };
+        match stored.await {
+            Ok(id) => enriched.id = Some(id),
+            Err(err) => warn!("[DEEP_FETCH] Failed to store {}: {}", doc.uri, err),
+        }
+        debug!("[DEEP_FETCH] Enriched {} ({} chars)", doc.uri, enriched.body.len());
+        Some(enriched)
+    }
+
+    /// Documents connected to one (links, shared graph nodes) and documents
+    /// similar to it, e.g. everything around a PR.
+    async fn handle_get_related(&self, params: serde_json::Value) -> Result<RelatedResult> {
+        let params: GetRelatedParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid get_related params: {}", e))?;
+        let doc = self
+            .ctx
+            .ingest
+            .resolve_document(&params.uri)
+            .await?
+            .ok_or_else(|| anyhow!("document not found"))?;
+        let id = doc.id.ok_or_else(|| anyhow!("document not found"))?;
+        let limit = params.limit.unwrap_or(10).max(1);
+
+        let mut connected: Vec<(i64, &str, f32)> = Vec::new();
+        for link in self.ctx.ingest.document_references(id).await? {
+            if link.from_id == id {
+                connected.push((link.to_id, "links_to", 1.0));
+            } else {
+                connected.push((link.from_id, "linked_from", 1.0));
+            }
+        }
+        if let Some(graph) = &self.ctx.graph {
+            let related = graph.related_documents(id, limit).await?;
+            let strongest = related.first().map(|(_, shared)| *shared).unwrap_or(1) as f32;
+            connected.extend(
+                related
+                    .into_iter()
+                    .map(|(other, shared)| (other, "graph", shared as f32 / strongest)),
+            );
+        }
+        let mut seen = HashSet::from([id]);
+        connected.retain(|(other, _, _)| seen.insert(*other));
+        connected.truncate(limit);
+
+        let mut similar = self
+            .ctx
+            .vector
+            .find_similar(id, RELATED_MIN_SIMILARITY, limit + connected.len())
+            .await?;
+        similar.retain(|(other, _)| seen.insert(*other));
+        similar.truncate(limit);
+
+        let ids: Vec<i64> = connected
+            .iter()
+            .map(|(other, _, _)| *other)
+            .chain(similar.iter().map(|(other, _)| *other))
+            .collect();
+        let docs: HashMap<i64, Document> = self
+            .ctx
+            .ingest
+            .fetch_documents_by_ids(&ids)
+            .await?
+            .into_iter()
+            .filter_map(|doc| doc.id.map(|id| (id, doc)))
+            .collect();
+        let item = |other: i64, relation: &str, score: f32| {
+            docs.get(&other).map(|doc| RelatedItem {
+                uri: doc.uri.clone(),
+                short_id: doc.short_id(),
+                source: doc.source.clone(),
+                title: doc.title.clone(),
+                relation: relation.to_string(),
+                score,
+                snippet: truncate(&doc.body, 240),
+            })
+        };
+
+        Ok(RelatedResult {
+            connected: connected
+                .into_iter()
+                .filter_map(|(other, relation, score)| item(other, relation, score))
+                .collect(),
+            similar: similar
+                .into_iter()
+                .filter_map(|(other, score)| item(other, "similar", score))
+                .collect(),
+            uri: doc.uri,
+        })
+    }
+
+    /// Condense a thread, PR or issue: fetched live when the router knows the
+    /// URL, else read from the index.
+    async fn handle_summarize_thread(&self, params: serde_json::Value) -> Result<ThreadSummary> {
+        let params: SummarizeThreadParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid summarize_thread params: {}", e))?;
+        let live = match self.router.fetch_url(&params.uri).await {
+            Ok(live) => live,
+            Err(err) => {
+                warn!("[SUMMARIZE] Live fetch of {} failed, using the index: {}", params.uri, err);
+                None
+            }
+        };
+        let (uri, source, title, text, live) = match live {
+            Some(content) => (content.url, content.source, content.title, content.markdown, true),
+            None => {
+                let doc = self
+                    .ctx
+                    .ingest
+                    .resolve_document(&params.uri)
+                    .await?
+                    .ok_or_else(|| anyhow!("thread not found"))?;
+                (doc.uri, doc.source, doc.title, doc.body, false)
+            }
+        };
+
+        let digest = threads::Digest::parse(&text);
+        let title = title.or_else(|| digest.title.clone());
+        let mut mode = "extractive";
+        let mut summary = None;
+        if let Some(summarizer) = &self.ctx.summarizer {
+            let input = threads::model_input(title.as_deref(), &text);
+            match summarizer.complete(threads::THREAD_PROMPT, &input).await {
+                Ok(text) => {
+                    summary = Some(text);
+                    mode = "llm";
+                }
+                Err(err) => warn!("[SUMMARIZE] Model summary of {} failed: {}", uri, err),
+            }
+        }
+        Ok(ThreadSummary {
+            summary: summary.unwrap_or_else(|| digest.extractive_summary()),
+            mode: mode.to_string(),
+            messages: digest.messages.len(),
+            participants: digest.participants,
+            key_facts: digest.key_facts,
+            open_questions: digest.open_questions,
+            action_items: digest.action_items,
+            uri,
+            source,
+            title,
+            live,
+        })
+    }
+
+    async fn handle_read_resource(&self, params: serde_json::Value) -> Result<ResourceResult> {
+        let params: ReadResourceParams = serde_json::from_value(params)
+            .map_err(|_| anyhow!("invalid read_resource params"))?;
+        if let Some(id) = attachments::parse_uri(&params.uri) {
+            return self.read_attachment(id).await;
+        }
+        if let Some(doc) = self.ctx.ingest.resolve_document(&params.uri).await? {
+            let (attachments, links) = match doc.id {
+                Some(id) => (
+                    self.ctx.ingest.list_attachments(id).await?,
+                    self.ctx.ingest.document_references(id).await?,
+                ),
+                None => (Vec::new(), Vec::new()),
+            };
+            let (references, referenced_by): (Vec<_>, Vec<_>) =
+                links.into_iter().partition(|link| Some(link.from_id) == doc.id);
+            return Ok(ResourceResult {
+                uri: doc.uri,
+                source: doc.source,
+                body: doc.body,
+                mime_type: None,
+                encoding: None,
+                attachments: attachments.iter().map(|a| a.uri()).collect(),
+                references: references.into_iter().map(|link| link.to_uri).collect(),
+                referenced_by: referenced_by.into_iter().map(|link| link.from_uri).collect(),
+            });
+        }
+        if let Some(sync) = self.router.fetch_url(&params.uri).await? {
+            return Ok(ResourceResult {
+                uri: sync.url,
+                source: sync.source,
+                body: sync.markdown,
+                mime_type: None,
+                encoding: None,
+                attachments: Vec::new(),
+                references: Vec::new(),
+                referenced_by: Vec::new(),
+            });
+        }
+        Err(anyhow!("resource not found"))
+    }
+
+    /// Text attachments come back as text, files we can extract text from
+    /// as their text, anything else base64-encoded.
+    async fn read_attachment(&self, id: i64) -> Result<ResourceResult> {
+        let (attachment, data) = self
+            .ctx
+            .ingest
+            .get_attachment(id)
+            .await?
+            .ok_or_else(|| anyhow!("resource not found"))?;
+        let source = self
+            .ctx
+            .ingest
+            .fetch_documents_by_ids(&[attachment.doc_id])
+            .await?
+            .into_iter()
+            .next()
+            .map(|doc| doc.source)
+            .unwrap_or_default();
+
+        let mime_type = attachment.mime_type.clone();
+        let text = if mime_type.starts_with("text/") {
+            String::from_utf8(data).map_err(|err| err.into_bytes())
+        } else if extractors::is_supported(&mime_type) {
+            let mime = mime_type.clone();
+            let bytes = data.clone();
+            match tokio::task::spawn_blocking(move || extractors::extract_text(&mime, &bytes)).await? {
+                Ok(Some(text)) => Ok(text),
+                Ok(None) => Err(data),
+                Err(err) => {
+                    warn!("[ATTACHMENT] Text extraction failed for {}: {}", attachment.uri(), err);
+                    Err(data)
+                }
+            }
+        } else {
+            Err(data)
+        };
+        let (body, encoding) = match text {
+            Ok(text) => (text, None),
+            Err(bytes) => (BASE64.encode(bytes), Some("base64".to_string())),
+        };
+        Ok(ResourceResult {
+            uri: attachment.uri(),
+            source,
+            body,
+            mime_type: Some(mime_type),
+            encoding,
+            attachments: Vec::new(),
+            references: Vec::new(),
+            referenced_by: Vec::new(),
+        })
+    }
+
+    async fn handle_check_freshness(&self, params: serde_json::Value) -> Result<FreshnessResult> {
+        let params: CheckFreshnessParams = if params.is_null() {
+            CheckFreshnessParams::default()
+        } else {
+            serde_json::from_value(params)
+                .map_err(|e| anyhow!("invalid check_freshness params: {}", e))?
+        };
+
+        let sync_times = self.ctx.ingest.get_sync_times().await?;
+        let sources = if params.sources.is_empty() {
+            match &self.ctx.sync {
+                Some(sync) => sync.sources(),
+                None => sync_times.iter().map(|(key, _)| key.clone()).collect(),
+            }
+        } else {
+            params.sources
+        };
+
+        let budget = match &self.ctx.sync {
+            Some(sync) => Some(sync.budget().await),
+            None => None,
+        };
+
+        let now = chrono::Utc::now();
+        let policy = &self.ctx.freshness;
+        let mut items = Vec::with_capacity(sources.len());
+        for source in sources {
+            let last_sync = freshness::last_sync_for(&source, &sync_times);
+            let reason = match (&self.ctx.sync, budget) {
+                (Some(sync), Some((remaining, _))) => {
+                    if !sync.sources().contains(&source) {
+                        Some("source not available for on-demand sync".to_string())
+                    } else if !self.sync_allowlisted(&source) {
+                        Some("source not in MINNA_MCP_SYNC_ALLOWLIST".to_string())
+                    } else {
+                        let syncing = sync.is_syncing(&source).await;
+                        policy.check_sync(last_sync, now, syncing, remaining)
+                    }
+                }
+                _ => Some("on-demand sync unavailable".to_string()),
+            };
+            items.push(SourceFreshness {
+                staleness: policy.classify(last_sync, now),
+                age_secs: last_sync.map(|t| (now - t).num_seconds()),
+                last_sync,
+                sync_allowed: reason.is_none(),
+                reason,
+                source,
+            });
+        }
+
+        Ok(FreshnessResult {
+            sources: items,
+            budget_remaining: budget.map(|(remaining, _)| remaining),
+            budget_total: budget.map(|(_, total)| total),
+        })
+    }
+
+    /// Every connected provider and every source with indexed documents,
+    /// with document counts and last sync times, so an agent knows what it
+    /// can search.
+    async fn handle_list_sources(&self) -> Result<ListSourcesResult> {
+        let counts = self.ctx.ingest.document_counts_by_source().await?;
+        let sync_times = self.ctx.ingest.get_sync_times().await?;
+        let connected = self.ctx.sync.as_ref().map(|sync| sync.sources()).unwrap_or_default();
+        let mut sources: Vec<SourceSummary> = connected
+            .into_iter()
+            .map(|source| SourceSummary {
+                connected: true,
+                documents: 0,
+                last_sync: freshness::last_sync_for(&source, &sync_times),
+                source,
+            })
+            .collect();
+        // Documents are stored under sub-source names like `jira` or `gmail`
+        for (doc_source, count) in &counts {
+            match sources.iter_mut().find(|summary| freshness::progress_matches(&summary.source, doc_source)) {
+                Some(summary) => summary.documents += count,
+                None => sources.push(SourceSummary {
+                    source: doc_source.clone(),
+                    connected: false,
+                    documents: *count,
+                    last_sync: freshness::last_sync_for(doc_source, &sync_times),
+                }),
+            }
+        }
+        sources.sort_by(|a, b| a.source.cmp(&b.source));
+        Ok(ListSourcesResult {
+            total_documents: counts.iter().map(|(_, count)| count).sum(),
+            sources,
+        })
+    }
+
+    async fn handle_list_packs(&self, params: serde_json::Value) -> Result<ListPacksResult> {
+        let params: ListPacksParams = if params.is_null() {
+            ListPacksParams::default()
+        } else {
+            serde_json::from_value(params).map_err(|e| anyhow!("invalid list_packs params: {}", e))?
+        };
+        let packs = self
+            .ctx
+            .ingest
+            .latest_clusters(params.limit.unwrap_or(50))
+            .await?
+            .into_iter()
+            .map(pack_summary)
+            .collect();
+        Ok(ListPacksResult { packs })
+    }
+
+    async fn handle_get_pack(&self, params: serde_json::Value) -> Result<PackResult> {
+        let params: GetPackParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid get_pack params: {}", e))?;
+        let cluster = self
+            .ctx
+            .ingest
+            .get_cluster(&params.label)
+            .await?
+            .ok_or_else(|| anyhow!("no pack named {}", params.label))?;
+        let mut docs = self.ctx.ingest.fetch_documents_by_ids(&cluster.doc_ids).await?;
+        docs.sort_by_key(|doc| std::cmp::Reverse(doc.updated_at));
+        docs.truncate(params.limit.unwrap_or(20));
+        Ok(PackResult {
+            pack: pack_summary(cluster),
+            items: docs.into_iter().map(document_entry).collect(),
+        })
+    }
+
+    /// Recently updated documents in order, grouped by day, e.g. to catch up
+    /// after time away.
+    async fn handle_get_timeline(&self, params: serde_json::Value) -> Result<TimelineResult> {
+        let params: GetTimelineParams = if params.is_null() {
+            GetTimelineParams::default()
+        } else {
+            serde_json::from_value(params).map_err(|e| anyhow!("invalid get_timeline params: {}", e))?
+        };
+        let since = chrono::Utc::now() - chrono::Duration::days(params.days.unwrap_or(7).max(1));
+        let limit = params.limit.unwrap_or(100).max(1);
+
+        // One more than asked for tells whether anything was left out
+        let mut docs = if params.sources.is_empty() {
+            self.ctx.ingest.fetch_documents(None, Some(since), None, limit + 1).await?
+        } else {
+            let wanted: Vec<String> = params.sources.iter().map(|source| source.to_ascii_lowercase()).collect();
+            let mut docs = Vec::new();
+            for (doc_source, _) in self.ctx.ingest.document_counts_by_source().await? {
+                let lower = doc_source.to_ascii_lowercase();
+                if wanted.iter().any(|source| freshness::progress_matches(source, &lower)) {
+                    docs.extend(
+                        self.ctx
+                            .ingest
+                            .fetch_documents(Some(&doc_source), Some(since), None, limit + 1)
+                            .await?,
+                    );
+                }
+            }
+            docs.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then(b.id.cmp(&a.id)));
+            docs
+        };
+        let truncated = docs.len() > limit;
+        docs.truncate(limit);
+        docs.reverse();
+
+        let total = docs.len();
+        let mut days: Vec<TimelineDay> = Vec::new();
+        for doc in docs {
+            let date = doc.updated_at.date_naive();
+            let entry = document_entry(doc);
+            match days.last_mut() {
+                Some(day) if day.date == date => day.items.push(entry),
+                _ => days.push(TimelineDay { date, items: vec![entry] }),
+            }
+        }
+        Ok(TimelineResult { since, days, total, truncated })
+    }
+
+    async fn handle_get_stats(&self) -> Result<StatsResult> {
+        let graph = match &self.ctx.graph {
+            Some(graph) => Some(graph.stats().await?),
+            None => None,
+        };
+        Ok(StatsResult {
+            documents: self.ctx.ingest.document_count().await?,
+            vectors: self.ctx.vector.count().await?,
+            clusters: self.ctx.ingest.cluster_count().await?,
+            graph,
+            db_size_bytes: self.ctx.ingest.database_size().await?,
+        })
+    }
+
+    /// The people the user worked with most recently, grouped by ring. The
+    /// user is whoever the last ring calculation put in the Core ring.
+    async fn handle_who_do_i_work_with(&self, params: serde_json::Value) -> Result<WhoDoIWorkWithResult> {
+        let params: WhoDoIWorkWithParams = if params.is_null() {
+            WhoDoIWorkWithParams::default()
+        } else {
+            serde_json::from_value(params).map_err(|e| anyhow!("invalid who_do_i_work_with params: {}", e))?
+        };
+        let graph = self
+            .ctx
+            .graph
+            .as_ref()
+            .ok_or_else(|| anyhow!("relationship graph unavailable"))?;
+        let me = graph.nodes_in_ring(Ring::Core).await?;
+        if me.is_empty() {
+            return Err(anyhow!("rings have not been calculated yet"));
+        }
+        let since = chrono::Utc::now() - chrono::Duration::days(params.days.unwrap_or(30));
+        let groups = RingEngine::new()
+            .collaborators(graph, &me, since, params.limit.unwrap_or(10))
+            .await?;
+        Ok(WhoDoIWorkWithResult { groups })
+    }
+
+    /// Resolve a person across providers: their accounts, how close they are
+    /// and what the user recently worked on with them.
+    async fn handle_who_is(&self, params: serde_json::Value) -> Result<WhoIsResult> {
+        let params: WhoIsParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid who_is params: {}", e))?;
+        let graph = self
+            .ctx
+            .graph
+            .as_ref()
+            .ok_or_else(|| anyhow!("relationship graph unavailable"))?;
+        let limit = params.limit.unwrap_or(5);
+        let me = graph.nodes_in_ring(Ring::Core).await?;
+
+        let mut seen = HashSet::new();
+        let mut people = Vec::new();
+        for node in graph.find_people(&params.name_or_handle, WHO_IS_MATCHES * 2).await? {
+            if people.len() == WHO_IS_MATCHES || seen.contains(&node.id) {
+                continue;
+            }
+            let linked = graph.linked_identities(&node.id).await?;
+            seen.extend(linked.iter().cloned());
+
+            let mut identities = Vec::new();
+            let mut ring: Option<Ring> = None;
+            for id in &linked {
+                if let Some(assignment) = graph.get_ring_assignment(id).await? {
+                    if ring.is_none_or(|ring| assignment.ring.as_int() < ring.as_int()) {
+                        ring = Some(assignment.ring);
+                    }
+                }
+                if let Some(identity) = graph.get_node(id).await? {
+                    identities.push(PersonIdentity {
+                        email: identity
+                            .metadata
+                            .as_ref()
+                            .and_then(|metadata| metadata.get("email"))
+                            .and_then(|email| email.as_str())
+                            .map(str::to_string),
+                        node_id: identity.id,
+                        provider: identity.provider,
+                        handle: identity.external_id,
+                        display_name: identity.display_name,
+                    });
+                }
+            }
+
+            let sources = if me.is_empty() {
+                Vec::new()
+            } else {
+                graph.shared_sources(&linked, &me, limit).await?
+            };
+            let doc_ids: Vec<i64> = sources.iter().map(|source| source.doc_id).collect();
+            let docs: HashMap<i64, Document> = self
+                .ctx
+                .ingest
+                .fetch_documents_by_ids(&doc_ids)
+                .await?
+                .into_iter()
+                .filter_map(|doc| doc.id.map(|id| (id, doc)))
+                .collect();
+            let shared = sources
+                .into_iter()
+                .filter_map(|source| {
+                    docs.get(&source.doc_id).map(|doc| SharedArtifact {
+                        uri: doc.uri.clone(),
+                        short_id: doc.short_id(),
+                        source: doc.source.clone(),
+                        title: doc.title.clone(),
+                        observed_at: source.observed_at,
+                    })
+                })
+                .collect();
+
+            people.push(PersonProfile {
+                display_name: node
+                    .display_name
+                    .or_else(|| identities.iter().find_map(|identity| identity.display_name.clone())),
+                avatar_url: node.avatar_url,
+                node_id: node.id,
+                ring,
+                identities,
+                shared,
             });
         }
+        if people.is_empty() {
+            return Err(anyhow!("no one matching {}", params.name_or_handle));
+        }
+        Ok(WhoIsResult { people })
+    }
 
-        let limit = params.limit.unwrap_or(6);
-        let allowed_ids = if let Some(pack) = &pack {
-            let ids = self.ctx.ingest.get_cluster_doc_ids(pack).await?;
-            Some(ids.into_iter().collect::<HashSet<_>>())
-        } else {
-            None
+    async fn handle_request_sync(&self, params: serde_json::Value) -> Result<RequestSyncResult> {
+        let params: RequestSyncParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid request_sync params: {}", e))?;
+        self.start_sync(&params.source, SyncMode::Recent).await?;
+        Ok(RequestSyncResult {
+            source: params.source,
+            status: "started".to_string(),
+        })
+    }
+
+    /// Sync an allowlisted provider in the chosen mode, subject to the same
+    /// freshness policy and budget as `request_sync`.
+    async fn handle_trigger_sync(&self, params: serde_json::Value) -> Result<TriggerSyncResult> {
+        let params: TriggerSyncParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("invalid trigger_sync params: {}", e))?;
+        self.start_sync(&params.provider, params.mode).await?;
+        Ok(TriggerSyncResult {
+            provider: params.provider,
+            mode: params.mode,
+            status: "started".to_string(),
+        })
+    }
+
+    async fn stream_request_sync(
+        &self,
+        request: ToolRequest,
+        warning: Option<String>,
+        tx: mpsc::UnboundedSender<ToolResponse>,
+    ) {
+        let id = request.id.clone();
+        let error_response = |err: String| ToolResponse {
+            id: id.clone(),
+            ok: false,
+            result: None,
+            error: Some(err),
+            warning: warning.clone(),
+            event: None,
         };
 
-        let semantic = self
-            .ctx
-            .vector
-            .search_semantic(&*self.ctx.embedder, &query, limit * 3)
-            .await?;
-        let keyword = self.ctx.ingest.search_keyword(&query, limit * 3).await?;
+        let params: RequestSyncParams = match serde_json::from_value(request.params) {
+            Ok(params) => params,
+            Err(e) => {
+                let _ = tx.send(error_response(format!("invalid request_sync params: {}", e)));
+                return;
+            }
+        };
 
-        let mut scores: HashMap<i64, f32> = HashMap::new();
-        for (doc_id, score) in semantic {
-            if let Some(filter) = &allowed_ids {
-                if !filter.contains(&doc_id) {
-                    continue;
-                }
+        // Subscribe before starting so early progress events aren't missed
+        let mut progress_rx = subscribe_progress();
+        let mut completion = match self.start_sync(&params.source, SyncMode::Recent).await {
+            Ok(completion) => completion,
+            Err(err) => {
+                let _ = tx.send(error_response(err.to_string()));
+                return;
             }
-            scores.insert(doc_id, score * 0.7);
+        };
+
+        let started = ToolResponse {
+            id: id.clone(),
+            ok: true,
+            result: Some(serde_json::json!({ "source": params.source, "status": "started" })),
+            error: None,
+            warning: warning.clone(),
+            event: None,
+        };
+        if tx.send(started).is_err() {
+            return;
         }
-        for (rank, doc) in keyword.iter().enumerate() {
-            if let Some(doc_id) = doc.id {
-                if let Some(filter) = &allowed_ids {
-                    if !filter.contains(&doc_id) {
+
+        // Without progress events the final response still follows the sync
+        let mut listening = true;
+        loop {
+            tokio::select! {
+                outcome = &mut completion => {
+                    let response = match outcome {
+                        Ok(Ok(summary)) => ToolResponse {
+                            id: id.clone(),
+                            ok: true,
+                            result: Some(serde_json::json!({
+                                "source": params.source,
+                                "status": "complete",
+                                "summary": summary,
+                            })),
+                            error: None,
+                            warning: None,
+                            event: None,
+                        },
+                        Ok(Err(err)) => error_response(err),
+                        Err(_) => error_response("sync task ended unexpectedly".to_string()),
+                    };
+                    let _ = tx.send(response);
+                    break;
+                }
+                event = progress_rx.recv(), if listening => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                            listening = false;
+                            continue;
+                        }
+                    };
+                    let matches = match &event {
+                        InternalEvent::Progress(p) => freshness::progress_matches(&params.source, &p.provider),
+                        InternalEvent::Result(_) | InternalEvent::RingChange(_) => false,
+                    };
+                    if !matches {
                         continue;
                     }
+                    let response = ToolResponse {
+                        id: id.clone(),
+                        ok: true,
+                        result: None,
+                        error: None,
+                        warning: None,
+                        event: Some(event),
+                    };
+                    if tx.send(response).is_err() {
+                        break;
+                    }
                 }
-                let bonus = 0.3 * (1.0 / (rank as f32 + 1.0));
-                *scores.entry(doc_id).or_insert(0.0) += bonus;
             }
         }
+    }
 
-        // Apply ring boost if GraphStore is available (Gravity Well)
-        if let Some(graph) = &self.ctx.graph {
-            scores = self.apply_ring_boost(graph, scores).await;
+    fn sync_allowlisted(&self, source: &str) -> bool {
+        self.ctx
+            .sync_allowlist
+            .iter()
+            .any(|provider| provider == "*" || provider == source)
+    }
+
+    /// Start an on-demand sync if the source is allowlisted and the
+    /// freshness policy and budget allow it.
+    async fn start_sync(&self, source: &str, mode: SyncMode) -> Result<SyncCompletion> {
+        let sync = self
+            .ctx
+            .sync
+            .as_ref()
+            .ok_or_else(|| anyhow!("on-demand sync unavailable"))?;
+
+        if !self.sync_allowlisted(source) {
+            return Err(anyhow!("on-demand sync is not allowed for {}", source));
         }
 
-        let mut scored: Vec<(i64, f32)> = scores.into_iter().collect();
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        scored.truncate(limit);
+        if !sync.sources().iter().any(|available| available == source) {
+            return Err(anyhow!("unknown or disabled source: {}", source));
+        }
 
-        let doc_ids: Vec<i64> = scored.iter().map(|(id, _)| *id).collect();
-        let docs = self.ctx.ingest.fetch_documents_by_ids(&doc_ids).await?;
-        let doc_map: HashMap<i64, Document> = docs
+        let sync_times = self.ctx.ingest.get_sync_times().await?;
+        let last_sync = freshness::last_sync_for(source, &sync_times);
+        let (remaining, _) = sync.budget().await;
+        let syncing = sync.is_syncing(source).await;
+        let now = chrono::Utc::now();
+        if let Some(reason) = self.ctx.freshness.check_sync(last_sync, now, syncing, remaining) {
+            return Err(anyhow!("sync not allowed: {}", reason));
+        }
+
+        sync.start_sync(source, mode).await
+    }
+
+    /// Reorder candidates by cross-encoder relevance, scored 0..1. Keeps the
+    /// hybrid order if the reranker fails or is still loading.
+    async fn rerank(&self, query: &str, ranked: Vec<(Document, f32)>) -> Vec<(Document, f32)> {
+        let Some(reranker) = &self.ctx.reranker else {
+            return ranked;
+        };
+        let passages: Vec<String> = ranked
+            .iter()
+            .map(|(doc, _)| {
+                let title = doc.title.as_deref().unwrap_or_default();
+                format!("{}\n{}", title, truncate(&doc.body, RERANK_PASSAGE_CHARS))
+            })
+            .collect();
+        let relevance = match reranker.rerank(query, &passages).await {
+            Ok(relevance) if relevance.len() == ranked.len() => relevance,
+            Ok(_) => return ranked,
+            Err(err) => {
+                debug!("[RERANK] Keeping hybrid order: {}", err);
+                return ranked;
+            }
+        };
+        let mut reranked: Vec<(Document, f32)> = ranked
             .into_iter()
-            .filter_map(|doc| doc.id.map(|id| (id, doc)))
+            .zip(relevance)
+            .map(|((doc, _), logit)| (doc, 1.0 / (1.0 + (-logit).exp())))
             .collect();
+        reranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        reranked
+    }
 
-        let items = scored
-            .into_iter()
-            .filter_map(|(id, score)| doc_map.get(&id).map(|doc| (doc.clone(), score)))
-            .map(|(doc, score)| ContextItem {
-                uri: doc.uri,
-                source: doc.source,
-                title: doc.title,
-                score,
-                snippet: truncate(&doc.body, 240),
-                content: None,
-            })
-            .collect::<Vec<_>>();
+    /// Down-weight documents from archived channels, repos and pages.
+    async fn apply_archive_penalty(&self, mut scores: HashMap<i64, f32>) -> HashMap<i64, f32> {
+        let prefixes = match self.ctx.ingest.archived_uri_prefixes().await {
+            Ok(prefixes) if !prefixes.is_empty() => prefixes,
+            _ => return scores,
+        };
 
-        Ok(ContextResult {
-            mode: "hybrid".to_string(),
-            items,
-        })
+        let doc_ids: Vec<i64> = scores.keys().copied().collect();
+        let docs = match self.ctx.ingest.fetch_documents_by_ids(&doc_ids).await {
+            Ok(docs) => docs,
+            Err(_) => return scores,
+        };
+
+        for doc in docs {
+            let Some(doc_id) = doc.id else { continue };
+            if prefixes.iter().any(|prefix| doc.uri.starts_with(prefix.as_str())) {
+                if let Some(score) = scores.get_mut(&doc_id) {
+                    *score *= ARCHIVED_SCORE_FACTOR;
+                }
+            }
+        }
+
+        scores
     }
 
-    async fn handle_read_resource(&self, params: serde_json::Value) -> Result<ResourceResult> {
-        let params: ReadResourceParams = serde_json::from_value(params)
-            .map_err(|_| anyhow!("invalid read_resource params"))?;
-        if let Some(doc) = self.ctx.ingest.get_document_by_uri(&params.uri).await? {
-            return Ok(ResourceResult {
-                uri: doc.uri,
-                source: doc.source,
-                body: doc.body,
-            });
+    /// Fold near-duplicates into their canonical document, which keeps the
+    /// best score of the group (and a keyword snippet if it has none). An
+    /// alternate whose canonical document is filtered out stands alone.
+    async fn collapse_duplicates(
+        &self,
+        scores: HashMap<i64, f32>,
+        allowed_ids: Option<&HashSet<i64>>,
+        snippets: &mut HashMap<i64, String>,
+    ) -> HashMap<i64, f32> {
+        let doc_ids: Vec<i64> = scores.keys().copied().collect();
+        let canonical = match self.ctx.ingest.canonical_ids(&doc_ids).await {
+            Ok(canonical) if !canonical.is_empty() => canonical,
+            _ => return scores,
+        };
+        let mut collapsed: HashMap<i64, f32> = HashMap::with_capacity(scores.len());
+        for (doc_id, score) in scores {
+            let target = canonical
+                .get(&doc_id)
+                .copied()
+                .filter(|id| allowed_ids.is_none_or(|allowed| allowed.contains(id)))
+                .unwrap_or(doc_id);
+            if target != doc_id && !snippets.contains_key(&target) {
+                if let Some(snippet) = snippets.remove(&doc_id) {
+                    snippets.insert(target, snippet);
+                }
+            }
+            let best = collapsed.entry(target).or_insert(score);
+            *best = best.max(score);
         }
-        if let Some(sync) = self.router.fetch_url(&params.uri).await? {
-            return Ok(ResourceResult {
-                uri: sync.url,
-                source: sync.source,
-                body: sync.markdown,
-            });
+        collapsed
+    }
+
+    /// Boost documents written in `language` (ISO 639-3).
+    async fn apply_language_boost(
+        &self,
+        language: &str,
+        mut scores: HashMap<i64, f32>,
+    ) -> HashMap<i64, f32> {
+        let doc_ids: Vec<i64> = scores.keys().copied().collect();
+        let languages = match self.ctx.ingest.document_languages(&doc_ids).await {
+            Ok(languages) => languages,
+            Err(_) => return scores,
+        };
+        for (doc_id, doc_language) in languages {
+            if doc_language == language {
+                if let Some(score) = scores.get_mut(&doc_id) {
+                    *score *= PREFERRED_LANGUAGE_FACTOR;
+                }
+            }
         }
-        Err(anyhow!("resource not found"))
+        scores
     }
 
     /// Apply ring-based boost to search scores.
     ///
-    /// Documents associated with closer ring assignments get higher scores:
+    /// A document takes the closest ring of the nodes it connects: its
+    /// author, the channel or project it belongs to, or the people it
+    /// mentions. Documents associated with closer rings get higher scores:
     /// - Core: 1.5x boost
     /// - Ring 1: 1.3x boost
     /// - Ring 2: 1.1x boost
@@ -375,44 +1995,39 @@ impl McpHandler {
         graph: &GraphStore,
         mut scores: HashMap<i64, f32>,
     ) -> HashMap<i64, f32> {
-        // Fetch documents to get their URIs
         let doc_ids: Vec<i64> = scores.keys().copied().collect();
-        let docs = match self.ctx.ingest.fetch_documents_by_ids(&doc_ids).await {
-            Ok(docs) => docs,
+        let doc_rings = match graph.rings_for_documents(&doc_ids).await {
+            Ok(rings) => rings,
             Err(_) => return scores, // Fall back to unboosted scores
         };
-
-        for doc in docs {
-            let Some(doc_id) = doc.id else { continue };
-            let Some(score) = scores.get_mut(&doc_id) else { continue };
-
-            // Try to find a ring assignment for this document's entity
-            // Construct potential node IDs from the document
-            let node_ids = extract_node_ids_from_doc(&doc);
-
-            let mut best_boost = 1.0f32;
-            for node_id in node_ids {
-                if let Ok(Some(assignment)) = graph.get_ring_assignment(&node_id).await {
-                    let boost = ring_boost(assignment.ring);
-                    if boost > best_boost {
-                        best_boost = boost;
-                    }
-                }
+        for (doc_id, ring) in doc_rings {
+            let boost = ring_boost(ring);
+            if boost <= 1.0 {
+                continue;
             }
-
-            if best_boost > 1.0 {
-                debug!(
-                    "Ring boost applied: doc_id={}, uri={}, boost={}",
-                    doc_id, doc.uri, best_boost
-                );
-                *score *= best_boost;
+            if let Some(score) = scores.get_mut(&doc_id) {
+                debug!("Ring boost applied: doc_id={}, boost={}", doc_id, boost);
+                *score *= boost;
             }
         }
-
         scores
     }
 }
 
+/// People `who_is` returns for an ambiguous name.
+const WHO_IS_MATCHES: usize = 5;
+/// Least cosine similarity for `get_related` to list a document as similar.
+const RELATED_MIN_SIMILARITY: f32 = 0.5;
+/// Score multiplier for documents from archived resources.
+const ARCHIVED_SCORE_FACTOR: f32 = 0.5;
+/// get_context candidates handed to the reranker by default.
+pub const DEFAULT_RERANK_CANDIDATES: usize = 20;
+/// Cross-encoders read ~512 tokens; the rest of a long body is wasted work.
+const RERANK_PASSAGE_CHARS: usize = 2000;
+
+/// Boost for documents in `prefer_language`.
+const PREFERRED_LANGUAGE_FACTOR: f32 = 1.3;
+
 /// Get the boost multiplier for a ring.
 fn ring_boost(ring: Ring) -> f32 {
     match ring {
@@ -423,62 +2038,6 @@ fn ring_boost(ring: Ring) -> f32 {
     }
 }
 
-/// Extract potential graph node IDs from a document.
-///
-/// Attempts to construct node IDs based on the document's source and URI.
-fn extract_node_ids_from_doc(doc: &Document) -> Vec<String> {
-    let mut ids = Vec::new();
-    let source = &doc.source;
-    let uri = &doc.uri;
-
-    // Try to extract entity type and ID from URI patterns
-    // Linear: linear://issue/ISSUE-123 -> issue:linear:ISSUE-123
-    if source == "linear" {
-        if let Some(id) = uri.strip_prefix("linear://issue/") {
-            ids.push(format!("issue:linear:{}", id));
-        }
-    }
-
-    // GitHub: github://org/repo/pr/123 -> pr:github:org/repo/123
-    if source == "github" {
-        if uri.contains("/pr/") || uri.contains("/pull/") {
-            // Extract PR number from URI
-            if let Some(num) = uri.split('/').next_back() {
-                ids.push(format!("pr:github:{}", num));
-            }
-        } else if uri.contains("/issues/") {
-            if let Some(num) = uri.split('/').next_back() {
-                ids.push(format!("issue:github:{}", num));
-            }
-        }
-    }
-
-    // Slack: slack://C123/1234567890.123456 -> message:slack:1234567890.123456
-    if source == "slack" {
-        if let Some(ts) = uri.split('/').next_back() {
-            if ts.contains('.') {
-                ids.push(format!("message:slack:{}", ts));
-            }
-        }
-    }
-
-    // Notion: notion://page/xxx -> document:notion:xxx
-    if source == "notion" {
-        if let Some(id) = uri.strip_prefix("notion://page/") {
-            ids.push(format!("document:notion:{}", id));
-        }
-    }
-
-    // Google: google://doc/xxx -> document:google:xxx
-    if source == "google" || source == "google_drive" {
-        if let Some(id) = uri.strip_prefix("google://doc/") {
-            ids.push(format!("document:google:{}", id));
-        }
-    }
-
-    ids
-}
-
 fn parse_get_context_params(params: serde_json::Value) -> Result<GetContextParams> {
     if let Ok(parsed) = serde_json::from_value::<GetContextParams>(params.clone()) {
         return Ok(parsed);
@@ -488,11 +2047,67 @@ fn parse_get_context_params(params: serde_json::Value) -> Result<GetContextParam
             query: query.to_string(),
             pack: None,
             limit: None,
+            tags: Vec::new(),
+            languages: Vec::new(),
+            prefer_language: None,
+            deep_fetch: None,
+            exclude_doc_ids: Vec::new(),
+            sources: Vec::new(),
+            after: None,
+            before: None,
+            uri_prefix: None,
+            cursor: None,
         });
     }
     Err(anyhow!("invalid get_context params"))
 }
 
+/// Documents with less than this much text beyond headings and `- Key: value`
+/// metadata lines are stubs (e.g. Drive files whose content couldn't be read).
+const STUB_CONTENT_CHARS: usize = 80;
+
+fn is_metadata_stub(doc: &Document) -> bool {
+    let content: usize = doc
+        .body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter(|line| !(line.starts_with("- ") && line.contains(": ")))
+        .map(|line| line.chars().count())
+        .sum();
+    content < STUB_CONTENT_CHARS
+}
+
+fn document_entry(doc: Document) -> DocumentEntry {
+    DocumentEntry {
+        short_id: doc.short_id(),
+        snippet: truncate(&doc.body, 240),
+        uri: doc.uri,
+        source: doc.source,
+        title: doc.title,
+        updated_at: doc.updated_at,
+    }
+}
+
+fn checkpoint_summary(checkpoint: Checkpoint) -> CheckpointSummary {
+    CheckpointSummary {
+        summary: truncate(&checkpoint.summary, 200),
+        title: checkpoint.title,
+        version: checkpoint.version,
+        trigger: checkpoint.trigger,
+        created_at: checkpoint.created_at,
+    }
+}
+
+fn pack_summary(cluster: ClusterRecord) -> PackSummary {
+    PackSummary {
+        documents: cluster.doc_ids.len(),
+        label: cluster.label,
+        summary: cluster.summary,
+        created_at: cluster.created_at,
+    }
+}
+
 fn truncate(text: &str, max: usize) -> String {
     if text.len() <= max {
         return text.to_string();
@@ -525,6 +2140,7 @@ enum UrlKind {
     GithubPr { owner: String, repo: String, number: i64 },
     SlackThread { channel: String, ts: String },
     LinearIssue { identifier: String },
+    DriveFile { kind: String, id: String },
 }
 
 #[derive(Clone)]
@@ -532,6 +2148,7 @@ pub struct UrlInterceptor {
     github: Regex,
     slack: Regex,
     linear: Regex,
+    drive: Regex,
 }
 
 impl Default for UrlInterceptor {
@@ -549,6 +2166,11 @@ impl UrlInterceptor {
                 .unwrap(),
             linear: Regex::new(r"(?:https?://)?linear\.app/[\w-]+/issue/([\w-]+-\d+)")
                 .unwrap(),
+            // Drive web links, plus the drive:// URIs of files indexed without one
+            drive: Regex::new(
+                r"(?:(?:https?://)?(?:docs|drive)\.google\.com/(document|spreadsheets|presentation|file)/d/|(drive)://)([\w-]+)",
+            )
+            .unwrap(),
         }
     }
 
@@ -580,6 +2202,16 @@ impl UrlInterceptor {
             let url = cap.get(0).unwrap().as_str().to_string();
             matches.push((url, UrlKind::LinearIssue { identifier }));
         }
+        for cap in self.drive.captures_iter(text) {
+            let kind = cap
+                .get(1)
+                .map(|m| m.as_str())
+                .unwrap_or("file")
+                .to_string();
+            let id = cap.get(3).unwrap().as_str().to_string();
+            let url = cap.get(0).unwrap().as_str().to_string();
+            matches.push((url, UrlKind::DriveFile { kind, id }));
+        }
         matches
     }
 }
@@ -650,6 +2282,16 @@ impl SynchronousRouter {
                     markdown,
                 })
             }
+            UrlKind::DriveFile { kind, id } => {
+                let token = self.get_token(Provider::Google).await?;
+                let markdown = self.fetch_drive_file(&token, &kind, &id).await?;
+                Ok(SyncContent {
+                    url: url.to_string(),
+                    source: "google_drive".to_string(),
+                    title: None,
+                    markdown,
+                })
+            }
         }
     }
 
@@ -799,6 +2441,59 @@ impl SynchronousRouter {
             title, state, assignee, url, description
         ))
     }
+
+    /// Fetch a Drive file's text in a single request: Google Docs, Sheets and
+    /// Slides are exported, other files downloaded and run through the
+    /// ingest extractors based on the returned content type.
+    async fn fetch_drive_file(&self, token: &str, kind: &str, id: &str) -> Result<String> {
+        let base = format!("https://www.googleapis.com/drive/v3/files/{}", id);
+        let request = match kind {
+            "document" | "presentation" => self
+                .client
+                .get(format!("{}/export", base))
+                .query(&[("mimeType", "text/plain")]),
+            "spreadsheets" => self
+                .client
+                .get(format!("{}/export", base))
+                .query(&[("mimeType", "text/csv")]),
+            _ => self.client.get(&base).query(&[("alt", "media")]),
+        };
+        let response = request.bearer_auth(token).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("drive fetch failed: {} - {}", status, body));
+        }
+
+        let mime_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .unwrap_or("application/octet-stream")
+            .trim()
+            .to_string();
+        let bytes = response.bytes().await?;
+        if bytes.len() > extractors::MAX_EXTRACT_BYTES {
+            return Err(anyhow!("drive file too large ({} bytes)", bytes.len()));
+        }
+
+        let text = if mime_type.starts_with("text/") {
+            String::from_utf8_lossy(&bytes).into_owned()
+        } else if extractors::is_supported(&mime_type) {
+            let mime = mime_type.clone();
+            tokio::task::spawn_blocking(move || extractors::extract_text(&mime, &bytes))
+                .await??
+                .unwrap_or_default()
+        } else {
+            return Err(anyhow!("no text extractor for {}", mime_type));
+        };
+        let text: String = text.trim().chars().take(extractors::MAX_EXTRACTED_CHARS).collect();
+        if text.is_empty() {
+            return Err(anyhow!("drive file {} has no text", id));
+        }
+        Ok(text)
+    }
 }
 
 fn slack_ts(raw: &str) -> String {
@@ -808,3 +2503,349 @@ fn slack_ts(raw: &str) -> String {
     let (secs, frac) = raw.split_at(10);
     format!("{}.{}", secs, frac)
 }
+
+#[cfg(test)]
+mod tests {
+    use minna_vector::HashEmbedder;
+
+    use super::*;
+
+    async fn handler(dir: &tempfile::TempDir) -> McpHandler {
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let vector = VectorStore::new(&dir.path().join("vectors.db")).await.unwrap();
+        let auth = TokenStore::load(&dir.path().join("auth.json")).unwrap();
+        McpHandler::new(McpContext::new(ingest, vector, auth, Arc::new(HashEmbedder::default())))
+    }
+
+    async fn index(handler: &McpHandler, uri: &str, source: &str) -> i64 {
+        let doc = Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: "body".to_string(),
+            updated_at: chrono::Utc::now(),
+        };
+        handler.ctx.ingest.upsert_document(&doc).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archived_documents_are_demoted_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let archived = index(&handler, "https://slack.com/archives/C123/p1700000000000100", "slack").await;
+        let sibling = index(&handler, "https://slack.com/archives/C1234/p1700000000000200", "slack").await;
+        let other = index(&handler, "https://github.com/acme/api/issues/7", "github").await;
+        let scores: HashMap<i64, f32> = [(archived, 0.8), (sibling, 0.6), (other, 0.4)].into();
+
+        // Nothing archived yet: scores pass through
+        assert_eq!(handler.apply_archive_penalty(scores.clone()).await, scores);
+
+        handler
+            .ctx
+            .ingest
+            .mark_archived("slack", "C123", Some("old-launch"), "https://slack.com/archives/C123/")
+            .await
+            .unwrap();
+        let demoted = handler.apply_archive_penalty(scores.clone()).await;
+        assert_eq!(demoted[&archived], 0.8 * ARCHIVED_SCORE_FACTOR);
+        // A longer channel ID sharing the prefix text isn't archived
+        assert_eq!(demoted[&sibling], 0.6);
+        assert_eq!(demoted[&other], 0.4);
+
+        handler.ctx.ingest.unmark_archived("slack", "C123").await.unwrap();
+        assert_eq!(handler.apply_archive_penalty(scores.clone()).await, scores);
+    }
+
+    fn document(uri: &str, body: &str) -> Document {
+        Document {
+            id: Some(1),
+            uri: uri.to_string(),
+            source: "google".to_string(),
+            title: Some("Q3 plan.pdf".to_string()),
+            body: body.to_string(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_metadata_stubs() {
+        let stub = "# Q3 plan.pdf\n\n- Owner: alice@example.com\n- Modified: 2024-05-01\n- Type: application/pdf";
+        assert!(is_metadata_stub(&document("drive://1", stub)));
+        assert!(is_metadata_stub(&document("drive://1", "")));
+
+        let content = format!(
+            "{}\n\nThe plan moves billing onto the new ledger service before the October freeze, then retires the old one.",
+            stub
+        );
+        assert!(!is_metadata_stub(&document("drive://1", &content)));
+    }
+
+    #[tokio::test]
+    async fn test_deep_fetch_needs_all_stubs_and_a_routable_uri() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let stub = document("mock://drive/1", "# Q3 plan.pdf\n\n- Owner: alice");
+        let full = document("mock://drive/2", &"Billing moves onto the ledger service. ".repeat(5));
+
+        assert_eq!(handler.deep_fetch_stubs(&mut []).await, None);
+
+        // A hit with real content means the results aren't thin
+        let mut ranked = vec![(stub.clone(), 0.9), (full, 0.5)];
+        assert_eq!(handler.deep_fetch_stubs(&mut ranked).await, None);
+        assert_eq!(ranked[0].0.body, stub.body);
+
+        // No provider can fetch this URI: the stub is returned as is
+        let mut ranked = vec![(stub.clone(), 0.9)];
+        assert_eq!(handler.deep_fetch_stubs(&mut ranked).await, None);
+        assert_eq!(ranked[0].0.body, stub.body);
+    }
+
+    #[tokio::test]
+    async fn test_preferred_language_is_boosted() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let mut ids = Vec::new();
+        for (n, body) in [
+            "The deployment was rolled back after the error rate doubled during the evening traffic peak.",
+            "Die Bereitstellung wurde zurückgesetzt, nachdem sich die Fehlerrate am Abend verdoppelt hatte.",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let mut doc = document(&format!("mock://{}", n), body);
+            doc.id = None;
+            ids.push(handler.ctx.ingest.upsert_document(&doc).await.unwrap());
+        }
+        let scores: HashMap<i64, f32> = [(ids[0], 0.5), (ids[1], 0.5)].into();
+
+        let boosted = handler.apply_language_boost("deu", scores).await;
+        assert_eq!(boosted[&ids[0]], 0.5);
+        assert_eq!(boosted[&ids[1]], 0.5 * PREFERRED_LANGUAGE_FACTOR);
+    }
+
+    #[tokio::test]
+    async fn test_read_resource_serves_attachments() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let doc_id = index(&handler, "slack://C1/1", "slack").await;
+        let ingest = &handler.ctx.ingest;
+        let text = ingest.store_attachment(doc_id, "notes.txt", "text/plain", b"standup notes").await.unwrap();
+        let image = ingest.store_attachment(doc_id, "shot.png", "image/png", &[0x89, b'P', 0xff]).await.unwrap();
+
+        let doc = handler.handle_read_resource(serde_json::json!({ "uri": "slack://C1/1" })).await.unwrap();
+        assert_eq!(doc.attachments, vec![text.uri(), image.uri()]);
+
+        let read = handler.handle_read_resource(serde_json::json!({ "uri": text.uri() })).await.unwrap();
+        assert_eq!((read.body.as_str(), read.encoding), ("standup notes", None));
+        assert_eq!((read.source.as_str(), read.mime_type.as_deref()), ("slack", Some("text/plain")));
+
+        let read = handler.handle_read_resource(serde_json::json!({ "uri": image.uri() })).await.unwrap();
+        assert_eq!(read.encoding.as_deref(), Some("base64"));
+        assert_eq!(BASE64.decode(&read.body).unwrap(), vec![0x89, b'P', 0xff]);
+
+        assert!(handler.handle_read_resource(serde_json::json!({ "uri": "attachment://999" })).await.is_err());
+    }
+
+    /// Scores passages by how often they mention "rollback"; fails on "fail".
+    struct CountingReranker;
+
+    #[async_trait::async_trait]
+    impl Reranker for CountingReranker {
+        async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<f32>> {
+            if query == "fail" {
+                return Err(anyhow!("model still loading"));
+            }
+            Ok(documents.iter().map(|doc| doc.matches("rollback").count() as f32).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rerank_reorders_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let vector = VectorStore::new(&dir.path().join("vectors.db")).await.unwrap();
+        let auth = TokenStore::load(&dir.path().join("auth.json")).unwrap();
+        let ctx = McpContext::new(ingest, vector, auth, Arc::new(HashEmbedder::default()))
+            .with_reranker(Arc::new(CountingReranker), 10);
+        let handler = McpHandler::new(ctx);
+        let ranked = vec![
+            (document("mock://1", "lunch menu"), 0.9),
+            (document("mock://2", "rollback plan, rollback owner"), 0.5),
+            (document("mock://3", "rollback notes"), 0.7),
+        ];
+        let uris = |ranked: &[(Document, f32)]| ranked.iter().map(|(doc, _)| doc.uri.clone()).collect::<Vec<_>>();
+
+        let reranked = handler.rerank("rollback", ranked.clone()).await;
+        assert_eq!(uris(&reranked), vec!["mock://2", "mock://3", "mock://1"]);
+        // Logits become 0..1 relevance
+        assert_eq!(reranked[2].1, 0.5);
+        assert!(reranked.iter().all(|(_, score)| (0.0..1.0).contains(score)));
+
+        // A failing reranker keeps the hybrid order and scores
+        let kept = handler.rerank("fail", ranked.clone()).await;
+        assert_eq!(uris(&kept), uris(&ranked));
+        assert_eq!(kept[0].1, 0.9);
+    }
+
+    #[tokio::test]
+    async fn test_ring_boost_reads_cached_rings() {
+        use minna_graph::{ExtractedEdge, NodeRef, Relation, RingEngine};
+
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let graph = handler.ctx.ingest.graph_store();
+        let me = NodeRef::user("slack", "me");
+        let stranger = NodeRef::user("slack", "stranger");
+        let now = chrono::Utc::now();
+        let mine = ExtractedEdge::new(me.clone(), NodeRef::message("slack", "C1:1.0"), Relation::AuthorOf, now);
+        graph.upsert_edge_from_document(&mine, 1).await.unwrap();
+        let theirs = ExtractedEdge::new(stranger, NodeRef::message("slack", "C9:1.0"), Relation::AuthorOf, now);
+        graph.upsert_edge_from_document(&theirs, 2).await.unwrap();
+        RingEngine::new().recalculate_rings(&graph, &me.canonical_id()).await.unwrap();
+
+        let scores: HashMap<i64, f32> = [(1, 0.5), (2, 0.5), (3, 0.5)].into();
+        let boosted = handler.apply_ring_boost(&graph, scores.clone()).await;
+        assert_eq!(boosted[&1], 0.5 * ring_boost(Ring::Core));
+        assert_eq!((boosted[&2], boosted[&3]), (0.5, 0.5));
+
+        // Edges stored since the last recalculation count once it refreshes
+        let late = ExtractedEdge::new(me, NodeRef::issue("linear", "ENG-1"), Relation::AuthorOf, now);
+        graph.upsert_edge_from_document(&late, 3).await.unwrap();
+        assert_eq!(handler.apply_ring_boost(&graph, scores.clone()).await[&3], 0.5);
+        graph.refresh_doc_rings().await.unwrap();
+        assert_eq!(handler.apply_ring_boost(&graph, scores).await[&3], 0.5 * ring_boost(Ring::Core));
+    }
+
+    #[tokio::test]
+    async fn test_get_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let kept = index(&handler, "slack://C1/1", "slack").await;
+        let gone = index(&handler, "slack://C1/2", "slack").await;
+        handler.ctx.ingest.delete_documents(&[gone]).await.unwrap();
+        handler.ctx.vector.upsert_embedding(kept, &[0.6, 0.8]).await.unwrap();
+        let cluster = minna_ingest::ClusterRecord {
+            id: None,
+            label: "standups".to_string(),
+            doc_ids: vec![kept],
+            created_at: chrono::Utc::now(),
+            summary: None,
+        };
+        handler.ctx.ingest.store_clusters(&[cluster]).await.unwrap();
+
+        let stats = handler.handle_get_stats().await.unwrap();
+        assert_eq!((stats.documents, stats.vectors, stats.clusters), (1, 1, 1));
+        assert!(stats.db_size_bytes > 0);
+        // Without a graph the field is left out rather than zeroed
+        assert!(stats.graph.is_none());
+        assert!(serde_json::to_value(&stats).unwrap().get("graph").is_none());
+
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        let vector = VectorStore::new(&dir.path().join("vectors.db")).await.unwrap();
+        let auth = TokenStore::load(&dir.path().join("auth.json")).unwrap();
+        let graph = ingest.graph_store();
+        let ctx = McpContext::with_graph(ingest, vector, auth, Arc::new(HashEmbedder::default()), graph);
+        let stats = McpHandler::new(ctx).handle_get_stats().await.unwrap();
+        assert_eq!(stats.graph.map(|graph| graph.nodes), Some(0));
+    }
+
+    /// Accepts every sync of `slack` or `github` and finishes it at once.
+    struct AcceptingSync;
+
+    #[async_trait::async_trait]
+    impl SyncController for AcceptingSync {
+        fn sources(&self) -> Vec<String> {
+            vec!["slack".to_string(), "github".to_string()]
+        }
+
+        async fn budget(&self) -> (u32, u32) {
+            (500, 500)
+        }
+
+        async fn is_syncing(&self, _source: &str) -> bool {
+            false
+        }
+
+        async fn start_sync(&self, _source: &str, _mode: SyncMode) -> Result<SyncCompletion> {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            let _ = tx.send(Ok(serde_json::json!({})));
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_sync_respects_the_allowlist() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = handler(&dir)
+            .await
+            .ctx
+            .with_sync_controller(Arc::new(AcceptingSync))
+            .with_sync_allowlist(vec!["github".to_string()]);
+        let handler = McpHandler::new(ctx);
+        let request = |tool: &str, params: serde_json::Value| ToolRequest {
+            id: Some("1".to_string()),
+            tool: Some(tool.to_string()),
+            method: None,
+            params,
+        };
+
+        let response = handler.handle(request("request_sync", serde_json::json!({ "source": "slack" }))).await;
+        assert!(!response.ok);
+        assert_eq!(response.error.as_deref(), Some("on-demand sync is not allowed for slack"));
+        let response = handler.handle(request("trigger_sync", serde_json::json!({ "provider": "slack" }))).await;
+        assert!(!response.ok);
+
+        let response = handler.handle(request("request_sync", serde_json::json!({ "source": "github" }))).await;
+        assert!(response.ok, "{:?}", response.error);
+    }
+
+    #[tokio::test]
+    async fn test_jsonrpc_unknown_tool_is_a_protocol_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler = handler(&dir).await;
+        let call = |name: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(1)),
+            method: Some("tools/call".to_string()),
+            params: serde_json::json!({ "name": name, "arguments": { "source": "slack" } }),
+        };
+
+        let response = handler.handle_jsonrpc(call("no_such_tool")).await.unwrap();
+        assert_eq!(response.error.map(|error| error.code), Some(protocol::INVALID_PARAMS));
+
+        // A failing tool is a result the model sees
+        let response = handler.handle_jsonrpc(call("request_sync")).await.unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["isError"], serde_json::json!(true));
+        assert_eq!(result["content"][0]["text"], serde_json::json!("on-demand sync unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_request_sync_ends_with_its_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = handler(&dir)
+            .await
+            .ctx
+            .with_sync_controller(Arc::new(AcceptingSync))
+            .with_sync_allowlist(vec!["*".to_string()]);
+        let handler = McpHandler::new(ctx);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let request = ToolRequest {
+            id: Some("1".to_string()),
+            tool: Some("request_sync".to_string()),
+            method: None,
+            params: serde_json::json!({ "source": "slack", "stream": true }),
+        };
+        handler.handle_streaming(request, tx).await;
+
+        let mut statuses = Vec::new();
+        while let Some(response) = rx.recv().await {
+            assert!(response.ok, "{:?}", response.error);
+            if let Some(result) = response.result {
+                statuses.push(result["status"].clone());
+            }
+        }
+        assert_eq!(statuses, vec![serde_json::json!("started"), serde_json::json!("complete")]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Rankings kept between `get_context` calls, so agents can page deeper with
+//! `next_cursor` without the query being searched and re-ranked again.
+//!
+//! A cursor is opaque to clients: it names a stored ranking and the offset of
+//! the next page. Rankings expire after a while and only the most recent are
+//! kept, so an old cursor asks the agent to run the query again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+/// How long a ranking can be paged through.
+const RANKING_TTL: Duration = Duration::from_secs(15 * 60);
+/// Rankings kept at once; the oldest goes first.
+const MAX_RANKINGS: usize = 64;
+
+/// One query's results, best first.
+#[derive(Debug)]
+pub struct Ranking {
+    /// The query as the agent sent it; a cursor only pages its own query.
+    pub query: String,
+    /// Document IDs and scores.
+    pub hits: Vec<(i64, f32)>,
+    /// Highlighted keyword matches, by document ID.
+    pub snippets: HashMap<i64, String>,
+    /// The first page's `mode`, reported on every page.
+    pub mode: String,
+    /// Whether a page of nothing but metadata stubs fetches its top result
+    /// live, as the first call asked.
+    pub deep_fetch: bool,
+}
+
+/// Where a cursor points: a stored ranking and the offset of its page.
+pub struct Page {
+    pub ranking: Arc<Ranking>,
+    pub offset: usize,
+    key: u64,
+}
+
+impl Page {
+    /// The cursor for the page of the same ranking starting at `offset`.
+    pub fn cursor_at(&self, offset: usize) -> String {
+        cursor(self.key, offset)
+    }
+}
+
+/// Rankings of every connection: the daemon keeps one, so a client that
+/// reconnects can still use its cursors.
+pub struct ContextPages {
+    inner: Mutex<Rankings>,
+}
+
+struct Rankings {
+    next_key: u64,
+    /// Oldest first.
+    stored: VecDeque<(u64, Instant, Arc<Ranking>)>,
+}
+
+impl Default for ContextPages {
+    fn default() -> Self {
+        // Start from the clock so a restarted daemon doesn't hand out the
+        // keys of cursors an agent still holds
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or_default();
+        Self {
+            inner: Mutex::new(Rankings { next_key: seed, stored: VecDeque::new() }),
+        }
+    }
+}
+
+impl ContextPages {
+    /// Keep `ranking` and return the cursor for its page starting at `offset`.
+    pub fn store(&self, ranking: Ranking, offset: usize) -> String {
+        let mut rankings = self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        rankings.evict_expired();
+        let key = rankings.next_key;
+        rankings.next_key = rankings.next_key.wrapping_add(1);
+        if rankings.stored.len() >= MAX_RANKINGS {
+            rankings.stored.pop_front();
+        }
+        rankings.stored.push_back((key, Instant::now(), Arc::new(ranking)));
+        cursor(key, offset)
+    }
+
+    /// The page a cursor points to.
+    pub fn get(&self, cursor: &str) -> Result<Page> {
+        let (key, offset) = parse_cursor(cursor).ok_or_else(|| anyhow!("invalid cursor"))?;
+        let mut rankings = self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        rankings.evict_expired();
+        rankings
+            .stored
+            .iter()
+            .find(|(stored_key, _, _)| *stored_key == key)
+            .map(|(_, _, ranking)| Page { ranking: ranking.clone(), offset, key })
+            .ok_or_else(|| anyhow!("cursor expired; run the query again"))
+    }
+}
+
+impl Rankings {
+    fn evict_expired(&mut self) {
+        self.stored.retain(|(_, stored_at, _)| stored_at.elapsed() < RANKING_TTL);
+    }
+}
+
+/// The cursor for the page of ranking `key` starting at `offset`.
+fn cursor(key: u64, offset: usize) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", key, offset))
+}
+
+fn parse_cursor(cursor: &str) -> Option<(u64, usize)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (key, offset) = decoded.split_once(':')?;
+    Some((key.parse().ok()?, offset.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranking(query: &str) -> Ranking {
+        Ranking {
+            query: query.to_string(),
+            hits: vec![(1, 0.9), (2, 0.8), (3, 0.7)],
+            snippets: HashMap::new(),
+            mode: "hybrid_deep_fetch".to_string(),
+            deep_fetch: true,
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let pages = ContextPages::default();
+        let cursor = pages.store(ranking("retro"), 2);
+        let page = pages.get(&cursor).unwrap();
+        assert_eq!(page.offset, 2);
+        assert_eq!(page.ranking.query, "retro");
+        assert_eq!(page.ranking.mode, "hybrid_deep_fetch");
+        assert!(page.ranking.deep_fetch);
+
+        let next = pages.get(&page.cursor_at(3)).unwrap();
+        assert_eq!(next.offset, 3);
+        assert!(Arc::ptr_eq(&next.ranking, &page.ranking));
+        assert!(pages.get("nope").is_err());
+    }
+
+    #[test]
+    fn test_oldest_ranking_evicted() {
+        let pages = ContextPages::default();
+        let first = pages.store(ranking("first"), 1);
+        for _ in 0..MAX_RANKINGS {
+            pages.store(ranking("later"), 1);
+        }
+        let err = pages.get(&first).err().unwrap();
+        assert!(err.to_string().contains("expired"), "{}", err);
+    }
+}
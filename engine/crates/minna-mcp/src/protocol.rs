@@ -0,0 +1,460 @@
+//! Model Context Protocol framing for the MCP handler.
+//!
+//! MCP clients such as Claude Desktop and Cursor speak JSON-RPC 2.0: they
+//! `initialize`, discover tools with `tools/list`, call them with
+//! `tools/call` and browse documents with `resources/list` and
+//! `resources/read`. Messages carrying `"jsonrpc": "2.0"` are handled here;
+//! anything else keeps the bespoke `{tool, params}` framing.
+//!
+//! Progress notifications are not supported: `_meta.progressToken` on
+//! `tools/call` is ignored and no `notifications/progress` are sent. Progress
+//! of `request_sync` is only streamed over the `{tool, params}` framing.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{McpHandler, ToolRequest};
+
+/// Protocol revisions we understand, newest first. A client asking for one
+/// of them gets it back; anything else gets the newest.
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Documents `resources/list` returns, most recently updated first.
+const RESOURCE_LIST_LIMIT: usize = 100;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    /// Absent for notifications, which get no response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id, result: Some(result), error: None }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError { code, message: message.into() }),
+        }
+    }
+}
+
+/// Whether a message uses JSON-RPC framing rather than `{tool, params}`.
+pub fn is_jsonrpc(message: &Value) -> bool {
+    message.get("jsonrpc").is_some()
+}
+
+/// Name, description and input schema of every tool, in `tools/list` form.
+fn tool_definitions() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "get_context",
+            "description": "Search everything Minna has indexed (Slack, GitHub, Linear, Google, Notion, Atlassian, local git) and return the most relevant documents for a query.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to look for. Supports \"phrases\", -term, source:, tag: and lang:." },
+                    "pack": { "type": "string", "description": "Context pack to search within (see list_packs)." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Documents to return." },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "Only documents carrying one of these tags." },
+                    "languages": { "type": "array", "items": { "type": "string" }, "description": "Only documents in these languages (ISO 639-3 codes or English names)." },
+                    "prefer_language": { "type": "string", "description": "Rank documents in this language higher." },
+                    "deep_fetch": { "type": "boolean", "description": "Fetch the top result live when only metadata is indexed." },
+                    "exclude_doc_ids": { "type": "array", "items": { "type": "string" }, "description": "Short IDs or URIs of documents already shown." },
+                    "sources": { "type": "array", "items": { "type": "string" }, "description": "Only documents from these sources, e.g. [\"slack\"]." },
+                    "after": { "type": "string", "format": "date-time", "description": "Only documents updated at or after this time (RFC 3339)." },
+                    "before": { "type": "string", "format": "date-time", "description": "Only documents updated before this time (RFC 3339)." },
+                    "uri_prefix": { "type": "string", "description": "Only documents whose URI starts with this." },
+                    "cursor": { "type": "string", "description": "next_cursor of an earlier call with the same query, for the next page." }
+                },
+                "required": ["query"]
+            }
+        }),
+        json!({
+            "name": "read_resource",
+            "description": "Read a document, attachment or supported URL in full.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "uri": { "type": "string", "description": "Document URI or short ID (e.g. sl-4f2a)." }
+                },
+                "required": ["uri"]
+            }
+        }),
+        json!({
+            "name": "get_related",
+            "description": "Find documents connected to a document (links, shared issues, PRs and threads) and documents similar to it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "uri": { "type": "string", "description": "Document URI or short ID (e.g. gh-4f2a)." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Documents to return per list (default 10)." }
+                },
+                "required": ["uri"]
+            }
+        }),
+        json!({
+            "name": "summarize_thread",
+            "description": "Condense a Slack thread, pull request or issue: participants, summary, key facts, open questions and action items.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "uri": { "type": "string", "description": "Thread, PR or issue URL, or an indexed document's URI or short ID." }
+                },
+                "required": ["uri"]
+            }
+        }),
+        json!({
+            "name": "save_state",
+            "description": "Save a checkpoint of the current task so it can be resumed later.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "summary": { "type": "string" },
+                    "task": { "type": "string" },
+                    "next_steps": { "type": "string" },
+                    "files": { "type": "array", "items": { "type": "string" } },
+                    "trigger": { "type": "string", "description": "What prompted the checkpoint (default manual)." }
+                },
+                "required": ["title", "summary", "task", "next_steps"]
+            }
+        }),
+        json!({
+            "name": "load_state",
+            "description": "Load a saved checkpoint, the latest one by default.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "version": { "type": "integer", "minimum": 1 }
+                }
+            }
+        }),
+        json!({
+            "name": "list_checkpoints",
+            "description": "List saved checkpoints, newest first, with their titles, versions, triggers and timestamps.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string", "description": "Only versions of this title." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Checkpoints to return (default 20)." }
+                }
+            }
+        }),
+        json!({
+            "name": "search_checkpoints",
+            "description": "Search saved checkpoints for words in their titles, summaries, tasks, next steps and files.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words that must all appear." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Checkpoints to return (default 10)." }
+                },
+                "required": ["query"]
+            }
+        }),
+        json!({
+            "name": "diff_checkpoints",
+            "description": "Show what changed, section by section, between two versions of a checkpoint.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "from": { "type": "integer", "minimum": 1, "description": "Older version (default the one before to)." },
+                    "to": { "type": "integer", "minimum": 1, "description": "Newer version (default the latest)." }
+                },
+                "required": ["title"]
+            }
+        }),
+        json!({
+            "name": "delete_checkpoint",
+            "description": "Delete a saved checkpoint: one version, or every version of a title.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "version": { "type": "integer", "minimum": 1, "description": "Only this version (default all)." }
+                },
+                "required": ["title"]
+            }
+        }),
+        json!({
+            "name": "check_freshness",
+            "description": "How recently each source was synced and whether a sync can be requested.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sources": { "type": "array", "items": { "type": "string" }, "description": "Sources to check; all by default." }
+                }
+            }
+        }),
+        json!({
+            "name": "request_sync",
+            "description": "Start syncing a source now. Returns once the sync has started; check_freshness shows when it is done.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "source": { "type": "string" }
+                },
+                "required": ["source"]
+            }
+        }),
+        json!({
+            "name": "trigger_sync",
+            "description": "Fetch fresh data from a provider now, if the user allowed agents to sync it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "provider": { "type": "string" },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["incremental", "recent", "full"],
+                        "description": "incremental (default): what changed since the last sync; recent: the last week in full; full: the last 90 days."
+                    }
+                },
+                "required": ["provider"]
+            }
+        }),
+        json!({
+            "name": "list_sources",
+            "description": "Connected sources with how many documents each has indexed and when each last synced.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        json!({
+            "name": "list_packs",
+            "description": "List context packs: clusters of related documents that get_context can search within.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "minimum": 1, "description": "Packs to return (default 50)." }
+                }
+            }
+        }),
+        json!({
+            "name": "get_pack",
+            "description": "Show a context pack: its summary and its most recently updated documents.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "label": { "type": "string", "description": "Pack label from list_packs." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Documents to return (default 20)." }
+                },
+                "required": ["label"]
+            }
+        }),
+        json!({
+            "name": "get_timeline",
+            "description": "What happened recently: documents updated in the last days, oldest first, grouped by day.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "days": { "type": "integer", "minimum": 1, "description": "How far back to look (default 7)." },
+                    "sources": { "type": "array", "items": { "type": "string" }, "description": "Only these sources, e.g. [\"slack\", \"linear\"]." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Most documents to return (default 100); the most recent are kept." }
+                }
+            }
+        }),
+        json!({
+            "name": "get_stats",
+            "description": "Index health: documents, embeddings, clusters, relationship graph size and database size.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        json!({
+            "name": "who_do_i_work_with",
+            "description": "People the user interacted with most recently, grouped by ring.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "minimum": 1, "description": "People to return (default 10)." },
+                    "days": { "type": "integer", "minimum": 1, "description": "Only count the last this many days (default 30)." }
+                }
+            }
+        }),
+        json!({
+            "name": "who_is",
+            "description": "Look up a person across providers: their accounts, ring and recent documents shared with the user.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name_or_handle": { "type": "string", "description": "Name, handle (@alice) or email address." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Shared documents per person (default 5)." }
+                },
+                "required": ["name_or_handle"]
+            }
+        }),
+    ]
+}
+
+impl McpHandler {
+    /// Handle a JSON-RPC message. Returns None for notifications.
+    pub async fn handle_jsonrpc(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let Some(id) = request.id else {
+            // Notifications (`notifications/initialized`, cancellations) need no reply
+            return None;
+        };
+        if request.jsonrpc != "2.0" {
+            return Some(JsonRpcResponse::error(id, INVALID_REQUEST, "jsonrpc must be \"2.0\""));
+        }
+        let Some(method) = request.method else {
+            return Some(JsonRpcResponse::error(id, INVALID_REQUEST, "missing method"));
+        };
+        let params = request.params;
+        let response = match method.as_str() {
+            "initialize" => JsonRpcResponse::success(id, initialize_result(&params)),
+            "ping" => JsonRpcResponse::success(id, json!({})),
+            "tools/list" => JsonRpcResponse::success(id, json!({ "tools": self.list_tools() })),
+            "tools/call" => self.call_tool(id, params).await,
+            "resources/list" => match self.list_resources().await {
+                Ok(resources) => JsonRpcResponse::success(id, json!({ "resources": resources })),
+                Err(err) => JsonRpcResponse::error(id, INTERNAL_ERROR, err.to_string()),
+            },
+            "resources/templates/list" => JsonRpcResponse::success(id, json!({ "resourceTemplates": [] })),
+            "resources/read" => self.read_resource(id, params).await,
+            _ => JsonRpcResponse::error(id, METHOD_NOT_FOUND, format!("unknown method: {}", method)),
+        };
+        Some(response)
+    }
+
+    /// Every tool plus the aliases that aren't deprecated, which take the
+    /// same arguments as their tool.
+    fn list_tools(&self) -> Vec<Value> {
+        let mut tools = tool_definitions();
+        let mut aliases: Vec<_> = self
+            .ctx
+            .aliases
+            .aliases
+            .iter()
+            .filter(|(_, alias)| alias.deprecated.is_none())
+            .collect();
+        aliases.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, alias) in aliases {
+            let Some(target) = tools.iter().find(|tool| tool["name"] == alias.tool.as_str()) else {
+                continue;
+            };
+            let mut tool = target.clone();
+            tool["name"] = json!(name);
+            tool["description"] = json!(format!("Same as {}. {}", alias.tool, target["description"].as_str().unwrap_or_default()));
+            tools.push(tool);
+        }
+        tools
+    }
+
+    /// Run a tool. Tool failures are results with `isError` set, so the
+    /// model sees them; only malformed calls are protocol errors.
+    async fn call_tool(&self, id: Value, params: Value) -> JsonRpcResponse {
+        let Some(name) = params.get("name").and_then(|v| v.as_str()) else {
+            return JsonRpcResponse::error(id, INVALID_PARAMS, "tools/call needs a tool name");
+        };
+        let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+        let request = ToolRequest { id: None, tool: Some(name.to_string()), method: None, params: arguments };
+        let Some(response) = self.try_handle(request).await else {
+            return JsonRpcResponse::error(id, INVALID_PARAMS, format!("unknown tool: {}", name));
+        };
+
+        let mut content = Vec::new();
+        if let Some(warning) = &response.warning {
+            content.push(json!({ "type": "text", "text": warning }));
+        }
+        let mut result = match (response.ok, response.result) {
+            (true, Some(result)) => {
+                let text = serde_json::to_string_pretty(&result).unwrap_or_default();
+                content.push(json!({ "type": "text", "text": text }));
+                json!({ "structuredContent": result })
+            }
+            _ => {
+                let error = response.error.unwrap_or_else(|| "tool failed".to_string());
+                content.push(json!({ "type": "text", "text": error }));
+                json!({})
+            }
+        };
+        result["content"] = json!(content);
+        result["isError"] = json!(!response.ok);
+        JsonRpcResponse::success(id, result)
+    }
+
+    /// The most recently updated documents, with their short IDs as names.
+    async fn list_resources(&self) -> anyhow::Result<Vec<Value>> {
+        let documents = self.ctx.ingest.fetch_documents(None, None, None, RESOURCE_LIST_LIMIT).await?;
+        Ok(documents
+            .into_iter()
+            .map(|doc| {
+                let name = doc.short_id().unwrap_or_else(|| doc.uri.clone());
+                let mut resource = json!({
+                    "uri": doc.uri,
+                    "name": name,
+                    "description": format!("{} document", doc.source),
+                    "mimeType": "text/plain",
+                });
+                if let Some(title) = doc.title {
+                    resource["title"] = json!(title);
+                }
+                resource
+            })
+            .collect())
+    }
+
+    async fn read_resource(&self, id: Value, params: Value) -> JsonRpcResponse {
+        let Some(uri) = params.get("uri").and_then(|v| v.as_str()) else {
+            return JsonRpcResponse::error(id, INVALID_PARAMS, "resources/read needs a uri");
+        };
+        match self.handle_read_resource(json!({ "uri": uri })).await {
+            Ok(resource) => {
+                let mime_type = resource.mime_type.unwrap_or_else(|| "text/plain".to_string());
+                let body = if resource.encoding.as_deref() == Some("base64") { "blob" } else { "text" };
+                let mut contents = json!({ "uri": resource.uri, "mimeType": mime_type });
+                contents[body] = json!(resource.body);
+                JsonRpcResponse::success(id, json!({ "contents": [contents] }))
+            }
+            Err(err) => JsonRpcResponse::error(id, INVALID_PARAMS, err.to_string()),
+        }
+    }
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+    let version = requested
+        .filter(|requested| PROTOCOL_VERSIONS.contains(requested))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {
+            "tools": { "listChanged": false },
+            "resources": { "listChanged": false }
+        },
+        "serverInfo": { "name": "minna", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Minna is the user's local memory of their work tools. Use get_context to search it and read_resource to read a result in full."
+    })
+}
@@ -0,0 +1,159 @@
+//! Condensed summaries of a single thread, PR or issue for `summarize_thread`.
+//!
+//! The structure (participants, key facts, questions, action items) is
+//! always extracted from the text itself. The prose summary comes from the
+//! configured chat model (`MINNA_CLUSTER_SUMMARY_MODEL`) if there is one,
+//! otherwise from the opening and the latest message.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Characters of a thread shown to the model.
+const MODEL_INPUT_CHARS: usize = 12_000;
+/// Items kept in each list.
+const MAX_ITEMS: usize = 5;
+
+pub const THREAD_PROMPT: &str = "Summarize this workplace thread (a chat thread, pull request \
+or issue) in at most three sentences: what it is about, what was decided, and what is still \
+open. No other text.";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SummarizeThreadParams {
+    /// Slack thread, PR or issue URL, or an indexed document's URI or short ID.
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThreadSummary {
+    pub uri: String,
+    pub source: String,
+    pub title: Option<String>,
+    /// `llm` when the summary was written by the chat model, else `extractive`.
+    pub mode: String,
+    /// Fetched from the provider just now rather than read from the index.
+    pub live: bool,
+    pub participants: Vec<String>,
+    pub messages: usize,
+    pub summary: String,
+    /// Facts such as the state, author or assignee.
+    pub key_facts: Vec<String>,
+    pub open_questions: Vec<String>,
+    pub action_items: Vec<String>,
+}
+
+/// What can be read off a thread's text without a model.
+#[derive(Debug, Default)]
+pub struct Digest {
+    pub title: Option<String>,
+    pub participants: Vec<String>,
+    pub messages: Vec<String>,
+    pub key_facts: Vec<String>,
+    pub open_questions: Vec<String>,
+    pub action_items: Vec<String>,
+}
+
+impl Digest {
+    /// Parse the markdown the router and providers produce: `# Title`,
+    /// `- Key: value` metadata, `- [ts] user: text` chat lines, and prose.
+    pub fn parse(text: &str) -> Self {
+        static CHAT_LINE: OnceLock<Regex> = OnceLock::new();
+        let chat_line = CHAT_LINE.get_or_init(|| Regex::new(r"^- \[[^\]]*\] ([^:]+): (.*)$").unwrap());
+
+        let mut digest = Self::default();
+        let mut paragraph = String::new();
+        let flush = |paragraph: &mut String, messages: &mut Vec<String>| {
+            if !paragraph.trim().is_empty() {
+                messages.push(paragraph.trim().to_string());
+            }
+            paragraph.clear();
+        };
+        for line in text.lines().map(str::trim) {
+            if let Some(title) = line.strip_prefix("# ") {
+                if digest.title.is_none() {
+                    digest.title = Some(title.trim().to_string());
+                }
+            } else if line.starts_with('#') || line.is_empty() {
+                flush(&mut paragraph, &mut digest.messages);
+            } else if let Some(caps) = chat_line.captures(line) {
+                flush(&mut paragraph, &mut digest.messages);
+                let author = caps[1].trim().to_string();
+                if !digest.participants.contains(&author) {
+                    digest.participants.push(author);
+                }
+                digest.messages.push(caps[2].trim().to_string());
+            } else if let Some((key, value)) = metadata(line) {
+                flush(&mut paragraph, &mut digest.messages);
+                if matches!(key, "Author" | "Assignee" | "Reporter" | "Owner")
+                    && value != "Unassigned"
+                    && !digest.participants.iter().any(|p| p == value)
+                {
+                    digest.participants.push(value.to_string());
+                }
+                if key != "URL" {
+                    digest.key_facts.push(format!("{}: {}", key, value));
+                }
+            } else {
+                if !paragraph.is_empty() {
+                    paragraph.push(' ');
+                }
+                paragraph.push_str(line);
+            }
+        }
+        flush(&mut paragraph, &mut digest.messages);
+
+        for message in &digest.messages {
+            for sentence in sentences(message) {
+                if sentence.ends_with('?') && digest.open_questions.len() < MAX_ITEMS {
+                    digest.open_questions.push(sentence.to_string());
+                } else if is_action_item(sentence) && digest.action_items.len() < MAX_ITEMS {
+                    digest.action_items.push(sentence.to_string());
+                }
+            }
+        }
+        digest.key_facts.truncate(MAX_ITEMS);
+        digest
+    }
+
+    /// The opening message and, for longer threads, the latest one.
+    pub fn extractive_summary(&self) -> String {
+        let Some(first) = self.messages.first() else {
+            return self.title.clone().unwrap_or_default();
+        };
+        let mut summary = crate::truncate(first, 300);
+        if self.messages.len() > 1 {
+            if let Some(last) = self.messages.last() {
+                summary.push_str(&format!(" Latest: {}", crate::truncate(last, 200)));
+            }
+        }
+        summary
+    }
+}
+
+/// The start of a thread's text, as much as the model is shown.
+pub fn model_input(title: Option<&str>, text: &str) -> String {
+    let text: String = text.chars().take(MODEL_INPUT_CHARS).collect();
+    match title {
+        Some(title) => format!("Title: {}\n\n{}", title, text),
+        None => text,
+    }
+}
+
+/// A `- Key: value` metadata line with a short, capitalized key.
+fn metadata(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix("- ")?.split_once(": ")?;
+    let short = key.len() <= 20 && !key.contains(' ');
+    (short && key.starts_with(|c: char| c.is_ascii_uppercase())).then(|| (key, value.trim()))
+}
+
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split_inclusive(['.', '!', '?', '\n']).map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn is_action_item(sentence: &str) -> bool {
+    let lower = sentence.to_lowercase();
+    ["todo", "action item", "follow up", "follow-up", "i'll ", "we'll ", "let's ", "[ ]"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
@@ -0,0 +1,227 @@
+//! A complete connector for a fictional recipe API.
+//!
+//! The API pages through recipes changed since a timestamp:
+//!
+//! ```text
+//! GET {COOKBOOK_API_URL}/recipes?updated_since=2026-01-01T00:00:00Z&page=1
+//! Authorization: Bearer {token}
+//!
+//! { "recipes": [{ "id": "42", "title": "...", "summary": "...",
+//!                 "ingredients": ["..."], "steps": ["..."],
+//!                 "author": { "id": "u1", "name": "Ada" },
+//!                 "cuisine": "Italian", "updated_at": "..." }],
+//!   "next_page": 2 }
+//! ```
+//!
+//! Run it against a scratch database with:
+//!
+//! ```text
+//! COOKBOOK_API_URL=http://localhost:8080 COOKBOOK_TOKEN=... \
+//!     cargo run -p minna-provider-sdk --example cookbook
+//! ```
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use minna_provider_sdk::progress::emit_progress;
+use minna_provider_sdk::{
+    call_with_backoff, DocumentBuilder, EdgeSet, Embedder, ExtractedEdge, GraphStore,
+    HashEmbedder, IngestionEngine, NodeRef, NodeType, ProviderHost, Relation, SyncContext,
+    SyncProvider, SyncSummary, VectorStore,
+};
+
+const PROVIDER: &str = "cookbook";
+
+/// Stop after this many pages so a misbehaving API can't loop forever.
+const MAX_PAGES: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+struct RecipePage {
+    recipes: Vec<Recipe>,
+    next_page: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Recipe {
+    id: String,
+    title: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    ingredients: Vec<String>,
+    #[serde(default)]
+    steps: Vec<String>,
+    author: Option<Author>,
+    cuisine: Option<String>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    id: String,
+    name: String,
+}
+
+/// Connector for the recipe API.
+pub struct CookbookProvider {
+    base_url: String,
+}
+
+impl CookbookProvider {
+    fn uri(&self, recipe: &Recipe) -> String {
+        format!("{}/recipes/{}", self.base_url, recipe.id)
+    }
+
+    fn document(&self, recipe: &Recipe) -> Result<minna_provider_sdk::Document> {
+        let ingredients = recipe
+            .ingredients
+            .iter()
+            .map(|item| format!("- {}", item))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let steps = recipe
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| format!("{}. {}", i + 1, step))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        DocumentBuilder::new(PROVIDER, self.uri(recipe))
+            .title(&recipe.title)
+            .field("Author", recipe.author.as_ref().map(|a| a.name.clone()).unwrap_or_default())
+            .field("Cuisine", recipe.cuisine.clone().unwrap_or_default())
+            .body(&recipe.summary)
+            .section("Ingredients", ingredients)
+            .section("Steps", steps)
+            .updated_at(recipe.updated_at)
+            .build()
+    }
+
+    fn edges(&self, recipe: &Recipe) -> Vec<ExtractedEdge> {
+        let node = NodeRef::document(PROVIDER, recipe.id.as_str());
+        let author = recipe
+            .author
+            .as_ref()
+            .map(|a| NodeRef::with_name(NodeType::User, PROVIDER, a.id.as_str(), a.name.as_str()));
+        let mut edges = EdgeSet::new(recipe.updated_at);
+        edges.add_opt(author, node, Relation::AuthorOf);
+        edges.into_edges()
+    }
+}
+
+#[async_trait]
+impl SyncProvider for CookbookProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Cookbook"
+    }
+
+    async fn sync(
+        &self,
+        ctx: &SyncContext<'_>,
+        since_days: Option<i64>,
+        mode: Option<&str>,
+    ) -> Result<SyncSummary> {
+        let token = ctx.host.load_token(PROVIDER)?;
+        let since = ctx.since(PROVIDER, since_days, mode).await?;
+        let started_at = Utc::now();
+
+        let mut scanned = 0;
+        let mut indexed = 0;
+        let mut page = Some(1);
+        let mut pages = 0;
+        while let Some(current) = page {
+            pages += 1;
+            if pages > MAX_PAGES {
+                return Err(anyhow!("{}: more than {} pages, giving up", PROVIDER, MAX_PAGES));
+            }
+
+            let response = call_with_backoff(PROVIDER, || {
+                ctx.http_client
+                    .get(format!("{}/recipes", self.base_url))
+                    .bearer_auth(&token)
+                    .query(&[("updated_since", since.to_rfc3339()), ("page", current.to_string())])
+            })
+            .await?;
+            let body: RecipePage = response.json().await?;
+
+            for recipe in &body.recipes {
+                scanned += 1;
+                let doc = match self.document(recipe) {
+                    Ok(doc) => doc,
+                    // Skip empty recipes rather than failing the whole sync
+                    Err(err) => {
+                        tracing::warn!("{}", err);
+                        continue;
+                    }
+                };
+                let doc_id = ctx.index_document(doc).await?;
+                if let Some(cuisine) = &recipe.cuisine {
+                    ctx.add_tags(doc_id, &[cuisine]).await?;
+                }
+                ctx.index_document_edges(doc_id, &self.edges(recipe)).await?;
+                indexed += 1;
+            }
+
+            emit_progress(PROVIDER, "syncing", &format!("Indexed {} recipes", indexed), Some(indexed));
+            page = body.next_page;
+        }
+
+        // Only move the cursor once everything is indexed
+        ctx.commit_cursor(PROVIDER, started_at).await?;
+
+        Ok(SyncSummary {
+            provider: PROVIDER.to_string(),
+            items_scanned: scanned,
+            documents_processed: indexed,
+            updated_at: started_at.to_rfc3339(),
+        })
+    }
+}
+
+/// Host that reads the token from the environment. Minna's daemon supplies
+/// keychain-backed credentials instead.
+struct EnvHost;
+
+impl ProviderHost for EnvHost {
+    fn load_token(&self, name: &str) -> Result<String> {
+        let var = format!("{}_TOKEN", name.to_ascii_uppercase());
+        std::env::var(&var).map_err(|_| anyhow!("set {} to run the {} example", var, name))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let db_path = std::env::temp_dir().join("minna-cookbook-example.db");
+    let ingest = IngestionEngine::new(&db_path).await?;
+    let vector = VectorStore::new(&db_path).await?;
+    let graph = GraphStore::new(ingest.pool().clone());
+    let embedder: Arc<dyn Embedder> = Arc::new(HashEmbedder::default());
+    let http_client = reqwest::Client::new();
+
+    let ctx = SyncContext {
+        ingest: &ingest,
+        vector: &vector,
+        embedder: &embedder,
+        http_client: &http_client,
+        host: &EnvHost,
+        graph: &graph,
+        auth_path: &db_path,
+    };
+
+    let provider = CookbookProvider {
+        base_url: std::env::var("COOKBOOK_API_URL")
+            .unwrap_or_else(|_| "https://cookbook.example.com/api".to_string()),
+    };
+    let summary = provider.sync(&ctx, None, None).await?;
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use minna_graph::{ExtractedEdge, GraphBackend, NodeRef, Relation};
+use minna_ingest::{Attachment, Document, DocumentStore};
+use minna_vector::{embed_document, Embedder, VectorIndex};
+
+use crate::cursor::calculate_since;
+
+/// Outcome of a provider sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub provider: String,
+    pub items_scanned: usize,
+    pub documents_processed: usize,
+    pub updated_at: String,
+}
+
+/// Credentials and settings the host application exposes to providers.
+pub trait ProviderHost: Send + Sync {
+    /// Load the stored token for a provider.
+    fn load_token(&self, name: &str) -> Result<String>;
+
+    /// Load Basic Auth credentials stored as `email:token`.
+    fn parse_basic_auth(&self, name: &str) -> Result<(String, String)> {
+        let creds = self.load_token(name)?;
+        let (user, token) = creds
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid Basic Auth format for {}. Expected 'email:token'", name))?;
+        Ok((user.to_string(), token.to_string()))
+    }
+
+    /// Whether a provider should keep syncing archived resources.
+    fn include_archived(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// Context passed to providers during sync operations.
+///
+/// Contains all the shared resources providers need to index documents.
+pub struct SyncContext<'a> {
+    /// Document storage (`IngestionEngine` for SQLite).
+    pub ingest: &'a dyn DocumentStore,
+    /// Vector embeddings storage (`VectorStore` for SQLite).
+    pub vector: &'a dyn VectorIndex,
+    /// Embedding model.
+    pub embedder: &'a Arc<dyn Embedder>,
+    /// HTTP client for API requests.
+    pub http_client: &'a reqwest::Client,
+    /// Credentials and provider settings.
+    pub host: &'a dyn ProviderHost,
+    /// Graph store for relationship tracking (Gravity Well).
+    pub graph: &'a dyn GraphBackend,
+    /// Path to auth token storage (for OAuth providers using TokenStore).
+    pub auth_path: &'a Path,
+}
+
+impl<'a> SyncContext<'a> {
+    /// Index a document (store + embed + vectorize). Long documents are also
+    /// embedded chunk by chunk.
+    pub async fn index_document(&self, doc: Document) -> Result<i64> {
+        let doc = self.ingest.prepare(&doc);
+        let id = self.ingest.upsert_document(&doc).await?;
+        embed_document(self.embedder.as_ref(), self.vector, id, &doc.source, &doc.body).await?;
+        index_reference_edges(self.ingest, self.graph, id).await?;
+        Ok(id)
+    }
+
+    /// Index many documents like [`Self::index_document`], storing them in a
+    /// single transaction. Prefer this for full syncs: per-document commits
+    /// dominate their run time. Returns the IDs in order.
+    pub async fn index_documents(&self, docs: &[Document]) -> Result<Vec<i64>> {
+        let docs: Vec<Document> = docs.iter().map(|doc| self.ingest.prepare(doc).into_owned()).collect();
+        let ids = self.ingest.upsert_documents_batch(&docs).await?;
+        for (doc, id) in docs.iter().zip(&ids) {
+            embed_document(self.embedder.as_ref(), self.vector, *id, &doc.source, &doc.body).await?;
+            index_reference_edges(self.ingest, self.graph, *id).await?;
+        }
+        Ok(ids)
+    }
+
+    /// Get sync cursor for incremental syncing.
+    pub async fn get_sync_cursor(&self, provider: &str) -> Result<Option<String>> {
+        self.ingest.get_sync_cursor(provider).await
+    }
+
+    /// Set sync cursor after successful sync.
+    pub async fn set_sync_cursor(&self, provider: &str, cursor: &str) -> Result<()> {
+        self.ingest.set_sync_cursor(provider, cursor).await
+    }
+
+    /// Start of the sync window, from the stored cursor and the requested
+    /// mode (see [`calculate_since`]).
+    pub async fn since(
+        &self,
+        provider: &str,
+        since_days: Option<i64>,
+        mode: Option<&str>,
+    ) -> Result<DateTime<Utc>> {
+        let cursor = self.get_sync_cursor(provider).await?;
+        Ok(calculate_since(since_days, mode, cursor.as_deref()))
+    }
+
+    /// Store a timestamp cursor so the next sync starts from `synced_at`.
+    pub async fn commit_cursor(&self, provider: &str, synced_at: DateTime<Utc>) -> Result<()> {
+        self.set_sync_cursor(provider, &synced_at.to_rfc3339()).await
+    }
+
+    /// Record whether an upstream resource (channel, repo, space) is archived.
+    ///
+    /// Returns true if the resource should be skipped: it's archived and the
+    /// provider isn't configured with `include_archived`.
+    pub async fn track_archived(
+        &self,
+        provider: &str,
+        resource_id: &str,
+        name: Option<&str>,
+        uri_prefix: &str,
+        archived: bool,
+    ) -> Result<bool> {
+        if !archived {
+            self.ingest.unmark_archived(provider, resource_id).await?;
+            return Ok(false);
+        }
+        self.ingest.mark_archived(provider, resource_id, name, uri_prefix).await?;
+        Ok(!self.host.include_archived(provider))
+    }
+
+    /// Tag a document indexed with [`Self::index_document`] with the
+    /// resource it belongs to (repo, channel, project), so searches can be
+    /// narrowed with `tag:`. Blank tags are skipped.
+    pub async fn add_tags(&self, doc_id: i64, tags: &[&str]) -> Result<()> {
+        for tag in tags {
+            self.ingest.add_tag(doc_id, tag).await?;
+        }
+        Ok(())
+    }
+
+    /// Attach a small file (up to 10 MiB) to a document
+    /// indexed with [`Self::index_document`]. Agents read it back through
+    /// `read_resource` with the returned attachment's `attachment://` URI.
+    pub async fn attach(&self, doc_id: i64, name: &str, mime_type: &str, data: &[u8]) -> Result<Attachment> {
+        self.ingest.store_attachment(doc_id, name, mime_type, data).await
+    }
+
+    /// Store edges extracted from a document indexed with
+    /// [`Self::index_document`], recording the document as their source so
+    /// relationships can cite the conversation or issue they came from.
+    pub async fn index_document_edges(&self, doc_id: i64, edges: &[ExtractedEdge]) -> Result<usize> {
+        Ok(self.graph.upsert_edges_from_document(edges, doc_id).await?.len())
+    }
+
+    /// Store extracted edges in the graph (Gravity Well).
+    ///
+    /// Upserts nodes and edges in one batch. The graph backend handles node
+    /// creation internally.
+    pub async fn index_edges(&self, edges: &[ExtractedEdge]) -> Result<usize> {
+        Ok(self.graph.upsert_edges(edges).await?.len())
+    }
+}
+
+/// Record `References` edges for the links between a stored document and
+/// other stored documents, in both directions: a document indexed after one
+/// linking to it gets the edge too. Documents are `Document` nodes keyed by
+/// URI; each edge cites the linking document. Returns the number of edges.
+pub async fn index_reference_edges(
+    ingest: &dyn DocumentStore,
+    graph: &dyn GraphBackend,
+    doc_id: i64,
+) -> Result<usize> {
+    let references = ingest.document_references(doc_id).await?;
+    let now = Utc::now();
+    for reference in &references {
+        let edge = ExtractedEdge::new(
+            NodeRef::document(&reference.from_source, &reference.from_uri),
+            NodeRef::document(&reference.to_source, &reference.to_uri),
+            Relation::References,
+            now,
+        );
+        graph.upsert_edge_from_document(&edge, reference.from_id).await?;
+    }
+    Ok(references.len())
+}
+
+/// Trait that all sync providers must implement.
+#[async_trait]
+pub trait SyncProvider: Send + Sync {
+    /// Provider identifier (e.g., "notion", "slack").
+    fn name(&self) -> &'static str;
+
+    /// Human-readable display name (e.g., "Notion", "Slack").
+    fn display_name(&self) -> &'static str;
+
+    /// Sync documents from this provider.
+    ///
+    /// # Arguments
+    /// * `ctx` - Shared context with storage and HTTP client
+    /// * `since_days` - Optional number of days to look back
+    /// * `mode` - Optional sync mode ("full", "sprint", etc.)
+    async fn sync(
+        &self,
+        ctx: &SyncContext<'_>,
+        since_days: Option<i64>,
+        mode: Option<&str>,
+    ) -> Result<SyncSummary>;
+
+    /// Optional: Quick discovery scan for UI metadata.
+    ///
+    /// Returns counts, available resources, etc. without full sync.
+    async fn discover(&self, _ctx: &SyncContext<'_>) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "provider": self.name(),
+            "error": "discovery not implemented"
+        }))
+    }
+
+    /// Extract relationship edges from synced data.
+    ///
+    /// Called after sync to populate the Gravity Well graph.
+    /// Default implementation returns empty - providers opt-in by overriding.
+    ///
+    /// # Arguments
+    /// * `ctx` - Shared context with graph store
+    /// * `doc` - The document that was just synced
+    /// * `raw_data` - Optional raw API response for richer extraction
+    async fn extract_edges(
+        &self,
+        _ctx: &SyncContext<'_>,
+        _doc: &Document,
+        _raw_data: Option<&serde_json::Value>,
+    ) -> Result<Vec<ExtractedEdge>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minna_graph::GraphStore;
+    use minna_ingest::IngestionEngine;
+
+    fn doc(uri: &str, source: &str, body: &str) -> Document {
+        Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reference_edges_point_from_the_linking_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let ingest = IngestionEngine::new(&dir.path().join("minna.db")).await.unwrap();
+        GraphStore::init_schema(ingest.pool()).await.unwrap();
+        let graph = ingest.graph_store();
+
+        let thread = ingest
+            .upsert_document(&doc("slack://C1/1", "slack", "fix is in github://acme/api/7"))
+            .await
+            .unwrap();
+        assert_eq!(index_reference_edges(&ingest, &graph, thread).await.unwrap(), 0);
+        // Indexing the target later still records the edge from the thread
+        let pr = ingest.upsert_document(&doc("github://acme/api/7", "github", "PR")).await.unwrap();
+        assert_eq!(index_reference_edges(&ingest, &graph, pr).await.unwrap(), 1);
+
+        let from = NodeRef::document("slack", "slack://C1/1").canonical_id();
+        let edges = graph.edges_from(&from).await.unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].relation, Relation::References);
+        assert_eq!(edges[0].to_node, NodeRef::document("github", "github://acme/api/7").canonical_id());
+    }
+}
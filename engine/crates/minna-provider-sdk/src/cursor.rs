@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+
+/// Calculate the "since" timestamp for sync operations.
+///
+/// A "full" sync looks back `since_days` (default 90); an explicit
+/// `since_days` wins over the cursor; otherwise the cursor is used, falling
+/// back to 30 days.
+pub fn calculate_since(
+    since_days: Option<i64>,
+    mode: Option<&str>,
+    cursor: Option<&str>,
+) -> DateTime<Utc> {
+    let is_full = mode == Some("full");
+
+    if is_full {
+        // Full sync: default 90 days
+        let days = since_days.unwrap_or(90);
+        Utc::now() - chrono::Duration::days(days)
+    } else if let Some(days) = since_days {
+        // Explicit days override
+        Utc::now() - chrono::Duration::days(days)
+    } else if let Some(cursor_str) = cursor {
+        // Use cursor if available
+        DateTime::parse_from_rfc3339(cursor_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now() - chrono::Duration::days(30))
+    } else {
+        // Default: 30 days
+        Utc::now() - chrono::Duration::days(30)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_used_for_incremental_sync() {
+        let cursor = "2026-01-02T03:04:05+00:00";
+        let since = calculate_since(None, None, Some(cursor));
+        assert_eq!(since.to_rfc3339(), cursor);
+    }
+
+    #[test]
+    fn test_full_sync_ignores_cursor() {
+        let since = calculate_since(None, Some("full"), Some("2026-01-02T03:04:05+00:00"));
+        let days = (Utc::now() - since).num_days();
+        assert!((89..=90).contains(&days));
+    }
+}
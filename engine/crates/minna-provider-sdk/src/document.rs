@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+
+use minna_ingest::Document;
+
+/// Builder for documents a provider indexes.
+///
+/// Sections added with [`Self::field`] and [`Self::section`] are rendered as
+/// markdown ahead of the body, which is how built-in providers lay out
+/// metadata such as status or assignee.
+#[derive(Debug, Clone)]
+pub struct DocumentBuilder {
+    source: String,
+    uri: String,
+    title: Option<String>,
+    fields: Vec<(String, String)>,
+    sections: Vec<(String, String)>,
+    body: String,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl DocumentBuilder {
+    /// Start a document for `source` identified by `uri`.
+    pub fn new(source: impl Into<String>, uri: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            uri: uri.into(),
+            title: None,
+            fields: Vec::new(),
+            sections: Vec::new(),
+            body: String::new(),
+            updated_at: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Add a `**Name:** value` metadata line. Empty values are skipped.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.trim().is_empty() {
+            self.fields.push((name.into(), value));
+        }
+        self
+    }
+
+    /// Add a `## Heading` section after the body. Empty sections are skipped.
+    pub fn section(mut self, heading: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        if !text.trim().is_empty() {
+            self.sections.push((heading.into(), text));
+        }
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// When the item last changed upstream (defaults to now).
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// Render the document. Fails if there's no URI or no content at all.
+    pub fn build(self) -> Result<Document> {
+        if self.uri.is_empty() {
+            return Err(anyhow!("{}: document has no URI", self.source));
+        }
+
+        let mut body = String::new();
+        if let Some(title) = &self.title {
+            body.push_str(&format!("# {}\n\n", title));
+        }
+        for (name, value) in &self.fields {
+            body.push_str(&format!("**{}:** {}\n", name, value));
+        }
+        if !self.fields.is_empty() {
+            body.push('\n');
+        }
+        body.push_str(self.body.trim());
+        for (heading, text) in &self.sections {
+            body.push_str(&format!("\n\n## {}\n\n{}", heading, text.trim()));
+        }
+
+        if self.body.trim().is_empty() && self.fields.is_empty() && self.sections.is_empty() {
+            return Err(anyhow!("{}: document {} has no content", self.source, self.uri));
+        }
+
+        Ok(Document {
+            id: None,
+            uri: self.uri,
+            source: self.source,
+            title: self.title,
+            body: body.trim_end().to_string(),
+            updated_at: self.updated_at.unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_renders_markdown() {
+        let doc = DocumentBuilder::new("cookbook", "cookbook://recipe/1")
+            .title("Pancakes")
+            .field("Cuisine", "American")
+            .field("Difficulty", "")
+            .body("Fluffy pancakes.")
+            .section("Ingredients", "- flour\n- eggs")
+            .build()
+            .unwrap();
+
+        assert_eq!(doc.source, "cookbook");
+        assert_eq!(doc.title.as_deref(), Some("Pancakes"));
+        assert_eq!(
+            doc.body,
+            "# Pancakes\n\n**Cuisine:** American\n\nFluffy pancakes.\n\n## Ingredients\n\n- flour\n- eggs"
+        );
+    }
+
+    #[test]
+    fn test_empty_document_rejected() {
+        assert!(DocumentBuilder::new("cookbook", "cookbook://recipe/2")
+            .title("Nothing here")
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+
+use minna_graph::{ExtractedEdge, NodeRef, Relation};
+
+/// Collects the relationship edges for one synced item.
+///
+/// All edges share the item's observation time, and duplicates (same
+/// endpoints and relation) are dropped, so providers can add edges for every
+/// mention without tracking what they've already emitted.
+#[derive(Debug, Clone)]
+pub struct EdgeSet {
+    observed_at: DateTime<Utc>,
+    edges: Vec<ExtractedEdge>,
+}
+
+impl EdgeSet {
+    pub fn new(observed_at: DateTime<Utc>) -> Self {
+        Self {
+            observed_at,
+            edges: Vec::new(),
+        }
+    }
+
+    /// Add an edge unless an identical one is already present.
+    pub fn add(&mut self, from: NodeRef, to: NodeRef, relation: Relation) -> &mut Self {
+        let exists = self.edges.iter().any(|edge| {
+            edge.relation == relation && edge.from == from && edge.to == to
+        });
+        if !exists {
+            self.edges.push(ExtractedEdge::new(from, to, relation, self.observed_at));
+        }
+        self
+    }
+
+    /// Add an edge when the source node is known, e.g. an optional author.
+    pub fn add_opt(&mut self, from: Option<NodeRef>, to: NodeRef, relation: Relation) -> &mut Self {
+        if let Some(from) = from {
+            self.add(from, to, relation);
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn into_edges(self) -> Vec<ExtractedEdge> {
+        self.edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_dropped() {
+        let mut edges = EdgeSet::new(Utc::now());
+        let user = NodeRef::user("cookbook", "chef-1");
+        let recipe = NodeRef::document("cookbook", "1");
+        edges
+            .add(user.clone(), recipe.clone(), Relation::AuthorOf)
+            .add(user.clone(), recipe.clone(), Relation::AuthorOf)
+            .add(user, recipe.clone(), Relation::MentionedIn)
+            .add_opt(None, recipe, Relation::AuthorOf);
+        assert_eq!(edges.len(), 2);
+    }
+}
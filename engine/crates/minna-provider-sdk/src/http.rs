@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::time::sleep;
+
+/// HTTP request helper with exponential backoff for rate limiting.
+///
+/// Retries 429s (honoring `Retry-After`) up to 8 times and 5xx responses up
+/// to 3 times. `builder_fn` is called for every attempt since a
+/// `RequestBuilder` can only be sent once.
+pub async fn call_with_backoff<F>(
+    provider: &str,
+    mut builder_fn: F,
+) -> Result<reqwest::Response>
+where
+    F: FnMut() -> reqwest::RequestBuilder,
+{
+    let mut retries = 0;
+    let mut delay = Duration::from_secs(1);
+    let max_retries = 8;
+
+    loop {
+        let response = builder_fn().send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        if status.as_u16() == 429 {
+            // Rate limited
+            if retries >= max_retries {
+                return Err(anyhow!("{}: Rate limited after {} retries", provider, retries));
+            }
+
+            // Check for Retry-After header
+            let wait = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(delay);
+
+            tracing::warn!("{}: Rate limited, waiting {:?}", provider, wait);
+            sleep(wait).await;
+
+            retries += 1;
+            delay = std::cmp::min(delay * 2, Duration::from_secs(60));
+            continue;
+        }
+
+        if status.is_server_error() && retries < 3 {
+            tracing::warn!("{}: Server error {}, retrying...", provider, status);
+            sleep(delay).await;
+            retries += 1;
+            delay *= 2;
+            continue;
+        }
+
+        if status.as_u16() == 403 {
+            return Err(anyhow!("{}: Access forbidden (403). Check permissions.", provider));
+        }
+
+        return Err(anyhow!("{}: HTTP {} - {}", provider, status, response.text().await.unwrap_or_default()));
+    }
+}
@@ -0,0 +1,47 @@
+//! Building blocks for Minna source connectors.
+//!
+//! A connector implements [`SyncProvider`]. During a sync it receives a
+//! [`SyncContext`] with everything it needs to write into local memory:
+//!
+//! - [`SyncContext::index_document`] stores, embeds and vectorizes a document
+//!   (build one with [`DocumentBuilder`]).
+//! - [`SyncContext::since`] / [`SyncContext::commit_cursor`] handle incremental
+//!   sync cursors.
+//! - [`SyncContext::add_tags`] tags documents with their repo, channel or
+//!   project.
+//! - [`SyncContext::index_document_edges`] records relationships (built with
+//!   [`EdgeSet`]) in the Gravity Well graph, citing the document they came from.
+//!   Links between indexed documents become `References` edges on their own.
+//! - [`call_with_backoff`] retries rate-limited and flaky HTTP calls.
+//! - [`progress::emit_progress`] reports progress to the app and MCP clients.
+//!
+//! Credentials and per-provider settings come from the host through
+//! [`ProviderHost`], so connectors never touch the keychain directly.
+//!
+//! # Writing a connector
+//!
+//! 1. Implement [`SyncProvider`] for a unit struct.
+//! 2. In `sync`, load the token with `ctx.host.load_token(name)`, compute the
+//!    window with [`SyncContext::since`], page through the API with
+//!    [`call_with_backoff`], and index each item.
+//! 3. Commit the cursor once the sync succeeds and return a [`SyncSummary`].
+//!
+//! `examples/cookbook.rs` is a complete connector built this way.
+
+pub mod progress;
+
+mod context;
+mod cursor;
+mod document;
+mod edges;
+mod http;
+
+pub use context::{index_reference_edges, ProviderHost, SyncContext, SyncProvider, SyncSummary};
+pub use cursor::calculate_since;
+pub use document::DocumentBuilder;
+pub use edges::EdgeSet;
+pub use http::call_with_backoff;
+
+pub use minna_graph::{EdgeSource, ExtractedEdge, GraphBackend, GraphStore, NodeRef, NodeType, Relation};
+pub use minna_ingest::{Attachment, Document, DocumentReference, DocumentStore, IngestionEngine};
+pub use minna_vector::{Embedder, HashEmbedder, VectorIndex, VectorStore};
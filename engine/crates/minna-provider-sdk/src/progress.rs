@@ -1,7 +1,9 @@
 use std::io::Write;
+use minna_graph::{Ring, RingChangeReason};
 use serde::{Deserialize, Serialize};
 use once_cell::sync::Lazy;
 use tokio::sync::broadcast;
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressEvent {
@@ -18,11 +20,25 @@ pub struct ResultEvent {
     pub data: serde_json::Value,
 }
 
+/// A node moving between Gravity Well rings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RingChangeEvent {
+    pub node_id: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub from: Ring,
+    pub to: Ring,
+    pub reason: RingChangeReason,
+    /// Readable summary, e.g. "Alice moved into your Core ring"
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum InternalEvent {
     Progress(ProgressEvent),
     Result(ResultEvent),
+    RingChange(RingChangeEvent),
 }
 
 static PROGRESS_TX: Lazy<broadcast::Sender<InternalEvent>> = Lazy::new(|| {
@@ -85,6 +101,25 @@ pub fn emit_result(result_type: &str, status: &str, data: serde_json::Value) {
     let _ = PROGRESS_TX.send(InternalEvent::Result(payload));
 }
 
+/// Emit a ring change to stdout for Swift to parse.
+///
+/// # Protocol
+/// Output format: `MINNA_RING_CHANGE:{"node_id":"user:slack:U123","from":"Two","to":"One",...}\n`
+pub fn emit_ring_change(event: RingChangeEvent) {
+    // 1. Emit to stdout for Swift app; the admin socket still gets the
+    // event if it can't be written as JSON
+    match serde_json::to_string(&event) {
+        Ok(line) => {
+            println!("MINNA_RING_CHANGE:{}", line);
+            let _ = std::io::stdout().flush();
+        }
+        Err(err) => warn!("[RINGS] Can't serialize ring change for {}: {}", event.node_id, err),
+    }
+
+    // 2. Broadcast to internal channel for Admin Socket
+    let _ = PROGRESS_TX.send(InternalEvent::RingChange(event));
+}
+
 /// Emit an error progress update.
 ///
 /// Convenience wrapper for emit_progress with status="error".
@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -6,14 +7,21 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
-use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration};
-use tracing::{error, info};
-
-use minna_core::{Core, MinnaPaths, TokenStore, ProviderRegistry, SyncScheduler, SyncPlanner};
+use tokio::sync::{Notify, RwLock};
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{error, info, warn};
+
+use minna_core::{Core, MaintenanceOptions, MinnaPaths, TokenStore, ProviderRegistry, SyncScheduler, SyncPlanner};
+use minna_core::bundle::ExportOptions;
+use minna_core::workspace::ContextFileConfig;
+use minna_core::trends::TrendOptions;
 use minna_auth_bridge::Provider;
 use minna_graph::Ring;
-use minna_mcp::{McpContext, McpHandler, ToolRequest, ToolResponse};
+use minna_mcp::{
+    JsonRpcRequest, JsonRpcResponse, McpContext, McpHandler, SyncCompletion, SyncController, SyncMode, ToolAliases,
+    ToolRequest, ToolResponse, FusionConfig, DEFAULT_RERANK_CANDIDATES,
+};
+use minna_mcp::pages::ContextPages;
 
 /// Shared state that tracks Core initialization
 struct ServerState {
@@ -21,6 +29,13 @@ struct ServerState {
     paths: MinnaPaths,
     registry: ProviderRegistry,
     scheduler: RwLock<SyncScheduler>,
+    /// Documents synced since rings were last recalculated.
+    synced_since_rings: AtomicUsize,
+    /// Documents after which a sync triggers a ring recalculation
+    /// (`MINNA_RING_RECALC_AFTER_DOCS`, 0 disables).
+    ring_recalc_after_docs: usize,
+    /// Woken when enough documents have synced to make rings stale.
+    rings_stale: Notify,
 }
 
 impl ServerState {
@@ -42,6 +57,21 @@ impl ServerState {
             paths,
             registry,
             scheduler: RwLock::new(scheduler),
+            synced_since_rings: AtomicUsize::new(0),
+            ring_recalc_after_docs: std::env::var("MINNA_RING_RECALC_AFTER_DOCS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500),
+            rings_stale: Notify::new(),
+        }
+    }
+
+    /// Count a finished sync's documents towards the next ring
+    /// recalculation, and wake the ring task once there are enough.
+    fn record_sync(&self, documents: usize) {
+        let total = self.synced_since_rings.fetch_add(documents, Ordering::Relaxed) + documents;
+        if self.ring_recalc_after_docs > 0 && total >= self.ring_recalc_after_docs {
+            self.rings_stale.notify_one();
         }
     }
 
@@ -125,6 +155,11 @@ impl AdminHandler {
                     let mut scheduler = self.state.get_scheduler().await;
                     scheduler.stats()
                 };
+                let embeddings = self
+                    .state
+                    .get_core()
+                    .await
+                    .and_then(|core| core.last_dimension_check());
                 let response = AdminResponse {
                     id,
                     ok: true,
@@ -137,7 +172,8 @@ impl AdminHandler {
                             "in_progress": scheduler_stats.in_progress,
                             "budget_used": scheduler_stats.budget_used,
                             "budget_total": scheduler_stats.budget_total,
-                        }
+                        },
+                        "embeddings": embeddings,
                     })),
                     error: None,
                     event: None,
@@ -156,6 +192,33 @@ impl AdminHandler {
             Some("reset") => {
                 self.handle_reset(id, id_log, request, tx).await;
             }
+            Some("export") => {
+                self.handle_export(id, id_log, request, tx).await;
+            }
+            Some("import") => {
+                self.handle_import(id, id_log, request, tx).await;
+            }
+            Some("maintenance") => {
+                self.handle_maintenance(id, id_log, request, tx).await;
+            }
+            Some("reindex_embeddings") => {
+                self.handle_reindex_embeddings(id, id_log, request, tx).await;
+            }
+            Some("trending_topics") => {
+                self.handle_trending_topics(id, id_log, request, tx).await;
+            }
+            Some("graph_stats") => {
+                self.handle_graph_stats(id, id_log, request, tx).await;
+            }
+            Some("collaborators") => {
+                self.handle_collaborators(id, id_log, request, tx).await;
+            }
+            Some("ring_changes") => {
+                self.handle_ring_changes(id, id_log, request, tx).await;
+            }
+            Some("recent_rings") => {
+                self.handle_recent_rings(id, id_log, request, tx).await;
+            }
             _ => {
                 let response = AdminResponse {
                     id,
@@ -263,7 +326,8 @@ impl AdminHandler {
             while let Ok(event) = progress_rx.recv().await {
                 let matches = match &event {
                     minna_core::progress::InternalEvent::Progress(p) => p.provider == provider_name,
-                    minna_core::progress::InternalEvent::Result(r) => r.result_type == "sync"
+                    minna_core::progress::InternalEvent::Result(r) => r.result_type == "sync",
+                    minna_core::progress::InternalEvent::RingChange(_) => false,
                 };
 
                 if matches {
@@ -306,6 +370,10 @@ impl AdminHandler {
                 let registry = self.state.get_registry();
                 core.sync_via_registry(registry, target, since_days, mode).await
             },
+            // Any other provider enabled in providers.toml (e.g. mock)
+            other if self.state.get_registry().is_available(other) => {
+                core.sync_via_registry(self.state.get_registry(), other, since_days, mode).await
+            },
             _ => {
                 let response = AdminResponse {
                     id,
@@ -327,6 +395,7 @@ impl AdminHandler {
                     let mut scheduler = self.state.get_scheduler().await;
                     scheduler.complete_sync(provider, Ring::One, api_calls);
                 }
+                self.state.record_sync(summary.documents_processed);
                 let response = AdminResponse {
                     id,
                     ok: true,
@@ -369,6 +438,9 @@ impl AdminHandler {
             "slack" => core.discover_slack().await,
             "google" | "google_drive" => core.discover_google_drive().await,
             "github" => core.discover_github().await,
+            other if self.state.get_registry().is_available(other) => {
+                core.discover_via_registry(self.state.get_registry(), other).await
+            }
             _ => {
                 let response = AdminResponse { id, ok: true, result: Some(serde_json::json!({ "provider": provider, "error": "discovery not implemented" })), error: None, event: None };
                 let _ = tx.send((id_log, response));
@@ -400,6 +472,207 @@ impl AdminHandler {
         };
         let _ = tx.send((id_log, response));
     }
+
+    async fn handle_export(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let Some(path) = request.params.get("path").and_then(|v| v.as_str()) else {
+            let response = AdminResponse { id, ok: false, result: None, error: Some("missing path".to_string()), event: None };
+            let _ = tx.send((id_log, response));
+            return;
+        };
+        let options: ExportOptions = serde_json::from_value(request.params.clone()).unwrap_or_default();
+        let response = match core.export_bundle(Path::new(path), options).await {
+            Ok(manifest) => {
+                info!("[EXPORT] Exported {} documents to {}", manifest.documents, path);
+                AdminResponse { id, ok: true, result: serde_json::to_value(&manifest).ok(), error: None, event: None }
+            }
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_import(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let Some(path) = request.params.get("path").and_then(|v| v.as_str()) else {
+            let response = AdminResponse { id, ok: false, result: None, error: Some("missing path".to_string()), event: None };
+            let _ = tx.send((id_log, response));
+            return;
+        };
+        let response = match core.import_bundle(Path::new(path)).await {
+            Ok(report) => {
+                info!(
+                    "[IMPORT] Imported {} documents from {} ({} already up to date)",
+                    report.documents_imported, path, report.documents_skipped
+                );
+                AdminResponse { id, ok: true, result: serde_json::to_value(&report).ok(), error: None, event: None }
+            }
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_maintenance(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let options: MaintenanceOptions = serde_json::from_value(request.params.clone()).unwrap_or_default();
+        info!("[MAINTENANCE] Starting: {:?}", options);
+        let response = match core.maintenance(&options).await {
+            Ok(summary) => AdminResponse { id, ok: true, result: serde_json::to_value(&summary).ok(), error: None, event: None },
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_reindex_embeddings(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let all = request.params.get("all").and_then(|v| v.as_bool()).unwrap_or(false);
+        let response = match core.reindex_embeddings(all).await {
+            Ok(summary) => AdminResponse { id, ok: true, result: serde_json::to_value(&summary).ok(), error: None, event: None },
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_trending_topics(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let mut options = TrendOptions::default();
+        if let Some(weeks) = request.params.get("weeks").and_then(|v| v.as_u64()) {
+            options.weeks = weeks as usize;
+        }
+        if let Some(min_similarity) = request.params.get("min_similarity").and_then(|v| v.as_f64()) {
+            options.min_similarity = min_similarity as f32;
+        }
+        if let Some(min_documents) = request.params.get("min_documents").and_then(|v| v.as_u64()) {
+            options.min_documents = min_documents as usize;
+        }
+        let response = match core.trending_topics(options).await {
+            Ok(report) => AdminResponse { id, ok: true, result: serde_json::to_value(&report).ok(), error: None, event: None },
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_graph_stats(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let limit = request.params.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+        let response = match core.graph_stats(limit).await {
+            Ok(stats) => AdminResponse { id, ok: true, result: serde_json::to_value(&stats).ok(), error: None, event: None },
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_collaborators(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let limit = request.params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let days = request.params.get("days").and_then(|v| v.as_i64()).unwrap_or(30);
+        let response = match core.collaborators(limit, days).await {
+            Ok(groups) => AdminResponse { id, ok: true, result: Some(serde_json::json!({ "groups": groups })), error: None, event: None },
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_ring_changes(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let days = request.params.get("days").and_then(|v| v.as_i64()).unwrap_or(7);
+        let response = match core.ring_changes(days).await {
+            Ok(diff) => {
+                let messages: Vec<String> = diff.changes().map(|change| change.describe()).collect();
+                let mut result = serde_json::to_value(&diff).unwrap_or_default();
+                result["messages"] = serde_json::json!(messages);
+                AdminResponse { id, ok: true, result: Some(result), error: None, event: None }
+            }
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
+
+    async fn handle_recent_rings(&self, id: Option<String>, id_log: String, request: AdminRequest, tx: tokio::sync::mpsc::UnboundedSender<(String, AdminResponse)>) {
+        let core = match self.state.get_core().await {
+            Some(c) => c,
+            None => {
+                let response = AdminResponse { id, ok: false, result: None, error: Some("Engine still initializing...".to_string()), event: None };
+                let _ = tx.send((id_log, response));
+                return;
+            }
+        };
+
+        let days = request.params.get("days").and_then(|v| v.as_i64()).unwrap_or(30);
+        let response = match core.recent_rings(days).await {
+            Ok(diff) => {
+                let messages: Vec<String> = diff.changes().map(|change| change.describe()).collect();
+                let mut result = serde_json::to_value(&diff).unwrap_or_default();
+                result["messages"] = serde_json::json!(messages);
+                AdminResponse { id, ok: true, result: Some(result), error: None, event: None }
+            }
+            Err(err) => AdminResponse { id, ok: false, result: None, error: Some(err.to_string()), event: None },
+        };
+        let _ = tx.send((id_log, response));
+    }
 }
 
 #[tokio::main]
@@ -413,8 +686,15 @@ async fn main() -> Result<()> {
         )
         .init();
 
+    // Refuse a malformed profile rather than fall back to the default one
+    if let Some(profile) = std::env::var("MINNA_PROFILE").ok().filter(|p| !p.trim().is_empty()) {
+        minna_auth_bridge::validate_profile(profile.trim())?;
+    }
     let paths = MinnaPaths::from_env();
     paths.ensure_dirs()?;
+    if let Some(profile) = minna_auth_bridge::profile_from_env() {
+        info!("Using profile '{}' in {}", profile, paths.base_dir.display());
+    }
 
     // Clean up old sockets
     if Path::new(&paths.socket_path).exists() {
@@ -459,9 +739,19 @@ async fn main() -> Result<()> {
     let state_clone = state.clone();
     let paths_clone = paths.clone();
     tokio::spawn(async move {
-        match Core::init(&paths_clone).await {
+        let core = Core::init(&paths_clone)
+            .await
+            .and_then(|core| core.with_transforms(state_clone.registry.transforms()))
+            .and_then(|core| core.with_redaction(state_clone.registry.redaction()))
+            .map(|core| core.with_embedding_models(state_clone.registry.embedding_models()))
+            .map(|core| core.with_rings(state_clone.registry.rings()))
+            .map(|core| core.with_graph_pruning(state_clone.registry.graph_pruning()));
+        match core {
             Ok(core) => {
                 info!("Engine initialized successfully!");
+                if let Err(err) = core.check_embedding_models().await {
+                    warn!("[EMBED] Model check failed: {}", err);
+                }
                 // Store the initialized core
                 *state_clone.core.write().await = Some(core.clone());
                 // Emit ready signal to Swift UI
@@ -470,6 +760,16 @@ async fn main() -> Result<()> {
                 state_clone.enable_scheduler().await;
                 // Start the scheduler background task
                 spawn_scheduler_task(state_clone.clone());
+                // Heal hash-fallback embeddings once the real model is ready
+                spawn_reembed_task(core.clone());
+                // Clean up after soft-deleted documents
+                spawn_tombstone_task(core.clone());
+                // Weekly digest document
+                spawn_digest_task(core.clone());
+                // Nightly ring recalculation, and after big syncs
+                spawn_ring_task(state_clone.clone(), core.clone());
+                // MINNA.md files for agents that only read the repo
+                spawn_context_file_task(core.clone());
                 // Start clustering task if enabled
                 spawn_cluster_task(core);
             }
@@ -486,28 +786,56 @@ async fn main() -> Result<()> {
     let mcp_listener = UnixListener::bind(&paths.socket_path)?;
     info!("MCP server listening on {}", paths.socket_path.display());
 
+    // Live-fetch stub results in get_context (disable with MINNA_DEEP_FETCH=0)
+    let deep_fetch = std::env::var("MINNA_DEEP_FETCH")
+        .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+    // How many hybrid results the reranker (MINNA_RERANK=1) reorders
+    let rerank_candidates = std::env::var("MINNA_RERANK_TOP_N")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RERANK_CANDIDATES);
+    // Hybrid ranking: RRF by default, MINNA_FUSION=weighted for the old scoring
+    let fusion = FusionConfig::from_env();
+
+    let aliases = match ToolAliases::load(&ToolAliases::default_paths(&paths.base_dir)) {
+        Ok(aliases) => aliases,
+        Err(err) => {
+            warn!("Failed to load tool aliases, using defaults: {}", err);
+            ToolAliases::with_defaults()
+        }
+    };
+    // Providers agents may sync with request_sync and trigger_sync
+    // (comma-separated, `*` for all)
+    let sync_allowlist: Vec<String> = std::env::var("MINNA_MCP_SYNC_ALLOWLIST")
+        .unwrap_or_default()
+        .split(',')
+        .map(|provider| provider.trim().to_string())
+        .filter(|provider| !provider.is_empty())
+        .collect();
+    let settings = McpSettings {
+        aliases,
+        deep_fetch,
+        fusion,
+        rerank_candidates,
+        sync_allowlist,
+        pages: Arc::default(),
+    };
+
+    // Optional localhost HTTP transport (MINNA_MCP_HTTP_PORT) for clients
+    // that can't open unix sockets
+    if let Some(port) = std::env::var("MINNA_MCP_HTTP_PORT").ok().and_then(|v| v.parse::<u16>().ok()) {
+        spawn_mcp_http(state.clone(), settings.clone(), port);
+    }
+
     // MCP listener (main loop) - needs Core to be ready for most operations
     let state_for_mcp = state.clone();
     loop {
         let (stream, _) = mcp_listener.accept().await?;
         let state = state_for_mcp.clone();
+        let settings = settings.clone();
         tokio::spawn(async move {
-            // Wait for Core to be ready before handling MCP requests
-            loop {
-                if state.is_ready().await {
-                    break;
-                }
-                sleep(Duration::from_millis(100)).await;
-            }
-            if let Some(core) = state.get_core().await {
-                let ctx = McpContext::with_graph(
-                    core.ingest.clone(),
-                    core.vector.clone(),
-                    core.auth.clone(),
-                    core.embedder.clone(),
-                    core.graph.clone(),
-                );
-                let handler = Arc::new(McpHandler::new(ctx));
+            if let Some(handler) = settings.handler(&state).await {
                 if let Err(err) = handle_mcp_client(stream, handler).await {
                     error!("MCP client error: {}", err);
                 }
@@ -516,6 +844,151 @@ async fn main() -> Result<()> {
     }
 }
 
+/// How MCP handlers are configured, whichever transport they serve.
+#[derive(Clone)]
+struct McpSettings {
+    aliases: ToolAliases,
+    deep_fetch: bool,
+    fusion: FusionConfig,
+    rerank_candidates: usize,
+    sync_allowlist: Vec<String>,
+    /// `get_context` cursors, shared by every connection and transport.
+    pages: Arc<ContextPages>,
+}
+
+impl McpSettings {
+    /// Wait for Core to be ready, then build a handler on it.
+    async fn handler(self, state: &Arc<ServerState>) -> Option<Arc<McpHandler>> {
+        while !state.is_ready().await {
+            sleep(Duration::from_millis(100)).await;
+        }
+        let core = state.get_core().await?;
+        let mut ctx = McpContext::with_graph(
+            core.ingest.clone(),
+            core.vector.clone(),
+            core.auth.clone(),
+            core.embedder.clone(),
+            core.graph.clone(),
+        )
+        .with_aliases(self.aliases)
+        .with_deep_fetch(self.deep_fetch)
+        .with_fusion(self.fusion)
+        .with_sync_controller(Arc::new(DaemonSyncController::new(state.clone())))
+        .with_sync_allowlist(self.sync_allowlist)
+        .with_pages(self.pages);
+        if let Some(reranker) = core.reranker.clone() {
+            ctx = ctx.with_reranker(reranker, self.rerank_candidates);
+        }
+        if let Some(chat) = core.chat.clone() {
+            ctx = ctx.with_summarizer(chat);
+        }
+        Some(Arc::new(McpHandler::new(ctx)))
+    }
+}
+
+/// Serve MCP over HTTP on 127.0.0.1:`port`. Browser pages on other origins
+/// than localhost need to be listed in `MINNA_MCP_HTTP_ORIGINS`
+/// (comma-separated).
+fn spawn_mcp_http(state: Arc<ServerState>, settings: McpSettings, port: u16) {
+    let allowed_origins: Vec<String> = std::env::var("MINNA_MCP_HTTP_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|origin| origin.trim().to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect();
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("[MCP-HTTP] Failed to listen on 127.0.0.1:{}: {}", port, err);
+                return;
+            }
+        };
+        info!("MCP HTTP transport listening on http://127.0.0.1:{}/mcp", port);
+        let Some(handler) = settings.handler(&state).await else {
+            return;
+        };
+        if let Err(err) = minna_mcp::http::serve(listener, handler, allowed_origins).await {
+            error!("[MCP-HTTP] Server stopped: {}", err);
+        }
+    });
+}
+
+/// Lets MCP clients trigger provider syncs through the daemon's scheduler.
+struct DaemonSyncController {
+    state: Arc<ServerState>,
+}
+
+impl DaemonSyncController {
+    fn new(state: Arc<ServerState>) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait::async_trait]
+impl SyncController for DaemonSyncController {
+    fn sources(&self) -> Vec<String> {
+        self.state
+            .get_registry()
+            .list_available()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    async fn budget(&self) -> (u32, u32) {
+        let mut scheduler = self.state.get_scheduler().await;
+        let (used, total) = scheduler.budget_status();
+        (total.saturating_sub(used), total)
+    }
+
+    async fn is_syncing(&self, source: &str) -> bool {
+        self.state.get_scheduler().await.is_syncing(source)
+    }
+
+    async fn start_sync(&self, source: &str, mode: SyncMode) -> Result<SyncCompletion> {
+        let core = self
+            .state
+            .get_core()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Engine still initializing, please wait..."))?;
+        if !self.state.get_scheduler().await.begin_on_demand(source) {
+            return Err(anyhow::anyhow!("sync already in progress or budget exhausted"));
+        }
+
+        info!("[MCP_SYNC] Starting on-demand sync: provider={}, mode={:?}", source, mode);
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        let state = self.state.clone();
+        let provider = source.to_string();
+        tokio::spawn(async move {
+            let (since_days, mode) = match mode {
+                SyncMode::Recent => SyncPlanner::plan_for_ring(Ring::One),
+                SyncMode::Incremental => (None, None),
+                SyncMode::Full => (None, Some("full")),
+            };
+            let result = core
+                .sync_via_registry(state.get_registry(), &provider, since_days, mode)
+                .await;
+
+            let mut scheduler = state.get_scheduler().await;
+            match result {
+                Ok(summary) => {
+                    let api_calls = (summary.documents_processed as u32 / 10).max(1);
+                    scheduler.complete_sync(&provider, Ring::One, api_calls);
+                    state.record_sync(summary.documents_processed);
+                    let _ = done_tx.send(Ok(serde_json::to_value(summary).unwrap_or_default()));
+                }
+                Err(err) => {
+                    scheduler.fail_sync(&provider);
+                    error!("[MCP_SYNC] Sync failed: provider={}, error={}", provider, err);
+                    let _ = done_tx.send(Err(err.to_string()));
+                }
+            }
+        });
+        Ok(done_rx)
+    }
+}
+
 fn spawn_cluster_task(core: Core) {
     let enabled = std::env::var("MINNA_ENABLE_CLUSTERING")
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
@@ -523,10 +996,16 @@ fn spawn_cluster_task(core: Core) {
     if !enabled {
         return;
     }
+    // New documents join existing clusters every interval; the full
+    // pairwise pass, which also finds new clusters, runs far less often
     let interval = std::env::var("MINNA_CLUSTER_INTERVAL_SECS")
         .ok()
         .and_then(|v| v.parse().ok())
-        .unwrap_or(60 * 60 * 24);
+        .unwrap_or(60 * 60);
+    let full_interval = std::env::var("MINNA_CLUSTER_FULL_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60 * 60 * 24 * 7);
     let min_similarity = std::env::var("MINNA_CLUSTER_MIN_SIMILARITY")
         .ok()
         .and_then(|v| v.parse().ok())
@@ -537,14 +1016,229 @@ fn spawn_cluster_task(core: Core) {
         .unwrap_or(4usize);
 
     tokio::spawn(async move {
-        if let Err(err) = core.run_clustering(min_similarity, min_points).await {
-            error!("cluster run failed: {}", err);
+        let mut clusters = Vec::new();
+        let mut last_full: Option<Instant> = None;
+        let mut last_run = chrono::Utc::now();
+        loop {
+            let started = chrono::Utc::now();
+            if last_full.is_none_or(|at| at.elapsed() >= Duration::from_secs(full_interval)) {
+                match core.run_clustering(min_similarity, min_points).await {
+                    Ok(found) => {
+                        info!("[CLUSTER] Full pass found {} clusters", found.len());
+                        clusters = found;
+                        last_full = Some(Instant::now());
+                        last_run = started;
+                    }
+                    Err(err) => error!("cluster run failed: {}", err),
+                }
+            } else {
+                match core.update_clusters(&mut clusters, last_run, min_similarity).await {
+                    Ok(assigned) => {
+                        if assigned > 0 {
+                            info!("[CLUSTER] Added {} documents to existing clusters", assigned);
+                        }
+                        last_run = started;
+                    }
+                    Err(err) => error!("cluster update failed: {}", err),
+                }
+            }
+            sleep(Duration::from_secs(interval.min(full_interval))).await;
+        }
+    });
+}
+
+/// Spawn the warm standby task that re-embeds documents indexed with the hash
+/// fallback once the real embedding model has finished loading.
+///
+/// Once the model is ready, documents whose vectors have another dimension
+/// than it produces are queued too (disable with MINNA_REEMBED_MISMATCHED=0;
+/// they are then only reported).
+fn spawn_reembed_task(core: Core) {
+    let reembed_mismatched = std::env::var("MINNA_REEMBED_MISMATCHED")
+        .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+
+    tokio::spawn(async move {
+        let standby = core.standby.clone();
+        if let Some(standby) = &standby {
+            if !standby.wait_ready().await {
+                // Hash embeddings stay queued; the next start re-embeds them
+                // once the model loads
+                warn!("[STANDBY] Embedding model failed to load, keeping the hash fallback");
+                return;
+            }
+            info!("[STANDBY] Embedding model ready, re-embedding queued documents");
         }
+        let mismatched = match core.check_embedding_dims(reembed_mismatched).await {
+            Ok(check) => check.map_or(0, |check| check.queued),
+            Err(err) => {
+                warn!("[EMBED] Dimension check failed: {}", err);
+                0
+            }
+        };
+        // Also drains what an earlier run queued with the hash fallback
+        match core.reembed_pending(64).await {
+            Ok(0) if standby.is_none() && mismatched == 0 => {}
+            Ok(count) => info!("[STANDBY] Re-embedded {} documents", count),
+            Err(err) => error!("[STANDBY] Re-embed failed: {}", err),
+        }
+    });
+}
+
+/// Periodically purge soft-deleted documents along with their embeddings,
+/// graph edges and cluster references.
+fn spawn_tombstone_task(core: Core) {
+    tokio::spawn(async move {
+        let interval = Duration::from_secs(10 * 60);
         loop {
-            sleep(Duration::from_secs(interval)).await;
-            if let Err(err) = core.run_clustering(min_similarity, min_points).await {
-                error!("cluster run failed: {}", err);
+            match core.purge_tombstones(256).await {
+                Ok(0) => {}
+                Ok(count) => info!("[TOMBSTONE] Purged {} deleted documents", count),
+                Err(err) => error!("[TOMBSTONE] Purge failed: {}", err),
             }
+            sleep(interval).await;
+        }
+    });
+}
+
+/// Index a weekly digest under source "minna" (disable with MINNA_WEEKLY_DIGEST=0).
+fn spawn_digest_task(core: Core) {
+    let enabled = std::env::var("MINNA_WEEKLY_DIGEST")
+        .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+
+    tokio::spawn(async move {
+        // Let the first syncs land before checking
+        sleep(Duration::from_secs(5 * 60)).await;
+        loop {
+            match core.weekly_digest_if_due().await {
+                Ok(Some(id)) => info!("[DIGEST] Indexed weekly digest (doc {})", id),
+                Ok(None) => {}
+                Err(err) => error!("[DIGEST] Weekly digest failed: {}", err),
+            }
+            sleep(Duration::from_secs(6 * 60 * 60)).await;
+        }
+    });
+}
+
+/// Prune stale edges, recompute centrality and recalculate Gravity Well
+/// rings every night at MINNA_RING_HOUR (local time, default 3) and log the
+/// ring changes (disable with MINNA_RING_RECALC=0).
+///
+/// Syncs that bring in MINNA_RING_RECALC_AFTER_DOCS documents (default 500,
+/// counted across syncs) also recalculate centrality and rings, so ring
+/// boosts don't wait a day to reflect them.
+fn spawn_ring_task(state: Arc<ServerState>, core: Core) {
+    let enabled = std::env::var("MINNA_RING_RECALC")
+        .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+    let hour = std::env::var("MINNA_RING_HOUR")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|hour| *hour < 24)
+        .unwrap_or(3u32);
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = sleep(until_next_hour(chrono::Local::now(), hour)) => {
+                    match core.prune_graph().await {
+                        Ok(report) => info!(
+                            "[GRAPH] Pruned {} stale edges ({} archived), dropped {} edge sources",
+                            report.edges_archived + report.edges_deleted,
+                            report.edges_archived,
+                            report.sources_dropped
+                        ),
+                        Err(err) => error!("[GRAPH] Graph pruning failed: {}", err),
+                    }
+                }
+                _ = state.rings_stale.notified() => {
+                    // Let syncs of the other providers finish first, so a
+                    // round of scheduled syncs recalculates once
+                    sleep(Duration::from_secs(60)).await;
+                    let synced = state.synced_since_rings.load(Ordering::Relaxed);
+                    // A wakeup left over from syncs the last run already covered
+                    if synced < state.ring_recalc_after_docs {
+                        continue;
+                    }
+                    info!("[RINGS] {} documents synced since the last recalculation", synced);
+                }
+            }
+            state.synced_since_rings.store(0, Ordering::Relaxed);
+            recalculate_rings(&core).await;
+        }
+    });
+}
+
+/// Recompute centrality and the user's rings, logging each ring change.
+async fn recalculate_rings(core: &Core) {
+    match core.recalculate_centrality().await {
+        Ok(nodes) => info!("[GRAPH] Centrality recalculated for {} nodes", nodes),
+        Err(err) => error!("[GRAPH] Centrality calculation failed: {}", err),
+    }
+    let users = match core.ring_user_nodes().await {
+        Ok(users) if !users.is_empty() => users,
+        Ok(_) => {
+            info!("[RINGS] No user node yet; set [rings] user in providers.toml");
+            return;
+        }
+        Err(err) => {
+            error!("[RINGS] Can't find the user's nodes: {}", err);
+            return;
+        }
+    };
+    match core.recalculate_rings(&users).await {
+        Ok(result) => {
+            let promoted = result.transitions.iter().filter(|t| t.is_promotion()).count();
+            info!(
+                "[RINGS] Recalculated {} nodes in {}ms: {} promoted, {} demoted",
+                result.nodes_processed,
+                result.duration_ms,
+                promoted,
+                result.transitions.len() - promoted
+            );
+            for transition in &result.transitions {
+                info!(
+                    "[RINGS] {}: {:?} -> {:?}",
+                    transition.node_id, transition.from, transition.to
+                );
+            }
+        }
+        Err(err) => error!("[RINGS] Ring recalculation failed: {}", err),
+    }
+}
+
+/// Time from `now` until the next time the local clock reads `hour`:00.
+fn until_next_hour(now: chrono::DateTime<chrono::Local>, hour: u32) -> Duration {
+    let mut next = now.date_naive().and_hms_opt(hour, 0, 0).unwrap_or_default();
+    if next <= now.naive_local() {
+        next += chrono::Duration::days(1);
+    }
+    (next - now.naive_local()).to_std().unwrap_or(Duration::from_secs(60 * 60))
+}
+
+/// Keep MINNA.md context files up to date in the repos listed in MINNA_CONTEXT_ROOTS.
+fn spawn_context_file_task(core: Core) {
+    let config = ContextFileConfig::from_env();
+    if !config.is_enabled() {
+        return;
+    }
+    info!("[CONTEXT] Writing {} into {} workspace(s)", config.format.file_name(), config.roots.len());
+
+    tokio::spawn(async move {
+        loop {
+            match core.write_context_files(&config).await {
+                Ok(0) => {}
+                Ok(count) => info!("[CONTEXT] Updated {} context file(s)", count),
+                Err(err) => error!("[CONTEXT] Context file update failed: {}", err),
+            }
+            sleep(Duration::from_secs(15 * 60)).await;
         }
     });
 }
@@ -620,6 +1314,7 @@ fn spawn_scheduler_task(state: Arc<ServerState>) {
                         // Estimate API calls from items synced (rough heuristic)
                         let api_calls = (summary.documents_processed as u32 / 10).max(1);
                         scheduler.complete_sync(&sync_task.provider, sync_task.ring, api_calls);
+                        state.record_sync(summary.documents_processed);
                         info!(
                             "[SCHEDULER] Sync complete: provider={}, items={}",
                             sync_task.provider, summary.documents_processed
@@ -645,30 +1340,110 @@ async fn handle_mcp_client(
     stream: tokio::net::UnixStream,
     handler: Arc<McpHandler>,
 ) -> Result<()> {
+    use tokio::sync::mpsc;
+
     let (reader, mut writer) = stream.into_split();
     let mut lines = BufReader::new(reader).lines();
 
+    // Requests with an ID run concurrently so a streamed sync doesn't block
+    // the connection. Those without one are answered in order, since their
+    // responses can only be matched up by position.
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let write_task = tokio::spawn(async move {
+        while let Some(payload) = rx.recv().await {
+            if writer.write_all(payload.as_bytes()).await.is_err()
+                || writer.write_all(b"\n").await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
     while let Some(line) = lines.next_line().await? {
         let trimmed = line.trim();
         if trimmed.is_empty() {
             continue;
         }
-        let response = match serde_json::from_str::<ToolRequest>(trimmed) {
-            Ok(request) => handler.handle(request).await,
-            Err(err) => ToolResponse {
-                id: None,
-                ok: false,
-                result: None,
-                error: Some(format!("invalid request: {}", err)),
-            },
-        };
-        let payload = serde_json::to_string(&response)?;
-        writer.write_all(payload.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
+        // MCP clients send JSON-RPC 2.0; everything else is `{tool, params}`
+        let message = serde_json::from_str::<serde_json::Value>(trimmed);
+        if let Some(message) = message.as_ref().ok().filter(|message| minna_mcp::protocol::is_jsonrpc(message)) {
+            match serde_json::from_value::<JsonRpcRequest>(message.clone()) {
+                Ok(request) => {
+                    let handler = handler.clone();
+                    let tx = tx.clone();
+                    let serve = async move {
+                        if let Some(response) = handler.handle_jsonrpc(request).await {
+                            send_mcp_message(&tx, &response);
+                        }
+                    };
+                    // Notifications have no ID; later requests may depend on them
+                    if message.get("id").is_some_and(|id| !id.is_null()) {
+                        tokio::spawn(serve);
+                    } else {
+                        serve.await;
+                    }
+                }
+                Err(err) => {
+                    let id = message.get("id").cloned().unwrap_or_default();
+                    let response = JsonRpcResponse::error(
+                        id,
+                        minna_mcp::protocol::INVALID_REQUEST,
+                        format!("invalid request: {}", err),
+                    );
+                    send_mcp_message(&tx, &response);
+                }
+            }
+            continue;
+        }
+        match message.and_then(serde_json::from_value::<ToolRequest>) {
+            Ok(request) => {
+                let handler = handler.clone();
+                let tx = tx.clone();
+                let concurrent = request.id.is_some();
+                let serve = async move {
+                    let (responses, mut rx) = mpsc::unbounded_channel::<ToolResponse>();
+                    let forward = async {
+                        while let Some(response) = rx.recv().await {
+                            send_mcp_message(&tx, &response);
+                        }
+                    };
+                    tokio::join!(handler.handle_streaming(request, responses), forward);
+                };
+                if concurrent {
+                    tokio::spawn(serve);
+                } else {
+                    serve.await;
+                }
+            }
+            Err(err) => {
+                let response = ToolResponse {
+                    id: None,
+                    ok: false,
+                    result: None,
+                    error: Some(format!("invalid request: {}", err)),
+                    warning: None,
+                    event: None,
+                };
+                send_mcp_message(&tx, &response);
+            }
+        }
     }
+
+    drop(tx);
+    let _ = write_task.await;
     Ok(())
 }
 
+/// Queue a response for an MCP client as one line of JSON.
+fn send_mcp_message<T: serde::Serialize>(tx: &tokio::sync::mpsc::UnboundedSender<String>, message: &T) {
+    match serde_json::to_string(message) {
+        Ok(payload) => {
+            let _ = tx.send(payload);
+        }
+        Err(err) => error!("Failed to serialize MCP response: {}", err),
+    }
+}
+
 async fn handle_admin_client(
     stream: tokio::net::UnixStream,
     handler: Arc<AdminHandler>,
@@ -759,3 +1534,45 @@ async fn handle_admin_client(
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_until_next_hour() {
+        let evening = chrono::Local.with_ymd_and_hms(2024, 5, 1, 22, 30, 0).unwrap();
+        assert_eq!(until_next_hour(evening, 3), Duration::from_secs((4 * 60 + 30) * 60));
+        let night = chrono::Local.with_ymd_and_hms(2024, 5, 1, 1, 0, 0).unwrap();
+        assert_eq!(until_next_hour(night, 3), Duration::from_secs(2 * 60 * 60));
+        // On the hour means tomorrow, not now
+        let on_time = chrono::Local.with_ymd_and_hms(2024, 5, 1, 3, 0, 0).unwrap();
+        assert_eq!(until_next_hour(on_time, 3), Duration::from_secs(24 * 60 * 60));
+    }
+
+    /// Whether the ring task would wake up now.
+    async fn woken(state: &ServerState) -> bool {
+        tokio::time::timeout(Duration::from_millis(50), state.rings_stale.notified()).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_big_syncs_wake_the_ring_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = ServerState::new(MinnaPaths::for_profile(dir.path(), None));
+        state.ring_recalc_after_docs = 100;
+
+        // Counted across syncs
+        state.record_sync(60);
+        assert!(!woken(&state).await);
+        state.record_sync(40);
+        assert!(woken(&state).await);
+        assert_eq!(state.synced_since_rings.load(Ordering::Relaxed), 100);
+
+        state.synced_since_rings.store(0, Ordering::Relaxed);
+        state.ring_recalc_after_docs = 0;
+        state.record_sync(1_000);
+        assert!(!woken(&state).await);
+    }
+}
@@ -0,0 +1,1640 @@
+//! End-to-end scenarios against a real daemon process.
+//!
+//! Each test boots the `minna-core` binary on a temporary data directory with
+//! the mock provider and the hash embedder, talks to it over the admin and MCP
+//! sockets, and checks the resulting database state.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::process::Stdio;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+use tokio::process::{Child, Command};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+use minna_graph::{ExtractedEdge, GraphStore, NodeRef, NodeType, Relation, RingEngine};
+use minna_ingest::{ClusterRecord, Document, IngestionEngine};
+use minna_vector::{
+    embed_document, Embedder, HashEmbedder, SearchFilter, SourceEmbedders, VectorStore,
+};
+
+const PROVIDERS_TOML: &str = r#"
+[providers.mock]
+enabled = true
+display_name = "Mock"
+auth = { type = "none" }
+"#;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct Daemon {
+    _dir: tempfile::TempDir,
+    data_dir: PathBuf,
+    child: Child,
+}
+
+impl Daemon {
+    async fn start() -> Result<Self> {
+        Self::start_with(None, PROVIDERS_TOML).await
+    }
+
+    /// Boot with `MINNA_PROFILE` set; `data_dir` is then the profile's
+    /// directory under the data root.
+    async fn start_in_profile(profile: Option<&str>) -> Result<Self> {
+        Self::start_with(profile, PROVIDERS_TOML).await
+    }
+
+    async fn start_with(profile: Option<&str>, providers_toml: &str) -> Result<Self> {
+        Self::launch(profile, providers_toml, &[]).await
+    }
+
+    /// Boot with extra environment variables.
+    async fn start_with_env(envs: &[(&str, &str)]) -> Result<Self> {
+        Self::launch(None, PROVIDERS_TOML, envs).await
+    }
+
+    async fn launch(profile: Option<&str>, providers_toml: &str, envs: &[(&str, &str)]) -> Result<Self> {
+        let dir = tempfile::tempdir()?;
+        let data_dir = match profile {
+            Some(profile) => dir.path().join("profiles").join(profile),
+            None => dir.path().to_path_buf(),
+        };
+        std::fs::create_dir_all(&data_dir)?;
+        std::fs::write(data_dir.join("providers.toml"), providers_toml)?;
+
+        let child = Command::new(env!("CARGO_BIN_EXE_minna-core"))
+            .env("MINNA_DATA_DIR", dir.path())
+            .env("MINNA_PROFILE", profile.unwrap_or(""))
+            .env("MINNA_EMBED_BACKEND", "hash")
+            .env("MINNA_WEEKLY_DIGEST", "0")
+            .env_remove("MINNA_ENABLE_SCHEDULER")
+            .env_remove("MINNA_ENABLE_CLUSTERING")
+            .env("RUST_LOG", "warn")
+            .envs(envs.iter().copied())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let daemon = Self {
+            _dir: dir,
+            data_dir,
+            child,
+        };
+        daemon.wait_ready().await?;
+        Ok(daemon)
+    }
+
+    async fn wait_ready(&self) -> Result<()> {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            if Instant::now() > deadline {
+                return Err(anyhow!("daemon did not become ready"));
+            }
+            if self.data_dir.join("mcp.sock").exists() {
+                if let Ok(mut admin) = self.admin().await {
+                    let status = admin.call("get_status", json!({})).await?;
+                    if status["result"]["ready"] == json!(true) {
+                        return Ok(());
+                    }
+                }
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    async fn admin(&self) -> Result<Client> {
+        Client::connect(&self.data_dir.join("admin.sock")).await
+    }
+
+    async fn mcp(&self) -> Result<Client> {
+        Client::connect(&self.data_dir.join("mcp.sock")).await
+    }
+
+    /// Open the daemon's database alongside it (SQLite WAL allows this).
+    async fn ingest(&self) -> Result<IngestionEngine> {
+        IngestionEngine::new(&self.data_dir.join("minna.db")).await
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.start_kill();
+    }
+}
+
+/// JSON-lines client for either socket.
+struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    next_id: u64,
+}
+
+impl Client {
+    async fn connect(path: &Path) -> Result<Self> {
+        let (reader, writer) = UnixStream::connect(path).await?.into_split();
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            next_id: 0,
+        })
+    }
+
+    /// Send a request and return its final response, skipping progress events.
+    async fn call(&mut self, tool: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = format!("e2e-{}", self.next_id);
+        let request = json!({ "id": id, "tool": tool, "params": params });
+        self.writer.write_all(format!("{}\n", request).as_bytes()).await?;
+
+        timeout(REQUEST_TIMEOUT, async {
+            while let Some(line) = self.lines.next_line().await? {
+                let response: Value = serde_json::from_str(&line)?;
+                if response["id"] == json!(id) && response.get("event").is_none() {
+                    return Ok(response);
+                }
+            }
+            Err(anyhow!("socket closed before {} responded", tool))
+        })
+        .await
+        .map_err(|_| anyhow!("{} timed out", tool))?
+    }
+
+    /// Send a JSON-RPC 2.0 request, as MCP clients do, and return its response.
+    async fn rpc(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.writer.write_all(format!("{}\n", request).as_bytes()).await?;
+
+        timeout(REQUEST_TIMEOUT, async {
+            while let Some(line) = self.lines.next_line().await? {
+                let response: Value = serde_json::from_str(&line)?;
+                if response["id"] == json!(id) {
+                    return Ok(response);
+                }
+            }
+            Err(anyhow!("socket closed before {} responded", method))
+        })
+        .await
+        .map_err(|_| anyhow!("{} timed out", method))?
+    }
+
+    /// Send a JSON-RPC notification, which gets no response.
+    async fn notify(&mut self, method: &str) -> Result<()> {
+        let notification = json!({ "jsonrpc": "2.0", "method": method });
+        self.writer.write_all(format!("{}\n", notification).as_bytes()).await?;
+        Ok(())
+    }
+}
+
+fn mock_count(counts: &[(String, i64)]) -> i64 {
+    counts
+        .iter()
+        .find(|(source, _)| source == "mock")
+        .map(|(_, count)| *count)
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_sync_discover_context_reset() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+
+    let pong = admin.call("ping", json!({})).await?;
+    assert_eq!(pong["result"]["pong"], json!(true));
+
+    // Sync writes documents, a cursor and graph edges
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+    assert_eq!(sync["result"]["documents_processed"], json!(3));
+
+    let ingest = daemon.ingest().await?;
+    assert_eq!(mock_count(&ingest.document_counts_by_source().await?), 3);
+    let doc = ingest
+        .get_document_by_uri("mock://doc/2")
+        .await?
+        .expect("mock document indexed");
+    assert_eq!(doc.title.as_deref(), Some("Incident report: search latency"));
+    assert!(ingest.get_sync_cursor("mock").await?.is_some());
+
+    // Mock documents were updated one, two and three hours ago
+    let now = chrono::Utc::now();
+    let recent = ingest
+        .fetch_documents(Some("mock"), Some(now - chrono::Duration::minutes(150)), None, 10)
+        .await?;
+    let uris: Vec<&str> = recent.iter().map(|doc| doc.uri.as_str()).collect();
+    assert_eq!(uris, vec!["mock://doc/1", "mock://doc/2"]);
+    let older = ingest
+        .fetch_documents(None, None, Some(now - chrono::Duration::minutes(90)), 1)
+        .await?;
+    assert_eq!(older.len(), 1);
+    assert_eq!(older[0].uri, "mock://doc/2");
+    assert!(ingest.fetch_documents(Some("slack"), None, None, 10).await?.is_empty());
+
+    let discover = admin.call("discover", json!({ "provider": "mock" })).await?;
+    assert_eq!(discover["result"]["documents"], json!(3));
+
+    // MCP retrieval sees the synced documents
+    let mut mcp = daemon.mcp().await?;
+    let context = mcp
+        .call("get_context", json!({ "query": "search latency incident" }))
+        .await?;
+    assert_eq!(context["ok"], json!(true), "get_context failed: {}", context);
+    let items = context["result"]["items"].as_array().cloned().unwrap_or_default();
+    let hit = items
+        .iter()
+        .find(|item| item["uri"] == json!("mock://doc/2"))
+        .unwrap_or_else(|| panic!("expected mock://doc/2 in {:?}", items));
+    let snippet = hit["snippet"].as_str().unwrap_or_default();
+    assert!(snippet.contains("**latency**"), "snippet not highlighted: {}", snippet);
+
+    // Documents are tagged as they sync; the tag narrows get_context
+    assert_eq!(ingest.tags_for(doc.id.expect("stored id")).await?, vec!["grace".to_string()]);
+    assert_eq!(ingest.list_by_tag("ada", 10).await?.len(), 2);
+    let tagged = mcp
+        .call("get_context", json!({ "query": "latency roadmap checklist", "tags": ["Ada"] }))
+        .await?;
+    let uris: Vec<&Value> = tagged["result"]["items"]
+        .as_array()
+        .map(|items| items.iter().map(|item| &item["uri"]).collect())
+        .unwrap_or_default();
+    assert!(!uris.is_empty(), "no tagged results: {}", tagged);
+    assert!(!uris.contains(&&json!("mock://doc/2")), "untagged document returned: {:?}", uris);
+
+    // Documents a client has already seen can be left out
+    let short_id = hit["short_id"].clone();
+    let excluded = mcp
+        .call(
+            "get_context",
+            json!({ "query": "search latency incident", "exclude_doc_ids": [short_id] }),
+        )
+        .await?;
+    let uris: Vec<&Value> = excluded["result"]["items"]
+        .as_array()
+        .map(|items| items.iter().map(|item| &item["uri"]).collect())
+        .unwrap_or_default();
+    assert!(!uris.is_empty(), "no results: {}", excluded);
+    assert!(!uris.contains(&&json!("mock://doc/2")), "excluded document returned: {:?}", uris);
+
+    // Languages are detected at ingest; a language filter narrows get_context
+    let languages = ingest.document_languages(&[doc.id.expect("stored id")]).await?;
+    assert_eq!(languages.values().collect::<Vec<_>>(), vec!["eng"]);
+    for (languages, expect_hits) in [(json!(["English"]), true), (json!(["deu"]), false)] {
+        let filtered = mcp
+            .call(
+                "get_context",
+                json!({ "query": "search latency incident", "languages": languages }),
+            )
+            .await?;
+        let items = filtered["result"]["items"].as_array().cloned().unwrap_or_default();
+        assert_eq!(!items.is_empty(), expect_hits, "languages {}: {}", languages, filtered);
+    }
+
+    // Attachments are listed on their document and read by URI
+    let resource = mcp.call("read_resource", json!({ "uri": "mock://doc/1" })).await?;
+    assert_eq!(resource["ok"], json!(true), "read_resource failed: {}", resource);
+    assert_eq!(resource["result"]["referenced_by"], json!(["mock://doc/3"]));
+    let attachment_uri = resource["result"]["attachments"][0].clone();
+    assert!(attachment_uri.as_str().unwrap_or_default().starts_with("attachment://"));
+    let attachment = mcp.call("read_resource", json!({ "uri": attachment_uri })).await?;
+    assert_eq!(attachment["result"]["mime_type"], json!("text/plain"));
+    assert_eq!(attachment["result"]["source"], json!("mock"));
+    assert!(attachment["result"]["body"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("Sync engine rewrite"));
+
+    // Maintenance checks and compacts the database without losing anything
+    let maintenance = admin.call("maintenance", json!({})).await?;
+    assert_eq!(maintenance["ok"], json!(true), "maintenance failed: {}", maintenance);
+    assert_eq!(maintenance["result"]["integrity_ok"], json!(true));
+    assert_eq!(maintenance["result"]["fts_ok"], json!(true));
+    assert_eq!(maintenance["result"]["fts_rebuilt"], json!(true));
+    assert_eq!(maintenance["result"]["vacuumed"], json!(true));
+    assert_eq!(ingest.search_keyword("latency", 5).await?.len(), 1);
+
+    // Export writes a bundle with the documents and, on request, the edges
+    let bundle = daemon.data_dir.join("export");
+    let export = admin
+        .call("export", json!({ "path": bundle, "edges": true }))
+        .await?;
+    assert_eq!(export["ok"], json!(true), "export failed: {}", export);
+    assert_eq!(export["result"]["documents"], json!(3));
+    // Three authorship edges and the reference from doc 3 to doc 1
+    assert_eq!(export["result"]["edges"], json!(4));
+    assert!(export["result"].get("embeddings").is_none());
+    let documents = std::fs::read_to_string(bundle.join("documents.jsonl"))?;
+    assert_eq!(documents.lines().count(), 3);
+    assert!(documents.contains("\"tags\":[\"grace\"]"));
+    let again = admin.call("export", json!({ "path": bundle })).await?;
+    assert_eq!(again["ok"], json!(false), "export overwrote an existing bundle");
+    let archive = daemon.data_dir.join("export.tar");
+    let tar = admin.call("export", json!({ "path": archive })).await?;
+    assert_eq!(tar["ok"], json!(true), "tar export failed: {}", tar);
+    assert!(archive.is_file());
+    assert!(!daemon.data_dir.join("export.tar.partial").exists());
+
+    // Reset tombstones everything from the provider and clears its cursor
+    let reset = admin.call("reset", json!({ "provider": "mock" })).await?;
+    assert_eq!(reset["result"]["status"], json!("reset_complete"));
+    assert_eq!(mock_count(&ingest.document_counts_by_source().await?), 0);
+    assert!(ingest.get_document_by_uri("mock://doc/2").await?.is_none());
+    assert_eq!(ingest.tombstone_count().await?, 3);
+    let gone = mcp.call("read_resource", json!({ "uri": attachment_uri })).await?;
+    assert_eq!(gone["ok"], json!(false), "attachment of a deleted document: {}", gone);
+
+    // A fresh sync brings the documents back
+    let resync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(resync["ok"], json!(true));
+    assert_eq!(mock_count(&ingest.document_counts_by_source().await?), 3);
+    assert_eq!(ingest.tombstone_count().await?, 0);
+
+    // Importing the earlier export dedups against the resynced documents
+    let import = admin.call("import", json!({ "path": archive })).await?;
+    assert_eq!(import["ok"], json!(true), "import failed: {}", import);
+    assert_eq!(import["result"]["documents_imported"], json!(0));
+    assert_eq!(import["result"]["documents_skipped"], json!(3));
+    assert_eq!(mock_count(&ingest.document_counts_by_source().await?), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_requests_are_rejected() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+
+    let unknown_tool = admin.call("does_not_exist", json!({})).await?;
+    assert_eq!(unknown_tool["ok"], json!(false));
+    assert_eq!(unknown_tool["error"], json!("unknown admin tool"));
+
+    let unknown_provider = admin
+        .call("sync_provider", json!({ "provider": "nope" }))
+        .await?;
+    assert_eq!(unknown_provider["ok"], json!(false));
+    assert_eq!(unknown_provider["error"], json!("unknown provider: nope"));
+
+    // Requests on one connection are handled concurrently but each gets its own reply
+    let mut mcp = daemon.mcp().await?;
+    let first = mcp.call("get_context", json!({ "query": "anything" })).await?;
+    let second = mcp.call("no_such_tool", json!({})).await?;
+    assert_eq!(first["ok"], json!(true));
+    assert_eq!(second["ok"], json!(false));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pipelined_requests_without_ids() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+    admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+
+    // Without IDs the responses can only be matched by order, so the quick
+    // second request must not overtake the search
+    let mut mcp = daemon.mcp().await?;
+    let requests = [
+        json!({ "tool": "get_context", "params": { "query": "search latency incident" } }),
+        json!({ "tool": "no_such_tool", "params": {} }),
+    ];
+    for request in &requests {
+        mcp.writer.write_all(format!("{}\n", request).as_bytes()).await?;
+    }
+    let mut responses = Vec::new();
+    while responses.len() < requests.len() {
+        let line = timeout(REQUEST_TIMEOUT, mcp.lines.next_line())
+            .await??
+            .ok_or_else(|| anyhow!("socket closed"))?;
+        responses.push(serde_json::from_str::<Value>(&line)?);
+    }
+    assert_eq!(responses[0]["ok"], json!(true), "{}", responses[0]);
+    assert!(responses[0]["result"]["items"].is_array(), "{}", responses[0]);
+    assert_eq!(responses[1]["ok"], json!(false), "{}", responses[1]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mcp_json_rpc() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    ingest
+        .upsert_document(&Document {
+            id: None,
+            uri: "https://example.com/handbook".to_string(),
+            source: "mock".to_string(),
+            title: Some("Handbook".to_string()),
+            body: "How we deploy on Fridays".to_string(),
+            updated_at: chrono::Utc::now(),
+        })
+        .await?;
+
+    let mut mcp = daemon.mcp().await?;
+    let init = mcp
+        .rpc("initialize", json!({ "protocolVersion": "2025-03-26", "capabilities": {}, "clientInfo": { "name": "e2e", "version": "1" } }))
+        .await?;
+    assert_eq!(init["jsonrpc"], json!("2.0"));
+    assert_eq!(init["result"]["protocolVersion"], json!("2025-03-26"));
+    assert_eq!(init["result"]["serverInfo"]["name"], json!("minna"));
+    mcp.notify("notifications/initialized").await?;
+
+    let tools = mcp.rpc("tools/list", json!({})).await?;
+    let names: Vec<&str> = tools["result"]["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(names.contains(&"get_context") && names.contains(&"search_memory"), "{:?}", names);
+
+    let call = mcp.rpc("tools/call", json!({ "name": "get_context", "arguments": { "query": "deploy" } })).await?;
+    assert_eq!(call["result"]["isError"], json!(false), "{}", call);
+    assert_eq!(call["result"]["content"][0]["type"], json!("text"));
+    let failed = mcp.rpc("tools/call", json!({ "name": "read_resource", "arguments": { "uri": "nope" } })).await?;
+    assert_eq!(failed["result"]["isError"], json!(true), "{}", failed);
+    let unknown = mcp.rpc("tools/call", json!({ "name": "no_such_tool" })).await?;
+    assert_eq!(unknown["error"]["code"], json!(-32602));
+
+    let resources = mcp.rpc("resources/list", json!({})).await?;
+    assert_eq!(resources["result"]["resources"][0]["uri"], json!("https://example.com/handbook"));
+    let read = mcp.rpc("resources/read", json!({ "uri": "https://example.com/handbook" })).await?;
+    assert_eq!(read["result"]["contents"][0]["text"], json!("How we deploy on Fridays"));
+
+    assert_eq!(mcp.rpc("no/such/method", json!({})).await?["error"]["code"], json!(-32601));
+    // The bespoke framing still works on the same connection
+    assert_eq!(mcp.call("get_context", json!({ "query": "deploy" })).await?["ok"], json!(true));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_sources_and_stats() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    for (uri, source) in [("mock://1", "mock"), ("mock://2", "mock"), ("file:///notes.md", "notes")] {
+        ingest
+            .upsert_document(&Document {
+                id: None,
+                uri: uri.to_string(),
+                source: source.to_string(),
+                title: None,
+                body: "body".to_string(),
+                updated_at: chrono::Utc::now(),
+            })
+            .await?;
+    }
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("list_sources", json!({})).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let result = &response["result"];
+    assert_eq!(result["total_documents"], json!(3));
+    let source = |name: &str| {
+        result["sources"].as_array().into_iter().flatten().find(|s| s["source"] == json!(name)).cloned()
+    };
+    let mock = source("mock").ok_or_else(|| anyhow!("no mock source: {}", result))?;
+    assert_eq!((mock["connected"].clone(), mock["documents"].clone()), (json!(true), json!(2)));
+    let notes = source("notes").ok_or_else(|| anyhow!("no notes source: {}", result))?;
+    assert_eq!((notes["connected"].clone(), notes["documents"].clone()), (json!(false), json!(1)));
+
+    let stats = mcp.call("get_stats", json!({})).await?;
+    assert_eq!(stats["ok"], json!(true), "{}", stats);
+    assert_eq!(stats["result"]["documents"], json!(3));
+    assert_eq!(stats["result"]["clusters"], json!(0));
+    assert!(stats["result"]["graph"]["nodes"].is_i64(), "{}", stats);
+    assert!(stats["result"]["db_size_bytes"].as_i64().unwrap_or_default() > 0, "{}", stats);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trigger_sync_allowlist() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut mcp = daemon.mcp().await?;
+    let refused = mcp.call("trigger_sync", json!({ "provider": "mock" })).await?;
+    assert_eq!(refused["ok"], json!(false), "{}", refused);
+    drop(mcp);
+    drop(daemon);
+
+    let daemon = Daemon::start_with_env(&[("MINNA_MCP_SYNC_ALLOWLIST", "mock")]).await?;
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("trigger_sync", json!({ "provider": "mock", "mode": "full" })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    assert_eq!(response["result"]["status"], json!("started"));
+    assert_eq!(response["result"]["mode"], json!("full"));
+    let other = mcp.call("trigger_sync", json!({ "provider": "github" })).await?;
+    assert_eq!(other["ok"], json!(false), "{}", other);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_timeline() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let now = chrono::Utc::now();
+    for (uri, source, hours) in [
+        ("slack://C1/1", "slack", 50),
+        ("linear://ENG-1", "linear", 49),
+        ("slack://C1/2", "slack", 1),
+        ("slack://C1/old", "slack", 24 * 30),
+    ] {
+        ingest
+            .upsert_document(&Document {
+                id: None,
+                uri: uri.to_string(),
+                source: source.to_string(),
+                title: Some(uri.to_string()),
+                body: "body".to_string(),
+                updated_at: now - chrono::Duration::hours(hours),
+            })
+            .await?;
+    }
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("get_timeline", json!({ "days": 7 })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let result = &response["result"];
+    assert_eq!((result["total"].clone(), result["truncated"].clone()), (json!(3), json!(false)));
+    let uris: Vec<&str> = result["days"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|day| day["items"].as_array().into_iter().flatten())
+        .filter_map(|item| item["uri"].as_str())
+        .collect();
+    assert_eq!(uris, vec!["slack://C1/1", "linear://ENG-1", "slack://C1/2"]);
+    let last_day = result["days"].as_array().and_then(|days| days.last()).cloned().unwrap_or_default();
+    assert_eq!(last_day["date"], json!(now.date_naive().to_string()));
+
+    let slack = mcp.call("get_timeline", json!({ "sources": ["Slack"], "limit": 1 })).await?;
+    let result = &slack["result"];
+    assert_eq!((result["total"].clone(), result["truncated"].clone()), (json!(1), json!(true)));
+    assert_eq!(result["days"][0]["items"][0]["uri"], json!("slack://C1/2"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_and_get_packs() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let now = chrono::Utc::now();
+    let mut ids = Vec::new();
+    for n in 0..3 {
+        let doc = Document {
+            id: None,
+            uri: format!("mock://billing/{}", n),
+            source: "mock".to_string(),
+            title: Some(format!("Billing {}", n)),
+            body: "billing outage".to_string(),
+            updated_at: now - chrono::Duration::hours(n),
+        };
+        ids.push(ingest.upsert_document(&doc).await?);
+    }
+    let cluster = |label: &str, doc_ids: &[i64], summary: Option<&str>| ClusterRecord {
+        id: None,
+        label: label.to_string(),
+        doc_ids: doc_ids.to_vec(),
+        created_at: now,
+        summary: summary.map(str::to_string),
+    };
+    ingest
+        .store_clusters(&[
+            cluster("billing", &ids[..1], None),
+            cluster("deploys", &ids[2..], None),
+            cluster("billing", &ids, Some("Billing outage")),
+        ])
+        .await?;
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("list_packs", json!({})).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let packs = response["result"]["packs"].as_array().cloned().unwrap_or_default();
+    let labels: Vec<&str> = packs.iter().filter_map(|pack| pack["label"].as_str()).collect();
+    assert_eq!(labels, vec!["billing", "deploys"]);
+    assert_eq!((packs[0]["documents"].clone(), packs[0]["summary"].clone()), (json!(3), json!("Billing outage")));
+
+    let pack = mcp.call("get_pack", json!({ "label": "billing", "limit": 2 })).await?;
+    assert_eq!(pack["ok"], json!(true), "{}", pack);
+    assert_eq!(pack["result"]["documents"], json!(3));
+    let uris: Vec<&str> = pack["result"]["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item["uri"].as_str())
+        .collect();
+    assert_eq!(uris, vec!["mock://billing/0", "mock://billing/1"]);
+    let missing = mcp.call("get_pack", json!({ "label": "nope" })).await?;
+    assert_eq!(missing["ok"], json!(false), "{}", missing);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_and_delete_checkpoints() -> Result<()> {
+    // Checkpoints live under $HOME
+    let home = tempfile::tempdir()?;
+    let daemon = Daemon::start_with_env(&[("HOME", &home.path().to_string_lossy())]).await?;
+    let mut mcp = daemon.mcp().await?;
+    for (title, trigger) in [("Auth refactor", "manual"), ("Auth refactor", "auto-compact"), ("Billing", "manual")] {
+        let params = json!({ "title": title, "summary": "s", "task": "t", "next_steps": "n", "trigger": trigger });
+        let saved = mcp.call("save_state", params).await?;
+        assert_eq!(saved["ok"], json!(true), "{}", saved);
+    }
+
+    let listed = mcp.call("list_checkpoints", json!({ "title": "Auth refactor" })).await?;
+    assert_eq!(listed["ok"], json!(true), "{}", listed);
+    assert_eq!(listed["result"]["total"], json!(2));
+    let mut versions: Vec<(u64, String)> = listed["result"]["checkpoints"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|checkpoint| {
+            assert_eq!(checkpoint["title"], json!("Auth refactor"));
+            assert!(checkpoint["created_at"].is_string());
+            (checkpoint["version"].as_u64().unwrap_or(0), checkpoint["trigger"].as_str().unwrap_or("").to_string())
+        })
+        .collect();
+    versions.sort();
+    assert_eq!(versions, vec![(1, "manual".to_string()), (2, "auto-compact".to_string())]);
+
+    let deleted = mcp.call("delete_checkpoint", json!({ "title": "Auth refactor", "version": 1 })).await?;
+    assert_eq!(deleted["result"]["deleted"], json!(1), "{}", deleted);
+    let deleted = mcp.call("delete_checkpoint", json!({ "title": "Billing" })).await?;
+    assert_eq!(deleted["result"]["deleted"], json!(1), "{}", deleted);
+    let missing = mcp.call("delete_checkpoint", json!({ "title": "Billing" })).await?;
+    assert_eq!(missing["ok"], json!(false), "{}", missing);
+
+    let listed = mcp.call("list_checkpoints", json!({})).await?;
+    assert_eq!(listed["result"]["total"], json!(1), "{}", listed);
+    assert_eq!(listed["result"]["checkpoints"][0]["version"], json!(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_and_diff_checkpoints() -> Result<()> {
+    let home = tempfile::tempdir()?;
+    let daemon = Daemon::start_with_env(&[("HOME", &home.path().to_string_lossy())]).await?;
+    let mut mcp = daemon.mcp().await?;
+    let saves = [
+        ("Parser", "- Write tokenizer\n- Add tests"),
+        ("Parser", "- Add tests\n- Wire into CLI"),
+        ("Docs", "- Document tokenizer flags"),
+    ];
+    for (title, next_steps) in saves {
+        let params = json!({ "title": title, "summary": "s", "task": "t", "next_steps": next_steps });
+        let saved = mcp.call("save_state", params).await?;
+        assert_eq!(saved["ok"], json!(true), "{}", saved);
+    }
+
+    let found = mcp.call("search_checkpoints", json!({ "query": "tokenizer" })).await?;
+    assert_eq!(found["ok"], json!(true), "{}", found);
+    let mut hits: Vec<String> = found["result"]["checkpoints"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|hit| {
+            let (title, snippet) = (hit["title"].as_str().unwrap_or(""), hit["snippet"].as_str().unwrap_or(""));
+            format!("{} v{}: {}", title, hit["version"], snippet)
+        })
+        .collect();
+    hits.sort();
+    assert_eq!(hits, vec!["Docs v1: - Document tokenizer flags", "Parser v1: - Write tokenizer"]);
+
+    let diff = mcp.call("diff_checkpoints", json!({ "title": "Parser" })).await?;
+    assert_eq!(diff["ok"], json!(true), "{}", diff);
+    assert_eq!((diff["result"]["from"].clone(), diff["result"]["to"].clone()), (json!(1), json!(2)));
+    assert_eq!(
+        diff["result"]["sections"],
+        json!([{ "section": "Next Steps", "removed": ["- Write tokenizer"], "added": ["- Wire into CLI"] }])
+    );
+    let single = mcp.call("diff_checkpoints", json!({ "title": "Docs" })).await?;
+    assert_eq!(single["ok"], json!(false), "{}", single);
+    Ok(())
+}
+
+/// Index a short Slack thread for `summarize_thread`.
+async fn ingest_thread(daemon: &Daemon) -> Result<()> {
+    let doc = Document {
+        id: None,
+        uri: "slack://C1/123".to_string(),
+        source: "slack".to_string(),
+        title: None,
+        body: "# Deploy freeze\n\n- [1.0] alice: Should we freeze deploys Friday?\n- [2.0] bob: I'll post the schedule."
+            .to_string(),
+        updated_at: chrono::Utc::now(),
+    };
+    daemon.ingest().await?.upsert_document(&doc).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_summarize_thread() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    ingest_thread(&daemon).await?;
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("summarize_thread", json!({ "uri": "slack://C1/123" })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let summary = &response["result"];
+    assert_eq!(summary["mode"], json!("extractive"));
+    assert_eq!(summary["live"], json!(false));
+    assert_eq!(summary["title"], json!("Deploy freeze"));
+    assert_eq!(summary["participants"], json!(["alice", "bob"]));
+    assert_eq!(summary["messages"], json!(2));
+    assert_eq!(summary["open_questions"], json!(["Should we freeze deploys Friday?"]));
+    assert_eq!(summary["action_items"], json!(["I'll post the schedule."]));
+    let missing = mcp.call("summarize_thread", json!({ "uri": "slack://C1/999" })).await?;
+    assert_eq!(missing["ok"], json!(false), "{}", missing);
+    Ok(())
+}
+
+/// POST `body` to the daemon's HTTP MCP endpoint and return the status line,
+/// the headers (lowercased) and the body.
+async fn http_post(port: u16, body: &Value, headers: &[(&str, &str)]) -> Result<(String, String, String)> {
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port)).await?;
+    let body = body.to_string();
+    let mut request = format!("POST /mcp HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: {}\r\n", body.len());
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str(&format!("\r\n{}", body));
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    timeout(REQUEST_TIMEOUT, tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut response))
+        .await
+        .map_err(|_| anyhow!("HTTP request timed out"))??;
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow!("no HTTP head: {}", response))?;
+    let (status, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+    Ok((status.to_string(), headers.to_ascii_lowercase(), body.to_string()))
+}
+
+#[tokio::test]
+async fn test_mcp_http_transport() -> Result<()> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let _daemon = Daemon::start_with_env(&[
+        ("MINNA_MCP_HTTP_PORT", &port.to_string()),
+        ("MINNA_MCP_HTTP_ORIGINS", "https://agent.example.com"),
+    ])
+    .await?;
+
+    let init = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2025-06-18" } });
+    let (status, headers, body) = http_post(port, &init, &[("Content-Type", "application/json")]).await?;
+    assert!(status.ends_with("200 OK"), "{}", status);
+    assert!(headers.contains("content-type: application/json"), "{}", headers);
+    let response: Value = serde_json::from_str(&body)?;
+    assert_eq!(response["result"]["serverInfo"]["name"], json!("minna"));
+
+    let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    let (status, _, _) = http_post(port, &notification, &[]).await?;
+    assert!(status.ends_with("202 Accepted"), "{}", status);
+
+    // Bespoke requests stream their responses as Server-Sent Events
+    let request = json!({ "id": "h1", "tool": "get_context", "params": { "query": "anything" } });
+    let (status, headers, body) = http_post(port, &request, &[("Accept", "text/event-stream")]).await?;
+    assert!(status.ends_with("200 OK"), "{}", status);
+    assert!(headers.contains("content-type: text/event-stream"), "{}", headers);
+    let event = body.strip_prefix("data: ").ok_or_else(|| anyhow!("not an event: {}", body))?;
+    let response: Value = serde_json::from_str(event.trim())?;
+    assert_eq!((response["id"].clone(), response["ok"].clone()), (json!("h1"), json!(true)));
+
+    // Browsers only from localhost and the configured origins
+    let (status, headers, _) = http_post(port, &init, &[("Origin", "https://agent.example.com")]).await?;
+    assert!(status.ends_with("200 OK"), "{}", status);
+    assert!(headers.contains("access-control-allow-origin: https://agent.example.com"), "{}", headers);
+    let (status, _, _) = http_post(port, &init, &[("Origin", "http://localhost:3000")]).await?;
+    assert!(status.ends_with("200 OK"), "{}", status);
+    let (status, _, _) = http_post(port, &init, &[("Origin", "https://evil.example.com")]).await?;
+    assert!(status.ends_with("403 Forbidden"), "{}", status);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_profile_has_its_own_data_dir() -> Result<()> {
+    let daemon = Daemon::start_in_profile(Some("work")).await?;
+    let root = daemon.data_dir.parent().and_then(Path::parent).expect("profile root");
+    assert!(daemon.data_dir.join("admin.sock").exists());
+    assert!(!root.join("admin.sock").exists());
+
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+    assert!(daemon.data_dir.join("minna.db").exists());
+    assert!(!root.join("minna.db").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_source_limit_evicts_oldest() -> Result<()> {
+    let config = format!("{}\n[limits.mock]\nmax_documents = 2\n", PROVIDERS_TOML);
+    let daemon = Daemon::start_with(None, &config).await?;
+    let mut admin = daemon.admin().await?;
+
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    // Mock document 3 is the oldest-updated
+    let ingest = daemon.ingest().await?;
+    assert_eq!(mock_count(&ingest.document_counts_by_source().await?), 2);
+    assert!(ingest.get_document_by_uri("mock://doc/3").await?.is_none());
+    assert!(ingest.get_document_by_uri("mock://doc/1").await?.is_some());
+    assert_eq!(ingest.tombstone_count().await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_redaction_before_storage() -> Result<()> {
+    let config = format!(
+        "{}\n[[redaction.patterns]]\nname = \"rollout\"\nregex = \"canary\"\n",
+        PROVIDERS_TOML
+    );
+    let daemon = Daemon::start_with(None, &config).await?;
+    let mut admin = daemon.admin().await?;
+
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let ingest = daemon.ingest().await?;
+    let doc = ingest
+        .get_document_by_uri("mock://doc/2")
+        .await?
+        .expect("mock://doc/2 stored");
+    assert!(doc.body.contains("added a [REDACTED:rollout]"), "body: {}", doc.body);
+    assert!(!doc.body.contains("canary"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_near_duplicates_collapse() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    // The incident report cross-posted to another source
+    let ingest = daemon.ingest().await?;
+    let original = ingest
+        .get_document_by_uri("mock://doc/2")
+        .await?
+        .expect("mock://doc/2 stored");
+    let copy = Document {
+        id: None,
+        uri: "mirror://incident/2".to_string(),
+        source: "mirror".to_string(),
+        title: original.title.clone(),
+        body: original.body.clone(),
+        updated_at: original.updated_at,
+    };
+    let copy_id = ingest.upsert_document(&copy).await?;
+    let canonical = ingest.canonical_ids(&[copy_id]).await?;
+    assert_eq!(canonical.get(&copy_id), original.id.as_ref());
+
+    let mut mcp = daemon.mcp().await?;
+    let context = mcp
+        .call("get_context", json!({ "query": "search latency incident" }))
+        .await?;
+    let items = context["result"]["items"].as_array().cloned().unwrap_or_default();
+    let uris: Vec<&Value> = items.iter().map(|item| &item["uri"]).collect();
+    assert!(!uris.contains(&&json!("mirror://incident/2")), "alternate returned: {:?}", uris);
+    let hit = items
+        .iter()
+        .find(|item| item["uri"] == json!("mock://doc/2"))
+        .unwrap_or_else(|| panic!("expected mock://doc/2 in {:?}", items));
+    assert_eq!(hit["alternates"], json!(["mirror://incident/2"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_long_documents_match_by_chunk() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+
+    // The answer sits deep in a long thread, far past the opening
+    let filler = "Standup notes: reviewed the sprint board and moved tickets along.\n\n".repeat(100);
+    let answer = "The rollback runbook lives in the operations wiki under incidents. ".repeat(20);
+    let body = format!("{}{}", filler, answer);
+    let doc = Document {
+        id: None,
+        uri: "mirror://thread/long".to_string(),
+        source: "mirror".to_string(),
+        title: Some("Team thread".to_string()),
+        body,
+        updated_at: chrono::Utc::now(),
+    };
+    let id = ingest.upsert_document(&doc).await?;
+    embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+
+    let query = embedder.embed("rollback runbook operations wiki incidents").await?;
+    let whole = vector.get_embedding(id).await?.expect("document embedded");
+    let whole_score: f32 = whole.iter().zip(&query).map(|(a, b)| a * b).sum();
+    let hits = vector.search_with_embedding(&query, 5).await?;
+    let (hit_id, score) = hits.first().copied().expect("a semantic hit");
+    assert_eq!(hit_id, id);
+    assert!(score > whole_score + 0.3, "chunk score {} vs whole {}", score, whole_score);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filtered_semantic_search() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+
+    // Many close matches from one source crowd out the only one from another
+    let mut ids = HashMap::new();
+    for (n, source) in ["slack"; 12].into_iter().chain(["jira"]).enumerate() {
+        let doc = Document {
+            id: None,
+            uri: format!("{}://deploy/{}", source, n),
+            source: source.to_string(),
+            title: Some(format!("Deploy note {}", n)),
+            body: if source == "jira" {
+                "payments deploy freeze tracked on the change calendar".to_string()
+            } else {
+                format!("deploy freeze for the payments service, note {}", n)
+            },
+            updated_at: chrono::Utc::now() - chrono::Duration::days(n as i64),
+        };
+        let id = ingest.upsert_document(&doc).await?;
+        embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+        ids.insert(id, source);
+    }
+    let query = embedder.embed("deploy freeze for the payments service").await?;
+    let unfiltered = vector.search_with_embedding(&query, 3).await?;
+    assert!(unfiltered.iter().all(|(id, _)| ids[id] == "slack"), "hits: {:?}", unfiltered);
+
+    let filter = SearchFilter {
+        sources: vec!["Jira".to_string()],
+        ..SearchFilter::default()
+    };
+    let hits = vector.search_with_embedding_filtered(&query, 3, &filter).await?;
+    assert_eq!(hits.len(), 1, "hits: {:?}", hits);
+    assert_eq!(ids[&hits[0].0], "jira");
+
+    let filter = SearchFilter {
+        excluded_sources: vec!["jira".to_string()],
+        updated_after: Some(chrono::Utc::now() - chrono::Duration::hours(36)),
+        ..SearchFilter::default()
+    };
+    let hits = vector.search_semantic_filtered(&embedder, "deploy freeze", 10, &filter).await?;
+    assert_eq!(hits.len(), 2, "hits: {:?}", hits);
+
+    let (pack, _) = unfiltered[0];
+    let filter = SearchFilter {
+        doc_ids: Some(vec![pack]),
+        ..SearchFilter::default()
+    };
+    let hits = vector.search_with_embedding_filtered(&query, 3, &filter).await?;
+    assert_eq!(hits.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![pack]);
+    Ok(())
+}
+
+/// URIs of the documents `get_context` returns for `params`, sorted.
+async fn context_uris(mcp: &mut Client, params: Value) -> Result<Vec<String>> {
+    let response = mcp.call("get_context", params).await?;
+    let mut uris: Vec<String> = response["result"]["items"]
+        .as_array()
+        .ok_or_else(|| anyhow!("no items: {}", response))?
+        .iter()
+        .filter_map(|item| item["uri"].as_str().map(String::from))
+        .collect();
+    uris.sort();
+    Ok(uris)
+}
+
+#[tokio::test]
+async fn test_get_context_structured_filters() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+    for (uri, source, days) in [
+        ("slack://C1/1", "slack", 1),
+        ("slack://C1/2", "slack", 20),
+        ("slack://C2/3", "slack", 2),
+        ("jira://PAY-1", "jira", 1),
+    ] {
+        let doc = Document {
+            id: None,
+            uri: uri.to_string(),
+            source: source.to_string(),
+            title: None,
+            body: "deploy freeze for the payments service".to_string(),
+            updated_at: chrono::Utc::now() - chrono::Duration::days(days),
+        };
+        let id = ingest.upsert_document(&doc).await?;
+        embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+    }
+
+    let mut mcp = daemon.mcp().await?;
+    let week_ago = (chrono::Utc::now() - chrono::Duration::days(7)).to_rfc3339();
+    let recent_slack = context_uris(&mut mcp, json!({ "query": "deploy freeze", "sources": ["Slack"], "after": week_ago })).await?;
+    assert_eq!(recent_slack, vec!["slack://C1/1", "slack://C2/3"]);
+    let channel = context_uris(&mut mcp, json!({ "query": "deploy freeze", "uri_prefix": "slack://C1/" })).await?;
+    assert_eq!(channel, vec!["slack://C1/1", "slack://C1/2"]);
+    let older = context_uris(&mut mcp, json!({ "query": "deploy freeze", "before": week_ago })).await?;
+    assert_eq!(older, vec!["slack://C1/2"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_context_cursor() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+    let topics = ["billing", "search", "login", "exports", "alerts", "onboarding", "invoices", "webhooks", "mobile"];
+    for (n, topic) in topics.iter().enumerate() {
+        let doc = Document {
+            id: None,
+            uri: format!("mock://retro/{}", n),
+            source: "mock".to_string(),
+            title: Some(format!("Retro {}", n)),
+            body: format!("retrospective notes on the {} project, week {}", topic, n),
+            updated_at: chrono::Utc::now(),
+        };
+        let id = ingest.upsert_document(&doc).await?;
+        embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor = Value::Null;
+    let mut pages = 0;
+    loop {
+        // A new connection per page: cursors outlive the one that made them
+        let mut mcp = daemon.mcp().await?;
+        let params = json!({ "query": "retrospective", "limit": 4, "cursor": cursor });
+        let response = mcp.call("get_context", params).await?;
+        assert_eq!(response["ok"], json!(true), "{}", response);
+        assert_eq!(response["result"]["mode"], json!("hybrid"), "{}", response);
+        let items = response["result"]["items"].as_array().cloned().unwrap_or_default();
+        assert!(!items.is_empty() && items.len() <= 4, "{}", response);
+        seen.extend(items.iter().filter_map(|item| item["uri"].as_str().map(String::from)));
+        pages += 1;
+        cursor = response["result"]["next_cursor"].clone();
+        if cursor.is_null() {
+            break;
+        }
+    }
+    assert_eq!(pages, 3);
+    seen.sort();
+    let expected: Vec<String> = (0..topics.len()).map(|n| format!("mock://retro/{}", n)).collect();
+    assert_eq!(seen, expected);
+
+    let mut mcp = daemon.mcp().await?;
+    let first = mcp.call("get_context", json!({ "query": "retrospective", "limit": 4 })).await?;
+    let cursor = first["result"]["next_cursor"].clone();
+    let other = mcp.call("get_context", json!({ "query": "billing", "cursor": cursor })).await?;
+    assert_eq!(other["ok"], json!(false), "{}", other);
+    let invalid = mcp.call("get_context", json!({ "query": "retrospective", "cursor": "nope" })).await?;
+    assert_eq!(invalid["ok"], json!(false), "{}", invalid);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trending_topics() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+
+    // Offsite planning comes up every week; the kafka incident is new
+    let now = chrono::Utc::now();
+    let mut docs = Vec::new();
+    for week in 0..8i64 {
+        let count = if week == 0 { 3 } else { 2 };
+        for n in 0..count {
+            let body = format!("quarterly planning offsite agenda and travel, week {} item {}", week, n);
+            docs.push((body, now - chrono::Duration::days(week * 7 + 1)));
+        }
+    }
+    for n in 0..4 {
+        let body = format!("kafka consumer lag alert on the billing cluster, page {}", n);
+        docs.push((body, now - chrono::Duration::hours(n + 1)));
+    }
+    for (n, (body, updated_at)) in docs.into_iter().enumerate() {
+        let doc = Document {
+            id: None,
+            uri: format!("mirror://trend/{}", n),
+            source: "mirror".to_string(),
+            title: None,
+            body,
+            updated_at,
+        };
+        let id = ingest.upsert_document(&doc).await?;
+        embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+    }
+
+    let mut admin = daemon.admin().await?;
+    let trends = admin.call("trending_topics", json!({ "weeks": 8, "min_similarity": 0.8 })).await?;
+    assert_eq!(trends["ok"], json!(true), "trending_topics failed: {}", trends);
+    let result = &trends["result"];
+    assert_eq!(result["weeks"].as_array().map(|weeks| weeks.len()), Some(8));
+    let topics = result["topics"].as_array().cloned().unwrap_or_default();
+    assert_eq!(topics.len(), 1, "topics: {:?}", topics);
+    assert_eq!(topics[0]["label"], json!("alert-billing-cluster"));
+    assert_eq!(topics[0]["weekly"], json!([0, 0, 0, 0, 0, 0, 0, 4]));
+    assert_eq!(topics[0]["doc_ids"].as_array().map(|ids| ids.len()), Some(4));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_related() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let ingest = daemon.ingest().await?;
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedder = HashEmbedder::default();
+    let graph = GraphStore::new(ingest.pool().clone());
+
+    let mut ids = HashMap::new();
+    for (uri, body) in [
+        ("github://acme/api/pull/7", "Retry failed webhook deliveries with backoff"),
+        ("linear://ENG-1", "Webhooks are dropped when the receiver times out"),
+        ("slack://C1/1", "recipe for banana bread"),
+        ("github://acme/api/pull/8", "Retry failed webhook deliveries with backoff and jitter"),
+    ] {
+        let doc = Document {
+            id: None,
+            uri: uri.to_string(),
+            source: uri.split(':').next().unwrap_or_default().to_string(),
+            title: None,
+            body: body.to_string(),
+            updated_at: chrono::Utc::now(),
+        };
+        let id = ingest.upsert_document(&doc).await?;
+        embed_document(&embedder, &vector, id, &doc.source, &doc.body).await?;
+        ids.insert(uri, id);
+    }
+    let now = chrono::Utc::now();
+    let pr = NodeRef::pull_request("github", "acme/api#7");
+    let issue = NodeRef::issue("linear", "ENG-1");
+    let alice = NodeRef::user("github", "alice");
+    // Sharing a person alone doesn't relate documents
+    let edges = [
+        (ExtractedEdge::new(pr.clone(), issue.clone(), Relation::MentionedIn, now), "github://acme/api/pull/7"),
+        (ExtractedEdge::new(alice.clone(), pr, Relation::AuthorOf, now), "github://acme/api/pull/7"),
+        (ExtractedEdge::new(alice.clone(), issue, Relation::AssignedTo, now), "linear://ENG-1"),
+        (ExtractedEdge::new(alice, NodeRef::issue("linear", "ENG-2"), Relation::MentionedIn, now), "slack://C1/1"),
+    ];
+    for (edge, uri) in &edges {
+        graph.upsert_edge_from_document(edge, ids[uri]).await?;
+    }
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("get_related", json!({ "uri": "github://acme/api/pull/7" })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let uris = |list: &str| -> Vec<String> {
+        response["result"][list]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item["uri"].as_str().map(str::to_string))
+            .collect()
+    };
+    assert_eq!(uris("connected"), vec!["linear://ENG-1"]);
+    assert_eq!(uris("similar"), vec!["github://acme/api/pull/8"]);
+
+    let missing = mcp.call("get_related", json!({ "uri": "github://nope" })).await?;
+    assert_eq!(missing["ok"], json!(false), "{}", missing);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_who_do_i_work_with() -> Result<()> {
+    let providers = format!("{}\n[rings]\nuser = \"user:slack:ME\"\n", PROVIDERS_TOML);
+    let daemon = Daemon::start_with(None, &providers).await?;
+    let ingest = daemon.ingest().await?;
+    let graph = GraphStore::new(ingest.pool().clone());
+
+    let now = chrono::Utc::now();
+    let dm = NodeRef::channel("slack", "D1");
+    let general = NodeRef::channel("slack", "C1");
+    for (user, channel, weight) in [("ME", &dm, 2.0), ("BOB", &dm, 2.0), ("ME", &general, 1.0), ("ALICE", &general, 1.0)] {
+        let edge = ExtractedEdge::new(NodeRef::user("slack", user), channel.clone(), Relation::MemberOf, now);
+        graph.upsert_edge(&edge.with_weight(weight)).await?;
+    }
+
+    // Before rings exist the MCP tool has no user to start from
+    let mut mcp = daemon.mcp().await?;
+    let early = mcp.call("who_do_i_work_with", json!({})).await?;
+    assert_eq!(early["ok"], json!(false), "{}", early);
+
+    RingEngine::new().recalculate_rings(&graph, "user:slack:ME").await?;
+    let people = |result: &Value| -> Vec<String> {
+        result["result"]["groups"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|group| group["people"].as_array().cloned().unwrap_or_default())
+            .filter_map(|person| person["node_id"].as_str().map(str::to_string))
+            .collect()
+    };
+    let response = mcp.call("who_do_i_work_with", json!({ "limit": 5, "days": 7 })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    assert_eq!(people(&response), vec!["user:slack:BOB", "user:slack:ALICE"]);
+
+    let mut admin = daemon.admin().await?;
+    let response = admin.call("collaborators", json!({ "limit": 1 })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    assert_eq!(people(&response), vec!["user:slack:BOB"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_who_is() -> Result<()> {
+    let providers = format!("{}\n[rings]\nuser = \"user:slack:ME\"\n", PROVIDERS_TOML);
+    let daemon = Daemon::start_with(None, &providers).await?;
+    let ingest = daemon.ingest().await?;
+    let graph = GraphStore::new(ingest.pool().clone());
+
+    let mut ids = HashMap::new();
+    for uri in ["slack://D1/1", "github://acme/api/pull/7"] {
+        let doc = Document {
+            id: None,
+            uri: uri.to_string(),
+            source: uri.split(':').next().unwrap_or_default().to_string(),
+            title: Some(uri.to_string()),
+            body: "body".to_string(),
+            updated_at: chrono::Utc::now(),
+        };
+        ids.insert(uri, ingest.upsert_document(&doc).await?);
+    }
+    let now = chrono::Utc::now();
+    let me = NodeRef::user("slack", "ME");
+    let bob_slack = NodeRef::with_name(NodeType::User, "slack", "U2", "Bob Builder");
+    let bob_github = NodeRef::user("github", "bobb");
+    let pr = NodeRef::pull_request("github", "acme/api#7");
+    let dm = NodeRef::channel("slack", "D1");
+    let edges = [
+        (ExtractedEdge::new(me.clone(), dm.clone(), Relation::MemberOf, now), "slack://D1/1"),
+        (ExtractedEdge::new(bob_slack.clone(), dm, Relation::MemberOf, now), "slack://D1/1"),
+        (ExtractedEdge::new(bob_github.clone(), pr.clone(), Relation::AuthorOf, now), "github://acme/api/pull/7"),
+        (ExtractedEdge::new(me.clone(), pr, Relation::ReviewerOf, now), "github://acme/api/pull/7"),
+    ];
+    for (edge, uri) in &edges {
+        graph.upsert_edge_from_document(edge, ids[uri]).await?;
+    }
+    graph.link_same_as(&bob_slack.canonical_id(), &bob_github.canonical_id(), "manual", 1.0).await?;
+    RingEngine::new().recalculate_rings(&graph, &me.canonical_id()).await?;
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("who_is", json!({ "name_or_handle": "@bobb" })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    let people = response["result"]["people"].as_array().cloned().unwrap_or_default();
+    assert_eq!(people.len(), 1, "{}", response);
+    let bob = &people[0];
+    assert_eq!(bob["display_name"], json!("Bob Builder"));
+    assert_eq!(bob["ring"], json!("One"));
+    let mut providers: Vec<&str> = bob["identities"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|identity| identity["provider"].as_str())
+        .collect();
+    providers.sort();
+    assert_eq!(providers, vec!["github", "slack"]);
+    let mut shared: Vec<&str> = bob["shared"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|artifact| artifact["uri"].as_str())
+        .collect();
+    shared.sort();
+    assert_eq!(shared, vec!["github://acme/api/pull/7", "slack://D1/1"]);
+
+    let nobody = mcp.call("who_is", json!({ "name_or_handle": "carol" })).await?;
+    assert_eq!(nobody["ok"], json!(false), "{}", nobody);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_int8_quantized_embeddings() -> Result<()> {
+    let daemon = Daemon::start_with_env(&[("MINNA_EMBED_QUANTIZE", "int8")]).await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let mut mcp = daemon.mcp().await?;
+    let context = mcp
+        .call("get_context", json!({ "query": "search latency incident" }))
+        .await?;
+    let items = context["result"]["items"].as_array().cloned().unwrap_or_default();
+    assert!(
+        items.iter().any(|item| item["uri"] == json!("mock://doc/2")),
+        "expected mock://doc/2 in {:?}",
+        items
+    );
+
+    // Vectors read back are dequantized, so still unit length
+    let ingest = daemon.ingest().await?;
+    let doc = ingest
+        .get_document_by_uri("mock://doc/2")
+        .await?
+        .expect("mock://doc/2 stored");
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let embedding = vector
+        .get_embedding(doc.id.expect("stored id"))
+        .await?
+        .expect("document embedded");
+    let norm: f32 = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    assert!((norm - 1.0).abs() < 0.05, "norm {}", norm);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reindex_embeddings_after_model_change() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let models = vector.embedding_models().await?;
+    assert_eq!(models.len(), 1, "models: {:?}", models);
+    assert_eq!(models[0].model, "hash-256");
+    assert_eq!(models[0].dims, 256);
+    let documents = models[0].documents;
+
+    // One document left over from a previous model
+    let ingest = daemon.ingest().await?;
+    let doc = ingest
+        .get_document_by_uri("mock://doc/1")
+        .await?
+        .expect("mock://doc/1 stored");
+    vector.record_model(doc.id.expect("stored id"), "OldModel", 256).await?;
+
+    let reindex = admin.call("reindex_embeddings", json!({})).await?;
+    assert_eq!(reindex["ok"], json!(true), "reindex failed: {}", reindex);
+    assert_eq!(reindex["result"]["model"], json!("hash-256"));
+    assert_eq!(reindex["result"]["reembedded"], json!(1));
+    let models = vector.embedding_models().await?;
+    assert_eq!(models.len(), 1, "models: {:?}", models);
+    assert_eq!(models[0].documents, documents);
+
+    let reindex = admin.call("reindex_embeddings", json!({ "all": true })).await?;
+    assert_eq!(reindex["result"]["reembedded"], json!(documents));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reindex_embeddings_of_another_dimension() -> Result<()> {
+    let daemon = Daemon::start().await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    // Recorded as the current model, but stored at a dimension it doesn't produce
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let ingest = daemon.ingest().await?;
+    let id = ingest
+        .get_document_by_uri("mock://doc/1")
+        .await?
+        .and_then(|doc| doc.id)
+        .expect("mock://doc/1 stored");
+    vector.upsert_embedding(id, &[0.125; 64]).await?;
+    assert_eq!(vector.count_dimension_mismatches(256, &HashMap::new()).await?, 1);
+
+    let reindex = admin.call("reindex_embeddings", json!({})).await?;
+    assert_eq!(reindex["ok"], json!(true), "reindex failed: {}", reindex);
+    assert_eq!(reindex["result"]["reembedded"], json!(1));
+    assert_eq!(vector.get_embedding(id).await?.map(|e| e.len()), Some(256));
+    assert_eq!(vector.count_dimension_mismatches(256, &HashMap::new()).await?, 0);
+
+    let status = admin.call("get_status", json!({})).await?;
+    let embeddings = &status["result"]["embeddings"];
+    assert_eq!(embeddings["model"], json!("hash-256"), "status: {}", status);
+    assert_eq!(embeddings["dims"], json!(256));
+    assert_eq!(embeddings["mismatched"], json!(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_per_source_embedding_models() -> Result<()> {
+    let providers_toml = format!("{}\n[embedding_models]\nmock = \"hash-128\"\n", PROVIDERS_TOML);
+    let daemon = Daemon::start_with(None, &providers_toml).await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let models = vector.embedding_models().await?;
+    assert_eq!(models.len(), 1, "models: {:?}", models);
+    assert_eq!(models[0].model, "hash-128");
+    assert_eq!(models[0].dims, 128);
+
+    // Another document in the default space
+    let ingest = daemon.ingest().await?;
+    let default = Arc::new(HashEmbedder::default());
+    let doc = Document {
+        id: None,
+        uri: "mirror://notes/latency".to_string(),
+        source: "mirror".to_string(),
+        title: Some("Notes".to_string()),
+        body: "search latency incident follow-up notes".to_string(),
+        updated_at: chrono::Utc::now(),
+    };
+    let id = ingest.upsert_document(&doc).await?;
+    let embedders = SourceEmbedders::new(
+        default,
+        HashMap::from([("mock".to_string(), Arc::new(HashEmbedder { dims: 128 }) as Arc<dyn Embedder>)]),
+    );
+    embed_document(&embedders, &vector, id, &doc.source, &doc.body).await?;
+
+    // Each space is searched with its own query embedding
+    let hits = vector.search_semantic(&embedders, "search latency incident", 10).await?;
+    let mock_doc = ingest
+        .get_document_by_uri("mock://doc/2")
+        .await?
+        .and_then(|doc| doc.id)
+        .expect("mock://doc/2 stored");
+    let ids: Vec<i64> = hits.iter().map(|hit| hit.0).collect();
+    assert!(ids.contains(&id), "hits: {:?}", hits);
+    assert!(ids.contains(&mock_doc), "hits: {:?}", hits);
+
+    let reindex = admin.call("reindex_embeddings", json!({})).await?;
+    assert_eq!(reindex["result"]["reembedded"], json!(0), "reindex: {}", reindex);
+    Ok(())
+}
+
+/// Serve embeddings like an OpenAI-compatible API (`/v1/embeddings`) or
+/// Ollama (`/api/embed`): each input becomes an 8-dimensional vector counting
+/// its words by length. `/v1/chat/completions` always answers with the same
+/// summary. Returns the base URL and the Authorization headers received.
+async fn fake_model_api() -> Result<(String, Arc<std::sync::Mutex<Vec<String>>>)> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://{}", listener.local_addr()?);
+    let auth = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = auth.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let seen = seen.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut reader = BufReader::new(reader);
+                let mut content_length = 0;
+                let mut request_line = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if request_line.is_empty() {
+                        request_line = line.to_string();
+                    }
+                    let lower = line.to_ascii_lowercase();
+                    if let Some(value) = lower.strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    if lower.starts_with("authorization:") {
+                        seen.lock().unwrap().push(line["authorization:".len()..].trim().to_string());
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                if tokio::io::AsyncReadExt::read_exact(&mut reader, &mut body).await.is_err() {
+                    return;
+                }
+                let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+                let mut embedding = [0.0f32; 8];
+                for word in request["input"].as_str().unwrap_or_default().split_whitespace() {
+                    embedding[word.len() % 8] += 1.0;
+                }
+                // Ollama's native API next to the OpenAI-compatible one
+                let response = if request_line.contains("/chat/completions") {
+                    json!({
+                        "choices": [{
+                            "index": 0,
+                            "message": {
+                                "role": "assistant",
+                                "content": "Mock sync notes\nDocuments from the mock provider.",
+                            },
+                        }],
+                    })
+                } else if request_line.contains(" /api/embed ") {
+                    json!({ "model": request["model"], "embeddings": [embedding] })
+                } else {
+                    json!({
+                        "data": [{ "embedding": embedding, "index": 0 }],
+                        "model": request["model"],
+                    })
+                }
+                .to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+                let _ = writer.write_all(reply.as_bytes()).await;
+            });
+        }
+    });
+    Ok((base_url, auth))
+}
+
+#[tokio::test]
+async fn test_remote_embedder_backend() -> Result<()> {
+    let (base_url, auth) = fake_model_api().await?;
+    let daemon = Daemon::start_with_env(&[
+        ("MINNA_EMBED_BACKEND", "remote"),
+        ("MINNA_EMBED_REMOTE_URL", &format!("{}/v1", base_url)),
+        ("MINNA_EMBED_MODEL", "test-embedding-small"),
+        ("MINNA_EMBED_API_KEY", "sk-test"),
+    ])
+    .await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let models = vector.embedding_models().await?;
+    assert_eq!(models.len(), 1, "models: {:?}", models);
+    assert_eq!(models[0].model, "test-embedding-small");
+    assert_eq!(models[0].dims, 8);
+    let auth = auth.lock().unwrap().clone();
+    assert!(!auth.is_empty());
+    assert!(auth.iter().all(|header| header == "Bearer sk-test"), "auth: {:?}", auth);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cluster_summaries() -> Result<()> {
+    let (base_url, _) = fake_model_api().await?;
+    let daemon = Daemon::start_with_env(&[
+        ("MINNA_ENABLE_CLUSTERING", "1"),
+        ("MINNA_CLUSTER_MIN_SIMILARITY", "0"),
+        ("MINNA_CLUSTER_MIN_POINTS", "2"),
+        ("MINNA_CLUSTER_INTERVAL_SECS", "1"),
+        ("MINNA_CLUSTER_FULL_INTERVAL_SECS", "1"),
+        ("MINNA_CLUSTER_SUMMARY_MODEL", "test-chat"),
+        ("MINNA_CLUSTER_SUMMARY_URL", &format!("{}/v1", base_url)),
+    ])
+    .await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let ingest = daemon.ingest().await?;
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    loop {
+        let clusters = ingest.list_clusters(10).await?;
+        if let Some(cluster) = clusters.iter().find(|cluster| cluster.summary.is_some()) {
+            assert_eq!(
+                cluster.summary.as_deref(),
+                Some("Mock sync notes\nDocuments from the mock provider.")
+            );
+            assert!(!cluster.label.starts_with("Cluster "), "label: {}", cluster.label);
+            break;
+        }
+        assert!(Instant::now() < deadline, "no summarized cluster: {:?}", clusters);
+        sleep(Duration::from_millis(200)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_summarize_thread_with_model() -> Result<()> {
+    let (base_url, _) = fake_model_api().await?;
+    let daemon = Daemon::start_with_env(&[
+        ("MINNA_CLUSTER_SUMMARY_MODEL", "test-chat"),
+        ("MINNA_CLUSTER_SUMMARY_URL", &format!("{}/v1", base_url)),
+    ])
+    .await?;
+    ingest_thread(&daemon).await?;
+
+    let mut mcp = daemon.mcp().await?;
+    let response = mcp.call("summarize_thread", json!({ "uri": "slack://C1/123" })).await?;
+    assert_eq!(response["ok"], json!(true), "{}", response);
+    assert_eq!(response["result"]["mode"], json!("llm"));
+    assert_eq!(response["result"]["summary"], json!("Mock sync notes\nDocuments from the mock provider."));
+    assert_eq!(response["result"]["participants"], json!(["alice", "bob"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ollama_embedder_backend() -> Result<()> {
+    let (base_url, auth) = fake_model_api().await?;
+    let daemon = Daemon::start_with_env(&[
+        ("MINNA_EMBED_BACKEND", "ollama"),
+        ("MINNA_OLLAMA_URL", &base_url),
+        ("MINNA_EMBED_MODEL", "all-minilm"),
+    ])
+    .await?;
+    let mut admin = daemon.admin().await?;
+    let sync = admin.call("sync_provider", json!({ "provider": "mock" })).await?;
+    assert_eq!(sync["ok"], json!(true), "sync failed: {}", sync);
+
+    let vector = VectorStore::new(&daemon.data_dir.join("minna.db")).await?;
+    let models = vector.embedding_models().await?;
+    assert_eq!(models.len(), 1, "models: {:?}", models);
+    assert_eq!(models[0].model, "all-minilm");
+    assert_eq!(models[0].dims, 8);
+    assert!(auth.lock().unwrap().is_empty());
+    Ok(())
+}
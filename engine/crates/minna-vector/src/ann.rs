@@ -0,0 +1,192 @@
+//! Approximate nearest-neighbour search over the stored embeddings.
+//!
+//! Small corpora are searched exactly. Once a database holds
+//! `MINNA_ANN_MIN_DOCUMENTS` embedded documents (20,000 by default), the
+//! first search builds an [`Hnsw`] graph per dimension in the background,
+//! and later searches use it. Upserts and deletes keep it current; an upsert
+//! made while it builds is replayed once it's done. `MINNA_ANN=0` turns it
+//! off.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::hnsw::Hnsw;
+
+const DEFAULT_MIN_DOCUMENTS: usize = 20_000;
+
+/// How long a corpus found too small waits before it's counted again.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+pub(crate) enum Op {
+    Insert {
+        doc_id: i64,
+        chunk: bool,
+        vector: Vec<f32>,
+    },
+    Remove {
+        doc_id: i64,
+        chunk: bool,
+    },
+}
+
+enum State {
+    Idle { checked_at: Option<Instant> },
+    Building { pending: Vec<Op> },
+    Ready(HashMap<usize, Hnsw>),
+}
+
+pub(crate) struct AnnIndex {
+    /// None when disabled.
+    min_documents: Option<usize>,
+    state: RwLock<State>,
+}
+
+impl AnnIndex {
+    pub(crate) fn from_env() -> Self {
+        let enabled = std::env::var("MINNA_ANN").map(|v| v != "0").unwrap_or(true);
+        let min_documents = std::env::var("MINNA_ANN_MIN_DOCUMENTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MIN_DOCUMENTS);
+        Self::new(enabled.then_some(min_documents))
+    }
+
+    pub(crate) fn new(min_documents: Option<usize>) -> Self {
+        Self {
+            min_documents,
+            state: RwLock::new(State::Idle { checked_at: None }),
+        }
+    }
+
+    pub(crate) fn min_documents(&self) -> Option<usize> {
+        self.min_documents
+    }
+
+    /// Nearest vectors from the graph for `query`'s dimension, or None if
+    /// there isn't one ready.
+    pub(crate) fn search(&self, query: &[f32], k: usize) -> Option<Vec<(i64, f32)>> {
+        let state = self.state.read().ok()?;
+        match &*state {
+            State::Ready(indexes) => indexes
+                .get(&query.len())
+                .map(|index| index.search(query, k)),
+            _ => None,
+        }
+    }
+
+    /// Live vectors across the graphs, or None if there isn't one ready.
+    pub(crate) fn len(&self) -> Option<usize> {
+        let state = self.state.read().ok()?;
+        match &*state {
+            State::Ready(indexes) => Some(indexes.values().map(Hnsw::len).sum()),
+            _ => None,
+        }
+    }
+
+    /// Whether the caller should build the graph now. Marks it as building,
+    /// so only one caller gets true.
+    pub(crate) fn start_build(&self) -> bool {
+        if self.min_documents.is_none() {
+            return false;
+        }
+        let Ok(mut state) = self.state.write() else {
+            return false;
+        };
+        let due = match &*state {
+            State::Idle { checked_at } => {
+                checked_at.is_none_or(|at| at.elapsed() >= RECHECK_INTERVAL)
+            }
+            _ => false,
+        };
+        if due {
+            *state = State::Building {
+                pending: Vec::new(),
+            };
+        }
+        due
+    }
+
+    /// Install a built graph, replaying changes made while it was built.
+    /// Dropped if the index was reset in the meantime.
+    pub(crate) fn finish_build(&self, mut indexes: HashMap<usize, Hnsw>) {
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+        let State::Building { pending } = &mut *state else {
+            return;
+        };
+        for op in pending.drain(..) {
+            apply(&mut indexes, op);
+        }
+        *state = State::Ready(indexes);
+    }
+
+    /// Give up on a build (corpus too small, or it failed) until the next
+    /// recheck.
+    pub(crate) fn abandon_build(&self) {
+        if let Ok(mut state) = self.state.write() {
+            if matches!(*state, State::Building { .. }) {
+                *state = State::Idle {
+                    checked_at: Some(Instant::now()),
+                };
+            }
+        }
+    }
+
+    pub(crate) fn apply(&self, op: Op) {
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+        match &mut *state {
+            State::Idle { .. } => {}
+            State::Building { pending } => pending.push(op),
+            State::Ready(indexes) => {
+                apply(indexes, op);
+                if indexes.values().any(Hnsw::needs_rebuild) {
+                    *state = State::Idle { checked_at: None };
+                }
+            }
+        }
+    }
+
+    /// Drop the graph; the next search rebuilds it.
+    pub(crate) fn reset(&self) {
+        if let Ok(mut state) = self.state.write() {
+            *state = State::Idle { checked_at: None };
+        }
+    }
+}
+
+fn apply(indexes: &mut HashMap<usize, Hnsw>, op: Op) {
+    match op {
+        Op::Insert {
+            doc_id,
+            chunk,
+            vector,
+        } => indexes
+            .entry(vector.len())
+            .or_insert_with(|| Hnsw::new(vector.len()))
+            .insert(doc_id, chunk, &vector),
+        Op::Remove { doc_id, chunk } => {
+            for index in indexes.values_mut() {
+                index.remove(doc_id, chunk);
+            }
+        }
+    }
+}
+
+/// Build a graph per dimension from `(doc_id, chunk, vector)` rows.
+pub(crate) fn build(vectors: Vec<(i64, bool, Vec<f32>)>) -> HashMap<usize, Hnsw> {
+    let mut indexes: HashMap<usize, Hnsw> = HashMap::new();
+    for (doc_id, chunk, vector) in vectors {
+        if vector.is_empty() {
+            continue;
+        }
+        indexes
+            .entry(vector.len())
+            .or_insert_with(|| Hnsw::new(vector.len()))
+            .insert(doc_id, chunk, &vector);
+    }
+    indexes
+}
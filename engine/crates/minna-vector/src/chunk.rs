@@ -0,0 +1,89 @@
+//! Splitting long documents into chunks that are embedded separately.
+//!
+//! A single vector for a 200 KB Drive document or a long Slack thread only
+//! reflects its opening (the embedding model reads about 512 tokens), so
+//! semantic search misses anything further in. Documents longer than one
+//! chunk also get a vector per chunk; search scores a document by its best
+//! chunk (see [`crate::VectorStore::search_with_embedding`]).
+
+use anyhow::Result;
+
+use crate::{embedder_for_source, Embedder, VectorIndex};
+
+/// Target chunk size in bytes, roughly what the default model reads.
+pub const CHUNK_BYTES: usize = 2000;
+
+/// Text beyond this many chunks isn't embedded.
+pub const MAX_CHUNKS: usize = 256;
+
+/// Split `text` into chunks of at most [`CHUNK_BYTES`], preferring paragraph,
+/// then line, then sentence, then word boundaries.
+pub fn chunk_text(text: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() && chunks.len() < MAX_CHUNKS {
+        if rest.len() <= CHUNK_BYTES {
+            chunks.push(rest);
+            break;
+        }
+        let mut end = CHUNK_BYTES;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &rest[..end];
+        // A boundary in the first half would make a needlessly small chunk
+        let cut = window
+            .rfind("\n\n")
+            .or_else(|| window.rfind('\n'))
+            .or_else(|| window.rfind(". ").map(|i| i + 1))
+            .or_else(|| window.rfind(' '))
+            .filter(|i| *i >= CHUNK_BYTES / 2)
+            .unwrap_or(end);
+        let chunk = rest[..cut].trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        rest = rest[cut..].trim_start();
+    }
+    chunks
+}
+
+/// Embed a stored document: its whole body, plus each chunk when the body
+/// spans more than one, and record the model. `source` picks the model when
+/// `embedder` has one per source. Queues the document for re-embedding when
+/// the model is still the fallback.
+pub async fn embed_document(
+    embedder: &dyn Embedder,
+    index: &dyn VectorIndex,
+    doc_id: i64,
+    source: &str,
+    body: &str,
+) -> Result<()> {
+    let routed = embedder_for_source(embedder, source);
+    let embedder = routed.as_deref().unwrap_or(embedder);
+    let embedding = embedder.embed_with_model(body).await?;
+    index.upsert_embedding(doc_id, &embedding.vector).await?;
+    index
+        .record_model(doc_id, &embedding.model, embedding.vector.len())
+        .await?;
+
+    let chunks = chunk_text(body);
+    let mut chunk_embeddings = Vec::new();
+    let mut fallback = embedding.fallback;
+    if chunks.len() > 1 {
+        for chunk in chunks {
+            let chunk_embedding = embedder.embed_with_model(chunk).await?;
+            // A promotion midway leaves chunks from two models; redo them all
+            fallback |= chunk_embedding.fallback || chunk_embedding.model != embedding.model;
+            chunk_embeddings.push(chunk_embedding.vector);
+        }
+    }
+    index
+        .upsert_chunk_embeddings(doc_id, &chunk_embeddings)
+        .await?;
+
+    if fallback {
+        index.queue_reembed(doc_id).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,381 @@
+//! In-memory HNSW (hierarchical navigable small world) graph for approximate
+//! nearest-neighbour search.
+//!
+//! Exact KNN scans every vector, which stops being interactive somewhere past
+//! 100k documents plus their chunks. [`VectorStore`](crate::VectorStore)
+//! builds one of these per embedding dimension once a database holds enough
+//! vectors (see the `ann` module) and keeps it current on upsert.
+//! Removed vectors stay in the graph as waypoints and are skipped in results;
+//! the index is rebuilt once they outnumber live ones.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Links per node above layer 0.
+const M: usize = 16;
+/// Links per node on layer 0, which holds every node.
+const M0: usize = 2 * M;
+const EF_CONSTRUCTION: usize = 100;
+const EF_SEARCH: usize = 64;
+
+struct Node {
+    doc_id: i64,
+    chunk: bool,
+    vector: Vec<f32>,
+    /// Neighbours on each layer the node is on, from layer 0 up.
+    links: Vec<Vec<usize>>,
+    removed: bool,
+}
+
+/// Similarity and node index, ordered by similarity.
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f32, usize);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+pub struct Hnsw {
+    dims: usize,
+    nodes: Vec<Node>,
+    entry: Option<usize>,
+    by_doc: HashMap<i64, Vec<usize>>,
+    removed: usize,
+    rng: u64,
+}
+
+impl Hnsw {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims,
+            nodes: Vec::new(),
+            entry: None,
+            by_doc: HashMap::new(),
+            removed: 0,
+            rng: 0x9e3779b97f4a7c15,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Number of live vectors.
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.removed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether removed vectors outnumber live ones, so searches waste most
+    /// of their effort.
+    pub fn needs_rebuild(&self) -> bool {
+        self.removed > self.len()
+    }
+
+    /// Add a document's whole-document (`chunk == false`) or chunk vector.
+    /// Vectors of another dimension are ignored.
+    pub fn insert(&mut self, doc_id: i64, chunk: bool, vector: &[f32]) {
+        if vector.len() != self.dims {
+            return;
+        }
+        let vector = normalized(vector);
+        let level = self.random_level();
+        let idx = self.nodes.len();
+        self.nodes.push(Node {
+            doc_id,
+            chunk,
+            vector,
+            links: vec![Vec::new(); level + 1],
+            removed: false,
+        });
+        self.by_doc.entry(doc_id).or_default().push(idx);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(idx);
+            return;
+        };
+        let top = self.nodes[entry].links.len() - 1;
+        let query = self.nodes[idx].vector.clone();
+
+        let mut nearest = entry;
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(&query, nearest, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            let candidates = self.search_layer(&query, nearest, EF_CONSTRUCTION, layer);
+            let max_links = if layer == 0 { M0 } else { M };
+            let neighbours: Vec<usize> = candidates.iter().take(M).map(|s| s.1).collect();
+            self.nodes[idx].links[layer] = neighbours.clone();
+            for neighbour in neighbours {
+                self.nodes[neighbour].links[layer].push(idx);
+                if self.nodes[neighbour].links[layer].len() > max_links {
+                    self.prune(neighbour, layer, max_links);
+                }
+            }
+            if let Some(best) = candidates.first() {
+                nearest = best.1;
+            }
+        }
+        if level > top {
+            self.entry = Some(idx);
+        }
+    }
+
+    /// Remove a document's chunk or whole-document vectors.
+    pub fn remove(&mut self, doc_id: i64, chunk: bool) {
+        let Some(indices) = self.by_doc.get_mut(&doc_id) else {
+            return;
+        };
+        let nodes = &mut self.nodes;
+        let mut removed = 0;
+        indices.retain(|idx| {
+            let node = &mut nodes[*idx];
+            if node.chunk != chunk {
+                return true;
+            }
+            node.removed = true;
+            removed += 1;
+            false
+        });
+        if indices.is_empty() {
+            self.by_doc.remove(&doc_id);
+        }
+        self.removed += removed;
+    }
+
+    /// Up to `k` nearest live vectors as `(doc_id, similarity)`, best first.
+    /// A document with several matching chunks appears more than once.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(i64, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if query.len() != self.dims {
+            return Vec::new();
+        }
+        let query = normalized(query);
+        let mut nearest = entry;
+        for layer in (1..self.nodes[entry].links.len()).rev() {
+            nearest = self.greedy(&query, nearest, layer);
+        }
+        // Removed nodes take up slots in the beam, so widen it
+        let ef = EF_SEARCH.max(k) + self.removed.min(EF_SEARCH);
+        self.search_layer(&query, nearest, ef, 0)
+            .into_iter()
+            .filter(|scored| !self.nodes[scored.1].removed)
+            .take(k)
+            .map(|Scored(score, idx)| (self.nodes[idx].doc_id, score))
+            .collect()
+    }
+
+    fn similarity(&self, query: &[f32], idx: usize) -> f32 {
+        query
+            .iter()
+            .zip(&self.nodes[idx].vector)
+            .map(|(a, b)| a * b)
+            .sum()
+    }
+
+    /// Walk to the node closest to `query` on `layer`.
+    fn greedy(&self, query: &[f32], start: usize, layer: usize) -> usize {
+        let mut current = start;
+        let mut best = self.similarity(query, current);
+        loop {
+            let mut improved = false;
+            for &neighbour in &self.nodes[current].links[layer] {
+                let score = self.similarity(query, neighbour);
+                if score > best {
+                    best = score;
+                    current = neighbour;
+                    improved = true;
+                }
+            }
+            if !improved {
+                return current;
+            }
+        }
+    }
+
+    /// Beam search on `layer`, returning up to `ef` nodes, best first.
+    fn search_layer(&self, query: &[f32], start: usize, ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited = HashSet::from([start]);
+        let first = Scored(self.similarity(query, start), start);
+        let mut candidates = BinaryHeap::from([first]);
+        // Min-heap of the best `ef` found so far
+        let mut found = BinaryHeap::from([std::cmp::Reverse(first)]);
+
+        while let Some(candidate) = candidates.pop() {
+            let worst = found.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+            if candidate.0 < worst && found.len() >= ef {
+                break;
+            }
+            for &neighbour in &self.nodes[candidate.1].links[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = Scored(self.similarity(query, neighbour), neighbour);
+                let worst = found.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+                if found.len() < ef || scored.0 > worst {
+                    candidates.push(scored);
+                    found.push(std::cmp::Reverse(scored));
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+
+        let mut results: Vec<Scored> = found.into_iter().map(|r| r.0).collect();
+        results.sort_by(|a, b| b.cmp(a));
+        results
+    }
+
+    /// Keep the `max_links` neighbours closest to `idx` on `layer`.
+    fn prune(&mut self, idx: usize, layer: usize, max_links: usize) {
+        let vector = self.nodes[idx].vector.clone();
+        let mut links: Vec<Scored> = self.nodes[idx].links[layer]
+            .iter()
+            .map(|&neighbour| Scored(self.similarity(&vector, neighbour), neighbour))
+            .collect();
+        links.sort_by(|a, b| b.cmp(a));
+        links.truncate(max_links);
+        self.nodes[idx].links[layer] = links.into_iter().map(|s| s.1).collect();
+    }
+
+    /// Geometric level distribution with ratio 1/M (xorshift, so builds are
+    /// reproducible).
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let uniform = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+        let level = -(uniform.max(f64::MIN_POSITIVE)).ln() / (M as f64).ln();
+        (level as usize).min(16)
+    }
+}
+
+fn normalized(vector: &[f32]) -> Vec<f32> {
+    let mut vector = vector.to_vec();
+    crate::normalize(&mut vector);
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seeded vectors with components in [-1, 1).
+    fn random_vectors(count: usize, dims: usize, mut seed: u64) -> Vec<Vec<f32>> {
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+        };
+        (0..count).map(|_| (0..dims).map(|_| next()).collect()).collect()
+    }
+
+    fn brute_force(vectors: &[Vec<f32>], query: &[f32], k: usize) -> Vec<i64> {
+        let query = normalized(query);
+        let mut scored: Vec<(f32, i64)> = vectors
+            .iter()
+            .enumerate()
+            .map(|(id, vector)| {
+                let vector = normalized(vector);
+                (query.iter().zip(&vector).map(|(a, b)| a * b).sum(), id as i64)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(k).map(|(_, id)| id).collect()
+    }
+
+    #[test]
+    fn test_recall_matches_brute_force() {
+        let vectors = random_vectors(1000, 32, 7);
+        let mut index = Hnsw::new(32);
+        for (id, vector) in vectors.iter().enumerate() {
+            index.insert(id as i64, false, vector);
+        }
+        assert_eq!(index.len(), 1000);
+
+        let k = 10;
+        let mut hits = 0;
+        let queries = random_vectors(50, 32, 11);
+        for query in &queries {
+            let expected: HashSet<i64> = brute_force(&vectors, query, k).into_iter().collect();
+            let found = index.search(query, k);
+            assert_eq!(found.len(), k);
+            assert!(found.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+            hits += found.iter().filter(|(id, _)| expected.contains(id)).count();
+        }
+        let recall = hits as f32 / (queries.len() * k) as f32;
+        assert!(recall >= 0.95, "recall {recall}");
+    }
+
+    #[test]
+    fn test_removed_vectors_are_never_returned() {
+        let vectors = random_vectors(300, 8, 3);
+        let mut index = Hnsw::new(8);
+        for (id, vector) in vectors.iter().enumerate() {
+            index.insert(id as i64, false, vector);
+            index.insert(id as i64, true, vector);
+        }
+        for id in (0..300).step_by(2) {
+            index.remove(id, false);
+            index.remove(id, true);
+        }
+        assert_eq!(index.len(), 300);
+
+        for query in &vectors {
+            let found = index.search(query, 20);
+            assert!(!found.is_empty());
+            assert!(found.iter().all(|(id, _)| id % 2 == 1), "{found:?}");
+        }
+        // Removing one kind of vector leaves the other
+        index.remove(1, true);
+        assert_eq!(index.search(&vectors[1], 1)[0].0, 1);
+    }
+
+    #[test]
+    fn test_other_dimensions_are_ignored() {
+        let mut index = Hnsw::new(4);
+        index.insert(1, false, &[1.0, 0.0, 0.0]);
+        assert!(index.is_empty());
+        assert!(index.search(&[1.0, 0.0, 0.0], 5).is_empty());
+
+        index.insert(2, false, &[1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(index.search(&[1.0, 0.0, 0.0, 0.0], 5), vec![(2, 1.0)]);
+        assert!(index.search(&[1.0, 0.0, 0.0], 5).is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_once_removed_outnumber_live() {
+        let mut index = Hnsw::new(4);
+        for (id, vector) in random_vectors(10, 4, 5).iter().enumerate() {
+            index.insert(id as i64, false, vector);
+        }
+        for id in 0..5 {
+            index.remove(id, false);
+        }
+        // Removing again, or a kind of vector the document doesn't have, counts nothing
+        index.remove(0, false);
+        index.remove(1, true);
+        assert_eq!(index.len(), 5);
+        assert!(!index.needs_rebuild());
+
+        index.remove(5, false);
+        assert!(index.needs_rebuild());
+    }
+}
@@ -1,20 +1,41 @@
-use std::collections::HashMap;
+mod ann;
+pub mod chunk;
+pub mod hnsw;
+pub mod ollama;
+mod query_cache;
+pub mod remote;
+pub mod rerank;
+
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::{Arc, Mutex, Once};
+use std::sync::{Arc, Mutex, Once, RwLock};
+use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use fastembed::{EmbeddingModel, TextEmbedding, TextInitOptions};
+use fastembed::{EmbeddingModel, ExecutionProviderDispatch, TextEmbedding, TextInitOptions};
 use libsqlite3_sys::sqlite3_auto_extension;
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions, SqlitePool};
+use sqlx::{
+    sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions, Executor, Sqlite, SqliteConnection,
+    SqlitePool,
+};
+use tokio::sync::watch;
 use tokio::task;
-use tracing::{instrument, warn};
+use tracing::{info, instrument, warn};
 
 use sqlite_vec::sqlite3_vec_init;
 
+use ann::{AnnIndex, Op};
+pub use chunk::embed_document;
+pub use ollama::{ollama_backend, OllamaEmbedder};
+use query_cache::QueryCache;
+pub use remote::{remote_backend, RemoteEmbedder};
+pub use rerank::{reranker_from_env, BackgroundReranker, FastReranker, Reranker};
+use chunk::{CHUNK_BYTES, MAX_CHUNKS};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredEmbedding {
     pub doc_id: i64,
@@ -22,35 +43,200 @@ pub struct StoredEmbedding {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Number of documents embedded with a model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub dims: usize,
+    pub documents: i64,
+}
+
+/// Restricts a semantic search to some documents. Applied in SQL before
+/// ranking, so excluded documents don't use up the result budget.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// Only these sources (any source when empty), compared case-insensitively.
+    pub sources: Vec<String>,
+    pub excluded_sources: Vec<String>,
+    /// Only these documents, e.g. a pack.
+    pub doc_ids: Option<Vec<i64>>,
+    /// Never these documents, e.g. ones a client has already seen.
+    pub excluded_doc_ids: Vec<i64>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Only documents whose URI starts with this.
+    pub uri_prefix: Option<String>,
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+            && self.excluded_sources.is_empty()
+            && self.doc_ids.is_none()
+            && self.excluded_doc_ids.is_empty()
+            && self.updated_after.is_none()
+            && self.updated_before.is_none()
+            && self.uri_prefix.is_none()
+    }
+
+    /// Conditions on `documents d`, and their bindings in order.
+    fn where_sql(&self) -> (String, Vec<String>) {
+        let mut clauses = vec!["d.deleted_at IS NULL".to_string()];
+        let mut binds = Vec::new();
+        if !self.sources.is_empty() {
+            clauses.push("lower(d.source) IN (SELECT lower(value) FROM json_each(?))".to_string());
+            binds.push(serde_json::to_string(&self.sources).unwrap_or_default());
+        }
+        if !self.excluded_sources.is_empty() {
+            clauses.push("lower(d.source) NOT IN (SELECT lower(value) FROM json_each(?))".to_string());
+            binds.push(serde_json::to_string(&self.excluded_sources).unwrap_or_default());
+        }
+        if let Some(doc_ids) = &self.doc_ids {
+            clauses.push("d.id IN (SELECT value FROM json_each(?))".to_string());
+            binds.push(serde_json::to_string(doc_ids).unwrap_or_default());
+        }
+        if !self.excluded_doc_ids.is_empty() {
+            clauses.push("d.id NOT IN (SELECT value FROM json_each(?))".to_string());
+            binds.push(serde_json::to_string(&self.excluded_doc_ids).unwrap_or_default());
+        }
+        if let Some(after) = self.updated_after {
+            clauses.push("d.updated_at >= ?".to_string());
+            binds.push(after.to_rfc3339());
+        }
+        if let Some(before) = self.updated_before {
+            clauses.push("d.updated_at < ?".to_string());
+            binds.push(before.to_rfc3339());
+        }
+        if let Some(prefix) = &self.uri_prefix {
+            clauses.push("substr(d.uri, 1, length(?)) = ?".to_string());
+            binds.push(prefix.clone());
+            binds.push(prefix.clone());
+        }
+        (clauses.join(" AND "), binds)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cluster {
     pub label: String,
     pub doc_ids: Vec<i64>,
+    /// Filled in by the caller if clusters are summarized.
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// An embedding and the model that produced it.
+#[derive(Debug, Clone)]
+pub struct ModelEmbedding {
+    pub vector: Vec<f32>,
+    pub model: String,
+    /// Produced by a stand-in model; see [`Embedder::is_fallback`].
+    pub fallback: bool,
 }
 
 #[async_trait]
 pub trait Embedder: Send + Sync {
     async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+
+    /// Embed `text` and report which model did it. Use this rather than
+    /// asking [`Self::model_name`] or [`Self::is_fallback`] afterwards: a
+    /// [`StandbyEmbedder`] can be promoted in between.
+    async fn embed_with_model(&self, text: &str) -> Result<ModelEmbedding> {
+        Ok(ModelEmbedding {
+            vector: self.embed(text).await?,
+            model: self.model_name(),
+            fallback: self.is_fallback(),
+        })
+    }
+
+    /// Whether embeddings produced right now come from a stand-in model and
+    /// should be redone once the real model is available.
+    fn is_fallback(&self) -> bool {
+        false
+    }
+
+    /// Name of the model, recorded with each embedding so a model change can
+    /// be detected and the documents re-embedded.
+    fn model_name(&self) -> String {
+        "unknown".to_string()
+    }
+
+    /// Embedders configured for particular document sources, which
+    /// [`embed_document`] uses instead of this one for their documents.
+    fn source_embedders(&self) -> Vec<(String, Arc<dyn Embedder>)> {
+        Vec::new()
+    }
+}
+
+/// The embedder for documents from `source`, if it has its own.
+pub fn embedder_for_source(embedder: &dyn Embedder, source: &str) -> Option<Arc<dyn Embedder>> {
+    embedder
+        .source_embedders()
+        .into_iter()
+        .find(|(name, _)| name == source)
+        .map(|(_, embedder)| embedder)
 }
 
 #[derive(Clone)]
 pub struct FastEmbedder {
     model: Arc<Mutex<TextEmbedding>>,
+    name: String,
 }
 
 impl FastEmbedder {
     pub fn new(model: EmbeddingModel, cache_dir: Option<PathBuf>) -> Result<Self> {
+        let name = format!("{:?}", model);
         let mut options = TextInitOptions::new(model);
         if let Some(dir) = cache_dir {
             options = options.with_cache_dir(dir);
         }
+        let (providers, names) = execution_providers();
+        if !providers.is_empty() {
+            info!("[EMBED] Running {} on {} (CPU fallback)", name, names.join(", "));
+            options = options.with_execution_providers(providers);
+        }
         let model = TextEmbedding::try_new(options)?;
         Ok(Self {
             model: Arc::new(Mutex::new(model)),
+            name,
         })
     }
 }
 
+/// ONNX Runtime execution providers for fastembed models, with their names.
+/// Builds with the `coreml` or `cuda` feature use those unless
+/// `MINNA_EMBED_PROVIDER` names another one (`cpu` for none); ONNX Runtime
+/// falls back to the CPU when a provider can't be loaded or doesn't support
+/// an operation.
+fn execution_providers() -> (Vec<ExecutionProviderDispatch>, Vec<&'static str>) {
+    let requested = std::env::var("MINNA_EMBED_PROVIDER").ok();
+    select_providers(requested.as_deref(), &["coreml", "cuda"])
+        .into_iter()
+        .filter_map(|name| Some((execution_provider(name)?, name)))
+        .unzip()
+}
+
+/// Which of `names` to try for a `MINNA_EMBED_PROVIDER` of `requested`.
+fn select_providers(requested: Option<&str>, names: &[&'static str]) -> Vec<&'static str> {
+    let requested = requested.unwrap_or("auto").to_ascii_lowercase();
+    names
+        .iter()
+        .copied()
+        .filter(|name| requested == "auto" || requested == *name)
+        .collect()
+}
+
+/// The named provider, if this build has its feature.
+fn execution_provider(name: &str) -> Option<ExecutionProviderDispatch> {
+    match name {
+        #[cfg(feature = "coreml")]
+        "coreml" => Some(ort::execution_providers::CoreMLExecutionProvider::default().build()),
+        #[cfg(feature = "cuda")]
+        "cuda" => Some(ort::execution_providers::CUDAExecutionProvider::default().build()),
+        _ => None,
+    }
+}
+
 #[async_trait]
 impl Embedder for FastEmbedder {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
@@ -69,6 +255,10 @@ impl Embedder for FastEmbedder {
         .await??;
         Ok(embedding)
     }
+
+    fn model_name(&self) -> String {
+        self.name.clone()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -97,23 +287,371 @@ impl Embedder for HashEmbedder {
         normalize(&mut vec);
         Ok(vec)
     }
+
+    fn model_name(&self) -> String {
+        format!("hash-{}", self.dims)
+    }
+}
+
+/// Embedder that serves the hash fallback while the real model is still
+/// downloading, then switches over once it has been promoted.
+pub struct StandbyEmbedder {
+    /// The embedder in use and whether it is the real model, swapped
+    /// together so an embed can't pair one with the other.
+    active: RwLock<(Arc<dyn Embedder>, bool)>,
+    loading: watch::Sender<ModelLoad>,
+}
+
+/// Progress of the real model behind a [`StandbyEmbedder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelLoad {
+    Loading,
+    Ready,
+    Failed,
+}
+
+impl StandbyEmbedder {
+    pub fn new(fallback: Arc<dyn Embedder>) -> Self {
+        Self {
+            active: RwLock::new((fallback, false)),
+            loading: watch::Sender::new(ModelLoad::Loading),
+        }
+    }
+
+    /// Whether the real model has been promoted.
+    pub fn is_warm(&self) -> bool {
+        self.current().map(|(_, warm)| warm).unwrap_or(false)
+    }
+
+    /// Swap in the real model. Subsequent embeds use it.
+    pub fn promote(&self, embedder: Arc<dyn Embedder>) {
+        if let Ok(mut guard) = self.active.write() {
+            *guard = (embedder, true);
+            self.loading.send_replace(ModelLoad::Ready);
+        }
+    }
+
+    /// The real model failed to load; the fallback keeps serving.
+    pub fn fail(&self) {
+        self.loading.send_if_modified(|load| {
+            let loading = *load == ModelLoad::Loading;
+            if loading {
+                *load = ModelLoad::Failed;
+            }
+            loading
+        });
+    }
+
+    /// Wait until the real model is promoted (true) or failed to load (false).
+    pub async fn wait_ready(&self) -> bool {
+        let mut loading = self.loading.subscribe();
+        loading
+            .wait_for(|load| *load != ModelLoad::Loading)
+            .await
+            .map(|load| *load == ModelLoad::Ready)
+            .unwrap_or(false)
+    }
+
+    fn current(&self) -> Result<(Arc<dyn Embedder>, bool)> {
+        self.active
+            .read()
+            .map(|guard| guard.clone())
+            .map_err(|_| anyhow!("standby embedder lock poisoned"))
+    }
+}
+
+#[async_trait]
+impl Embedder for StandbyEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let (embedder, _) = self.current()?;
+        embedder.embed(text).await
+    }
+
+    async fn embed_with_model(&self, text: &str) -> Result<ModelEmbedding> {
+        let (embedder, warm) = self.current()?;
+        Ok(ModelEmbedding {
+            vector: embedder.embed(text).await?,
+            model: embedder.model_name(),
+            fallback: !warm,
+        })
+    }
+
+    fn is_fallback(&self) -> bool {
+        !self.is_warm()
+    }
+
+    fn model_name(&self) -> String {
+        self.current()
+            .map(|(embedder, _)| embedder.model_name())
+            .unwrap_or_else(|_| "unknown".to_string())
+    }
+}
+
+/// Embeds documents from some sources with their own model (say, a code
+/// model for GitHub) and everything else, including queries, with `default`.
+/// Each model's vectors form their own space: search embeds the query once
+/// per model and only compares it with documents embedded by that model.
+pub struct SourceEmbedders {
+    default: Arc<dyn Embedder>,
+    by_source: HashMap<String, Arc<dyn Embedder>>,
+}
+
+impl SourceEmbedders {
+    pub fn new(default: Arc<dyn Embedder>, by_source: HashMap<String, Arc<dyn Embedder>>) -> Self {
+        Self { default, by_source }
+    }
+}
+
+#[async_trait]
+impl Embedder for SourceEmbedders {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.default.embed(text).await
+    }
+
+    async fn embed_with_model(&self, text: &str) -> Result<ModelEmbedding> {
+        self.default.embed_with_model(text).await
+    }
+
+    fn is_fallback(&self) -> bool {
+        self.default.is_fallback()
+    }
+
+    fn model_name(&self) -> String {
+        self.default.model_name()
+    }
+
+    fn source_embedders(&self) -> Vec<(String, Arc<dyn Embedder>)> {
+        self.by_source
+            .iter()
+            .map(|(source, embedder)| (source.clone(), embedder.clone()))
+            .collect()
+    }
+}
+
+/// Storage interface for embeddings, so a backend other than the local
+/// SQLite database (e.g. pgvector) can be plugged in. [`VectorStore`] is the
+/// SQLite implementation and the default.
+#[async_trait]
+pub trait VectorIndex: Send + Sync {
+    async fn upsert_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()>;
+
+    /// Mark a document for re-embedding once the real model is loaded.
+    async fn queue_reembed(&self, doc_id: i64) -> Result<()>;
+
+    async fn delete_embeddings(&self, doc_ids: &[i64]) -> Result<()>;
+
+    async fn get_embedding(&self, doc_id: i64) -> Result<Option<Vec<f32>>>;
+
+    /// Record which model produced a document's embeddings.
+    async fn record_model(&self, doc_id: i64, model: &str, dims: usize) -> Result<()>;
+
+    /// Replace a document's chunk embeddings (see [`chunk`]); an empty slice
+    /// removes them.
+    async fn upsert_chunk_embeddings(&self, doc_id: i64, embeddings: &[Vec<f32>]) -> Result<()>;
+
+    /// Nearest documents to `query_embedding` as `(doc_id, similarity)`,
+    /// best first. A document scores as its best-matching chunk when that
+    /// beats its whole-document embedding.
+    async fn search_with_embedding(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>>;
+
+    /// Documents queued by [`Self::queue_reembed`], oldest first.
+    async fn pending_reembed(&self, limit: usize) -> Result<Vec<i64>>;
+
+    async fn dequeue_reembed(&self, doc_id: i64) -> Result<()>;
+
+    /// Drop embeddings of documents that no longer exist, returning how many.
+    async fn scrub_orphaned_embeddings(&self) -> Result<u64>;
+}
+
+/// Embeddings live in one `vec0` virtual table per dimension when sqlite-vec
+/// is loaded, since the hash fallback and the real model produce vectors of
+/// different sizes. Without sqlite-vec they stay in the `vectors` table as
+/// JSON and search scans them.
+const VEC_TABLE_PREFIX: &str = "vec_embeddings_";
+
+/// Chunk embeddings, keyed by `doc_id * MAX_CHUNKS + chunk_idx`.
+const CHUNK_TABLE_PREFIX: &str = "vec_chunks_";
+
+/// Marks tables holding int8-quantized vectors (`MINNA_EMBED_QUANTIZE=int8`),
+/// e.g. `vec_embeddings_i8_768`.
+const QUANTIZED_INFIX: &str = "i8_";
+
+/// Quantized components are the normalized vector scaled by this.
+const INT8_SCALE: f32 = 127.0;
+
+/// Largest `k` a `vec0` KNN query accepts.
+const MAX_KNN: usize = 4096;
+
+/// Corpora larger than this aren't benchmarked: scanning them in memory is
+/// never the faster choice, and timing it would slow startup.
+const BENCHMARK_MAX_VECTORS: i64 = 20_000;
+
+/// Timed searches per backend; the fastest counts.
+const BENCHMARK_RUNS: usize = 3;
+
+/// How unfiltered searches are answered while there is no HNSW graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBackend {
+    /// KNN queries against the `vec0` tables.
+    SqliteVec,
+    /// Cosine similarity against every stored vector, in memory.
+    BruteForce,
+}
+
+impl SearchBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchBackend::SqliteVec => "sqlite-vec",
+            SearchBackend::BruteForce => "brute force",
+        }
+    }
+}
+
+fn table_prefix(prefix: &str, quantized: bool) -> String {
+    if quantized {
+        format!("{}{}", prefix, QUANTIZED_INFIX)
+    } else {
+        prefix.to_string()
+    }
+}
+
+fn vec_table(dims: usize, quantized: bool) -> String {
+    format!("{}{}", table_prefix(VEC_TABLE_PREFIX, quantized), dims)
+}
+
+fn chunk_table(dims: usize, quantized: bool) -> String {
+    format!("{}{}", table_prefix(CHUNK_TABLE_PREFIX, quantized), dims)
+}
+
+/// SQL function that turns a JSON payload into a vector of the table format.
+fn vec_constructor(quantized: bool) -> &'static str {
+    if quantized {
+        "vec_int8"
+    } else {
+        "vec_f32"
+    }
+}
+
+/// JSON payload for a `vec0` table. Quantized vectors are normalized first,
+/// so every component fits in [-127, 127] and cosine distance is unchanged up
+/// to rounding.
+fn vec_payload(embedding: &[f32], quantized: bool) -> Result<String> {
+    if !quantized {
+        return Ok(serde_json::to_string(embedding)?);
+    }
+    let mut normalized = embedding.to_vec();
+    normalize(&mut normalized);
+    let quantized: Vec<i8> = normalized
+        .iter()
+        .map(|v| (v * INT8_SCALE).round().clamp(-INT8_SCALE, INT8_SCALE) as i8)
+        .collect();
+    Ok(serde_json::to_string(&quantized)?)
+}
+
+/// Read a `vec0` vector column, dequantizing int8 vectors. The raw bytes are
+/// exact, unlike `vec_to_json`, which rounds to six digits.
+fn decode_vec(bytes: &[u8], quantized: bool) -> Option<Vec<f32>> {
+    if quantized {
+        return Some(bytes.iter().map(|&b| b as i8 as f32 / INT8_SCALE).collect());
+    }
+    decode_f32s(bytes)
+}
+
+/// Little-endian f32 BLOB for the `vectors` and `vector_chunks` tables.
+fn embedding_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Read an `embedding_blob`, or a JSON array from before embeddings were
+/// stored as BLOBs.
+fn decode_blob(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.first() == Some(&b'[') {
+        if let Ok(vector) = serde_json::from_slice(bytes) {
+            return Some(vector);
+        }
+    }
+    decode_f32s(bytes)
+}
+
+/// Little-endian f32 components, as in `embedding_blob` and f32 `vec0` columns.
+fn decode_f32s(bytes: &[u8]) -> Option<Vec<f32>> {
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    )
+}
+
+fn chunk_id(doc_id: i64, chunk_idx: usize) -> i64 {
+    doc_id * MAX_CHUNKS as i64 + chunk_idx as i64
+}
+
+/// SQL for the value (model, dimension) expected of documents from
+/// `d.source`; binds each source and value in turn, then the default.
+fn expected_per_source_sql<T>(by_source: &HashMap<String, T>) -> String {
+    if by_source.is_empty() {
+        return "?".to_string();
+    }
+    format!(
+        "CASE d.source {} ELSE ? END",
+        vec!["WHEN ? THEN ?"; by_source.len()].join(" ")
+    )
+}
+
+/// Keep each document's best score, best first.
+fn best_per_document(hits: impl IntoIterator<Item = (i64, f32)>, limit: usize) -> Vec<(i64, f32)> {
+    let mut best: HashMap<i64, f32> = HashMap::new();
+    for (doc_id, score) in hits {
+        let entry = best.entry(doc_id).or_insert(score);
+        *entry = entry.max(score);
+    }
+    let mut scored: Vec<(i64, f32)> = best.into_iter().collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(limit);
+    scored
 }
 
 #[derive(Clone)]
 pub struct VectorStore {
     pool: SqlitePool,
     sqlite_vec_available: bool,
+    /// Dimensions this store has created or seen a `vec0` table for, so
+    /// writes don't re-create them.
+    vec_dims: Arc<RwLock<Vec<usize>>>,
+    /// Whether the `vec0` tables hold int8 rather than f32 vectors.
+    quantized: bool,
+    ann: Arc<AnnIndex>,
+    query_cache: Arc<QueryCache>,
+    backend: SearchBackend,
 }
 
 impl VectorStore {
     pub async fn new(db_path: &Path) -> Result<Self> {
+        Self::open(db_path, None).await
+    }
+
+    /// Open a database encrypted with SQLCipher using a raw hex `key`. Open
+    /// it through `IngestionEngine::open` first, which validates the key and
+    /// encrypts a plaintext database.
+    pub async fn open(db_path: &Path, key: Option<&str>) -> Result<Self> {
         register_sqlite_vec();
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let options = SqliteConnectOptions::from_str("sqlite:")?
+        let mut options = SqliteConnectOptions::from_str("sqlite:")?
             .filename(db_path)
             .create_if_missing(true);
+        if let Some(key) = key {
+            options = options.pragma("key", format!("\"x'{}'\"", key));
+        }
         let pool = SqlitePoolOptions::new()
             .max_connections(4)
             .connect_with(options)
@@ -121,12 +659,149 @@ impl VectorStore {
         let mut store = Self {
             pool,
             sqlite_vec_available: false,
+            vec_dims: Arc::new(RwLock::new(Vec::new())),
+            quantized: false,
+            ann: Arc::new(AnnIndex::from_env()),
+            query_cache: Arc::new(QueryCache::from_env()),
+            backend: SearchBackend::BruteForce,
         };
         store.init_schema().await?;
         store.sqlite_vec_available = store.detect_sqlite_vec().await.unwrap_or(false);
+        let quantize = store.quantization_setting().await?;
+        if store.sqlite_vec_available {
+            store.quantized = quantize;
+            store.load_vec_dims().await?;
+            store.migrate_to_vec0().await?;
+            store.convert_vec_tables().await?;
+        } else if quantize {
+            warn!("[VECTOR] int8 quantization needs sqlite-vec; storing f32 embeddings");
+        }
+        store.backend = store.select_backend().await?;
         Ok(store)
     }
 
+    /// Search with `backend` instead of the one chosen at startup.
+    /// sqlite-vec is ignored if it isn't loaded.
+    pub fn with_search_backend(mut self, backend: SearchBackend) -> Self {
+        if backend == SearchBackend::BruteForce || self.sqlite_vec_available {
+            self.backend = backend;
+        }
+        self
+    }
+
+    /// Backend for searches without an HNSW graph.
+    pub fn search_backend(&self) -> SearchBackend {
+        self.backend
+    }
+
+    /// `MINNA_VECTOR_BACKEND` (`sqlite-vec`, `brute-force` or `auto`), or by
+    /// default whichever backend answers a query built from a stored vector
+    /// faster. The choice is logged along with when HNSW takes over.
+    async fn select_backend(&self) -> Result<SearchBackend> {
+        let vectors = self.count().await?;
+        let ann = match self.ann.min_documents() {
+            Some(min_documents) => format!("HNSW from {} documents", min_documents),
+            None => "HNSW off".to_string(),
+        };
+        if !self.sqlite_vec_available {
+            info!(
+                "[VECTOR] Search backend: brute force (sqlite-vec not loaded), {} vectors, {}",
+                vectors, ann
+            );
+            return Ok(SearchBackend::BruteForce);
+        }
+        let setting = std::env::var("MINNA_VECTOR_BACKEND").unwrap_or_default();
+        let forced = match setting.as_str() {
+            "sqlite-vec" => Some(SearchBackend::SqliteVec),
+            "brute-force" => Some(SearchBackend::BruteForce),
+            "" | "auto" => None,
+            other => {
+                warn!(
+                    "[VECTOR] Unknown MINNA_VECTOR_BACKEND {:?}, using auto",
+                    other
+                );
+                None
+            }
+        };
+        if let Some(backend) = forced {
+            info!(
+                "[VECTOR] Search backend: {} (MINNA_VECTOR_BACKEND), {} vectors, {}",
+                backend.as_str(),
+                vectors,
+                ann
+            );
+            return Ok(backend);
+        }
+        if vectors > BENCHMARK_MAX_VECTORS {
+            info!(
+                "[VECTOR] Search backend: sqlite-vec ({} vectors, too many to scan), {}",
+                vectors, ann
+            );
+            return Ok(SearchBackend::SqliteVec);
+        }
+        let Some(sample) = self.list_embeddings().await?.into_iter().next() else {
+            info!(
+                "[VECTOR] Search backend: sqlite-vec (nothing to benchmark yet), {}",
+                ann
+            );
+            return Ok(SearchBackend::SqliteVec);
+        };
+
+        let mut sqlite_vec = Duration::MAX;
+        let mut brute_force = Duration::MAX;
+        for _ in 0..BENCHMARK_RUNS {
+            let started = std::time::Instant::now();
+            if let Err(err) = self
+                .search_with_embedding_sqlite_vec(&sample.embedding, 10)
+                .await
+            {
+                warn!(
+                    "[VECTOR] sqlite-vec search failed, using brute force: {}",
+                    err
+                );
+                return Ok(SearchBackend::BruteForce);
+            }
+            sqlite_vec = sqlite_vec.min(started.elapsed());
+            let started = std::time::Instant::now();
+            self.search_brute_force(&sample.embedding, 10).await?;
+            brute_force = brute_force.min(started.elapsed());
+        }
+        let backend = if sqlite_vec <= brute_force {
+            SearchBackend::SqliteVec
+        } else {
+            SearchBackend::BruteForce
+        };
+        info!(
+            "[VECTOR] Search backend: {} (sqlite-vec {:?}, brute force {:?} over {} vectors), {}",
+            backend.as_str(),
+            sqlite_vec,
+            brute_force,
+            vectors,
+            ann
+        );
+        Ok(backend)
+    }
+
+    /// Use the HNSW index once `min_documents` documents are embedded
+    /// (None searches exactly), instead of `MINNA_ANN_MIN_DOCUMENTS`.
+    pub fn with_ann_min_documents(mut self, min_documents: Option<usize>) -> Self {
+        self.ann = Arc::new(AnnIndex::new(min_documents));
+        self
+    }
+
+    /// Keep the last `capacity` query embeddings for `ttl` (0 turns the
+    /// cache off), instead of `MINNA_QUERY_CACHE_SIZE` and
+    /// `MINNA_QUERY_CACHE_TTL_SECS`.
+    pub fn with_query_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.query_cache = Arc::new(QueryCache::new(capacity, ttl));
+        self
+    }
+
+    /// Vectors in the HNSW index, or None while searches are exact.
+    pub fn ann_vectors(&self) -> Option<usize> {
+        self.ann.len()
+    }
+
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -136,70 +811,979 @@ impl VectorStore {
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS vectors (\
                 doc_id INTEGER PRIMARY KEY,\
-                embedding TEXT NOT NULL,\
+                embedding BLOB NOT NULL,\
                 updated_at TEXT NOT NULL\
             )",
         )
         .execute(&self.pool)
         .await?;
-        Ok(())
-    }
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS reembed_queue (\
+                doc_id INTEGER PRIMARY KEY,\
+                queued_at TEXT NOT NULL\
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        // Embeddings stored before this have no row and count as "unknown"
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS vector_models (\
+                doc_id INTEGER PRIMARY KEY,\
+                model TEXT NOT NULL,\
+                dims INTEGER NOT NULL\
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
 
-    pub async fn upsert_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()> {
-        let payload = serde_json::to_string(embedding)?;
+        let has_chunks: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'vector_chunks'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        // Chunk embeddings without sqlite-vec; with it they're in vec0 tables
+        // and vector_chunk_counts records how many each document has
         sqlx::query(
-            "INSERT INTO vectors (doc_id, embedding, updated_at) VALUES (?1, ?2, ?3)\
-            ON CONFLICT(doc_id) DO UPDATE SET embedding=excluded.embedding, updated_at=excluded.updated_at",
+            "CREATE TABLE IF NOT EXISTS vector_chunks (\
+                doc_id INTEGER NOT NULL,\
+                chunk_idx INTEGER NOT NULL,\
+                embedding BLOB NOT NULL,\
+                PRIMARY KEY (doc_id, chunk_idx)\
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS vector_chunk_counts (\
+                doc_id INTEGER PRIMARY KEY,\
+                dims INTEGER NOT NULL,\
+                chunks INTEGER NOT NULL\
+            )",
         )
-        .bind(doc_id)
-        .bind(payload)
-        .bind(Utc::now().to_rfc3339())
         .execute(&self.pool)
         .await?;
+        if !has_chunks {
+            self.queue_long_documents().await?;
+        }
+        self.migrate_to_blobs().await?;
         Ok(())
     }
 
-    pub async fn scrub_orphaned_embeddings(&self) -> Result<()> {
-        sqlx::query("DELETE FROM vectors WHERE doc_id NOT IN (SELECT id FROM documents)")
-            .execute(&self.pool)
+    /// Rewrite embeddings stored as JSON text into little-endian f32 BLOBs.
+    /// Unreadable rows are dropped and their documents queued for
+    /// re-embedding.
+    async fn migrate_to_blobs(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let mut converted = 0;
+        let mut unreadable = HashSet::new();
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            "SELECT doc_id, embedding FROM vectors WHERE typeof(embedding) = 'text'",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for (doc_id, embedding) in rows {
+            let Ok(vector) = serde_json::from_str::<Vec<f32>>(&embedding) else {
+                sqlx::query("DELETE FROM vectors WHERE doc_id = ?1")
+                    .bind(doc_id)
+                    .execute(&mut *tx)
+                    .await?;
+                unreadable.insert(doc_id);
+                continue;
+            };
+            sqlx::query("UPDATE vectors SET embedding = ?1 WHERE doc_id = ?2")
+                .bind(embedding_blob(&vector))
+                .bind(doc_id)
+                .execute(&mut *tx)
+                .await?;
+            converted += 1;
+        }
+        let rows = sqlx::query_as::<_, (i64, i64, String)>(
+            "SELECT doc_id, chunk_idx, embedding FROM vector_chunks \
+            WHERE typeof(embedding) = 'text'",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for (doc_id, chunk_idx, embedding) in rows {
+            let Ok(vector) = serde_json::from_str::<Vec<f32>>(&embedding) else {
+                sqlx::query("DELETE FROM vector_chunks WHERE doc_id = ?1 AND chunk_idx = ?2")
+                    .bind(doc_id)
+                    .bind(chunk_idx)
+                    .execute(&mut *tx)
+                    .await?;
+                unreadable.insert(doc_id);
+                continue;
+            };
+            sqlx::query(
+                "UPDATE vector_chunks SET embedding = ?1 WHERE doc_id = ?2 AND chunk_idx = ?3",
+            )
+            .bind(embedding_blob(&vector))
+            .bind(doc_id)
+            .bind(chunk_idx)
+            .execute(&mut *tx)
             .await?;
+            converted += 1;
+        }
+        for doc_id in &unreadable {
+            sqlx::query(
+                "INSERT INTO reembed_queue (doc_id, queued_at) VALUES (?1, ?2)\
+                ON CONFLICT(doc_id) DO NOTHING",
+            )
+            .bind(doc_id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        if converted > 0 {
+            info!("[VECTOR] Converted {} embeddings to binary", converted);
+        }
+        if !unreadable.is_empty() {
+            warn!("[VECTOR] Queued {} documents with unreadable embeddings for re-embedding", unreadable.len());
+        }
         Ok(())
     }
 
-    pub async fn get_embedding(&self, doc_id: i64) -> Result<Option<Vec<f32>>> {
-        let row = sqlx::query_as::<_, (String,)>(
-            "SELECT embedding FROM vectors WHERE doc_id = ?1",
+    /// Queue documents embedded before chunking so they get chunk
+    /// embeddings.
+    async fn queue_long_documents(&self) -> Result<()> {
+        let has_documents: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'documents'",
         )
-        .bind(doc_id)
-        .fetch_optional(&self.pool)
+        .fetch_one(&self.pool)
+        .await?;
+        if !has_documents {
+            return Ok(());
+        }
+        sqlx::query(
+            "INSERT INTO reembed_queue (doc_id, queued_at) \
+            SELECT id, ?1 FROM documents WHERE deleted_at IS NULL AND length(body) > ?2 \
+            ON CONFLICT(doc_id) DO NOTHING",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(CHUNK_BYTES as i64)
+        .execute(&self.pool)
         .await?;
+        Ok(())
+    }
 
-        Ok(row.and_then(|(payload,)| serde_json::from_str(&payload).ok()))
+    async fn load_vec_dims(&self) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        for dims in self.known_vec_dims(&mut *conn).await? {
+            self.create_vec_tables(&mut conn, dims).await?;
+        }
+        Ok(())
     }
 
-    /// Get total vector count
-    pub async fn count(&self) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM vectors")
-            .fetch_one(&self.pool)
-            .await?;
-        Ok(count)
+    /// Dimensions that have a `vec0` table. Read from the schema rather than
+    /// the cache: another store on the database, such as the CLI's, may have
+    /// created tables since this one opened.
+    async fn known_vec_dims<'e, E>(&self, executor: E) -> Result<Vec<usize>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let dims = self.read_vec_dims(executor).await?;
+        *self
+            .vec_dims
+            .write()
+            .map_err(|_| anyhow!("vector dims lock poisoned"))? = dims.clone();
+        Ok(dims)
     }
 
-    pub async fn list_embeddings(&self) -> Result<Vec<StoredEmbedding>> {
-        let rows = sqlx::query_as::<_, (i64, String, String)>(
-            "SELECT doc_id, embedding, updated_at FROM vectors",
+    /// [`Self::known_vec_dims`] without updating the cache, for reads inside
+    /// a transaction that may roll back.
+    async fn read_vec_dims<'e, E>(&self, executor: E) -> Result<Vec<usize>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'vec_embeddings_%' \
+            AND sql LIKE '%USING vec0%'",
         )
-        .fetch_all(&self.pool)
+        .fetch_all(executor)
         .await?;
-        Ok(rows
+        let prefix = table_prefix(VEC_TABLE_PREFIX, self.quantized);
+        Ok(names
+            .iter()
+            .filter_map(|name| name.strip_prefix(&prefix)?.parse().ok())
+            .collect())
+    }
+
+    /// `vec0` tables to read, or none without sqlite-vec.
+    async fn vec_tables(&self) -> Result<Vec<String>> {
+        if !self.sqlite_vec_available {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .known_vec_dims(&self.pool)
+            .await?
             .into_iter()
-            .filter_map(|(doc_id, embedding, updated_at)| {
-                let embedding: Vec<f32> = serde_json::from_str(&embedding).ok()?;
-                let updated_at = DateTime::parse_from_rfc3339(&updated_at)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now());
-                Some(StoredEmbedding {
-                    doc_id,
+            .map(|dims| vec_table(dims, self.quantized))
+            .collect())
+    }
+
+    async fn ensure_vec_table(&self, dims: usize) -> Result<()> {
+        let cached = self.vec_dims.read().map(|known| known.contains(&dims)).unwrap_or(false);
+        if cached {
+            return Ok(());
+        }
+        let mut conn = self.pool.acquire().await?;
+        self.create_vec_tables(&mut conn, dims).await?;
+        let mut known = self
+            .vec_dims
+            .write()
+            .map_err(|_| anyhow!("vector dims lock poisoned"))?;
+        if !known.contains(&dims) {
+            known.push(dims);
+        }
+        Ok(())
+    }
+
+    async fn create_vec_tables(&self, conn: &mut SqliteConnection, dims: usize) -> Result<()> {
+        let element_type = if self.quantized { "int8" } else { "float" };
+        sqlx::query(&format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING vec0(\
+                doc_id INTEGER PRIMARY KEY,\
+                embedding {}[{}] distance_metric=cosine,\
+                +updated_at TEXT\
+            )",
+            vec_table(dims, self.quantized),
+            element_type,
+            dims
+        ))
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING vec0(\
+                chunk_id INTEGER PRIMARY KEY,\
+                embedding {}[{}] distance_metric=cosine,\
+                +doc_id INTEGER\
+            )",
+            chunk_table(dims, self.quantized),
+            element_type,
+            dims
+        ))
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Move embeddings stored in `vectors` into `vec0` tables.
+    async fn migrate_to_vec0(&self) -> Result<()> {
+        let rows = sqlx::query_as::<_, (i64, Vec<u8>, String)>(
+            "SELECT doc_id, embedding, updated_at FROM vectors",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut by_dims: HashMap<usize, Vec<(i64, String, String)>> = HashMap::new();
+        let mut unreadable = Vec::new();
+        for (doc_id, embedding, updated_at) in rows {
+            // Unreadable rows are dropped and the document re-embedded
+            let Some(vector) = decode_blob(&embedding).filter(|vector| !vector.is_empty()) else {
+                unreadable.push(doc_id);
+                continue;
+            };
+            let payload = vec_payload(&vector, self.quantized)?;
+            by_dims
+                .entry(vector.len())
+                .or_default()
+                .push((doc_id, payload, updated_at));
+        }
+        for dims in by_dims.keys() {
+            self.ensure_vec_table(*dims).await?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut migrated = 0;
+        for (dims, rows) in &by_dims {
+            let table = vec_table(*dims, self.quantized);
+            for (doc_id, embedding, updated_at) in rows {
+                sqlx::query(&format!("DELETE FROM {} WHERE doc_id = ?1", table))
+                    .bind(doc_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(&format!(
+                    "INSERT INTO {} (doc_id, embedding, updated_at) VALUES (?1, {}(?2), ?3)",
+                    table,
+                    vec_constructor(self.quantized)
+                ))
+                .bind(doc_id)
+                .bind(embedding)
+                .bind(updated_at)
+                .execute(&mut *tx)
+                .await?;
+                migrated += 1;
+            }
+        }
+        for doc_id in &unreadable {
+            sqlx::query(
+                "INSERT INTO reembed_queue (doc_id, queued_at) VALUES (?1, ?2)\
+                ON CONFLICT(doc_id) DO NOTHING",
+            )
+            .bind(doc_id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query("DELETE FROM vectors").execute(&mut *tx).await?;
+        tx.commit().await?;
+        info!("[VECTOR] Moved {} embeddings into vec0 tables", migrated);
+        if !unreadable.is_empty() {
+            warn!("[VECTOR] Queued {} documents with unreadable embeddings for re-embedding", unreadable.len());
+        }
+        Ok(())
+    }
+
+    /// `MINNA_EMBED_QUANTIZE` (`int8` or `none`); unset keeps the format the
+    /// database already uses.
+    async fn quantization_setting(&self) -> Result<bool> {
+        match std::env::var("MINNA_EMBED_QUANTIZE")
+            .unwrap_or_default()
+            .as_str()
+        {
+            "int8" => Ok(true),
+            "none" | "f32" => Ok(false),
+            "" => {
+                let quantized: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' \
+                    AND name LIKE 'vec_embeddings_i8_%' AND sql LIKE '%USING vec0%'",
+                )
+                .fetch_one(&self.pool)
+                .await?;
+                Ok(quantized > 0)
+            }
+            other => Err(anyhow!(
+                "unknown MINNA_EMBED_QUANTIZE value {:?} (expected int8 or none)",
+                other
+            )),
+        }
+    }
+
+    /// Rewrite `vec0` tables left in the other format after
+    /// `MINNA_EMBED_QUANTIZE` changed. Going back to f32 keeps the rounding
+    /// of the int8 copies until documents are re-embedded.
+    async fn convert_vec_tables(&self) -> Result<()> {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'vec_embeddings_%' \
+            AND sql LIKE '%USING vec0%'",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let other = !self.quantized;
+        let prefix = table_prefix(VEC_TABLE_PREFIX, other);
+        let stale: Vec<usize> = names
+            .iter()
+            .filter_map(|name| name.strip_prefix(&prefix)?.parse().ok())
+            .collect();
+
+        let mut converted = 0;
+        for dims in stale {
+            self.ensure_vec_table(dims).await?;
+            let rows = sqlx::query_as::<_, (i64, Vec<u8>, String)>(&format!(
+                "SELECT doc_id, embedding, updated_at FROM {}",
+                vec_table(dims, other)
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+            let chunks = sqlx::query_as::<_, (i64, Vec<u8>, i64)>(&format!(
+                "SELECT chunk_id, embedding, doc_id FROM {}",
+                chunk_table(dims, other)
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut tx = self.pool.begin().await?;
+            for (doc_id, embedding, updated_at) in rows {
+                let Some(vector) = decode_vec(&embedding, other) else {
+                    continue;
+                };
+                sqlx::query(&format!(
+                    "INSERT INTO {} (doc_id, embedding, updated_at) VALUES (?1, {}(?2), ?3)",
+                    vec_table(dims, self.quantized),
+                    vec_constructor(self.quantized)
+                ))
+                .bind(doc_id)
+                .bind(vec_payload(&vector, self.quantized)?)
+                .bind(updated_at)
+                .execute(&mut *tx)
+                .await?;
+                converted += 1;
+            }
+            for (chunk_id, embedding, doc_id) in chunks {
+                let Some(vector) = decode_vec(&embedding, other) else {
+                    continue;
+                };
+                sqlx::query(&format!(
+                    "INSERT INTO {} (chunk_id, embedding, doc_id) VALUES (?1, {}(?2), ?3)",
+                    chunk_table(dims, self.quantized),
+                    vec_constructor(self.quantized)
+                ))
+                .bind(chunk_id)
+                .bind(vec_payload(&vector, self.quantized)?)
+                .bind(doc_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            sqlx::query(&format!("DROP TABLE {}", vec_table(dims, other)))
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(&format!("DROP TABLE {}", chunk_table(dims, other)))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        if converted > 0 {
+            info!(
+                "[VECTOR] Converted {} embeddings to {}",
+                converted,
+                if self.quantized { "int8" } else { "f32" }
+            );
+        }
+        Ok(())
+    }
+
+    /// Mark a document as embedded with the fallback model so it can be
+    /// re-embedded once the real model is ready.
+    pub async fn queue_reembed(&self, doc_id: i64) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.queue_reembed_on(&mut conn, doc_id).await
+    }
+
+    /// [`Self::queue_reembed`] on `conn`, inside the caller's transaction.
+    pub async fn queue_reembed_on(&self, conn: &mut SqliteConnection, doc_id: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO reembed_queue (doc_id, queued_at) VALUES (?1, ?2)\
+            ON CONFLICT(doc_id) DO NOTHING",
+        )
+        .bind(doc_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Oldest queued document IDs awaiting re-embedding.
+    pub async fn pending_reembed(&self, limit: usize) -> Result<Vec<i64>> {
+        let rows = sqlx::query_as::<_, (i64,)>(
+            "SELECT doc_id FROM reembed_queue ORDER BY queued_at LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    pub async fn dequeue_reembed(&self, doc_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM reembed_queue WHERE doc_id = ?1")
+            .bind(doc_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Get number of documents awaiting re-embedding
+    pub async fn reembed_queue_len(&self) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reembed_queue")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn record_model(&self, doc_id: i64, model: &str, dims: usize) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO vector_models (doc_id, model, dims) VALUES (?1, ?2, ?3)\
+            ON CONFLICT(doc_id) DO UPDATE SET model=excluded.model, dims=excluded.dims",
+        )
+        .bind(doc_id)
+        .bind(model)
+        .bind(dims as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Embedded documents per model, most used first. Documents embedded
+    /// before models were recorded are counted under "unknown".
+    pub async fn embedding_models(&self) -> Result<Vec<ModelUsage>> {
+        let rows = sqlx::query_as::<_, (String, i64, i64)>(
+            "SELECT model, dims, COUNT(*) FROM vector_models GROUP BY model, dims \
+            ORDER BY COUNT(*) DESC, model",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut models: Vec<ModelUsage> = rows
+            .into_iter()
+            .map(|(model, dims, documents)| ModelUsage {
+                model,
+                dims: dims as usize,
+                documents,
+            })
+            .collect();
+        let recorded: i64 = models.iter().map(|usage| usage.documents).sum();
+        let unrecorded = self.count().await? - recorded;
+        if unrecorded > 0 {
+            models.push(ModelUsage {
+                model: "unknown".to_string(),
+                dims: 0,
+                documents: unrecorded,
+            });
+        }
+        Ok(models)
+    }
+
+    /// Queue every live document for re-embedding. Returns how many were
+    /// queued.
+    pub async fn queue_reembed_all(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "INSERT INTO reembed_queue (doc_id, queued_at) \
+            SELECT id, ?1 FROM documents WHERE deleted_at IS NULL \
+            ON CONFLICT(doc_id) DO NOTHING",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Queue live documents not embedded with the model for their source:
+    /// `source_models` where listed, `default_model` otherwise. Returns how
+    /// many were queued.
+    pub async fn queue_reembed_stale(
+        &self,
+        default_model: &str,
+        source_models: &HashMap<String, String>,
+    ) -> Result<u64> {
+        let expected = expected_per_source_sql(source_models);
+        let sql = format!(
+            "INSERT INTO reembed_queue (doc_id, queued_at) \
+            SELECT d.id, ? FROM documents d LEFT JOIN vector_models m ON m.doc_id = d.id \
+            WHERE d.deleted_at IS NULL AND (m.model IS NULL OR m.model != {}) \
+            ON CONFLICT(doc_id) DO NOTHING",
+            expected
+        );
+        let mut query = sqlx::query(&sql).bind(Utc::now().to_rfc3339());
+        for (source, model) in source_models {
+            query = query.bind(source).bind(model);
+        }
+        let result = query.bind(default_model).execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Embedded documents whose recorded model isn't the one for their
+    /// source (see [`Self::queue_reembed_stale`]).
+    pub async fn count_stale(
+        &self,
+        default_model: &str,
+        source_models: &HashMap<String, String>,
+    ) -> Result<i64> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM documents d JOIN vector_models m ON m.doc_id = d.id \
+            WHERE d.deleted_at IS NULL AND m.model != {}",
+            expected_per_source_sql(source_models)
+        );
+        let mut query = sqlx::query_scalar::<_, i64>(&sql);
+        for (source, model) in source_models {
+            query = query.bind(source).bind(model);
+        }
+        Ok(query.bind(default_model).fetch_one(&self.pool).await?)
+    }
+
+    /// `(doc_id, dims)` of every document embedding, as SQL.
+    async fn stored_dims_sql(&self) -> Result<String> {
+        let mut selects =
+            vec!["SELECT doc_id, length(embedding) / 4 AS dims FROM vectors".to_string()];
+        if self.sqlite_vec_available {
+            for dims in self.known_vec_dims(&self.pool).await? {
+                selects.push(format!(
+                    "SELECT doc_id, {} AS dims FROM {}",
+                    dims,
+                    vec_table(dims, self.quantized)
+                ));
+            }
+        }
+        Ok(selects.join(" UNION ALL "))
+    }
+
+    /// Embedded documents whose vectors don't have the dimension of the
+    /// model for their source: `source_dims` for those sources, else
+    /// `default_dims`. Searches can't compare them with queries.
+    pub async fn count_dimension_mismatches(
+        &self,
+        default_dims: usize,
+        source_dims: &HashMap<String, usize>,
+    ) -> Result<i64> {
+        let sql = format!(
+            "SELECT COUNT(DISTINCT v.doc_id) FROM ({}) v JOIN documents d ON d.id = v.doc_id \
+            WHERE d.deleted_at IS NULL AND v.dims != {}",
+            self.stored_dims_sql().await?,
+            expected_per_source_sql(source_dims)
+        );
+        let mut query = sqlx::query_scalar::<_, i64>(&sql);
+        for (source, dims) in source_dims {
+            query = query.bind(source).bind(*dims as i64);
+        }
+        Ok(query.bind(default_dims as i64).fetch_one(&self.pool).await?)
+    }
+
+    /// Queue the documents [`Self::count_dimension_mismatches`] counts for
+    /// re-embedding. Returns how many were queued.
+    pub async fn queue_reembed_mismatched_dims(
+        &self,
+        default_dims: usize,
+        source_dims: &HashMap<String, usize>,
+    ) -> Result<u64> {
+        let sql = format!(
+            "INSERT INTO reembed_queue (doc_id, queued_at) \
+            SELECT d.id, ? FROM ({}) v JOIN documents d ON d.id = v.doc_id \
+            WHERE d.deleted_at IS NULL AND v.dims != {} \
+            ON CONFLICT(doc_id) DO NOTHING",
+            self.stored_dims_sql().await?,
+            expected_per_source_sql(source_dims)
+        );
+        let mut query = sqlx::query(&sql).bind(Utc::now().to_rfc3339());
+        for (source, dims) in source_dims {
+            query = query.bind(source).bind(*dims as i64);
+        }
+        let result = query.bind(default_dims as i64).execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn upsert_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()> {
+        self.store_embedding(doc_id, embedding).await?;
+        self.ann.apply(Op::Remove {
+            doc_id,
+            chunk: false,
+        });
+        self.ann.apply(Op::Insert {
+            doc_id,
+            chunk: false,
+            vector: embedding.to_vec(),
+        });
+        Ok(())
+    }
+
+    async fn store_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()> {
+        if self.sqlite_vec_available && !embedding.is_empty() {
+            self.ensure_vec_table(embedding.len()).await?;
+        }
+        let mut tx = self.pool.begin().await?;
+        self.write_embedding(&mut tx, doc_id, embedding).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// [`Self::upsert_embedding`] on `conn`, inside the caller's
+    /// transaction. The ANN graphs are left alone; call
+    /// [`Self::reset_ann`] once the transaction commits.
+    pub async fn upsert_embedding_on(
+        &self,
+        conn: &mut SqliteConnection,
+        doc_id: i64,
+        embedding: &[f32],
+    ) -> Result<()> {
+        if self.sqlite_vec_available && !embedding.is_empty() {
+            // Not cached: the transaction may yet roll the table back
+            self.create_vec_tables(conn, embedding.len()).await?;
+        }
+        self.write_embedding(conn, doc_id, embedding).await
+    }
+
+    /// Drop the ANN graphs after writes through
+    /// [`Self::upsert_embedding_on`]; the next search rebuilds them.
+    pub fn reset_ann(&self) {
+        self.ann.reset();
+    }
+
+    /// Write the embedding row; its `vec0` table must exist.
+    async fn write_embedding(&self, conn: &mut SqliteConnection, doc_id: i64, embedding: &[f32]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        if !self.sqlite_vec_available || embedding.is_empty() {
+            let payload = embedding_blob(embedding);
+            sqlx::query(
+                "INSERT INTO vectors (doc_id, embedding, updated_at) VALUES (?1, ?2, ?3)\
+                ON CONFLICT(doc_id) DO UPDATE SET embedding=excluded.embedding, updated_at=excluded.updated_at",
+            )
+            .bind(doc_id)
+            .bind(payload)
+            .bind(now)
+            .execute(&mut *conn)
+            .await?;
+            return Ok(());
+        }
+
+        // vec0 has no upsert, and the document may have been embedded at
+        // another dimension before
+        for dims in self.read_vec_dims(&mut *conn).await? {
+            sqlx::query(&format!("DELETE FROM {} WHERE doc_id = ?1", vec_table(dims, self.quantized)))
+                .bind(doc_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        sqlx::query(&format!(
+            "INSERT INTO {} (doc_id, embedding, updated_at) VALUES (?1, {}(?2), ?3)",
+            vec_table(embedding.len(), self.quantized),
+            vec_constructor(self.quantized)
+        ))
+        .bind(doc_id)
+        .bind(vec_payload(embedding, self.quantized)?)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Replace a document's chunk embeddings; an empty slice removes them.
+    pub async fn upsert_chunk_embeddings(
+        &self,
+        doc_id: i64,
+        embeddings: &[Vec<f32>],
+    ) -> Result<()> {
+        let dims = embeddings.first().map(Vec::len).unwrap_or_default();
+        if embeddings.iter().any(|embedding| embedding.len() != dims) {
+            return Err(anyhow!(
+                "chunk embeddings of document {} differ in size",
+                doc_id
+            ));
+        }
+        let use_vec0 = self.sqlite_vec_available && dims > 0;
+        if use_vec0 {
+            self.ensure_vec_table(dims).await?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        self.delete_chunks(&mut tx, doc_id).await?;
+        for (chunk_idx, embedding) in embeddings.iter().enumerate().take(MAX_CHUNKS) {
+            if use_vec0 {
+                sqlx::query(&format!(
+                    "INSERT INTO {} (chunk_id, embedding, doc_id) VALUES (?1, {}(?2), ?3)",
+                    chunk_table(dims, self.quantized),
+                    vec_constructor(self.quantized)
+                ))
+                .bind(chunk_id(doc_id, chunk_idx))
+                .bind(vec_payload(embedding, self.quantized)?)
+                .bind(doc_id)
+                .execute(&mut *tx)
+                .await?;
+            } else {
+                sqlx::query(
+                    "INSERT INTO vector_chunks (doc_id, chunk_idx, embedding) VALUES (?1, ?2, ?3)",
+                )
+                .bind(doc_id)
+                .bind(chunk_idx as i64)
+                .bind(embedding_blob(embedding))
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        if use_vec0 {
+            sqlx::query(
+                "INSERT INTO vector_chunk_counts (doc_id, dims, chunks) VALUES (?1, ?2, ?3)",
+            )
+            .bind(doc_id)
+            .bind(dims as i64)
+            .bind(embeddings.len().min(MAX_CHUNKS) as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.ann.apply(Op::Remove {
+            doc_id,
+            chunk: true,
+        });
+        for embedding in embeddings.iter().take(MAX_CHUNKS) {
+            self.ann.apply(Op::Insert {
+                doc_id,
+                chunk: true,
+                vector: embedding.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// vec0 only deletes efficiently by key, so chunks are removed one by one
+    /// using the recorded count.
+    async fn delete_chunks(&self, conn: &mut SqliteConnection, doc_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM vector_chunks WHERE doc_id = ?1")
+            .bind(doc_id)
+            .execute(&mut *conn)
+            .await?;
+        let counts: Option<(i64, i64)> =
+            sqlx::query_as("SELECT dims, chunks FROM vector_chunk_counts WHERE doc_id = ?1")
+                .bind(doc_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let Some((dims, chunks)) = counts else {
+            return Ok(());
+        };
+        let known = self.known_vec_dims(&mut *conn).await?;
+        if self.sqlite_vec_available && known.contains(&(dims as usize)) {
+            for chunk_idx in 0..chunks as usize {
+                sqlx::query(&format!(
+                    "DELETE FROM {} WHERE chunk_id = ?1",
+                    chunk_table(dims as usize, self.quantized)
+                ))
+                .bind(chunk_id(doc_id, chunk_idx))
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+        sqlx::query("DELETE FROM vector_chunk_counts WHERE doc_id = ?1")
+            .bind(doc_id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove embeddings (and pending re-embeds) for the given documents.
+    pub async fn delete_embeddings(&self, doc_ids: &[i64]) -> Result<()> {
+        let tables = self.vec_tables().await?;
+        for doc_id in doc_ids {
+            let mut conn = self.pool.acquire().await?;
+            self.delete_chunks(&mut conn, *doc_id).await?;
+            sqlx::query("DELETE FROM vectors WHERE doc_id = ?1")
+                .bind(doc_id)
+                .execute(&self.pool)
+                .await?;
+            for table in &tables {
+                sqlx::query(&format!("DELETE FROM {} WHERE doc_id = ?1", table))
+                    .bind(doc_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            sqlx::query("DELETE FROM vector_models WHERE doc_id = ?1")
+                .bind(doc_id)
+                .execute(&self.pool)
+                .await?;
+            self.dequeue_reembed(*doc_id).await?;
+            self.ann.apply(Op::Remove {
+                doc_id: *doc_id,
+                chunk: false,
+            });
+            self.ann.apply(Op::Remove {
+                doc_id: *doc_id,
+                chunk: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Delete embeddings and queued re-embeds of documents that no longer
+    /// exist. Returns the number of embeddings removed.
+    pub async fn scrub_orphaned_embeddings(&self) -> Result<u64> {
+        let mut removed =
+            sqlx::query("DELETE FROM vectors WHERE doc_id NOT IN (SELECT id FROM documents)")
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        for table in self.vec_tables().await? {
+            removed += sqlx::query(&format!(
+                "DELETE FROM {} WHERE doc_id NOT IN (SELECT id FROM documents)",
+                table
+            ))
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+        sqlx::query("DELETE FROM vector_models WHERE doc_id NOT IN (SELECT id FROM documents)")
+            .execute(&self.pool)
+            .await?;
+        let chunked: Vec<i64> = sqlx::query_scalar(
+            "SELECT doc_id FROM vector_chunks WHERE doc_id NOT IN (SELECT id FROM documents) \
+            UNION SELECT doc_id FROM vector_chunk_counts WHERE doc_id NOT IN (SELECT id FROM documents)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut conn = self.pool.acquire().await?;
+        let chunks_removed = !chunked.is_empty();
+        for doc_id in chunked {
+            self.delete_chunks(&mut conn, doc_id).await?;
+        }
+        if removed > 0 || chunks_removed {
+            self.ann.reset();
+        }
+        sqlx::query("DELETE FROM reembed_queue WHERE doc_id NOT IN (SELECT id FROM documents)")
+            .execute(&self.pool)
+            .await?;
+        Ok(removed)
+    }
+
+    /// Periodic cleanup of vector tables: drops orphaned embeddings. Returns
+    /// the number removed. File-level maintenance (vacuum, integrity check)
+    /// is done once for the shared database by the ingestion engine.
+    pub async fn maintenance(&self) -> Result<u64> {
+        self.scrub_orphaned_embeddings().await
+    }
+
+    pub async fn get_embedding(&self, doc_id: i64) -> Result<Option<Vec<f32>>> {
+        let payload: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT embedding FROM vectors WHERE doc_id = ?1")
+                .bind(doc_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if let Some(payload) = payload {
+            return Ok(decode_blob(&payload));
+        }
+        for table in self.vec_tables().await? {
+            let payload: Option<Vec<u8>> = sqlx::query_scalar(&format!(
+                "SELECT embedding FROM {} WHERE doc_id = ?1",
+                table
+            ))
+            .bind(doc_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(payload) = payload {
+                return Ok(decode_vec(&payload, self.quantized));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get total vector count
+    pub async fn count(&self) -> Result<i64> {
+        let mut count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM vectors")
+            .fetch_one(&self.pool)
+            .await?;
+        for table in self.vec_tables().await? {
+            count += sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&self.pool)
+                .await?;
+        }
+        Ok(count)
+    }
+
+    pub async fn list_embeddings(&self) -> Result<Vec<StoredEmbedding>> {
+        let rows = sqlx::query_as::<_, (i64, Vec<u8>, String)>(
+            "SELECT doc_id, embedding, updated_at FROM vectors",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut rows: Vec<(i64, Option<Vec<f32>>, String)> = rows
+            .into_iter()
+            .map(|(doc_id, embedding, updated_at)| (doc_id, decode_blob(&embedding), updated_at))
+            .collect();
+        for table in self.vec_tables().await? {
+            let vec_rows = sqlx::query_as::<_, (i64, Vec<u8>, String)>(&format!(
+                "SELECT doc_id, embedding, updated_at FROM {}",
+                table
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+            rows.extend(vec_rows.into_iter().map(|(doc_id, embedding, updated_at)| {
+                (doc_id, decode_vec(&embedding, self.quantized), updated_at)
+            }));
+        }
+        Ok(rows
+            .into_iter()
+            .filter_map(|(doc_id, embedding, updated_at)| {
+                let embedding = embedding?;
+                let updated_at = DateTime::parse_from_rfc3339(&updated_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+                Some(StoredEmbedding {
+                    doc_id,
                     embedding,
                     updated_at,
                 })
@@ -213,8 +1797,93 @@ impl VectorStore {
         query: &str,
         limit: usize,
     ) -> Result<Vec<(i64, f32)>> {
-        let query_embedding = embedder.embed(query).await?;
-        self.search_with_embedding(&query_embedding, limit).await
+        self.search_semantic_filtered(embedder, query, limit, &SearchFilter::default())
+            .await
+    }
+
+    /// [`Self::search_semantic`] over the documents `filter` admits.
+    pub async fn search_semantic_filtered<E: Embedder + ?Sized>(
+        &self,
+        embedder: &E,
+        query: &str,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(i64, f32)>> {
+        let query_embedding = self.embed_query(embedder, query).await?;
+        let default_model = embedder.model_name();
+        let mut others: Vec<Arc<dyn Embedder>> = Vec::new();
+        for (_, source_embedder) in embedder.source_embedders() {
+            let model = source_embedder.model_name();
+            if model != default_model && !others.iter().any(|other| other.model_name() == model) {
+                others.push(source_embedder);
+            }
+        }
+        if others.is_empty() {
+            return self
+                .search_with_embedding_filtered(&query_embedding, limit, filter)
+                .await;
+        }
+
+        // Models can share a dimension, and so a table; vectors from another
+        // model are filtered out. Documents recorded under no model
+        // predate per-source models and belong to the default space.
+        let other_models: Vec<String> = others.iter().map(|other| other.model_name()).collect();
+        let hits = self
+            .search_with_embedding_filtered(&query_embedding, limit * 4, filter)
+            .await?;
+        let models = self.models_of(&hits).await?;
+        let mut merged: Vec<(i64, f32)> = hits
+            .into_iter()
+            .filter(|(doc_id, _)| models.get(doc_id).is_none_or(|model| !other_models.contains(model)))
+            .collect();
+        for other in &others {
+            let model = other.model_name();
+            let query_embedding = self.embed_query(&**other, query).await?;
+            let hits = self
+                .search_with_embedding_filtered(&query_embedding, limit * 4, filter)
+                .await?;
+            let models = self.models_of(&hits).await?;
+            merged.extend(
+                hits.into_iter()
+                    .filter(|(doc_id, _)| models.get(doc_id) == Some(&model)),
+            );
+        }
+        Ok(best_per_document(merged, limit))
+    }
+
+    /// Embed a search query, reusing a recent embedding of the same query.
+    /// Stand-in models aren't cached; the real one replaces them soon.
+    async fn embed_query<E: Embedder + ?Sized>(
+        &self,
+        embedder: &E,
+        query: &str,
+    ) -> Result<Vec<f32>> {
+        let model = embedder.model_name();
+        if let Some(embedding) = self.query_cache.get(&model, query) {
+            return Ok(embedding);
+        }
+        let embedding = embedder.embed_with_model(query).await?;
+        if !embedding.fallback {
+            self.query_cache.insert(&embedding.model, query, &embedding.vector);
+        }
+        Ok(embedding.vector)
+    }
+
+    /// Recorded model of each hit's document.
+    async fn models_of(&self, hits: &[(i64, f32)]) -> Result<HashMap<i64, String>> {
+        if hits.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders = vec!["?"; hits.len()].join(", ");
+        let sql = format!(
+            "SELECT doc_id, model FROM vector_models WHERE doc_id IN ({})",
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, (i64, String)>(&sql);
+        for (doc_id, _) in hits {
+            query = query.bind(doc_id);
+        }
+        Ok(query.fetch_all(&self.pool).await?.into_iter().collect())
     }
 
     pub async fn search_with_embedding(
@@ -222,25 +1891,197 @@ impl VectorStore {
         query_embedding: &[f32],
         limit: usize,
     ) -> Result<Vec<(i64, f32)>> {
-        if self.sqlite_vec_available {
-            if let Ok(results) = self
+        self.search_with_embedding_filtered(query_embedding, limit, &SearchFilter::default())
+            .await
+    }
+
+    /// Documents whose embedding is at least `threshold` similar to
+    /// `doc_id`'s, most similar first, from the same search (and index) as
+    /// queries. Only documents embedded by the same model are compared.
+    /// Empty if `doc_id` has no embedding.
+    pub async fn find_similar(
+        &self,
+        doc_id: i64,
+        threshold: f32,
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>> {
+        let Some(embedding) = self.get_embedding(doc_id).await? else {
+            return Ok(Vec::new());
+        };
+        let model = self.models_of(&[(doc_id, 1.0)]).await?.remove(&doc_id);
+        // Room for the document itself and neighbours from other models
+        let hits = self
+            .search_with_embedding(&embedding, limit * 4 + 1)
+            .await?;
+        let models = self.models_of(&hits).await?;
+        Ok(hits
+            .into_iter()
+            .filter(|(id, score)| *id != doc_id && *score >= threshold)
+            .filter(|(id, _)| models.get(id) == model.as_ref())
+            .take(limit)
+            .collect())
+    }
+
+    /// Nearest documents among those `filter` admits. Filtered searches are
+    /// exact: they scan the admitted documents' vectors rather than taking
+    /// the nearest neighbours overall and dropping the excluded ones.
+    pub async fn search_with_embedding_filtered(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(i64, f32)>> {
+        if !filter.is_empty() {
+            return self
+                .search_filtered(query_embedding, limit, filter)
+                .await;
+        }
+        // Large corpora go through the HNSW graph once it's built
+        if let Some(hits) = self.ann.search(query_embedding, limit * 4) {
+            return Ok(best_per_document(hits, limit));
+        }
+        if self.ann.start_build() {
+            let store = self.clone();
+            tokio::spawn(async move {
+                if let Err(err) = store.build_ann().await {
+                    warn!("[ANN] Index build failed: {}", err);
+                    store.ann.abandon_build();
+                }
+            });
+        }
+
+        if self.backend == SearchBackend::SqliteVec {
+            match self
                 .search_with_embedding_sqlite_vec(query_embedding, limit)
                 .await
             {
-                return Ok(results);
+                Ok(results) => return Ok(results),
+                Err(err) => warn!(
+                    "[VECTOR] sqlite-vec search failed, using brute force: {}",
+                    err
+                ),
             }
         }
+        self.search_brute_force(query_embedding, limit).await
+    }
+
+    /// Compare the query with every document and chunk embedding of its
+    /// dimension; the rest came from another model.
+    async fn search_brute_force(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>> {
         let embeddings = self.list_embeddings().await?;
-        let mut scored: Vec<(i64, f32)> = embeddings
+        let chunks = self.list_chunk_embeddings().await?;
+        let hits = embeddings
             .into_iter()
-            .map(|row| {
-                let score = cosine_similarity(query_embedding, &row.embedding);
-                (row.doc_id, score)
-            })
+            .map(|row| (row.doc_id, row.embedding))
+            .chain(chunks)
+            .filter(|(_, embedding)| embedding.len() == query_embedding.len())
+            .map(|(doc_id, embedding)| (doc_id, cosine_similarity(query_embedding, &embedding)));
+        Ok(best_per_document(hits, limit))
+    }
+
+    async fn search_filtered(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(i64, f32)>> {
+        // vec0 tables can only be filtered in SQL, whichever backend is faster
+        if self.sqlite_vec_available {
+            match self
+                .search_filtered_sqlite_vec(query_embedding, limit, filter)
+                .await
+            {
+                Ok(results) => return Ok(results),
+                Err(err) => warn!("[VECTOR] Filtered sqlite-vec search failed: {}", err),
+            }
+        }
+        let (conditions, binds) = filter.where_sql();
+        let mut hits = Vec::new();
+        for table in ["vectors", "vector_chunks"] {
+            let sql = format!(
+                "SELECT v.doc_id, v.embedding FROM {} v JOIN documents d ON d.id = v.doc_id \
+                WHERE {}",
+                table, conditions
+            );
+            let mut query = sqlx::query_as::<_, (i64, Vec<u8>)>(&sql);
+            for bind in &binds {
+                query = query.bind(bind);
+            }
+            let rows = query.fetch_all(&self.pool).await?;
+            hits.extend(rows.into_iter().filter_map(|(doc_id, embedding)| {
+                let embedding = decode_blob(&embedding)?;
+                (embedding.len() == query_embedding.len())
+                    .then(|| (doc_id, cosine_similarity(query_embedding, &embedding)))
+            }));
+        }
+        Ok(best_per_document(hits, limit))
+    }
+
+    /// Build the ANN graphs if the corpus is large enough; the search that
+    /// triggered it is answered exactly.
+    async fn build_ann(&self) -> Result<()> {
+        let documents = self.count().await?;
+        let Some(min_documents) = self.ann.min_documents() else {
+            return Ok(());
+        };
+        if (documents as usize) < min_documents {
+            self.ann.abandon_build();
+            return Ok(());
+        }
+
+        let started = std::time::Instant::now();
+        let mut vectors: Vec<(i64, bool, Vec<f32>)> = self
+            .list_embeddings()
+            .await?
+            .into_iter()
+            .map(|row| (row.doc_id, false, row.embedding))
             .collect();
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        scored.truncate(limit);
-        Ok(scored)
+        vectors.extend(
+            self.list_chunk_embeddings()
+                .await?
+                .into_iter()
+                .map(|(doc_id, embedding)| (doc_id, true, embedding)),
+        );
+        let count = vectors.len();
+        let indexes = task::spawn_blocking(move || ann::build(vectors)).await?;
+        self.ann.finish_build(indexes);
+        info!(
+            "[ANN] Indexed {} vectors of {} documents in {:?}",
+            count,
+            documents,
+            started.elapsed()
+        );
+        Ok(())
+    }
+
+    /// `(doc_id, embedding)` of every chunk embedding.
+    async fn list_chunk_embeddings(&self) -> Result<Vec<(i64, Vec<f32>)>> {
+        let rows =
+            sqlx::query_as::<_, (i64, Vec<u8>)>("SELECT doc_id, embedding FROM vector_chunks")
+                .fetch_all(&self.pool)
+                .await?;
+        let mut chunks: Vec<(i64, Vec<f32>)> = rows
+            .into_iter()
+            .filter_map(|(doc_id, embedding)| Some((doc_id, decode_blob(&embedding)?)))
+            .collect();
+        if self.sqlite_vec_available {
+            for dims in self.known_vec_dims(&self.pool).await? {
+                let rows = sqlx::query_as::<_, (i64, Vec<u8>)>(&format!(
+                    "SELECT doc_id, embedding FROM {}",
+                    chunk_table(dims, self.quantized)
+                ))
+                .fetch_all(&self.pool)
+                .await?;
+                chunks.extend(rows.into_iter().filter_map(|(doc_id, embedding)| {
+                    Some((doc_id, decode_vec(&embedding, self.quantized)?))
+                }));
+            }
+        }
+        Ok(chunks)
     }
 
     pub async fn cluster_documents(
@@ -276,12 +2117,71 @@ impl VectorStore {
                 results.push(Cluster {
                     label: format!("Cluster {}", idx + 1),
                     doc_ids,
+                    summary: None,
                 });
             }
         }
         Ok(results)
     }
 
+    /// Add documents embedded after `since` to the existing `clusters`, each
+    /// to the cluster holding its most similar member if that similarity
+    /// reaches `min_similarity` (the linkage rule of
+    /// [`Self::cluster_documents`]). New documents that only resemble each
+    /// other, or that would join two clusters, wait for the next full pass.
+    /// Members whose embedding is gone (purged documents) are dropped first.
+    /// Returns the indices of the clusters that changed.
+    pub async fn assign_to_clusters(
+        &self,
+        clusters: &mut [Cluster],
+        since: DateTime<Utc>,
+        min_similarity: f32,
+    ) -> Result<Vec<usize>> {
+        let embeddings = self.list_embeddings().await?;
+        let by_id: HashMap<i64, &Vec<f32>> = embeddings
+            .iter()
+            .map(|stored| (stored.doc_id, &stored.embedding))
+            .collect();
+        let mut changed = Vec::new();
+        let mut clustered: HashMap<i64, usize> = HashMap::new();
+        for (idx, cluster) in clusters.iter_mut().enumerate() {
+            let members = cluster.doc_ids.len();
+            cluster.doc_ids.retain(|doc_id| by_id.contains_key(doc_id));
+            if cluster.doc_ids.len() < members {
+                changed.push(idx);
+            }
+            for doc_id in &cluster.doc_ids {
+                clustered.insert(*doc_id, idx);
+            }
+        }
+
+        for stored in embeddings.iter().filter(|stored| stored.updated_at > since) {
+            if clustered.contains_key(&stored.doc_id) {
+                continue;
+            }
+            let mut best: Option<(usize, f32)> = None;
+            for (idx, cluster) in clusters.iter().enumerate() {
+                for member in &cluster.doc_ids {
+                    let Some(embedding) = by_id.get(member) else {
+                        continue;
+                    };
+                    let sim = cosine_similarity(&stored.embedding, embedding);
+                    if sim >= min_similarity && best.is_none_or(|(_, best_sim)| sim > best_sim) {
+                        best = Some((idx, sim));
+                    }
+                }
+            }
+            if let Some((idx, _)) = best {
+                clusters[idx].doc_ids.push(stored.doc_id);
+                clustered.insert(stored.doc_id, idx);
+                if !changed.contains(&idx) {
+                    changed.push(idx);
+                }
+            }
+        }
+        Ok(changed)
+    }
+
     async fn detect_sqlite_vec(&self) -> Result<bool> {
         let version = sqlx::query_scalar::<_, String>("SELECT vec_version()")
             .fetch_one(&self.pool)
@@ -293,21 +2193,130 @@ impl VectorStore {
         Ok(false)
     }
 
+    /// KNN over the `vec0` table of the query's dimension. Embeddings of
+    /// another dimension come from the other model and aren't comparable;
+    /// they're re-embedded from the re-embed queue.
+    async fn search_filtered_sqlite_vec(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(i64, f32)>> {
+        let known = self.known_vec_dims(&self.pool).await?;
+        if !known.contains(&query_embedding.len()) {
+            return Ok(Vec::new());
+        }
+        let (conditions, binds) = filter.where_sql();
+        let payload = vec_payload(query_embedding, self.quantized)?;
+        let mut hits = Vec::new();
+        for (table, k) in [
+            (vec_table(query_embedding.len(), self.quantized), limit),
+            (chunk_table(query_embedding.len(), self.quantized), limit * 4),
+        ] {
+            let sql = format!(
+                "SELECT v.doc_id, vec_distance_cosine(v.embedding, {}(?)) AS distance \
+                FROM {} v JOIN documents d ON d.id = v.doc_id WHERE {} \
+                ORDER BY distance LIMIT ?",
+                vec_constructor(self.quantized),
+                table,
+                conditions
+            );
+            let mut query = sqlx::query_as::<_, (i64, f32)>(&sql).bind(&payload);
+            for bind in &binds {
+                query = query.bind(bind);
+            }
+            let rows = query.bind(k as i64).fetch_all(&self.pool).await?;
+            hits.extend(rows.into_iter().map(|(doc_id, distance)| (doc_id, 1.0 - distance)));
+        }
+        Ok(best_per_document(hits, limit))
+    }
+
     async fn search_with_embedding_sqlite_vec(
         &self,
         query_embedding: &[f32],
         limit: usize,
     ) -> Result<Vec<(i64, f32)>> {
-        let payload = serde_json::to_string(query_embedding)?;
-        let rows = sqlx::query_as::<_, (i64, f32)>(
-            "SELECT doc_id, (1.0 - vec_distance_cosine(vec_f32(?1), vec_f32(embedding))) as score \
-            FROM vectors ORDER BY score DESC LIMIT ?2",
-        )
-        .bind(payload)
-        .bind(limit as i64)
+        let known = self.known_vec_dims(&self.pool).await?;
+        if !known.contains(&query_embedding.len()) {
+            return Ok(Vec::new());
+        }
+        // Against int8 tables the query is quantized too; cosine distance
+        // between the quantized vectors equals that of the dequantized ones
+        let payload = vec_payload(query_embedding, self.quantized)?;
+        let constructor = vec_constructor(self.quantized);
+        let rows = sqlx::query_as::<_, (i64, f32)>(&format!(
+            "SELECT doc_id, distance FROM {} WHERE embedding MATCH {}(?1) AND k = ?2 \
+            ORDER BY distance",
+            vec_table(query_embedding.len(), self.quantized),
+            constructor
+        ))
+        .bind(&payload)
+        .bind(limit.min(MAX_KNN) as i64)
         .fetch_all(&self.pool)
         .await?;
-        Ok(rows)
+        // Several chunks of one document can crowd the nearest neighbours
+        let chunk_rows = sqlx::query_as::<_, (i64, f32)>(&format!(
+            "SELECT doc_id, distance FROM {} WHERE embedding MATCH {}(?1) AND k = ?2 \
+            ORDER BY distance",
+            chunk_table(query_embedding.len(), self.quantized),
+            constructor
+        ))
+        .bind(&payload)
+        .bind((limit * 4).min(MAX_KNN) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        let hits = rows
+            .into_iter()
+            .chain(chunk_rows)
+            .map(|(doc_id, distance)| (doc_id, 1.0 - distance));
+        Ok(best_per_document(hits, limit))
+    }
+}
+
+#[async_trait]
+impl VectorIndex for VectorStore {
+    async fn upsert_embedding(&self, doc_id: i64, embedding: &[f32]) -> Result<()> {
+        VectorStore::upsert_embedding(self, doc_id, embedding).await
+    }
+
+    async fn queue_reembed(&self, doc_id: i64) -> Result<()> {
+        VectorStore::queue_reembed(self, doc_id).await
+    }
+
+    async fn delete_embeddings(&self, doc_ids: &[i64]) -> Result<()> {
+        VectorStore::delete_embeddings(self, doc_ids).await
+    }
+
+    async fn get_embedding(&self, doc_id: i64) -> Result<Option<Vec<f32>>> {
+        VectorStore::get_embedding(self, doc_id).await
+    }
+
+    async fn record_model(&self, doc_id: i64, model: &str, dims: usize) -> Result<()> {
+        VectorStore::record_model(self, doc_id, model, dims).await
+    }
+
+    async fn upsert_chunk_embeddings(&self, doc_id: i64, embeddings: &[Vec<f32>]) -> Result<()> {
+        VectorStore::upsert_chunk_embeddings(self, doc_id, embeddings).await
+    }
+
+    async fn search_with_embedding(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>> {
+        VectorStore::search_with_embedding(self, query_embedding, limit).await
+    }
+
+    async fn pending_reembed(&self, limit: usize) -> Result<Vec<i64>> {
+        VectorStore::pending_reembed(self, limit).await
+    }
+
+    async fn dequeue_reembed(&self, doc_id: i64) -> Result<()> {
+        VectorStore::dequeue_reembed(self, doc_id).await
+    }
+
+    async fn scrub_orphaned_embeddings(&self) -> Result<u64> {
+        VectorStore::scrub_orphaned_embeddings(self).await
     }
 }
 
@@ -320,8 +2329,13 @@ fn normalize(vec: &mut [f32]) {
     }
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    let len = a.len().min(b.len());
+/// Cosine similarity of two embeddings; 0 if either is all zeros or they
+/// have different dimensions, since they then come from different models.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let len = a.len();
     let mut dot = 0.0;
     let mut norm_a = 0.0;
     let mut norm_b = 0.0;
@@ -358,11 +2372,16 @@ pub fn embedder_from_env() -> Result<Arc<dyn Embedder>> {
     if backend.eq_ignore_ascii_case("hash") {
         return Ok(Arc::new(HashEmbedder::default()));
     }
+    if remote_backend() {
+        return Ok(Arc::new(RemoteEmbedder::from_env(None)?));
+    }
+    if ollama_backend() {
+        return Ok(Arc::new(OllamaEmbedder::from_env()?));
+    }
 
     let model_name =
         std::env::var("MINNA_EMBED_MODEL").unwrap_or_else(|_| "nomic-embed-text-v1.5".to_string());
-    let model = EmbeddingModel::from_str(&model_name)
-        .unwrap_or(EmbeddingModel::NomicEmbedTextV15);
+    let model = EmbeddingModel::from_str(&model_name).unwrap_or(EmbeddingModel::NomicEmbedTextV15);
     let cache_dir = std::env::var("MINNA_EMBED_CACHE_DIR")
         .ok()
         .map(PathBuf::from);
@@ -370,6 +2389,39 @@ pub fn embedder_from_env() -> Result<Arc<dyn Embedder>> {
     Ok(Arc::new(embedder))
 }
 
+/// Load a model by name for a single source. `hash-<dims>` is the hash
+/// embedder at that size; other names are fastembed models, models of the
+/// remote endpoint under `MINNA_EMBED_BACKEND=remote` (using `api_key`),
+/// Ollama models under `MINNA_EMBED_BACKEND=ollama`, or the hash embedder
+/// under `MINNA_EMBED_BACKEND=hash`. Blocks while the model
+/// loads.
+pub fn embedder_for_model(name: &str, api_key: Option<String>) -> Result<Arc<dyn Embedder>> {
+    if let Some(dims) = name.strip_prefix("hash-") {
+        let dims = dims
+            .parse()
+            .ok()
+            .filter(|dims| *dims > 0)
+            .ok_or_else(|| anyhow!("invalid hash embedder size in {:?}", name))?;
+        return Ok(Arc::new(HashEmbedder { dims }));
+    }
+    let backend = std::env::var("MINNA_EMBED_BACKEND").unwrap_or_else(|_| "fastembed".to_string());
+    if backend.eq_ignore_ascii_case("hash") {
+        return Ok(Arc::new(HashEmbedder::default()));
+    }
+    if remote_backend() {
+        return Ok(Arc::new(RemoteEmbedder::from_env(api_key)?.with_model(name)));
+    }
+    if ollama_backend() {
+        return Ok(Arc::new(OllamaEmbedder::from_env()?.with_model(name)));
+    }
+    let model = EmbeddingModel::from_str(name)
+        .map_err(|_| anyhow!("unknown embedding model {:?}", name))?;
+    let cache_dir = std::env::var("MINNA_EMBED_CACHE_DIR")
+        .ok()
+        .map(PathBuf::from);
+    Ok(Arc::new(FastEmbedder::new(model, cache_dir)?))
+}
+
 pub fn embedder_from_env_or_hash() -> Arc<dyn Embedder> {
     match embedder_from_env() {
         Ok(embedder) => embedder,
@@ -380,12 +2432,487 @@ pub fn embedder_from_env_or_hash() -> Arc<dyn Embedder> {
     }
 }
 
+/// Build an embedder that never blocks startup on a model download.
+///
+/// Waits up to `wait` for the configured model to load. If it isn't ready by
+/// then, the hash fallback is served through a `StandbyEmbedder` and the model
+/// keeps loading in the background, promoting itself when done. Returns the
+/// standby handle (if one was needed) so callers can tell when it warms up.
+pub fn embedder_with_standby(wait: Duration) -> (Arc<dyn Embedder>, Option<Arc<StandbyEmbedder>>) {
+    let backend = std::env::var("MINNA_EMBED_BACKEND").unwrap_or_else(|_| "fastembed".to_string());
+    if backend.eq_ignore_ascii_case("hash") {
+        return (Arc::new(HashEmbedder::default()), None);
+    }
+    // Nothing to download; a bad configuration falls back to hashing
+    if remote_backend() || ollama_backend() {
+        return (embedder_from_env_or_hash(), None);
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let standby = Arc::new(StandbyEmbedder::new(Arc::new(HashEmbedder::default())));
+    let loader = standby.clone();
+    std::thread::spawn(move || {
+        let loaded = embedder_from_env();
+        match loaded {
+            Ok(embedder) => {
+                loader.promote(embedder.clone());
+                // Receiver is gone if startup already moved on with the standby.
+                if tx.send(embedder).is_err() {
+                    info!("embedding model ready, standby promoted");
+                }
+            }
+            Err(err) => {
+                warn!("fast embedding unavailable: {}", err);
+                loader.fail();
+            }
+        }
+    });
+
+    match rx.recv_timeout(wait) {
+        Ok(embedder) => (embedder, None),
+        Err(_) => {
+            warn!("embedding model not ready, serving hash fallback until it loads");
+            (standby.clone(), Some(standby))
+        }
+    }
+}
+
 #[allow(clippy::missing_transmute_annotations)]
 fn register_sqlite_vec() {
     static INIT: Once = Once::new();
     INIT.call_once(|| unsafe {
-        let _ = sqlite3_auto_extension(Some(std::mem::transmute(
-            sqlite3_vec_init as *const (),
-        )));
+        let _ = sqlite3_auto_extension(Some(std::mem::transmute(sqlite3_vec_init as *const ())));
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{OnceLock, Weak};
+
+    use tokio::time::{sleep, Duration, Instant};
+
+    /// Promotes the standby embedder it serves as the fallback for while
+    /// embedding, as the model loader thread can.
+    struct PromotingEmbedder {
+        standby: OnceLock<Weak<StandbyEmbedder>>,
+    }
+
+    #[async_trait]
+    impl Embedder for PromotingEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if let Some(standby) = self.standby.get().and_then(Weak::upgrade) {
+                standby.promote(Arc::new(HashEmbedder { dims: 16 }));
+            }
+            HashEmbedder { dims: 8 }.embed(text).await
+        }
+
+        fn model_name(&self) -> String {
+            "hash-8".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_standby_promotion() {
+        let standby = StandbyEmbedder::new(Arc::new(HashEmbedder { dims: 8 }));
+        let embedding = standby.embed_with_model("release notes").await.unwrap();
+        assert!(embedding.fallback);
+        assert_eq!((embedding.model.as_str(), embedding.vector.len()), ("hash-8", 8));
+
+        standby.promote(Arc::new(HashEmbedder { dims: 16 }));
+        assert!(standby.wait_ready().await);
+        let embedding = standby.embed_with_model("release notes").await.unwrap();
+        assert!(!embedding.fallback);
+        assert_eq!((embedding.model.as_str(), embedding.vector.len()), ("hash-16", 16));
+    }
+
+    #[tokio::test]
+    async fn test_standby_load_failure() {
+        let standby = StandbyEmbedder::new(Arc::new(HashEmbedder { dims: 8 }));
+        standby.fail();
+        assert!(!standby.wait_ready().await);
+        assert!(standby.is_fallback());
+    }
+
+    #[tokio::test]
+    async fn test_promotion_during_embed_reports_fallback() {
+        let fallback = Arc::new(PromotingEmbedder { standby: OnceLock::new() });
+        let standby = Arc::new(StandbyEmbedder::new(fallback.clone()));
+        let _ = fallback.standby.set(Arc::downgrade(&standby));
+
+        let embedding = standby.embed_with_model("release notes").await.unwrap();
+        assert!(standby.is_warm());
+        assert!(embedding.fallback);
+        assert_eq!((embedding.model.as_str(), embedding.vector.len()), ("hash-8", 8));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_documents_queued_for_reembedding() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VectorStore::new(&dir.path().join("minna.db")).await.unwrap();
+        let fallback = Arc::new(PromotingEmbedder { standby: OnceLock::new() });
+        let standby = Arc::new(StandbyEmbedder::new(fallback.clone()));
+        let _ = fallback.standby.set(Arc::downgrade(&standby));
+
+        // Promoted while embedding: still a fallback vector, so queued
+        embed_document(standby.as_ref(), &store, 1, "slack", "launch plan").await.unwrap();
+        assert_eq!(store.pending_reembed(10).await.unwrap(), vec![1]);
+        assert_eq!(store.get_embedding(1).await.unwrap().unwrap().len(), 8);
+
+        embed_document(standby.as_ref(), &store, 2, "slack", "launch plan").await.unwrap();
+        assert_eq!(store.pending_reembed(10).await.unwrap(), vec![1]);
+        assert_eq!(store.get_embedding(2).await.unwrap().unwrap().len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_search_backends_agree() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db")).await?;
+        let embedder = HashEmbedder::default();
+        let filler = "Standup notes: reviewed the sprint board and moved tickets along.\n\n".repeat(100);
+        let bodies = [
+            "billing service rollback checklist".to_string(),
+            "onboarding guide for new engineers".to_string(),
+            format!("{}The billing rollback was rehearsed in staging.", filler),
+        ];
+        for (id, body) in bodies.iter().enumerate() {
+            embed_document(&embedder, &vector, id as i64 + 1, "mirror", body).await?;
+        }
+
+        let query = embedder.embed("billing rollback").await?;
+        let chosen = vector.search_with_embedding(&query, 3).await?;
+        let brute_force = vector
+            .clone()
+            .with_search_backend(SearchBackend::BruteForce)
+            .search_with_embedding(&query, 3)
+            .await?;
+        let ids = |hits: &[(i64, f32)]| hits.iter().map(|hit| hit.0).collect::<Vec<_>>();
+        assert_eq!(ids(&chosen), ids(&brute_force), "{:?} vs {:?}", chosen, brute_force);
+        for ((_, a), (_, b)) in chosen.iter().zip(&brute_force) {
+            assert!((a - b).abs() < 1e-3, "{:?} vs {:?}", chosen, brute_force);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_clustering() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db")).await?;
+        let embedder = HashEmbedder::default();
+        let texts = [
+            "kafka consumer lag alert on the billing cluster",
+            "kafka consumer lag alert on the billing cluster again",
+            "kafka consumer lag alert on the billing cluster overnight",
+            "quarterly planning offsite agenda and travel",
+            "quarterly planning offsite agenda and hotel",
+            "quarterly planning offsite agenda and venue",
+        ];
+        for (id, text) in texts.iter().enumerate() {
+            vector.upsert_embedding(id as i64 + 1, &embedder.embed(text).await?).await?;
+        }
+        let mut clusters = vector.cluster_documents(0.8, 2).await?;
+        assert_eq!(clusters.len(), 2, "clusters: {:?}", clusters);
+
+        let since = chrono::Utc::now();
+        sleep(Duration::from_millis(10)).await;
+        let lag = embedder.embed("kafka consumer lag alert on the billing cluster today").await?;
+        vector.upsert_embedding(7, &lag).await?;
+        let unrelated = embedder.embed("new laptop setup checklist").await?;
+        vector.upsert_embedding(8, &unrelated).await?;
+
+        let grown = vector.assign_to_clusters(&mut clusters, since, 0.8).await?;
+        assert_eq!(grown.len(), 1);
+        let kafka = &clusters[grown[0]];
+        assert!(kafka.doc_ids.contains(&1) && kafka.doc_ids.contains(&7), "{:?}", kafka);
+        assert!(clusters.iter().all(|cluster| !cluster.doc_ids.contains(&8)));
+
+        // Already assigned documents are left alone
+        let grown = vector.assign_to_clusters(&mut clusters, since, 0.8).await?;
+        assert!(grown.is_empty());
+
+        // Purged members drop out
+        vector.delete_embeddings(&[7]).await?;
+        let changed = vector.assign_to_clusters(&mut clusters, since, 0.8).await?;
+        assert_eq!(changed.len(), 1);
+        assert!(clusters.iter().all(|cluster| !cluster.doc_ids.contains(&7)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_embeddings_become_blobs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("minna.db");
+        let embedder = HashEmbedder::default();
+        let legacy = embedder.embed("runbook for rotating the database credentials").await?;
+        {
+            let vector = VectorStore::new(&path).await?;
+            vector.upsert_embedding(1, &embedder.embed("team lunch menu").await?).await?;
+            // Rows as written before embeddings were stored as BLOBs
+            sqlx::query("INSERT INTO vectors (doc_id, embedding, updated_at) VALUES (2, ?1, ?2)")
+                .bind(serde_json::to_string(&legacy)?)
+                .bind(chrono::Utc::now().to_rfc3339())
+                .execute(vector.pool())
+                .await?;
+            sqlx::query("INSERT INTO vector_chunks (doc_id, chunk_idx, embedding) VALUES (3, 0, ?1)")
+                .bind(serde_json::to_string(&legacy)?)
+                .execute(vector.pool())
+                .await?;
+            sqlx::query("INSERT INTO vectors (doc_id, embedding, updated_at) VALUES (4, '[0.1, oops', ?1)")
+                .bind(chrono::Utc::now().to_rfc3339())
+                .execute(vector.pool())
+                .await?;
+        }
+
+        let vector = VectorStore::new(&path).await?;
+        let text_rows: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM vectors WHERE typeof(embedding) = 'text') + \
+            (SELECT COUNT(*) FROM vector_chunks WHERE typeof(embedding) = 'text')",
+        )
+        .fetch_one(vector.pool())
+        .await?;
+        assert_eq!(text_rows, 0);
+        assert_eq!(vector.get_embedding(2).await?, Some(legacy.clone()));
+        // The unreadable row is gone, and its document queued to replace it
+        assert_eq!(vector.get_embedding(4).await?, None);
+        assert_eq!(vector.pending_reembed(10).await?, vec![4]);
+        let hits = vector.search_with_embedding(&legacy, 2).await?;
+        let ids: Vec<i64> = hits.iter().map(|hit| hit.0).collect();
+        assert!(ids.contains(&2) && ids.contains(&3), "hits: {:?}", hits);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vec0_tables() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("minna.db");
+        let embedder = HashEmbedder { dims: 16 };
+        let runbook = embedder.embed("runbook for rotating the database credentials").await?;
+        {
+            let vector = VectorStore::new(&path).await?;
+            if !vector.sqlite_vec_available {
+                return Ok(());
+            }
+            // Rows left in `vectors` from before vec0 tables existed
+            for (doc_id, blob) in [(1, embedding_blob(&runbook)), (4, vec![0x01, 0x02, 0x03])] {
+                sqlx::query("INSERT INTO vectors (doc_id, embedding, updated_at) VALUES (?1, ?2, ?3)")
+                    .bind(doc_id)
+                    .bind(blob)
+                    .bind(Utc::now().to_rfc3339())
+                    .execute(vector.pool())
+                    .await?;
+            }
+        }
+
+        let vector = VectorStore::new(&path).await?;
+        let legacy_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM vectors").fetch_one(vector.pool()).await?;
+        assert_eq!(legacy_rows, 0);
+        assert_eq!(vector.known_vec_dims(vector.pool()).await?, vec![16]);
+        assert_eq!(vector.get_embedding(1).await?, Some(runbook.clone()));
+        assert_eq!(vector.pending_reembed(10).await?, vec![4]);
+
+        // Each dimension gets its own table; KNN only sees the query's
+        vector.upsert_embedding(2, &embedder.embed("team lunch menu").await?).await?;
+        vector.upsert_embedding(3, &HashEmbedder { dims: 8 }.embed("database credentials").await?).await?;
+        let mut dims = vector.known_vec_dims(vector.pool()).await?;
+        dims.sort_unstable();
+        assert_eq!(dims, vec![8, 16]);
+        assert_eq!(vector.count().await?, 3);
+        let query = embedder.embed("database credentials").await?;
+        for backend in [SearchBackend::SqliteVec, SearchBackend::BruteForce] {
+            let hits = vector.clone().with_search_backend(backend).search_with_embedding(&query, 5).await?;
+            assert_eq!(hits.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![1, 2], "{backend:?}");
+        }
+
+        vector.delete_embeddings(&[1]).await?;
+        assert_eq!(vector.get_embedding(1).await?, None);
+        assert_eq!(vector.count().await?, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vec0_tables_created_by_another_store() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("minna.db");
+        let embedder = HashEmbedder { dims: 16 };
+        let daemon = VectorStore::new(&path).await?;
+        if !daemon.sqlite_vec_available {
+            return Ok(());
+        }
+
+        // E.g. the CLI embedding while the daemon runs
+        let cli = VectorStore::new(&path).await?;
+        let runbook = embedder.embed("runbook for rotating the database credentials").await?;
+        cli.upsert_embedding(1, &runbook).await?;
+        assert_eq!(daemon.get_embedding(1).await?, Some(runbook.clone()));
+        assert_eq!(daemon.count().await?, 1);
+        let hits = daemon.search_with_embedding(&runbook, 5).await?;
+        assert_eq!(hits.iter().map(|hit| hit.0).collect::<Vec<_>>(), vec![1]);
+
+        // Re-embedding at another dimension replaces the row the CLI wrote
+        daemon.upsert_embedding(1, &HashEmbedder { dims: 8 }.embed("runbook").await?).await?;
+        assert_eq!(cli.count().await?, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_documents() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db")).await?;
+        let embedder = HashEmbedder::default();
+        let texts = [
+            "postmortem for the billing outage on tuesday",
+            "postmortem for the billing outage on tuesday night",
+            "postmortem for the billing outage",
+            "recipe for banana bread",
+        ];
+        for (id, text) in texts.iter().enumerate() {
+            vector.upsert_embedding(id as i64 + 1, &embedder.embed(text).await?).await?;
+        }
+
+        let similar = vector.find_similar(1, 0.5, 5).await?;
+        let ids: Vec<i64> = similar.iter().map(|hit| hit.0).collect();
+        assert_eq!(ids.len(), 2, "similar: {:?}", similar);
+        assert!(ids.contains(&2) && ids.contains(&3));
+        assert!(similar[0].1 >= similar[1].1);
+        assert_eq!(vector.find_similar(1, 0.5, 1).await?.len(), 1);
+        assert!(vector.find_similar(99, 0.5, 5).await?.is_empty());
+        Ok(())
+    }
+
+    /// Counts the texts it embeds.
+    #[derive(Default)]
+    struct CountingEmbedder {
+        inner: HashEmbedder,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Embedder for CountingEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.embed(text).await
+        }
+
+        fn model_name(&self) -> String {
+            self.inner.model_name()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_embeddings_are_cached() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db"))
+            .await?
+            .with_query_cache(2, Duration::from_millis(200));
+        let embedder = CountingEmbedder::default();
+        let calls = || embedder.calls.load(Ordering::SeqCst);
+        vector.upsert_embedding(1, &embedder.inner.embed("deploy checklist").await?).await?;
+
+        let first = vector.search_semantic(&embedder, "deploy checklist", 5).await?;
+        let again = vector.search_semantic(&embedder, "deploy checklist", 5).await?;
+        assert_eq!(calls(), 1);
+        assert_eq!(first, again);
+
+        // The least recently used query makes room
+        vector.search_semantic(&embedder, "incident review", 5).await?;
+        vector.search_semantic(&embedder, "deploy checklist", 5).await?;
+        vector.search_semantic(&embedder, "oncall rotation", 5).await?;
+        assert_eq!(calls(), 3);
+        vector.search_semantic(&embedder, "incident review", 5).await?;
+        assert_eq!(calls(), 4);
+
+        // And entries expire
+        sleep(Duration::from_millis(250)).await;
+        vector.search_semantic(&embedder, "oncall rotation", 5).await?;
+        assert_eq!(calls(), 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ann_index_tracks_upserts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db"))
+            .await?
+            .with_ann_min_documents(Some(100));
+        let embedder = HashEmbedder::default();
+
+        for id in 1..=300i64 {
+            let text = format!("ticket {} about service {} owned by team {}", id, id % 17, id % 5);
+            vector.upsert_embedding(id, &embedder.embed(&text).await?).await?;
+        }
+        let query = embedder.embed("ticket 42 about service 8 owned by team 2").await?;
+        let exact = vector.search_with_embedding(&query, 5).await?;
+        assert_eq!(exact.first().map(|hit| hit.0), Some(42));
+
+        // The first search builds the graph in the background
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while vector.ann_vectors().is_none() {
+            assert!(Instant::now() < deadline, "ANN index was not built");
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(vector.ann_vectors(), Some(300));
+        let approximate = vector.search_with_embedding(&query, 5).await?;
+        assert_eq!(approximate.first().map(|hit| hit.0), Some(42));
+
+        // Upserts and deletes reach the graph without a rebuild
+        let fresh = embedder.embed("pager escalation policy for the storage team").await?;
+        vector.upsert_embedding(301, &fresh).await?;
+        assert_eq!(vector.ann_vectors(), Some(301));
+        let hits = vector.search_with_embedding(&fresh, 1).await?;
+        assert_eq!(hits.first().map(|hit| hit.0), Some(301));
+        vector.delete_embeddings(&[301]).await?;
+        let hits = vector.search_with_embedding(&fresh, 1).await?;
+        assert_ne!(hits.first().map(|hit| hit.0), Some(301));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_excluded_documents_leave_the_budget() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vector = VectorStore::new(&dir.path().join("minna.db")).await?;
+        // Filtered searches join the ingest database's documents table
+        sqlx::query(
+            "CREATE TABLE documents (id INTEGER PRIMARY KEY, uri TEXT, source TEXT, updated_at TEXT, deleted_at TEXT)",
+        )
+        .execute(vector.pool())
+        .await?;
+        let embedder = HashEmbedder::default();
+        let bodies = ["billing rollback checklist", "billing rollback notes", "team lunch menu"];
+        for (id, body) in bodies.iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO documents (id, uri, source, updated_at) VALUES (?1, ?2, 'mock', ?3)")
+                .bind(id)
+                .bind(format!("mock://{}", id))
+                .bind(chrono::Utc::now().to_rfc3339())
+                .execute(vector.pool())
+                .await?;
+            vector.upsert_embedding(id, &embedder.embed(body).await?).await?;
+        }
+
+        let query = embedder.embed("billing rollback checklist").await?;
+        let ids = |hits: Vec<(i64, f32)>| hits.into_iter().map(|hit| hit.0).collect::<Vec<_>>();
+        assert_eq!(ids(vector.search_with_embedding(&query, 1).await?), vec![1]);
+        let seen = SearchFilter { excluded_doc_ids: vec![1], ..SearchFilter::default() };
+        assert_eq!(ids(vector.search_with_embedding_filtered(&query, 1, &seen).await?), vec![2]);
+        let pack = SearchFilter { doc_ids: Some(vec![1, 3]), excluded_doc_ids: vec![1], ..SearchFilter::default() };
+        assert_eq!(ids(vector.search_with_embedding_filtered(&query, 5, &pack).await?), vec![3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_execution_providers() {
+        let names = ["coreml", "cuda"];
+        assert_eq!(select_providers(None, &names), names);
+        assert_eq!(select_providers(Some("auto"), &names), names);
+        assert_eq!(select_providers(Some("CUDA"), &names), ["cuda"]);
+        assert!(select_providers(Some("cpu"), &names).is_empty());
+        assert!(select_providers(Some("rocm"), &names).is_empty());
+
+        // Providers without their feature are never built
+        #[cfg(not(feature = "cuda"))]
+        assert!(execution_provider("cuda").is_none());
+        assert!(execution_provider("cpu").is_none());
+    }
+}
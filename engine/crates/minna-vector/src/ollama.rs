@@ -0,0 +1,92 @@
+//! Embeddings from a local Ollama server (`MINNA_EMBED_BACKEND=ollama`), for
+//! users who already run one and would rather not download an ONNX model.
+//!
+//! `MINNA_OLLAMA_URL` is the server (`http://localhost:11434` by default) and
+//! `MINNA_EMBED_MODEL` the model, which must already be pulled.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::Embedder;
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "nomic-embed-text";
+// Ollama loads the model on first use, which can take a while on a cold start.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Whether `MINNA_EMBED_BACKEND=ollama`.
+pub fn ollama_backend() -> bool {
+    std::env::var("MINNA_EMBED_BACKEND")
+        .map(|backend| backend.eq_ignore_ascii_case("ollama"))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone)]
+pub struct OllamaEmbedder {
+    client: Client,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl OllamaEmbedder {
+    pub fn from_env() -> Result<Self> {
+        let base_url = std::env::var("MINNA_OLLAMA_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let model = std::env::var("MINNA_EMBED_MODEL")
+            .ok()
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+        })
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl Embedder for OllamaEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&json!({ "model": self.model, "input": text }))
+            .send()
+            .await
+            .map_err(|err| anyhow!("ollama unreachable at {}: {}", self.base_url, err))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("ollama embed failed ({}): {}", status, body));
+        }
+        let parsed: EmbedResponse = response.json().await?;
+        parsed
+            .embeddings
+            .into_iter()
+            .next()
+            .filter(|embedding| !embedding.is_empty())
+            .ok_or_else(|| anyhow!("ollama returned no embedding"))
+    }
+
+    fn model_name(&self) -> String {
+        self.model.clone()
+    }
+}
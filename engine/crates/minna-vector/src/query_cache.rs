@@ -0,0 +1,108 @@
+//! Recent query embeddings.
+//!
+//! An agent tends to ask the same or a slightly reworded question several
+//! times in a row, and embedding the query is the slowest part of a search
+//! with a local model. The last `MINNA_QUERY_CACHE_SIZE` query embeddings
+//! (64 by default) are kept for `MINNA_QUERY_CACHE_TTL_SECS` (5 minutes);
+//! the least recently used one makes room for a new query. A size of 0
+//! turns the cache off.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CAPACITY: usize = 64;
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+struct Entry {
+    embedding: Vec<f32>,
+    stored_at: Instant,
+    used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    /// Keyed by model and query: each model embeds into its own space.
+    entries: HashMap<(String, String), Entry>,
+    clock: u64,
+}
+
+pub(crate) struct QueryCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<State>,
+}
+
+impl QueryCache {
+    pub(crate) fn from_env() -> Self {
+        let capacity = std::env::var("MINNA_QUERY_CACHE_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        let ttl = std::env::var("MINNA_QUERY_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TTL);
+        Self::new(capacity, ttl)
+    }
+
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(crate) fn get(&self, model: &str, query: &str) -> Option<Vec<f32>> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut state = self.state.lock().ok()?;
+        state.clock += 1;
+        let clock = state.clock;
+        let key = (model.to_string(), query.to_string());
+        let entry = state.entries.get_mut(&key)?;
+        if entry.stored_at.elapsed() > self.ttl {
+            state.entries.remove(&key);
+            return None;
+        }
+        entry.used = clock;
+        Some(entry.embedding.clone())
+    }
+
+    pub(crate) fn insert(&self, model: &str, query: &str, embedding: &[f32]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.clock += 1;
+        let clock = state.clock;
+        let ttl = self.ttl;
+        state
+            .entries
+            .retain(|_, entry| entry.stored_at.elapsed() <= ttl);
+        let key = (model.to_string(), query.to_string());
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(
+            key,
+            Entry {
+                embedding: embedding.to_vec(),
+                stored_at: Instant::now(),
+                used: clock,
+            },
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Embeddings from an OpenAI-compatible `/embeddings` endpoint, for users who
+//! prefer a hosted model (`MINNA_EMBED_BACKEND=remote`).
+//!
+//! `MINNA_EMBED_REMOTE_URL` is the API base (OpenAI's by default),
+//! `MINNA_EMBED_MODEL` the model and `MINNA_EMBED_DIMENSIONS` an optional
+//! output size for models that support shortening. The daemon reads the key
+//! from the keychain; `MINNA_EMBED_API_KEY` is used otherwise.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::Embedder;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "text-embedding-3-small";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether `MINNA_EMBED_BACKEND=remote`.
+pub fn remote_backend() -> bool {
+    std::env::var("MINNA_EMBED_BACKEND")
+        .map(|backend| backend.eq_ignore_ascii_case("remote"))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteEmbedder {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    dimensions: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+impl RemoteEmbedder {
+    /// Configure from the environment. `api_key` takes precedence over
+    /// `MINNA_EMBED_API_KEY`; local servers may need neither.
+    pub fn from_env(api_key: Option<String>) -> Result<Self> {
+        let base_url = std::env::var("MINNA_EMBED_REMOTE_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let model = std::env::var("MINNA_EMBED_MODEL")
+            .ok()
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let dimensions = match std::env::var("MINNA_EMBED_DIMENSIONS") {
+            Ok(value) => Some(
+                value
+                    .parse()
+                    .map_err(|_| anyhow!("invalid MINNA_EMBED_DIMENSIONS {:?}", value))?,
+            ),
+            Err(_) => None,
+        };
+        let api_key = api_key.or_else(|| {
+            std::env::var("MINNA_EMBED_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty())
+        });
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+            dimensions,
+        })
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl Embedder for RemoteEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut body = json!({ "model": self.model, "input": text });
+        if let Some(dimensions) = self.dimensions {
+            body["dimensions"] = json!(dimensions);
+        }
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("embedding API failed ({}): {}", status, body));
+        }
+        let parsed: EmbeddingResponse = response.json().await?;
+        parsed
+            .data
+            .into_iter()
+            .next()
+            .map(|data| data.embedding)
+            .filter(|embedding| !embedding.is_empty())
+            .ok_or_else(|| anyhow!("embedding API returned no embedding"))
+    }
+
+    fn model_name(&self) -> String {
+        self.model.clone()
+    }
+}
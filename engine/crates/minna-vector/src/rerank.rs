@@ -0,0 +1,167 @@
+//! Cross-encoder reranking of search candidates (`MINNA_RERANK=1`).
+//!
+//! A cross-encoder reads the query and each candidate together, which is far
+//! more precise than comparing embeddings but too slow for more than a few
+//! dozen documents, so it only reorders the top of the hybrid ranking.
+//! `MINNA_RERANK_MODEL` picks the model (`jina-reranker-v1-turbo-en` by
+//! default); it shares `MINNA_EMBED_CACHE_DIR` with the embedding model.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+use tokio::task;
+use tracing::{info, warn};
+
+const DEFAULT_MODEL: &str = "jina-reranker-v1-turbo-en";
+
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Relevance of each document to `query`, in the order given. Higher is
+    /// more relevant; scores are only comparable within one call.
+    async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<f32>>;
+}
+
+pub struct FastReranker {
+    model: Arc<Mutex<TextRerank>>,
+}
+
+impl FastReranker {
+    pub fn new(model: RerankerModel, cache_dir: Option<PathBuf>) -> Result<Self> {
+        let mut options = RerankInitOptions::new(model);
+        if let Some(dir) = cache_dir {
+            options = options.with_cache_dir(dir);
+        }
+        let model = TextRerank::try_new(options)?;
+        Ok(Self {
+            model: Arc::new(Mutex::new(model)),
+        })
+    }
+}
+
+#[async_trait]
+impl Reranker for FastReranker {
+    async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = query.to_string();
+        let documents = documents.to_vec();
+        let model = self.model.clone();
+        task::spawn_blocking(move || {
+            let mut guard = model
+                .lock()
+                .map_err(|_| anyhow!("reranker model lock poisoned"))?;
+            let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+            let results = guard.rerank(query.as_str(), documents.as_slice(), false, None)?;
+            // Results come back sorted by score; put them back in input order
+            let mut scores = vec![f32::MIN; documents.len()];
+            for result in results {
+                if let Some(score) = scores.get_mut(result.index) {
+                    *score = result.score;
+                }
+            }
+            Ok(scores)
+        })
+        .await?
+    }
+}
+
+/// A reranker whose model loads on a background thread, so enabling it never
+/// delays startup. Calls fail until the model is ready; callers keep their
+/// own order in the meantime.
+pub struct BackgroundReranker {
+    loaded: RwLock<Option<Arc<dyn Reranker>>>,
+}
+
+#[async_trait]
+impl Reranker for BackgroundReranker {
+    async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<f32>> {
+        let loaded = self
+            .loaded
+            .read()
+            .map_err(|_| anyhow!("reranker lock poisoned"))?
+            .clone();
+        match loaded {
+            Some(reranker) => reranker.rerank(query, documents).await,
+            None => Err(anyhow!("reranker model still loading")),
+        }
+    }
+}
+
+fn reranker_model(name: &str) -> Result<RerankerModel> {
+    match name.to_ascii_lowercase().as_str() {
+        "jina-reranker-v1-turbo-en" => Ok(RerankerModel::JINARerankerV1TurboEn),
+        "bge-reranker-base" => Ok(RerankerModel::BGERerankerBase),
+        "bge-reranker-v2-m3" => Ok(RerankerModel::BGERerankerV2M3),
+        _ => Err(anyhow!("unknown reranker model {:?}", name)),
+    }
+}
+
+/// Start loading the reranker if `MINNA_RERANK` enables it.
+pub fn reranker_from_env() -> Result<Option<Arc<BackgroundReranker>>> {
+    let enabled = std::env::var("MINNA_RERANK")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+    let name = std::env::var("MINNA_RERANK_MODEL")
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let model = reranker_model(&name)?;
+    let cache_dir = std::env::var("MINNA_EMBED_CACHE_DIR")
+        .ok()
+        .map(PathBuf::from);
+
+    let reranker = Arc::new(BackgroundReranker {
+        loaded: RwLock::new(None),
+    });
+    let slot = reranker.clone();
+    std::thread::spawn(move || match FastReranker::new(model, cache_dir) {
+        Ok(loaded) => {
+            if let Ok(mut guard) = slot.loaded.write() {
+                *guard = Some(Arc::new(loaded));
+            }
+            info!("[RERANK] {} ready", name);
+        }
+        Err(err) => warn!("[RERANK] Failed to load {}: {}", name, err),
+    });
+    Ok(Some(reranker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LengthReranker;
+
+    #[async_trait]
+    impl Reranker for LengthReranker {
+        async fn rerank(&self, _query: &str, documents: &[String]) -> Result<Vec<f32>> {
+            Ok(documents.iter().map(|doc| doc.len() as f32).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_background_reranker_waits_for_model() {
+        let reranker = BackgroundReranker {
+            loaded: RwLock::new(None),
+        };
+        let documents = vec!["short".to_string(), "a little longer".to_string()];
+        assert!(reranker.rerank("query", &documents).await.is_err());
+
+        *reranker.loaded.write().unwrap() = Some(Arc::new(LengthReranker));
+        assert_eq!(reranker.rerank("query", &documents).await.unwrap(), vec![5.0, 15.0]);
+    }
+
+    #[test]
+    fn test_reranker_model_names() {
+        assert!(matches!(reranker_model("BGE-Reranker-Base"), Ok(RerankerModel::BGERerankerBase)));
+        assert!(matches!(reranker_model(DEFAULT_MODEL), Ok(RerankerModel::JINARerankerV1TurboEn)));
+        assert!(reranker_model("ms-marco").is_err());
+    }
+}